@@ -0,0 +1,73 @@
+//! GitHub GraphQL API client for the creation dates of user accounts
+//!
+//! Looks up to [`MAX_LOGINS_PER_QUERY`] accounts per query, each under its own
+//! alias. Accounts deleted since, or organizations, are left out of the answer.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::GitHubClient;
+use crate::graphql::{parse_partial_graphql_response, post_graphql, ParseGraphQLResponseError, PostGraphQLError};
+
+/// Accounts per query, a page of stargazers at most.
+pub const MAX_LOGINS_PER_QUERY: usize = 100;
+
+#[derive(Debug, Error)]
+pub enum FetchAccountCreationDatesError {
+    #[error("PostGraphQL: {source}")]
+    PostGraphQL {
+        source: PostGraphQLError,
+    },
+
+    #[error("ParseGraphQLResponse: {source}")]
+    ParseGraphQLResponse {
+        source: ParseGraphQLResponseError,
+    },
+}
+
+/// When each account of `logins` was created, by login as GitHub spells it.
+pub async fn fetch_account_creation_dates(
+    client: &GitHubClient,
+    logins: &[&str],
+) -> Result<HashMap<String, DateTime<Utc>>, FetchAccountCreationDatesError> {
+    let mut created = HashMap::with_capacity(logins.len());
+    for chunk in logins.chunks(MAX_LOGINS_PER_QUERY) {
+        let payload = account_creation_dates_payload(chunk);
+        let result = post_graphql(client, &payload)
+            .await
+            .map_err(|source| FetchAccountCreationDatesError::PostGraphQL { source })?;
+        let accounts: HashMap<String, Option<UserAccount>> = parse_partial_graphql_response(&result)
+            .map_err(|source| FetchAccountCreationDatesError::ParseGraphQLResponse { source })?;
+
+        created.extend(accounts.into_values().flatten().map(|account| (account.login, account.created_at)));
+    }
+    Ok(created)
+}
+
+/// Query looking up `logins[n]` as `an`, its login passed as `$ln`.
+fn account_creation_dates_payload(logins: &[&str]) -> serde_json::Value {
+    let declarations: Vec<String> = (0..logins.len()).map(|n| format!("$l{n}: String!")).collect();
+    let lookups: String = (0..logins.len())
+        .map(|n| format!("a{n}: user(login: $l{n}) {{ login createdAt }}\n"))
+        .collect();
+    let variables: serde_json::Map<String, serde_json::Value> = logins
+        .iter()
+        .enumerate()
+        .map(|(n, login)| (format!("l{n}"), (*login).into()))
+        .collect();
+
+    serde_json::json!({
+        "query": format!("query getAccountCreationDates({}) {{\n{lookups}}}", declarations.join(", ")),
+        "variables": variables,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct UserAccount {
+    login: String,
+    #[serde(rename = "createdAt")]
+    created_at: DateTime<Utc>,
+}
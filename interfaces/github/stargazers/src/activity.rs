@@ -0,0 +1,176 @@
+//! GitHub GraphQL API client for the issues and pull requests of a repository
+//!
+//! Lists them most recently updated first, in batches of 100, down to those
+//! updated before a given time: the ones already known and unchanged since.
+//! At most [`MAX_ACTIVITY_PAGES`] are fetched per call, so a busy repository
+//! synced for the first time doesn't spend the whole rate limit on it.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::GitHubClient;
+use crate::graphql::{parse_graphql_response, post_graphql, ParseGraphQLResponseError, PostGraphQLError};
+use crate::index::PageInfo;
+
+/// Pages of 100 issues, or pull requests, fetched at most per call.
+pub const MAX_ACTIVITY_PAGES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivityKind {
+    Issues,
+    PullRequests,
+}
+
+impl ActivityKind {
+    pub const ALL: [ActivityKind; 2] = [ActivityKind::Issues, ActivityKind::PullRequests];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::Issues => "issues",
+            ActivityKind::PullRequests => "prs",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "issues" => Some(ActivityKind::Issues),
+            "prs" => Some(ActivityKind::PullRequests),
+            _ => None,
+        }
+    }
+
+    /// Connection of the repository listing them.
+    fn connection(&self) -> &'static str {
+        match self {
+            ActivityKind::Issues => "issues",
+            ActivityKind::PullRequests => "pullRequests",
+        }
+    }
+}
+
+impl std::fmt::Display for ActivityKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Issue or pull request, merged ones being closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityItem {
+    pub number: u32,
+    pub created_at: DateTime<Utc>,
+    /// Unset while open.
+    pub closed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Issues, or pull requests, updated since the time asked for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositoryActivity {
+    /// Most recently updated first.
+    pub items: Vec<ActivityItem>,
+    /// Whether [`MAX_ACTIVITY_PAGES`] were fetched before reaching the time asked for, the older
+    /// updates left out.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum FetchRepositoryActivityError {
+    #[error("PostGraphQL: {source}")]
+    PostGraphQL {
+        source: PostGraphQLError,
+    },
+
+    #[error("ParseGraphQLResponse: {source}")]
+    ParseGraphQLResponse {
+        source: ParseGraphQLResponseError,
+    },
+}
+
+/// Issues, or pull requests, of `owner/name` updated at or after `since`. `None` when GitHub
+/// doesn't know the repository.
+pub async fn fetch_repository_activity(
+    client: &GitHubClient,
+    owner: &str,
+    name: &str,
+    kind: ActivityKind,
+    since: DateTime<Utc>,
+) -> Result<Option<RepositoryActivity>, FetchRepositoryActivityError> {
+    // Issues and pull requests share their fields and ordering, only the connection differs.
+    let graphql_query = format!(r#"
+        query getRepositoryActivity($owner: String!, $name: String!, $cursor: String) {{
+            repository(owner: $owner, name: $name) {{
+                {connection}(first: 100, after: $cursor, orderBy: {{field: UPDATED_AT, direction: DESC}}) {{
+                    nodes {{ number createdAt closedAt updatedAt }}
+                    pageInfo {{ hasNextPage endCursor }}
+                }}
+            }}
+        }}
+    "#, connection = kind.connection());
+
+    let mut activity = RepositoryActivity::default();
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_ACTIVITY_PAGES {
+        let payload = serde_json::json!({
+            "query": graphql_query,
+            "variables": {
+                "owner": owner,
+                "name": name,
+                "cursor": cursor,
+            }
+        });
+
+        let result = post_graphql(client, &payload)
+            .await
+            .map_err(|source| FetchRepositoryActivityError::PostGraphQL { source })?;
+        let data: RepositoryActivityData = parse_graphql_response(&result)
+            .map_err(|source| FetchRepositoryActivityError::ParseGraphQLResponse { source })?;
+        let Some(repository) = data.repository else {
+            return Ok(None);
+        };
+
+        let page = repository.issues.or(repository.pull_requests).unwrap_or_default();
+        let reached_since = page.nodes.iter().any(|item| item.updated_at < since);
+        activity.items.extend(
+            page.nodes
+                .into_iter()
+                .take_while(|item| item.updated_at >= since)
+                .map(|item| ActivityItem { number: item.number, created_at: item.created_at, closed_at: item.closed_at, updated_at: item.updated_at }),
+        );
+        match page.page_info.and_then(|info| info.end_cursor.filter(|_| info.has_next_page)) {
+            Some(next) if !reached_since => cursor = Some(next),
+            _ => return Ok(Some(activity)),
+        }
+    }
+    activity.truncated = true;
+    Ok(Some(activity))
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryActivityData {
+    repository: Option<ActivityRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityRepository {
+    issues: Option<ActivityConnection>,
+    pull_requests: Option<ActivityConnection>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityConnection {
+    nodes: Vec<ActivityNode>,
+    page_info: Option<PageInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityNode {
+    number: u32,
+    created_at: DateTime<Utc>,
+    closed_at: Option<DateTime<Utc>>,
+    updated_at: DateTime<Utc>,
+}
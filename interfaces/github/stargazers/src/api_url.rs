@@ -0,0 +1,88 @@
+//! Location of the GitHub API
+//!
+//! `https://api.github.com` for github.com, `https://HOST/api/v3` for a GitHub
+//! Enterprise Server. REST paths are appended to it, while the GraphQL
+//! endpoint is derived from it since Enterprise serves it at `/api/graphql`.
+
+use std::str::FromStr;
+
+use thiserror::Error;
+use url::Url;
+
+pub const GITHUB_COM_API_URL: &str = "https://api.github.com";
+
+/// Path of Enterprise REST base URLs, the GraphQL endpoint replaces its `/v3`.
+const ENTERPRISE_REST_SUFFIX: &str = "/api/v3";
+
+#[derive(Debug, Error)]
+pub enum ParseGitHubApiUrlError {
+    #[error("InvalidUrl: {source}")]
+    InvalidUrl {
+        source: url::ParseError,
+    },
+
+    #[error("UnsupportedScheme: {value}")]
+    UnsupportedScheme {
+        value: String,
+    },
+
+    #[error("UnexpectedQueryOrFragment: {value}")]
+    UnexpectedQueryOrFragment {
+        value: String,
+    },
+}
+
+/// Base URL of the REST API, an `http(s)` URL without query, fragment or trailing slash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitHubApiUrl(String);
+
+impl Default for GitHubApiUrl {
+    fn default() -> Self {
+        Self(GITHUB_COM_API_URL.into())
+    }
+}
+
+impl GitHubApiUrl {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// REST endpoint at `path`, e.g. `app/installations/1/access_tokens`.
+    pub fn rest(&self, path: &str) -> String {
+        format!("{}/{}", self.0, path.trim_start_matches('/'))
+    }
+
+    /// GraphQL endpoint, `/api/graphql` on Enterprise and `/graphql` below the base URL otherwise.
+    pub fn graphql(&self) -> String {
+        match self.0.strip_suffix(ENTERPRISE_REST_SUFFIX) {
+            Some(host) => format!("{host}/api/graphql"),
+            None => format!("{}/graphql", self.0),
+        }
+    }
+}
+
+impl FromStr for GitHubApiUrl {
+    type Err = ParseGitHubApiUrlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let url = Url::parse(value)
+            .map_err(|source| ParseGitHubApiUrlError::InvalidUrl { source })?;
+
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ParseGitHubApiUrlError::UnsupportedScheme { value: value.into() });
+        }
+        if url.query().is_some() || url.fragment().is_some() {
+            return Err(ParseGitHubApiUrlError::UnexpectedQueryOrFragment { value: value.into() });
+        }
+
+        // Normalized by the parser, e.g. the host is lowercased.
+        Ok(Self(url.as_str().trim_end_matches('/').to_string()))
+    }
+}
+
+impl std::fmt::Display for GitHubApiUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
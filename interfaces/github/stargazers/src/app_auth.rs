@@ -0,0 +1,159 @@
+//! GitHub App authentication
+//!
+//! Signs a short-lived JWT with the app's private key and exchanges it for an
+//! installation token. The token is cached and renewed shortly before GitHub
+//! expires it, so callers always get a usable one.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+use crate::api_url::GitHubApiUrl;
+
+/// Installation tokens are renewed when they have less than this left.
+const RENEW_BEFORE_EXPIRY: Duration = Duration::minutes(5);
+
+#[derive(Debug, Clone)]
+pub struct GitHubAppAuth {
+    inner: Arc<GitHubAppAuthInner>,
+}
+
+struct GitHubAppAuthInner {
+    app_id: String,
+    installation_id: u64,
+    key: EncodingKey,
+    cached: Mutex<Option<InstallationToken>>,
+}
+
+// Keeps the private key and installation token out of logs.
+impl std::fmt::Debug for GitHubAppAuthInner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubAppAuthInner")
+            .field("app_id", &self.app_id)
+            .field("installation_id", &self.installation_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, Deserialize)]
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct AppClaims<'a> {
+    iat: i64,
+    exp: i64,
+    iss: &'a str,
+}
+
+impl GitHubAppAuth {
+    pub fn new(app_id: String, installation_id: u64, private_key_pem: &[u8]) -> Result<Self, NewGitHubAppAuthError> {
+        let key = EncodingKey::from_rsa_pem(private_key_pem)
+            .map_err(|source| NewGitHubAppAuthError::PrivateKeyParse { source })?;
+
+        Ok(Self {
+            inner: Arc::new(GitHubAppAuthInner {
+                app_id,
+                installation_id,
+                key,
+                cached: Mutex::new(None),
+            }),
+        })
+    }
+
+    /// Current installation token, requesting a new one when missing or about to expire.
+    pub async fn installation_token(&self, http: &Client, api_url: &GitHubApiUrl) -> Result<String, InstallationTokenError> {
+        let mut cached = self.inner.cached.lock().await;
+
+        if let Some(token) = cached.as_ref() {
+            if token.expires_at - RENEW_BEFORE_EXPIRY > Utc::now() {
+                return Ok(token.token.clone());
+            }
+        }
+
+        let token = self.request_installation_token(http, api_url).await?;
+        let value = token.token.clone();
+        *cached = Some(token);
+
+        Ok(value)
+    }
+
+    async fn request_installation_token(&self, http: &Client, api_url: &GitHubApiUrl) -> Result<InstallationToken, InstallationTokenError> {
+        // Backdated to tolerate clock drift, GitHub caps the lifetime at 10 minutes.
+        let now = Utc::now();
+        let claims = AppClaims {
+            iat: (now - Duration::seconds(60)).timestamp(),
+            exp: (now + Duration::minutes(9)).timestamp(),
+            iss: &self.inner.app_id,
+        };
+
+        let jwt = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.inner.key)
+            .map_err(|source| InstallationTokenError::JwtEncode { source })?;
+
+        let response = http
+            .post(api_url.rest(&format!("app/installations/{}/access_tokens", self.inner.installation_id)))
+            .header("Authorization", format!("Bearer {jwt}"))
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .map_err(|source| InstallationTokenError::RequestSend { source })?;
+
+        let status = response.status();
+
+        let body = response
+            .text()
+            .await
+            .map_err(|source| InstallationTokenError::ResponseRead { source })?;
+
+        if status != StatusCode::CREATED {
+            return Err(InstallationTokenError::UnexpectedStatus { status, body });
+        }
+
+        serde_json::from_str(&body)
+            .map_err(|source| InstallationTokenError::ResponseBodyDeserialization { source })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum NewGitHubAppAuthError {
+    #[error("PrivateKeyParse: {source}")]
+    PrivateKeyParse {
+        source: jsonwebtoken::errors::Error,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum InstallationTokenError {
+    #[error("JwtEncode: {source}")]
+    JwtEncode {
+        source: jsonwebtoken::errors::Error,
+    },
+
+    #[error("RequestSend: {source}")]
+    RequestSend {
+        source: reqwest::Error,
+    },
+
+    #[error("ResponseRead: {source}")]
+    ResponseRead {
+        source: reqwest::Error,
+    },
+
+    #[error("UnexpectedStatus: {status}: {body}")]
+    UnexpectedStatus {
+        status: StatusCode,
+        body: String,
+    },
+
+    #[error("ResponseBodyDeserialization: {source}")]
+    ResponseBodyDeserialization {
+        source: serde_json::Error,
+    },
+}
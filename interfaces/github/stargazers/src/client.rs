@@ -0,0 +1,183 @@
+//! Shared HTTP client for every GitHub call
+//!
+//! Wraps a single `reqwest::Client` so connections are pooled across pages
+//! and handlers. Cloning is cheap, clones share the same pool.
+
+use std::time::Duration;
+
+use reqwest::{header::HeaderMap, Client, Proxy};
+use thiserror::Error;
+
+use crate::api_url::GitHubApiUrl;
+use crate::app_auth::{GitHubAppAuth, InstallationTokenError};
+use crate::page_size::PageSizeConfig;
+use crate::retry::RetryConfig;
+use crate::token_pool::{PickedToken, TokenPool};
+
+#[derive(Debug, Clone)]
+pub struct GitHubClientConfig {
+    /// github.com unless set, see [`GitHubApiUrl`] for Enterprise Server.
+    pub api_url: GitHubApiUrl,
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub pool_idle_timeout: Duration,
+    pub pool_max_idle_per_host: usize,
+    /// Explicit proxy URL, `HTTPS_PROXY`/`HTTP_PROXY` are honoured when unset.
+    pub proxy: Option<String>,
+    pub retry: RetryConfig,
+    /// Stargazers per page of a sync, see [`crate::page_size::AdaptivePageSize`].
+    pub page_size: PageSizeConfig,
+}
+
+impl Default for GitHubClientConfig {
+    fn default() -> Self {
+        Self {
+            api_url: GitHubApiUrl::default(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            pool_idle_timeout: Duration::from_secs(90),
+            pool_max_idle_per_host: 8,
+            proxy: None,
+            retry: RetryConfig::default(),
+            page_size: PageSizeConfig::default(),
+        }
+    }
+}
+
+/// How requests authenticate against GitHub.
+#[derive(Debug, Clone)]
+pub enum GitHubAuth {
+    /// Personal access tokens rotated by remaining quota.
+    Tokens(TokenPool),
+    /// Installation tokens of a GitHub App, renewed before expiry.
+    App(GitHubAppAuth),
+}
+
+/// Credential used for a single request.
+pub(crate) enum Credential {
+    Pooled(PickedToken),
+    Installation(String),
+}
+
+impl Credential {
+    pub(crate) fn token(&self) -> &str {
+        match self {
+            Credential::Pooled(picked) => &picked.token,
+            Credential::Installation(token) => token,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GitHubClient {
+    http: Client,
+    api_url: GitHubApiUrl,
+    retry: RetryConfig,
+    page_size: PageSizeConfig,
+    auth: GitHubAuth,
+}
+
+impl GitHubClient {
+    pub fn new(config: &GitHubClientConfig, auth: GitHubAuth) -> Result<Self, NewGitHubClientError> {
+        let mut builder = Client::builder()
+            .user_agent("rust-client")
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host);
+
+        if let Some(proxy) = &config.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|source| NewGitHubClientError::ProxyParse { source })?;
+            builder = builder.proxy(proxy);
+        }
+
+        let http = builder
+            .build()
+            .map_err(|source| NewGitHubClientError::ClientBuild { source })?;
+
+        Ok(Self { http, api_url: config.api_url.clone(), retry: config.retry.clone(), page_size: config.page_size, auth })
+    }
+
+    pub fn http(&self) -> &Client {
+        &self.http
+    }
+
+    pub fn api_url(&self) -> &GitHubApiUrl {
+        &self.api_url
+    }
+
+    pub fn retry(&self) -> &RetryConfig {
+        &self.retry
+    }
+
+    pub fn page_size(&self) -> &PageSizeConfig {
+        &self.page_size
+    }
+
+    pub fn auth(&self) -> &GitHubAuth {
+        &self.auth
+    }
+
+    /// Whether requests can be authenticated at all.
+    pub fn has_credentials(&self) -> bool {
+        match &self.auth {
+            GitHubAuth::Tokens(pool) => !pool.is_empty(),
+            GitHubAuth::App(_) => true,
+        }
+    }
+
+    pub(crate) async fn credential(&self) -> Result<Credential, CredentialError> {
+        match &self.auth {
+            GitHubAuth::Tokens(pool) => pool
+                .pick()
+                .map(Credential::Pooled)
+                .ok_or(CredentialError::MissingToken),
+            GitHubAuth::App(app) => app
+                .installation_token(&self.http, &self.api_url)
+                .await
+                .map(Credential::Installation)
+                .map_err(|source| CredentialError::InstallationToken { source }),
+        }
+    }
+
+    pub(crate) fn record_rate_limit(&self, credential: &Credential, headers: &HeaderMap) {
+        if let (GitHubAuth::Tokens(pool), Credential::Pooled(picked)) = (&self.auth, credential) {
+            pool.record(picked, headers);
+        }
+    }
+
+    /// Whether `credential` ran out of quota while another pooled token still has some.
+    pub(crate) fn can_switch_token(&self, credential: &Credential) -> bool {
+        match (&self.auth, credential) {
+            (GitHubAuth::Tokens(pool), Credential::Pooled(picked)) => {
+                pool.remaining(picked) == 0 && pool.has_other_available(picked)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CredentialError {
+    #[error("MissingToken")]
+    MissingToken,
+
+    #[error("InstallationToken: {source}")]
+    InstallationToken {
+        source: InstallationTokenError,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum NewGitHubClientError {
+    #[error("ProxyParse: {source}")]
+    ProxyParse {
+        source: reqwest::Error,
+    },
+
+    #[error("ClientBuild: {source}")]
+    ClientBuild {
+        source: reqwest::Error,
+    },
+}
@@ -0,0 +1,168 @@
+//! GitHub GraphQL API client for the commit authors of a repository
+//!
+//! Walks the history of the default branch newest commit first, in batches of
+//! 100, between optional bounds on the commit dates: callers backfill the
+//! history a batch of [`MAX_COMMIT_PAGES`] at a time with `until`, and pick up
+//! the commits pushed since with `since`. Authors are GitHub users, commits of
+//! emails no account claims have none.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::GitHubClient;
+use crate::graphql::{parse_graphql_response, post_graphql, ParseGraphQLResponseError, PostGraphQLError};
+use crate::index::PageInfo;
+
+/// Pages of 100 commits fetched at most per call.
+pub const MAX_COMMIT_PAGES: usize = 20;
+
+/// Commit of the default branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitAuthor {
+    /// Unset when no GitHub account claims the author's email.
+    pub login: Option<String>,
+    pub authored_at: DateTime<Utc>,
+    /// What `since` and `until` bound, later than `authored_at` for rebased commits.
+    pub committed_at: DateTime<Utc>,
+}
+
+/// Commits between the bounds asked for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitHistory {
+    /// Newest first.
+    pub commits: Vec<CommitAuthor>,
+    /// Whether every commit between the bounds was fetched, rather than the newest
+    /// [`MAX_COMMIT_PAGES`].
+    pub complete: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum FetchCommitAuthorsError {
+    #[error("PostGraphQL: {source}")]
+    PostGraphQL {
+        source: PostGraphQLError,
+    },
+
+    #[error("ParseGraphQLResponse: {source}")]
+    ParseGraphQLResponse {
+        source: ParseGraphQLResponseError,
+    },
+}
+
+/// Commits of the default branch of `owner/name` committed from `since` to `until`, both included
+/// and open when unset. `None` when GitHub doesn't know the repository, empty without any commit.
+pub async fn fetch_commit_authors(
+    client: &GitHubClient,
+    owner: &str,
+    name: &str,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Option<CommitHistory>, FetchCommitAuthorsError> {
+    let graphql_query = r#"
+        query getCommitAuthors($owner: String!, $name: String!, $cursor: String, $since: GitTimestamp, $until: GitTimestamp) {
+            repository(owner: $owner, name: $name) {
+                defaultBranchRef {
+                    target {
+                        ... on Commit {
+                            history(first: 100, after: $cursor, since: $since, until: $until) {
+                                nodes { authoredDate committedDate author { user { login } } }
+                                pageInfo { hasNextPage endCursor }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    "#;
+
+    let mut history = CommitHistory::default();
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_COMMIT_PAGES {
+        let payload = serde_json::json!({
+            "query": graphql_query,
+            "variables": {
+                "owner": owner,
+                "name": name,
+                "cursor": cursor,
+                "since": since.map(|since| since.to_rfc3339()),
+                "until": until.map(|until| until.to_rfc3339()),
+            }
+        });
+
+        let result = post_graphql(client, &payload)
+            .await
+            .map_err(|source| FetchCommitAuthorsError::PostGraphQL { source })?;
+        let data: CommitAuthorsData = parse_graphql_response(&result)
+            .map_err(|source| FetchCommitAuthorsError::ParseGraphQLResponse { source })?;
+        let Some(repository) = data.repository else {
+            return Ok(None);
+        };
+        // An empty repository has no default branch.
+        let Some(page) = repository.default_branch_ref.and_then(|branch| branch.target.history) else {
+            history.complete = true;
+            return Ok(Some(history));
+        };
+
+        history.commits.extend(page.nodes.into_iter().map(|commit| CommitAuthor {
+            login: commit.author.and_then(|author| author.user).map(|user| user.login),
+            authored_at: commit.authored_date,
+            committed_at: commit.committed_date,
+        }));
+        match page.page_info.end_cursor.filter(|_| page.page_info.has_next_page) {
+            Some(next) => cursor = Some(next),
+            None => {
+                history.complete = true;
+                return Ok(Some(history));
+            }
+        }
+    }
+    Ok(Some(history))
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitAuthorsData {
+    repository: Option<CommitRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitRepository {
+    default_branch_ref: Option<BranchRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchRef {
+    target: BranchTarget,
+}
+
+/// A commit, the only target of a branch, its fields absent otherwise.
+#[derive(Debug, Deserialize)]
+struct BranchTarget {
+    history: Option<HistoryConnection>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryConnection {
+    nodes: Vec<CommitNode>,
+    page_info: PageInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitNode {
+    authored_date: DateTime<Utc>,
+    committed_date: DateTime<Utc>,
+    author: Option<GitActor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitActor {
+    user: Option<GitHubUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    login: String,
+}
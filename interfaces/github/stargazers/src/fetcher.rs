@@ -0,0 +1,111 @@
+//! Forge clients as trait objects
+//!
+//! [`ForgeClient`] returns `impl Future`, which keeps every client's own error
+//! type but rules out `dyn ForgeClient`. [`StargazerFetcher`] is the same two
+//! calls boxed, implemented for every client, so callers can hold any of them
+//! behind an `Arc<dyn StargazerFetcher>` and tests can hand in a fake one that
+//! answers pages and failures of their choosing, without a network.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::forge::{Forge, ForgeApiError, ForgeClient, ForgeErrorKind, StargazerPage};
+use crate::retry::RetryConfig;
+
+/// Answer of a [`StargazerFetcher`] call.
+pub type FetchFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, StargazerFetchError>> + Send + 'a>>;
+
+/// Failure of a [`StargazerFetcher`]: the client's own error, with its kind.
+#[derive(Debug)]
+pub struct StargazerFetchError {
+    kind: ForgeErrorKind,
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl StargazerFetchError {
+    pub fn new<E: ForgeApiError>(error: E) -> Self {
+        Self { kind: error.kind(), source: Box::new(error) }
+    }
+
+    /// Error of `kind` described by `message`, as fakes answer.
+    pub fn with_kind(kind: ForgeErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, source: message.into().into() }
+    }
+
+    /// The client's error, when it is an `E`.
+    pub fn downcast_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        self.source.downcast_ref()
+    }
+}
+
+impl std::fmt::Display for StargazerFetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.source, f)
+    }
+}
+
+impl std::error::Error for StargazerFetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.source()
+    }
+}
+
+impl ForgeApiError for StargazerFetchError {
+    fn kind(&self) -> ForgeErrorKind {
+        self.kind
+    }
+}
+
+/// Object-safe [`ForgeClient`], see the module documentation.
+pub trait StargazerFetcher: Send + Sync {
+    fn forge(&self) -> Forge;
+
+    /// Retries and backoff of the client's requests.
+    fn retry(&self) -> &RetryConfig;
+
+    /// See [`ForgeClient::fetch_stargazers`].
+    fn fetch_stargazers<'a>(
+        &'a self,
+        owner: &'a str,
+        name: &'a str,
+        cursor: Option<&'a str>,
+        page_size: u32,
+        with_profiles: bool,
+    ) -> FetchFuture<'a, Option<StargazerPage>>;
+
+    /// See [`ForgeClient::stargazer_count`].
+    fn stargazer_count<'a>(&'a self, owner: &'a str, name: &'a str) -> FetchFuture<'a, Option<u32>>;
+}
+
+impl<C: ForgeClient + Send + Sync> StargazerFetcher for C {
+    fn forge(&self) -> Forge {
+        ForgeClient::forge(self)
+    }
+
+    fn retry(&self) -> &RetryConfig {
+        ForgeClient::retry(self)
+    }
+
+    fn fetch_stargazers<'a>(
+        &'a self,
+        owner: &'a str,
+        name: &'a str,
+        cursor: Option<&'a str>,
+        page_size: u32,
+        with_profiles: bool,
+    ) -> FetchFuture<'a, Option<StargazerPage>> {
+        Box::pin(async move {
+            ForgeClient::fetch_stargazers(self, owner, name, cursor, page_size, with_profiles)
+                .await
+                .map_err(StargazerFetchError::new)
+        })
+    }
+
+    fn stargazer_count<'a>(&'a self, owner: &'a str, name: &'a str) -> FetchFuture<'a, Option<u32>> {
+        Box::pin(async move {
+            ForgeClient::stargazer_count(self, owner, name)
+                .await
+                .map_err(StargazerFetchError::new)
+        })
+    }
+}
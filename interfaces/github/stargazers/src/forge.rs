@@ -0,0 +1,311 @@
+//! Forges stars are synced from
+//!
+//! GitHub, GitLab and Gitea (Codeberg, Forgejo) all list who starred a
+//! project, page by page. [`ForgeClient`] hides their APIs behind the same
+//! two calls, answering with forge-neutral pages. GitHub's client implements
+//! it here, see [`crate::gitlab`] and [`crate::gitea`] for the others, and
+//! [`crate::fetcher`] to hold any of them as a trait object.
+
+use std::future::Future;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::client::GitHubClient;
+use crate::graphql::ParseGraphQLResponseError;
+use crate::index::{fetch_repo_stargazers, parse_repo_stargazers_response, FetchRepoStargazersError};
+use crate::repository_location::{fetch_repository_location, FetchRepositoryLocationError, RepositoryLocation};
+use crate::retry::RetryConfig;
+use crate::stargazer_count::{fetch_repo_stargazer_count, parse_repo_stargazer_count_response, FetchRepoStargazerCountError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Forge {
+    #[default]
+    #[serde(rename = "github")]
+    GitHub,
+    #[serde(rename = "gitlab")]
+    GitLab,
+    Gitea,
+}
+
+impl Forge {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Forge::GitHub => "github",
+            Forge::GitLab => "gitlab",
+            Forge::Gitea => "gitea",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "github" => Some(Forge::GitHub),
+            "gitlab" => Some(Forge::GitLab),
+            "gitea" => Some(Forge::Gitea),
+            _ => None,
+        }
+    }
+
+    /// Whether stargazers are listed oldest first, pages then only hold stars given after the previous ones.
+    pub fn lists_oldest_first(&self) -> bool {
+        matches!(self, Forge::GitHub)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("UnknownForge: {value}")]
+pub struct ParseForgeError {
+    pub value: String,
+}
+
+impl FromStr for Forge {
+    type Err = ParseForgeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Forge::parse(&s.trim().to_ascii_lowercase()).ok_or_else(|| ParseForgeError { value: s.into() })
+    }
+}
+
+impl std::fmt::Display for Forge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Descriptive fields of a project, returned alongside every page.
+#[derive(Debug, Clone)]
+pub struct ForgeRepository {
+    /// ID given by the forge, kept across renames and transfers.
+    pub id: String,
+    /// Current owner and name, which differ from the requested ones once renamed or transferred.
+    pub owner: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub primary_language: Option<String>,
+    /// SPDX identifier, e.g. `MIT`.
+    pub license: Option<String>,
+    pub topics: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    /// Stars of the project when the page was served.
+    pub stargazer_count: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForgeStargazer {
+    pub login: String,
+    pub starred_at: DateTime<Utc>,
+    /// Free text from the profile, only on forges exposing it and when asked for.
+    pub company: Option<String>,
+    /// Free text from the profile, only on forges exposing it and when asked for.
+    pub location: Option<String>,
+    /// Public email of the profile, only on GitHub. Blank when the user keeps it private.
+    pub email: Option<String>,
+}
+
+/// Page of stargazers, and where the next one starts.
+#[derive(Debug, Clone)]
+pub struct StargazerPage {
+    pub repository: ForgeRepository,
+    pub stargazers: Vec<ForgeStargazer>,
+    /// Cursor of the page after this one, stored to resume a sync. Set on the last page too
+    /// when the forge gives one.
+    pub end_cursor: Option<String>,
+    pub has_next_page: bool,
+    /// Attempts that failed transiently before this page was obtained.
+    pub retries: u32,
+}
+
+/// What went wrong with a forge answer, as far as callers care.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeErrorKind {
+    NotFound,
+    /// The configured credentials are rejected.
+    Unauthorized,
+    Forbidden,
+    RateLimited,
+    /// Throttled or timed out in a way a smaller page may avoid.
+    Overloaded,
+    Other,
+}
+
+pub trait ForgeApiError: std::error::Error + Send + Sync + 'static {
+    fn kind(&self) -> ForgeErrorKind;
+}
+
+/// Stargazers and star count of a project on one forge.
+pub trait ForgeClient {
+    type Error: ForgeApiError;
+
+    fn forge(&self) -> Forge;
+
+    /// Retries and backoff of the client's requests.
+    fn retry(&self) -> &RetryConfig;
+
+    /// Page of at most `page_size` stargazers after `cursor`, `None` when the project doesn't exist.
+    /// Profile fields are only requested with `with_profiles`.
+    fn fetch_stargazers(
+        &self,
+        owner: &str,
+        name: &str,
+        cursor: Option<&str>,
+        page_size: u32,
+        with_profiles: bool,
+    ) -> impl Future<Output = Result<Option<StargazerPage>, Self::Error>> + Send;
+
+    /// Current star count of the project, `None` when it doesn't exist.
+    fn stargazer_count(&self, owner: &str, name: &str) -> impl Future<Output = Result<Option<u32>, Self::Error>> + Send;
+}
+
+#[derive(Debug, Error)]
+pub enum GitHubForgeError {
+    #[error("FetchRepoStargazers: {source}")]
+    FetchRepoStargazers {
+        source: FetchRepoStargazersError,
+    },
+
+    #[error("FetchRepoStargazerCount: {source}")]
+    FetchRepoStargazerCount {
+        source: FetchRepoStargazerCountError,
+    },
+
+    #[error("ParseResponse: {source}")]
+    ParseResponse {
+        source: ParseGraphQLResponseError,
+    },
+
+    #[error("FetchRepositoryLocation: {source}")]
+    FetchRepositoryLocation {
+        source: FetchRepositoryLocationError,
+    },
+}
+
+impl ForgeApiError for GitHubForgeError {
+    fn kind(&self) -> ForgeErrorKind {
+        let source = match self {
+            GitHubForgeError::ParseResponse { source } => source,
+            GitHubForgeError::FetchRepositoryLocation { source: FetchRepositoryLocationError::GetRepository { source } } => return source.kind(),
+            _ => return ForgeErrorKind::Other,
+        };
+        match source {
+            ParseGraphQLResponseError::NotFound { .. } => ForgeErrorKind::NotFound,
+            ParseGraphQLResponseError::Unauthorized { .. } => ForgeErrorKind::Unauthorized,
+            ParseGraphQLResponseError::Forbidden { .. } => ForgeErrorKind::Forbidden,
+            // Secondary rate limits target expensive queries, GitHub answers 502/504 to the ones taking too long.
+            error if error.is_secondary_rate_limit() => ForgeErrorKind::Overloaded,
+            ParseGraphQLResponseError::RateLimited { .. } => ForgeErrorKind::RateLimited,
+            ParseGraphQLResponseError::UnexpectedStatus { status, .. }
+                if *status == StatusCode::BAD_GATEWAY || *status == StatusCode::GATEWAY_TIMEOUT => ForgeErrorKind::Overloaded,
+            _ => ForgeErrorKind::Other,
+        }
+    }
+}
+
+impl ForgeClient for GitHubClient {
+    type Error = GitHubForgeError;
+
+    fn forge(&self) -> Forge {
+        Forge::GitHub
+    }
+
+    fn retry(&self) -> &RetryConfig {
+        GitHubClient::retry(self)
+    }
+
+    /// Follows a repository GraphQL doesn't resolve to where it moved, see [`crate::repository_location`].
+    async fn fetch_stargazers(
+        &self,
+        owner: &str,
+        name: &str,
+        cursor: Option<&str>,
+        page_size: u32,
+        with_profiles: bool,
+    ) -> Result<Option<StargazerPage>, GitHubForgeError> {
+        if let Some(page) = fetch_github_stargazers(self, owner, name, cursor, page_size, with_profiles).await? {
+            return Ok(Some(page));
+        }
+        match moved_location(self, owner, name).await? {
+            Some(moved) => fetch_github_stargazers(self, &moved.owner, &moved.name, cursor, page_size, with_profiles).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Follows a moved repository like [`ForgeClient::fetch_stargazers`].
+    async fn stargazer_count(&self, owner: &str, name: &str) -> Result<Option<u32>, GitHubForgeError> {
+        if let Some(count) = fetch_github_stargazer_count(self, owner, name).await? {
+            return Ok(Some(count));
+        }
+        match moved_location(self, owner, name).await? {
+            Some(moved) => fetch_github_stargazer_count(self, &moved.owner, &moved.name).await,
+            None => Ok(None),
+        }
+    }
+}
+
+/// Where `owner/name` moved, `None` when GitHub doesn't know it or it lives there still.
+async fn moved_location(client: &GitHubClient, owner: &str, name: &str) -> Result<Option<RepositoryLocation>, GitHubForgeError> {
+    let location = fetch_repository_location(client, owner, name)
+        .await
+        .map_err(|source| GitHubForgeError::FetchRepositoryLocation { source })?;
+    Ok(location.filter(|location| location.differs_from(owner, name)))
+}
+
+async fn fetch_github_stargazers(
+    client: &GitHubClient,
+    owner: &str,
+    name: &str,
+    cursor: Option<&str>,
+    page_size: u32,
+    with_profiles: bool,
+) -> Result<Option<StargazerPage>, GitHubForgeError> {
+    let result = fetch_repo_stargazers(client, owner, name, cursor, page_size, with_profiles)
+        .await
+        .map_err(|source| GitHubForgeError::FetchRepoStargazers { source })?;
+    let data = match parse_repo_stargazers_response(&result) {
+        Ok(data) => data,
+        Err(ParseGraphQLResponseError::NotFound { .. }) => return Ok(None),
+        Err(source) => return Err(GitHubForgeError::ParseResponse { source }),
+    };
+
+    Ok(data.repository.map(|repo| StargazerPage {
+        stargazers: repo.stargazers.edges
+            .into_iter()
+            .map(|edge| ForgeStargazer {
+                login: edge.node.login,
+                starred_at: edge.starred_at,
+                company: edge.node.company,
+                location: edge.node.location,
+                email: edge.node.email,
+            })
+            .collect(),
+        end_cursor: repo.stargazers.page_info.end_cursor,
+        has_next_page: repo.stargazers.page_info.has_next_page,
+        retries: result.retries,
+        repository: ForgeRepository {
+            id: repo.id,
+            owner: repo.owner.login,
+            name: repo.name,
+            description: repo.description,
+            primary_language: repo.primary_language.map(|language| language.name),
+            license: repo.license_info.and_then(|license| license.spdx_id),
+            topics: repo.repository_topics.nodes.into_iter().map(|node| node.topic.name).collect(),
+            created_at: repo.created_at,
+            stargazer_count: repo.stargazer_count,
+        },
+    }))
+}
+
+async fn fetch_github_stargazer_count(client: &GitHubClient, owner: &str, name: &str) -> Result<Option<u32>, GitHubForgeError> {
+    let result = fetch_repo_stargazer_count(client, owner, name)
+        .await
+        .map_err(|source| GitHubForgeError::FetchRepoStargazerCount { source })?;
+
+    match parse_repo_stargazer_count_response(&result) {
+        Ok(data) => Ok(data.repository.map(|repo| repo.stargazer_count)),
+        Err(ParseGraphQLResponseError::NotFound { .. }) => Ok(None),
+        Err(source) => Err(GitHubForgeError::ParseResponse { source }),
+    }
+}
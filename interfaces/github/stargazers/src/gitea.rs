@@ -0,0 +1,165 @@
+//! Gitea REST API client for repository stars
+//!
+//! Also serves Forgejo instances such as Codeberg, which kept the same API.
+//! Stargazers are listed with page-number pagination, but without the date
+//! they starred the repository: each one is dated when the page is fetched,
+//! so stars of a Gitea repository are dated by their first sync.
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::forge::{Forge, ForgeClient, ForgeRepository, ForgeStargazer, StargazerPage};
+use crate::rest::{get_optional, RestApiUrl, RestForgeError};
+use crate::retry::RetryConfig;
+
+/// Default `MAX_RESPONSE_ITEMS` of Gitea, larger pages being cut to it.
+pub const GITEA_MAX_PAGE_SIZE: u32 = 50;
+
+#[derive(Clone)]
+pub struct GiteaClient {
+    http: Client,
+    api_url: RestApiUrl,
+    token: Option<String>,
+    retry: RetryConfig,
+}
+
+impl std::fmt::Debug for GiteaClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GiteaClient")
+            .field("api_url", &self.api_url)
+            .field("token", &self.token.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl GiteaClient {
+    /// Client of the instance at `api_url`, e.g. `https://codeberg.org/api/v1`, sharing the connection pool
+    /// of `http`. Anonymous without `token`, which only reaches public repositories.
+    pub fn new(http: Client, api_url: RestApiUrl, token: Option<String>, retry: RetryConfig) -> Self {
+        Self { http, api_url, token: token.map(|token| format!("token {token}")), retry }
+    }
+
+    pub fn api_url(&self) -> &RestApiUrl {
+        &self.api_url
+    }
+
+    fn auth(&self) -> Option<(&str, &str)> {
+        self.token.as_deref().map(|token| ("Authorization", token))
+    }
+
+    async fn repository(&self, owner: &str, name: &str) -> Result<Option<(GiteaRepository, u32)>, RestForgeError> {
+        let url = self.api_url.endpoint(&["repos", owner, name]);
+        let repository = get_optional::<GiteaRepository>(&self.http, &url, self.auth(), &self.retry).await?;
+
+        Ok(repository.map(|(repository, result)| (repository, result.retries)))
+    }
+}
+
+impl ForgeClient for GiteaClient {
+    type Error = RestForgeError;
+
+    fn forge(&self) -> Forge {
+        Forge::Gitea
+    }
+
+    fn retry(&self) -> &RetryConfig {
+        &self.retry
+    }
+
+    async fn fetch_stargazers(
+        &self,
+        owner: &str,
+        name: &str,
+        cursor: Option<&str>,
+        page_size: u32,
+        with_profiles: bool,
+    ) -> Result<Option<StargazerPage>, RestForgeError> {
+        let page = match cursor {
+            Some(cursor) => cursor
+                .parse::<u32>()
+                .map_err(|_| RestForgeError::InvalidCursor { value: cursor.into() })?,
+            None => 1,
+        };
+        let limit = page_size.clamp(1, GITEA_MAX_PAGE_SIZE);
+
+        let Some((repository, repository_retries)) = self.repository(owner, name).await? else {
+            return Ok(None);
+        };
+
+        let mut url = self.api_url.endpoint(&["repos", owner, name, "stargazers"]);
+        url.query_pairs_mut()
+            .append_pair("page", &page.to_string())
+            .append_pair("limit", &limit.to_string());
+
+        let Some((users, result)) = get_optional::<Vec<GiteaUser>>(&self.http, &url, self.auth(), &self.retry).await? else {
+            return Ok(None);
+        };
+
+        // No total nor next page is given, a full page may be followed by an empty one.
+        let has_next_page = users.len() >= limit as usize;
+        let starred_at = Utc::now();
+
+        Ok(Some(StargazerPage {
+            stargazers: users
+                .into_iter()
+                .map(|user| ForgeStargazer {
+                    login: user.login,
+                    starred_at,
+                    company: None,
+                    location: user.location.filter(|location| with_profiles && !location.is_empty()),
+                    email: None,
+                })
+                .collect(),
+            end_cursor: has_next_page.then(|| (page + 1).to_string()),
+            has_next_page,
+            retries: repository_retries + result.retries,
+            repository: repository.into(),
+        }))
+    }
+
+    async fn stargazer_count(&self, owner: &str, name: &str) -> Result<Option<u32>, RestForgeError> {
+        let repository = self.repository(owner, name).await?;
+
+        Ok(repository.map(|(repository, _)| u32::try_from(repository.stars_count).unwrap_or(0)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaRepository {
+	id: u64,
+	name: String,
+	owner: GiteaUser,
+	description: Option<String>,
+	language: Option<String>,
+	/// SPDX identifiers, set by Gitea 1.22 and later.
+	#[serde(default)]
+	licenses: Vec<String>,
+	#[serde(default)]
+	topics: Vec<String>,
+	created_at: DateTime<Utc>,
+	stars_count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaUser {
+	login: String,
+	location: Option<String>,
+}
+
+impl From<GiteaRepository> for ForgeRepository {
+    fn from(repository: GiteaRepository) -> Self {
+        Self {
+            id: repository.id.to_string(),
+            owner: repository.owner.login,
+            name: repository.name,
+            // Gitea answers empty strings rather than none.
+            description: repository.description.filter(|description| !description.is_empty()),
+            primary_language: repository.language.filter(|language| !language.is_empty()),
+            license: repository.licenses.into_iter().next(),
+            topics: repository.topics,
+            created_at: repository.created_at,
+            stargazer_count: repository.stars_count,
+        }
+    }
+}
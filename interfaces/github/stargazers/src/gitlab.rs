@@ -0,0 +1,178 @@
+//! GitLab REST API client for project stars
+//!
+//! Lists the starrers of a project with page-number pagination, the page
+//! after the current one being given by the `X-Next-Page` header. Projects
+//! are addressed by their full path, so `owner` is the namespace. GitLab
+//! doesn't say in which order starrers come, and exposes neither the
+//! primary language nor the SPDX license of a project in the same call.
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::forge::{Forge, ForgeClient, ForgeRepository, ForgeStargazer, StargazerPage};
+use crate::rest::{get_optional, RestApiUrl, RestForgeError};
+use crate::retry::RetryConfig;
+
+pub const GITLAB_COM_API_URL: &str = "https://gitlab.com/api/v4";
+
+/// Most starrers GitLab serves in one page.
+pub const GITLAB_MAX_PAGE_SIZE: u32 = 100;
+
+#[derive(Clone)]
+pub struct GitLabClient {
+    http: Client,
+    api_url: RestApiUrl,
+    token: Option<String>,
+    retry: RetryConfig,
+}
+
+impl std::fmt::Debug for GitLabClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitLabClient")
+            .field("api_url", &self.api_url)
+            .field("token", &self.token.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl GitLabClient {
+    /// Client of the GitLab at `api_url`, sharing the connection pool of `http`. Anonymous without `token`,
+    /// which only reaches public projects.
+    pub fn new(http: Client, api_url: RestApiUrl, token: Option<String>, retry: RetryConfig) -> Self {
+        Self { http, api_url, token, retry }
+    }
+
+    pub fn api_url(&self) -> &RestApiUrl {
+        &self.api_url
+    }
+
+    fn auth(&self) -> Option<(&str, &str)> {
+        self.token.as_deref().map(|token| ("PRIVATE-TOKEN", token))
+    }
+
+    async fn project(&self, owner: &str, name: &str) -> Result<Option<(GitLabProject, u32)>, RestForgeError> {
+        let url = self.api_url.endpoint(&["projects", &format!("{owner}/{name}")]);
+        let project = get_optional::<GitLabProject>(&self.http, &url, self.auth(), &self.retry).await?;
+
+        Ok(project.map(|(project, result)| (project, result.retries)))
+    }
+}
+
+impl ForgeClient for GitLabClient {
+    type Error = RestForgeError;
+
+    fn forge(&self) -> Forge {
+        Forge::GitLab
+    }
+
+    fn retry(&self) -> &RetryConfig {
+        &self.retry
+    }
+
+    async fn fetch_stargazers(
+        &self,
+        owner: &str,
+        name: &str,
+        cursor: Option<&str>,
+        page_size: u32,
+        _with_profiles: bool,
+    ) -> Result<Option<StargazerPage>, RestForgeError> {
+        let page = match cursor {
+            Some(cursor) => cursor
+                .parse::<u32>()
+                .map_err(|_| RestForgeError::InvalidCursor { value: cursor.into() })?,
+            None => 1,
+        };
+
+        let Some((project, project_retries)) = self.project(owner, name).await? else {
+            return Ok(None);
+        };
+
+        let mut url = self.api_url.endpoint(&["projects", &project.id.to_string(), "starrers"]);
+        url.query_pairs_mut()
+            .append_pair("page", &page.to_string())
+            .append_pair("per_page", &page_size.clamp(1, GITLAB_MAX_PAGE_SIZE).to_string());
+
+        let Some((starrers, result)) = get_optional::<Vec<GitLabStarrer>>(&self.http, &url, self.auth(), &self.retry).await? else {
+            return Ok(None);
+        };
+
+        // Empty on the last page.
+        let next_page = result
+            .headers
+            .get("x-next-page")
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string);
+
+        Ok(Some(StargazerPage {
+            stargazers: starrers
+                .into_iter()
+                .map(|starrer| ForgeStargazer {
+                    login: starrer.user.username,
+                    starred_at: starrer.starred_since,
+                    company: None,
+                    location: None,
+                    email: None,
+                })
+                .collect(),
+            has_next_page: next_page.is_some(),
+            end_cursor: next_page,
+            retries: project_retries + result.retries,
+            repository: project.into(),
+        }))
+    }
+
+    async fn stargazer_count(&self, owner: &str, name: &str) -> Result<Option<u32>, RestForgeError> {
+        let project = self.project(owner, name).await?;
+
+        Ok(project.map(|(project, _)| u32::try_from(project.star_count).unwrap_or(0)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabProject {
+	id: u64,
+	path: String,
+	namespace: GitLabNamespace,
+	description: Option<String>,
+	created_at: DateTime<Utc>,
+	star_count: i64,
+	#[serde(default)]
+	topics: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabNamespace {
+	full_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabStarrer {
+	starred_since: DateTime<Utc>,
+	user: GitLabUser,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabUser {
+	username: String,
+}
+
+impl From<GitLabProject> for ForgeRepository {
+    fn from(project: GitLabProject) -> Self {
+        Self {
+            id: project.id.to_string(),
+            owner: project.namespace.full_path,
+            name: project.path,
+            // GitLab answers an empty description rather than none.
+            description: project.description.filter(|description| !description.is_empty()),
+            primary_language: None,
+            license: None,
+            topics: project.topics,
+            created_at: project.created_at,
+            stargazer_count: project.star_count,
+        }
+    }
+}
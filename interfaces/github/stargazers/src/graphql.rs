@@ -0,0 +1,245 @@
+//! Transport shared by every GitHub GraphQL query
+//!
+//! Posts a query with retries on transient failures, then classifies the
+//! answer into typed errors before handing back the `data` payload.
+
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::client::{Credential, CredentialError, GitHubClient};
+
+pub struct GitHubGraphQLResult {
+    pub body: String,
+    pub status: StatusCode,
+    /// Attempts that failed transiently before this result was obtained.
+    pub retries: u32,
+}
+
+/// Sends `payload` to the GraphQL endpoint, retrying 5xx answers and network errors.
+///
+/// An answer exhausting the token's quota is retried straight away with another
+/// token of the pool when one still has quota left.
+pub async fn post_graphql(
+    client: &GitHubClient,
+    payload: &serde_json::Value,
+) -> Result<GitHubGraphQLResult, PostGraphQLError> {
+    let retry = client.retry();
+    let mut retries = 0;
+
+    loop {
+        let credential = client
+            .credential()
+            .await
+            .map_err(|source| PostGraphQLError::Credential { source })?;
+        let attempt = send_graphql_request(client, &credential, payload).await;
+        let last_attempt = retries + 1 >= retry.max_attempts;
+
+        match attempt {
+            Ok((status, _)) if is_quota_exhausted(status) && client.can_switch_token(&credential) && !last_attempt => {
+                warn!(%status, retries, "GitHub token quota exhausted, switching token");
+                retries += 1;
+                continue;
+            }
+            Ok((status, body)) if !status.is_server_error() || last_attempt => {
+                return Ok(GitHubGraphQLResult { body, status, retries });
+            }
+            Err(error) if !error.is_transient() || last_attempt => return Err(error),
+            Ok((status, _)) => warn!(%status, retries, "GitHub server error, retrying"),
+            Err(error) => warn!(%error, retries, "GitHub request failed, retrying"),
+        }
+
+        tokio::time::sleep(retry.backoff_delay(retries)).await;
+        retries += 1;
+    }
+}
+
+fn is_quota_exhausted(status: StatusCode) -> bool {
+    status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+async fn send_graphql_request(
+    client: &GitHubClient,
+    credential: &Credential,
+    payload: &serde_json::Value,
+) -> Result<(StatusCode, String), PostGraphQLError> {
+    let response = client
+        .http()
+        .post(client.api_url().graphql())
+        .header("Authorization", format!("Bearer {}", credential.token()))
+        .header("Content-Type", "application/json")
+        .json(payload)
+        .send()
+        .await
+        .map_err(|source| PostGraphQLError::RequestSend { source })?;
+
+    let status = response.status();
+    client.record_rate_limit(credential, response.headers());
+
+    let body = response
+        .text()
+        .await
+        .map_err(|source| PostGraphQLError::ResponseRead { source })?;
+
+    Ok((status, body))
+}
+
+#[derive(Debug, Error)]
+pub enum PostGraphQLError {
+    #[error("Credential: {source}")]
+    Credential {
+        source: CredentialError,
+    },
+
+    #[error("RequestSend: {source}")]
+    RequestSend {
+        source: reqwest::Error,
+    },
+    
+    #[error("ResponseRead: {source}")]
+    ResponseRead {
+        source: reqwest::Error,
+    },
+}
+
+impl PostGraphQLError {
+    /// Network-level failures worth retrying, as opposed to malformed requests.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PostGraphQLError::RequestSend { source } => {
+                source.is_timeout() || source.is_connect() || source.is_request()
+            }
+            PostGraphQLError::ResponseRead { .. } => true,
+            PostGraphQLError::Credential { .. } => false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQLResponse<T> {
+	pub data: Option<T>,
+	#[serde(default)]
+	pub errors: Vec<GraphQLError>,
+}
+
+/// Entry of the GraphQL `errors` array, `type` is only set by GitHub for known failures.
+#[derive(Debug, Deserialize)]
+pub struct GraphQLError {
+	#[serde(rename = "type")]
+	pub error_type: Option<String>,
+	pub message: String,
+}
+
+/// Error body returned by GitHub outside of GraphQL, e.g. on bad credentials.
+#[derive(Debug, Deserialize)]
+struct RestErrorBody {
+	message: String,
+}
+
+#[derive(Debug, Error)]
+pub enum ParseGraphQLResponseError {
+    #[error("Unauthorized: {message}")]
+    Unauthorized {
+        message: String,
+    },
+
+    #[error("Forbidden: {message}")]
+    Forbidden {
+        message: String,
+    },
+
+    #[error("RateLimited: {message}")]
+    RateLimited {
+        message: String,
+    },
+
+    #[error("NotFound: {message}")]
+    NotFound {
+        message: String,
+    },
+
+    #[error("GraphQL: {message}")]
+    GraphQL {
+        message: String,
+    },
+
+    #[error("UnexpectedStatus: {status}: {body}")]
+    UnexpectedStatus {
+        status: StatusCode,
+        body: String,
+    },
+
+    #[error("ResponseBodyDeserialization: {source}")]
+    ResponseBodyDeserialization {
+        source: serde_json::Error,
+    },
+}
+
+impl ParseGraphQLResponseError {
+    /// GitHub throttling bursts of expensive queries, while the token still has quota left.
+    pub fn is_secondary_rate_limit(&self) -> bool {
+        matches!(self, ParseGraphQLResponseError::RateLimited { message } if message.to_lowercase().contains("secondary rate limit"))
+    }
+}
+
+/// Turns a raw GitHub answer into its `data` payload, classifying HTTP and GraphQL failures.
+pub fn parse_graphql_response<T: DeserializeOwned>(
+    result: &GitHubGraphQLResult,
+) -> Result<T, ParseGraphQLResponseError> {
+    parse_graphql_data(result, false)
+}
+
+/// Same as [`parse_graphql_response`], but `NOT_FOUND` errors are ignored when data came along,
+/// for queries looking up several nodes under aliases, some of which may be gone.
+pub fn parse_partial_graphql_response<T: DeserializeOwned>(
+    result: &GitHubGraphQLResult,
+) -> Result<T, ParseGraphQLResponseError> {
+    parse_graphql_data(result, true)
+}
+
+fn parse_graphql_data<T: DeserializeOwned>(
+    result: &GitHubGraphQLResult,
+    skip_not_found: bool,
+) -> Result<T, ParseGraphQLResponseError> {
+    if !result.status.is_success() {
+        let message = serde_json::from_str::<RestErrorBody>(&result.body)
+            .map(|rest| rest.message)
+            .unwrap_or_else(|_| result.body.clone());
+
+        return Err(match result.status {
+            StatusCode::UNAUTHORIZED => ParseGraphQLResponseError::Unauthorized { message },
+            StatusCode::TOO_MANY_REQUESTS => ParseGraphQLResponseError::RateLimited { message },
+            StatusCode::FORBIDDEN if message.to_lowercase().contains("rate limit") => {
+                ParseGraphQLResponseError::RateLimited { message }
+            }
+            StatusCode::FORBIDDEN => ParseGraphQLResponseError::Forbidden { message },
+            status => ParseGraphQLResponseError::UnexpectedStatus {
+                status,
+                body: result.body.clone(),
+            },
+        });
+    }
+
+    let parsed: GraphQLResponse<T> = serde_json::from_str(&result.body)
+        .map_err(|source| ParseGraphQLResponseError::ResponseBodyDeserialization { source })?;
+
+    let skip_not_found = skip_not_found && parsed.data.is_some();
+    let error = parsed.errors
+        .into_iter()
+        .find(|error| !(skip_not_found && error.error_type.as_deref() == Some("NOT_FOUND")));
+    if let Some(error) = error {
+        let message = error.message;
+
+        return Err(match error.error_type.as_deref() {
+            Some("NOT_FOUND") => ParseGraphQLResponseError::NotFound { message },
+            Some("FORBIDDEN") | Some("INSUFFICIENT_SCOPES") => ParseGraphQLResponseError::Forbidden { message },
+            Some("RATE_LIMITED") => ParseGraphQLResponseError::RateLimited { message },
+            _ => ParseGraphQLResponseError::GraphQL { message },
+        });
+    }
+
+    parsed.data.ok_or_else(|| ParseGraphQLResponseError::GraphQL {
+        message: "response contains neither data nor errors".into(),
+    })
+}
@@ -1,33 +1,61 @@
 //! GitHub GraphQL API client for stargazer data
 //! 
-//! Fetches repository stars in batches of 100 using cursor-based pagination.
-//! Requires GitHub token with repo read access.
+//! Fetches repository stars in pages of up to 100 using cursor-based pagination,
+//! the caller picking the size of each page, see [`crate::page_size`].
+//! Requires GitHub token with repo read access. Stargazers' public company and
+//! location are only requested when asked for.
 
 use chrono::{DateTime, Utc};
-use reqwest::{Client, StatusCode};
 use serde::Deserialize;
 use thiserror::Error;
 
-pub struct GitHubGraphQLResult {
-    pub body: String,
-    pub status: StatusCode,
-}
+use crate::client::GitHubClient;
+use crate::graphql::{
+    parse_graphql_response, post_graphql, GitHubGraphQLResult, ParseGraphQLResponseError,
+    PostGraphQLError,
+};
 
+/// Page of at most `page_size` stargazers after `cursor`, with their profile fields when `with_profiles` is set.
 pub async fn fetch_repo_stargazers(
-    token: &str,
+    client: &GitHubClient,
     owner: &str,
     name: &str,
     cursor: Option<&str>,
+    page_size: u32,
+    with_profiles: bool,
 ) -> Result<GitHubGraphQLResult, FetchRepoStargazersError> {
     let graphql_query = r#"
-        query getRepoStargazers($owner: String!, $name: String!, $cursor: String) {
+        query getRepoStargazers($owner: String!, $name: String!, $cursor: String, $first: Int!, $withProfiles: Boolean!) {
             repository(owner: $owner, name: $name) {
-                stargazers(first: 100, after: $cursor, orderBy: {field: STARRED_AT, direction: ASC}) {
+                id
+                name
+                owner {
+                    login
+                }
+                description
+                createdAt
+                stargazerCount
+                primaryLanguage {
+                    name
+                }
+                licenseInfo {
+                    spdxId
+                }
+                repositoryTopics(first: 20) {
+                    nodes {
+                        topic {
+                            name
+                        }
+                    }
+                }
+                stargazers(first: $first, after: $cursor, orderBy: {field: STARRED_AT, direction: ASC}) {
                     edges {
                         starredAt
                         node {
                             login
                             email
+                            company @include(if: $withProfiles)
+                            location @include(if: $withProfiles)
                         }
                     }
                     pageInfo {
@@ -45,47 +73,29 @@ pub async fn fetch_repo_stargazers(
             "owner": owner,
             "name": name,
             "cursor": cursor,
+            "first": page_size,
+            "withProfiles": with_profiles,
         }
     });
 
-    let client = Client::new();
-
-    let response = client
-        .post("https://api.github.com/graphql")
-        .header("Authorization", format!("Bearer {token}"))
-        .header("Content-Type", "application/json")
-        .header("User-Agent", "rust-client")
-        .json(&payload)
-        .send()
+    post_graphql(client, &payload)
         .await
-        .map_err(|source| FetchRepoStargazersError::RequestSend { source })?;
-
-    let status = response.status();
-
-    let body = response
-        .text()
-        .await
-        .map_err(|source| FetchRepoStargazersError::ResponseRead { source })?;
-
-    Ok(GitHubGraphQLResult { body, status })
+        .map_err(|source| FetchRepoStargazersError::PostGraphQL { source })
 }
 
 #[derive(Debug, Error)]
 pub enum FetchRepoStargazersError {
-    #[error("RequestSend: {source}")]
-    RequestSend {
-        source: reqwest::Error,
-    },
-    
-    #[error("ResponseRead: {source}")]
-    ResponseRead {
-        source: reqwest::Error,
+    #[error("PostGraphQL: {source}")]
+    PostGraphQL {
+        source: PostGraphQLError,
     },
 }
 
-#[derive(Debug, Deserialize)]
-pub struct GraphQLResponse {
-	pub data: RepositoryData,
+/// Parses a stargazers page answer, see [`parse_graphql_response`].
+pub fn parse_repo_stargazers_response(
+    result: &GitHubGraphQLResult,
+) -> Result<RepositoryData, ParseGraphQLResponseError> {
+    parse_graphql_response(result)
 }
 
 #[derive(Debug, Deserialize)]
@@ -95,9 +105,57 @@ pub struct RepositoryData {
 
 #[derive(Debug, Deserialize)]
 pub struct Repository {
+	/// Global node ID, kept across renames and transfers.
+	pub id: String,
+	/// Current name, GitHub answers for renamed repositories under their old name too.
+	pub name: String,
+	pub owner: RepositoryOwner,
+	pub description: Option<String>,
+	#[serde(rename = "createdAt")]
+	pub created_at: DateTime<Utc>,
+	/// Stars of the repository when the page was served, pages may be behind by the stars given since.
+	#[serde(rename = "stargazerCount")]
+	pub stargazer_count: i64,
+	#[serde(rename = "primaryLanguage")]
+	pub primary_language: Option<Language>,
+	#[serde(rename = "licenseInfo")]
+	pub license_info: Option<License>,
+	#[serde(rename = "repositoryTopics")]
+	pub repository_topics: TopicConnection,
 	pub stargazers: StargazerConnection,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RepositoryOwner {
+	pub login: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Language {
+	pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct License {
+	#[serde(rename = "spdxId")]
+	pub spdx_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TopicConnection {
+	pub nodes: Vec<RepositoryTopic>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryTopic {
+	pub topic: Topic,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Topic {
+	pub name: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct StargazerConnection {
 	pub edges: Vec<StargazerEdge>,
@@ -116,6 +174,10 @@ pub struct StargazerEdge {
 pub struct StargazerUser {
 	pub login: String,
 	pub email: Option<String>,
+	/// Free text from the profile, absent unless profiles were requested.
+	pub company: Option<String>,
+	/// Free text from the profile, absent unless profiles were requested.
+	pub location: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -125,4 +187,3 @@ pub struct PageInfo {
 	#[serde(rename = "endCursor")]
 	pub end_cursor: Option<String>,
 }
-
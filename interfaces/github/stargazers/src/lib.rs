@@ -1 +1,22 @@
+pub mod account_ages;
+pub mod activity;
+pub mod api_url;
+pub mod app_auth;
+pub mod client;
+pub mod contributors;
+pub mod fetcher;
+pub mod forge;
+pub mod gitea;
+pub mod gitlab;
+pub mod graphql;
 pub mod index;
+pub mod owner_repositories;
+pub mod page_size;
+pub mod releases;
+pub mod repository_forks;
+pub mod repository_location;
+pub mod rest;
+pub mod retry;
+pub mod stargazer_count;
+pub mod token_pool;
+pub mod traffic;
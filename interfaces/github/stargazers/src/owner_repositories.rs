@@ -0,0 +1,92 @@
+//! GitHub GraphQL API client for the repositories of a user or organization
+//!
+//! Lists non-fork repositories owned by a login in batches of 100 using
+//! cursor-based pagination.
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::GitHubClient;
+use crate::graphql::{
+    parse_graphql_response, post_graphql, GitHubGraphQLResult, ParseGraphQLResponseError,
+    PostGraphQLError,
+};
+use crate::index::PageInfo;
+
+pub async fn fetch_owner_repositories(
+    client: &GitHubClient,
+    owner: &str,
+    cursor: Option<&str>,
+) -> Result<GitHubGraphQLResult, FetchOwnerRepositoriesError> {
+    let graphql_query = r#"
+        query getOwnerRepositories($owner: String!, $cursor: String) {
+            repositoryOwner(login: $owner) {
+                repositories(first: 100, after: $cursor, isFork: false, ownerAffiliations: OWNER, orderBy: {field: NAME, direction: ASC}) {
+                    nodes {
+                        name
+                        isArchived
+                        stargazerCount
+                    }
+                    pageInfo {
+                        hasNextPage
+                        endCursor
+                    }
+                }
+            }
+        }
+    "#;
+
+    let payload = serde_json::json!({
+        "query": graphql_query,
+        "variables": {
+            "owner": owner,
+            "cursor": cursor,
+        }
+    });
+
+    post_graphql(client, &payload)
+        .await
+        .map_err(|source| FetchOwnerRepositoriesError::PostGraphQL { source })
+}
+
+#[derive(Debug, Error)]
+pub enum FetchOwnerRepositoriesError {
+    #[error("PostGraphQL: {source}")]
+    PostGraphQL {
+        source: PostGraphQLError,
+    },
+}
+
+/// Parses an owner repositories page answer, see [`parse_graphql_response`].
+pub fn parse_owner_repositories_response(
+    result: &GitHubGraphQLResult,
+) -> Result<RepositoryOwnerData, ParseGraphQLResponseError> {
+    parse_graphql_response(result)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryOwnerData {
+	#[serde(rename = "repositoryOwner")]
+	pub repository_owner: Option<RepositoryOwner>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryOwner {
+	pub repositories: OwnerRepositoryConnection,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OwnerRepositoryConnection {
+	pub nodes: Vec<OwnerRepository>,
+	#[serde(rename = "pageInfo")]
+	pub page_info: PageInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OwnerRepository {
+	pub name: String,
+	#[serde(rename = "isArchived")]
+	pub is_archived: bool,
+	#[serde(rename = "stargazerCount")]
+	pub stargazer_count: u32,
+}
@@ -0,0 +1,72 @@
+//! Adaptive page size for stargazer pagination
+//!
+//! GitHub serves at most 100 stargazers per page, but large pages are the first
+//! to trip secondary rate limits and query timeouts. The page size halves on
+//! such failures, down to `min`, and doubles back towards `max` once enough
+//! pages in a row went through.
+
+/// Most stargazers GitHub serves in one page.
+pub const GITHUB_MAX_PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PageSizeConfig {
+    /// Page size to start from and to grow back to, capped at [`GITHUB_MAX_PAGE_SIZE`].
+    pub max: u32,
+    /// Page size never shrunk below.
+    pub min: u32,
+    /// Successful pages in a row before the page size doubles.
+    pub grow_after: u32,
+}
+
+impl Default for PageSizeConfig {
+    fn default() -> Self {
+        Self {
+            max: GITHUB_MAX_PAGE_SIZE,
+            min: 10,
+            grow_after: 5,
+        }
+    }
+}
+
+/// Page size of one pagination, adjusted by the outcome of each page.
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptivePageSize {
+    config: PageSizeConfig,
+    current: u32,
+    healthy_pages: u32,
+}
+
+impl AdaptivePageSize {
+    /// Starts at `config.max`, bounds brought back within what GitHub accepts.
+    pub fn new(config: &PageSizeConfig) -> Self {
+        let max = config.max.clamp(1, GITHUB_MAX_PAGE_SIZE);
+        let config = PageSizeConfig { max, min: config.min.clamp(1, max), ..*config };
+        Self { config, current: max, healthy_pages: 0 }
+    }
+
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    /// Halves the page size after a failure it may have caused, `false` when already at `min`.
+    pub fn shrink(&mut self) -> bool {
+        self.healthy_pages = 0;
+        if self.current <= self.config.min {
+            return false;
+        }
+        self.current = (self.current / 2).max(self.config.min);
+        true
+    }
+
+    /// Counts a successful page, doubling the page size after `grow_after` of them in a row.
+    pub fn record_success(&mut self) {
+        if self.current >= self.config.max {
+            return;
+        }
+        self.healthy_pages += 1;
+        if self.healthy_pages >= self.config.grow_after {
+            self.current = self.current.saturating_mul(2).min(self.config.max);
+            self.healthy_pages = 0;
+        }
+    }
+}
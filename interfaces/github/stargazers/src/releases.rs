@@ -0,0 +1,190 @@
+//! GitHub GraphQL API client for a repository's releases and tags
+//!
+//! A single query for the latest [`MAX_RELEASES`] releases and tags. Tags
+//! without a release stand for the projects that only tag their versions,
+//! dated by their tagger or, for lightweight tags, by their commit. Drafts
+//! aren't published and are left out.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::GitHubClient;
+use crate::graphql::{parse_graphql_response, post_graphql, ParseGraphQLResponseError, PostGraphQLError};
+
+/// Releases, and tags, fetched at most, the latest ones.
+pub const MAX_RELEASES: usize = 100;
+
+/// What a [`ForgeRelease`] was published as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseKind {
+    Release,
+    /// Tag without a release.
+    Tag,
+}
+
+impl ReleaseKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseKind::Release => "release",
+            ReleaseKind::Tag => "tag",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "release" => Some(ReleaseKind::Release),
+            "tag" => Some(ReleaseKind::Tag),
+            _ => None,
+        }
+    }
+}
+
+/// Version of a repository, published or tagged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeRelease {
+    pub tag_name: String,
+    /// Title of the release, unset for tags.
+    pub name: Option<String>,
+    pub published_at: DateTime<Utc>,
+    pub prerelease: bool,
+    pub kind: ReleaseKind,
+    /// Page of the release, unset for tags.
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum FetchRepositoryReleasesError {
+    #[error("PostGraphQL: {source}")]
+    PostGraphQL {
+        source: PostGraphQLError,
+    },
+
+    #[error("ParseGraphQLResponse: {source}")]
+    ParseGraphQLResponse {
+        source: ParseGraphQLResponseError,
+    },
+}
+
+/// Releases of `owner/name`, and its tags without one, oldest first. `None` when GitHub doesn't
+/// know the repository.
+pub async fn fetch_repository_releases(
+    client: &GitHubClient,
+    owner: &str,
+    name: &str,
+) -> Result<Option<Vec<ForgeRelease>>, FetchRepositoryReleasesError> {
+    let graphql_query = r#"
+        query getRepositoryReleases($owner: String!, $name: String!, $first: Int!) {
+            repository(owner: $owner, name: $name) {
+                releases(first: $first, orderBy: {field: CREATED_AT, direction: DESC}) {
+                    nodes { tagName name publishedAt isDraft isPrerelease url }
+                }
+                refs(refPrefix: "refs/tags/", first: $first, orderBy: {field: TAG_COMMIT_DATE, direction: DESC}) {
+                    nodes {
+                        name
+                        target {
+                            ... on Commit { committedDate }
+                            ... on Tag { tagger { date } }
+                        }
+                    }
+                }
+            }
+        }
+    "#;
+
+    let payload = serde_json::json!({
+        "query": graphql_query,
+        "variables": {
+            "owner": owner,
+            "name": name,
+            "first": MAX_RELEASES,
+        }
+    });
+
+    let result = post_graphql(client, &payload)
+        .await
+        .map_err(|source| FetchRepositoryReleasesError::PostGraphQL { source })?;
+    let data: RepositoryReleasesData = parse_graphql_response(&result)
+        .map_err(|source| FetchRepositoryReleasesError::ParseGraphQLResponse { source })?;
+
+    Ok(data.repository.map(RepositoryReleases::into_releases))
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryReleasesData {
+    repository: Option<RepositoryReleases>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryReleases {
+    releases: Nodes<ReleaseNode>,
+    refs: Option<Nodes<TagNode>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Nodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseNode {
+    tag_name: String,
+    name: Option<String>,
+    /// Unset on drafts.
+    published_at: Option<DateTime<Utc>>,
+    is_draft: bool,
+    is_prerelease: bool,
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagNode {
+    name: String,
+    target: Option<TagTarget>,
+}
+
+/// A commit for lightweight tags, a tag object for annotated ones.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TagTarget {
+    committed_date: Option<DateTime<Utc>>,
+    tagger: Option<Tagger>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tagger {
+    date: Option<DateTime<Utc>>,
+}
+
+impl RepositoryReleases {
+    fn into_releases(self) -> Vec<ForgeRelease> {
+        let mut releases: Vec<ForgeRelease> = self.releases.nodes
+            .into_iter()
+            .filter(|release| !release.is_draft)
+            .filter_map(|release| Some(ForgeRelease {
+                published_at: release.published_at?,
+                tag_name: release.tag_name,
+                name: release.name.filter(|name| !name.trim().is_empty()),
+                prerelease: release.is_prerelease,
+                kind: ReleaseKind::Release,
+                url: release.url,
+            }))
+            .collect();
+
+        let tags: Vec<ForgeRelease> = self.refs
+            .map_or_else(Vec::new, |refs| refs.nodes)
+            .into_iter()
+            .filter(|tag| !releases.iter().any(|release| release.tag_name == tag.name))
+            .filter_map(|tag| {
+                let target = tag.target?;
+                let tagged_at = target.tagger.and_then(|tagger| tagger.date).or(target.committed_date)?;
+                Some(ForgeRelease { tag_name: tag.name, name: None, published_at: tagged_at, prerelease: false, kind: ReleaseKind::Tag, url: None })
+            })
+            .collect();
+
+        releases.extend(tags);
+        releases.sort_by(|a, b| a.published_at.cmp(&b.published_at).then_with(|| a.tag_name.cmp(&b.tag_name)));
+        releases
+    }
+}
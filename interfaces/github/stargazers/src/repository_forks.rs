@@ -0,0 +1,94 @@
+//! GitHub GraphQL API client for the forks of a repository
+//!
+//! Lists forks most starred first in batches of 100 using cursor-based
+//! pagination, so callers looking for popular forks can stop early.
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::GitHubClient;
+use crate::graphql::{
+    parse_graphql_response, post_graphql, GitHubGraphQLResult, ParseGraphQLResponseError,
+    PostGraphQLError,
+};
+use crate::index::{PageInfo, RepositoryOwner};
+
+pub async fn fetch_repository_forks(
+    client: &GitHubClient,
+    owner: &str,
+    name: &str,
+    cursor: Option<&str>,
+) -> Result<GitHubGraphQLResult, FetchRepositoryForksError> {
+    let graphql_query = r#"
+        query getRepositoryForks($owner: String!, $name: String!, $cursor: String) {
+            repository(owner: $owner, name: $name) {
+                forks(first: 100, after: $cursor, orderBy: {field: STARGAZERS, direction: DESC}) {
+                    nodes {
+                        name
+                        owner {
+                            login
+                        }
+                        stargazerCount
+                    }
+                    pageInfo {
+                        hasNextPage
+                        endCursor
+                    }
+                }
+            }
+        }
+    "#;
+
+    let payload = serde_json::json!({
+        "query": graphql_query,
+        "variables": {
+            "owner": owner,
+            "name": name,
+            "cursor": cursor,
+        }
+    });
+
+    post_graphql(client, &payload)
+        .await
+        .map_err(|source| FetchRepositoryForksError::PostGraphQL { source })
+}
+
+#[derive(Debug, Error)]
+pub enum FetchRepositoryForksError {
+    #[error("PostGraphQL: {source}")]
+    PostGraphQL {
+        source: PostGraphQLError,
+    },
+}
+
+/// Parses a repository forks page answer, see [`parse_graphql_response`].
+pub fn parse_repository_forks_response(
+    result: &GitHubGraphQLResult,
+) -> Result<RepositoryForksData, ParseGraphQLResponseError> {
+    parse_graphql_response(result)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryForksData {
+	pub repository: Option<ForkedRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForkedRepository {
+	pub forks: ForkConnection,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForkConnection {
+	pub nodes: Vec<Fork>,
+	#[serde(rename = "pageInfo")]
+	pub page_info: PageInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Fork {
+	pub name: String,
+	pub owner: RepositoryOwner,
+	#[serde(rename = "stargazerCount")]
+	pub stargazer_count: u32,
+}
@@ -0,0 +1,85 @@
+//! Where a moved GitHub repository lives now
+//!
+//! GraphQL answers for a renamed repository under its former name, but not
+//! always for one transferred to another owner. REST still does: it answers
+//! `301 Moved Permanently` towards the repository's ID-based URL, which the
+//! HTTP client follows, and that answer names the repository's current owner
+//! and name.
+
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::client::{CredentialError, GitHubClient};
+use crate::rest::{get_optional, RestForgeError};
+
+/// Current owner and name of a repository on GitHub.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryLocation {
+    pub owner: String,
+    pub name: String,
+}
+
+impl RepositoryLocation {
+    /// Whether the repository lives elsewhere than `owner/name`, which GitHub compares case-insensitively.
+    pub fn differs_from(&self, owner: &str, name: &str) -> bool {
+        !self.owner.eq_ignore_ascii_case(owner) || !self.name.eq_ignore_ascii_case(name)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FetchRepositoryLocationError {
+    #[error("Credential: {source}")]
+    Credential {
+        source: CredentialError,
+    },
+
+    #[error("InvalidUrl: {source}")]
+    InvalidUrl {
+        source: url::ParseError,
+    },
+
+    #[error("GetRepository: {source}")]
+    GetRepository {
+        source: RestForgeError,
+    },
+}
+
+/// Location of `owner/name` once redirects are followed, `None` when GitHub doesn't know it.
+pub async fn fetch_repository_location(
+    client: &GitHubClient,
+    owner: &str,
+    name: &str,
+) -> Result<Option<RepositoryLocation>, FetchRepositoryLocationError> {
+    let mut url = Url::parse(&client.api_url().rest("repos"))
+        .map_err(|source| FetchRepositoryLocationError::InvalidUrl { source })?;
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.extend([owner, name]);
+    }
+
+    let credential = client
+        .credential()
+        .await
+        .map_err(|source| FetchRepositoryLocationError::Credential { source })?;
+    let authorization = format!("Bearer {}", credential.token());
+
+    let answer = get_optional::<RestRepository>(client.http(), &url, Some(("Authorization", &authorization)), client.retry())
+        .await
+        .map_err(|source| FetchRepositoryLocationError::GetRepository { source })?;
+
+    Ok(answer.map(|(repository, result)| {
+        client.record_rate_limit(&credential, &result.headers);
+        RepositoryLocation { owner: repository.owner.login, name: repository.name }
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct RestRepository {
+    name: String,
+    owner: RestOwner,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestOwner {
+    login: String,
+}
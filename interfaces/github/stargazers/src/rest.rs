@@ -0,0 +1,281 @@
+//! Transport shared by the GitLab and Gitea REST clients
+//!
+//! GETs a JSON resource with the same retries as GitHub queries, then
+//! classifies failed answers by status before deserializing the body.
+
+use std::str::FromStr;
+
+use reqwest::{header::HeaderMap, Client, StatusCode};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use tracing::warn;
+use url::Url;
+
+use crate::forge::{ForgeApiError, ForgeErrorKind};
+use crate::retry::RetryConfig;
+
+/// Base URL of a REST API, e.g. `https://gitlab.com/api/v4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestApiUrl(Url);
+
+#[derive(Debug, Error)]
+pub enum ParseRestApiUrlError {
+    #[error("InvalidUrl: {source}")]
+    InvalidUrl {
+        source: url::ParseError,
+    },
+
+    #[error("UnsupportedScheme: {value}")]
+    UnsupportedScheme {
+        value: String,
+    },
+
+    #[error("UnexpectedQueryOrFragment: {value}")]
+    UnexpectedQueryOrFragment {
+        value: String,
+    },
+}
+
+impl RestApiUrl {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str().trim_end_matches('/')
+    }
+
+    /// Endpoint below the base URL, each segment percent-encoded, `/` included.
+    pub fn endpoint(&self, segments: &[&str]) -> Url {
+        let mut url = self.0.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        url
+    }
+}
+
+impl FromStr for RestApiUrl {
+    type Err = ParseRestApiUrlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let url = Url::parse(value)
+            .map_err(|source| ParseRestApiUrlError::InvalidUrl { source })?;
+
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ParseRestApiUrlError::UnsupportedScheme { value: value.into() });
+        }
+        if url.query().is_some() || url.fragment().is_some() {
+            return Err(ParseRestApiUrlError::UnexpectedQueryOrFragment { value: value.into() });
+        }
+
+        Ok(Self(url))
+    }
+}
+
+impl std::fmt::Display for RestApiUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub struct RestResult {
+    pub body: String,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Attempts that failed transiently before this result was obtained.
+    pub retries: u32,
+}
+
+/// GETs `url`, retrying 5xx answers and network errors. `auth` is the header carrying the token, if any.
+pub async fn get_rest(
+    http: &Client,
+    url: &Url,
+    auth: Option<(&str, &str)>,
+    retry: &RetryConfig,
+) -> Result<RestResult, GetRestError> {
+    let mut retries = 0;
+
+    loop {
+        let attempt = send_rest_request(http, url, auth).await;
+        let last_attempt = retries + 1 >= retry.max_attempts;
+
+        match attempt {
+            Ok((status, headers, body)) if !status.is_server_error() || last_attempt => {
+                return Ok(RestResult { body, status, headers, retries });
+            }
+            Err(error) if !error.is_transient() || last_attempt => return Err(error),
+            Ok((status, _, _)) => warn!(%status, retries, host = url.host_str(), "forge server error, retrying"),
+            Err(error) => warn!(%error, retries, host = url.host_str(), "forge request failed, retrying"),
+        }
+
+        tokio::time::sleep(retry.backoff_delay(retries)).await;
+        retries += 1;
+    }
+}
+
+async fn send_rest_request(
+    http: &Client,
+    url: &Url,
+    auth: Option<(&str, &str)>,
+) -> Result<(StatusCode, HeaderMap, String), GetRestError> {
+    let mut request = http.get(url.clone()).header("Accept", "application/json");
+    if let Some((name, value)) = auth {
+        request = request.header(name, value);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|source| GetRestError::RequestSend { source })?;
+
+    let status = response.status();
+    let headers = response.headers().clone();
+
+    let body = response
+        .text()
+        .await
+        .map_err(|source| GetRestError::ResponseRead { source })?;
+
+    Ok((status, headers, body))
+}
+
+#[derive(Debug, Error)]
+pub enum GetRestError {
+    #[error("RequestSend: {source}")]
+    RequestSend {
+        source: reqwest::Error,
+    },
+
+    #[error("ResponseRead: {source}")]
+    ResponseRead {
+        source: reqwest::Error,
+    },
+}
+
+impl GetRestError {
+    /// Network-level failures worth retrying, as opposed to malformed requests.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            GetRestError::RequestSend { source } => {
+                source.is_timeout() || source.is_connect() || source.is_request()
+            }
+            GetRestError::ResponseRead { .. } => true,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseRestResponseError {
+    #[error("Unauthorized: {message}")]
+    Unauthorized {
+        message: String,
+    },
+
+    #[error("Forbidden: {message}")]
+    Forbidden {
+        message: String,
+    },
+
+    #[error("RateLimited: {message}")]
+    RateLimited {
+        message: String,
+    },
+
+    #[error("NotFound: {message}")]
+    NotFound {
+        message: String,
+    },
+
+    #[error("UnexpectedStatus: {status}: {body}")]
+    UnexpectedStatus {
+        status: StatusCode,
+        body: String,
+    },
+
+    #[error("ResponseBodyDeserialization: {source}")]
+    ResponseBodyDeserialization {
+        source: serde_json::Error,
+    },
+}
+
+/// Deserializes a successful answer, classifying failed ones by status.
+pub fn parse_rest_response<T: DeserializeOwned>(
+    result: &RestResult,
+) -> Result<T, ParseRestResponseError> {
+    if !result.status.is_success() {
+        // Both forges answer `{"message": ...}`, GitLab sometimes with a nested object.
+        let message = serde_json::from_str::<serde_json::Value>(&result.body)
+            .ok()
+            .and_then(|body| body.get("message").map(|message| match message {
+                serde_json::Value::String(message) => message.clone(),
+                message => message.to_string(),
+            }))
+            .unwrap_or_else(|| result.body.clone());
+
+        return Err(match result.status {
+            StatusCode::NOT_FOUND => ParseRestResponseError::NotFound { message },
+            StatusCode::UNAUTHORIZED => ParseRestResponseError::Unauthorized { message },
+            StatusCode::TOO_MANY_REQUESTS => ParseRestResponseError::RateLimited { message },
+            StatusCode::FORBIDDEN => ParseRestResponseError::Forbidden { message },
+            status => ParseRestResponseError::UnexpectedStatus {
+                status,
+                body: result.body.clone(),
+            },
+        });
+    }
+
+    serde_json::from_str(&result.body)
+        .map_err(|source| ParseRestResponseError::ResponseBodyDeserialization { source })
+}
+
+/// Failure of a GitLab or Gitea call.
+#[derive(Debug, Error)]
+pub enum RestForgeError {
+    #[error("GetRest: {source}")]
+    GetRest {
+        source: GetRestError,
+    },
+
+    #[error("ParseResponse: {source}")]
+    ParseResponse {
+        source: ParseRestResponseError,
+    },
+
+    #[error("InvalidCursor: {value}")]
+    InvalidCursor {
+        value: String,
+    },
+}
+
+impl ForgeApiError for RestForgeError {
+    fn kind(&self) -> ForgeErrorKind {
+        let RestForgeError::ParseResponse { source } = self else {
+            return ForgeErrorKind::Other;
+        };
+        match source {
+            ParseRestResponseError::NotFound { .. } => ForgeErrorKind::NotFound,
+            ParseRestResponseError::Unauthorized { .. } => ForgeErrorKind::Unauthorized,
+            ParseRestResponseError::Forbidden { .. } => ForgeErrorKind::Forbidden,
+            ParseRestResponseError::RateLimited { .. } => ForgeErrorKind::RateLimited,
+            ParseRestResponseError::UnexpectedStatus { status, .. }
+                if *status == StatusCode::BAD_GATEWAY || *status == StatusCode::GATEWAY_TIMEOUT => ForgeErrorKind::Overloaded,
+            _ => ForgeErrorKind::Other,
+        }
+    }
+}
+
+/// GETs and parses `url`, `None` when the forge answers 404.
+pub(crate) async fn get_optional<T: DeserializeOwned>(
+    http: &Client,
+    url: &Url,
+    auth: Option<(&str, &str)>,
+    retry: &RetryConfig,
+) -> Result<Option<(T, RestResult)>, RestForgeError> {
+    let result = get_rest(http, url, auth, retry)
+        .await
+        .map_err(|source| RestForgeError::GetRest { source })?;
+
+    match parse_rest_response(&result) {
+        Ok(data) => Ok(Some((data, result))),
+        Err(ParseRestResponseError::NotFound { .. }) => Ok(None),
+        Err(source) => Err(RestForgeError::ParseResponse { source }),
+    }
+}
@@ -0,0 +1,37 @@
+//! Retry policy for transient GitHub failures
+//!
+//! Delays grow exponentially from `base_delay` up to `max_delay`, and each one
+//! is jittered so concurrent syncs don't hammer GitHub in lockstep.
+
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Total attempts including the first one, `1` disables retries.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Delay before retry number `retry` (starting at 0), picked in `[delay / 2, delay]`.
+    pub fn backoff_delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let half = delay / 2;
+
+        half + half.mul_f64(fastrand::f64())
+    }
+}
@@ -0,0 +1,65 @@
+//! GitHub GraphQL API client for a repository's star count
+//!
+//! A single cheap query, used to check stored stars against GitHub without
+//! paginating through the stargazers.
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::GitHubClient;
+use crate::graphql::{
+    parse_graphql_response, post_graphql, GitHubGraphQLResult, ParseGraphQLResponseError,
+    PostGraphQLError,
+};
+
+pub async fn fetch_repo_stargazer_count(
+    client: &GitHubClient,
+    owner: &str,
+    name: &str,
+) -> Result<GitHubGraphQLResult, FetchRepoStargazerCountError> {
+    let graphql_query = r#"
+        query getRepoStargazerCount($owner: String!, $name: String!) {
+            repository(owner: $owner, name: $name) {
+                stargazerCount
+            }
+        }
+    "#;
+
+    let payload = serde_json::json!({
+        "query": graphql_query,
+        "variables": {
+            "owner": owner,
+            "name": name,
+        }
+    });
+
+    post_graphql(client, &payload)
+        .await
+        .map_err(|source| FetchRepoStargazerCountError::PostGraphQL { source })
+}
+
+#[derive(Debug, Error)]
+pub enum FetchRepoStargazerCountError {
+    #[error("PostGraphQL: {source}")]
+    PostGraphQL {
+        source: PostGraphQLError,
+    },
+}
+
+/// Parses a star count answer, see [`parse_graphql_response`].
+pub fn parse_repo_stargazer_count_response(
+    result: &GitHubGraphQLResult,
+) -> Result<RepositoryStargazerCountData, ParseGraphQLResponseError> {
+    parse_graphql_response(result)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryStargazerCountData {
+	pub repository: Option<RepositoryStargazerCount>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryStargazerCount {
+	#[serde(rename = "stargazerCount")]
+	pub stargazer_count: u32,
+}
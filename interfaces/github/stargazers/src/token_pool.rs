@@ -0,0 +1,139 @@
+//! Pool of GitHub tokens rotated by remaining rate-limit quota
+//!
+//! Every answer's `x-ratelimit-*` headers update the state of the token that
+//! was used, and each request picks the token with the most quota left. Ties
+//! are broken round-robin so fresh tokens share the load.
+
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+
+/// Hourly GraphQL quota assumed for a token GitHub hasn't reported on yet.
+const DEFAULT_QUOTA: u32 = 5_000;
+
+#[derive(Debug, Clone, Copy)]
+struct RateLimit {
+    remaining: u32,
+    /// Unix timestamp at which `remaining` resets to the full quota.
+    reset_at: u64,
+}
+
+#[derive(Debug)]
+struct TokenState {
+    token: String,
+    rate_limit: Mutex<Option<RateLimit>>,
+}
+
+impl TokenState {
+    fn remaining(&self, now: u64) -> u32 {
+        match *self.rate_limit.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+            Some(limit) if limit.reset_at > now => limit.remaining,
+            _ => DEFAULT_QUOTA,
+        }
+    }
+}
+
+/// Token handed out by [`TokenPool::pick`], used to report rate-limit headers back.
+#[derive(Debug, Clone)]
+pub struct PickedToken {
+    index: usize,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TokenPool {
+    tokens: Arc<Vec<TokenState>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl TokenPool {
+    pub fn new<I: IntoIterator<Item = String>>(tokens: I) -> Self {
+        let tokens = tokens
+            .into_iter()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+            .map(|token| TokenState { token, rate_limit: Mutex::new(None) })
+            .collect();
+
+        Self { tokens: Arc::new(tokens), next: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    /// Reads `GITHUB_TOKENS` (comma-separated), falling back to `GITHUB_TOKEN`.
+    pub fn from_env() -> Self {
+        let tokens = std::env::var("GITHUB_TOKENS")
+            .or_else(|_| std::env::var("GITHUB_TOKEN"))
+            .unwrap_or_default();
+
+        Self::new(tokens.split(',').map(str::to_string))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Token with the most remaining quota, `None` when the pool is empty.
+    pub fn pick(&self) -> Option<PickedToken> {
+        let len = self.tokens.len();
+        if len == 0 {
+            return None;
+        }
+
+        let now = unix_now();
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % len;
+
+        // First token in rotation order among those with the most quota.
+        let index = (0..len)
+            .map(|offset| (start + offset) % len)
+            .min_by_key(|&index| Reverse(self.tokens[index].remaining(now)))?;
+
+        Some(PickedToken { index, token: self.tokens[index].token.clone() })
+    }
+
+    /// Whether a token other than `picked` still has quota left.
+    pub fn has_other_available(&self, picked: &PickedToken) -> bool {
+        let now = unix_now();
+
+        self.tokens
+            .iter()
+            .enumerate()
+            .any(|(index, state)| index != picked.index && state.remaining(now) > 0)
+    }
+
+    /// Updates the state of `picked` from the rate-limit headers of its answer.
+    pub fn record(&self, picked: &PickedToken, headers: &HeaderMap) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+
+        let (Some(remaining), Some(reset_at)) = (header("x-ratelimit-remaining"), header("x-ratelimit-reset")) else {
+            return;
+        };
+
+        let limit = RateLimit { remaining: remaining.min(u64::from(u32::MAX)) as u32, reset_at };
+        *self.tokens[picked.index]
+            .rate_limit
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(limit);
+    }
+
+    /// Remaining quota of `picked` as last reported by GitHub.
+    pub fn remaining(&self, picked: &PickedToken) -> u32 {
+        self.tokens[picked.index].remaining(unix_now())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
@@ -0,0 +1,131 @@
+//! GitHub REST API client for the traffic of a repository
+//!
+//! GitHub counts the views of a repository's pages and its clones, per day
+//! over the last 14 days, and only shows them to those with push access to
+//! the repository: other tokens are answered `403 Forbidden`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::client::{CredentialError, GitHubClient};
+use crate::forge::{ForgeApiError, ForgeErrorKind};
+use crate::rest::{get_optional, RestForgeError};
+
+/// Days of traffic GitHub keeps.
+pub const TRAFFIC_DAYS: u32 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrafficKind {
+    /// Views of the repository's pages.
+    Views,
+    Clones,
+}
+
+impl TrafficKind {
+    pub const ALL: [TrafficKind; 2] = [TrafficKind::Views, TrafficKind::Clones];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrafficKind::Views => "views",
+            TrafficKind::Clones => "clones",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "views" => Some(TrafficKind::Views),
+            "clones" => Some(TrafficKind::Clones),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for TrafficKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Traffic of one day, UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrafficDay {
+    pub day: NaiveDate,
+    pub count: u32,
+    /// Distinct visitors, or cloners.
+    pub uniques: u32,
+}
+
+#[derive(Debug, Error)]
+pub enum FetchRepositoryTrafficError {
+    #[error("Credential: {source}")]
+    Credential {
+        source: CredentialError,
+    },
+
+    #[error("InvalidUrl: {source}")]
+    InvalidUrl {
+        source: url::ParseError,
+    },
+
+    #[error("GetTraffic: {source}")]
+    GetTraffic {
+        source: RestForgeError,
+    },
+}
+
+impl FetchRepositoryTrafficError {
+    /// Whether GitHub refused the traffic to a token without push access to the repository.
+    pub fn lacks_push_access(&self) -> bool {
+        matches!(self, FetchRepositoryTrafficError::GetTraffic { source } if source.kind() == ForgeErrorKind::Forbidden)
+    }
+}
+
+/// Daily `kind` traffic of `owner/name` over the last [`TRAFFIC_DAYS`], oldest first, the current
+/// day still counting. `None` when GitHub doesn't know the repository.
+pub async fn fetch_repository_traffic(
+    client: &GitHubClient,
+    owner: &str,
+    name: &str,
+    kind: TrafficKind,
+) -> Result<Option<Vec<TrafficDay>>, FetchRepositoryTrafficError> {
+    let mut url = Url::parse(&client.api_url().rest("repos"))
+        .map_err(|source| FetchRepositoryTrafficError::InvalidUrl { source })?;
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.extend([owner, name, "traffic", kind.as_str()]);
+    }
+    url.query_pairs_mut().append_pair("per", "day");
+
+    let credential = client
+        .credential()
+        .await
+        .map_err(|source| FetchRepositoryTrafficError::Credential { source })?;
+    let authorization = format!("Bearer {}", credential.token());
+
+    let answer = get_optional::<RestTraffic>(client.http(), &url, Some(("Authorization", &authorization)), client.retry())
+        .await
+        .map_err(|source| FetchRepositoryTrafficError::GetTraffic { source })?;
+
+    Ok(answer.map(|(traffic, result)| {
+        client.record_rate_limit(&credential, &result.headers);
+        traffic.days
+            .into_iter()
+            .map(|day| TrafficDay { day: day.timestamp.date_naive(), count: day.count, uniques: day.uniques })
+            .collect()
+    }))
+}
+
+/// Answer of both endpoints, the days being under `views` or `clones`.
+#[derive(Debug, Deserialize)]
+struct RestTraffic {
+    #[serde(alias = "views", alias = "clones")]
+    days: Vec<RestTrafficDay>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestTrafficDay {
+    timestamp: DateTime<Utc>,
+    count: u32,
+    uniques: u32,
+}
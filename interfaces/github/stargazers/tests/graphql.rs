@@ -0,0 +1,106 @@
+//! GitHub answers to a stargazers page, classified into typed errors
+//!
+//! Canned bodies handed to the parser, no network needed.
+
+use interfaces_github_stargazers::graphql::GitHubGraphQLResult;
+use interfaces_github_stargazers::index::parse_repo_stargazers_response;
+use reqwest::StatusCode;
+use serde_json::json;
+
+fn answer(status: StatusCode, body: &str) -> GitHubGraphQLResult {
+    GitHubGraphQLResult { body: body.into(), status, retries: 0 }
+}
+
+#[test]
+fn pages_are_parsed_with_their_stargazers_and_cursor() -> Result<(), Box<dyn std::error::Error>> {
+    let body = json!({ "data": { "repository": {
+        "id": "R_kgDOA",
+        "name": "rocket",
+        "owner": { "login": "acme" },
+        "description": null,
+        "createdAt": "2020-01-01T00:00:00Z",
+        "stargazerCount": 2,
+        "primaryLanguage": { "name": "Rust" },
+        "licenseInfo": null,
+        "repositoryTopics": { "nodes": [{ "topic": { "name": "cli" } }] },
+        "stargazers": {
+            "edges": [
+                { "starredAt": "2025-03-01T12:00:00Z", "node": { "login": "ada", "email": "" } },
+                { "starredAt": "2025-03-02T12:00:00Z", "node": { "login": "grace", "email": null, "company": "Navy" } },
+            ],
+            "pageInfo": { "hasNextPage": true, "endCursor": "Y3Vyc29yOjI=" },
+        },
+    } } });
+
+    let repository = parse_repo_stargazers_response(&answer(StatusCode::OK, &body.to_string()))?
+        .repository
+        .ok_or("no repository")?;
+    assert_eq!((repository.owner.login.as_str(), repository.name.as_str(), repository.stargazer_count), ("acme", "rocket", 2));
+    assert_eq!(repository.primary_language.map(|language| language.name).as_deref(), Some("Rust"));
+    let logins: Vec<&str> = repository.stargazers.edges.iter().map(|edge| edge.node.login.as_str()).collect();
+    assert_eq!(logins, ["ada", "grace"]);
+    assert_eq!(repository.stargazers.edges[1].node.company.as_deref(), Some("Navy"));
+    assert!(repository.stargazers.page_info.has_next_page);
+    assert_eq!(repository.stargazers.page_info.end_cursor.as_deref(), Some("Y3Vyc29yOjI="));
+
+    // GitHub answers `null` for repositories it doesn't know, without an error.
+    let missing = parse_repo_stargazers_response(&answer(StatusCode::OK, r#"{ "data": { "repository": null } }"#))?;
+    assert!(missing.repository.is_none());
+    Ok(())
+}
+
+#[test]
+fn failures_are_classified_by_status_and_error_type() {
+    let errors = |error_type: &str, message: &str| {
+        json!({ "data": null, "errors": [{ "type": error_type, "message": message }] }).to_string()
+    };
+    let cases = [
+        (StatusCode::OK, errors("NOT_FOUND", "Could not resolve to a Repository"), "NotFound"),
+        (StatusCode::OK, errors("FORBIDDEN", "Resource protected by organization SAML enforcement"), "Forbidden"),
+        (StatusCode::OK, errors("INSUFFICIENT_SCOPES", "Your token has not been granted the required scopes"), "Forbidden"),
+        (StatusCode::OK, errors("RATE_LIMITED", "API rate limit exceeded"), "RateLimited"),
+        (StatusCode::OK, errors("MAX_NODE_LIMIT_EXCEEDED", "Too many nodes"), "GraphQL"),
+        (StatusCode::OK, r#"{ "data": null }"#.into(), "GraphQL"),
+        (StatusCode::OK, "<html>".into(), "ResponseBodyDeserialization"),
+        (StatusCode::UNAUTHORIZED, r#"{ "message": "Bad credentials" }"#.into(), "Unauthorized"),
+        (StatusCode::FORBIDDEN, r#"{ "message": "API rate limit exceeded for user" }"#.into(), "RateLimited"),
+        (StatusCode::FORBIDDEN, r#"{ "message": "Must have admin rights" }"#.into(), "Forbidden"),
+        (StatusCode::TOO_MANY_REQUESTS, "slow down".into(), "RateLimited"),
+        (StatusCode::BAD_GATEWAY, "<html>Bad gateway</html>".into(), "UnexpectedStatus"),
+    ];
+
+    for (status, body, variant) in cases {
+        let Err(error) = parse_repo_stargazers_response(&answer(status, &body)) else {
+            panic!("{status} {body} parsed");
+        };
+        assert!(error.to_string().starts_with(&format!("{variant}: ")), "{status} {body}: {error}");
+    }
+}
+
+#[test]
+fn the_first_error_is_reported_even_next_to_data() {
+    let body = json!({ "data": null, "errors": [
+        { "type": "FORBIDDEN", "message": "first" },
+        { "type": "NOT_FOUND", "message": "second" },
+    ] });
+    let error = parse_repo_stargazers_response(&answer(StatusCode::OK, &body.to_string())).err();
+    assert_eq!(error.map(|error| error.to_string()).as_deref(), Some("Forbidden: first"));
+
+    let body = json!({ "data": { "repository": null }, "errors": [{ "type": "NOT_FOUND", "message": "gone" }] });
+    assert!(parse_repo_stargazers_response(&answer(StatusCode::OK, &body.to_string())).is_err());
+}
+
+#[test]
+fn secondary_rate_limits_are_told_apart() {
+    let secondary = r#"{ "message": "You have exceeded a secondary rate limit. Please wait a few minutes." }"#;
+    let Err(error) = parse_repo_stargazers_response(&answer(StatusCode::FORBIDDEN, secondary)) else {
+        panic!("secondary rate limit parsed");
+    };
+    assert!(error.is_secondary_rate_limit(), "{error}");
+
+    let primary = r#"{ "message": "API rate limit exceeded for user ID 1." }"#;
+    let Err(error) = parse_repo_stargazers_response(&answer(StatusCode::FORBIDDEN, primary)) else {
+        panic!("rate limit parsed");
+    };
+    assert!(!error.is_secondary_rate_limit(), "{error}");
+}
@@ -0,0 +1,112 @@
+//! Transient GitHub failures retried with a jittered exponential backoff
+//!
+//! GraphQL answers scripted on a wiremock server.
+
+use std::time::Duration;
+
+use interfaces_github_stargazers::client::{GitHubAuth, GitHubClient, GitHubClientConfig};
+use interfaces_github_stargazers::graphql::{post_graphql, PostGraphQLError};
+use interfaces_github_stargazers::retry::RetryConfig;
+use interfaces_github_stargazers::token_pool::TokenPool;
+use reqwest::StatusCode;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+fn client(api_url: &str, max_attempts: u32) -> Result<GitHubClient, Box<dyn std::error::Error>> {
+    let config = GitHubClientConfig {
+        api_url: api_url.parse()?,
+        retry: RetryConfig { max_attempts, base_delay: Duration::ZERO, max_delay: Duration::ZERO },
+        ..GitHubClientConfig::default()
+    };
+    Ok(GitHubClient::new(&config, GitHubAuth::Tokens(TokenPool::new(["test-token".to_string()])))?)
+}
+
+async fn answer_once(github: &MockServer, status: u16) {
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .respond_with(ResponseTemplate::new(status))
+        .up_to_n_times(1)
+        .mount(github)
+        .await;
+}
+
+async fn answer_always(github: &MockServer, status: u16) {
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .respond_with(ResponseTemplate::new(status).set_body_json(json!({ "data": {} })))
+        .mount(github)
+        .await;
+}
+
+async fn requests(github: &MockServer) -> usize {
+    github.received_requests().await.map_or(0, |requests| requests.len())
+}
+
+#[test]
+fn delays_double_up_to_the_cap_and_are_jittered_down_to_half() {
+    let retry = RetryConfig { max_attempts: 5, base_delay: Duration::from_millis(100), max_delay: Duration::from_secs(1) };
+    let cases = [
+        (0, Duration::from_millis(100)),
+        (1, Duration::from_millis(200)),
+        (3, Duration::from_millis(800)),
+        (4, Duration::from_secs(1)),
+        (40, Duration::from_secs(1)),
+        (u32::MAX, Duration::from_secs(1)),
+    ];
+
+    for (attempt, delay) in cases {
+        for _ in 0..50 {
+            let backoff = retry.backoff_delay(attempt);
+            assert!(backoff >= delay / 2 && backoff <= delay, "retry {attempt}: {backoff:?} out of [{:?}, {delay:?}]", delay / 2);
+        }
+    }
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_until_github_answers() -> TestResult {
+    let github = MockServer::start().await;
+    answer_once(&github, 502).await;
+    answer_once(&github, 503).await;
+    answer_always(&github, 200).await;
+
+    let result = post_graphql(&client(github.uri().as_str(), 5)?, &json!({ "query": "{}" })).await?;
+    assert_eq!((result.status, result.retries), (StatusCode::OK, 2));
+    assert_eq!(requests(&github).await, 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn the_last_server_error_is_handed_back_once_attempts_run_out() -> TestResult {
+    let github = MockServer::start().await;
+    answer_always(&github, 500).await;
+
+    let result = post_graphql(&client(github.uri().as_str(), 3)?, &json!({ "query": "{}" })).await?;
+    assert_eq!((result.status, result.retries), (StatusCode::INTERNAL_SERVER_ERROR, 2));
+    assert_eq!(requests(&github).await, 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() -> TestResult {
+    for status in [400, 401, 404, 422] {
+        let github = MockServer::start().await;
+        answer_always(&github, status).await;
+
+        let result = post_graphql(&client(github.uri().as_str(), 5)?, &json!({ "query": "{}" })).await?;
+        assert_eq!((result.status.as_u16(), result.retries), (status, 0));
+        assert_eq!(requests(&github).await, 1, "{status}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn unreachable_servers_are_retried_then_reported() -> TestResult {
+    // Nothing listens on port 1.
+    let error = post_graphql(&client("http://127.0.0.1:1", 2)?, &json!({ "query": "{}" })).await.err().ok_or("GitHub answered")?;
+    assert!(matches!(error, PostGraphQLError::RequestSend { .. }), "{error}");
+    assert!(error.is_transient(), "{error}");
+    Ok(())
+}
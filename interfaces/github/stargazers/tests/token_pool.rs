@@ -0,0 +1,135 @@
+//! GitHub tokens picked by remaining quota, and switched once one runs out
+//!
+//! Rate-limit headers are canned, GraphQL answers come from a wiremock server.
+
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use interfaces_github_stargazers::client::{GitHubAuth, GitHubClient, GitHubClientConfig};
+use interfaces_github_stargazers::graphql::post_graphql;
+use interfaces_github_stargazers::retry::RetryConfig;
+use interfaces_github_stargazers::token_pool::{PickedToken, TokenPool};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::StatusCode;
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+fn pool(tokens: &[&str]) -> TokenPool {
+    TokenPool::new(tokens.iter().map(|token| token.to_string()))
+}
+
+fn rate_limit(remaining: u32, reset_in: TimeDelta) -> Result<HeaderMap, Box<dyn std::error::Error>> {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-ratelimit-remaining", HeaderValue::from_str(&remaining.to_string())?);
+    headers.insert("x-ratelimit-reset", HeaderValue::from_str(&(Utc::now() + reset_in).timestamp().to_string())?);
+    Ok(headers)
+}
+
+/// Every token of the pool, picked once in turn while none reported its quota.
+fn pick_all(pool: &TokenPool) -> Vec<PickedToken> {
+    (0..pool.len()).filter_map(|_| pool.pick()).collect()
+}
+
+#[test]
+fn tokens_are_read_trimmed_and_without_blanks() {
+    let tokens = pool(&[" a ", "", "b", "  "]);
+    let picked: Vec<String> = pick_all(&tokens).into_iter().map(|picked| picked.token).collect();
+    assert_eq!(picked, ["a", "b"]);
+    assert!(pool(&[]).pick().is_none());
+    assert!(pool(&["", " "]).is_empty());
+}
+
+#[test]
+fn fresh_tokens_are_picked_in_turn() {
+    let tokens = pool(&["a", "b", "c"]);
+    let picked: Vec<String> = (0..4).filter_map(|_| tokens.pick()).map(|picked| picked.token).collect();
+    assert_eq!(picked, ["a", "b", "c", "a"]);
+}
+
+#[test]
+fn the_token_with_the_most_quota_left_is_picked() -> TestResult {
+    let tokens = pool(&["a", "b", "c"]);
+    let [a, b, c] = <[PickedToken; 3]>::try_from(pick_all(&tokens)).map_err(|_| "not three tokens")?;
+    tokens.record(&a, &rate_limit(10, TimeDelta::hours(1))?);
+    tokens.record(&b, &rate_limit(4_000, TimeDelta::hours(1))?);
+    tokens.record(&c, &rate_limit(200, TimeDelta::hours(1))?);
+
+    for _ in 0..3 {
+        assert_eq!(tokens.pick().ok_or("empty pool")?.token, "b");
+    }
+
+    // Once GitHub reset the quota of `a`, it is assumed full again.
+    tokens.record(&a, &rate_limit(0, -TimeDelta::seconds(1))?);
+    assert_eq!(tokens.remaining(&a), 5_000);
+    assert_eq!(tokens.pick().ok_or("empty pool")?.token, "a");
+    Ok(())
+}
+
+#[test]
+fn answers_without_rate_limit_headers_change_nothing() -> TestResult {
+    let tokens = pool(&["a"]);
+    let a = tokens.pick().ok_or("empty pool")?;
+    tokens.record(&a, &rate_limit(12, TimeDelta::hours(1))?);
+
+    let mut partial = HeaderMap::new();
+    partial.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+    tokens.record(&a, &partial);
+    tokens.record(&a, &HeaderMap::new());
+    assert_eq!(tokens.remaining(&a), 12);
+    Ok(())
+}
+
+#[test]
+fn other_tokens_are_only_available_with_quota_left() -> TestResult {
+    let tokens = pool(&["a", "b"]);
+    let [a, b] = <[PickedToken; 2]>::try_from(pick_all(&tokens)).map_err(|_| "not two tokens")?;
+    assert!(tokens.has_other_available(&a));
+
+    tokens.record(&b, &rate_limit(0, TimeDelta::hours(1))?);
+    assert!(!tokens.has_other_available(&a));
+    assert!(tokens.has_other_available(&b));
+
+    let alone = pool(&["a"]);
+    assert!(!alone.has_other_available(&alone.pick().ok_or("empty pool")?));
+    Ok(())
+}
+
+#[tokio::test]
+async fn exhausted_tokens_are_switched_without_waiting() -> TestResult {
+    let github = MockServer::start().await;
+    let reset = (Utc::now() + TimeDelta::hours(1)).timestamp().to_string();
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(header("Authorization", "Bearer spent"))
+        .respond_with(ResponseTemplate::new(403)
+            .insert_header("x-ratelimit-remaining", "0")
+            .insert_header("x-ratelimit-reset", reset.as_str())
+            .set_body_json(json!({ "message": "API rate limit exceeded" })))
+        .mount(&github)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(header("Authorization", "Bearer fresh"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": {} })))
+        .mount(&github)
+        .await;
+
+    let config = GitHubClientConfig {
+        api_url: github.uri().parse()?,
+        // Delays far longer than the test, a switch doesn't wait.
+        retry: RetryConfig { max_attempts: 3, base_delay: Duration::from_secs(60), max_delay: Duration::from_secs(60) },
+        ..GitHubClientConfig::default()
+    };
+    let client = GitHubClient::new(&config, GitHubAuth::Tokens(pool(&["spent", "fresh"])))?;
+
+    let result = tokio::time::timeout(Duration::from_secs(10), post_graphql(&client, &json!({ "query": "{}" }))).await??;
+    assert_eq!((result.status, result.retries), (StatusCode::OK, 1));
+
+    // Later requests go straight to the token with quota left.
+    let result = post_graphql(&client, &json!({ "query": "{}" })).await?;
+    assert_eq!((result.status, result.retries), (StatusCode::OK, 0));
+    Ok(())
+}
@@ -0,0 +1,230 @@
+// Dashboard of the star tracker, calling the service's own API.
+//
+// The API key, when the deployment needs one, is kept in the browser's local
+// storage and sent as `X-API-Key`.
+
+"use strict";
+
+const API_KEY_STORAGE = "star-tracker-api-key";
+const SVG_NS = "http://www.w3.org/2000/svg";
+
+const state = {
+    selected: null,
+    series: [],
+};
+
+const $ = (id) => document.getElementById(id);
+
+function apiKey() {
+    return localStorage.getItem(API_KEY_STORAGE) || "";
+}
+
+function headers(extra = {}) {
+    const key = apiKey();
+    return key ? { ...extra, "X-API-Key": key } : extra;
+}
+
+function showStatus(message, isError = false) {
+    const status = $("status");
+    status.textContent = message;
+    status.classList.toggle("error", isError);
+}
+
+/** Message of an error answer of the API, see `ApiError`. */
+async function errorMessage(response) {
+    try {
+        const body = await response.json();
+        return body.message || body.error?.message || response.statusText;
+    } catch {
+        return response.statusText;
+    }
+}
+
+async function api(method, path, body) {
+    const init = { method, headers: headers(body ? { "Content-Type": "application/json" } : {}) };
+    if (body) {
+        init.body = JSON.stringify(body);
+    }
+    const response = await fetch(path, init);
+    if (!response.ok) {
+        throw new Error(`${response.status}: ${await errorMessage(response)}`);
+    }
+    return response;
+}
+
+async function loadRepositories() {
+    const repositories = await (await api("GET", "/github/repositories")).json();
+    const rows = $("repositories");
+    rows.replaceChildren();
+
+    for (const repo of repositories) {
+        const row = document.createElement("tr");
+        const fullName = `${repo.owner}/${repo.name}`;
+        row.classList.toggle("selected", state.selected === fullName);
+
+        const name = document.createElement("td");
+        name.textContent = fullName;
+        const forge = document.createElement("td");
+        forge.textContent = repo.forge;
+        const stars = document.createElement("td");
+        stars.className = "number";
+        stars.textContent = repo.total_stars.toLocaleString();
+        const tags = document.createElement("td");
+        for (const tag of repo.tags) {
+            const chip = document.createElement("span");
+            chip.className = "tag";
+            chip.textContent = tag;
+            tags.append(chip);
+        }
+        const actions = document.createElement("td");
+        const sync = document.createElement("button");
+        sync.textContent = "Sync";
+        sync.addEventListener("click", (event) => {
+            event.stopPropagation();
+            syncRepository(repo.forge, repo.owner, repo.name);
+        });
+        actions.append(sync);
+
+        row.append(name, forge, stars, tags, actions);
+        row.addEventListener("click", () => selectRepository(repo.owner, repo.name));
+        rows.append(row);
+    }
+    if (repositories.length === 0) {
+        showStatus("No repository tracked yet, sync one to start.");
+    }
+}
+
+/** Syncs with `stream`, reporting each page as the NDJSON lines come in. */
+async function syncRepository(forge, owner, name) {
+    const buttons = document.querySelectorAll("button");
+    buttons.forEach((button) => (button.disabled = true));
+    showStatus(`Syncing ${owner}/${name}…`);
+
+    try {
+        const response = await api("POST", "/github/repo_stars/update", { forge, owner, name, stream: true });
+        const reader = response.body.pipeThrough(new TextDecoderStream()).getReader();
+        let buffered = "";
+        let fetched = 0;
+        for (;;) {
+            const { value, done } = await reader.read();
+            if (done) {
+                break;
+            }
+            buffered += value;
+            const lines = buffered.split("\n");
+            buffered = lines.pop();
+            for (const line of lines.filter((line) => line.trim())) {
+                const event = JSON.parse(line);
+                if (event.event === "page") {
+                    fetched += event.stargazers.length;
+                    showStatus(`Syncing ${owner}/${name}: page ${event.page}, ${fetched.toLocaleString()} of ${event.stargazer_count.toLocaleString()} stars`);
+                } else if (event.event === "completed") {
+                    showStatus(`Synced ${owner}/${name}: ${event.stars.toLocaleString()} stars over ${event.pages} pages`);
+                } else if (event.event === "failed") {
+                    showStatus(`Sync of ${owner}/${name} failed: ${event.error.message}`, true);
+                }
+            }
+        }
+        await loadRepositories();
+        await selectRepository(owner, name);
+    } catch (error) {
+        showStatus(`Sync of ${owner}/${name} failed: ${error.message}`, true);
+    } finally {
+        buttons.forEach((button) => (button.disabled = false));
+    }
+}
+
+async function selectRepository(owner, name) {
+    state.selected = `${owner}/${name}`;
+    document.querySelectorAll("#repositories tr").forEach((row) => {
+        row.classList.toggle("selected", row.firstChild.textContent === state.selected);
+    });
+
+    try {
+        const response = await api("POST", "/github/repo_stars/read_per_day", { owner, name });
+        state.series = await response.json();
+        $("chart-title").textContent = state.selected;
+        $("chart-panel").hidden = false;
+        drawChart();
+    } catch (error) {
+        showStatus(`Could not load ${state.selected}: ${error.message}`, true);
+    }
+}
+
+function chartMode() {
+    return document.querySelector("input[name=mode]:checked").value;
+}
+
+/** Line of the running total, or bars of the daily stars, over the days with stars. */
+function drawChart() {
+    const chart = $("chart");
+    chart.replaceChildren();
+    if (state.series.length === 0) {
+        chart.textContent = "No star stored yet.";
+        return;
+    }
+
+    const width = 800;
+    const height = 360;
+    const margin = { top: 16, right: 16, bottom: 32, left: 64 };
+    const days = state.series.map(([day]) => Date.parse(day));
+    let total = 0;
+    const values = chartMode() === "cumulative"
+        ? state.series.map(([, stars]) => (total += stars))
+        : state.series.map(([, stars]) => stars);
+
+    const first = days[0];
+    const last = Math.max(days[days.length - 1], first + 86400000);
+    const max = Math.max(...values, 1);
+    const x = (day) => margin.left + ((day - first) / (last - first)) * (width - margin.left - margin.right);
+    const y = (value) => height - margin.bottom - (value / max) * (height - margin.top - margin.bottom);
+
+    const svg = document.createElementNS(SVG_NS, "svg");
+    svg.setAttribute("viewBox", `0 0 ${width} ${height}`);
+    const element = (tag, attributes, text) => {
+        const node = document.createElementNS(SVG_NS, tag);
+        for (const [attribute, value] of Object.entries(attributes)) {
+            node.setAttribute(attribute, value);
+        }
+        if (text !== undefined) {
+            node.textContent = text;
+        }
+        svg.append(node);
+        return node;
+    };
+
+    element("line", { class: "axis", x1: margin.left, x2: width - margin.right, y1: y(0), y2: y(0) });
+    element("line", { class: "axis", x1: margin.left, x2: margin.left, y1: margin.top, y2: y(0) });
+    for (const fraction of [0, 0.5, 1]) {
+        const value = Math.round(max * fraction);
+        element("text", { x: margin.left - 8, y: y(value) + 4, "text-anchor": "end" }, value.toLocaleString());
+    }
+    for (const day of [first, last]) {
+        element("text", { x: x(day), y: height - 8, "text-anchor": day === first ? "start" : "end" }, new Date(day).toISOString().slice(0, 10));
+    }
+
+    if (chartMode() === "cumulative") {
+        const points = days.map((day, i) => `${x(day).toFixed(1)},${y(values[i]).toFixed(1)}`);
+        element("polyline", { class: "line", points: points.join(" ") });
+    } else {
+        const barWidth = Math.max(1, (width - margin.left - margin.right) / ((last - first) / 86400000 + 1) - 1);
+        days.forEach((day, i) => {
+            element("rect", { class: "bar", x: x(day), y: y(values[i]), width: barWidth, height: y(0) - y(values[i]) });
+        });
+    }
+    chart.append(svg);
+}
+
+$("api-key").value = apiKey();
+$("api-key-form").addEventListener("submit", (event) => {
+    event.preventDefault();
+    localStorage.setItem(API_KEY_STORAGE, $("api-key").value.trim());
+    loadRepositories().catch((error) => showStatus(error.message, true));
+});
+$("sync-form").addEventListener("submit", (event) => {
+    event.preventDefault();
+    syncRepository($("sync-forge").value, $("sync-owner").value.trim(), $("sync-name").value.trim());
+});
+document.querySelectorAll("input[name=mode]").forEach((input) => input.addEventListener("change", drawChart));
+
+loadRepositories().catch((error) => showStatus(`Could not list repositories: ${error.message}`, true));
@@ -0,0 +1,45 @@
+//! Prints every sync job ending announced on `SYNC_NOTIFY_CHANNEL`
+//!
+//! Connects to `DATABASE_URL`, the database of the service, listens on the
+//! channel and waits for notifications, without querying anything:
+//!
+//! ```sh
+//! SYNC_NOTIFY_CHANNEL=star_syncs cargo run --example sync_listener
+//! ```
+
+use std::time::Duration;
+
+use diesel::{sql_query, Connection, PgConnection, RunQueryDsl};
+use projects_databases::env::sync_notify_channel_from_env;
+use projects_databases::live::SyncNotification;
+
+/// Wait between two reads of the notifications received by the connection.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    let channel = sync_notify_channel_from_env()?.ok_or("SYNC_NOTIFY_CHANNEL is not set")?;
+    let mut conn = PgConnection::establish(&std::env::var("DATABASE_URL")?)?;
+
+    // The channel is checked to be a plain identifier, it needs no quoting.
+    sql_query(format!("LISTEN {channel}")).execute(&mut conn)?;
+    println!("Listening on {channel}");
+
+    loop {
+        for notification in conn.notifications_iter() {
+            let notification = notification?;
+            match serde_json::from_str::<SyncNotification>(&notification.payload) {
+                Ok(sync) => println!(
+                    "{}/{} {}: job {}, {} stars added",
+                    sync.owner,
+                    sync.name,
+                    sync.status,
+                    sync.job_id,
+                    sync.stars_added.map_or("unknown".to_string(), |stars| stars.to_string()),
+                ),
+                Err(error) => eprintln!("Unexpected payload {:?}: {error}", notification.payload),
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,13 @@
+CREATE TABLE sync_jobs (
+    id UUID PRIMARY KEY,
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    end_cursor TEXT,
+    pages_fetched INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_sync_jobs_repository_id ON sync_jobs (repository_id);
+SELECT diesel_manage_updated_at('sync_jobs');
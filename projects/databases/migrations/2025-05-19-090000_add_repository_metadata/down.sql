@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE repositories
+    DROP COLUMN IF EXISTS description,
+    DROP COLUMN IF EXISTS primary_language,
+    DROP COLUMN IF EXISTS license,
+    DROP COLUMN IF EXISTS topics,
+    DROP COLUMN IF EXISTS github_created_at;
@@ -0,0 +1,6 @@
+ALTER TABLE repositories
+    ADD COLUMN description TEXT,
+    ADD COLUMN primary_language TEXT,
+    ADD COLUMN license TEXT,
+    ADD COLUMN topics TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN github_created_at TIMESTAMP;
@@ -0,0 +1,9 @@
+CREATE TABLE sync_batches (
+    id UUID PRIMARY KEY,
+    owner TEXT NOT NULL,
+    total_repositories INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE sync_jobs ADD COLUMN batch_id UUID REFERENCES sync_batches(id) ON DELETE SET NULL;
+CREATE INDEX idx_sync_jobs_batch_id ON sync_jobs (batch_id);
@@ -0,0 +1,7 @@
+CREATE TABLE milestones (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    stars INTEGER NOT NULL,
+    reached_on DATE NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repository_id, stars)
+);
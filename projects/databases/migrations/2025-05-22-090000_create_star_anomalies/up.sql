@@ -0,0 +1,8 @@
+CREATE TABLE star_anomalies (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    stars INTEGER NOT NULL,
+    trailing_average DOUBLE PRECISION NOT NULL,
+    detected_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repository_id, day)
+);
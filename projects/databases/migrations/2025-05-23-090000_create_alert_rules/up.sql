@@ -0,0 +1,12 @@
+CREATE TABLE alert_rules (
+    id UUID PRIMARY KEY,
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    threshold INTEGER NOT NULL,
+    target TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    last_triggered_on DATE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_alert_rules_repository_id ON alert_rules(repository_id);
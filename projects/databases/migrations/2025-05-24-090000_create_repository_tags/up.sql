@@ -0,0 +1,8 @@
+CREATE TABLE repository_tags (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repository_id, tag)
+);
+
+CREATE INDEX idx_repository_tags_tag ON repository_tags(tag);
@@ -0,0 +1,10 @@
+CREATE TABLE star_verifications (
+    id UUID PRIMARY KEY,
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    github_stars INTEGER NOT NULL,
+    stored_stars INTEGER NOT NULL,
+    repair_queued BOOLEAN NOT NULL DEFAULT FALSE,
+    checked_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_star_verifications_repository_checked_at ON star_verifications(repository_id, checked_at DESC);
@@ -0,0 +1,14 @@
+CREATE TABLE sync_runs (
+    id UUID PRIMARY KEY,
+    job_id UUID NOT NULL REFERENCES sync_jobs(id) ON DELETE CASCADE,
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    trigger TEXT NOT NULL,
+    status TEXT NOT NULL,
+    pages_fetched INTEGER NOT NULL DEFAULT 0,
+    stars_added INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMP
+);
+
+CREATE INDEX idx_sync_runs_repository_started_at ON sync_runs(repository_id, started_at DESC);
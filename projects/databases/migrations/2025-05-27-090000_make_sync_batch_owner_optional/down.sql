@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DELETE FROM sync_batches WHERE owner IS NULL;
+ALTER TABLE sync_batches ALTER COLUMN owner SET NOT NULL;
@@ -0,0 +1 @@
+ALTER TABLE sync_batches ALTER COLUMN owner DROP NOT NULL;
@@ -0,0 +1,6 @@
+CREATE TABLE stargazer_profiles (
+    login TEXT PRIMARY KEY,
+    company TEXT,
+    location TEXT,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE sync_jobs
+    DROP COLUMN IF EXISTS stars_fetched,
+    DROP COLUMN IF EXISTS estimated_total_stars;
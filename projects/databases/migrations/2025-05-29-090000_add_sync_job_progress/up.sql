@@ -0,0 +1,3 @@
+ALTER TABLE sync_jobs
+    ADD COLUMN stars_fetched INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN estimated_total_stars INTEGER;
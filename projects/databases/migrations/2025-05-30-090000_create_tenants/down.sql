@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE sync_batches DROP COLUMN tenant_id;
+
+DELETE FROM repositories WHERE tenant_id <> '00000000-0000-0000-0000-000000000000';
+ALTER TABLE repositories DROP CONSTRAINT repositories_tenant_id_owner_name_key;
+ALTER TABLE repositories ADD CONSTRAINT repositories_owner_name_key UNIQUE (owner, name);
+ALTER TABLE repositories DROP COLUMN tenant_id;
+
+DROP TABLE api_keys;
+DROP TABLE tenants;
@@ -0,0 +1,24 @@
+CREATE TABLE tenants (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Owns everything tracked before tenants existed, and anonymous requests.
+INSERT INTO tenants (id, name) VALUES ('00000000-0000-0000-0000-000000000000', 'default');
+
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    key_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_api_keys_tenant_id ON api_keys (tenant_id);
+
+ALTER TABLE repositories ADD COLUMN tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000' REFERENCES tenants(id) ON DELETE CASCADE;
+ALTER TABLE repositories ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE repositories DROP CONSTRAINT repositories_owner_name_key;
+ALTER TABLE repositories ADD CONSTRAINT repositories_tenant_id_owner_name_key UNIQUE (tenant_id, owner, name);
+
+ALTER TABLE sync_batches ADD COLUMN tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000' REFERENCES tenants(id) ON DELETE CASCADE;
+ALTER TABLE sync_batches ALTER COLUMN tenant_id DROP DEFAULT;
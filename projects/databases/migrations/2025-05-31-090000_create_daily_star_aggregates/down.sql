@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE repositories DROP COLUMN IF EXISTS compacted_before;
+DROP TABLE IF EXISTS daily_star_aggregates;
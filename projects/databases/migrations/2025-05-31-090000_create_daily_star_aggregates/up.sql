@@ -0,0 +1,10 @@
+-- Stars older than the retention period, rolled up per day once their rows are deleted
+CREATE TABLE daily_star_aggregates (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    stars INTEGER NOT NULL,
+    PRIMARY KEY (repository_id, day)
+);
+
+-- Stars starred before this day are only counted in daily_star_aggregates
+ALTER TABLE repositories ADD COLUMN compacted_before DATE;
@@ -0,0 +1,19 @@
+-- Stars per day of every repository, stored and compacted ones alike, refreshed after each write
+CREATE TABLE daily_star_counts (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    stars INTEGER NOT NULL,
+    PRIMARY KEY (repository_id, day)
+);
+
+INSERT INTO daily_star_counts (repository_id, day, stars)
+SELECT repository_id, day, SUM(stars)::INTEGER
+FROM (
+    SELECT repository_id, DATE(starred_at) AS day, COUNT(*) AS stars
+    FROM stars
+    GROUP BY repository_id, DATE(starred_at)
+    UNION ALL
+    SELECT repository_id, day, stars
+    FROM daily_star_aggregates
+) counts
+GROUP BY repository_id, day;
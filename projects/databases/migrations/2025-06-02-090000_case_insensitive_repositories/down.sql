@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`, merged repositories stay merged
+DROP INDEX IF EXISTS repositories_tenant_id_lower_owner_lower_name_key;
+ALTER TABLE repositories ADD CONSTRAINT repositories_tenant_id_owner_name_key UNIQUE (tenant_id, owner, name);
@@ -0,0 +1,74 @@
+-- GitHub owners and names are case-insensitive, repositories of a tenant differing only by case are merged.
+-- The one compacted the furthest is kept, its compacted days already count the others' stars.
+CREATE TEMPORARY TABLE repository_duplicates ON COMMIT DROP AS
+SELECT id, FIRST_VALUE(id) OVER (
+    PARTITION BY tenant_id, LOWER(owner), LOWER(name)
+    ORDER BY compacted_before DESC NULLS LAST, created_at, id
+) AS keeper_id
+FROM repositories;
+DELETE FROM repository_duplicates WHERE id = keeper_id;
+
+INSERT INTO stars (repository_id, stargazer, starred_at, fetched_at)
+SELECT duplicates.keeper_id, stars.stargazer, stars.starred_at, stars.fetched_at
+FROM stars
+JOIN repository_duplicates duplicates ON duplicates.id = stars.repository_id
+JOIN repositories keepers ON keepers.id = duplicates.keeper_id
+WHERE keepers.compacted_before IS NULL OR stars.starred_at >= keepers.compacted_before
+ON CONFLICT (repository_id, stargazer) DO NOTHING;
+
+-- Both count the stars of the same repository, the larger count saw more of them
+INSERT INTO daily_star_aggregates (repository_id, day, stars)
+SELECT duplicates.keeper_id, aggregates.day, MAX(aggregates.stars)
+FROM daily_star_aggregates aggregates
+JOIN repository_duplicates duplicates ON duplicates.id = aggregates.repository_id
+GROUP BY duplicates.keeper_id, aggregates.day
+ON CONFLICT (repository_id, day) DO UPDATE SET stars = GREATEST(daily_star_aggregates.stars, EXCLUDED.stars);
+
+INSERT INTO milestones (repository_id, stars, reached_on, recorded_at)
+SELECT duplicates.keeper_id, milestones.stars, MIN(milestones.reached_on), MIN(milestones.recorded_at)
+FROM milestones
+JOIN repository_duplicates duplicates ON duplicates.id = milestones.repository_id
+GROUP BY duplicates.keeper_id, milestones.stars
+ON CONFLICT (repository_id, stars) DO UPDATE SET reached_on = LEAST(milestones.reached_on, EXCLUDED.reached_on);
+
+INSERT INTO repository_tags (repository_id, tag, created_at)
+SELECT duplicates.keeper_id, tags.tag, tags.created_at
+FROM repository_tags tags
+JOIN repository_duplicates duplicates ON duplicates.id = tags.repository_id
+ON CONFLICT (repository_id, tag) DO NOTHING;
+
+INSERT INTO star_anomalies (repository_id, day, stars, trailing_average, detected_at)
+SELECT duplicates.keeper_id, anomalies.day, anomalies.stars, anomalies.trailing_average, anomalies.detected_at
+FROM star_anomalies anomalies
+JOIN repository_duplicates duplicates ON duplicates.id = anomalies.repository_id
+ON CONFLICT (repository_id, day) DO NOTHING;
+
+UPDATE alert_rules SET repository_id = duplicates.keeper_id
+FROM repository_duplicates duplicates WHERE duplicates.id = alert_rules.repository_id;
+UPDATE star_verifications SET repository_id = duplicates.keeper_id
+FROM repository_duplicates duplicates WHERE duplicates.id = star_verifications.repository_id;
+UPDATE sync_jobs SET repository_id = duplicates.keeper_id
+FROM repository_duplicates duplicates WHERE duplicates.id = sync_jobs.repository_id;
+UPDATE sync_runs SET repository_id = duplicates.keeper_id
+FROM repository_duplicates duplicates WHERE duplicates.id = sync_runs.repository_id;
+
+-- Whatever wasn't moved goes with them
+DELETE FROM repositories WHERE id IN (SELECT id FROM repository_duplicates);
+
+DELETE FROM daily_star_counts WHERE repository_id IN (SELECT keeper_id FROM repository_duplicates);
+INSERT INTO daily_star_counts (repository_id, day, stars)
+SELECT repository_id, day, SUM(stars)::INTEGER
+FROM (
+    SELECT repository_id, DATE(starred_at) AS day, COUNT(*) AS stars
+    FROM stars
+    WHERE repository_id IN (SELECT keeper_id FROM repository_duplicates)
+    GROUP BY repository_id, DATE(starred_at)
+    UNION ALL
+    SELECT repository_id, day, stars
+    FROM daily_star_aggregates
+    WHERE repository_id IN (SELECT keeper_id FROM repository_duplicates)
+) counts
+GROUP BY repository_id, day;
+
+ALTER TABLE repositories DROP CONSTRAINT repositories_tenant_id_owner_name_key;
+CREATE UNIQUE INDEX repositories_tenant_id_lower_owner_lower_name_key ON repositories (tenant_id, LOWER(owner), LOWER(name));
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS repository_aliases;
+DROP INDEX IF EXISTS repositories_tenant_id_github_node_id_key;
+ALTER TABLE repositories DROP COLUMN IF EXISTS github_node_id;
@@ -0,0 +1,15 @@
+-- GitHub's node ID of the repository, which survives renames and transfers
+ALTER TABLE repositories ADD COLUMN github_node_id TEXT;
+CREATE UNIQUE INDEX repositories_tenant_id_github_node_id_key ON repositories (tenant_id, github_node_id);
+
+-- Former names of renamed or transferred repositories, still resolved by the API
+CREATE TABLE repository_aliases (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    renamed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, owner, name)
+);
+CREATE UNIQUE INDEX repository_aliases_tenant_id_lower_owner_lower_name_key ON repository_aliases (tenant_id, LOWER(owner), LOWER(name));
+CREATE INDEX idx_repository_aliases_repository_id ON repository_aliases (repository_id);
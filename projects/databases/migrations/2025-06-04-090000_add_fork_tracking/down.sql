@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_repositories_fork_of;
+ALTER TABLE repositories DROP COLUMN IF EXISTS fork_of, DROP COLUMN IF EXISTS forks_min_stars;
@@ -0,0 +1,5 @@
+-- Fork tracking: forks of a repository with at least `forks_min_stars` stars on GitHub are tracked too
+ALTER TABLE repositories
+    ADD COLUMN forks_min_stars INTEGER,
+    ADD COLUMN fork_of UUID REFERENCES repositories(id) ON DELETE SET NULL;
+CREATE INDEX idx_repositories_fork_of ON repositories (fork_of);
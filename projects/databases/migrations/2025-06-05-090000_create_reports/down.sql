@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS reports;
+ALTER TABLE tenants DROP COLUMN IF EXISTS digest_recipients;
@@ -0,0 +1,16 @@
+-- Weekly digests: generated reports are kept, and emailed to the tenant's digest recipients
+ALTER TABLE tenants ADD COLUMN digest_recipients TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE TABLE reports (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    content JSONB NOT NULL,
+    html TEXT NOT NULL,
+    sent_to TEXT[] NOT NULL DEFAULT '{}',
+    sent_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, kind, period_start)
+);
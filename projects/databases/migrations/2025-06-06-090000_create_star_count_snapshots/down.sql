@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS star_count_snapshots;
+ALTER TABLE repositories DROP COLUMN IF EXISTS snapshot_mode;
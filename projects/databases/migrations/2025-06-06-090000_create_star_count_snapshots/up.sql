@@ -0,0 +1,9 @@
+-- Snapshot mode: repositories too large to enumerate get GitHub's stargazerCount recorded on a schedule instead
+ALTER TABLE repositories ADD COLUMN snapshot_mode BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE star_count_snapshots (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    taken_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    stargazer_count INTEGER NOT NULL,
+    PRIMARY KEY (repository_id, taken_at)
+);
@@ -0,0 +1,7 @@
+-- Lifecycle timestamps of sync jobs: first start, and end of the last run
+ALTER TABLE sync_jobs
+    ADD COLUMN started_at TIMESTAMP,
+    ADD COLUMN finished_at TIMESTAMP;
+
+UPDATE sync_jobs SET started_at = created_at;
+UPDATE sync_jobs SET finished_at = updated_at WHERE status <> 'running';
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DELETE FROM sync_runs WHERE job_id IS NULL;
+ALTER TABLE sync_runs
+    ALTER COLUMN job_id SET NOT NULL,
+    DROP CONSTRAINT sync_runs_job_id_fkey,
+    ADD CONSTRAINT sync_runs_job_id_fkey FOREIGN KEY (job_id) REFERENCES sync_jobs(id) ON DELETE CASCADE;
@@ -0,0 +1,5 @@
+-- Finished sync jobs are purged after a retention period, their runs stay in the sync history
+ALTER TABLE sync_runs
+    ALTER COLUMN job_id DROP NOT NULL,
+    DROP CONSTRAINT sync_runs_job_id_fkey,
+    ADD CONSTRAINT sync_runs_job_id_fkey FOREIGN KEY (job_id) REFERENCES sync_jobs(id) ON DELETE SET NULL;
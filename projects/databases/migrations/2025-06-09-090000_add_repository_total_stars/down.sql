@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE repositories
+    DROP COLUMN IF EXISTS total_stars;
@@ -0,0 +1,12 @@
+-- Current star count of each repository, kept in step with daily_star_counts so it is read without scanning stars
+ALTER TABLE repositories
+    ADD COLUMN total_stars BIGINT NOT NULL DEFAULT 0;
+
+UPDATE repositories
+SET total_stars = counts.stars
+FROM (
+    SELECT repository_id, SUM(stars) AS stars
+    FROM daily_star_counts
+    GROUP BY repository_id
+) AS counts
+WHERE counts.repository_id = repositories.id;
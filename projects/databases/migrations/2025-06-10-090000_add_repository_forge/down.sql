@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS repositories_tenant_id_forge_github_node_id_key;
+DELETE FROM repositories WHERE forge <> 'github';
+CREATE UNIQUE INDEX repositories_tenant_id_github_node_id_key ON repositories (tenant_id, github_node_id);
+
+ALTER TABLE repositories
+    DROP COLUMN IF EXISTS forge;
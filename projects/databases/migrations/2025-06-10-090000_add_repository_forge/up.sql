@@ -0,0 +1,8 @@
+-- Forge each repository is tracked on, github_node_id then holding the ID given by that forge
+ALTER TABLE repositories
+    ADD COLUMN forge TEXT NOT NULL DEFAULT 'github'
+    CONSTRAINT repositories_forge_check CHECK (forge IN ('github', 'gitlab', 'gitea'));
+
+-- IDs are only unique within a forge
+DROP INDEX repositories_tenant_id_github_node_id_key;
+CREATE UNIQUE INDEX repositories_tenant_id_forge_github_node_id_key ON repositories (tenant_id, forge, github_node_id);
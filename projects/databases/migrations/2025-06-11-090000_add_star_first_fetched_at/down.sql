@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE sync_runs
+    DROP COLUMN IF EXISTS fetched_at;
+
+ALTER TABLE stars
+    DROP COLUMN IF EXISTS first_fetched_at;
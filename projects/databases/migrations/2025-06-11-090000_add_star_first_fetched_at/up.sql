@@ -0,0 +1,7 @@
+-- When each star was first stored, and the fetched_at each sync run wrote to the stars it fetched,
+-- so two runs can be compared. Unset for stars and runs recorded before.
+ALTER TABLE stars
+    ADD COLUMN first_fetched_at TIMESTAMP;
+
+ALTER TABLE sync_runs
+    ADD COLUMN fetched_at TIMESTAMP;
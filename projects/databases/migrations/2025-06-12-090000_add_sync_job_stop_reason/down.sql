@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+UPDATE sync_jobs SET status = 'completed' WHERE status = 'partially_completed';
+UPDATE sync_runs SET status = 'completed' WHERE status = 'partially_completed';
+
+ALTER TABLE sync_jobs
+    DROP COLUMN IF EXISTS stop_reason;
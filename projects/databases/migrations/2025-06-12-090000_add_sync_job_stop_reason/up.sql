@@ -0,0 +1,4 @@
+-- Limit that stopped a partially_completed job, see sync_limits
+ALTER TABLE sync_jobs
+    ADD COLUMN stop_reason TEXT
+    CONSTRAINT sync_jobs_stop_reason_check CHECK (stop_reason IN ('max_pages', 'max_duration', 'max_stars'));
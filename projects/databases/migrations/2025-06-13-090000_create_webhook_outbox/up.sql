@@ -0,0 +1,18 @@
+-- Webhook alert deliveries, written along with the final status of the sync job that fired
+-- them, then sent by the outbox dispatcher of any instance until delivered or given up on
+CREATE TABLE webhook_outbox (
+    id UUID PRIMARY KEY,
+    alert_rule_id UUID REFERENCES alert_rules(id) ON DELETE SET NULL,
+    job_id UUID REFERENCES sync_jobs(id) ON DELETE SET NULL,
+    destination TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CONSTRAINT webhook_outbox_status_check CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhook_outbox_pending_next_attempt_at ON webhook_outbox(next_attempt_at) WHERE status = 'pending';
@@ -0,0 +1,7 @@
+-- Public emails of stargazers, only stored while COLLECT_STARGAZER_EMAILS is set. A stargazer
+-- hiding theirs is forgotten by the next sync seeing them
+CREATE TABLE stargazer_emails (
+    login TEXT PRIMARY KEY,
+    email TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
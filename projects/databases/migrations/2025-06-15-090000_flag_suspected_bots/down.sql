@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE daily_star_aggregates
+    DROP COLUMN IF EXISTS bot_stars;
+
+ALTER TABLE daily_star_counts
+    DROP COLUMN IF EXISTS bot_stars;
+
+ALTER TABLE stars
+    DROP COLUMN IF EXISTS suspected_bot;
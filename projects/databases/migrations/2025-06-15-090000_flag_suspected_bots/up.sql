@@ -0,0 +1,11 @@
+-- Stars of stargazers looking like bots or throwaway accounts, flagged by syncs while
+-- FLAG_SUSPECTED_BOTS is set, see bots.rs. Once flagged, a star stays flagged
+ALTER TABLE stars
+    ADD COLUMN suspected_bot BOOLEAN NOT NULL DEFAULT false;
+
+-- Of the day's stars, those flagged, so reads can leave them out
+ALTER TABLE daily_star_counts
+    ADD COLUMN bot_stars INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE daily_star_aggregates
+    ADD COLUMN bot_stars INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,13 @@
+CREATE TABLE releases (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    tag_name TEXT NOT NULL,
+    name TEXT,
+    kind TEXT NOT NULL CHECK (kind IN ('release', 'tag')),
+    prerelease BOOLEAN NOT NULL DEFAULT false,
+    url TEXT,
+    published_at TIMESTAMP NOT NULL,
+    fetched_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repository_id, tag_name)
+);
+
+CREATE INDEX releases_repository_published_at_idx ON releases (repository_id, published_at);
@@ -0,0 +1,9 @@
+CREATE TABLE traffic_snapshots (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('views', 'clones')),
+    day DATE NOT NULL,
+    count INTEGER NOT NULL,
+    uniques INTEGER NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repository_id, kind, day)
+);
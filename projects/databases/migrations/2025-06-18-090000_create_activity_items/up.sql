@@ -0,0 +1,12 @@
+CREATE TABLE activity_items (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('issues', 'prs')),
+    number INTEGER NOT NULL,
+    opened_at TIMESTAMP NOT NULL,
+    closed_at TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL,
+    fetched_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repository_id, kind, number)
+);
+
+CREATE INDEX activity_items_repository_updated_at_idx ON activity_items (repository_id, kind, updated_at);
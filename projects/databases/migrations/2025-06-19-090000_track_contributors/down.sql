@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS contributors;
+DROP TABLE IF EXISTS contributor_tracking;
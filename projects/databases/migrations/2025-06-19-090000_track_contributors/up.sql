@@ -0,0 +1,18 @@
+CREATE TABLE contributor_tracking (
+    repository_id UUID PRIMARY KEY REFERENCES repositories(id) ON DELETE CASCADE,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    newest_commit_at TIMESTAMP,
+    oldest_commit_at TIMESTAMP,
+    history_complete BOOLEAN NOT NULL DEFAULT false,
+    tracked_at TIMESTAMP
+);
+
+CREATE TABLE contributors (
+    repository_id UUID NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    login TEXT NOT NULL,
+    first_commit_at TIMESTAMP NOT NULL,
+    last_commit_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (repository_id, login)
+);
+
+CREATE INDEX contributors_repository_first_commit_at_idx ON contributors (repository_id, first_commit_at);
@@ -0,0 +1,3 @@
+-- Archived repositories are hidden from listings and charts and left out of scheduled jobs, their data kept
+ALTER TABLE repositories
+    ADD COLUMN archived_at TIMESTAMP;
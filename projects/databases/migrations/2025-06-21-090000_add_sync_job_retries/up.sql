@@ -0,0 +1,3 @@
+-- Transient forge failures retried by a sync job so far, reported with its progress
+ALTER TABLE sync_jobs
+    ADD COLUMN retries INTEGER NOT NULL DEFAULT 0;
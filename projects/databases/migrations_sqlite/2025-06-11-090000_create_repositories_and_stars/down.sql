@@ -0,0 +1,2 @@
+DROP TABLE stars;
+DROP TABLE repositories;
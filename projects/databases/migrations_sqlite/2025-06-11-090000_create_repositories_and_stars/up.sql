@@ -0,0 +1,20 @@
+CREATE TABLE repositories (
+    id TEXT PRIMARY KEY NOT NULL,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    forge TEXT NOT NULL DEFAULT 'github' CHECK (forge IN ('github', 'gitlab', 'gitea')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Owners and names are case-insensitive, as on GitHub
+CREATE UNIQUE INDEX repositories_owner_name ON repositories (lower(owner), lower(name));
+
+CREATE TABLE stars (
+    repository_id TEXT NOT NULL REFERENCES repositories (id) ON DELETE CASCADE,
+    stargazer TEXT NOT NULL,
+    starred_at TIMESTAMP NOT NULL,
+    fetched_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (repository_id, stargazer)
+);
+
+CREATE INDEX stars_repository_id_starred_at ON stars (repository_id, starred_at);
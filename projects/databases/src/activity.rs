@@ -0,0 +1,88 @@
+//! Issue and pull request activity of the tracked repositories
+//!
+//! Once `COLLECT_REPOSITORY_ACTIVITY` is `true`, every completed sync of a
+//! GitHub repository also fetches the issues and pull requests updated since
+//! the previous one, those of the last [`ACTIVITY_HISTORY_DAYS`] the first
+//! time, and stores when each was opened and closed. The `issues` and `prs`
+//! metrics of the comparison endpoints count them per day along with the
+//! stars, see [`crate::analytics::compare`].
+//!
+//! A failed fetch is logged and leaves the stored activity as it was, the next
+//! sync fetching the same updates again. Of more than 1000 issues, or pull
+//! requests, updated between two syncs only the latest are stored.
+
+use chrono::{Duration, NaiveDateTime};
+use diesel::PgConnection;
+use interfaces_github_stargazers::activity::{fetch_repository_activity, ActivityKind, FetchRepositoryActivityError};
+use interfaces_github_stargazers::client::GitHubClient;
+use thiserror::Error;
+use tracing::warn;
+
+use crate::db::{
+	    activity_item::{
+	        models::NewActivityItem,
+	        queries::{get_latest_activity_update, upsert_activity_items, GetLatestActivityUpdateError, UpsertActivityItemsError},
+	    },
+	    repository::models::Repository,
+	};
+
+/// Days of issues and pull requests fetched on a repository's first sync collecting them.
+pub const ACTIVITY_HISTORY_DAYS: i64 = 90;
+
+#[derive(Debug, Error)]
+pub enum StoreActivityError {
+    #[error("GetLatestActivityUpdate: {source}")]
+    GetLatestActivityUpdate {
+        #[from]
+        source: GetLatestActivityUpdateError,
+    },
+    #[error("FetchRepositoryActivity: {source}")]
+    FetchRepositoryActivity {
+        #[from]
+        source: FetchRepositoryActivityError,
+    },
+    #[error("UpsertActivityItems: {source}")]
+    UpsertActivityItems {
+        #[from]
+        source: UpsertActivityItemsError,
+    },
+}
+
+/// Fetches the issues and pull requests of `repo` updated since the last fetch and stores them,
+/// the number stored. 0 when GitHub no longer knows the repository.
+pub async fn store_activity(
+    conn: &mut PgConnection,
+    github: &GitHubClient,
+    repo: &Repository,
+    fetched_at: NaiveDateTime,
+) -> Result<usize, StoreActivityError> {
+    let mut stored = 0;
+    for kind in ActivityKind::ALL {
+        let since = get_latest_activity_update(conn, repo.id, kind.as_str())?
+            .unwrap_or(fetched_at - Duration::days(ACTIVITY_HISTORY_DAYS))
+            .and_utc();
+        let Some(activity) = fetch_repository_activity(github, &repo.owner, &repo.name, kind, since).await? else {
+            return Ok(stored);
+        };
+        if activity.truncated {
+            warn!(owner = %repo.owner, name = %repo.name, %kind, "too many updates since the last sync, only the latest stored");
+        }
+
+        let items: Vec<NewActivityItem> = activity.items
+            .iter()
+            .map(|item| NewActivityItem {
+                repository_id: repo.id,
+                kind:          kind.as_str(),
+                number:        i32::try_from(item.number).unwrap_or(i32::MAX),
+                opened_at:     item.created_at.naive_utc(),
+                closed_at:     item.closed_at.map(|closed_at| closed_at.naive_utc()),
+                updated_at:    item.updated_at.naive_utc(),
+                fetched_at,
+            })
+            .collect();
+        if !items.is_empty() {
+            stored += upsert_activity_items(conn, &items)?;
+        }
+    }
+    Ok(stored)
+}
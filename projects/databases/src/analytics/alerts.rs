@@ -0,0 +1,33 @@
+//! Alert rule conditions
+//!
+//! Each check only looks at days on or after `since`, so a rule fires for
+//! activity that happened after it was registered or last triggered.
+
+use chrono::NaiveDate;
+
+/// Latest day from `since` on with more than `threshold` stars.
+pub fn daily_stars_trigger(
+    daily: &[(NaiveDate, i64)],
+    threshold: i64,
+    since: NaiveDate,
+) -> Option<(NaiveDate, i64)> {
+    daily
+        .iter()
+        .rev()
+        .take_while(|(day, _)| *day >= since)
+        .find(|(_, stars)| *stars > threshold)
+        .copied()
+}
+
+/// Day the running total reached `threshold`, if that happened from `since` on.
+pub fn total_stars_trigger(
+    cumulative: &[(NaiveDate, i64)],
+    threshold: i64,
+    since: NaiveDate,
+) -> Option<(NaiveDate, i64)> {
+    cumulative
+        .iter()
+        .find(|(_, total)| *total >= threshold)
+        .filter(|(day, _)| *day >= since)
+        .copied()
+}
@@ -0,0 +1,73 @@
+//! Star spike detection
+//!
+//! A day is a spike when its star count exceeds `multiple` times the average of
+//! the `window` days before it, which is what an aggregator front page looks
+//! like in the data.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Longest trailing window, in days.
+pub const MAX_WINDOW: usize = 365;
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct AnomalyConfig {
+    #[serde(default = "default_multiple")]
+    pub multiple: f64,
+    /// Days averaged before each day, 1 to [`MAX_WINDOW`].
+    #[serde(default = "default_window")]
+    pub window: usize,
+    /// Days below this count are never flagged, so 0 → 3 stars isn't a spike.
+    #[serde(default = "default_min_stars")]
+    pub min_stars: i64,
+}
+
+fn default_multiple() -> f64 {
+    3.0
+}
+
+fn default_window() -> usize {
+    28
+}
+
+fn default_min_stars() -> i64 {
+    10
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            multiple: default_multiple(),
+            window: default_window(),
+            min_stars: default_min_stars(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct Anomaly {
+    pub day: NaiveDate,
+    pub stars: i64,
+    pub trailing_average: f64,
+}
+
+/// Spikes in `daily` (one entry per day, gaps filled with zero). Days without `window` days
+/// before them are never flagged.
+pub fn detect_anomalies(daily: &[(NaiveDate, i64)], config: &AnomalyConfig) -> Vec<Anomaly> {
+    let window = config.window.max(1);
+    let Some(days_with_window) = window.checked_add(1) else {
+        return Vec::new();
+    };
+
+    daily
+        .windows(days_with_window)
+        .filter_map(|days| {
+            let (&(day, stars), trailing) = days.split_last()?;
+            let trailing_average = trailing.iter().map(|(_, count)| *count as f64).sum::<f64>() / window as f64;
+
+            let is_spike = stars >= config.min_stars && stars as f64 > config.multiple * trailing_average;
+            is_spike.then_some(Anomaly { day, stars, trailing_average })
+        })
+        .collect()
+}
@@ -0,0 +1,63 @@
+//! Stargazer cohorts
+//!
+//! Stargazers grouped by the period they starred in, e.g. the month, and
+//! how much of the running star total each group makes up over time. Works
+//! on stars per period as loaded by `get_stars_per_period`: every period
+//! from the first star on, oldest first, empty ones included.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Stargazers who starred during one period.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct StarCohort {
+    /// First day of the period.
+    pub start: NaiveDate,
+    pub stars: i64,
+    /// Share of every star, rounded to a tenth of a percent.
+    pub share_percent: f64,
+    /// Stars of the cohort in the running total at the end of each period of
+    /// [`CohortAnalysis::periods`], 0 before the cohort's period.
+    pub cumulative: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct CohortAnalysis {
+    pub total_stars: i64,
+    /// First day of every period, oldest first.
+    pub periods: Vec<NaiveDate>,
+    /// Running star total at the end of each period.
+    pub totals: Vec<i64>,
+    /// One cohort per period with stars, oldest first.
+    pub cohorts: Vec<StarCohort>,
+}
+
+/// Cohorts of `period_counts`, `(period start, stars)` pairs for consecutive periods in ascending order.
+pub fn star_cohorts(period_counts: &[(NaiveDate, i64)]) -> CohortAnalysis {
+    let total_stars: i64 = period_counts.iter().map(|&(_, stars)| stars).sum();
+    let periods: Vec<NaiveDate> = period_counts.iter().map(|&(start, _)| start).collect();
+
+    let mut total = 0;
+    let totals = period_counts
+        .iter()
+        .map(|&(_, stars)| {
+            total += stars;
+            total
+        })
+        .collect();
+
+    let cohorts = period_counts
+        .iter()
+        .enumerate()
+        .filter(|&(_, &(_, stars))| stars > 0)
+        .map(|(index, &(start, stars))| StarCohort {
+            start,
+            stars,
+            share_percent: (1000.0 * stars as f64 / total_stars as f64).round() / 10.0,
+            cumulative: (0..periods.len()).map(|period| if period >= index { stars } else { 0 }).collect(),
+        })
+        .collect();
+
+    CohortAnalysis { total_stars, periods, totals, cohorts }
+}
@@ -0,0 +1,354 @@
+//! Star histories side by side
+//!
+//! Lines up the series of several repositories so a frontend can chart them
+//! together: either on the calendar, sharing a first day, or relative to each
+//! repository's first star. Every series runs until `today`, days without
+//! stars included. Running totals can also be rescaled by a [`Normalization`]
+//! so repositories of very different sizes compare by relative growth, or
+//! differentiated into velocity and acceleration, see [`derivatives`]. A
+//! [`FillPolicy`] tells how the days a repository has no data for are shown,
+//! e.g. before the first snapshot of one tracked by snapshots only.
+//!
+//! The views and clones of a repository, recorded by [`crate::traffic`], and
+//! its opened and closed issues and pull requests, stored by
+//! [`crate::activity`], can be lined up with its stars the same way, as daily
+//! counts, and so can its running total of contributors, tracked by
+//! [`crate::contributors`].
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::analytics::derivatives::{self, DerivativeOptions};
+use crate::analytics::series::{daily_series, FillPolicy};
+
+/// Days averaged by [`CompareMetric::RollingAverage`].
+const ROLLING_DAYS: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareMetric {
+    /// Stars gained each day.
+    Daily,
+    /// Running star total.
+    Cumulative,
+    /// Mean of the daily stars over the trailing 7 days, fewer at the start of the series.
+    RollingAverage,
+    /// Running star total of the repository and its tracked forks together, see [`crate::forks`].
+    WithForks,
+    /// Running star total rescaled by the comparison's [`Normalization`].
+    Normalized,
+    /// Stars per day over the comparison's derivative window, see [`derivatives::velocity`].
+    Velocity,
+    /// Change of the velocity per day, see [`derivatives::acceleration`].
+    Acceleration,
+    /// Views of the repository's pages each day, 0 on the days without a traffic snapshot.
+    Views,
+    /// Clones of the repository each day, 0 on the days without a traffic snapshot.
+    Clones,
+    /// Issues opened and closed each day.
+    Issues,
+    /// Pull requests opened and closed each day, merged ones being closed.
+    Prs,
+    /// Running total of contributors, by the day of their first commit, those before the start
+    /// of the series included.
+    Contributors,
+}
+
+impl CompareMetric {
+    /// Whether the metric is drawn from the repository's traffic rather than its stars.
+    pub fn is_traffic(&self) -> bool {
+        matches!(self, CompareMetric::Views | CompareMetric::Clones)
+    }
+
+    /// Whether the metric is drawn from the repository's issues or pull requests.
+    pub fn is_activity(&self) -> bool {
+        matches!(self, CompareMetric::Issues | CompareMetric::Prs)
+    }
+}
+
+/// How [`CompareMetric::Normalized`] rescales running totals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Normalization {
+    /// Percent of the series' last running total, ending at 100.
+    #[default]
+    PercentOfTotal,
+    /// 100 for the running total at the end of `base`, a calendar date whatever the alignment.
+    Index {
+        base: NaiveDate,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Alignment {
+    /// Index 0 is the same date for every series, the earliest first star among them.
+    #[default]
+    Calendar,
+    /// Index 0 is each repository's own first star, to compare launches.
+    Relative,
+}
+
+/// How the series of a comparison are computed, the defaults being the plain running totals
+/// on the calendar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompareOptions {
+    pub alignment: Alignment,
+    /// Only applies to [`CompareMetric::Normalized`].
+    pub normalization: Normalization,
+    /// Only applies to [`CompareMetric::Velocity`] and [`CompareMetric::Acceleration`].
+    pub derivative: DerivativeOptions,
+    /// Applies to every metric. Snapshot gaps are filled when the history is loaded, see
+    /// `snapshots::get_star_history`.
+    pub fill: FillPolicy,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ComparedSeries {
+    /// `owner/name`
+    pub repository: String,
+    /// Unset for a repository without stars, whose series are empty.
+    pub first_star_on: Option<NaiveDate>,
+    /// Date of index 0 of this series, the same for every calendar-aligned one unless the fill
+    /// policy starts them on their first day of data.
+    pub start: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily: Option<Vec<i64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cumulative: Option<Vec<i64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rolling_average: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_forks: Option<Vec<i64>>,
+    /// Empty when the total it is relative to is 0, e.g. an `index` base before the first star.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acceleration: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub views: Option<Vec<i64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clones: Option<Vec<i64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issues: Option<ActivitySeries>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prs: Option<ActivitySeries>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contributors: Option<Vec<i64>>,
+    /// Series interpolated between star count snapshots, the repository's stargazers not
+    /// being stored, see [`crate::snapshots`]. `first_star_on` is then the first snapshot.
+    #[serde(default)]
+    pub from_snapshots: bool,
+}
+
+/// Star history of a compared repository.
+#[derive(Debug, Clone)]
+pub struct RepositoryHistory {
+    /// `owner/name`
+    pub repository: String,
+    /// From `get_daily_star_count`, or derived from star count snapshots.
+    pub daily_counts: Vec<(NaiveDate, i64)>,
+    /// Whether `daily_counts` come from snapshots.
+    pub from_snapshots: bool,
+    /// Daily counts with the forks' added, for [`CompareMetric::WithForks`].
+    pub with_forks: Option<Vec<(NaiveDate, i64)>>,
+    /// Recorded traffic, for [`CompareMetric::Views`] and [`CompareMetric::Clones`].
+    pub traffic: Option<DailyTraffic>,
+    /// Stored issues and pull requests, for [`CompareMetric::Issues`] and [`CompareMetric::Prs`].
+    pub activity: Option<DailyActivity>,
+    /// Contributors by the day of their first commit, for [`CompareMetric::Contributors`].
+    pub first_contributions: Option<Vec<(NaiveDate, i64)>>,
+}
+
+/// Issues and pull requests opened and closed each day.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ActivitySeries {
+    pub opened: Vec<i64>,
+    pub closed: Vec<i64>,
+}
+
+/// `(day, opened, closed)` counts of a repository's issues and pull requests, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct DailyActivity {
+    pub issues: Vec<(NaiveDate, i64, i64)>,
+    pub prs: Vec<(NaiveDate, i64, i64)>,
+}
+
+/// Daily traffic counts of a repository, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct DailyTraffic {
+    pub views: Vec<(NaiveDate, i64)>,
+    pub clones: Vec<(NaiveDate, i64)>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct Comparison {
+    pub alignment: Alignment,
+    /// Last day of every series.
+    pub end: NaiveDate,
+    /// Set when the normalized series were asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalization: Option<Normalization>,
+    /// Set when velocity or acceleration were asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivative: Option<DerivativeOptions>,
+    #[serde(default)]
+    pub fill: FillPolicy,
+    pub series: Vec<ComparedSeries>,
+}
+
+/// Aligned `metrics` of every repository.
+///
+/// With [`CompareMetric::WithForks`], a series starts at the first star of the repository
+/// or of its forks, whichever came first, so no fork star is left out.
+pub fn compare_series(
+    repositories: &[RepositoryHistory],
+    metrics: &[CompareMetric],
+    options: &CompareOptions,
+    today: NaiveDate,
+) -> Comparison {
+    let wants = |metric| metrics.contains(&metric);
+    let differentiates = wants(CompareMetric::Velocity) || wants(CompareMetric::Acceleration);
+    let first_day = |history: &RepositoryHistory| {
+        let counts = history.with_forks.as_ref().filter(|_| wants(CompareMetric::WithForks)).unwrap_or(&history.daily_counts);
+        counts.first().map(|&(day, _)| day)
+    };
+    let calendar_start = repositories.iter().filter_map(first_day).min();
+    let own_start = matches!(options.fill, FillPolicy::Skip | FillPolicy::ClampToTrackingStart);
+
+    let series = repositories
+        .iter()
+        .map(|history| {
+            let first_day = first_day(history);
+            let start = match options.alignment {
+                Alignment::Calendar if !own_start => calendar_start.filter(|_| first_day.is_some()),
+                _ => first_day,
+            };
+            let mut daily = aligned_daily(&history.daily_counts, start, today);
+            let baseline = clamped_baseline(&mut daily, options.fill);
+            // Stars gained since the start, what the derivatives see, the baseline left out.
+            let gained = running_total(&daily);
+            let cumulative: Vec<i64> = gained.iter().map(|total| total + baseline).collect();
+            let with_forks = history.with_forks.as_deref().filter(|_| wants(CompareMetric::WithForks)).map(|daily_counts| {
+                let mut daily = aligned_daily(daily_counts, start, today);
+                let baseline = clamped_baseline(&mut daily, options.fill);
+                running_total(&daily).into_iter().map(|total| total + baseline).collect()
+            });
+            // Traffic shares the stars' days, none recorded before the start being drawn.
+            let traffic = |metric, pick: fn(&DailyTraffic) -> &[(NaiveDate, i64)]| {
+                wants(metric).then(|| aligned_daily(history.traffic.as_ref().map_or(&[][..], pick), start, today))
+            };
+            let activity = |metric, pick: fn(&DailyActivity) -> &[(NaiveDate, i64, i64)]| {
+                wants(metric).then(|| {
+                    let days = history.activity.as_ref().map_or(&[][..], pick);
+                    let opened: Vec<(NaiveDate, i64)> = days.iter().map(|&(day, opened, _)| (day, opened)).collect();
+                    let closed: Vec<(NaiveDate, i64)> = days.iter().map(|&(day, _, closed)| (day, closed)).collect();
+                    ActivitySeries { opened: aligned_daily(&opened, start, today), closed: aligned_daily(&closed, start, today) }
+                })
+            };
+
+            ComparedSeries {
+                repository: history.repository.clone(),
+                first_star_on: history.daily_counts.first().map(|&(day, _)| day),
+                start,
+                rolling_average: wants(CompareMetric::RollingAverage).then(|| rolling_average(&daily)),
+                with_forks,
+                normalized: wants(CompareMetric::Normalized).then(|| normalize(&cumulative, start, options.normalization)),
+                velocity: wants(CompareMetric::Velocity).then(|| derivatives::velocity(&gained, &options.derivative)),
+                acceleration: wants(CompareMetric::Acceleration).then(|| derivatives::acceleration(&gained, &options.derivative)),
+                views: traffic(CompareMetric::Views, |traffic| &traffic.views),
+                clones: traffic(CompareMetric::Clones, |traffic| &traffic.clones),
+                issues: activity(CompareMetric::Issues, |activity| &activity.issues),
+                prs: activity(CompareMetric::Prs, |activity| &activity.prs),
+                contributors: wants(CompareMetric::Contributors).then(|| {
+                    contributor_total(history.first_contributions.as_deref().unwrap_or_default(), start, today)
+                }),
+                cumulative: wants(CompareMetric::Cumulative).then_some(cumulative),
+                daily: wants(CompareMetric::Daily).then_some(daily),
+                from_snapshots: history.from_snapshots,
+            }
+        })
+        .collect();
+
+    Comparison {
+        alignment: options.alignment,
+        end: today,
+        normalization: wants(CompareMetric::Normalized).then_some(options.normalization),
+        derivative: differentiates.then_some(options.derivative),
+        fill: options.fill,
+        series,
+    }
+}
+
+/// With [`FillPolicy::ClampToTrackingStart`], the stars of the first day, zeroed so they don't
+/// show as gained that day, to be added to every running total instead. 0 otherwise.
+fn clamped_baseline(daily: &mut [i64], fill: FillPolicy) -> i64 {
+    match (fill, daily.first_mut()) {
+        (FillPolicy::ClampToTrackingStart, Some(first)) => std::mem::take(first),
+        _ => 0,
+    }
+}
+
+/// Stars of every day from `start` to `today`, zero before the first star and after the last.
+fn aligned_daily(daily_counts: &[(NaiveDate, i64)], start: Option<NaiveDate>, today: NaiveDate) -> Vec<i64> {
+    let Some(start) = start else {
+        return Vec::new();
+    };
+    let mut daily: Vec<i64> = start.iter_days().take_while(|day| *day <= today).map(|_| 0).collect();
+    for (day, stars) in daily_series(daily_counts) {
+        if let Some(slot) = usize::try_from((day - start).num_days()).ok().and_then(|index| daily.get_mut(index)) {
+            *slot = stars;
+        }
+    }
+    daily
+}
+
+/// Running total of contributors on every day from `start` to `today`, starting from those who
+/// contributed before.
+fn contributor_total(first_contributions: &[(NaiveDate, i64)], start: Option<NaiveDate>, today: NaiveDate) -> Vec<i64> {
+    let before: i64 = first_contributions
+        .iter()
+        .filter(|(day, _)| start.is_some_and(|start| *day < start))
+        .map(|(_, contributors)| contributors)
+        .sum();
+    running_total(&aligned_daily(first_contributions, start, today))
+        .into_iter()
+        .map(|total| total + before)
+        .collect()
+}
+
+fn running_total(daily: &[i64]) -> Vec<i64> {
+    daily
+        .iter()
+        .scan(0, |total, stars| {
+            *total += stars;
+            Some(*total)
+        })
+        .collect()
+}
+
+/// `cumulative`, running from `start`, as a percentage of the total `normalization` picks.
+fn normalize(cumulative: &[i64], start: Option<NaiveDate>, normalization: Normalization) -> Vec<f64> {
+    let reference = match normalization {
+        Normalization::PercentOfTotal => cumulative.last().copied(),
+        Normalization::Index { base } => start
+            .and_then(|start| usize::try_from((base - start).num_days()).ok())
+            .and_then(|index| cumulative.get(index).copied()),
+    };
+    match reference {
+        Some(reference) if reference > 0 => cumulative.iter().map(|&total| 100.0 * total as f64 / reference as f64).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn rolling_average(daily: &[i64]) -> Vec<f64> {
+    (0..daily.len())
+        .map(|index| {
+            let window = &daily[(index + 1).saturating_sub(ROLLING_DAYS)..=index];
+            window.iter().sum::<i64>() as f64 / window.len() as f64
+        })
+        .collect()
+}
@@ -0,0 +1,84 @@
+//! Star velocity and acceleration
+//!
+//! Derivatives of a series of one value per day, e.g. the running total, over
+//! a window centered on each day rather than from one day to the next, which
+//! makes the daily noise of small repositories look like swings. Series
+//! start from nothing, the value before their first day is taken as 0, and
+//! windows are cut short at the last day. An exponential moving average can
+//! smooth the velocity further before it is differentiated again.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Longest derivative window and moving average span, in days.
+pub const MAX_WINDOW_DAYS: usize = 365;
+
+/// How velocity and acceleration are computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct DerivativeOptions {
+    /// Days each difference spans, odd so they center on their day; 1 for the day-to-day difference.
+    #[serde(default = "default_window_days")]
+    pub window_days: usize,
+    /// Span in days of the exponential moving average applied to the velocity, unsmoothed when unset.
+    #[serde(default)]
+    pub ema_span_days: Option<usize>,
+}
+
+impl Default for DerivativeOptions {
+    fn default() -> Self {
+        Self { window_days: default_window_days(), ema_span_days: None }
+    }
+}
+
+fn default_window_days() -> usize {
+    1
+}
+
+/// Change per day of `values` over `window_days` days centered on each day.
+///
+/// Day `i` compares the value `window_days / 2` days later, or the last one, with the one before
+/// the window, or 0 before the first day. A running total gives the stars of every day with a
+/// 1-day window, and their mean over the window otherwise.
+pub fn centered_difference(values: &[f64], window_days: usize) -> Vec<f64> {
+    let half = window_days / 2;
+    (0..values.len())
+        .map(|index| {
+            let upper = (index + half).min(values.len() - 1);
+            let (lower_value, lower) = match index.checked_sub(half + 1) {
+                Some(lower) => (values[lower], lower as f64),
+                None => (0.0, -1.0),
+            };
+            (values[upper] - lower_value) / (upper as f64 - lower)
+        })
+        .collect()
+}
+
+/// Exponential moving average of `values` with a smoothing factor of `2 / (span_days + 1)`,
+/// starting from the first value.
+pub fn exponential_moving_average(values: &[f64], span_days: usize) -> Vec<f64> {
+    let alpha = 2.0 / (span_days as f64 + 1.0);
+    let mut average = None;
+    values
+        .iter()
+        .map(|&value| {
+            let next = average.map_or(value, |previous: f64| previous + alpha * (value - previous));
+            average = Some(next);
+            next
+        })
+        .collect()
+}
+
+/// Stars per day of the running total `cumulative`, smoothed as `options` tell.
+pub fn velocity(cumulative: &[i64], options: &DerivativeOptions) -> Vec<f64> {
+    let totals: Vec<f64> = cumulative.iter().map(|&total| total as f64).collect();
+    let velocity = centered_difference(&totals, options.window_days);
+    match options.ema_span_days {
+        Some(span) => exponential_moving_average(&velocity, span),
+        None => velocity,
+    }
+}
+
+/// Change per day of the [`velocity`] of `cumulative`, over the same window.
+pub fn acceleration(cumulative: &[i64], options: &DerivativeOptions) -> Vec<f64> {
+    centered_difference(&velocity(cumulative, options), options.window_days)
+}
@@ -0,0 +1,104 @@
+//! Star count projections
+//!
+//! Fits a least-squares line over the trailing window of the cumulative series,
+//! either on the totals themselves (steady growth) or on their logarithm
+//! (compounding growth), and extrapolates it.
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastModel {
+    #[default]
+    Linear,
+    LogLinear,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct Projection {
+    pub days: u32,
+    pub date: NaiveDate,
+    pub total: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct Forecast {
+    pub model: ForecastModel,
+    pub window_days: usize,
+    pub current_total: i64,
+    /// Stars per day at the end of the history according to the fitted model.
+    pub daily_rate: f64,
+    pub projections: Vec<Projection>,
+}
+
+/// Projects `cumulative` (one entry per day) `horizons` days past its last day.
+///
+/// Returns `None` when fewer than two days are available to fit the model.
+pub fn forecast(
+    cumulative: &[(NaiveDate, i64)],
+    model: ForecastModel,
+    window_days: usize,
+    horizons: &[u32],
+) -> Option<Forecast> {
+    let window = &cumulative[cumulative.len().saturating_sub(window_days.max(2))..];
+    let &(last_date, current_total) = window.last()?;
+    if window.len() < 2 {
+        return None;
+    }
+
+    let points: Vec<(f64, f64)> = window
+        .iter()
+        .enumerate()
+        .map(|(x, &(_, total))| {
+            let y = match model {
+                ForecastModel::Linear => total as f64,
+                ForecastModel::LogLinear => (total.max(1) as f64).ln(),
+            };
+            (x as f64, y)
+        })
+        .collect();
+
+    let (slope, intercept) = least_squares(&points);
+    let last_x = (window.len() - 1) as f64;
+
+    let predict = |x: f64| match model {
+        ForecastModel::Linear => intercept + slope * x,
+        ForecastModel::LogLinear => (intercept + slope * x).exp(),
+    };
+
+    // Anchor the fitted curve on the actual current total, stars never go down.
+    let offset = current_total as f64 - predict(last_x);
+    let daily_rate = (predict(last_x + 1.0) - predict(last_x)).max(0.0);
+
+    let projections = horizons
+        .iter()
+        .map(|&days| Projection {
+            days,
+            date: last_date + Duration::days(i64::from(days)),
+            total: ((predict(last_x + f64::from(days)) + offset).round() as i64).max(current_total),
+        })
+        .collect();
+
+    Some(Forecast {
+        model,
+        window_days: window.len(),
+        current_total,
+        daily_rate,
+        projections,
+    })
+}
+
+/// Slope and intercept of the least-squares line through `points`.
+fn least_squares(points: &[(f64, f64)]) -> (f64, f64) {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+    let slope = if variance == 0.0 { 0.0 } else { covariance / variance };
+    (slope, mean_y - slope * mean_x)
+}
@@ -0,0 +1,72 @@
+//! Suspicious gaps in star series
+//!
+//! A repository getting a few stars every day and then none for a week is more
+//! likely missing data than out of fashion for a while, e.g. a sync that gave
+//! up early or pages GitHub skipped. A run of days without stars between two
+//! days with some is flagged when the stars of the days around it make that
+//! many empty days unlikely: at a steady rate, days go without stars like a
+//! Poisson process, the chance of an empty gap expecting 5 stars being under 1%.
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Days on each side of a gap whose stars give the rate expected over it.
+pub const RATE_WINDOW_DAYS: i64 = 14;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct ZeroStarGap {
+    /// First day without stars.
+    pub first_day: NaiveDate,
+    /// Last day without stars, the day after has some.
+    pub last_day: NaiveDate,
+    pub days: i64,
+    /// Stars over the gap at the daily rate of the days around it.
+    pub expected_stars: f64,
+}
+
+/// Runs of days without stars in `daily_counts`, a daily count series, each expecting at least
+/// `min_expected_stars` at the rate of the [`RATE_WINDOW_DAYS`] days on each side, oldest first.
+/// Days before the first star and after the last aren't gaps.
+pub fn zero_star_gaps(daily_counts: &[(NaiveDate, i64)], min_expected_stars: f64) -> Vec<ZeroStarGap> {
+    let (Some(&(first, _)), Some(&(last, _))) = (daily_counts.first(), daily_counts.last()) else {
+        return Vec::new();
+    };
+    // `totals[i]` stars before `daily_counts[i]`.
+    let totals: Vec<i64> = std::iter::once(0)
+        .chain(daily_counts.iter().scan(0, |total, (_, stars)| {
+            *total += stars;
+            Some(*total)
+        }))
+        .collect();
+    // Stars from `from` to `to`, both included.
+    let stars_between = |from: NaiveDate, to: NaiveDate| {
+        let start = daily_counts.partition_point(|(day, _)| *day < from);
+        let end = daily_counts.partition_point(|(day, _)| *day <= to);
+        totals[end] - totals[start]
+    };
+
+    daily_counts
+        .windows(2)
+        .filter_map(|pair| {
+            let ((before, _), (after, _)) = (pair[0], pair[1]);
+            let days = (after - before).num_days() - 1;
+            if days < 1 {
+                return None;
+            }
+
+            let window_start = (before - Duration::days(RATE_WINDOW_DAYS - 1)).max(first);
+            let window_end = (after + Duration::days(RATE_WINDOW_DAYS - 1)).min(last);
+            let window_days = (before - window_start).num_days() + 1 + (window_end - after).num_days() + 1;
+            let window_stars = stars_between(window_start, before) + stars_between(after, window_end);
+            let expected_stars = window_stars as f64 / window_days as f64 * days as f64;
+
+            (expected_stars >= min_expected_stars).then(|| ZeroStarGap {
+                first_day: before.succ_opt().unwrap_or(before),
+                last_day: after.pred_opt().unwrap_or(after),
+                days,
+                expected_stars,
+            })
+        })
+        .collect()
+}
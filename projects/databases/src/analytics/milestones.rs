@@ -0,0 +1,78 @@
+//! Milestone crossings and time-to-target estimates
+
+use chrono::{Days, NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Star counts recorded as milestones when a repository crosses them.
+pub const MILESTONES: [i64; 6] = [1_000, 5_000, 10_000, 25_000, 50_000, 100_000];
+
+/// First day each of `thresholds` was reached in `cumulative` (one entry per day).
+pub fn milestone_crossings(cumulative: &[(NaiveDate, i64)], thresholds: &[i64]) -> Vec<(i64, NaiveDate)> {
+    thresholds
+        .iter()
+        .filter_map(|&threshold| {
+            cumulative
+                .iter()
+                .find(|(_, total)| *total >= threshold)
+                .map(|&(day, _)| (threshold, day))
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MilestoneEta {
+    pub target: i64,
+    pub current_total: i64,
+    /// Average stars per day over the velocity window.
+    pub daily_rate: f64,
+    pub velocity_window_days: i64,
+    /// Set when the target is already reached.
+    pub reached_on: Option<NaiveDate>,
+    /// Estimated day the target is reached, `None` without recent growth or past the last date.
+    pub eta: Option<NaiveDate>,
+}
+
+/// Estimates when `cumulative` reaches `target` from the velocity of the last
+/// `window_days` before `today`, which must be positive.
+pub fn milestone_eta(
+    cumulative: &[(NaiveDate, i64)],
+    target: i64,
+    today: NaiveDate,
+    window_days: i64,
+) -> MilestoneEta {
+    let total_at = |day: NaiveDate| {
+        cumulative
+            .iter()
+            .take_while(|(date, _)| *date <= day)
+            .last()
+            .map_or(0, |&(_, total)| total)
+    };
+
+    let current_total = total_at(today);
+    // A window reaching past the first date starts before any star.
+    let window_start = u64::try_from(window_days).ok().and_then(|days| today.checked_sub_days(Days::new(days)));
+    let daily_rate = (current_total - window_start.map_or(0, total_at)) as f64 / window_days as f64;
+
+    let reached_on = milestone_crossings(cumulative, &[target])
+        .first()
+        .map(|&(_, day)| day);
+
+    let eta = match reached_on {
+        Some(day) => Some(day),
+        None if daily_rate > 0.0 => {
+            let days = (target.saturating_sub(current_total) as f64 / daily_rate).ceil() as i64;
+            TimeDelta::try_days(days).and_then(|days| today.checked_add_signed(days))
+        }
+        None => None,
+    };
+
+    MilestoneEta {
+        target,
+        current_total,
+        daily_rate,
+        velocity_window_days: window_days,
+        reached_on,
+        eta,
+    }
+}
@@ -0,0 +1,26 @@
+//! Star series analysis
+//!
+//! Plain functions over star series, independent of the database and the
+//! HTTP layer so other crates can run them on their own data. A series is a
+//! slice of `(day, value)` pairs; the functions expect:
+//!
+//! - days in ascending order, each day at most once
+//! - daily counts (`get_daily_star_count`) to skip days without stars, while
+//!   the series built by [`series`] have every day from the first to the last
+//!
+//! [`series::normalize_daily_counts`] brings raw data to that shape and
+//! [`series::DataPoint`] is the serializable form of a pair. Results derive
+//! `Serialize` and `Deserialize`.
+
+pub mod alerts;
+pub mod anomalies;
+pub mod cohorts;
+pub mod compare;
+pub mod derivatives;
+pub mod forecast;
+pub mod gaps;
+pub mod milestones;
+pub mod profiles;
+pub mod releases;
+pub mod series;
+pub mod stats;
\ No newline at end of file
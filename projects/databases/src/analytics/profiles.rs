@@ -0,0 +1,61 @@
+//! Who the stargazers are
+//!
+//! Tallies the free-text company and location of stargazer profiles. Both
+//! are written by hand on GitHub, so companies are grouped ignoring case and
+//! a leading `@`, and the country is taken as the last comma-separated part
+//! of the location, which is how most people write it ("Lyon, France").
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ProfileCount {
+    /// Spelling of the first stargazer seen with this value.
+    pub name: String,
+    pub stargazers: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ProfileBreakdown {
+    /// Stargazers with a company or a location, the others are left out.
+    pub profiles: usize,
+    pub top_companies: Vec<ProfileCount>,
+    pub top_countries: Vec<ProfileCount>,
+}
+
+/// Up to `limit` companies and countries with the most stargazers among `profiles`, pairs of company and location.
+pub fn profile_breakdown(profiles: &[(Option<String>, Option<String>)], limit: usize) -> ProfileBreakdown {
+    let companies = profiles
+        .iter()
+        .filter_map(|(company, _)| company.as_deref())
+        .map(|company| company.trim().trim_start_matches('@').trim());
+    let countries = profiles
+        .iter()
+        .filter_map(|(_, location)| location.as_deref())
+        .filter_map(|location| location.rsplit(',').next())
+        .map(str::trim);
+
+    ProfileBreakdown {
+        profiles: profiles.len(),
+        top_companies: top_counts(companies, limit),
+        top_countries: top_counts(countries, limit),
+    }
+}
+
+/// Most frequent values ignoring case, ties broken by name.
+fn top_counts<'a>(values: impl Iterator<Item = &'a str>, limit: usize) -> Vec<ProfileCount> {
+    let mut counts: HashMap<String, ProfileCount> = HashMap::new();
+    for value in values.filter(|value| !value.is_empty()) {
+        counts
+            .entry(value.to_lowercase())
+            .or_insert_with(|| ProfileCount { name: value.into(), stargazers: 0 })
+            .stargazers += 1;
+    }
+
+    let mut top: Vec<ProfileCount> = counts.into_values().collect();
+    top.sort_by(|a, b| b.stargazers.cmp(&a.stargazers).then_with(|| a.name.cmp(&b.name)));
+    top.truncate(limit);
+    top
+}
@@ -0,0 +1,125 @@
+//! Star velocity around releases
+//!
+//! Each release is weighed by the stars of the `window_days` starting on the
+//! day it was published against those of the `window_days` before it. Over the
+//! whole history, the days inside a post-release window are weighed against the
+//! others, the baseline.
+
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Stars around one release.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct ReleaseImpact {
+    pub tag_name: String,
+    pub published_on: NaiveDate,
+    /// Stars of the window before the release day.
+    pub stars_before: i64,
+    /// Stars of the window starting on the release day.
+    pub stars_after: i64,
+    /// Days of the window after the release already past, fewer than the window for recent releases.
+    pub days_after: i64,
+    /// Average stars per day before the release.
+    pub velocity_before: f64,
+    /// Average stars per day since the release.
+    pub velocity_after: f64,
+    /// `velocity_after` over `velocity_before`, `None` without stars before.
+    pub lift: Option<f64>,
+}
+
+/// Stars around every release of a repository.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct ReleaseCorrelation {
+    pub window_days: i64,
+    /// Oldest release first.
+    pub releases: Vec<ReleaseImpact>,
+    /// Average stars per day inside the post-release windows.
+    pub post_release_velocity: f64,
+    /// Average stars per day outside of them, from the first star on.
+    pub baseline_velocity: f64,
+    /// `post_release_velocity` over `baseline_velocity`, `None` without a baseline.
+    pub lift: Option<f64>,
+}
+
+/// Star velocity around each of `releases`, `(tag, day published)` pairs oldest first, in the daily
+/// counts `daily_counts` up to `today`.
+pub fn release_correlation(
+    daily_counts: &[(NaiveDate, i64)],
+    releases: &[(String, NaiveDate)],
+    window_days: i64,
+    today: NaiveDate,
+) -> ReleaseCorrelation {
+    let stars_between = |from: NaiveDate, to: NaiveDate| -> i64 {
+        daily_counts
+            .iter()
+            .filter(|(day, _)| (from..=to).contains(day))
+            .map(|(_, stars)| stars)
+            .sum()
+    };
+    let window = Duration::days(window_days);
+
+    let impacts: Vec<ReleaseImpact> = releases
+        .iter()
+        .filter(|(_, published_on)| *published_on <= today)
+        .map(|(tag_name, published_on)| {
+            let published_on = *published_on;
+            let stars_before = stars_between(published_on - window, published_on - Duration::days(1));
+            let stars_after = stars_between(published_on, published_on + window - Duration::days(1));
+            let days_after = ((today - published_on).num_days() + 1).min(window_days);
+            let velocity_before = stars_before as f64 / window_days as f64;
+            let velocity_after = stars_after as f64 / days_after as f64;
+
+            ReleaseImpact {
+                tag_name: tag_name.clone(),
+                published_on,
+                stars_before,
+                stars_after,
+                days_after,
+                velocity_before,
+                velocity_after,
+                lift: ratio(velocity_after, velocity_before),
+            }
+        })
+        .collect();
+
+    let post_release_days: HashSet<NaiveDate> = impacts
+        .iter()
+        .flat_map(|impact| impact.published_on.iter_days().take(impact.days_after as usize))
+        .collect();
+    let first_day = daily_counts.first().map_or(today, |&(day, _)| day);
+    let history_days = ((today - first_day).num_days() + 1).max(0);
+    let post_release_days_in_history = post_release_days.iter().filter(|day| (first_day..=today).contains(*day)).count() as i64;
+    let baseline_days = history_days - post_release_days_in_history;
+
+    let (post_release_stars, baseline_stars) = daily_counts
+        .iter()
+        .filter(|(day, _)| *day <= today)
+        .fold((0, 0), |(post, baseline), (day, stars)| match post_release_days.contains(day) {
+            true => (post + stars, baseline),
+            false => (post, baseline + stars),
+        });
+    let post_release_velocity = average(post_release_stars, post_release_days_in_history);
+    let baseline_velocity = average(baseline_stars, baseline_days);
+
+    ReleaseCorrelation {
+        window_days,
+        releases: impacts,
+        post_release_velocity,
+        baseline_velocity,
+        lift: ratio(post_release_velocity, baseline_velocity),
+    }
+}
+
+fn average(stars: i64, days: i64) -> f64 {
+    if days <= 0 {
+        return 0.0;
+    }
+    stars as f64 / days as f64
+}
+
+fn ratio(value: f64, reference: f64) -> Option<f64> {
+    (reference > 0.0).then(|| value / reference)
+}
@@ -0,0 +1,136 @@
+//! Daily and cumulative series, and conversions from raw data
+//!
+//! Stored stars are the whole history of a repository: a day without stars
+//! is a day nobody starred it. Snapshots only tell the star count on the days
+//! they were taken, and nothing of the days before the first one, so how
+//! those are filled is up to the caller, see [`FillPolicy`].
+
+use std::collections::BTreeMap;
+
+use chrono::{Days, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// One day of a series, serializable counterpart of the `(day, value)` pairs the functions take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, ToSchema)]
+pub struct DataPoint {
+    pub day: NaiveDate,
+    pub value: i64,
+}
+
+impl From<(NaiveDate, i64)> for DataPoint {
+    fn from((day, value): (NaiveDate, i64)) -> Self {
+        Self { day, value }
+    }
+}
+
+impl From<DataPoint> for (NaiveDate, i64) {
+    fn from(point: DataPoint) -> Self {
+        (point.day, point.value)
+    }
+}
+
+/// `series` as [`DataPoint`]s, e.g. to serialize it.
+pub fn to_data_points(series: &[(NaiveDate, i64)]) -> Vec<DataPoint> {
+    series.iter().copied().map(DataPoint::from).collect()
+}
+
+/// Daily counts from `(day, stars)` pairs in any order, days listed more than once summed.
+///
+/// The result meets the invariants of the analytics functions: ascending days, each once.
+pub fn normalize_daily_counts(raw: impl IntoIterator<Item = (NaiveDate, i64)>) -> Vec<(NaiveDate, i64)> {
+    let mut counts = BTreeMap::new();
+    for (day, stars) in raw {
+        *counts.entry(day).or_insert(0) += stars;
+    }
+    counts.into_iter().collect()
+}
+
+/// How the days a repository has no data for are filled: the days before its tracking start,
+/// its first star or first snapshot, and the days between two snapshots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FillPolicy {
+    /// No stars before the tracking start, the change between two snapshots spread evenly over
+    /// the days in between.
+    #[default]
+    Zero,
+    /// Like `zero`, the star count holding between two snapshots until the day of the next one.
+    ForwardFill,
+    /// Series start on their tracking start whatever their alignment, the days before left out.
+    Skip,
+    /// Like `skip`, the stars of the tracking start's day taken as a baseline of the running
+    /// total rather than as stars gained that day, as most were gained before tracking began.
+    ClampToTrackingStart,
+}
+
+/// Daily counts whose running total follows `snapshots`, `(taken_at, star_count)` pairs in
+/// ascending order: the first snapshot's count on its day, then the change up to each next
+/// snapshot, spread evenly over the days in between or, with [`FillPolicy::ForwardFill`], all
+/// on the next snapshot's day. The last snapshot of a day counts.
+///
+/// The position curve of a repository whose stargazers aren't stored, unstars showing as
+/// negative days. Later days without change are left out, as in stored daily counts.
+pub fn snapshot_daily_counts(snapshots: &[(NaiveDateTime, i64)], fill: FillPolicy) -> Vec<(NaiveDate, i64)> {
+    let positions: BTreeMap<NaiveDate, i64> = snapshots.iter().map(|&(taken_at, count)| (taken_at.date(), count)).collect();
+
+    let mut daily = Vec::new();
+    let mut previous: Option<(NaiveDate, i64)> = None;
+    for (day, count) in positions {
+        match previous {
+            None => daily.push((day, count)),
+            Some((_, previous_count)) if fill == FillPolicy::ForwardFill => {
+                if count != previous_count {
+                    daily.push((day, count - previous_count));
+                }
+            }
+            Some((previous_day, previous_count)) => {
+                let gap = (day - previous_day).num_days();
+                let mut reached = previous_count;
+                for offset in 1..=gap {
+                    let position = previous_count + (count - previous_count) * offset / gap;
+                    if position != reached {
+                        daily.push((previous_day + Days::new(offset as u64), position - reached));
+                    }
+                    reached = position;
+                }
+            }
+        }
+        previous = Some((day, count));
+    }
+    daily
+}
+
+/// Star count for every day from the first star to the last, missing days as zero.
+pub fn daily_series(daily_counts: &[(NaiveDate, i64)]) -> Vec<(NaiveDate, i64)> {
+    let (Some(&(first, _)), Some(&(last, _))) = (daily_counts.first(), daily_counts.last()) else {
+        return Vec::new();
+    };
+
+    let mut counts = daily_counts.iter().peekable();
+
+    first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .map(|day| {
+            let mut stars = 0;
+            while let Some((_, count)) = counts.next_if(|(date, _)| *date == day) {
+                stars += count;
+            }
+            (day, stars)
+        })
+        .collect()
+}
+
+/// Running star total for every day from the first star to the last, gaps included.
+pub fn cumulative_series(daily_counts: &[(NaiveDate, i64)]) -> Vec<(NaiveDate, i64)> {
+    let mut total = 0;
+
+    daily_series(daily_counts)
+        .into_iter()
+        .map(|(day, stars)| {
+            total += stars;
+            (day, total)
+        })
+        .collect()
+}
@@ -0,0 +1,92 @@
+//! Star history summary
+//!
+//! Headline numbers of a repository for summary cards, from its stars per day
+//! as loaded by `get_daily_star_count`. Trailing windows end on `today`,
+//! which is included, so the figures move with the date and not only with syncs.
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct BestDay {
+    pub date: NaiveDate,
+    pub stars: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StarStats {
+    pub total_stars: i64,
+    pub last_7_days: i64,
+    pub last_30_days: i64,
+    pub last_365_days: i64,
+    /// Day with the most stars, the earliest one on a tie.
+    pub best_day: Option<BestDay>,
+    /// Stars per day since the first star, today included.
+    pub average_daily_rate: f64,
+    /// Consecutive days with stars up to today, or up to yesterday while today has none yet.
+    pub current_streak_days: u32,
+    pub first_star_on: Option<NaiveDate>,
+}
+
+/// Summary of `daily_counts`, one entry per day with stars in ascending order.
+pub fn star_stats(daily_counts: &[(NaiveDate, i64)], today: NaiveDate) -> StarStats {
+    let total_stars = daily_counts.iter().map(|&(_, stars)| stars).sum();
+    let stars_since = |days: i64| -> i64 {
+        let first_day = today - Duration::days(days - 1);
+        daily_counts
+            .iter()
+            .filter(|&&(day, _)| day >= first_day && day <= today)
+            .map(|&(_, stars)| stars)
+            .sum()
+    };
+
+    // `max_by_key` keeps the last maximum, reversing makes that the earliest day.
+    let best_day = daily_counts
+        .iter()
+        .rev()
+        .max_by_key(|&&(_, stars)| stars)
+        .map(|&(date, stars)| BestDay { date, stars });
+
+    let first_star_on = daily_counts.first().map(|&(day, _)| day);
+    let average_daily_rate = match first_star_on {
+        Some(first_day) => total_stars as f64 / ((today - first_day).num_days() + 1).max(1) as f64,
+        None => 0.0,
+    };
+
+    StarStats {
+        total_stars,
+        last_7_days: stars_since(7),
+        last_30_days: stars_since(30),
+        last_365_days: stars_since(365),
+        best_day,
+        average_daily_rate,
+        current_streak_days: current_streak(daily_counts, today),
+        first_star_on,
+    }
+}
+
+fn current_streak(daily_counts: &[(NaiveDate, i64)], today: NaiveDate) -> u32 {
+    let mut starred_days = daily_counts
+        .iter()
+        .rev()
+        .filter(|&&(day, stars)| stars > 0 && day <= today)
+        .map(|&(day, _)| day)
+        .peekable();
+
+    // A streak still counts until a whole day passes without stars.
+    let mut expected = match starred_days.peek() {
+        Some(&day) if day == today || day == today - Duration::days(1) => day,
+        _ => return 0,
+    };
+
+    let mut streak = 0;
+    for day in starred_days {
+        if day != expected {
+            break;
+        }
+        streak += 1;
+        expected = day - Duration::days(1);
+    }
+    streak
+}
@@ -1,18 +1,28 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, num::NonZeroUsize, time::Duration};
 
 use anyhow::Result;
-use axum::{
-	http::StatusCode, response::IntoResponse, routing::post, serve, Extension, Router
-};
+use axum::{http::StatusCode, response::IntoResponse, serve};
 use utils_trace::tracing_init;
 use thiserror::Error;
-use tracing::{error, info};
-use projects_databases::endpoints::github::repo_stars::{update::index::handler as github_repo_stars_update_handler, read_per_day::index::handler as github_repo_stars_read_per_day_handler};
-use diesel::{r2d2::{ConnectionManager, Pool}, PgConnection};
+use tracing::{error, info, warn};
+use projects_databases::env::{admin_token_from_env, chart_cache_config_from_env, collect_repository_activity_from_env, collect_stargazer_emails_from_env, collect_stargazer_profiles_from_env, contributor_tracking_interval_from_env, flag_suspected_bots_from_env, cors_from_env, db_pool_from_env, digest_schedule_from_env, forge_clients_from_env, github_client_from_env, notifier_from_env, read_pool_from_env, repos_file_from_env, request_limits_from_env, require_api_key_from_env, star_insert_config_from_env, star_retention_from_env, star_snapshot_interval_from_env, stargazer_ids_from_env, sync_job_retention_from_env, sync_limits_from_env, sync_notify_channel_from_env, traffic_snapshot_interval_from_env, webhook_secret_from_env, ChartCacheFromEnvError, CollectRepositoryActivityFromEnvError, CollectStargazerEmailsFromEnvError, CollectStargazerProfilesFromEnvError, ContributorTrackingIntervalFromEnvError, FlagSuspectedBotsFromEnvError, CorsFromEnvError, DbPoolFromEnvError, DigestScheduleFromEnvError, ForgeClientsFromEnvError, GitHubClientFromEnvError, NotifierFromEnvError, ReadPoolFromEnvError, RequestLimitsFromEnvError, RequireApiKeyFromEnvError, StarInsertConfigFromEnvError, StarRetentionFromEnvError, StarSnapshotIntervalFromEnvError, SyncJobRetentionFromEnvError, SyncLimitsFromEnvError, SyncNotifyChannelFromEnvError, TrafficSnapshotIntervalFromEnvError};
+use projects_databases::chart_cache::ChartCache;
+use projects_databases::db::{migrations::{run_pending_migrations, RunPendingMigrationsError}, pool::PoolConfig};
+use projects_databases::endpoints::tenant::TenantAuth;
+use projects_databases::live::LiveUpdates;
+use projects_databases::router::{build_router, RouterDependencies};
+use projects_databases::digest::run_digests;
+use projects_databases::job_retention::run_job_cleanup;
+use projects_databases::outbox::run_outbox_dispatcher;
+use projects_databases::retention::run_compaction;
+use projects_databases::runtime_config::{AdjustableSettings, RuntimeConfig, ServiceSettings};
+use projects_databases::snapshots::run_snapshots;
+use projects_databases::traffic::run_traffic_snapshots;
+use projects_databases::contributors::run_contributor_tracking;
+use projects_databases::sync_queue::{interrupt_abandoned_sync_jobs, InterruptAbandonedSyncJobsError, StargazerDetails, SyncQueue};
+use projects_databases::watch_list::{load_watch_list, read_watch_list, LoadWatchListError, ReadWatchListError};
 use dotenvy::dotenv;
 
-pub type PgPool = Pool<ConnectionManager<PgConnection>>;
-
 #[derive(Debug, Error)]
 pub enum MainError {
     #[error("TracingInit: {source}")]
@@ -25,16 +35,151 @@ pub enum MainError {
 		#[source]
 		source: dotenvy::Error,
 	},
-	#[error("DbEnvVar: {source}")]
-	DbEnvVar {
+	#[error("DbPoolFromEnv: {source}")]
+	DbPoolFromEnv {
+		#[source]
+		source: DbPoolFromEnvError,
+	},
+	#[error("ReadPoolFromEnv: {source}")]
+	ReadPoolFromEnv {
 		#[source]
-		source: std::env::VarError,
+		source: ReadPoolFromEnvError,
 	},
-	#[error("DbPoolBuild: {source}")]
-	DbPoolBuild {
+	#[error("DbConnection: {source}")]
+	DbConnection {
 		#[source]
 		source: r2d2::Error,
 	},
+	#[error("RunPendingMigrations: {source}")]
+	RunPendingMigrations {
+		#[source]
+		source: RunPendingMigrationsError,
+	},
+	#[error("InterruptAbandonedSyncJobs: {source}")]
+	InterruptAbandonedSyncJobs {
+		#[source]
+		source: InterruptAbandonedSyncJobsError,
+	},
+	#[error("GitHubClientFromEnv: {source}")]
+	GitHubClientFromEnv {
+		#[source]
+		source: GitHubClientFromEnvError,
+	},
+	#[error("ForgeClientsFromEnv: {source}")]
+	ForgeClientsFromEnv {
+		#[source]
+		source: ForgeClientsFromEnvError,
+	},
+	#[error("NotifierFromEnv: {source}")]
+	NotifierFromEnv {
+		#[source]
+		source: NotifierFromEnvError,
+	},
+	#[error("ChartCacheFromEnv: {source}")]
+	ChartCacheFromEnv {
+		#[source]
+		source: ChartCacheFromEnvError,
+	},
+	#[error("RequestLimitsFromEnv: {source}")]
+	RequestLimitsFromEnv {
+		#[source]
+		source: RequestLimitsFromEnvError,
+	},
+	#[error("CollectStargazerProfilesFromEnv: {source}")]
+	CollectStargazerProfilesFromEnv {
+		#[source]
+		source: CollectStargazerProfilesFromEnvError,
+	},
+	#[error("CollectStargazerEmailsFromEnv: {source}")]
+	CollectStargazerEmailsFromEnv {
+		#[source]
+		source: CollectStargazerEmailsFromEnvError,
+	},
+	#[error("FlagSuspectedBotsFromEnv: {source}")]
+	FlagSuspectedBotsFromEnv {
+		#[source]
+		source: FlagSuspectedBotsFromEnvError,
+	},
+	#[error("CollectRepositoryActivityFromEnv: {source}")]
+	CollectRepositoryActivityFromEnv {
+		#[source]
+		source: CollectRepositoryActivityFromEnvError,
+	},
+	#[error("CorsFromEnv: {source}")]
+	CorsFromEnv {
+		#[source]
+		source: CorsFromEnvError,
+	},
+	#[error("RequireApiKeyFromEnv: {source}")]
+	RequireApiKeyFromEnv {
+		#[source]
+		source: RequireApiKeyFromEnvError,
+	},
+	#[error("DigestScheduleFromEnv: {source}")]
+	DigestScheduleFromEnv {
+		#[source]
+		source: DigestScheduleFromEnvError,
+	},
+	#[error("StarInsertConfigFromEnv: {source}")]
+	StarInsertConfigFromEnv {
+		#[source]
+		source: StarInsertConfigFromEnvError,
+	},
+	#[error("StarSnapshotIntervalFromEnv: {source}")]
+	StarSnapshotIntervalFromEnv {
+		#[source]
+		source: StarSnapshotIntervalFromEnvError,
+	},
+	#[error("ContributorTrackingIntervalFromEnv: {source}")]
+	ContributorTrackingIntervalFromEnv {
+		#[source]
+		source: ContributorTrackingIntervalFromEnvError,
+	},
+	#[error("SyncNotifyChannelFromEnv: {source}")]
+	SyncNotifyChannelFromEnv {
+		#[source]
+		source: SyncNotifyChannelFromEnvError,
+	},
+	#[error("TrafficSnapshotIntervalFromEnv: {source}")]
+	TrafficSnapshotIntervalFromEnv {
+		#[source]
+		source: TrafficSnapshotIntervalFromEnvError,
+	},
+	#[error("StarRetentionFromEnv: {source}")]
+	StarRetentionFromEnv {
+		#[source]
+		source: StarRetentionFromEnvError,
+	},
+	#[error("SyncJobRetentionFromEnv: {source}")]
+	SyncJobRetentionFromEnv {
+		#[source]
+		source: SyncJobRetentionFromEnvError,
+	},
+	#[error("SyncLimitsFromEnv: {source}")]
+	SyncLimitsFromEnv {
+		#[source]
+		source: SyncLimitsFromEnvError,
+	},
+	#[error("ReadWatchList: {source}")]
+	ReadWatchList {
+		#[source]
+		source: ReadWatchListError,
+	},
+	#[error("LoadWatchList: {source}")]
+	LoadWatchList {
+		#[source]
+		source: LoadWatchListError,
+	},
+	#[error("SyncWorkersEnvVar: {source}")]
+	SyncWorkersEnvVar {
+		#[source]
+		source: std::num::ParseIntError,
+	},
+	#[error("ShutdownGraceEnvVar: {source}")]
+	ShutdownGraceEnvVar {
+		#[source]
+		source: std::num::ParseIntError,
+	},
 	#[error("TcpListenerBind: {source}")]
 	TcpListenerBind {
 		#[source]
@@ -56,15 +201,210 @@ async fn main() -> Result<(), MainError> {
 	dotenv().map_err(|source| MainError::EnvVarSetup { source })?;
 
 	// Set up the database connection pool
-	let db_pool = PgPool::builder()
-    	.build(ConnectionManager::new(std::env::var("DATABASE_URL").map_err(|source| MainError::DbEnvVar { source })?))
-    	.map_err(|source| MainError::DbPoolBuild { source })?;
+	let (db_pool, pool_metrics) = db_pool_from_env(PoolConfig::default())
+		.map_err(|source| MainError::DbPoolFromEnv { source })?;
+
+	// Read-only endpoints use the DATABASE_URL_RO replica when set, the primary when it isn't or is unreachable
+	let read_pool = read_pool_from_env(db_pool.clone(), PoolConfig::default())
+		.map_err(|source| MainError::ReadPoolFromEnv { source })?;
+
+	// Bring the schema up to date before anything touches it
+	let mut conn = db_pool.get().map_err(|source| MainError::DbConnection { source })?;
+	let applied = run_pending_migrations(&mut conn).map_err(|source| MainError::RunPendingMigrations { source })?;
+	drop(conn);
+	info!(count = applied.len(), versions = ?applied, "Applied pending migrations");
+
+	if std::env::args().skip(1).any(|arg| arg == "--migrate-only") {
+		return Ok(());
+	}
+
+	// Set up the GitHub client shared by every handler
+	let github_client = github_client_from_env()
+		.map_err(|source| MainError::GitHubClientFromEnv { source })?;
+	// GitLab and Gitea repositories are synced through clients sharing its connections
+	let forge_clients = forge_clients_from_env(github_client.clone())
+		.map_err(|source| MainError::ForgeClientsFromEnv { source })?;
+
+	// Set up alert delivery, email only when an SMTP server is configured
+	let notifier = notifier_from_env()
+		.map_err(|source| MainError::NotifierFromEnv { source })?;
+
+	// Charts are served again for CHART_CACHE_TTL_SECONDS, dropped once a sync of one of their repositories ends
+	let chart_cache_config = chart_cache_config_from_env()
+		.map_err(|source| MainError::ChartCacheFromEnv { source })?;
+	let chart_cache = ChartCache::new(chart_cache_config);
+
+	// Star webhook deliveries are refused until GITHUB_WEBHOOK_SECRET is set
+	let webhook_secret = webhook_secret_from_env();
+
+	// Set up the workers running every sync, 2 unless SYNC_WORKERS says otherwise
+	let sync_workers = match std::env::var("SYNC_WORKERS") {
+		Ok(workers) => workers.parse::<NonZeroUsize>().map_err(|source| MainError::SyncWorkersEnvVar { source })?.get(),
+		Err(_) => 2,
+	};
+	// Stargazers' company and location are stored unless COLLECT_STARGAZER_PROFILES=false
+	let collect_profiles = collect_stargazer_profiles_from_env()
+		.map_err(|source| MainError::CollectStargazerProfilesFromEnv { source })?;
+	// Their public emails only once COLLECT_STARGAZER_EMAILS=true, for the stargazer email export
+	let collect_emails = collect_stargazer_emails_from_env()
+		.map_err(|source| MainError::CollectStargazerEmailsFromEnv { source })?;
+	// Stars of suspected bots are only flagged once FLAG_SUSPECTED_BOTS=true
+	let flag_bots = flag_suspected_bots_from_env()
+		.map_err(|source| MainError::FlagSuspectedBotsFromEnv { source })?;
+	// Issues and pull requests of GitHub repositories only once COLLECT_REPOSITORY_ACTIVITY=true
+	let collect_activity = collect_repository_activity_from_env()
+		.map_err(|source| MainError::CollectRepositoryActivityFromEnv { source })?;
+	// Stargazer logins are only stored hashed once STARGAZER_HASH_SECRET is set
+	let stargazer_ids = stargazer_ids_from_env();
+	let star_insert_config = star_insert_config_from_env()
+		.map_err(|source| MainError::StarInsertConfigFromEnv { source })?;
+	if (collect_profiles || collect_emails) && stargazer_ids.is_hashed() {
+		info!("Stargazer logins are hashed, profiles and emails won't be collected");
+	}
+	// Other services hear of every sync job ending on SYNC_NOTIFY_CHANNEL once it is set
+	let sync_notify_channel = sync_notify_channel_from_env()
+		.map_err(|source| MainError::SyncNotifyChannelFromEnv { source })?;
+	let live_updates = match &sync_notify_channel {
+		Some(channel) => {
+			info!(channel, "Notifying sync job endings");
+			LiveUpdates::new().with_notify_channel(channel)
+		}
+		None => LiveUpdates::new(),
+	};
+	// Jobs a previous run left pending or running can't move on, they are interrupted to be resumed
+	let mut conn = db_pool.get().map_err(|source| MainError::DbConnection { source })?;
+	let interrupted = interrupt_abandoned_sync_jobs(&mut conn).map_err(|source| MainError::InterruptAbandonedSyncJobs { source })?;
+	drop(conn);
+	if interrupted > 0 {
+		info!(count = interrupted, "Interrupted sync jobs left unfinished by a previous run");
+	}
+	let sync_queue = SyncQueue::start(sync_workers, db_pool.clone(), forge_clients.clone(), notifier.clone(), live_updates.clone(), StargazerDetails { profiles: collect_profiles, emails: collect_emails, bots: flag_bots, activity: collect_activity }, stargazer_ids.clone(), chart_cache.clone());
+
+	// Star rows older than STAR_RETENTION_MONTHS are rolled up into daily counts, all are kept while it is unset
+	let star_retention = star_retention_from_env()
+		.map_err(|source| MainError::StarRetentionFromEnv { source })?;
+	if let Some(retention) = star_retention {
+		info!(months = retention.months, interval = ?retention.interval, "Compacting old stars");
+		tokio::spawn(run_compaction(db_pool.clone(), retention, sync_queue.stop_signal()));
+	}
+
+	// Sync jobs finished more than SYNC_JOB_RETENTION_HOURS ago are deleted every SYNC_JOB_CLEANUP_INTERVAL_MINUTES
+	let sync_job_retention = sync_job_retention_from_env()
+		.map_err(|source| MainError::SyncJobRetentionFromEnv { source })?;
+
+	// Sync jobs stop once they reach SYNC_MAX_PAGES, SYNC_MAX_DURATION_SECONDS or SYNC_MAX_STARS, unless their request sets its own
+	let sync_limits = sync_limits_from_env()
+		.map_err(|source| MainError::SyncLimitsFromEnv { source })?;
+
+	// Repositories listed in REPOS_FILE are registered, and queued for a first sync when the file asks for it
+	if let Some(path) = repos_file_from_env() {
+		let watch_list = read_watch_list(&path)
+			.map_err(|source| MainError::ReadWatchList { source })?;
+		load_watch_list(&db_pool, &sync_queue, &watch_list, sync_limits)
+			.await
+			.map_err(|source| MainError::LoadWatchList { source })?;
+	}
+
+	// Webhook alerts queued by finished sync jobs are posted, and retried, by every instance
+	tokio::spawn(run_outbox_dispatcher(db_pool.clone(), notifier.clone(), sync_queue.stop_signal()));
+
+	// Repositories in snapshot mode get their forge's star count recorded every STAR_SNAPSHOT_INTERVAL_HOURS
+	let snapshot_interval = star_snapshot_interval_from_env()
+		.map_err(|source| MainError::StarSnapshotIntervalFromEnv { source })?;
+	tokio::spawn(run_snapshots(db_pool.clone(), forge_clients.clone(), snapshot_interval, sync_queue.stop_signal()));
+
+	// Repositories in contributors mode get their commit authors tracked every CONTRIBUTOR_TRACKING_INTERVAL_HOURS
+	let contributor_interval = contributor_tracking_interval_from_env()
+		.map_err(|source| MainError::ContributorTrackingIntervalFromEnv { source })?;
+	tokio::spawn(run_contributor_tracking(db_pool.clone(), forge_clients.clone(), stargazer_ids.clone(), contributor_interval, sync_queue.stop_signal()));
+
+	// GitHub repositories the token can push to get their views and clones recorded every TRAFFIC_SNAPSHOT_INTERVAL_HOURS, once set
+	let traffic_interval = traffic_snapshot_interval_from_env()
+		.map_err(|source| MainError::TrafficSnapshotIntervalFromEnv { source })?;
+	if let Some(interval) = traffic_interval {
+		tokio::spawn(run_traffic_snapshots(db_pool.clone(), forge_clients.clone(), interval, sync_queue.stop_signal()));
+	}
+
+	// Weekly digests are stored and emailed at DIGEST_SCHEDULE, only generated from the CLI while it is unset
+	let digest_schedule = digest_schedule_from_env()
+		.map_err(|source| MainError::DigestScheduleFromEnv { source })?;
+	if let Some(schedule) = digest_schedule {
+		info!(weekday = %schedule.weekday, time = %schedule.time, "Scheduling weekly digests");
+		tokio::spawn(run_digests(db_pool.clone(), notifier.clone(), schedule, sync_queue.stop_signal()));
+	}
+
+	// Time given to running syncs to stop once a shutdown is requested
+	let shutdown_grace = match std::env::var("SHUTDOWN_GRACE_SECONDS") {
+		Ok(seconds) => Duration::from_secs(seconds.parse().map_err(|source| MainError::ShutdownGraceEnvVar { source })?),
+		Err(_) => Duration::from_secs(30),
+	};
  
-	// Set up the router
-	let app = Router::new()
-		.route("/github/repo_stars/update", post(github_repo_stars_update_handler))
-		.route("/github/repo_stars/read_per_day", post(github_repo_stars_read_per_day_handler))
-		.layer(Extension(db_pool.clone()));
+	// Deadline, body size cap and requests in flight shared by the routes
+	let request_limits = request_limits_from_env()
+		.map_err(|source| MainError::RequestLimitsFromEnv { source })?;
+
+	// Origins allowed to call the API from a browser, none unless CORS_ALLOWED_ORIGINS is set
+	let cors = cors_from_env()
+		.map_err(|source| MainError::CorsFromEnv { source })?;
+
+	// Requests name their tenant with an API key, anonymous ones get the default tenant unless REQUIRE_API_KEY is set
+	let tenant_auth = TenantAuth {
+		pool: db_pool.clone(),
+		require_api_key: require_api_key_from_env().map_err(|source| MainError::RequireApiKeyFromEnv { source })?,
+	};
+
+	// Effective settings shown on /admin/config, star inserts, job retention and sync limits adjustable there without a restart
+	let runtime_config = RuntimeConfig::new(
+		ServiceSettings {
+			sync_workers,
+			shutdown_grace_seconds: shutdown_grace.as_secs(),
+			request_timeout_seconds: request_limits.timeout.as_secs(),
+			max_request_body_bytes: request_limits.max_body_bytes,
+			max_concurrent_requests: request_limits.max_concurrent_requests,
+			max_concurrent_renders: request_limits.max_concurrent_renders,
+			chart_cache_ttl_seconds: chart_cache_config.ttl.as_secs(),
+			chart_cache_max_bytes: chart_cache_config.max_bytes,
+			require_api_key: tenant_auth.require_api_key,
+			github_retry_max_attempts: github_client.retry().max_attempts,
+			github_page_size: github_client.page_size().max,
+			github_min_page_size: github_client.page_size().min,
+			star_retention_months: star_retention.map(|retention| retention.months),
+			star_compaction_interval_hours: star_retention.map(|retention| retention.interval.as_secs() / 3600),
+			star_snapshot_interval_hours: snapshot_interval.as_secs() / 3600,
+			contributor_tracking_interval_hours: contributor_interval.as_secs() / 3600,
+			traffic_snapshot_interval_hours: traffic_interval.map(|interval| interval.as_secs() / 3600),
+			collect_stargazer_profiles: collect_profiles,
+			collect_stargazer_emails: collect_emails,
+			hashed_stargazer_ids: stargazer_ids.is_hashed(),
+			flag_suspected_bots: flag_bots,
+			collect_repository_activity: collect_activity,
+			sync_notify_channel,
+		},
+		AdjustableSettings { star_insert: star_insert_config, sync_job_retention, sync_limits },
+	);
+	tokio::spawn(run_job_cleanup(db_pool.clone(), runtime_config.clone(), sync_queue.stop_signal()));
+
+	// Operators need ADMIN_TOKEN, admin routes are disabled while it is unset
+	let admin_token = admin_token_from_env();
+
+	let app = build_router(RouterDependencies {
+		db_pool,
+		pool_metrics,
+		read_pool,
+		github_client,
+		forge_clients,
+		notifier,
+		webhook_secret,
+		stargazer_ids,
+		runtime_config,
+		sync_queue: sync_queue.clone(),
+		live_updates,
+		chart_cache,
+		request_limits,
+		cors,
+		tenant_auth,
+		admin_token,
+	});
 
 	let addr = SocketAddr::from(([0, 0, 0, 0], 8000));
 	let listener = tokio::net::TcpListener::bind(addr)
@@ -73,13 +413,61 @@ async fn main() -> Result<(), MainError> {
 
 	info!("Server running on addr: {}", addr);
 
-	serve(listener, app)
-		.await
-		.map_err(|source| MainError::Serve { source })?;
+	let stop = sync_queue.stop_signal();
+	let queue = sync_queue.clone();
+	let server = serve(listener, app).with_graceful_shutdown(async move {
+		shutdown_signal().await;
+		info!("Shutdown requested, stopping sync jobs");
+		queue.shutdown();
+	});
+
+	// Requests waiting on a sync are answered once it stops, the grace period bounds the wait.
+	tokio::select! {
+		result = server => result.map_err(|source| MainError::Serve { source })?,
+		_ = async { stop.stopped().await; tokio::time::sleep(shutdown_grace).await } => {
+			warn!("Grace period elapsed with requests still in flight");
+		}
+	}
+
+	if !sync_queue.drain(shutdown_grace).await {
+		warn!("Grace period elapsed before every sync job stopped");
+	}
 
+	info!("Server stopped");
 	Ok(())
 }
 
+/// Resolves on Ctrl-C, or SIGTERM on unix.
+async fn shutdown_signal() {
+	let ctrl_c = async {
+		if let Err(error) = tokio::signal::ctrl_c().await {
+			error!(%error, "Ctrl-C handler could not be installed");
+			std::future::pending::<()>().await;
+		}
+	};
+
+	#[cfg(unix)]
+	let terminate = async {
+		match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+			Ok(mut signal) => {
+				signal.recv().await;
+			}
+			Err(error) => {
+				error!(%error, "SIGTERM handler could not be installed");
+				std::future::pending::<()>().await;
+			}
+		}
+	};
+
+	#[cfg(not(unix))]
+	let terminate = std::future::pending::<()>();
+
+	tokio::select! {
+		_ = ctrl_c => {},
+		_ = terminate => {},
+	}
+}
+
 impl IntoResponse for MainError {
 	fn into_response(self) -> axum::response::Response {
 		let err = self;
@@ -0,0 +1,941 @@
+//! Command line access to the star tracker, without the HTTP server
+//!
+//! Reads the same `.env` as the service, e.g. from a cron job:
+//!
+//! ```sh
+//! star-tracker sync rust-lang/rust
+//! star-tracker sync rust-lang/rust --forks-min-stars 100
+//! star-tracker export rust-lang/rust --format csv -o rust.csv
+//! star-tracker export --tag competitors --format json -o competitors.json
+//! star-tracker graph rust-lang/rust -o rust.svg
+//! star-tracker graph rust-lang/rust --theme dark --series '#ff7b72' -o rust-dark.svg
+//! star-tracker graph rust-lang/rust --format html -o rust.html
+//! star-tracker graph rust-lang/rust --max-x-ticks 10 --rotate-x-labels -o rust.svg
+//! star-tracker tenant create acme
+//! star-tracker tenant digest-recipients acme team@acme.dev
+//! star-tracker --tenant acme sync acme/rocket
+//! star-tracker --tenant acme digest --send
+//! star-tracker compact --months 24
+//! star-tracker bench-inserts --rows 100000
+//! ```
+//!
+//! With the `sqlite` feature, a `sqlite://` `DATABASE_URL` keeps the stars in
+//! a file instead, for `sync`, `export` and `graph` of a single tenant:
+//!
+//! ```sh
+//! DATABASE_URL=sqlite://stars.db star-tracker sync rust-lang/rust
+//! ```
+
+use std::{io::Write, path::PathBuf, time::Instant};
+#[cfg(feature = "sqlite")]
+use std::pin::pin;
+
+use chrono::{NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use diesel::{r2d2::{ConnectionManager, Pool}, Connection, PgConnection};
+use dotenvy::dotenv;
+use interfaces_github_stargazers::forge::Forge;
+#[cfg(feature = "sqlite")]
+use interfaces_github_stargazers::page_size::AdaptivePageSize;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::info;
+use utils_trace::tracing_init;
+use uuid::Uuid;
+use projects_databases::chart::{render_star_history_svg, AxisOptions, ChartTheme, HexColor, MAX_TICKS, MIN_TICKS};
+use projects_databases::db::{
+	api_key::{
+		models::NewApiKey,
+		queries::{insert_api_key, InsertApiKeyError},
+	},
+	migrations::{run_pending_migrations, RunPendingMigrationsError},
+	pool::{PoolConfig, ReadPool},
+	repository::{
+		models::Repository,
+		models::NewRepository,
+		queries::{get_repository_by_name, insert_repository, list_repositories, GetRepositoryByNameError, InsertRepositoryError, ListRepositoriesError},
+	},
+	star::{
+		models::NewStar,
+		queries::{
+			get_daily_star_count, insert_missing_stars, insert_stars_batch, GetDailyStarCountError, InsertMissingStarsError,
+			InsertStarsBatchError, StarInsertConfig, StarInsertMethod, MAX_INSERT_CHUNK_SIZE,
+		},
+	},
+	sync_run::models::SyncTrigger,
+	tenant::{
+		models::{NewTenant, Tenant},
+		queries::{get_tenant_by_name, insert_tenant, set_digest_recipients, GetTenantByNameError, InsertTenantError, SetDigestRecipientsError},
+	},
+};
+use projects_databases::export::{self, day_rows, DayRow, ExportFormat, RepoDayRow, RepoName, WriteRowsError};
+use projects_databases::digest::{generate_weekly_digest, previous_week_start, send_digest, week_start, GenerateWeeklyDigestError, SendDigestError};
+use projects_databases::endpoints::tenant::{generate_api_key, hash_api_key};
+use projects_databases::env::{collect_repository_activity_from_env, collect_stargazer_emails_from_env, collect_stargazer_profiles_from_env, flag_suspected_bots_from_env, db_pool_from_env, forge_clients_from_env, github_client_from_env, notifier_from_env, read_pool_from_env, star_insert_config_from_env, star_retention_from_env, stargazer_ids_from_env, storage_backend_from_env, sync_limits_from_env, sync_notify_channel_from_env, CollectRepositoryActivityFromEnvError, CollectStargazerEmailsFromEnvError, CollectStargazerProfilesFromEnvError, FlagSuspectedBotsFromEnvError, DbPoolFromEnvError, ForgeClientsFromEnvError, GitHubClientFromEnvError, NotifierFromEnvError, ReadPoolFromEnvError, StarInsertConfigFromEnvError, StarRetentionFromEnvError, StorageBackendFromEnvError, SyncLimitsFromEnvError, SyncNotifyChannelFromEnvError};
+use projects_databases::chart_cache::ChartCache;
+use projects_databases::forges::ForgeClients;
+#[cfg(feature = "sqlite")]
+use projects_databases::forges::ForgeClientError;
+use projects_databases::live::{LiveUpdates, PageSink};
+use projects_databases::retention::{compact_old_stars, compaction_cutoff, CompactOldStarsError};
+use projects_databases::vega::{render_star_history_html, star_history_vega_lite};
+use projects_databases::storage::StorageBackend;
+#[cfg(feature = "sqlite")]
+use projects_databases::storage::{
+	sqlite::{OpenSqliteStorageError, SqliteStorage},
+	NewStoredRepository, NewStoredStar, StarStorage, StorageError,
+};
+use projects_databases::sync_queue::{RunSyncTaskError, StargazerDetails, SyncPriority, SyncQueue, SyncTask};
+use projects_databases::validation::validate_email;
+
+pub type PgPool = Pool<ConnectionManager<PgConnection>>;
+
+#[derive(Debug, Error)]
+pub enum CliError {
+	#[error("TracingInit: {source}")]
+	TracingInit {
+		#[source]
+		source: utils_trace::TracingInitError,
+	},
+	#[error("EnvVarSetup: {source}")]
+	EnvVarSetup {
+		#[source]
+		source: dotenvy::Error,
+	},
+	#[error("StorageBackendFromEnv: {source}")]
+	StorageBackendFromEnv {
+		#[source]
+		source: StorageBackendFromEnvError,
+	},
+	#[cfg(feature = "sqlite")]
+	#[error("OpenSqliteStorage: {source}")]
+	OpenSqliteStorage {
+		#[source]
+		source: OpenSqliteStorageError,
+	},
+	#[cfg(feature = "sqlite")]
+	#[error("Storage: {source}")]
+	Storage {
+		#[source]
+		source: StorageError,
+	},
+	#[cfg(feature = "sqlite")]
+	#[error("RequiresPostgres: {command} needs a postgres:// DATABASE_URL")]
+	RequiresPostgres {
+		command: &'static str,
+	},
+	#[cfg(feature = "sqlite")]
+	#[error("FetchStargazers: {source}")]
+	FetchStargazers {
+		#[source]
+		source: ForgeClientError,
+	},
+	#[cfg(feature = "sqlite")]
+	#[error("RepositoryNotOnForge: {repo} on {forge}")]
+	RepositoryNotOnForge {
+		repo: RepoName,
+		forge: Forge,
+	},
+	#[error("DbPoolFromEnv: {source}")]
+	DbPoolFromEnv {
+		#[source]
+		source: DbPoolFromEnvError,
+	},
+	#[error("ReadPoolFromEnv: {source}")]
+	ReadPoolFromEnv {
+		#[source]
+		source: ReadPoolFromEnvError,
+	},
+	#[error("DbConnection: {source}")]
+	DbConnection {
+		#[source]
+		source: r2d2::Error,
+	},
+	#[error("RunPendingMigrations: {source}")]
+	RunPendingMigrations {
+		#[source]
+		source: RunPendingMigrationsError,
+	},
+	#[error("GitHubClientFromEnv: {source}")]
+	GitHubClientFromEnv {
+		#[source]
+		source: GitHubClientFromEnvError,
+	},
+	#[error("ForgeClientsFromEnv: {source}")]
+	ForgeClientsFromEnv {
+		#[source]
+		source: ForgeClientsFromEnvError,
+	},
+	#[error("ForgeNotConfigured: {forge}")]
+	ForgeNotConfigured {
+		forge: Forge,
+	},
+	#[error("NotifierFromEnv: {source}")]
+	NotifierFromEnv {
+		#[source]
+		source: NotifierFromEnvError,
+	},
+	#[error("SyncLimitsFromEnv: {source}")]
+	SyncLimitsFromEnv {
+		#[source]
+		source: SyncLimitsFromEnvError,
+	},
+	#[error("CollectStargazerProfilesFromEnv: {source}")]
+	CollectStargazerProfilesFromEnv {
+		#[source]
+		source: CollectStargazerProfilesFromEnvError,
+	},
+	#[error("CollectStargazerEmailsFromEnv: {source}")]
+	CollectStargazerEmailsFromEnv {
+		#[source]
+		source: CollectStargazerEmailsFromEnvError,
+	},
+	#[error("FlagSuspectedBotsFromEnv: {source}")]
+	FlagSuspectedBotsFromEnv {
+		#[source]
+		source: FlagSuspectedBotsFromEnvError,
+	},
+	#[error("CollectRepositoryActivityFromEnv: {source}")]
+	CollectRepositoryActivityFromEnv {
+		#[source]
+		source: CollectRepositoryActivityFromEnvError,
+	},
+	#[error("SyncNotifyChannelFromEnv: {source}")]
+	SyncNotifyChannelFromEnv {
+		#[source]
+		source: SyncNotifyChannelFromEnvError,
+	},
+	#[error("RunSyncTask: {source}")]
+	RunSyncTask {
+		#[source]
+		source: RunSyncTaskError,
+	},
+	#[error("SyncWorkerGone")]
+	SyncWorkerGone,
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[source]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {repo}")]
+	RepositoryNotInDatabase {
+		repo: RepoName,
+	},
+	#[error("ListRepositories: {source}")]
+	ListRepositories {
+		#[source]
+		source: ListRepositoriesError,
+	},
+	#[error("NoRepositoryTagged: {tag}")]
+	NoRepositoryTagged {
+		tag: String,
+	},
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
+		#[source]
+		source: GetDailyStarCountError,
+	},
+	#[error("WriteRows: {source}")]
+	WriteRows {
+		#[source]
+		source: WriteRowsError,
+	},
+	#[error("OutputWrite: {source}")]
+	OutputWrite {
+		#[source]
+		source: std::io::Error,
+	},
+	#[error("GetTenantByName: {source}")]
+	GetTenantByName {
+		#[source]
+		source: GetTenantByNameError,
+	},
+	#[error("TenantNotFound: {name}")]
+	TenantNotFound {
+		name: String,
+	},
+	#[error("InsertTenant: {source}")]
+	InsertTenant {
+		#[source]
+		source: InsertTenantError,
+	},
+	#[error("GenerateApiKey")]
+	GenerateApiKey,
+	#[error("InsertApiKey: {source}")]
+	InsertApiKey {
+		#[source]
+		source: InsertApiKeyError,
+	},
+	#[error("InvalidEmail: {email}: {message}")]
+	InvalidEmail {
+		email: String,
+		message: String,
+	},
+	#[error("SetDigestRecipients: {source}")]
+	SetDigestRecipients {
+		#[source]
+		source: SetDigestRecipientsError,
+	},
+	#[error("GenerateWeeklyDigest: {source}")]
+	GenerateWeeklyDigest {
+		#[source]
+		source: GenerateWeeklyDigestError,
+	},
+	#[error("NoRepositoryTracked: {tenant}")]
+	NoRepositoryTracked {
+		tenant: String,
+	},
+	#[error("NoDigestRecipients: {tenant}, set them with `tenant digest-recipients`")]
+	NoDigestRecipients {
+		tenant: String,
+	},
+	#[error("SmtpNotConfigured: set SMTP_URL to email digests")]
+	SmtpNotConfigured,
+	#[error("SendDigest: {source}")]
+	SendDigest {
+		#[source]
+		source: SendDigestError,
+	},
+	#[error("StarRetentionFromEnv: {source}")]
+	StarRetentionFromEnv {
+		#[source]
+		source: StarRetentionFromEnvError,
+	},
+	#[error("StarInsertConfigFromEnv: {source}")]
+	StarInsertConfigFromEnv {
+		#[source]
+		source: StarInsertConfigFromEnvError,
+	},
+	#[error("RetentionNotSet: pass --months or set STAR_RETENTION_MONTHS")]
+	RetentionNotSet,
+	#[error("CompactOldStars: {source}")]
+	CompactOldStars {
+		#[source]
+		source: CompactOldStarsError,
+	},
+	#[error("BeginBenchTransaction: {source}")]
+	BeginBenchTransaction {
+		#[source]
+		source: diesel::result::Error,
+	},
+	#[error("InsertRepository: {source}")]
+	InsertRepository {
+		#[source]
+		source: InsertRepositoryError,
+	},
+	#[error("InsertStarsBatch: {source}")]
+	InsertStarsBatch {
+		#[source]
+		source: InsertStarsBatchError,
+	},
+	#[error("InsertMissingStars: {source}")]
+	InsertMissingStars {
+		#[source]
+		source: InsertMissingStarsError,
+	},
+}
+
+#[derive(Parser)]
+#[command(name = "star-tracker", about = "Sync and export GitHub star history without the HTTP server")]
+struct Cli {
+	/// Tenant whose repositories are synced, exported or graphed
+	#[arg(long, global = true, default_value = "default")]
+	tenant: String,
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Fetches the repository's stargazers from its forge and stores them
+	Sync {
+		repo: RepoName,
+		/// `github`, `gitlab` or `gitea`
+		#[arg(long, default_value_t = Forge::GitHub)]
+		forge: Forge,
+		/// Also syncs its forks with at least this many stars, now and on its later syncs
+		#[arg(long)]
+		forks_min_stars: Option<u32>,
+	},
+	/// Writes the stored daily star counts, with the running total
+	Export {
+		#[arg(required_unless_present = "tag")]
+		repo: Option<RepoName>,
+		/// Exports every repository with this tag instead, each row naming its repository
+		#[arg(long, conflicts_with = "repo")]
+		tag: Option<String>,
+		#[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+		format: ExportFormat,
+		/// Written to stdout when unset
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+	},
+	/// Draws the stored star history as a line chart
+	Graph {
+		repo: RepoName,
+		#[arg(long, value_enum, default_value_t = GraphFormat::Svg)]
+		format: GraphFormat,
+		/// `chart.svg`, `chart.vl.json` or `chart.html` when unset, depending on the format
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+		#[command(flatten)]
+		theme: ThemeArgs,
+		#[command(flatten)]
+		axes: AxisArgs,
+	},
+	/// Manages the tenants sharing the service and their API keys
+	Tenant {
+		#[command(subcommand)]
+		command: TenantCommand,
+	},
+	/// Generates the tenant's weekly digest, stored as a report, unless it already was
+	Digest {
+		/// Any day of the week covered, the last full week when unset
+		#[arg(long)]
+		week_of: Option<NaiveDate>,
+		/// Also emails it to the tenant's digest recipients, even when it already was
+		#[arg(long)]
+		send: bool,
+		/// Writes the digest as HTML, charts inlined as SVG
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+	},
+	/// Rolls old star rows of every tenant up into daily counts and deletes them
+	Compact {
+		/// Months of star rows kept, `STAR_RETENTION_MONTHS` when unset
+		#[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+		months: Option<u32>,
+	},
+	/// Times INSERT against COPY on synthetic stars, in a transaction rolled back afterwards
+	BenchInserts {
+		/// Stars written by each run
+		#[arg(long, default_value_t = 100_000, value_parser = clap::value_parser!(u32).range(1..=10_000_000))]
+		rows: u32,
+		/// Rows per statement, `STAR_INSERT_CHUNK_SIZE` when unset
+		#[arg(long, value_parser = clap::value_parser!(u16).range(1..=MAX_INSERT_CHUNK_SIZE as i64))]
+		chunk_size: Option<u16>,
+	},
+}
+
+#[derive(Subcommand)]
+enum TenantCommand {
+	/// Creates a tenant and prints its first API key
+	Create {
+		name: String,
+	},
+	/// Prints a new API key for the tenant, its other keys keep working
+	AddKey {
+		name: String,
+	},
+	/// Replaces the addresses the tenant's weekly digest is emailed to, none to stop emailing it
+	DigestRecipients {
+		name: String,
+		emails: Vec<String>,
+	},
+}
+
+/// Chart colors: a base theme, any of whose colors can be replaced by a `#rrggbb` value.
+#[derive(Args)]
+struct ThemeArgs {
+	#[arg(long, value_enum, default_value_t = ThemeName::Light)]
+	theme: ThemeName,
+	#[arg(long)]
+	background: Option<HexColor>,
+	#[arg(long)]
+	grid: Option<HexColor>,
+	#[arg(long)]
+	text: Option<HexColor>,
+	/// Comma-separated line colors
+	#[arg(long, value_delimiter = ',')]
+	series: Vec<HexColor>,
+}
+
+/// Axis labelling of SVG charts, the other formats laying out their own axes.
+#[derive(Args)]
+struct AxisArgs {
+	/// Date labels at most
+	#[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u8).range(MIN_TICKS as i64..=MAX_TICKS as i64))]
+	max_x_ticks: u8,
+	/// Star count labels at most
+	#[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u8).range(MIN_TICKS as i64..=MAX_TICKS as i64))]
+	max_y_ticks: u8,
+	/// Writes the date labels at an angle
+	#[arg(long)]
+	rotate_x_labels: bool,
+}
+
+impl AxisArgs {
+	fn options(&self) -> AxisOptions {
+		AxisOptions { max_x_ticks: self.max_x_ticks.into(), max_y_ticks: self.max_y_ticks.into(), rotate_x_labels: self.rotate_x_labels }
+	}
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ThemeName {
+	Light,
+	Dark,
+}
+
+impl ThemeArgs {
+	fn into_theme(self) -> ChartTheme {
+		let mut theme = match self.theme {
+			ThemeName::Light => ChartTheme::light(),
+			ThemeName::Dark => ChartTheme::dark(),
+		};
+		if let Some(background) = self.background {
+			theme.background = background;
+		}
+		if let Some(grid) = self.grid {
+			theme.grid = grid;
+		}
+		if let Some(text) = self.text {
+			theme.text = text;
+		}
+		if !self.series.is_empty() {
+			theme.series = self.series;
+		}
+		theme
+	}
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GraphFormat {
+	/// Static image
+	Svg,
+	/// Vega-Lite spec, with tooltips and zooming once embedded
+	VegaLite,
+	/// Page rendering the Vega-Lite spec in a browser
+	Html,
+}
+
+impl GraphFormat {
+	fn default_output(self) -> PathBuf {
+		match self {
+			GraphFormat::Svg => "chart.svg".into(),
+			GraphFormat::VegaLite => "chart.vl.json".into(),
+			GraphFormat::Html => "chart.html".into(),
+		}
+	}
+}
+
+#[tokio::main]
+async fn main() -> Result<(), CliError> {
+	let cli = Cli::parse();
+
+	tracing_init("info")
+		.map_err(|source| CliError::TracingInit { source })?;
+
+	// A missing .env is fine when the variables come from the environment, e.g. under cron
+	if let Err(source) = dotenv() {
+		if !source.not_found() {
+			return Err(CliError::EnvVarSetup { source });
+		}
+	}
+
+	match storage_backend_from_env().map_err(|source| CliError::StorageBackendFromEnv { source })? {
+		StorageBackend::Postgres => {}
+		#[cfg(feature = "sqlite")]
+		StorageBackend::Sqlite { path } => return run_on_sqlite(cli, &path).await,
+	}
+
+	let (db_pool, _) = db_pool_from_env(PoolConfig { max_size: 2, ..PoolConfig::default() })
+		.map_err(|source| CliError::DbPoolFromEnv { source })?;
+
+	let mut conn = db_pool.get().map_err(|source| CliError::DbConnection { source })?;
+	run_pending_migrations(&mut conn).map_err(|source| CliError::RunPendingMigrations { source })?;
+	drop(conn);
+
+	match cli.command {
+		Command::Tenant { command } => return manage_tenant(&db_pool, command),
+		Command::Compact { months } => return compact(&db_pool, months),
+		Command::BenchInserts { rows, chunk_size } => return bench_inserts(&db_pool, &cli.tenant, rows, chunk_size),
+		Command::Digest { week_of, send, output } => return digest(&db_pool, &cli.tenant, week_of, send, output.as_ref()).await,
+		_ => {}
+	}
+	let tenant_id = tenant_id(&db_pool, &cli.tenant)?;
+	// Exports and graphs read from the DATABASE_URL_RO replica when set
+	let read_pool = read_pool_from_env(db_pool.clone(), PoolConfig { max_size: 2, ..PoolConfig::default() })
+		.map_err(|source| CliError::ReadPoolFromEnv { source })?;
+
+	match cli.command {
+		Command::Sync { repo, forge, forks_min_stars } => sync(db_pool, tenant_id, forge, repo, forks_min_stars).await,
+		Command::Export { repo, tag, format, output } => match (repo, tag) {
+			(Some(repo), _) => write_rows(&load_days(&read_pool, tenant_id, &repo).await?, format, output.as_ref()),
+			(None, Some(tag)) => write_rows(&load_tagged_days(&read_pool, tenant_id, &tag).await?, format, output.as_ref()),
+			// clap requires one of them
+			(None, None) => Ok(()),
+		},
+		Command::Graph { repo, format, output, theme, axes } => {
+			write_graph(&repo, &load_days(&read_pool, tenant_id, &repo).await?, format, output, theme, &axes)
+		}
+		Command::Tenant { .. } | Command::Compact { .. } | Command::Digest { .. } | Command::BenchInserts { .. } => Ok(()),
+	}
+}
+
+/// Runs `sync`, `export` and `graph` on the SQLite file at `path`. It holds the default tenant
+/// only and none of tags, forks or digests, the other commands need PostgreSQL.
+#[cfg(feature = "sqlite")]
+async fn run_on_sqlite(cli: Cli, path: &str) -> Result<(), CliError> {
+	if cli.tenant != "default" {
+		return Err(CliError::RequiresPostgres { command: "--tenant" });
+	}
+	let storage = SqliteStorage::open(path)
+		.map_err(|source| CliError::OpenSqliteStorage { source })?;
+
+	match cli.command {
+		Command::Sync { repo, forge, forks_min_stars: None } => sync_to_storage(&storage, forge, repo).await,
+		Command::Sync { .. } => Err(CliError::RequiresPostgres { command: "sync --forks-min-stars" }),
+		Command::Export { repo: Some(repo), format, output, .. } => {
+			write_rows(&load_stored_days(&storage, &repo).await?, format, output.as_ref())
+		}
+		Command::Export { .. } => Err(CliError::RequiresPostgres { command: "export --tag" }),
+		Command::Graph { repo, format, output, theme, axes } => {
+			write_graph(&repo, &load_stored_days(&storage, &repo).await?, format, output, theme, &axes)
+		}
+		Command::Tenant { .. } => Err(CliError::RequiresPostgres { command: "tenant" }),
+		Command::Digest { .. } => Err(CliError::RequiresPostgres { command: "digest" }),
+		Command::Compact { .. } => Err(CliError::RequiresPostgres { command: "compact" }),
+		Command::BenchInserts { .. } => Err(CliError::RequiresPostgres { command: "bench-inserts" }),
+	}
+}
+
+fn tenant_id(db_pool: &PgPool, name: &str) -> Result<Uuid, CliError> {
+	tenant(db_pool, name).map(|tenant| tenant.id)
+}
+
+fn tenant(db_pool: &PgPool, name: &str) -> Result<Tenant, CliError> {
+	let mut conn = db_pool.get().map_err(|source| CliError::DbConnection { source })?;
+
+	get_tenant_by_name(&mut conn, name)
+		.map_err(|source| CliError::GetTenantByName { source })?
+		.ok_or_else(|| CliError::TenantNotFound { name: name.into() })
+}
+
+/// Creates the tenant or looks it up, then prints a new API key for it, or sets its digest recipients.
+fn manage_tenant(db_pool: &PgPool, command: TenantCommand) -> Result<(), CliError> {
+	match command {
+		TenantCommand::Create { name } => {
+			let mut conn = db_pool.get().map_err(|source| CliError::DbConnection { source })?;
+			let tenant_id = insert_tenant(&mut conn, &NewTenant { id: Uuid::new_v4(), name: &name })
+				.map_err(|source| CliError::InsertTenant { source })?
+				.id;
+			print_new_api_key(db_pool, tenant_id)
+		}
+		TenantCommand::AddKey { name } => print_new_api_key(db_pool, tenant_id(db_pool, &name)?),
+		TenantCommand::DigestRecipients { name, emails } => {
+			if let Some((email, message)) = emails.iter().find_map(|email| validate_email(email).err().map(|message| (email, message))) {
+				return Err(CliError::InvalidEmail { email: email.clone(), message });
+			}
+			let tenant_id = tenant_id(db_pool, &name)?;
+			let mut conn = db_pool.get().map_err(|source| CliError::DbConnection { source })?;
+			let tenant = set_digest_recipients(&mut conn, tenant_id, &emails)
+				.map_err(|source| CliError::SetDigestRecipients { source })?;
+			info!(tenant = %tenant.name, recipients = ?tenant.digest_recipients, "Digest recipients set");
+			Ok(())
+		}
+	}
+}
+
+/// Prints a new API key for the tenant. Only the key's hash is stored.
+fn print_new_api_key(db_pool: &PgPool, tenant_id: Uuid) -> Result<(), CliError> {
+	let key = generate_api_key().map_err(|_| CliError::GenerateApiKey)?;
+	let mut conn = db_pool.get().map_err(|source| CliError::DbConnection { source })?;
+	insert_api_key(&mut conn, &NewApiKey { id: Uuid::new_v4(), tenant_id, key_hash: &hash_api_key(&key) })
+		.map_err(|source| CliError::InsertApiKey { source })?;
+
+	let mut out = std::io::stdout().lock();
+	writeln!(out, "{key}").map_err(|source| CliError::OutputWrite { source })
+}
+
+/// Generates the digest of the week containing `week_of`, the last full week by default, and prints its report ID.
+async fn digest(db_pool: &PgPool, tenant_name: &str, week_of: Option<NaiveDate>, send: bool, output: Option<&PathBuf>) -> Result<(), CliError> {
+	let tenant = tenant(db_pool, tenant_name)?;
+	let week_start = week_of.map_or_else(|| previous_week_start(Utc::now().date_naive()), week_start);
+
+	let mut conn = db_pool.get().map_err(|source| CliError::DbConnection { source })?;
+	let generated = generate_weekly_digest(&mut conn, tenant.id, week_start)
+		.map_err(|source| CliError::GenerateWeeklyDigest { source })?
+		.ok_or_else(|| CliError::NoRepositoryTracked { tenant: tenant.name.clone() })?;
+	info!(%week_start, report_id = %generated.report.id, created = generated.created, "Digest generated");
+
+	if let Some(path) = output {
+		std::fs::write(path, &generated.report.html)
+			.map_err(|source| CliError::OutputWrite { source })?;
+		info!(path = %path.display(), "Digest written");
+	}
+
+	if send {
+		if tenant.digest_recipients.is_empty() {
+			return Err(CliError::NoDigestRecipients { tenant: tenant.name });
+		}
+		let notifier = notifier_from_env()
+			.map_err(|source| CliError::NotifierFromEnv { source })?;
+		let email = notifier.email().ok_or(CliError::SmtpNotConfigured)?;
+		let sent = send_digest(&mut conn, email, &generated, &tenant.digest_recipients)
+			.await
+			.map_err(|source| CliError::SendDigest { source })?;
+		info!(recipients = ?sent, "Digest sent");
+	}
+
+	let mut out = std::io::stdout().lock();
+	writeln!(out, "{}", generated.report.id).map_err(|source| CliError::OutputWrite { source })
+}
+
+/// Compacts the stars older than `months`, or than `STAR_RETENTION_MONTHS`, once.
+fn compact(db_pool: &PgPool, months: Option<u32>) -> Result<(), CliError> {
+	let months = match months {
+		Some(months) => months,
+		None => star_retention_from_env()
+			.map_err(|source| CliError::StarRetentionFromEnv { source })?
+			.ok_or(CliError::RetentionNotSet)?
+			.months,
+	};
+
+	let before = compaction_cutoff(Utc::now().date_naive(), months);
+	let summary = compact_old_stars(db_pool, before)
+		.map_err(|source| CliError::CompactOldStars { source })?;
+
+	info!(%before, repositories = summary.repositories, stars = summary.stars, failed = summary.failed, "Compaction done");
+	Ok(())
+}
+
+/// Writes `rows` synthetic stars through both bulk paths with INSERT then COPY, each run into a
+/// repository of its own, and logs the throughput. Nothing is committed.
+fn bench_inserts(db_pool: &PgPool, tenant_name: &str, rows: u32, chunk_size: Option<u16>) -> Result<(), CliError> {
+	let tenant_id = tenant_id(db_pool, tenant_name)?;
+	let mut base = star_insert_config_from_env()
+		.map_err(|source| CliError::StarInsertConfigFromEnv { source })?;
+	if let Some(chunk_size) = chunk_size {
+		base.chunk_size = chunk_size.into();
+	}
+
+	let mut conn = db_pool.get().map_err(|source| CliError::DbConnection { source })?;
+	conn.begin_test_transaction()
+		.map_err(|source| CliError::BeginBenchTransaction { source })?;
+
+	let stargazers: Vec<String> = (0..rows).map(|n| format!("bench-stargazer-{n}")).collect();
+	let now = Utc::now().naive_utc();
+
+	for path in ["insert_stars_batch", "insert_missing_stars"] {
+		for method in [StarInsertMethod::Insert, StarInsertMethod::Copy] {
+			let id = Uuid::new_v4();
+			let name = format!("bench-{id}");
+			let repo = insert_repository(&mut conn, &NewRepository {
+				id,
+				owner: "star-tracker-bench",
+				name: &name,
+				description: None,
+				primary_language: None,
+				license: None,
+				topics: Vec::new(),
+				github_created_at: None,
+				tenant_id,
+				github_node_id: None,
+				forge: Forge::GitHub.as_str(),
+			})
+				.map_err(|source| CliError::InsertRepository { source })?;
+			let new_stars: Vec<NewStar> = stargazers
+				.iter()
+				.enumerate()
+				.map(|(n, stargazer)| NewStar { repository_id: repo.id, stargazer, starred_at: now - chrono::Duration::seconds(n as i64), fetched_at: now, first_fetched_at: now, suspected_bot: false })
+				.collect();
+			let config = StarInsertConfig { method, ..base };
+
+			let started = Instant::now();
+			let written = match path {
+				"insert_stars_batch" => insert_stars_batch(&mut conn, &new_stars, &config)
+					.map_err(|source| CliError::InsertStarsBatch { source })?,
+				_ => insert_missing_stars(&mut conn, &new_stars, &config)
+					.map_err(|source| CliError::InsertMissingStars { source })?,
+			};
+			let elapsed = started.elapsed();
+
+			let rows_per_sec = (f64::from(rows) / elapsed.as_secs_f64()).round();
+			info!(path, ?method, rows, written, chunk_size = config.chunk_size, elapsed_ms = elapsed.as_millis() as u64, rows_per_sec, "Bench run done");
+		}
+	}
+	// Dropping the connection rolls the bench transaction back
+	Ok(())
+}
+
+/// Runs the sync through a single-worker queue, so Ctrl-C stops it after the current page.
+async fn sync(db_pool: PgPool, tenant_id: Uuid, forge: Forge, repo: RepoName, forks_min_stars: Option<u32>) -> Result<(), CliError> {
+	let forge_clients = forge_clients_for(forge)?;
+
+	let notifier = notifier_from_env()
+		.map_err(|source| CliError::NotifierFromEnv { source })?;
+
+	let collect_profiles = collect_stargazer_profiles_from_env()
+		.map_err(|source| CliError::CollectStargazerProfilesFromEnv { source })?;
+
+	let collect_emails = collect_stargazer_emails_from_env()
+		.map_err(|source| CliError::CollectStargazerEmailsFromEnv { source })?;
+
+	let flag_bots = flag_suspected_bots_from_env()
+		.map_err(|source| CliError::FlagSuspectedBotsFromEnv { source })?;
+
+	let collect_activity = collect_repository_activity_from_env()
+		.map_err(|source| CliError::CollectRepositoryActivityFromEnv { source })?;
+
+	let limits = sync_limits_from_env()
+		.map_err(|source| CliError::SyncLimitsFromEnv { source })?;
+
+	let live_updates = match sync_notify_channel_from_env().map_err(|source| CliError::SyncNotifyChannelFromEnv { source })? {
+		Some(channel) => LiveUpdates::new().with_notify_channel(&channel),
+		None => LiveUpdates::new(),
+	};
+
+	let queue = SyncQueue::start(1, db_pool, forge_clients, notifier, live_updates, StargazerDetails { profiles: collect_profiles, emails: collect_emails, bots: flag_bots, activity: collect_activity }, stargazer_ids_from_env(), ChartCache::default());
+	let task = SyncTask::Repository { tenant_id, forge, owner: repo.owner.clone(), name: repo.name.clone(), batch_id: None, trigger: SyncTrigger::Cli, forks_min_stars, pages: PageSink::default(), limits };
+	let mut result = queue.enqueue(task, SyncPriority::Normal);
+
+	let outcome = tokio::select! {
+		outcome = &mut result => outcome,
+		_ = tokio::signal::ctrl_c() => {
+			info!("Interrupted, stopping after the current page");
+			queue.shutdown();
+			result.await
+		}
+	};
+
+	let summary = outcome
+		.map_err(|_| CliError::SyncWorkerGone)?
+		.map_err(|source| CliError::RunSyncTask { source })?;
+
+	info!(%repo, job_id = %summary.job_id, pages = summary.pages, stars = summary.stars, retries = summary.retries, forks = summary.forks.len(), stop_reason = summary.stop_reason.map(|reason| reason.as_str()), "Sync done");
+	Ok(())
+}
+
+/// Clients of every forge, `forge` having what it needs to be called.
+fn forge_clients_for(forge: Forge) -> Result<ForgeClients, CliError> {
+	let github_client = github_client_from_env()
+		.map_err(|source| CliError::GitHubClientFromEnv { source })?;
+	let forge_clients = forge_clients_from_env(github_client)
+		.map_err(|source| CliError::ForgeClientsFromEnv { source })?;
+	if !forge_clients.is_available(forge) {
+		return Err(CliError::ForgeNotConfigured { forge });
+	}
+	Ok(forge_clients)
+}
+
+/// Fetches every stargazer of the repository into `storage`, Ctrl-C stopping it before the next
+/// page. Stars given since the last sync are added, the removed ones kept.
+#[cfg(feature = "sqlite")]
+async fn sync_to_storage(storage: &impl StarStorage, forge: Forge, repo: RepoName) -> Result<(), CliError> {
+	let forge_clients = forge_clients_for(forge)?;
+	let stargazer_ids = stargazer_ids_from_env();
+	let stored = storage.insert_repository(NewStoredRepository { owner: &repo.owner, name: &repo.name, forge })
+		.await
+		.map_err(|source| CliError::Storage { source })?;
+
+	let page_size = AdaptivePageSize::new(forge_clients.page_size()).current();
+	let mut interrupted = pin!(tokio::signal::ctrl_c());
+	let (mut cursor, mut pages, mut stars) = (None, 0, 0);
+	loop {
+		let page = tokio::select! {
+			page = forge_clients.fetch_stargazers(forge, &repo.owner, &repo.name, cursor.as_deref(), page_size, false) => page
+				.map_err(|source| CliError::FetchStargazers { source })?
+				.ok_or_else(|| CliError::RepositoryNotOnForge { repo: repo.clone(), forge })?,
+			_ = &mut interrupted => {
+				info!("Interrupted, the stored pages are kept");
+				break;
+			}
+		};
+
+		let ids: Vec<_> = page.stargazers.iter().map(|stargazer| stargazer_ids.stargazer_id(&stargazer.login)).collect();
+		let new: Vec<NewStoredStar> = page.stargazers
+			.iter()
+			.zip(&ids)
+			.map(|(stargazer, id)| NewStoredStar { stargazer: id, starred_at: stargazer.starred_at.naive_utc() })
+			.collect();
+		stars += storage.insert_stars_batch(stored.id, &new)
+			.await
+			.map_err(|source| CliError::Storage { source })?;
+		pages += 1;
+
+		if !page.has_next_page {
+			break;
+		}
+		cursor = page.end_cursor;
+	}
+
+	info!(%repo, pages, stars, "Sync done");
+	Ok(())
+}
+
+/// [`load_days`] from a [`StarStorage`].
+#[cfg(feature = "sqlite")]
+async fn load_stored_days(storage: &impl StarStorage, repo: &RepoName) -> Result<Vec<DayRow>, CliError> {
+	let stored = storage.get_repository_by_name(&repo.owner, &repo.name)
+		.await
+		.map_err(|source| CliError::Storage { source })?
+		.ok_or_else(|| CliError::RepositoryNotInDatabase { repo: repo.clone() })?;
+
+	let daily = storage.get_daily_star_count(stored.id)
+		.await
+		.map_err(|source| CliError::Storage { source })?;
+	Ok(day_rows(&daily))
+}
+
+/// Every day from the first star to the last, with its stars and the running total.
+async fn load_days(read_pool: &ReadPool, tenant_id: Uuid, repo: &RepoName) -> Result<Vec<DayRow>, CliError> {
+	let mut conn = read_pool.get().map_err(|source| CliError::DbConnection { source })?;
+
+	let stored = get_repository_by_name(&mut conn, tenant_id, &repo.owner, &repo.name)
+		.await
+		.map_err(|source| CliError::GetRepositoryByName { source })?
+		.ok_or_else(|| CliError::RepositoryNotInDatabase { repo: repo.clone() })?;
+
+	let daily = get_daily_star_count(&mut conn, stored.id)
+		.map_err(|source| CliError::GetDailyStarCount { source })?;
+	Ok(day_rows(&daily))
+}
+
+/// The days of every repository tagged `tag`, one repository after the other.
+async fn load_tagged_days(read_pool: &ReadPool, tenant_id: Uuid, tag: &str) -> Result<Vec<RepoDayRow>, CliError> {
+	let repositories: Vec<Repository> = {
+		let mut conn = read_pool.get().map_err(|source| CliError::DbConnection { source })?;
+		list_repositories(&mut conn, tenant_id, Some(&tag.to_ascii_lowercase()))
+			.map_err(|source| CliError::ListRepositories { source })?
+	};
+	if repositories.is_empty() {
+		return Err(CliError::NoRepositoryTagged { tag: tag.into() });
+	}
+
+	let mut rows = Vec::new();
+	for stored in repositories {
+		let repo = RepoName { owner: stored.owner, name: stored.name };
+		let days = load_days(read_pool, tenant_id, &repo).await?;
+		rows.extend(days.into_iter().map(|day| RepoDayRow::new(&repo, day)));
+	}
+	Ok(rows)
+}
+
+fn write_graph(repo: &RepoName, rows: &[DayRow], format: GraphFormat, output: Option<PathBuf>, theme: ThemeArgs, axes: &AxisArgs) -> Result<(), CliError> {
+	let cumulative: Vec<(NaiveDate, i64)> = rows.iter().map(|row| (row.day, row.total)).collect();
+	let (title, theme) = (format!("{repo} stars"), theme.into_theme());
+	let chart = match format {
+		GraphFormat::Svg => render_star_history_svg(&title, &cumulative, &theme, &axes.options()),
+		GraphFormat::VegaLite => star_history_vega_lite(&title, &cumulative, &theme).to_string(),
+		GraphFormat::Html => render_star_history_html(&title, &cumulative, &theme),
+	};
+	let output = output.unwrap_or_else(|| format.default_output());
+	std::fs::write(&output, chart)
+		.map_err(|source| CliError::OutputWrite { source })?;
+	info!(path = %output.display(), "Chart written");
+	Ok(())
+}
+
+fn write_rows<T: Serialize>(rows: &[T], format: ExportFormat, output: Option<&PathBuf>) -> Result<(), CliError> {
+	export::write_rows(rows, format, open_output(output)?)
+		.map_err(|source| CliError::WriteRows { source })
+}
+
+fn open_output(path: Option<&PathBuf>) -> Result<Box<dyn Write>, CliError> {
+	match path {
+		Some(path) => std::fs::File::create(path)
+			.map(|file| Box::new(std::io::BufWriter::new(file)) as Box<dyn Write>)
+			.map_err(|source| CliError::OutputWrite { source }),
+		None => Ok(Box::new(std::io::stdout().lock())),
+	}
+}
@@ -0,0 +1,104 @@
+//! Suspected bot stargazers
+//!
+//! Bots and throwaway accounts starring repositories inflate their metrics.
+//! Once `FLAG_SUSPECTED_BOTS=true`, syncs flag the stars of the stargazers
+//! that look like one, and the star series read endpoints leave them out when
+//! asked with `?exclude_bots=true`. Stars stay stored and counted otherwise.
+//!
+//! A stargazer is suspected when either heuristic matches:
+//! - its login, see [`login_looks_automated`]
+//! - on GitHub, an account created less than [`MIN_ACCOUNT_AGE`] before
+//!   starring, looked up with one extra GraphQL query per page
+//!
+//! A flag is never lifted by a later sync, only set. Stars stored before
+//! flagging was turned on are flagged by the next full sync seeing them.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use interfaces_github_stargazers::{account_ages::fetch_account_creation_dates, forge::{Forge, ForgeStargazer}};
+use serde::Deserialize;
+use tracing::warn;
+use utoipa::IntoParams;
+
+use crate::sync_queue::SyncContext;
+
+/// Accounts younger than this when starring are taken for throwaway ones.
+pub const MIN_ACCOUNT_AGE: TimeDelta = TimeDelta::days(7);
+
+/// Trailing digits from which a login looks generated, `user12345678`.
+const GENERATED_SUFFIX_DIGITS: usize = 6;
+
+/// Length from which an all-hexadecimal login looks generated.
+const GENERATED_HEX_LENGTH: usize = 16;
+
+/// Query parameter of the read endpoints leaving out the stars of suspected bots.
+#[derive(Debug, Clone, Copy, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BotFilter {
+    /// Leaves out the stars of suspected bots, `false` by default.
+    #[serde(default)]
+    pub exclude_bots: bool,
+}
+
+/// Whether `login` looks like an automated account's: a GitHub App's `[bot]` suffix, `bot` as one of
+/// its dash or underscore separated words, a long run of trailing digits, or a long hexadecimal string.
+pub fn login_looks_automated(login: &str) -> bool {
+    let login = login.to_ascii_lowercase();
+    if login.ends_with("[bot]") || login.split(['-', '_']).any(|word| word == "bot" || word == "bots") {
+        return true;
+    }
+
+    let digits = login.len() - login.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let hexadecimal = login.len() >= GENERATED_HEX_LENGTH
+        && login.chars().all(|c| c.is_ascii_hexdigit())
+        && login.chars().any(|c| c.is_ascii_digit())
+        && login.chars().any(|c| c.is_ascii_alphabetic());
+    digits >= GENERATED_SUFFIX_DIGITS || hexadecimal
+}
+
+/// Whether an account created at `created_at` was too young when starring at `starred_at`.
+pub fn is_throwaway_account(created_at: DateTime<Utc>, starred_at: DateTime<Utc>) -> bool {
+    starred_at - created_at < MIN_ACCOUNT_AGE
+}
+
+/// Logins of the suspected bots among `stars`, the accounts' creation dates being those of `created_at`.
+/// Stargazers whose account isn't in it are judged by their login only.
+pub fn suspected_bots(stars: &[ForgeStargazer], created_at: &HashMap<String, DateTime<Utc>>) -> HashSet<String> {
+    stars
+        .iter()
+        .filter(|star| {
+            login_looks_automated(&star.login)
+                || created_at.get(&star.login).is_some_and(|created_at| is_throwaway_account(*created_at, star.starred_at))
+        })
+        .map(|star| star.login.clone())
+        .collect()
+}
+
+/// Logins of the suspected bots of a page of `forge` stargazers, none unless the sync flags them.
+///
+/// A failed lookup of the accounts' ages is logged and leaves the page judged by logins only, the
+/// next sync seeing those stars flags them.
+pub async fn flag_suspected_bots(context: &SyncContext, forge: Forge, stars: &[ForgeStargazer]) -> HashSet<String> {
+    if !context.flags_bots() {
+        return HashSet::new();
+    }
+    if !context.checks_account_age(forge) {
+        return suspected_bots(stars, &HashMap::new());
+    }
+
+    // Logins already giving their owner away need no lookup.
+    let logins: Vec<&str> = stars
+        .iter()
+        .map(|star| star.login.as_str())
+        .filter(|login| !login_looks_automated(login))
+        .collect();
+    let created_at = match fetch_account_creation_dates(context.forges.github(), &logins).await {
+        Ok(created_at) => created_at,
+        Err(error) => {
+            warn!(%error, stargazers = logins.len(), "account ages not fetched, judging stargazers by login");
+            HashMap::new()
+        }
+    };
+    suspected_bots(stars, &created_at)
+}
@@ -0,0 +1,419 @@
+//! Star history charts
+//!
+//! Self-contained SVG line charts of a cumulative star series, stacked area
+//! charts of several series adding up, and weekday heatmaps of daily stars,
+//! written without a plotting
+//! library so they render anywhere a browser does. Colors come from a
+//! [`ChartTheme`], light unless told otherwise, and tick counts and label
+//! rotation from [`AxisOptions`]. Date labels drop the day, then the month,
+//! as the ticks grow apart, so multi-year histories stay readable.
+
+use std::{fmt::Write, str::FromStr};
+
+use chrono::{Datelike, Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 400.0;
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+/// Bottom margin fitting date labels written at an angle.
+const ROTATED_MARGIN_BOTTOM: f64 = 80.0;
+/// Ticks allowed on an axis, ends included.
+pub const MIN_TICKS: usize = 2;
+pub const MAX_TICKS: usize = 20;
+/// Bands named in the legend of a stacked area chart, the others are counted.
+const LEGEND_ENTRIES: usize = 12;
+/// Side of a heatmap cell, and the distance from one cell to the next.
+const HEATMAP_CELL: f64 = 12.0;
+const HEATMAP_STEP: f64 = 15.0;
+/// Left margin of a heatmap, fitting the weekday labels.
+const HEATMAP_MARGIN_LEFT: f64 = 40.0;
+/// Narrowest heatmap, so the title and the legend fit with few weeks.
+const HEATMAP_MIN_WIDTH: f64 = 320.0;
+
+#[derive(Debug, Error)]
+pub enum ParseHexColorError {
+    #[error("InvalidHexColor: {value}")]
+    InvalidHexColor {
+        value: String,
+    },
+}
+
+/// CSS hex color, `#rgb`, `#rrggbb` or `#rrggbbaa`. Checked so it can't break out of an SVG attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexColor(String);
+
+impl HexColor {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Red, green and blue components, for the raster renderers. Alpha is ignored.
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        let digits = self.0.trim_start_matches('#');
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).unwrap_or_default();
+        if digits.len() == 3 {
+            let short = |index: usize| channel(&digits[index..=index].repeat(2));
+            return (short(0), short(1), short(2));
+        }
+        (channel(&digits[0..2]), channel(&digits[2..4]), channel(&digits[4..6]))
+    }
+
+    /// Only for the built-in themes, which are known to be valid.
+    fn preset(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl FromStr for HexColor {
+    type Err = ParseHexColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('#') {
+            Some(digits) if matches!(digits.len(), 3 | 6 | 8) && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                Ok(Self(s.to_ascii_lowercase()))
+            }
+            _ => Err(ParseHexColorError::InvalidHexColor { value: s.into() }),
+        }
+    }
+}
+
+impl std::fmt::Display for HexColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Colors of a chart. Start from [`ChartTheme::light`] or [`ChartTheme::dark`]
+/// and replace any field for a custom theme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChartTheme {
+    pub background: HexColor,
+    pub grid: HexColor,
+    pub text: HexColor,
+    /// One color per series, reused from the start when there are more series than colors.
+    pub series: Vec<HexColor>,
+}
+
+impl Default for ChartTheme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl ChartTheme {
+    pub fn light() -> Self {
+        Self {
+            background: HexColor::preset("#ffffff"),
+            grid: HexColor::preset("#e0e0e0"),
+            text: HexColor::preset("#000000"),
+            series: vec![HexColor::preset("#f1c40f"), HexColor::preset("#2980b9"), HexColor::preset("#27ae60")],
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            background: HexColor::preset("#0d1117"),
+            grid: HexColor::preset("#30363d"),
+            text: HexColor::preset("#c9d1d9"),
+            series: vec![HexColor::preset("#f1c40f"), HexColor::preset("#58a6ff"), HexColor::preset("#3fb950")],
+        }
+    }
+
+    /// Color of the `index`-th series, falling back to the first preset one without series colors.
+    pub fn series_color(&self, index: usize) -> &str {
+        match self.series.len() {
+            0 => "#f1c40f",
+            len => self.series[index % len].as_str(),
+        }
+    }
+}
+
+/// How the axes of a chart are labelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisOptions {
+    /// Labels on the x axis at most, ends included, between [`MIN_TICKS`] and [`MAX_TICKS`].
+    pub max_x_ticks: usize,
+    /// Labels on the y axis at most, bounded the same way.
+    pub max_y_ticks: usize,
+    /// Writes the x labels at an angle, for long labels on narrow charts.
+    pub rotate_x_labels: bool,
+}
+
+impl Default for AxisOptions {
+    fn default() -> Self {
+        Self { max_x_ticks: 5, max_y_ticks: 5, rotate_x_labels: false }
+    }
+}
+
+impl AxisOptions {
+    /// The tick counts clamped between [`MIN_TICKS`] and [`MAX_TICKS`].
+    pub fn clamped(self) -> Self {
+        Self {
+            max_x_ticks: self.max_x_ticks.clamp(MIN_TICKS, MAX_TICKS),
+            max_y_ticks: self.max_y_ticks.clamp(MIN_TICKS, MAX_TICKS),
+            ..self
+        }
+    }
+
+    fn margin_bottom(&self) -> f64 {
+        if self.rotate_x_labels { ROTATED_MARGIN_BOTTOM } else { MARGIN_BOTTOM }
+    }
+}
+
+/// `strftime` format of date labels at least `step_days` apart: the year once they're two years
+/// apart or more, so skipped years show, the month once a month apart, the whole date otherwise.
+/// Multi-year histories get short labels, and no two labels read the same.
+pub fn date_label_format(step_days: i64) -> &'static str {
+    match step_days {
+        731.. => "%Y",
+        31.. => "%Y-%m",
+        _ => "%Y-%m-%d",
+    }
+}
+
+/// Built-in theme picked by name, e.g. in query parameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl ThemeName {
+    pub fn theme(self) -> ChartTheme {
+        match self {
+            ThemeName::Light => ChartTheme::light(),
+            ThemeName::Dark => ChartTheme::dark(),
+        }
+    }
+}
+
+/// One series of a stacked area chart.
+#[derive(Debug, Clone)]
+pub struct StackedBand {
+    pub label: String,
+    /// One value per period of the chart.
+    pub values: Vec<i64>,
+}
+
+/// One column of a heatmap.
+#[derive(Debug, Clone)]
+pub struct HeatmapWeek {
+    /// Monday of the week.
+    pub start: NaiveDate,
+    /// Stars of each day, Monday first, `None` for the days left blank.
+    pub days: Vec<Option<i64>>,
+}
+
+/// SVG line chart of `cumulative`, one point per day as built by `analytics::series::cumulative_series`.
+pub fn render_star_history_svg(title: &str, cumulative: &[(NaiveDate, i64)], theme: &ChartTheme, axes: &AxisOptions) -> String {
+    let axes = axes.clamped();
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - axes.margin_bottom();
+
+    let mut svg = String::new();
+    // Writing to a String can't fail.
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" font-size="12" fill="{}">"#, theme.text);
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, theme.background);
+    let _ = writeln!(svg, r#"<text x="{}" y="24" text-anchor="middle" font-size="16">{}</text>"#, WIDTH / 2.0, escape(title));
+
+    let (Some(&(first_day, _)), Some(&(last_day, _))) = (cumulative.first(), cumulative.last()) else {
+        let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle">No stars yet</text>"#, WIDTH / 2.0, HEIGHT / 2.0);
+        svg.push_str("</svg>\n");
+        return svg;
+    };
+
+    let days = (last_day - first_day).num_days().max(1) as f64;
+    // No more date labels than days, so none repeats.
+    let x_ticks = axes.max_x_ticks.min((last_day - first_day).num_days() as usize + 1).max(MIN_TICKS);
+    let date_format = date_label_format((last_day - first_day).num_days() / (x_ticks - 1) as i64);
+    let max_stars = cumulative.iter().map(|&(_, stars)| stars).max().unwrap_or(0).max(1) as f64;
+    let x = |day: NaiveDate| MARGIN_LEFT + (day - first_day).num_days() as f64 / days * plot_width;
+    let y = |stars: f64| MARGIN_TOP + plot_height - stars / max_stars * plot_height;
+
+    write_y_ticks(&mut svg, max_stars, &axes, theme, y);
+    for tick in 0..x_ticks {
+        let day = first_day + chrono::Duration::days((days * tick as f64 / (x_ticks - 1) as f64).round() as i64);
+        write_x_label(&mut svg, x(day), &day.format(date_format).to_string(), &axes);
+    }
+
+    let points: Vec<String> = cumulative
+        .iter()
+        .map(|&(day, stars)| format!("{:.1},{:.1}", x(day), y(stars as f64)))
+        .collect();
+    let _ = writeln!(svg, r#"<polyline fill="none" stroke="{}" stroke-width="2" points="{}"/>"#, theme.series_color(0), points.join(" "));
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// SVG stacked area chart of `bands` over `periods`, the first band at the bottom.
+/// Periods are spaced evenly, whatever their length.
+pub fn render_stacked_area_svg(title: &str, periods: &[NaiveDate], bands: &[StackedBand], theme: &ChartTheme, axes: &AxisOptions) -> String {
+    let axes = axes.clamped();
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - axes.margin_bottom();
+
+    let mut svg = String::new();
+    // Writing to a String can't fail.
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" font-size="12" fill="{}">"#, theme.text);
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, theme.background);
+    let _ = writeln!(svg, r#"<text x="{}" y="24" text-anchor="middle" font-size="16">{}</text>"#, WIDTH / 2.0, escape(title));
+
+    if periods.is_empty() || bands.is_empty() {
+        let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle">No stars yet</text>"#, WIDTH / 2.0, HEIGHT / 2.0);
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+
+    // Running sums of the bands below each one, the last row being the top of the stack.
+    let mut stacked = vec![vec![0; periods.len()]];
+    for band in bands {
+        let below = &stacked[stacked.len() - 1];
+        let row = (0..periods.len()).map(|index| below[index] + band.values.get(index).copied().unwrap_or(0)).collect();
+        stacked.push(row);
+    }
+
+    let spans = (periods.len() - 1).max(1) as f64;
+    let max_stars = stacked[stacked.len() - 1].iter().copied().max().unwrap_or(0).max(1) as f64;
+    let x = |index: usize| MARGIN_LEFT + index as f64 / spans * plot_width;
+    let y = |stars: f64| MARGIN_TOP + plot_height - stars / max_stars * plot_height;
+
+    write_y_ticks(&mut svg, max_stars, &axes, theme, y);
+    // No more date labels than periods, so none repeats.
+    let x_ticks = axes.max_x_ticks.min(periods.len()).max(MIN_TICKS);
+    let date_format = date_label_format((periods[periods.len() - 1] - periods[0]).num_days() / (x_ticks - 1) as i64);
+    let mut labelled = None;
+    for tick in 0..x_ticks {
+        let index = (spans * tick as f64 / (x_ticks - 1) as f64).round() as usize;
+        if index < periods.len() && labelled != Some(index) {
+            write_x_label(&mut svg, x(index), &periods[index].format(date_format).to_string(), &axes);
+            labelled = Some(index);
+        }
+    }
+
+    for (band, bounds) in stacked.windows(2).enumerate() {
+        let upper = (0..periods.len()).map(|index| format!("{:.1},{:.1}", x(index), y(bounds[1][index] as f64)));
+        let lower = (0..periods.len()).rev().map(|index| format!("{:.1},{:.1}", x(index), y(bounds[0][index] as f64)));
+        let points: Vec<String> = upper.chain(lower).collect();
+        let _ = writeln!(svg, r#"<polygon fill="{}" fill-opacity="0.85" stroke="{}" stroke-width="0.5" points="{}"/>"#, theme.series_color(band), theme.background, points.join(" "));
+    }
+
+    for (index, band) in bands.iter().take(LEGEND_ENTRIES).enumerate() {
+        let top = MARGIN_TOP + 6.0 + index as f64 * 16.0;
+        let _ = writeln!(svg, r#"<rect x="{}" y="{top:.1}" width="10" height="10" fill="{}"/>"#, MARGIN_LEFT + 10.0, theme.series_color(index));
+        let _ = writeln!(svg, r#"<text x="{}" y="{:.1}">{}</text>"#, MARGIN_LEFT + 26.0, top + 9.0, escape(&band.label));
+    }
+    if bands.len() > LEGEND_ENTRIES {
+        let top = MARGIN_TOP + 6.0 + LEGEND_ENTRIES as f64 * 16.0;
+        let _ = writeln!(svg, r#"<text x="{}" y="{:.1}">+{} more</text>"#, MARGIN_LEFT + 26.0, top + 9.0, bands.len() - LEGEND_ENTRIES);
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// SVG heatmap of `weeks`, one column per week and one row per weekday, as GitHub draws
+/// contributions. Cells are shaded in 4 steps up to the busiest day, days without stars are
+/// drawn in the grid color. The chart is as wide as the weeks need.
+pub fn render_heatmap_svg(title: &str, weeks: &[HeatmapWeek], theme: &ChartTheme) -> String {
+    let width = (HEATMAP_MARGIN_LEFT + weeks.len() as f64 * HEATMAP_STEP + MARGIN_RIGHT).max(HEATMAP_MIN_WIDTH);
+    // Month labels sit between the title and the grid.
+    let grid_top = MARGIN_TOP + 20.0;
+    let grid_bottom = grid_top + 7.0 * HEATMAP_STEP;
+    let height = grid_bottom + MARGIN_BOTTOM;
+
+    let mut svg = String::new();
+    // Writing to a String can't fail.
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="12" fill="{}">"#, theme.text);
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, theme.background);
+    let _ = writeln!(svg, r#"<text x="{}" y="24" text-anchor="middle" font-size="16">{}</text>"#, width / 2.0, escape(title));
+
+    let x = |week: usize| HEATMAP_MARGIN_LEFT + week as f64 * HEATMAP_STEP;
+    let y = |weekday: usize| grid_top + weekday as f64 * HEATMAP_STEP;
+
+    // A label over the first week of every month, and over the first week when the next label is far enough.
+    let month_starts: Vec<usize> = (1..weeks.len()).filter(|&index| weeks[index].start.month() != weeks[index - 1].start.month()).collect();
+    let first_label = match month_starts.first() {
+        Some(&index) if index < 3 => None,
+        _ if weeks.is_empty() => None,
+        _ => Some(0),
+    };
+    for index in first_label.into_iter().chain(month_starts) {
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}">{}</text>"#, x(index), grid_top - 6.0, weeks[index].start.format("%b"));
+    }
+    for (weekday, label) in [(0, "Mon"), (2, "Wed"), (4, "Fri")] {
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{label}</text>"#, HEATMAP_MARGIN_LEFT - 6.0, y(weekday) + HEATMAP_CELL - 2.0);
+    }
+
+    let max_stars = weeks.iter().flat_map(|week| week.days.iter().flatten()).copied().max().unwrap_or(0);
+    for (index, week) in weeks.iter().enumerate() {
+        for (weekday, stars) in week.days.iter().take(7).enumerate() {
+            let Some(stars) = *stars else { continue };
+            let day = week.start + Days::new(weekday as u64);
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="{HEATMAP_CELL}" height="{HEATMAP_CELL}" rx="2" {}><title>{day}: {stars}</title></rect>"#,
+                x(index),
+                y(weekday),
+                heatmap_fill(heatmap_level(stars, max_stars), theme),
+            );
+        }
+    }
+
+    let legend_y = grid_bottom + 12.0;
+    let _ = writeln!(svg, r#"<text x="{HEATMAP_MARGIN_LEFT}" y="{:.1}">Less</text>"#, legend_y + HEATMAP_CELL - 2.0);
+    for level in 0..=4 {
+        let _ = writeln!(svg, r#"<rect x="{:.1}" y="{legend_y:.1}" width="{HEATMAP_CELL}" height="{HEATMAP_CELL}" rx="2" {}/>"#, HEATMAP_MARGIN_LEFT + 32.0 + level as f64 * HEATMAP_STEP, heatmap_fill(level, theme));
+    }
+    let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}">More</text>"#, HEATMAP_MARGIN_LEFT + 36.0 + 5.0 * HEATMAP_STEP, legend_y + HEATMAP_CELL - 2.0);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Shade of a heatmap cell, 0 for no stars, then 1 to 4 by quarters of `max_stars`.
+fn heatmap_level(stars: i64, max_stars: i64) -> u8 {
+    if stars <= 0 || max_stars <= 0 {
+        return 0;
+    }
+    ((4 * stars + max_stars - 1) / max_stars).clamp(1, 4) as u8
+}
+
+/// Fill attributes of a heatmap cell of `level`.
+fn heatmap_fill(level: u8, theme: &ChartTheme) -> String {
+    match level {
+        0 => format!(r#"fill="{}""#, theme.grid),
+        level => format!(r#"fill="{}" fill-opacity="{:.2}""#, theme.series_color(0), f64::from(level) / 4.0),
+    }
+}
+
+/// Grid lines and labels of the y axis, evenly spaced from 0 to `max_stars`.
+fn write_y_ticks(svg: &mut String, max_stars: f64, axes: &AxisOptions, theme: &ChartTheme, y: impl Fn(f64) -> f64) {
+    for tick in 0..axes.max_y_ticks {
+        let stars = max_stars * tick as f64 / (axes.max_y_ticks - 1) as f64;
+        let _ = writeln!(svg, r##"<line x1="{MARGIN_LEFT}" y1="{0:.1}" x2="{1}" y2="{0:.1}" stroke="{2}"/>"##, y(stars), WIDTH - MARGIN_RIGHT, theme.grid);
+        let _ = writeln!(svg, r#"<text x="{}" y="{:.1}" text-anchor="end">{}</text>"#, MARGIN_LEFT - 8.0, y(stars) + 4.0, stars.round());
+    }
+}
+
+/// Label of the x axis centered under `x`, or ending under it when rotated.
+fn write_x_label(svg: &mut String, x: f64, label: &str, axes: &AxisOptions) {
+    let y = HEIGHT - axes.margin_bottom() + 18.0;
+    if axes.rotate_x_labels {
+        let _ = writeln!(svg, r#"<text x="{x:.1}" y="{y}" text-anchor="end" transform="rotate(-45 {x:.1} {y})">{label}</text>"#);
+    } else {
+        let _ = writeln!(svg, r#"<text x="{x:.1}" y="{y}" text-anchor="middle">{label}</text>"#);
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
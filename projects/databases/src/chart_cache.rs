@@ -0,0 +1,182 @@
+//! Rendered comparison charts kept in memory
+//!
+//! Drawing the chart of a few popular repositories takes far longer than
+//! loading their histories, and dashboards ask for the same chart over and
+//! over, as do crawlers with social cards. The SVG of
+//! `POST /github/repo_stars/compare/chart` and the PNG of
+//! `GET /github/repo_stars/social_card/{owner}/{name}` are kept under a
+//! [`ChartKey`]: the tenant, the request as sent, the day it was drawn on and
+//! the star version of every repository drawn, and of its forks when the
+//! chart adds their stars, the same the ETags use. A sync of another instance
+//! or of the CLI changes the version, so the chart is drawn again.
+//!
+//! Charts are dropped after `CHART_CACHE_TTL_SECONDS` (300 by default), the
+//! least recently served first once they take up more than
+//! `CHART_CACHE_MAX_BYTES` (32 MiB by default), and as soon as a sync job of
+//! one of their repositories ends on this instance, since releases and
+//! activity are refreshed by syncs without changing the star version. Star
+//! count and traffic snapshots show up in charts once the TTL passed. A TTL
+//! of 0 turns the cache off.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use axum::body::Bytes;
+use chrono::{NaiveDate, NaiveDateTime};
+use uuid::Uuid;
+
+/// How long charts are kept, and how many bytes of them at most.
+#[derive(Debug, Clone, Copy)]
+pub struct ChartCacheConfig {
+    pub ttl: Duration,
+    pub max_bytes: usize,
+}
+
+impl Default for ChartCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(300),
+            max_bytes: 32 * 1024 * 1024,
+        }
+    }
+}
+
+/// Stars stored for a compared repository, which change with every sync.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepositoryVersion {
+    pub repository_id: Uuid,
+    pub last_fetched_at: Option<NaiveDateTime>,
+    pub stars: i64,
+}
+
+/// What a chart was drawn from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChartKey {
+    pub tenant_id: Uuid,
+    /// In the order they are drawn, each followed by its forks when the chart adds their stars.
+    pub repositories: Vec<RepositoryVersion>,
+    /// Metrics and options of the chart, e.g. the request body as JSON.
+    pub config: String,
+    /// Series run up to the day they are drawn on.
+    pub day: NaiveDate,
+}
+
+struct CachedChart {
+    svg: Bytes,
+    rendered_at: Instant,
+    /// Tick of the last time it was served, the lowest evicted first.
+    last_served: u64,
+}
+
+#[derive(Default)]
+struct Charts {
+    entries: HashMap<ChartKey, CachedChart>,
+    bytes: usize,
+    tick: u64,
+}
+
+impl Charts {
+    fn remove(&mut self, key: &ChartKey) {
+        if let Some(chart) = self.entries.remove(key) {
+            self.bytes -= chart.svg.len();
+        }
+    }
+}
+
+/// Charts shared by clones, the handlers serving them and the syncs invalidating them.
+#[derive(Clone)]
+pub struct ChartCache {
+    config: ChartCacheConfig,
+    charts: Arc<Mutex<Charts>>,
+}
+
+impl Default for ChartCache {
+    fn default() -> Self {
+        Self::new(ChartCacheConfig::default())
+    }
+}
+
+impl ChartCache {
+    pub fn new(config: ChartCacheConfig) -> Self {
+        Self { config, charts: Arc::new(Mutex::new(Charts::default())) }
+    }
+
+    /// The chart drawn for `key` within the TTL.
+    pub fn get(&self, key: &ChartKey) -> Option<Bytes> {
+        let mut charts = self.lock();
+        charts.tick += 1;
+        let tick = charts.tick;
+        if charts.entries.get(key)?.rendered_at.elapsed() >= self.config.ttl {
+            charts.remove(key);
+            return None;
+        }
+        let chart = charts.entries.get_mut(key)?;
+        chart.last_served = tick;
+        Some(chart.svg.clone())
+    }
+
+    /// Keeps `svg` for `key`, evicting the least recently served charts past the size bound.
+    /// Charts larger than the whole bound aren't kept.
+    pub fn insert(&self, key: ChartKey, svg: Bytes) {
+        if self.config.ttl.is_zero() || svg.len() > self.config.max_bytes {
+            return;
+        }
+        let mut charts = self.lock();
+        charts.remove(&key);
+
+        let ttl = self.config.ttl;
+        let expired: Vec<ChartKey> = charts
+            .entries
+            .iter()
+            .filter(|(_, chart)| chart.rendered_at.elapsed() >= ttl)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            charts.remove(key);
+        }
+        while charts.bytes + svg.len() > self.config.max_bytes {
+            let Some(oldest) = charts.entries.iter().min_by_key(|(_, chart)| chart.last_served).map(|(key, _)| key.clone()) else {
+                break;
+            };
+            charts.remove(&oldest);
+        }
+
+        charts.tick += 1;
+        charts.bytes += svg.len();
+        let last_served = charts.tick;
+        charts.entries.insert(key, CachedChart { svg, rendered_at: Instant::now(), last_served });
+    }
+
+    /// Drops every chart of the repository, e.g. once one of its syncs ended.
+    pub fn invalidate(&self, repository_id: Uuid) {
+        let mut charts = self.lock();
+        let stale: Vec<ChartKey> = charts
+            .entries
+            .keys()
+            .filter(|key| key.repositories.iter().any(|repository| repository.repository_id == repository_id))
+            .cloned()
+            .collect();
+        for key in &stale {
+            charts.remove(key);
+        }
+    }
+
+    /// Charts kept, expired ones included until they are next looked at.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes of charts kept.
+    pub fn bytes(&self) -> usize {
+        self.lock().bytes
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Charts> {
+        self.charts.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,580 @@
+//! Typed client for the REST API, behind the `client` feature
+//!
+//! Answers are decoded into the same types the handlers serialize, so a
+//! change on the server side shows up as a compile error here.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use projects_databases::client::StarTrackerClient;
+//! use projects_databases::sync_queue::SyncPriority;
+//!
+//! let client = StarTrackerClient::new("http://localhost:8000".parse()?)?;
+//! let summary = client.start_sync("rust-lang", "rust", SyncPriority::Normal).await?;
+//! let job = client.job_status(summary.job_id).await?;
+//! let days = client.daily_data("rust-lang", "rust").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use interfaces_github_stargazers::forge::Forge;
+use reqwest::{Client, Method, RequestBuilder, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::analytics::{anomalies::AnomalyConfig, compare::{Alignment, CompareMetric, Comparison}, forecast::{Forecast, ForecastModel}, milestones::MilestoneEta, profiles::ProfileBreakdown, stats::StarStats};
+use crate::db::alert_rule::models::{AlertKind, AlertTarget};
+use crate::db::daily_star_count::models::GrowthPeriod;
+use crate::endpoints::admin::config::{get::index::ConfigResponse, update::index::ConfigPatch};
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::endpoints::github::{
+    alerts::list::index::AlertRuleResponse,
+    org_stars::{batch::index::BatchProgress, update::index::BatchCreated},
+    repo_stars::{
+        anomalies::{detect::index::DetectResponse, list::index::AnomalyResponse},
+        drift::{list::index::StarDriftResponse, verify::index::VerificationReport},
+        earliest_stargazers::index::StargazerResponse,
+        import::index::ImportSummary,
+        jobs::{purge::index::PurgeResponse, status::index::SyncJobResponse},
+        milestones::list::index::MilestoneResponse,
+        read_per_day::index::DailyStarCountPage,
+        snapshots::list::index::{StarCountSnapshotResponse, StarCountSnapshotsResponse},
+        trends::index::TrendReport,
+        cohorts::index::CohortReport,
+        diff::index::StarDiffResponse,
+        update::index::SyncSummary,
+    },
+    reports::{get::index::{ReportFormat, ReportResponse}, list::index::ReportSummary},
+    repositories::{list::index::RepositoryResponse, sync_history::index::SyncRunResponse, tags::list::index::TagResponse},
+    stargazers::top::index::TopStargazerResponse,
+};
+#[cfg(feature = "charting")]
+use crate::endpoints::github::repo_stars::{compare::chart::index::CompareChartQuery, social_card::index::CardTheme};
+use crate::endpoints::metrics::index::MetricsResponse;
+use crate::sync_queue::{SyncPriority, SyncQueueStats};
+
+#[derive(Debug, Error)]
+pub enum NewStarTrackerClientError {
+    #[error("CannotBeABase: {url}")]
+    CannotBeABase {
+        url: Url,
+    },
+    #[error("HttpClientBuild: {source}")]
+    HttpClientBuild {
+        source: reqwest::Error,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum StarTrackerRequestError {
+    #[error("RequestSend: {source}")]
+    RequestSend {
+        source: reqwest::Error,
+    },
+    /// The server answered with an error status. `code` and `request_id` come from its
+    /// [`ApiError`] body, unset when the body isn't one, e.g. from a proxy.
+    #[error("UnexpectedStatus: {status}: {message}")]
+    UnexpectedStatus {
+        status: StatusCode,
+        code: Option<ErrorCode>,
+        message: String,
+        request_id: Option<String>,
+    },
+    #[error("ResponseDecode: {source}")]
+    ResponseDecode {
+        source: reqwest::Error,
+    },
+}
+
+impl StarTrackerRequestError {
+    /// Status returned by the server, if it answered at all.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            StarTrackerRequestError::UnexpectedStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Code of the server's error, to branch on.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            StarTrackerRequestError::UnexpectedStatus { code, .. } => *code,
+            _ => None,
+        }
+    }
+}
+
+/// Format of a star dump given to [`StarTrackerClient::import_stars`] or read from
+/// [`StarTrackerClient::export_stars`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// `stargazer,starred_at` columns, with a header row.
+    Csv,
+    /// `{"stargazer", "starred_at"}` objects or GH Archive events, one per line.
+    Ndjson,
+}
+
+impl ImportFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "text/csv",
+            ImportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+/// Client of a running star tracker service. Cloning is cheap, clones share connections.
+#[derive(Debug, Clone)]
+pub struct StarTrackerClient {
+    http: Client,
+    base_url: Url,
+    api_key: Option<String>,
+}
+
+impl StarTrackerClient {
+    /// Client of the service at `base_url`, e.g. `http://localhost:8000`.
+    ///
+    /// Syncs answer once they are done, so there is no overall request timeout.
+    pub fn new(base_url: Url) -> Result<Self, NewStarTrackerClientError> {
+        let http = Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|source| NewStarTrackerClientError::HttpClientBuild { source })?;
+
+        Self::with_http_client(http, base_url)
+    }
+
+    /// Same as [`StarTrackerClient::new`] with a preconfigured `reqwest::Client`.
+    pub fn with_http_client(http: Client, base_url: Url) -> Result<Self, NewStarTrackerClientError> {
+        if base_url.cannot_be_a_base() {
+            return Err(NewStarTrackerClientError::CannotBeABase { url: base_url });
+        }
+
+        Ok(Self { http, base_url, api_key: None })
+    }
+
+    /// Sends `api_key` with every request, scoping them to its tenant instead of the default one.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Syncs the repository's stargazers, waiting for a sync worker to run it.
+    pub async fn start_sync(&self, owner: &str, name: &str, priority: SyncPriority) -> Result<SyncSummary, StarTrackerRequestError> {
+        let body = json!({ "owner": owner, "name": name, "priority": priority });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "update"]).json(&body)).await
+    }
+
+    /// Like [`StarTrackerClient::start_sync`], tracking the repository's forks with at least `forks_min_stars` stars from now on.
+    pub async fn start_sync_with_forks(&self, owner: &str, name: &str, forks_min_stars: u32, priority: SyncPriority) -> Result<SyncSummary, StarTrackerRequestError> {
+        let body = json!({ "owner": owner, "name": name, "priority": priority, "forks_min_stars": forks_min_stars });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "update"]).json(&body)).await
+    }
+
+    /// Like [`StarTrackerClient::start_sync`], for a repository hosted on `forge`.
+    pub async fn start_forge_sync(&self, forge: Forge, owner: &str, name: &str, priority: SyncPriority) -> Result<SyncSummary, StarTrackerRequestError> {
+        let body = json!({ "forge": forge, "owner": owner, "name": name, "priority": priority });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "update"]).json(&body)).await
+    }
+
+    /// Like [`StarTrackerClient::start_sync`], the stargazers of each page sent back as they are
+    /// stored. The answer is NDJSON lines of
+    /// [`SyncStreamLine`](crate::endpoints::github::repo_stars::update::index::SyncStreamLine):
+    /// read it with `chunk()`, the sync waits for a slow reader.
+    pub async fn stream_sync(&self, owner: &str, name: &str, priority: SyncPriority) -> Result<reqwest::Response, StarTrackerRequestError> {
+        let body = json!({ "owner": owner, "name": name, "priority": priority, "stream": true });
+        self.send_checked(self.request(Method::POST, &["github", "repo_stars", "update"]).json(&body)).await
+    }
+
+    /// Queues a sync of each `(owner, name)` repository, progress is reported by [`StarTrackerClient::batch_progress`].
+    pub async fn start_batch_sync(&self, repositories: &[(&str, &str)], priority: SyncPriority) -> Result<BatchCreated, StarTrackerRequestError> {
+        let repositories: Vec<_> = repositories
+            .iter()
+            .map(|(owner, name)| json!({ "owner": owner, "name": name }))
+            .collect();
+        let body = json!({ "repositories": repositories, "priority": priority });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "update_batch"]).json(&body)).await
+    }
+
+    /// Continues a failed, interrupted or partially completed sync from its last stored page.
+    pub async fn resume_job(&self, id: Uuid) -> Result<SyncSummary, StarTrackerRequestError> {
+        self.send(self.request(Method::POST, &["github", "repo_stars", "jobs", &id.to_string(), "resume"])).await
+    }
+
+    /// Seeds the repository's stars from a dump, without the service calling GitHub.
+    pub async fn import_stars(&self, owner: &str, name: &str, format: ImportFormat, dump: impl Into<reqwest::Body>) -> Result<ImportSummary, StarTrackerRequestError> {
+        let request = self
+            .request(Method::POST, &["github", "repo_stars", "import"])
+            .query(&[("owner", owner), ("name", name)])
+            .header(reqwest::header::CONTENT_TYPE, format.content_type())
+            .body(dump);
+        self.send(request).await
+    }
+
+    /// Checks stored star counts against GitHub, for the repositories tagged `tag` or every one,
+    /// and queues a sync of the ones missing more than `repair_threshold` stars.
+    pub async fn verify_star_counts(&self, tag: Option<&str>, repair_threshold: Option<u32>, priority: SyncPriority) -> Result<VerificationReport, StarTrackerRequestError> {
+        let body = json!({ "tag": tag, "repair_threshold": repair_threshold, "priority": priority });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "drift", "verify"]).json(&body)).await
+    }
+
+    /// Latest verification of every verified repository, largest drift first.
+    pub async fn star_drift(&self) -> Result<Vec<StarDriftResponse>, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "repo_stars", "drift"])).await
+    }
+
+    pub async fn job_status(&self, id: Uuid) -> Result<SyncJobResponse, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "repo_stars", "jobs", &id.to_string()])).await
+    }
+
+    /// Deletes the sync jobs finished more than `older_than_hours` ago, the service's
+    /// `SYNC_JOB_RETENTION_HOURS` when unset.
+    pub async fn purge_jobs(&self, older_than_hours: Option<u64>) -> Result<PurgeResponse, StarTrackerRequestError> {
+        let mut request = self.request(Method::DELETE, &["github", "repo_stars", "jobs"]);
+        if let Some(hours) = older_than_hours {
+            request = request.query(&[("older_than_hours", hours)]);
+        }
+        self.send(request).await
+    }
+
+    pub async fn sync_queue(&self) -> Result<SyncQueueStats, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "repo_stars", "jobs", "queue"])).await
+    }
+
+    pub async fn metrics(&self) -> Result<MetricsResponse, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["metrics"])).await
+    }
+
+    /// Stars received on every day with at least one, oldest first.
+    pub async fn daily_data(&self, owner: &str, name: &str) -> Result<Vec<(NaiveDate, i64)>, StarTrackerRequestError> {
+        let body = json!({ "owner": owner, "name": name });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "read_per_day"]).json(&body)).await
+    }
+
+    /// One page of [`StarTrackerClient::daily_data`].
+    pub async fn daily_data_page(&self, owner: &str, name: &str, limit: i64, offset: i64) -> Result<DailyStarCountPage, StarTrackerRequestError> {
+        let body = json!({ "owner": owner, "name": name, "limit": limit, "offset": offset });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "read_per_day"]).json(&body)).await
+    }
+
+    /// `metrics` of every listed `(owner, name)` repository, aligned for charting them together.
+    pub async fn compare(&self, repositories: &[(&str, &str)], metrics: &[CompareMetric], alignment: Alignment) -> Result<Comparison, StarTrackerRequestError> {
+        let repositories: Vec<_> = repositories
+            .iter()
+            .map(|(owner, name)| json!({ "owner": owner, "name": name }))
+            .collect();
+        let body = json!({ "repositories": repositories, "metrics": metrics, "alignment": alignment });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "compare"]).json(&body)).await
+    }
+
+    /// SVG chart of one metric of the repositories, or two on separate axes, served by builds with the `charting` feature.
+    /// Start the query from [`CompareChartQuery::new`].
+    #[cfg(feature = "charting")]
+    pub async fn compare_chart(&self, query: &CompareChartQuery) -> Result<String, StarTrackerRequestError> {
+        let response = self.send_checked(self.request(Method::POST, &["github", "repo_stars", "compare", "chart"]).json(query)).await?;
+        response
+            .text()
+            .await
+            .map_err(|source| StarTrackerRequestError::ResponseDecode { source })
+    }
+
+    /// Totals, trailing windows, best day and current streak of the repository's stars.
+    pub async fn star_stats(&self, owner: &str, name: &str) -> Result<StarStats, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "repo_stars", "stats", owner, name])).await
+    }
+
+    /// Stars of the last `periods` periods up to the one containing `as_of` (today when unset),
+    /// each compared to the period before and to the same period a year earlier.
+    pub async fn star_trends(&self, owner: &str, name: &str, period: GrowthPeriod, periods: u32, as_of: Option<NaiveDate>) -> Result<TrendReport, StarTrackerRequestError> {
+        let mut request = self
+            .request(Method::GET, &["github", "repo_stars", "trends", owner, name])
+            .query(&[("period", period)])
+            .query(&[("periods", periods)]);
+        if let Some(as_of) = as_of {
+            request = request.query(&[("as_of", as_of)]);
+        }
+        self.send(request).await
+    }
+
+    /// Stargazers grouped by the period they starred in, up to today.
+    pub async fn star_cohorts(&self, owner: &str, name: &str, period: GrowthPeriod) -> Result<CohortReport, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["github", "repo_stars", "cohorts", owner, name])
+            .query(&[("period", period)]);
+        self.send(request).await
+    }
+
+    /// 1200x630 PNG social card of the repository, served by builds with the `charting` feature.
+    #[cfg(feature = "charting")]
+    pub async fn social_card(&self, owner: &str, name: &str, theme: CardTheme) -> Result<Vec<u8>, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["github", "repo_stars", "social_card", owner, name])
+            .query(&[("theme", theme)]);
+        let response = self.send_checked(request).await?;
+        response
+            .bytes()
+            .await
+            .map(|png| png.to_vec())
+            .map_err(|source| StarTrackerRequestError::ResponseDecode { source })
+    }
+
+    /// Projected star counts, fitted on the last `window` days (`90` when unset).
+    pub async fn forecast(&self, owner: &str, name: &str, model: ForecastModel, window: Option<usize>) -> Result<Forecast, StarTrackerRequestError> {
+        let mut request = self
+            .request(Method::GET, &["github", "repo_stars", "forecast", owner, name])
+            .query(&[("model", model)]);
+        if let Some(window) = window {
+            request = request.query(&[("window", window)]);
+        }
+        self.send(request).await
+    }
+
+    pub async fn milestones(&self, owner: &str, name: &str) -> Result<Vec<MilestoneResponse>, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "repo_stars", "milestones", owner, name])).await
+    }
+
+    /// Estimated day `target` stars are reached, from the velocity over the last `window` days (`30` when unset).
+    pub async fn milestone_eta(&self, owner: &str, name: &str, target: i64, window: Option<i64>) -> Result<MilestoneEta, StarTrackerRequestError> {
+        let mut request = self
+            .request(Method::GET, &["github", "repo_stars", "milestones", owner, name, "eta"])
+            .query(&[("target", target)]);
+        if let Some(window) = window {
+            request = request.query(&[("window", window)]);
+        }
+        self.send(request).await
+    }
+
+    pub async fn anomalies(&self, owner: &str, name: &str) -> Result<Vec<AnomalyResponse>, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "repo_stars", "anomalies", owner, name])).await
+    }
+
+    /// Detects new star spikes, posting them to `webhook_url` when given.
+    pub async fn detect_anomalies(&self, owner: &str, name: &str, config: &AnomalyConfig, webhook_url: Option<&str>) -> Result<DetectResponse, StarTrackerRequestError> {
+        let body = json!({
+            "multiple": config.multiple,
+            "window": config.window,
+            "min_stars": config.min_stars,
+            "webhook_url": webhook_url,
+        });
+        self.send(self.request(Method::POST, &["github", "repo_stars", "anomalies", owner, name, "detect"]).json(&body)).await
+    }
+
+    /// Every stored star of the repository, oldest first, in a format [`Self::import_stars`]
+    /// reads back. The answer is streamed: read it with `chunk()` to keep memory flat.
+    pub async fn export_stars(&self, owner: &str, name: &str, format: ImportFormat) -> Result<reqwest::Response, StarTrackerRequestError> {
+        let format = match format {
+            ImportFormat::Csv => "csv",
+            ImportFormat::Ndjson => "ndjson",
+        };
+        let request = self
+            .request(Method::GET, &["github", "repo_stars", "export", owner, name])
+            .query(&[("format", format)]);
+        self.send_checked(request).await
+    }
+
+    /// Snapshots the repository's star count on schedule instead of syncing its stargazers,
+    /// and takes the first snapshot.
+    pub async fn start_star_count_snapshots(&self, owner: &str, name: &str) -> Result<StarCountSnapshotResponse, StarTrackerRequestError> {
+        self.send(self.request(Method::POST, &["github", "repo_stars", "snapshots", owner, name])).await
+    }
+
+    pub async fn star_count_snapshots(&self, owner: &str, name: &str) -> Result<StarCountSnapshotsResponse, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "repo_stars", "snapshots", owner, name])).await
+    }
+
+    pub async fn stop_star_count_snapshots(&self, owner: &str, name: &str) -> Result<(), StarTrackerRequestError> {
+        self.send_expecting_no_body(self.request(Method::DELETE, &["github", "repo_stars", "snapshots", owner, name])).await
+    }
+
+    pub async fn earliest_stargazers(&self, owner: &str, name: &str, limit: i64) -> Result<Vec<StargazerResponse>, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["github", "repo_stars", "earliest_stargazers", owner, name])
+            .query(&[("limit", limit)]);
+        self.send(request).await
+    }
+
+    /// Users starring the most tracked repositories, only counting the ones tagged `tag` when given.
+    pub async fn top_stargazers(&self, limit: i64, tag: Option<&str>) -> Result<Vec<TopStargazerResponse>, StarTrackerRequestError> {
+        let mut request = self
+            .request(Method::GET, &["github", "stargazers", "top"])
+            .query(&[("limit", limit)]);
+        if let Some(tag) = tag {
+            request = request.query(&[("tag", tag)]);
+        }
+        self.send(request).await
+    }
+
+    /// Top `limit` companies and countries among the repository's stargazers with a stored profile.
+    pub async fn stargazer_profiles(&self, owner: &str, name: &str, limit: usize) -> Result<ProfileBreakdown, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["github", "stargazers", "profiles", owner, name])
+            .query(&[("limit", limit)]);
+        self.send(request).await
+    }
+
+    /// Tracked repositories, only the ones tagged `tag` when given.
+    pub async fn repositories(&self, tag: Option<&str>) -> Result<Vec<RepositoryResponse>, StarTrackerRequestError> {
+        let mut request = self.request(Method::GET, &["github", "repositories"]);
+        if let Some(tag) = tag {
+            request = request.query(&[("tag", tag)]);
+        }
+        self.send(request).await
+    }
+
+    pub async fn tag_repository(&self, owner: &str, name: &str, tag: &str) -> Result<(), StarTrackerRequestError> {
+        self.send_expecting_no_body(self.request(Method::PUT, &["github", "repositories", owner, name, "tags", tag])).await
+    }
+
+    pub async fn untag_repository(&self, owner: &str, name: &str, tag: &str) -> Result<(), StarTrackerRequestError> {
+        self.send_expecting_no_body(self.request(Method::DELETE, &["github", "repositories", owner, name, "tags", tag])).await
+    }
+
+    pub async fn repository_tags(&self) -> Result<Vec<TagResponse>, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "repositories", "tags"])).await
+    }
+
+    /// Latest `limit` sync runs of the repository, newest first.
+    pub async fn sync_history(&self, owner: &str, name: &str, limit: i64) -> Result<Vec<SyncRunResponse>, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["github", "repositories", owner, name, "sync_history"])
+            .query(&[("limit", limit)]);
+        self.send(request).await
+    }
+
+    /// Stargazers added and removed between sync runs `from_run` and `to_run` of the repository, `limit` per side.
+    pub async fn diff_sync_runs(&self, owner: &str, name: &str, from_run: Uuid, to_run: Uuid, limit: i64) -> Result<StarDiffResponse, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["github", "repo_stars", "diff", owner, name])
+            .query(&[("from_run", from_run.to_string()), ("to_run", to_run.to_string()), ("limit", limit.to_string())]);
+        self.send(request).await
+    }
+
+    /// Queues a sync of every non-fork, non-archived repository of `owner` with at least `min_stars` stars.
+    pub async fn start_owner_sync(&self, owner: &str, min_stars: u32, priority: SyncPriority) -> Result<BatchCreated, StarTrackerRequestError> {
+        let body = json!({ "owner": owner, "min_stars": min_stars, "priority": priority });
+        self.send(self.request(Method::POST, &["github", "org_stars", "update"]).json(&body)).await
+    }
+
+    pub async fn batch_progress(&self, id: Uuid) -> Result<BatchProgress, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "org_stars", "batches", &id.to_string()])).await
+    }
+
+    /// Creates an alert rule, `destination` is a webhook URL or an email address depending on `target`.
+    pub async fn create_alert_rule(
+        &self,
+        owner: &str,
+        name: &str,
+        kind: AlertKind,
+        threshold: i32,
+        target: AlertTarget,
+        destination: &str,
+    ) -> Result<AlertRuleResponse, StarTrackerRequestError> {
+        let body = json!({
+            "owner": owner,
+            "name": name,
+            "kind": kind,
+            "threshold": threshold,
+            "target": target,
+            "destination": destination,
+        });
+        self.send(self.request(Method::POST, &["github", "alerts"]).json(&body)).await
+    }
+
+    pub async fn alert_rules(&self) -> Result<Vec<AlertRuleResponse>, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "alerts"])).await
+    }
+
+    pub async fn delete_alert_rule(&self, id: Uuid) -> Result<(), StarTrackerRequestError> {
+        self.send_expecting_no_body(self.request(Method::DELETE, &["github", "alerts", &id.to_string()])).await
+    }
+
+    /// Generated reports, most recent period first.
+    pub async fn reports(&self, limit: i64) -> Result<Vec<ReportSummary>, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["github", "reports"])
+            .query(&[("limit", limit)]);
+        self.send(request).await
+    }
+
+    pub async fn report(&self, id: Uuid) -> Result<ReportResponse, StarTrackerRequestError> {
+        self.send(self.request(Method::GET, &["github", "reports", &id.to_string()])).await
+    }
+
+    /// The report as emailed, charts inlined as SVG.
+    pub async fn report_html(&self, id: Uuid) -> Result<String, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["github", "reports", &id.to_string()])
+            .query(&[("format", ReportFormat::Html)]);
+        let response = self.send_checked(request).await?;
+        response
+            .text()
+            .await
+            .map_err(|source| StarTrackerRequestError::ResponseDecode { source })
+    }
+
+    /// Effective settings of the service, `admin_token` being its `ADMIN_TOKEN`.
+    pub async fn admin_config(&self, admin_token: &str) -> Result<ConfigResponse, StarTrackerRequestError> {
+        let request = self
+            .request(Method::GET, &["admin", "config"])
+            .header("x-admin-token", admin_token);
+        self.send(request).await
+    }
+
+    /// Adjusts the settings set in `patch` until the service restarts, and returns them all.
+    pub async fn update_admin_config(&self, admin_token: &str, patch: &ConfigPatch) -> Result<ConfigResponse, StarTrackerRequestError> {
+        let request = self
+            .request(Method::PATCH, &["admin", "config"])
+            .header("x-admin-token", admin_token)
+            .json(patch);
+        self.send(request).await
+    }
+
+    /// Request to the base URL followed by `segments`, each one percent-encoded.
+    fn request(&self, method: Method, segments: &[&str]) -> RequestBuilder {
+        let mut url = self.base_url.clone();
+        // Checked in `with_http_client`, base URLs always have path segments.
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        let request = self.http.request(method, url);
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, StarTrackerRequestError> {
+        let response = self.send_checked(request).await?;
+        response
+            .json()
+            .await
+            .map_err(|source| StarTrackerRequestError::ResponseDecode { source })
+    }
+
+    async fn send_expecting_no_body(&self, request: RequestBuilder) -> Result<(), StarTrackerRequestError> {
+        self.send_checked(request).await.map(|_| ())
+    }
+
+    async fn send_checked(&self, request: RequestBuilder) -> Result<reqwest::Response, StarTrackerRequestError> {
+        let response = request
+            .send()
+            .await
+            .map_err(|source| StarTrackerRequestError::RequestSend { source })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            // An unreadable or foreign body still leaves the status.
+            let body = response.text().await.unwrap_or_default();
+            return Err(match serde_json::from_str::<ApiError>(&body) {
+                Ok(error) => StarTrackerRequestError::UnexpectedStatus { status, code: Some(error.code), message: error.message, request_id: error.request_id },
+                Err(_) => StarTrackerRequestError::UnexpectedStatus { status, code: None, message: body, request_id: None },
+            });
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,213 @@
+//! Contributor tracking
+//!
+//! A GitHub repository put in contributors mode gets the authors of the
+//! commits of its default branch recorded, each with the dates of their first
+//! and last commit. Its history is walked back a batch at a time, right away
+//! and then every `CONTRIBUTOR_TRACKING_INTERVAL_HOURS`, until its first
+//! commit, each run also picking up the commits pushed since the previous one.
+//! Commits of emails no GitHub account claims are left out. Authors are stored
+//! under their hash when stargazer logins are, see [`crate::privacy`].
+//!
+//! The `contributors` metric of the comparison endpoints draws the running
+//! total of contributors, by their first commit, along with the stars, see
+//! [`crate::analytics::compare`].
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::PgConnection;
+use interfaces_github_stargazers::client::GitHubClient;
+use interfaces_github_stargazers::contributors::{fetch_commit_authors, CommitAuthor, FetchCommitAuthorsError};
+use interfaces_github_stargazers::forge::Forge;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::db::{
+	    contributor::{
+	        models::NewContributor,
+	        queries::{upsert_contributors, UpsertContributorsError},
+	    },
+	    contributor_tracking::{
+	        models::ContributorTracking,
+	        queries::{list_contributor_tracking, update_contributor_tracking, ListContributorTrackingError, UpdateContributorTrackingError},
+	    },
+	    repository::models::Repository,
+	    PgPool,
+	};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::forges::ForgeClients;
+use crate::privacy::StargazerIds;
+use crate::sync_queue::StopSignal;
+
+#[derive(Debug, Error)]
+pub enum TrackContributorsError {
+    #[error("FetchCommitAuthors: {source}")]
+    FetchCommitAuthors {
+        #[from]
+        source: FetchCommitAuthorsError,
+    },
+    #[error("UpsertContributors: {source}")]
+    UpsertContributors {
+        #[from]
+        source: UpsertContributorsError,
+    },
+    #[error("UpdateContributorTracking: {source}")]
+    UpdateContributorTracking {
+        #[from]
+        source: UpdateContributorTrackingError,
+    },
+}
+
+/// Fetches the commits pushed since `tracking` last ran and the next batch of older ones until the
+/// history is complete, stores their authors as `stargazer_ids` identifies them and records the
+/// progress. `None` when GitHub doesn't know the repository anymore.
+pub async fn track_contributors(
+    conn: &mut PgConnection,
+    github: &GitHubClient,
+    stargazer_ids: &StargazerIds,
+    repo: &Repository,
+    tracking: &ContributorTracking,
+) -> Result<Option<ContributorTracking>, TrackContributorsError> {
+    let mut progress = tracking.clone();
+    let mut commits = Vec::new();
+    if let Some(newest) = tracking.newest_commit_at {
+        let Some(history) = fetch_commit_authors(github, &repo.owner, &repo.name, Some(newest.and_utc()), None).await? else {
+            return Ok(None);
+        };
+        if !history.complete {
+            warn!(owner = %repo.owner, name = %repo.name, "too many commits since the last run, only the latest stored");
+        }
+        commits.extend(history.commits);
+    }
+    if !tracking.history_complete {
+        let until = tracking.oldest_commit_at.map(|oldest| oldest.and_utc());
+        let Some(history) = fetch_commit_authors(github, &repo.owner, &repo.name, None, until).await? else {
+            return Ok(None);
+        };
+        progress.history_complete = history.complete;
+        commits.extend(history.commits);
+    }
+
+    let committed = commits.iter().map(|commit| commit.committed_at.naive_utc());
+    progress.newest_commit_at = committed.clone().chain(tracking.newest_commit_at).max();
+    progress.oldest_commit_at = committed.chain(tracking.oldest_commit_at).min();
+    progress.tracked_at = Some(Utc::now().naive_utc());
+
+    let dates = contribution_dates(&commits, stargazer_ids);
+    let new_contributors: Vec<NewContributor> = dates
+        .iter()
+        .map(|(login, &(first, last))| NewContributor {
+            repository_id:   repo.id,
+            login,
+            first_commit_at: first,
+            last_commit_at:  last,
+        })
+        .collect();
+    if !new_contributors.is_empty() {
+        upsert_contributors(conn, &new_contributors)?;
+    }
+    Ok(Some(update_contributor_tracking(conn, &progress)?))
+}
+
+/// First and last authored date of each login among `commits`, identified by `stargazer_ids`,
+/// one entry per login as a single upsert can't touch a row twice.
+fn contribution_dates<'a>(commits: &'a [CommitAuthor], stargazer_ids: &StargazerIds) -> BTreeMap<Cow<'a, str>, (NaiveDateTime, NaiveDateTime)> {
+    let mut dates: BTreeMap<Cow<'a, str>, (NaiveDateTime, NaiveDateTime)> = BTreeMap::new();
+    for commit in commits {
+        let Some(login) = commit.login.as_deref() else {
+            continue;
+        };
+        let authored_at = authored_at(commit);
+        dates
+            .entry(stargazer_ids.stargazer_id(login))
+            .and_modify(|(first, last)| {
+                *first = (*first).min(authored_at);
+                *last = (*last).max(authored_at);
+            })
+            .or_insert((authored_at, authored_at));
+    }
+    dates
+}
+
+/// Authored date, or the commit date when the author's clock claims the commit postdates it.
+fn authored_at(commit: &CommitAuthor) -> NaiveDateTime {
+    let authored_at: DateTime<Utc> = commit.authored_at.min(commit.committed_at);
+    authored_at.naive_utc()
+}
+
+/// Outcome of [`track_all_contributors`].
+#[derive(Debug, Default)]
+pub struct ContributorSummary {
+    pub repositories: usize,
+    /// Repositories whose history is now walked back to their first commit.
+    pub complete: usize,
+    /// Repositories GitHub doesn't know anymore, e.g. deleted or renamed.
+    pub not_found: usize,
+    /// Repositories whose run failed, left for the next one.
+    pub failed: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum TrackAllContributorsError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        #[from]
+        source: DbConnRejection,
+    },
+    #[error("ListContributorTracking: {source}")]
+    ListContributorTracking {
+        #[from]
+        source: ListContributorTrackingError,
+    },
+}
+
+/// Tracks the contributors of every repository in contributors mode, of every tenant, none
+/// without GitHub credentials. A failing repository is logged and left for the next run.
+pub async fn track_all_contributors(pool: &PgPool, forges: &ForgeClients, stargazer_ids: &StargazerIds) -> Result<ContributorSummary, TrackAllContributorsError> {
+    let mut summary = ContributorSummary::default();
+    if !forges.is_available(Forge::GitHub) {
+        return Ok(summary);
+    }
+
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+        .await
+        .map_err(|source| TrackAllContributorsError::Checkout { source })?;
+    let tracked = list_contributor_tracking(&mut conn)
+        .map_err(|source| TrackAllContributorsError::ListContributorTracking { source })?;
+
+    for (tracking, repo) in tracked {
+        match track_contributors(&mut conn, forges.github(), stargazer_ids, &repo, &tracking).await {
+            Ok(Some(progress)) => {
+                summary.repositories += 1;
+                summary.complete += usize::from(progress.history_complete);
+            }
+            Ok(None) => {
+                warn!(owner = %repo.owner, name = %repo.name, "contributors repository not found on GitHub");
+                summary.not_found += 1;
+            }
+            Err(error) => {
+                warn!(owner = %repo.owner, name = %repo.name, %error, "contributor tracking failed");
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Tracks contributors every `interval`, starting right away, until `stop` fires.
+pub async fn run_contributor_tracking(pool: PgPool, forges: ForgeClients, stargazer_ids: StargazerIds, interval: Duration, stop: StopSignal) {
+    let mut ticks = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = stop.stopped() => return,
+            _ = ticks.tick() => {}
+        }
+
+        match track_all_contributors(&pool, &forges, &stargazer_ids).await {
+            Ok(summary) => info!(repositories = summary.repositories, complete = summary.complete, not_found = summary.not_found, failed = summary.failed, "tracked contributors"),
+            Err(error) => warn!(%error, "contributor tracking failed"),
+        }
+    }
+}
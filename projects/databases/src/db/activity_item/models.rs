@@ -0,0 +1,34 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::activity_items;
+use crate::db::repository::models::Repository;
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = activity_items)]
+#[diesel(primary_key(repository_id, kind, number))]
+pub struct ActivityItem {
+    pub repository_id: Uuid,
+    /// `issues` or `prs`.
+    pub kind: String,
+    pub number: i32,
+    pub opened_at: NaiveDateTime,
+    /// Unset while open, merged pull requests being closed.
+    pub closed_at: Option<NaiveDateTime>,
+    /// Last update on GitHub, where the next fetch starts from.
+    pub updated_at: NaiveDateTime,
+    pub fetched_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = activity_items)]
+pub struct NewActivityItem<'a> {
+    pub repository_id: Uuid,
+    pub kind: &'a str,
+    pub number: i32,
+    pub opened_at: NaiveDateTime,
+    pub closed_at: Option<NaiveDateTime>,
+    pub updated_at: NaiveDateTime,
+    pub fetched_at: NaiveDateTime,
+}
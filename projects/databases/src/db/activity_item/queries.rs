@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use chrono::{NaiveDate, NaiveDateTime};
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{dsl::{count_star, max, sql}, pg::upsert::excluded, prelude::*, sql_types::Date};
+use crate::db::{activity_item::models::*, schema::activity_items::dsl::*};
+
+/// Rows per insert, 7 bind parameters each.
+const INSERT_CHUNK_SIZE: usize = 5_000;
+
+#[derive(Debug, Error)]
+pub enum UpsertActivityItemsError {
+    #[error("UpsertActivityItems: {source}")]
+    UpsertActivityItems{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stores issues or pull requests, replacing the known ones: they may have been closed, or
+/// reopened, since.
+pub fn upsert_activity_items(
+    conn: &mut PgConnection,
+    new: &[NewActivityItem]
+) -> Result<usize, UpsertActivityItemsError> {
+    let mut stored = 0;
+    for chunk in new.chunks(INSERT_CHUNK_SIZE) {
+        stored += diesel::insert_into(activity_items)
+            .values(chunk)
+            .on_conflict((repository_id, kind, number))
+            .do_update()
+            .set((
+                opened_at.eq(excluded(opened_at)),
+                closed_at.eq(excluded(closed_at)),
+                updated_at.eq(excluded(updated_at)),
+                fetched_at.eq(excluded(fetched_at)),
+            ))
+            .execute(conn)
+            .map_err(|source| UpsertActivityItemsError::UpsertActivityItems{ source })?;
+    }
+    Ok(stored)
+}
+
+#[derive(Debug, Error)]
+pub enum GetLatestActivityUpdateError {
+    #[error("GetLatestActivityUpdate: {source}")]
+    GetLatestActivityUpdate{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Last update on GitHub among the repository's stored `kind_val`, `None` before the first fetch.
+pub fn get_latest_activity_update(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    kind_val: &str
+) -> Result<Option<NaiveDateTime>, GetLatestActivityUpdateError> {
+    activity_items
+        .filter(repository_id.eq(repo_id_val))
+        .filter(kind.eq(kind_val))
+        .select(max(updated_at))
+        .first(conn)
+        .map_err(|source| GetLatestActivityUpdateError::GetLatestActivityUpdate{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetDailyActivityError {
+    #[error("CountOpened: {source}")]
+    CountOpened{
+        source: diesel::result::Error
+    },
+    #[error("CountClosed: {source}")]
+    CountClosed{
+        source: diesel::result::Error
+    },
+}
+
+/// `(day, opened, closed)` of the repository's `kind_val`, oldest first, days with neither left out.
+pub fn get_daily_activity(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    kind_val: &str
+) -> Result<Vec<(NaiveDate, i64, i64)>, GetDailyActivityError> {
+    let opened = activity_items
+        .filter(repository_id.eq(repo_id_val))
+        .filter(kind.eq(kind_val))
+        .select((sql::<Date>("DATE(opened_at)"), count_star()))
+        .group_by(sql::<Date>("DATE(opened_at)"))
+        .load::<(NaiveDate, i64)>(conn)
+        .map_err(|source| GetDailyActivityError::CountOpened{ source })?;
+    let closed = activity_items
+        .filter(repository_id.eq(repo_id_val))
+        .filter(kind.eq(kind_val))
+        .filter(closed_at.is_not_null())
+        .select((sql::<Date>("DATE(closed_at)"), count_star()))
+        .group_by(sql::<Date>("DATE(closed_at)"))
+        .load::<(NaiveDate, i64)>(conn)
+        .map_err(|source| GetDailyActivityError::CountClosed{ source })?;
+
+    let mut days: BTreeMap<NaiveDate, (i64, i64)> = BTreeMap::new();
+    for (date, total) in opened {
+        days.entry(date).or_default().0 = total;
+    }
+    for (date, total) in closed {
+        days.entry(date).or_default().1 = total;
+    }
+    Ok(days.into_iter().map(|(date, (opened_on, closed_on))| (date, opened_on, closed_on)).collect())
+}
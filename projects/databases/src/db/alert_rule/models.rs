@@ -0,0 +1,109 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::analytics::alerts::{daily_stars_trigger, total_stars_trigger};
+use crate::db::schema::alert_rules;
+use crate::db::repository::models::Repository;
+
+/// Condition of an alert rule, stored as text in `alert_rules.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// More than `threshold` stars received in a single day.
+    DailyStars,
+    /// Total star count reaching `threshold`.
+    TotalStars,
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::DailyStars => "daily_stars",
+            AlertKind::TotalStars => "total_stars",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "daily_stars" => Some(AlertKind::DailyStars),
+            "total_stars" => Some(AlertKind::TotalStars),
+            _ => None,
+        }
+    }
+}
+
+/// Delivery channel of an alert rule, stored as text in `alert_rules.target`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertTarget {
+    Webhook,
+    Email,
+}
+
+impl AlertTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertTarget::Webhook => "webhook",
+            AlertTarget::Email => "email",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "webhook" => Some(AlertTarget::Webhook),
+            "email" => Some(AlertTarget::Email),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = alert_rules)]
+pub struct AlertRule {
+    pub id: Uuid,
+    pub repository_id: Uuid,
+    pub kind: String,
+    pub threshold: i32,
+    pub target: String,
+    /// Webhook URL or email address, depending on `target`.
+    pub destination: String,
+    /// Day of the star activity that last fired the rule.
+    pub last_triggered_on: Option<NaiveDate>,
+    pub created_at: NaiveDateTime,
+}
+
+impl AlertRule {
+    /// Day and star count firing the rule of condition `kind`, only considering days after it last
+    /// fired. A `TotalStars` rule fires once.
+    pub fn trigger(
+        &self,
+        kind: AlertKind,
+        daily: &[(NaiveDate, i64)],
+        cumulative: &[(NaiveDate, i64)],
+    ) -> Option<(NaiveDate, i64)> {
+        let since = match self.last_triggered_on {
+            Some(day) => day.succ_opt()?,
+            None => self.created_at.date(),
+        };
+
+        match kind {
+            AlertKind::DailyStars => daily_stars_trigger(daily, self.threshold.into(), since),
+            AlertKind::TotalStars if self.last_triggered_on.is_none() => total_stars_trigger(cumulative, self.threshold.into(), since),
+            AlertKind::TotalStars => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = alert_rules)]
+pub struct NewAlertRule<'a> {
+    pub id: Uuid,
+    pub repository_id: Uuid,
+    pub kind: &'a str,
+    pub threshold: i32,
+    pub target: &'a str,
+    pub destination: &'a str,
+}
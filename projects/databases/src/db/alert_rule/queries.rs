@@ -0,0 +1,112 @@
+use chrono::NaiveDate;
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::{alert_rule::models::*, repository::models::Repository, schema::{alert_rules::dsl::*, repositories}};
+
+#[derive(Debug, Error)]
+pub enum InsertAlertRuleError {
+    #[error("InsertAlertRule: {source}")]
+    InsertAlertRule{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn insert_alert_rule(
+    conn: &mut PgConnection,
+    new: &NewAlertRule
+) -> Result<AlertRule, InsertAlertRuleError> {
+    diesel::insert_into(alert_rules)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertAlertRuleError::InsertAlertRule{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum ListAlertRulesError {
+    #[error("ListAlertRules: {source}")]
+    ListAlertRules{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Every rule of the tenant along with the repository it watches.
+pub fn list_alert_rules(
+    conn: &mut PgConnection,
+    tenant_id_val: Uuid
+) -> Result<Vec<(AlertRule, Repository)>, ListAlertRulesError> {
+    alert_rules
+        .inner_join(repositories::table)
+        .filter(repositories::tenant_id.eq(tenant_id_val))
+        .order_by(created_at)
+        .load::<(AlertRule, Repository)>(conn)
+        .map_err(|source| ListAlertRulesError::ListAlertRules{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetAlertRulesByRepositoryError {
+    #[error("GetAlertRulesByRepository: {source}")]
+    GetAlertRulesByRepository{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn get_alert_rules_by_repository(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Vec<AlertRule>, GetAlertRulesByRepositoryError> {
+    alert_rules
+        .filter(repository_id.eq(repo_id_val))
+        .load::<AlertRule>(conn)
+        .map_err(|source| GetAlertRulesByRepositoryError::GetAlertRulesByRepository{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateAlertRuleTriggeredError {
+    #[error("UpdateAlertRuleTriggered: {source}")]
+    UpdateAlertRuleTriggered{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Remembers the day that fired the rule so it isn't notified twice.
+pub fn update_alert_rule_triggered(
+    conn: &mut PgConnection,
+    rule_id: Uuid,
+    day: NaiveDate
+) -> Result<(), UpdateAlertRuleTriggeredError> {
+    diesel::update(alert_rules.find(rule_id))
+        .set(last_triggered_on.eq(day))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|source| UpdateAlertRuleTriggeredError::UpdateAlertRuleTriggered{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteAlertRuleError {
+    #[error("DeleteAlertRule: {source}")]
+    DeleteAlertRule{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Returns whether the tenant had a rule with this id.
+pub fn delete_alert_rule(
+    conn: &mut PgConnection,
+    tenant_id_val: Uuid,
+    rule_id: Uuid
+) -> Result<bool, DeleteAlertRuleError> {
+    let owned = repositories::table
+        .filter(repositories::tenant_id.eq(tenant_id_val))
+        .select(repositories::id);
+
+    diesel::delete(alert_rules.find(rule_id).filter(repository_id.eq_any(owned)))
+        .execute(conn)
+        .map(|deleted| deleted > 0)
+        .map_err(|source| DeleteAlertRuleError::DeleteAlertRule{ source })
+}
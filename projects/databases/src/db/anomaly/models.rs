@@ -0,0 +1,26 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::star_anomalies;
+use crate::db::repository::models::Repository;
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = star_anomalies)]
+#[diesel(primary_key(repository_id, day))]
+pub struct StarAnomaly {
+    pub repository_id: Uuid,
+    pub day: NaiveDate,
+    pub stars: i32,
+    pub trailing_average: f64,
+    pub detected_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = star_anomalies)]
+pub struct NewStarAnomaly {
+    pub repository_id: Uuid,
+    pub day: NaiveDate,
+    pub stars: i32,
+    pub trailing_average: f64,
+}
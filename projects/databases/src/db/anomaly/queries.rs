@@ -0,0 +1,46 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::{anomaly::models::*, schema::star_anomalies::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum InsertStarAnomaliesError {
+    #[error("InsertStarAnomalies: {source}")]
+    InsertStarAnomalies{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stores detected spikes and returns only the ones not already known.
+pub fn insert_star_anomalies(
+    conn: &mut PgConnection,
+    new: &[NewStarAnomaly]
+) -> Result<Vec<StarAnomaly>, InsertStarAnomaliesError> {
+    diesel::insert_into(star_anomalies)
+        .values(new)
+        .on_conflict((repository_id, day))
+        .do_nothing()
+        .get_results::<StarAnomaly>(conn)
+        .map_err(|source| InsertStarAnomaliesError::InsertStarAnomalies{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarAnomaliesByRepositoryError {
+    #[error("GetStarAnomaliesByRepository: {source}")]
+    GetStarAnomaliesByRepository{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn get_star_anomalies_by_repository(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Vec<StarAnomaly>, GetStarAnomaliesByRepositoryError> {
+    star_anomalies
+        .filter(repository_id.eq(repo_id_val))
+        .order_by(day)
+        .load::<StarAnomaly>(conn)
+        .map_err(|source| GetStarAnomaliesByRepositoryError::GetStarAnomaliesByRepository{ source })
+}
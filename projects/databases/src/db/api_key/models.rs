@@ -0,0 +1,23 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::api_keys;
+
+/// Only the SHA-256 of the key is stored, the key itself is shown once at creation.
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[diesel(table_name = api_keys)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    /// Hex SHA-256 of the key.
+    pub key_hash: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = api_keys)]
+pub struct NewApiKey<'a> {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub key_hash: &'a str,
+}
@@ -0,0 +1,45 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::{api_key::models::*, schema::api_keys::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum InsertApiKeyError {
+    #[error("InsertApiKey: {source}")]
+    InsertApiKey{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn insert_api_key(
+    conn: &mut PgConnection,
+    new: &NewApiKey
+) -> Result<ApiKey, InsertApiKeyError> {
+    diesel::insert_into(api_keys)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertApiKeyError::InsertApiKey{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetTenantIdByKeyHashError {
+    #[error("GetTenantIdByKeyHash: {source}")]
+    GetTenantIdByKeyHash{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Tenant owning the key hashed to `key_hash_val`, if any.
+pub fn get_tenant_id_by_key_hash(
+    conn: &mut PgConnection,
+    key_hash_val: &str
+) -> Result<Option<Uuid>, GetTenantIdByKeyHashError> {
+    api_keys
+        .filter(key_hash.eq(key_hash_val))
+        .select(tenant_id)
+        .first::<Uuid>(conn)
+        .optional()
+        .map_err(|source| GetTenantIdByKeyHashError::GetTenantIdByKeyHash{ source })
+}
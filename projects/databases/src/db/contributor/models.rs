@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::contributors;
+use crate::db::repository::models::Repository;
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = contributors)]
+#[diesel(primary_key(repository_id, login))]
+pub struct Contributor {
+    pub repository_id: Uuid,
+    pub login: String,
+    /// Authored date of the earliest commit fetched.
+    pub first_commit_at: NaiveDateTime,
+    pub last_commit_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = contributors)]
+pub struct NewContributor<'a> {
+    pub repository_id: Uuid,
+    pub login: &'a str,
+    pub first_commit_at: NaiveDateTime,
+    pub last_commit_at: NaiveDateTime,
+}
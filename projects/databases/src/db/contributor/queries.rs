@@ -0,0 +1,76 @@
+use chrono::NaiveDate;
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{dsl::{count_star, sql}, prelude::*, sql_types::{Date, Timestamp}};
+use crate::db::{contributor::models::*, schema::contributors::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum UpsertContributorsError {
+    #[error("UpsertContributors: {source}")]
+    UpsertContributors{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stores contributors, one row per login, widening the commit dates of the known ones: batches
+/// of history come in any order.
+pub fn upsert_contributors(
+    conn: &mut PgConnection,
+    new: &[NewContributor]
+) -> Result<usize, UpsertContributorsError> {
+    diesel::insert_into(contributors)
+        .values(new)
+        .on_conflict((repository_id, login))
+        .do_update()
+        .set((
+            first_commit_at.eq(sql::<Timestamp>("LEAST(contributors.first_commit_at, excluded.first_commit_at)")),
+            last_commit_at.eq(sql::<Timestamp>("GREATEST(contributors.last_commit_at, excluded.last_commit_at)")),
+        ))
+        .execute(conn)
+        .map_err(|source| UpsertContributorsError::UpsertContributors{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetContributorsError {
+    #[error("GetContributors: {source}")]
+    GetContributors{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Contributors of the repository, first to contribute first.
+pub fn get_contributors(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Vec<Contributor>, GetContributorsError> {
+    contributors
+        .filter(repository_id.eq(repo_id_val))
+        .order_by((first_commit_at, login))
+        .load::<Contributor>(conn)
+        .map_err(|source| GetContributorsError::GetContributors{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetDailyFirstContributionsError {
+    #[error("GetDailyFirstContributions: {source}")]
+    GetDailyFirstContributions{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// `(day, contributors)` whose first commit was that day, oldest first.
+pub fn get_daily_first_contributions(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Vec<(NaiveDate, i64)>, GetDailyFirstContributionsError> {
+    contributors
+        .filter(repository_id.eq(repo_id_val))
+        .select((sql::<Date>("DATE(first_commit_at)"), count_star()))
+        .group_by(sql::<Date>("DATE(first_commit_at)"))
+        .order_by(sql::<Date>("DATE(first_commit_at)"))
+        .load::<(NaiveDate, i64)>(conn)
+        .map_err(|source| GetDailyFirstContributionsError::GetDailyFirstContributions{ source })
+}
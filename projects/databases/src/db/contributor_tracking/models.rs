@@ -0,0 +1,22 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::contributor_tracking;
+use crate::db::repository::models::Repository;
+
+/// Repository whose contributors are tracked, and how far its history was walked.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = contributor_tracking)]
+#[diesel(primary_key(repository_id))]
+pub struct ContributorTracking {
+    pub repository_id: Uuid,
+    pub started_at: NaiveDateTime,
+    /// Latest commit date fetched, the next commits are fetched from there.
+    pub newest_commit_at: Option<NaiveDateTime>,
+    /// Earliest commit date fetched, the history is backfilled from there until complete.
+    pub oldest_commit_at: Option<NaiveDateTime>,
+    pub history_complete: bool,
+    /// Last run, unset before the first.
+    pub tracked_at: Option<NaiveDateTime>,
+}
@@ -0,0 +1,114 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::{contributor_tracking::models::*, repository::models::Repository, schema::{contributor_tracking::dsl::*, repositories}};
+
+#[derive(Debug, Error)]
+pub enum StartContributorTrackingError {
+    #[error("StartContributorTracking: {source}")]
+    StartContributorTracking{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Tracks the contributors of the repository, the progress of an already tracked one kept.
+pub fn start_contributor_tracking(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<ContributorTracking, StartContributorTrackingError> {
+    conn.transaction(|conn| {
+        diesel::insert_into(contributor_tracking)
+            .values(repository_id.eq(repo_id_val))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        contributor_tracking.find(repo_id_val).first::<ContributorTracking>(conn)
+    })
+    .map_err(|source| StartContributorTrackingError::StartContributorTracking{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum StopContributorTrackingError {
+    #[error("StopContributorTracking: {source}")]
+    StopContributorTracking{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stops tracking the contributors of the repository, keeping those stored. Whether they were tracked.
+pub fn stop_contributor_tracking(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<bool, StopContributorTrackingError> {
+    diesel::delete(contributor_tracking.find(repo_id_val))
+        .execute(conn)
+        .map(|deleted| deleted > 0)
+        .map_err(|source| StopContributorTrackingError::StopContributorTracking{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetContributorTrackingError {
+    #[error("GetContributorTracking: {source}")]
+    GetContributorTracking{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn get_contributor_tracking(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Option<ContributorTracking>, GetContributorTrackingError> {
+    contributor_tracking
+        .find(repo_id_val)
+        .first::<ContributorTracking>(conn)
+        .optional()
+        .map_err(|source| GetContributorTrackingError::GetContributorTracking{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum ListContributorTrackingError {
+    #[error("ListContributorTracking: {source}")]
+    ListContributorTracking{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Repositories whose contributors are tracked, of every tenant, archived ones left out.
+pub fn list_contributor_tracking(
+    conn: &mut PgConnection
+) -> Result<Vec<(ContributorTracking, Repository)>, ListContributorTrackingError> {
+    contributor_tracking
+        .inner_join(repositories::table)
+        .filter(repositories::archived_at.is_null())
+        .order_by((repositories::tenant_id, repositories::owner, repositories::name))
+        .load::<(ContributorTracking, Repository)>(conn)
+        .map_err(|source| ListContributorTrackingError::ListContributorTracking{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateContributorTrackingError {
+    #[error("UpdateContributorTracking: {source}")]
+    UpdateContributorTracking{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Records how far the history of the repository was walked.
+pub fn update_contributor_tracking(
+    conn: &mut PgConnection,
+    progress: &ContributorTracking
+) -> Result<ContributorTracking, UpdateContributorTrackingError> {
+    diesel::update(contributor_tracking.find(progress.repository_id))
+        .set((
+            newest_commit_at.eq(progress.newest_commit_at),
+            oldest_commit_at.eq(progress.oldest_commit_at),
+            history_complete.eq(progress.history_complete),
+            tracked_at.eq(progress.tracked_at),
+        ))
+        .get_result::<ContributorTracking>(conn)
+        .map_err(|source| UpdateContributorTrackingError::UpdateContributorTracking{ source })
+}
@@ -0,0 +1,18 @@
+use chrono::NaiveDate;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::daily_star_aggregates;
+use crate::db::repository::models::Repository;
+
+/// Stars received by a repository on a day whose rows were compacted.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Insertable)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = daily_star_aggregates)]
+#[diesel(primary_key(repository_id, day))]
+pub struct DailyStarAggregate {
+    pub repository_id: Uuid,
+    pub day: NaiveDate,
+    pub stars: i32,
+    /// Of `stars`, those of suspected bots.
+    pub bot_stars: i32,
+}
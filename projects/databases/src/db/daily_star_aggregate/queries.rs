@@ -0,0 +1,108 @@
+use chrono::NaiveDate;
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{prelude::*, sql_types::{BigInt, Timestamp}};
+use crate::db::schema::{repositories, stars as star_rows};
+
+#[derive(Debug, Error)]
+pub enum GetRepositoriesWithStarsBeforeError {
+    #[error("GetRepositoriesWithStarsBefore: {source}")]
+    GetRepositoriesWithStarsBefore{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Repositories still storing star rows starred before `before`, archived ones left out as their stars are
+/// kept as they were.
+pub fn get_repositories_with_stars_before(
+    conn: &mut PgConnection,
+    before: NaiveDate
+) -> Result<Vec<Uuid>, GetRepositoriesWithStarsBeforeError> {
+    let archived = repositories::table
+        .filter(repositories::archived_at.is_not_null())
+        .select(repositories::id);
+    star_rows::table
+        .filter(star_rows::starred_at.lt(before.and_time(chrono::NaiveTime::MIN)))
+        .filter(star_rows::repository_id.ne_all(archived))
+        .select(star_rows::repository_id)
+        .distinct()
+        .load::<Uuid>(conn)
+        .map_err(|source| GetRepositoriesWithStarsBeforeError::GetRepositoriesWithStarsBefore{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum CompactStarsError {
+    #[error("CompactStars: {source}")]
+    CompactStars{
+        #[from]
+        source: diesel::result::Error
+    },
+    #[error("MoveStarsToAggregates: {source}")]
+    MoveStarsToAggregates{
+        source: diesel::result::Error
+    },
+    #[error("UpdateCompactedBefore: {source}")]
+    UpdateCompactedBefore{
+        source: diesel::result::Error
+    },
+}
+
+#[derive(QueryableByName)]
+struct CompactedStars {
+    #[diesel(sql_type = BigInt)]
+    deleted: i64,
+}
+
+/// Rolls the repository's stars starred before `before` up into daily aggregates and deletes their rows,
+/// all or none. Returns how many rows were deleted.
+///
+/// Stars are counted from the rows the `DELETE` returns, so one stored meanwhile by the star webhook is
+/// either counted and deleted or left for the next run, never deleted uncounted.
+///
+/// Later syncs skip the stars starred before the repository's `compacted_before`, moved up to `before`.
+pub fn compact_stars(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    before: NaiveDate
+) -> Result<usize, CompactStarsError> {
+    let cutoff = before.and_time(chrono::NaiveTime::MIN);
+
+    conn.transaction(|conn| {
+        // Days compacted earlier only gain the stars stored since, e.g. by the star webhook.
+        let compacted = diesel::sql_query(
+            "WITH deleted AS (
+                DELETE FROM stars
+                WHERE repository_id = $1 AND starred_at < $2
+                RETURNING starred_at, suspected_bot
+            ),
+            upserted AS (
+                INSERT INTO daily_star_aggregates (repository_id, day, stars, bot_stars)
+                SELECT $1, DATE(starred_at),
+                    LEAST(COUNT(*), 2147483647)::INTEGER,
+                    LEAST(COUNT(*) FILTER (WHERE suspected_bot), 2147483647)::INTEGER
+                FROM deleted
+                GROUP BY DATE(starred_at)
+                ON CONFLICT (repository_id, day) DO UPDATE
+                SET stars = daily_star_aggregates.stars + EXCLUDED.stars,
+                    bot_stars = daily_star_aggregates.bot_stars + EXCLUDED.bot_stars
+            )
+            SELECT COUNT(*) AS deleted FROM deleted"
+        )
+            .bind::<diesel::sql_types::Uuid, _>(repo_id_val)
+            .bind::<Timestamp, _>(cutoff)
+            .get_result::<CompactedStars>(conn)
+            .map_err(|source| CompactStarsError::MoveStarsToAggregates{ source })?;
+
+        diesel::update(
+            repositories::table
+                .find(repo_id_val)
+                .filter(repositories::compacted_before.is_null().or(repositories::compacted_before.lt(before)))
+        )
+            .set(repositories::compacted_before.eq(before))
+            .execute(conn)
+            .map_err(|source| CompactStarsError::UpdateCompactedBefore{ source })?;
+
+        Ok(usize::try_from(compacted.deleted).unwrap_or(usize::MAX))
+    })
+}
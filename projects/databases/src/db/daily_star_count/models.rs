@@ -0,0 +1,117 @@
+use chrono::{Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Date, Double, Nullable};
+use crate::db::schema::daily_star_counts;
+use crate::db::repository::models::Repository;
+
+/// Stars received by a repository on a day, stored and compacted ones alike.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Insertable)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = daily_star_counts)]
+#[diesel(primary_key(repository_id, day))]
+pub struct DailyStarCount {
+    pub repository_id: Uuid,
+    pub day: NaiveDate,
+    pub stars: i32,
+    /// Of `stars`, those of suspected bots.
+    pub bot_stars: i32,
+}
+
+/// Length of the periods star growth is compared over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GrowthPeriod {
+    /// Weeks starting on Monday.
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    Year,
+}
+
+impl GrowthPeriod {
+    /// `date_trunc` field of the period.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GrowthPeriod::Week => "week",
+            GrowthPeriod::Month => "month",
+            GrowthPeriod::Quarter => "quarter",
+            GrowthPeriod::Year => "year",
+        }
+    }
+
+    /// Length of the period as a Postgres interval.
+    pub fn interval(&self) -> &'static str {
+        match self {
+            GrowthPeriod::Week => "1 week",
+            GrowthPeriod::Month => "1 month",
+            GrowthPeriod::Quarter => "3 months",
+            GrowthPeriod::Year => "1 year",
+        }
+    }
+
+    /// First day of the period after the one starting on `start`.
+    pub fn next_start(&self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            GrowthPeriod::Week => start.checked_add_days(Days::new(7)),
+            GrowthPeriod::Month => start.checked_add_months(Months::new(1)),
+            GrowthPeriod::Quarter => start.checked_add_months(Months::new(3)),
+            GrowthPeriod::Year => start.checked_add_months(Months::new(12)),
+        }
+    }
+
+    /// Periods between one and the same period a year earlier.
+    pub fn per_year(&self) -> i32 {
+        match self {
+            GrowthPeriod::Week => 52,
+            GrowthPeriod::Month => 12,
+            GrowthPeriod::Quarter => 4,
+            GrowthPeriod::Year => 1,
+        }
+    }
+}
+
+/// Stars received during a period, compared to the previous period and the same period a year earlier.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct PeriodStarGrowth {
+    #[diesel(sql_type = Date)]
+    pub period_start: NaiveDate,
+    #[diesel(sql_type = BigInt)]
+    pub stars: i64,
+    #[diesel(sql_type = BigInt)]
+    pub previous_stars: i64,
+    #[diesel(sql_type = BigInt)]
+    pub change: i64,
+    /// `None` when the previous period had no stars.
+    #[diesel(sql_type = Nullable<Double>)]
+    pub change_percent: Option<f64>,
+    #[diesel(sql_type = BigInt)]
+    pub year_ago_stars: i64,
+    #[diesel(sql_type = BigInt)]
+    pub year_over_year_change: i64,
+    /// `None` when the period a year earlier had no stars.
+    #[diesel(sql_type = Nullable<Double>)]
+    pub year_over_year_percent: Option<f64>,
+}
+
+/// Stars of each day of a week, Monday first.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct WeekdayStars {
+    #[diesel(sql_type = Date)]
+    pub week_start: NaiveDate,
+    /// Seven entries, `None` for the days outside the range asked for.
+    #[diesel(sql_type = Array<Nullable<BigInt>>)]
+    pub stars: Vec<Option<i64>>,
+}
+
+/// Stars received during a period.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct PeriodStars {
+    #[diesel(sql_type = Date)]
+    pub period_start: NaiveDate,
+    #[diesel(sql_type = BigInt)]
+    pub stars: i64,
+}
@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+use chrono::{NaiveDate, NaiveTime};
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{dsl::{count_star, sql, sum}, prelude::*, sql_types::{BigInt, Date, Integer, Text}};
+use crate::db::{daily_star_count::models::*, schema::{daily_star_aggregates, daily_star_counts::dsl::*, repositories, stars as star_rows}};
+
+/// Rows per insert, 4 bind parameters each.
+const INSERT_CHUNK_SIZE: usize = 10_000;
+
+#[derive(Debug, Error)]
+pub enum RefreshDailyStarCountsError {
+    #[error("RefreshDailyStarCounts: {source}")]
+    RefreshDailyStarCounts{
+        #[from]
+        source: diesel::result::Error
+    },
+    #[error("CountStoredStars: {source}")]
+    CountStoredStars{
+        source: diesel::result::Error
+    },
+    #[error("GetDailyStarAggregates: {source}")]
+    GetDailyStarAggregates{
+        source: diesel::result::Error
+    },
+    #[error("DeleteDailyStarCounts: {source}")]
+    DeleteDailyStarCounts{
+        source: diesel::result::Error
+    },
+    #[error("InsertDailyStarCounts: {source}")]
+    InsertDailyStarCounts{
+        source: diesel::result::Error
+    },
+    #[error("SumDailyStarCounts: {source}")]
+    SumDailyStarCounts{
+        source: diesel::result::Error
+    },
+    #[error("UpdateTotalStars: {source}")]
+    UpdateTotalStars{
+        source: diesel::result::Error
+    },
+}
+
+/// Recomputes the repository's daily star counts from its stars and compacted aggregates,
+/// from `since` on or every day when `None`. Returns how many days have stars.
+///
+/// Called after every write to the repository's stars, reads only see the counts. The
+/// repository's `total_stars` is set to their sum in the same transaction.
+pub fn refresh_daily_star_counts(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    since: Option<NaiveDate>
+) -> Result<usize, RefreshDailyStarCountsError> {
+    conn.transaction(|conn| {
+        let mut stored = star_rows::table
+            .filter(star_rows::repository_id.eq(repo_id_val))
+            .select((
+                sql::<Date>("DATE(starred_at)"),
+                count_star(),
+                sql::<BigInt>("COUNT(*) FILTER (WHERE suspected_bot)")
+            ))
+            .group_by(sql::<Date>("DATE(starred_at)"))
+            .into_boxed();
+        let mut aggregated = daily_star_aggregates::table
+            .filter(daily_star_aggregates::repository_id.eq(repo_id_val))
+            .select((daily_star_aggregates::day, daily_star_aggregates::stars, daily_star_aggregates::bot_stars))
+            .into_boxed();
+        if let Some(since) = since {
+            stored = stored.filter(star_rows::starred_at.ge(since.and_time(NaiveTime::MIN)));
+            aggregated = aggregated.filter(daily_star_aggregates::day.ge(since));
+        }
+
+        let stored = stored
+            .load::<(NaiveDate, i64, i64)>(conn)
+            .map_err(|source| RefreshDailyStarCountsError::CountStoredStars{ source })?;
+        let aggregated = aggregated
+            .load::<(NaiveDate, i32, i32)>(conn)
+            .map_err(|source| RefreshDailyStarCountsError::GetDailyStarAggregates{ source })?;
+
+        // Compaction leaves no rows behind, but the star webhook may store a star on a compacted day.
+        let mut days = BTreeMap::new();
+        let stored = stored.into_iter().map(|(date, count, bots)| (date, i32::try_from(count).unwrap_or(i32::MAX), i32::try_from(bots).unwrap_or(i32::MAX)));
+        for (date, count, bots) in aggregated.into_iter().chain(stored) {
+            let (day_stars, day_bots) = days.entry(date).or_insert((0i32, 0i32));
+            *day_stars += count;
+            *day_bots += bots;
+        }
+        let counts: Vec<DailyStarCount> = days
+            .into_iter()
+            .map(|(date, (count, bots))| DailyStarCount { repository_id: repo_id_val, day: date, stars: count, bot_stars: bots })
+            .collect();
+
+        let stale = daily_star_counts
+            .filter(repository_id.eq(repo_id_val))
+            .filter(day.ge(since.unwrap_or(NaiveDate::MIN)));
+        diesel::delete(stale)
+            .execute(conn)
+            .map_err(|source| RefreshDailyStarCountsError::DeleteDailyStarCounts{ source })?;
+        for chunk in counts.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(daily_star_counts)
+                .values(chunk)
+                .execute(conn)
+                .map_err(|source| RefreshDailyStarCountsError::InsertDailyStarCounts{ source })?;
+        }
+
+        // Days before `since` are untouched, summing every day keeps the total exact.
+        let total = daily_star_counts
+            .filter(repository_id.eq(repo_id_val))
+            .select(sum(stars))
+            .first::<Option<i64>>(conn)
+            .map_err(|source| RefreshDailyStarCountsError::SumDailyStarCounts{ source })?;
+        diesel::update(repositories::table.find(repo_id_val))
+            .set(repositories::total_stars.eq(total.unwrap_or(0)))
+            .execute(conn)
+            .map_err(|source| RefreshDailyStarCountsError::UpdateTotalStars{ source })?;
+
+        Ok(counts.len())
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum GetPeriodStarGrowthError {
+    #[error("GetPeriodStarGrowth: {source}")]
+    GetPeriodStarGrowth{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stars of the repository over the last `periods` periods up to the one containing `as_of`, most
+/// recent first, each compared to the period before it and to the same period a year earlier.
+/// Days after `as_of` aren't counted, so the last period may be partial.
+///
+/// Periods are generated and compared in SQL: the year-earlier ones are generated too, the window
+/// functions need them, and dropped from the answer.
+pub fn get_period_star_growth(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    period: GrowthPeriod,
+    as_of: NaiveDate,
+    periods: i32,
+) -> Result<Vec<PeriodStarGrowth>, GetPeriodStarGrowthError> {
+    diesel::sql_query(
+        "WITH periods AS (
+            SELECT generate_series(
+                date_trunc($2, $3::timestamp) - ($4 - 1 + $5) * $6::interval,
+                date_trunc($2, $3::timestamp),
+                $6::interval
+            )::date AS period_start
+        ),
+        totals AS (
+            SELECT periods.period_start, COALESCE(SUM(daily_star_counts.stars), 0)::BIGINT AS stars
+            FROM periods
+            LEFT JOIN daily_star_counts
+                ON daily_star_counts.repository_id = $1
+                AND daily_star_counts.day >= periods.period_start
+                AND daily_star_counts.day < (periods.period_start + $6::interval)::date
+                AND daily_star_counts.day <= $3
+            GROUP BY periods.period_start
+        ),
+        compared AS (
+            SELECT
+                period_start,
+                stars,
+                COALESCE(LAG(stars, 1) OVER by_start, 0) AS previous_stars,
+                COALESCE(LAG(stars, $5) OVER by_start, 0) AS year_ago_stars
+            FROM totals
+            WINDOW by_start AS (ORDER BY period_start)
+        )
+        SELECT
+            period_start,
+            stars,
+            previous_stars,
+            stars - previous_stars AS change,
+            ROUND(100.0 * (stars - previous_stars) / NULLIF(previous_stars, 0), 1)::DOUBLE PRECISION AS change_percent,
+            year_ago_stars,
+            stars - year_ago_stars AS year_over_year_change,
+            ROUND(100.0 * (stars - year_ago_stars) / NULLIF(year_ago_stars, 0), 1)::DOUBLE PRECISION AS year_over_year_percent
+        FROM compared
+        ORDER BY period_start DESC
+        LIMIT $4"
+    )
+        .bind::<diesel::sql_types::Uuid, _>(repo_id_val)
+        .bind::<Text, _>(period.as_str())
+        .bind::<Date, _>(as_of)
+        .bind::<Integer, _>(periods)
+        .bind::<Integer, _>(period.per_year())
+        .bind::<Text, _>(period.interval())
+        .load::<PeriodStarGrowth>(conn)
+        .map_err(|source| GetPeriodStarGrowthError::GetPeriodStarGrowth{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarsPerPeriodError {
+    #[error("GetStarsPerPeriod: {source}")]
+    GetStarsPerPeriod{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stars of the repository per period, from the one of its first star to the one containing
+/// `as_of`, oldest first. Periods without stars are listed with 0, days after `as_of` aren't counted.
+pub fn get_stars_per_period(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    period: GrowthPeriod,
+    as_of: NaiveDate,
+) -> Result<Vec<PeriodStars>, GetStarsPerPeriodError> {
+    diesel::sql_query(
+        "WITH first_period AS (
+            SELECT date_trunc($2, MIN(day)::timestamp) AS period_start
+            FROM daily_star_counts
+            WHERE repository_id = $1 AND day <= $3
+        ),
+        periods AS (
+            SELECT generate_series(first_period.period_start, date_trunc($2, $3::timestamp), $4::interval)::date AS period_start
+            FROM first_period
+            WHERE first_period.period_start IS NOT NULL
+        )
+        SELECT periods.period_start, COALESCE(SUM(daily_star_counts.stars), 0)::BIGINT AS stars
+        FROM periods
+        LEFT JOIN daily_star_counts
+            ON daily_star_counts.repository_id = $1
+            AND daily_star_counts.day >= periods.period_start
+            AND daily_star_counts.day < (periods.period_start + $4::interval)::date
+            AND daily_star_counts.day <= $3
+        GROUP BY periods.period_start
+        ORDER BY periods.period_start"
+    )
+        .bind::<diesel::sql_types::Uuid, _>(repo_id_val)
+        .bind::<Text, _>(period.as_str())
+        .bind::<Date, _>(as_of)
+        .bind::<Text, _>(period.interval())
+        .load::<PeriodStars>(conn)
+        .map_err(|source| GetStarsPerPeriodError::GetStarsPerPeriod{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetWeekdayStarsError {
+    #[error("GetWeekdayStars: {source}")]
+    GetWeekdayStars{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stars of the repository per weekday of every ISO week from the one containing `from` to the one
+/// containing `to`, oldest first. Days without stars are 0, days outside `from..=to` `None`.
+pub fn get_weekday_stars(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<WeekdayStars>, GetWeekdayStarsError> {
+    diesel::sql_query(
+        "WITH days AS (
+            SELECT generate_series(
+                date_trunc('week', $2::timestamp),
+                date_trunc('week', $3::timestamp) + interval '6 days',
+                interval '1 day'
+            )::date AS day
+        )
+        SELECT
+            date_trunc('week', days.day)::date AS week_start,
+            array_agg(
+                CASE WHEN days.day BETWEEN $2 AND $3 THEN COALESCE(daily_star_counts.stars, 0)::BIGINT END
+                ORDER BY days.day
+            ) AS stars
+        FROM days
+        LEFT JOIN daily_star_counts
+            ON daily_star_counts.repository_id = $1
+            AND daily_star_counts.day = days.day
+        GROUP BY week_start
+        ORDER BY week_start"
+    )
+        .bind::<diesel::sql_types::Uuid, _>(repo_id_val)
+        .bind::<Date, _>(from)
+        .bind::<Date, _>(to)
+        .load::<WeekdayStars>(conn)
+        .map_err(|source| GetWeekdayStarsError::GetWeekdayStars{ source })
+}
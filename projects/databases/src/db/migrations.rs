@@ -0,0 +1,23 @@
+use diesel::PgConnection;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use thiserror::Error;
+
+/// SQL migrations of `migrations/`, bundled into the binary.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+#[derive(Debug, Error)]
+pub enum RunPendingMigrationsError {
+    #[error("RunPendingMigrations: {source}")]
+    RunPendingMigrations{
+        source: Box<dyn std::error::Error + Send + Sync>
+    },
+}
+
+/// Applies the migrations not yet recorded in the database, returns their versions.
+pub fn run_pending_migrations(
+    conn: &mut PgConnection
+) -> Result<Vec<String>, RunPendingMigrationsError> {
+    conn.run_pending_migrations(MIGRATIONS)
+        .map(|versions| versions.iter().map(ToString::to_string).collect())
+        .map_err(|source| RunPendingMigrationsError::RunPendingMigrations{ source })
+}
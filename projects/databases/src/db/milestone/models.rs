@@ -0,0 +1,24 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::milestones;
+use crate::db::repository::models::Repository;
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = milestones)]
+#[diesel(primary_key(repository_id, stars))]
+pub struct Milestone {
+    pub repository_id: Uuid,
+    pub stars: i32,
+    pub reached_on: NaiveDate,
+    pub recorded_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = milestones)]
+pub struct NewMilestone {
+    pub repository_id: Uuid,
+    pub stars: i32,
+    pub reached_on: NaiveDate,
+}
@@ -0,0 +1,46 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::{milestone::models::*, schema::milestones::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum InsertMilestonesError {
+    #[error("InsertMilestones: {source}")]
+    InsertMilestones{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Records milestone crossings, keeping the first recorded date of known ones.
+pub fn insert_milestones(
+    conn: &mut PgConnection,
+    new: &[NewMilestone]
+) -> Result<usize, InsertMilestonesError> {
+    diesel::insert_into(milestones)
+        .values(new)
+        .on_conflict((repository_id, stars))
+        .do_nothing()
+        .execute(conn)
+        .map_err(|source| InsertMilestonesError::InsertMilestones{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetMilestonesByRepositoryError {
+    #[error("GetMilestonesByRepository: {source}")]
+    GetMilestonesByRepository{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn get_milestones_by_repository(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Vec<Milestone>, GetMilestonesByRepositoryError> {
+    milestones
+        .filter(repository_id.eq(repo_id_val))
+        .order_by(stars)
+        .load::<Milestone>(conn)
+        .map_err(|source| GetMilestonesByRepositoryError::GetMilestonesByRepository{ source })
+}
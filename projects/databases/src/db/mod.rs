@@ -1,8 +1,33 @@
 pub mod schema;
+pub mod activity_item;
+pub mod alert_rule;
+pub mod api_key;
+pub mod anomaly;
+pub mod contributor;
+pub mod contributor_tracking;
+pub mod daily_star_aggregate;
+pub mod daily_star_count;
+pub mod migrations;
+pub mod milestone;
+pub mod pool;
+pub mod release;
+pub mod report;
 pub mod star;
+pub mod star_count_snapshot;
+pub mod star_verification;
+pub mod stargazer_email;
+pub mod stargazer_profile;
 pub mod repository;
+pub mod repository_alias;
+pub mod repository_tag;
+pub mod sync_batch;
+pub mod sync_job;
+pub mod sync_run;
+pub mod tenant;
+pub mod traffic_snapshot;
+pub mod webhook_outbox;
 
 use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::PgConnection;
 
-pub type PgPool = Pool<ConnectionManager<PgConnection>>;
\ No newline at end of file
+pub type PgPool = Pool<ConnectionManager<PgConnection>>;
@@ -0,0 +1,240 @@
+//! Instrumented connection pool
+//!
+//! Every connection logs the queries slower than a threshold, SQL only since
+//! bind parameters may hold user data. The pool records how long checkouts
+//! wait for a free connection, reported with its size by [`PoolMetrics::stats`].
+//!
+//! Read-only endpoints check out from a [`ReadPool`], served by a replica
+//! when one is configured and by the primary when the replica can't be reached.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use diesel::{
+    connection::{Instrumentation, InstrumentationEvent},
+    r2d2::{
+        event::{CheckoutEvent, TimeoutEvent},
+        Builder, ConnectionManager, CustomizeConnection, HandleEvent, Pool, PooledConnection,
+    },
+    Connection, PgConnection,
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::db::PgPool;
+
+/// Separates the SQL from its bind parameters in the text of a diesel query.
+const BINDS_SEPARATOR: &str = " -- binds: ";
+
+/// Longest wait for a replica connection before reading from the primary instead.
+const REPLICA_CHECKOUT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Time reads skip the replica after it failed, rather than each waiting for it.
+const REPLICA_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_size: u32,
+    /// Queries taking longer are logged as warnings.
+    pub slow_query_threshold: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 10,
+            slow_query_threshold: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Pool of instrumented connections to `database_url`, along with its metrics.
+pub fn build_pg_pool(database_url: &str, config: &PoolConfig) -> Result<(PgPool, PoolMetrics), r2d2::Error> {
+    let metrics = PoolMetrics::default();
+    let pool = pool_builder(config, &metrics).build(ConnectionManager::new(database_url))?;
+
+    Ok((pool, metrics))
+}
+
+/// Like [`build_pg_pool`], but connecting on first use: an unreachable database doesn't fail startup.
+pub fn build_lazy_pg_pool(database_url: &str, config: &PoolConfig) -> (PgPool, PoolMetrics) {
+    let metrics = PoolMetrics::default();
+    let pool = pool_builder(config, &metrics).build_unchecked(ConnectionManager::new(database_url));
+
+    (pool, metrics)
+}
+
+fn pool_builder(config: &PoolConfig, metrics: &PoolMetrics) -> Builder<ConnectionManager<PgConnection>> {
+    Pool::builder()
+        .max_size(config.max_size)
+        .connection_customizer(Box::new(SlowQueryLogging { threshold: config.slow_query_threshold }))
+        .event_handler(Box::new(metrics.clone()))
+}
+
+/// Connections for queries that don't write, from the replica if any. Cloning is cheap.
+///
+/// Replicas lag behind the primary: reads may miss the latest sync for a moment.
+#[derive(Debug, Clone)]
+pub struct ReadPool {
+    primary: PgPool,
+    replica: Option<(PgPool, PoolMetrics)>,
+    /// Set when the replica last failed, shared by clones.
+    replica_failed_at: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ReadPool {
+    /// Reads go to `replica` when given, to `primary` otherwise.
+    pub fn new(primary: PgPool, replica: Option<(PgPool, PoolMetrics)>) -> Self {
+        Self { primary, replica, replica_failed_at: Arc::default() }
+    }
+
+    /// A replica connection, or a primary one when none is available in time
+    /// or the replica failed less than [`REPLICA_RETRY_AFTER`] ago.
+    pub fn get(&self) -> Result<PooledConnection<ConnectionManager<PgConnection>>, r2d2::Error> {
+        self.get_timeout(self.primary.connection_timeout())
+    }
+
+    /// Like [`ReadPool::get`], waiting at most `timeout` for a primary connection.
+    pub fn get_timeout(&self, timeout: Duration) -> Result<PooledConnection<ConnectionManager<PgConnection>>, r2d2::Error> {
+        if let Some((replica, _)) = &self.replica {
+            if !self.replica_cooling_down() {
+                match replica.get_timeout(REPLICA_CHECKOUT_TIMEOUT) {
+                    Ok(conn) => return Ok(conn),
+                    Err(error) => {
+                        warn!(%error, retry_after_s = REPLICA_RETRY_AFTER.as_secs(), "replica unavailable, reading from the primary");
+                        *self.lock_replica_failed_at() = Some(Instant::now());
+                    }
+                }
+            }
+        }
+        self.primary.get_timeout(timeout)
+    }
+
+    fn replica_cooling_down(&self) -> bool {
+        self.lock_replica_failed_at()
+            .is_some_and(|failed_at| failed_at.elapsed() < REPLICA_RETRY_AFTER)
+    }
+
+    fn lock_replica_failed_at(&self) -> std::sync::MutexGuard<'_, Option<Instant>> {
+        // Only ever holds an instant, a panic can't leave it half-written.
+        self.replica_failed_at.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Usage of the replica pool, when there is one.
+    pub fn replica_stats(&self) -> Option<PoolStats> {
+        self.replica.as_ref().map(|(replica, metrics)| metrics.stats(replica))
+    }
+}
+
+/// Installs a [`SlowQueryLogger`] on every new connection.
+#[derive(Debug)]
+struct SlowQueryLogging {
+    threshold: Duration,
+}
+
+impl CustomizeConnection<PgConnection, diesel::r2d2::Error> for SlowQueryLogging {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.set_instrumentation(SlowQueryLogger { threshold: self.threshold, started: None });
+        Ok(())
+    }
+}
+
+/// Times the queries of a connection, which only runs one at a time.
+struct SlowQueryLogger {
+    threshold: Duration,
+    started: Option<Instant>,
+}
+
+impl Instrumentation for SlowQueryLogger {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => self.started = Some(Instant::now()),
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                let Some(elapsed) = self.started.take().map(|started| started.elapsed()) else {
+                    return;
+                };
+                if elapsed < self.threshold {
+                    return;
+                }
+
+                // Only rendered for slow queries, the text walks the whole query.
+                let query = query.to_string();
+                let sql = query.split_once(BINDS_SEPARATOR).map_or(query.as_str(), |(sql, _)| sql);
+                let error = error.map(ToString::to_string);
+                warn!(elapsed_ms = elapsed.as_millis() as u64, sql, ?error, "slow query");
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PoolMetricsInner {
+    checkouts: AtomicU64,
+    timeouts: AtomicU64,
+    wait_micros: AtomicU64,
+    max_wait_micros: AtomicU64,
+}
+
+/// Checkout counters of a pool built by [`build_pg_pool`]. Cloning is cheap, clones share the counters.
+#[derive(Debug, Clone, Default)]
+pub struct PoolMetrics {
+    inner: Arc<PoolMetricsInner>,
+}
+
+/// Snapshot of the pool, served by the metrics endpoint. Waits are counted since startup.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct PoolStats {
+    pub max_size: u32,
+    /// Open connections, idle or in use.
+    pub connections: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    pub checkouts: u64,
+    /// Checkouts that gave up waiting for a connection.
+    pub timeouts: u64,
+    pub mean_wait_ms: f64,
+    pub max_wait_ms: f64,
+}
+
+impl PoolMetrics {
+    pub fn stats(&self, pool: &PgPool) -> PoolStats {
+        let state = pool.state();
+        let checkouts = self.inner.checkouts.load(Ordering::Relaxed);
+        let wait_micros = self.inner.wait_micros.load(Ordering::Relaxed);
+
+        PoolStats {
+            max_size: pool.max_size(),
+            connections: state.connections,
+            idle: state.idle_connections,
+            in_use: state.connections - state.idle_connections,
+            checkouts,
+            timeouts: self.inner.timeouts.load(Ordering::Relaxed),
+            mean_wait_ms: match checkouts {
+                0 => 0.0,
+                checkouts => wait_micros as f64 / checkouts as f64 / 1000.0,
+            },
+            max_wait_ms: self.inner.max_wait_micros.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+impl HandleEvent for PoolMetrics {
+    fn handle_checkout(&self, event: CheckoutEvent) {
+        let wait_micros = event.duration().as_micros() as u64;
+        self.inner.checkouts.fetch_add(1, Ordering::Relaxed);
+        self.inner.wait_micros.fetch_add(wait_micros, Ordering::Relaxed);
+        self.inner.max_wait_micros.fetch_max(wait_micros, Ordering::Relaxed);
+    }
+
+    fn handle_timeout(&self, event: TimeoutEvent) {
+        self.inner.timeouts.fetch_add(1, Ordering::Relaxed);
+        warn!(waited_ms = event.timeout().as_millis() as u64, "no database connection available in time");
+    }
+}
@@ -0,0 +1,34 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::releases;
+use crate::db::repository::models::Repository;
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = releases)]
+#[diesel(primary_key(repository_id, tag_name))]
+pub struct Release {
+    pub repository_id: Uuid,
+    pub tag_name: String,
+    pub name: Option<String>,
+    /// `release`, or `tag` for a tag without a release.
+    pub kind: String,
+    pub prerelease: bool,
+    pub url: Option<String>,
+    pub published_at: NaiveDateTime,
+    pub fetched_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = releases)]
+pub struct NewRelease {
+    pub repository_id: Uuid,
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub kind: String,
+    pub prerelease: bool,
+    pub url: Option<String>,
+    pub published_at: NaiveDateTime,
+    pub fetched_at: NaiveDateTime,
+}
@@ -0,0 +1,63 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{pg::upsert::excluded, prelude::*};
+use crate::db::{release::models::*, schema::releases::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum UpsertReleasesError {
+    #[error("UpsertReleases: {source}")]
+    UpsertReleases{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stores the releases, replacing those of known tags so edited ones are up to date. Releases no
+/// longer fetched are kept, only the latest ones are.
+pub fn upsert_releases(
+    conn: &mut PgConnection,
+    new: &[NewRelease]
+) -> Result<usize, UpsertReleasesError> {
+    diesel::insert_into(releases)
+        .values(new)
+        .on_conflict((repository_id, tag_name))
+        .do_update()
+        .set((
+            name.eq(excluded(name)),
+            kind.eq(excluded(kind)),
+            prerelease.eq(excluded(prerelease)),
+            url.eq(excluded(url)),
+            published_at.eq(excluded(published_at)),
+            fetched_at.eq(excluded(fetched_at)),
+        ))
+        .execute(conn)
+        .map_err(|source| UpsertReleasesError::UpsertReleases{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetReleasesByRepositoryError {
+    #[error("GetReleasesByRepository: {source}")]
+    GetReleasesByRepository{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Releases of the repositories, oldest first.
+pub fn get_releases_by_repositories(
+    conn: &mut PgConnection,
+    repo_ids: &[Uuid]
+) -> Result<Vec<Release>, GetReleasesByRepositoryError> {
+    releases
+        .filter(repository_id.eq_any(repo_ids))
+        .order_by((published_at, tag_name))
+        .load::<Release>(conn)
+        .map_err(|source| GetReleasesByRepositoryError::GetReleasesByRepository{ source })
+}
+
+pub fn get_releases_by_repository(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Vec<Release>, GetReleasesByRepositoryError> {
+    get_releases_by_repositories(conn, &[repo_id_val])
+}
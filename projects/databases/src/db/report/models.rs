@@ -0,0 +1,51 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::reports;
+
+/// Kind of a report, stored as text in `reports.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKind {
+    /// Stars of the tenant's repositories over a week, Monday to Sunday.
+    WeeklyDigest,
+}
+
+impl ReportKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportKind::WeeklyDigest => "weekly_digest",
+        }
+    }
+}
+
+/// A generated report, kept once per tenant, kind and period.
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[diesel(table_name = reports)]
+pub struct Report {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: String,
+    pub period_start: NaiveDate,
+    /// Last day covered.
+    pub period_end: NaiveDate,
+    /// The report's data, e.g. a serialized `WeeklyDigest`.
+    pub content: serde_json::Value,
+    /// The report as a standalone HTML page, charts inlined as SVG.
+    pub html: String,
+    /// Addresses the report was emailed to, empty until it is sent.
+    pub sent_to: Vec<String>,
+    pub sent_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = reports)]
+pub struct NewReport<'a> {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: &'a str,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub content: serde_json::Value,
+    pub html: &'a str,
+}
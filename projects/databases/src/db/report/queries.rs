@@ -0,0 +1,124 @@
+use chrono::NaiveDate;
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{dsl::now, prelude::*};
+use crate::db::{report::models::*, schema::reports::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum InsertReportError {
+    #[error("InsertReport: {source}")]
+    InsertReport{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stores the report, `None` when the tenant already has one of this kind for the period.
+pub fn insert_report(
+    conn: &mut PgConnection,
+    new: &NewReport
+) -> Result<Option<Report>, InsertReportError> {
+    diesel::insert_into(reports)
+        .values(new)
+        .on_conflict((tenant_id, kind, period_start))
+        .do_nothing()
+        .get_result(conn)
+        .optional()
+        .map_err(|source| InsertReportError::InsertReport{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetReportError {
+    #[error("GetReport: {source}")]
+    GetReport{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// The tenant's report `report_id`, `None` when it doesn't exist or belongs to another tenant.
+pub fn get_report(
+    conn: &mut PgConnection,
+    tenant_id_val: Uuid,
+    report_id: Uuid
+) -> Result<Option<Report>, GetReportError> {
+    reports
+        .filter(id.eq(report_id))
+        .filter(tenant_id.eq(tenant_id_val))
+        .first::<Report>(conn)
+        .optional()
+        .map_err(|source| GetReportError::GetReport{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetReportByPeriodError {
+    #[error("GetReportByPeriod: {source}")]
+    GetReportByPeriod{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// The tenant's report of this kind starting on `start`, if generated.
+pub fn get_report_by_period(
+    conn: &mut PgConnection,
+    tenant_id_val: Uuid,
+    kind_val: ReportKind,
+    start: NaiveDate
+) -> Result<Option<Report>, GetReportByPeriodError> {
+    reports
+        .filter(tenant_id.eq(tenant_id_val))
+        .filter(kind.eq(kind_val.as_str()))
+        .filter(period_start.eq(start))
+        .first::<Report>(conn)
+        .optional()
+        .map_err(|source| GetReportByPeriodError::GetReportByPeriod{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum ListReportsError {
+    #[error("ListReports: {source}")]
+    ListReports{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Latest `max` reports of the tenant, most recent period first.
+pub fn list_reports(
+    conn: &mut PgConnection,
+    tenant_id_val: Uuid,
+    max: i64
+) -> Result<Vec<Report>, ListReportsError> {
+    reports
+        .filter(tenant_id.eq(tenant_id_val))
+        .order_by((period_start.desc(), kind))
+        .limit(max)
+        .load::<Report>(conn)
+        .map_err(|source| ListReportsError::ListReports{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum MarkReportSentError {
+    #[error("MarkReportSent: {source}")]
+    MarkReportSent{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Records the addresses the report was emailed to and stamps `sent_at`.
+pub fn mark_report_sent(
+    conn: &mut PgConnection,
+    report_id: Uuid,
+    recipients: &[String]
+) -> Result<(), MarkReportSentError> {
+    diesel::update(reports.find(report_id))
+        .set((
+            sent_to.eq(recipients),
+            sent_at.eq(now.nullable()),
+        ))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|source| MarkReportSentError::MarkReportSent{ source })
+}
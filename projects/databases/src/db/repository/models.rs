@@ -1,6 +1,7 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use uuid::Uuid;
 use diesel::prelude::*;
+use interfaces_github_stargazers::forge::Forge;
 use crate::db::schema::repositories;
 
 #[derive(Debug, Clone, Queryable, Identifiable)]
@@ -10,6 +11,56 @@ pub struct Repository {
     pub owner: String,
     pub name: String,
     pub created_at: NaiveDateTime,
+    pub description: Option<String>,
+    pub primary_language: Option<String>,
+    /// SPDX identifier, e.g. `MIT`.
+    pub license: Option<String>,
+    pub topics: Vec<String>,
+    /// Creation date on GitHub, `created_at` is when tracking started.
+    pub github_created_at: Option<NaiveDateTime>,
+    pub tenant_id: Uuid,
+    /// Stars starred before this day were compacted into daily aggregates, syncs don't store them again.
+    pub compacted_before: Option<NaiveDate>,
+    /// ID given by its forge, GitHub's node ID for GitHub, unset until synced. Renames and transfers keep it.
+    pub github_node_id: Option<String>,
+    /// Its forks with at least this many stars on GitHub are tracked too, unset when fork tracking is off.
+    pub forks_min_stars: Option<i32>,
+    /// Repository this one was tracked as a fork of.
+    pub fork_of: Option<Uuid>,
+    /// GitHub's star count is recorded on a schedule, see [`crate::snapshots`].
+    pub snapshot_mode: bool,
+    /// Stars stored and compacted, the sum of its daily star counts and refreshed along with them.
+    pub total_stars: i64,
+    /// `github`, `gitlab` or `gitea`, see [`Repository::forge`].
+    pub forge: String,
+    /// Hidden from listings and charts and left out of scheduled jobs since then, its data kept.
+    pub archived_at: Option<NaiveDateTime>,
+}
+
+impl Repository {
+    /// Whether a star starred at `starred_at` is already counted in the compacted daily aggregates.
+    pub fn is_compacted(&self, starred_at: NaiveDateTime) -> bool {
+        self.compacted_before.is_some_and(|before| starred_at.date() < before)
+    }
+
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// Forge the repository is synced from, GitHub for a value the database constraint would reject.
+    pub fn forge(&self) -> Forge {
+        Forge::parse(&self.forge).unwrap_or_default()
+    }
+}
+
+/// Descriptive fields of a repository as its forge reports them, refreshed by every sync.
+#[derive(Debug, Clone)]
+pub struct RepositoryMetadata<'a> {
+    pub description: Option<&'a str>,
+    pub primary_language: Option<&'a str>,
+    pub license: Option<&'a str>,
+    pub topics: Vec<&'a str>,
+    pub github_created_at: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -18,4 +69,12 @@ pub struct NewRepository<'a> {
     pub id: Uuid,
     pub owner: &'a str,
     pub name: &'a str,
+    pub description: Option<&'a str>,
+    pub primary_language: Option<&'a str>,
+    pub license: Option<&'a str>,
+    pub topics: Vec<&'a str>,
+    pub github_created_at: Option<NaiveDateTime>,
+    pub tenant_id: Uuid,
+    pub github_node_id: Option<&'a str>,
+    pub forge: &'a str,
 }
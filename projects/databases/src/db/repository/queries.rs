@@ -1,5 +1,10 @@
-use diesel::prelude::*;
-use crate::db::{repository::models::*, schema::repositories::dsl::*};
+use diesel::{prelude::*, sql_types::Text};
+use crate::db::{repository::models::*, repository_alias::models::NewRepositoryAlias, schema::{repositories::dsl::*, repository_aliases, repository_tags}};
+
+diesel::define_sql_function! {
+    /// PostgreSQL's `lower`, the unique index on repositories is built with it.
+    fn lower(value: Text) -> Text;
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum InsertRepositoryError {
@@ -10,14 +15,39 @@ pub enum InsertRepositoryError {
     },
 }
 
+/// Inserts the repository with its owner and name trimmed, or returns the one the tenant
+/// already tracks under another case, e.g. when two syncs of it start at once.
 pub fn insert_repository(
     conn: &mut PgConnection,
     new: &NewRepository
 ) -> Result<Repository, InsertRepositoryError> {
-    diesel::insert_into(repositories)
-        .values(new)
+    let new = NewRepository { owner: new.owner.trim(), name: new.name.trim(), ..new.clone() };
+    let inserted = diesel::insert_into(repositories)
+        .values(&new)
+        .on_conflict_do_nothing()
         .get_result(conn)
-        .map_err(|source| InsertRepositoryError::InsertRepository{ source })
+        .optional()
+        .map_err(|source| InsertRepositoryError::InsertRepository{ source })?;
+
+    match inserted {
+        Some(repo) => Ok(repo),
+        None => find_repository(conn, new.tenant_id, new.owner, new.name)
+            .map_err(|source| InsertRepositoryError::InsertRepository{ source }),
+    }
+}
+
+/// The tenant's repository, whatever the case of `owner_val` and `name_val`.
+fn find_repository(
+    conn: &mut PgConnection,
+    tenant_id_val: uuid::Uuid,
+    owner_val: &str,
+    name_val: &str
+) -> QueryResult<Repository> {
+    repositories
+        .filter(tenant_id.eq(tenant_id_val))
+        .filter(lower(owner).eq(lower(owner_val.trim())))
+        .filter(lower(name).eq(lower(name_val.trim())))
+        .first::<Repository>(conn)
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -29,15 +59,421 @@ pub enum GetRepositoryByNameError {
     },
 }
 
+/// The tenant's repository, GitHub owners and names being case-insensitive.
+/// Former names of renamed repositories resolve to them.
 pub async fn get_repository_by_name(
     conn: &mut PgConnection,
+    tenant_id_val: uuid::Uuid,
     owner_val: &str,
     name_val: &str
 ) -> Result<Option<Repository>, GetRepositoryByNameError> {
+    if let Some(repo) = find_repository(conn, tenant_id_val, owner_val, name_val)
+        .optional()
+        .map_err(|source| GetRepositoryByNameError::GetRepositoryByName{ source })?
+    {
+        return Ok(Some(repo));
+    }
+
+    let renamed = repository_aliases::table
+        .filter(repository_aliases::tenant_id.eq(tenant_id_val))
+        .filter(lower(repository_aliases::owner).eq(lower(owner_val.trim())))
+        .filter(lower(repository_aliases::name).eq(lower(name_val.trim())))
+        .select(repository_aliases::repository_id);
+
     repositories
-        .filter(owner.eq(owner_val))
-        .filter(name.eq(name_val))
+        .filter(id.eq_any(renamed))
         .first::<Repository>(conn)
         .optional()
         .map_err(|source| GetRepositoryByNameError::GetRepositoryByName{ source })
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum ListRepositoriesError {
+    #[error("ListRepositories: {source}")]
+    ListRepositories{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Every repository tracked by the tenant, or only the ones carrying `tag_val`, archived ones left out.
+pub fn list_repositories(
+    conn: &mut PgConnection,
+    tenant_id_val: uuid::Uuid,
+    tag_val: Option<&str>
+) -> Result<Vec<Repository>, ListRepositoriesError> {
+    let mut query = repositories
+        .filter(tenant_id.eq(tenant_id_val))
+        .filter(archived_at.is_null())
+        .into_boxed();
+    if let Some(tag_val) = tag_val {
+        let tagged = repository_tags::table
+            .filter(repository_tags::tag.eq(tag_val))
+            .select(repository_tags::repository_id);
+        query = query.filter(id.eq_any(tagged));
+    }
+
+    query
+        .order_by((owner, name))
+        .load::<Repository>(conn)
+        .map_err(|source| ListRepositoriesError::ListRepositories{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ListArchivedRepositoriesError {
+    #[error("ListArchivedRepositories: {source}")]
+    ListArchivedRepositories{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Archived repositories of the tenant, most recently archived first.
+pub fn list_archived_repositories(
+    conn: &mut PgConnection,
+    tenant_id_val: uuid::Uuid
+) -> Result<Vec<Repository>, ListArchivedRepositoriesError> {
+    repositories
+        .filter(tenant_id.eq(tenant_id_val))
+        .filter(archived_at.is_not_null())
+        .order_by((archived_at.desc(), owner, name))
+        .load::<Repository>(conn)
+        .map_err(|source| ListArchivedRepositoriesError::ListArchivedRepositories{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SetRepositoryArchivedError {
+    #[error("SetRepositoryArchived: {source}")]
+    SetRepositoryArchived{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Archives the repository, keeping the date of an earlier archival, or brings it back when `archived`
+/// is unset. Nothing else is touched.
+pub fn set_repository_archived(
+    conn: &mut PgConnection,
+    repo_id: uuid::Uuid,
+    archived: bool
+) -> Result<Repository, SetRepositoryArchivedError> {
+    let update = diesel::update(repositories.find(repo_id));
+    match archived {
+        true => update.set(archived_at.eq(diesel::dsl::sql("COALESCE(archived_at, NOW())"))).get_result::<Repository>(conn),
+        false => update.set(archived_at.eq(None::<chrono::NaiveDateTime>)).get_result::<Repository>(conn),
+    }
+    .map_err(|source| SetRepositoryArchivedError::SetRepositoryArchived{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GetRepositoryByIdError {
+    #[error("GetRepositoryById: {source}")]
+    GetRepositoryById{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn get_repository_by_id(
+    conn: &mut PgConnection,
+    repo_id: uuid::Uuid
+) -> Result<Option<Repository>, GetRepositoryByIdError> {
+    repositories
+        .find(repo_id)
+        .first::<Repository>(conn)
+        .optional()
+        .map_err(|source| GetRepositoryByIdError::GetRepositoryById{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GetRepositoriesByNameError {
+    #[error("GetRepositoriesByName: {source}")]
+    GetRepositoriesByName{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// The repository as tracked on `forge_val` by every tenant, for events not tied to one. Matches
+/// the forge's ID too, so repositories renamed since their last sync are found.
+pub fn get_repositories_by_name(
+    conn: &mut PgConnection,
+    forge_val: &str,
+    node_id: Option<&str>,
+    owner_val: &str,
+    name_val: &str
+) -> Result<Vec<Repository>, GetRepositoriesByNameError> {
+    let mut query = repositories
+        .filter(forge.eq(forge_val))
+        .filter(lower(owner).eq(lower(owner_val.trim())))
+        .filter(lower(name).eq(lower(name_val.trim())))
+        .into_boxed();
+    if let Some(node_id) = node_id {
+        query = query.or_filter(forge.eq(forge_val).and(github_node_id.eq(node_id)));
+    }
+
+    query
+        .load::<Repository>(conn)
+        .map_err(|source| GetRepositoriesByNameError::GetRepositoriesByName{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GetCompactedBeforeError {
+    #[error("GetCompactedBefore: {source}")]
+    GetCompactedBefore{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Current `compacted_before` of the repository, which compaction may move while a sync runs.
+pub fn get_compacted_before(
+    conn: &mut PgConnection,
+    repo_id: uuid::Uuid
+) -> Result<Option<chrono::NaiveDate>, GetCompactedBeforeError> {
+    repositories
+        .find(repo_id)
+        .select(compacted_before)
+        .first::<Option<chrono::NaiveDate>>(conn)
+        .optional()
+        .map(Option::flatten)
+        .map_err(|source| GetCompactedBeforeError::GetCompactedBefore{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GetRepositoryByNodeIdError {
+    #[error("GetRepositoryByNodeId: {source}")]
+    GetRepositoryByNodeId{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// The tenant's repository with this ID on `forge_val`, whatever it was called when synced.
+pub fn get_repository_by_node_id(
+    conn: &mut PgConnection,
+    tenant_id_val: uuid::Uuid,
+    forge_val: &str,
+    node_id: &str
+) -> Result<Option<Repository>, GetRepositoryByNodeIdError> {
+    repositories
+        .filter(tenant_id.eq(tenant_id_val))
+        .filter(forge.eq(forge_val))
+        .filter(github_node_id.eq(node_id))
+        .first::<Repository>(conn)
+        .optional()
+        .map_err(|source| GetRepositoryByNodeIdError::GetRepositoryByNodeId{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateRepositoryMetadataError {
+    #[error("UpdateRepositoryMetadata: {source}")]
+    UpdateRepositoryMetadata{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Replaces the repository's description, language, license, topics and creation date with the
+/// ones its forge reports, cleared fields included.
+pub fn update_repository_metadata(
+    conn: &mut PgConnection,
+    repo_id: uuid::Uuid,
+    metadata: &RepositoryMetadata
+) -> Result<Repository, UpdateRepositoryMetadataError> {
+    diesel::update(repositories.find(repo_id))
+        .set((
+            description.eq(metadata.description),
+            primary_language.eq(metadata.primary_language),
+            license.eq(metadata.license),
+            topics.eq(&metadata.topics),
+            github_created_at.eq(metadata.github_created_at),
+        ))
+        .get_result(conn)
+        .map_err(|source| UpdateRepositoryMetadataError::UpdateRepositoryMetadata{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordGitHubIdentityError {
+    #[error("RecordGitHubIdentity: {source}")]
+    RecordGitHubIdentity{
+        #[from]
+        source: diesel::result::Error
+    },
+    #[error("InsertRepositoryAlias: {source}")]
+    InsertRepositoryAlias{
+        source: diesel::result::Error
+    },
+    #[error("UpdateRepositoryIdentity: {source}")]
+    UpdateRepositoryIdentity{
+        source: diesel::result::Error
+    },
+}
+
+/// Stores the repository's node ID along with its current owner and name on GitHub, all or none.
+///
+/// When renamed or transferred, the former name becomes an alias of the repository,
+/// taken over from any other repository it pointed to.
+pub fn record_github_identity(
+    conn: &mut PgConnection,
+    repo: &Repository,
+    node_id: &str,
+    owner_val: &str,
+    name_val: &str
+) -> Result<Repository, RecordGitHubIdentityError> {
+    let renamed = !repo.owner.eq_ignore_ascii_case(owner_val) || !repo.name.eq_ignore_ascii_case(name_val);
+
+    conn.transaction(|conn| {
+        if renamed {
+            // The current name isn't an alias anymore, the former one now is.
+            for (alias_owner, alias_name) in [(owner_val, name_val), (repo.owner.as_str(), repo.name.as_str())] {
+                diesel::delete(
+                    repository_aliases::table
+                        .filter(repository_aliases::tenant_id.eq(repo.tenant_id))
+                        .filter(lower(repository_aliases::owner).eq(lower(alias_owner)))
+                        .filter(lower(repository_aliases::name).eq(lower(alias_name)))
+                )
+                    .execute(conn)
+                    .map_err(|source| RecordGitHubIdentityError::InsertRepositoryAlias{ source })?;
+            }
+
+            diesel::insert_into(repository_aliases::table)
+                .values(&NewRepositoryAlias { tenant_id: repo.tenant_id, owner: &repo.owner, name: &repo.name, repository_id: repo.id })
+                .execute(conn)
+                .map_err(|source| RecordGitHubIdentityError::InsertRepositoryAlias{ source })?;
+        }
+
+        diesel::update(repositories.find(repo.id))
+            .set((owner.eq(owner_val), name.eq(name_val), github_node_id.eq(node_id)))
+            .get_result::<Repository>(conn)
+            .map_err(|source| RecordGitHubIdentityError::UpdateRepositoryIdentity{ source })
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SetForksMinStarsError {
+    #[error("SetForksMinStars: {source}")]
+    SetForksMinStars{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Turns fork tracking of the repository on with the given threshold, or off when unset.
+pub fn set_forks_min_stars(
+    conn: &mut PgConnection,
+    repo_id: uuid::Uuid,
+    min_stars: Option<i32>
+) -> Result<Repository, SetForksMinStarsError> {
+    diesel::update(repositories.find(repo_id))
+        .set(forks_min_stars.eq(min_stars))
+        .get_result::<Repository>(conn)
+        .map_err(|source| SetForksMinStarsError::SetForksMinStars{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SetSnapshotModeError {
+    #[error("SetSnapshotMode: {source}")]
+    SetSnapshotMode{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Turns the scheduled star count snapshots of the repository on or off.
+pub fn set_snapshot_mode(
+    conn: &mut PgConnection,
+    repo_id: uuid::Uuid,
+    enabled: bool
+) -> Result<Repository, SetSnapshotModeError> {
+    diesel::update(repositories.find(repo_id))
+        .set(snapshot_mode.eq(enabled))
+        .get_result::<Repository>(conn)
+        .map_err(|source| SetSnapshotModeError::SetSnapshotMode{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ListSnapshotRepositoriesError {
+    #[error("ListSnapshotRepositories: {source}")]
+    ListSnapshotRepositories{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Repositories in snapshot mode, of every tenant, archived ones left out.
+pub fn list_snapshot_repositories(
+    conn: &mut PgConnection
+) -> Result<Vec<Repository>, ListSnapshotRepositoriesError> {
+    repositories
+        .filter(snapshot_mode.eq(true))
+        .filter(archived_at.is_null())
+        .order_by((tenant_id, owner, name))
+        .load::<Repository>(conn)
+        .map_err(|source| ListSnapshotRepositoriesError::ListSnapshotRepositories{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ListForgeRepositoriesError {
+    #[error("ListForgeRepositories: {source}")]
+    ListForgeRepositories{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Repositories tracked on `forge_val`, of every tenant, archived ones left out.
+pub fn list_forge_repositories(
+    conn: &mut PgConnection,
+    forge_val: &str
+) -> Result<Vec<Repository>, ListForgeRepositoriesError> {
+    repositories
+        .filter(forge.eq(forge_val))
+        .filter(archived_at.is_null())
+        .order_by((tenant_id, owner, name))
+        .load::<Repository>(conn)
+        .map_err(|source| ListForgeRepositoriesError::ListForgeRepositories{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LinkForkError {
+    #[error("LinkFork: {source}")]
+    LinkFork{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Records the repository as a fork of `parent_id`, whose combined series it joins.
+pub fn link_fork(
+    conn: &mut PgConnection,
+    repo_id: uuid::Uuid,
+    parent_id: uuid::Uuid
+) -> Result<(), LinkForkError> {
+    diesel::update(repositories.find(repo_id))
+        .set(fork_of.eq(parent_id))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|source| LinkForkError::LinkFork{ source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GetForksError {
+    #[error("GetForks: {source}")]
+    GetForks{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Tracked forks of the repository, by name.
+pub fn get_forks(
+    conn: &mut PgConnection,
+    parent_id: uuid::Uuid
+) -> Result<Vec<Repository>, GetForksError> {
+    repositories
+        .filter(fork_of.eq(parent_id))
+        .order((owner, name))
+        .load::<Repository>(conn)
+        .map_err(|source| GetForksError::GetForks{ source })
+}
@@ -0,0 +1,27 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::repository_aliases;
+use crate::db::repository::models::Repository;
+
+/// Former `owner/name` of a renamed or transferred repository.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(primary_key(tenant_id, owner, name))]
+#[diesel(table_name = repository_aliases)]
+pub struct RepositoryAlias {
+    pub tenant_id: Uuid,
+    pub owner: String,
+    pub name: String,
+    pub repository_id: Uuid,
+    pub renamed_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = repository_aliases)]
+pub struct NewRepositoryAlias<'a> {
+    pub tenant_id: Uuid,
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub repository_id: Uuid,
+}
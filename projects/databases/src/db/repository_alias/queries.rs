@@ -0,0 +1,25 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::{repository_alias::models::*, schema::repository_aliases::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum GetAliasesByRepositoriesError {
+    #[error("GetAliasesByRepositories: {source}")]
+    GetAliasesByRepositories{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Former names of the given repositories, oldest rename first.
+pub fn get_aliases_by_repositories(
+    conn: &mut PgConnection,
+    repo_ids: &[Uuid]
+) -> Result<Vec<RepositoryAlias>, GetAliasesByRepositoriesError> {
+    repository_aliases
+        .filter(repository_id.eq_any(repo_ids))
+        .order_by(renamed_at)
+        .load::<RepositoryAlias>(conn)
+        .map_err(|source| GetAliasesByRepositoriesError::GetAliasesByRepositories{ source })
+}
@@ -0,0 +1,34 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::repository_tags;
+use crate::db::repository::models::Repository;
+
+/// Longest tag accepted, tags are labels rather than descriptions.
+pub const MAX_TAG_LENGTH: usize = 50;
+
+/// Lowercased tag if it is 1 to `MAX_TAG_LENGTH` letters, digits, `-`, `_` or `.`,
+/// so it fits in a URL path segment as is.
+pub fn parse_tag(value: &str) -> Option<String> {
+    let valid = (1..=MAX_TAG_LENGTH).contains(&value.len())
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    valid.then(|| value.to_ascii_lowercase())
+}
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(primary_key(repository_id, tag))]
+#[diesel(table_name = repository_tags)]
+pub struct RepositoryTag {
+    pub repository_id: Uuid,
+    pub tag: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = repository_tags)]
+pub struct NewRepositoryTag<'a> {
+    pub repository_id: Uuid,
+    pub tag: &'a str,
+}
@@ -0,0 +1,93 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{dsl::count_star, prelude::*};
+use crate::db::{repository_tag::models::*, schema::{repositories, repository_tags::dsl::*}};
+
+#[derive(Debug, Error)]
+pub enum InsertRepositoryTagError {
+    #[error("InsertRepositoryTag: {source}")]
+    InsertRepositoryTag{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Returns whether the tag was new for the repository, tagging twice is a no-op.
+pub fn insert_repository_tag(
+    conn: &mut PgConnection,
+    new: &NewRepositoryTag
+) -> Result<bool, InsertRepositoryTagError> {
+    diesel::insert_into(repository_tags)
+        .values(new)
+        .on_conflict((repository_id, tag))
+        .do_nothing()
+        .execute(conn)
+        .map(|inserted| inserted > 0)
+        .map_err(|source| InsertRepositoryTagError::InsertRepositoryTag{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteRepositoryTagError {
+    #[error("DeleteRepositoryTag: {source}")]
+    DeleteRepositoryTag{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Returns whether the repository had the tag.
+pub fn delete_repository_tag(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    tag_val: &str
+) -> Result<bool, DeleteRepositoryTagError> {
+    diesel::delete(repository_tags.find((repo_id_val, tag_val)))
+        .execute(conn)
+        .map(|deleted| deleted > 0)
+        .map_err(|source| DeleteRepositoryTagError::DeleteRepositoryTag{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetTagsByRepositoriesError {
+    #[error("GetTagsByRepositories: {source}")]
+    GetTagsByRepositories{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Tags of the given repositories, sorted by tag.
+pub fn get_tags_by_repositories(
+    conn: &mut PgConnection,
+    repo_ids: &[Uuid]
+) -> Result<Vec<RepositoryTag>, GetTagsByRepositoriesError> {
+    repository_tags
+        .filter(repository_id.eq_any(repo_ids))
+        .order_by(tag)
+        .load::<RepositoryTag>(conn)
+        .map_err(|source| GetTagsByRepositoriesError::GetTagsByRepositories{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum CountRepositoriesByTagError {
+    #[error("CountRepositoriesByTag: {source}")]
+    CountRepositoriesByTag{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Every tag the tenant uses with the number of its repositories carrying it.
+pub fn count_repositories_by_tag(
+    conn: &mut PgConnection,
+    tenant_id_val: Uuid
+) -> Result<Vec<(String, i64)>, CountRepositoriesByTagError> {
+    repository_tags
+        .inner_join(repositories::table)
+        .filter(repositories::tenant_id.eq(tenant_id_val))
+        .group_by(tag)
+        .select((tag, count_star()))
+        .order_by(tag)
+        .load::<(String, i64)>(conn)
+        .map_err(|source| CountRepositoriesByTagError::CountRepositoriesByTag{ source })
+}
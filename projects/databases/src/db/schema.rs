@@ -1,11 +1,198 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    activity_items (repository_id, kind, number) {
+        repository_id -> Uuid,
+        kind -> Text,
+        number -> Int4,
+        opened_at -> Timestamp,
+        closed_at -> Nullable<Timestamp>,
+        updated_at -> Timestamp,
+        fetched_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    api_keys (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        key_hash -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    alert_rules (id) {
+        id -> Uuid,
+        repository_id -> Uuid,
+        kind -> Text,
+        threshold -> Int4,
+        target -> Text,
+        destination -> Text,
+        last_triggered_on -> Nullable<Date>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    contributor_tracking (repository_id) {
+        repository_id -> Uuid,
+        started_at -> Timestamp,
+        newest_commit_at -> Nullable<Timestamp>,
+        oldest_commit_at -> Nullable<Timestamp>,
+        history_complete -> Bool,
+        tracked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    contributors (repository_id, login) {
+        repository_id -> Uuid,
+        login -> Text,
+        first_commit_at -> Timestamp,
+        last_commit_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    daily_star_aggregates (repository_id, day) {
+        repository_id -> Uuid,
+        day -> Date,
+        stars -> Int4,
+        bot_stars -> Int4,
+    }
+}
+
+diesel::table! {
+    daily_star_counts (repository_id, day) {
+        repository_id -> Uuid,
+        day -> Date,
+        stars -> Int4,
+        bot_stars -> Int4,
+    }
+}
+
+diesel::table! {
+    milestones (repository_id, stars) {
+        repository_id -> Uuid,
+        stars -> Int4,
+        reached_on -> Date,
+        recorded_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    releases (repository_id, tag_name) {
+        repository_id -> Uuid,
+        tag_name -> Text,
+        name -> Nullable<Text>,
+        kind -> Text,
+        prerelease -> Bool,
+        url -> Nullable<Text>,
+        published_at -> Timestamp,
+        fetched_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     repositories (id) {
         id -> Uuid,
         owner -> Text,
         name -> Text,
         created_at -> Timestamp,
+        description -> Nullable<Text>,
+        primary_language -> Nullable<Text>,
+        license -> Nullable<Text>,
+        topics -> Array<Text>,
+        github_created_at -> Nullable<Timestamp>,
+        tenant_id -> Uuid,
+        compacted_before -> Nullable<Date>,
+        github_node_id -> Nullable<Text>,
+        forks_min_stars -> Nullable<Int4>,
+        fork_of -> Nullable<Uuid>,
+        snapshot_mode -> Bool,
+        total_stars -> Int8,
+        forge -> Text,
+        archived_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    repository_aliases (tenant_id, owner, name) {
+        tenant_id -> Uuid,
+        owner -> Text,
+        name -> Text,
+        repository_id -> Uuid,
+        renamed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    repository_tags (repository_id, tag) {
+        repository_id -> Uuid,
+        tag -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    star_anomalies (repository_id, day) {
+        repository_id -> Uuid,
+        day -> Date,
+        stars -> Int4,
+        trailing_average -> Float8,
+        detected_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    star_count_snapshots (repository_id, taken_at) {
+        repository_id -> Uuid,
+        taken_at -> Timestamp,
+        stargazer_count -> Int4,
+    }
+}
+
+diesel::table! {
+    star_verifications (id) {
+        id -> Uuid,
+        repository_id -> Uuid,
+        github_stars -> Int4,
+        stored_stars -> Int4,
+        repair_queued -> Bool,
+        checked_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    reports (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        kind -> Text,
+        period_start -> Date,
+        period_end -> Date,
+        content -> Jsonb,
+        html -> Text,
+        sent_to -> Array<Text>,
+        sent_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    stargazer_emails (login) {
+        login -> Text,
+        email -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    stargazer_profiles (login) {
+        login -> Text,
+        company -> Nullable<Text>,
+        location -> Nullable<Text>,
+        updated_at -> Timestamp,
     }
 }
 
@@ -15,12 +202,143 @@ diesel::table! {
         stargazer -> Text,
         starred_at -> Timestamp,
         fetched_at -> Timestamp,
+        first_fetched_at -> Nullable<Timestamp>,
+        suspected_bot -> Bool,
+    }
+}
+
+diesel::table! {
+    sync_batches (id) {
+        id -> Uuid,
+        owner -> Nullable<Text>,
+        total_repositories -> Int4,
+        created_at -> Timestamp,
+        tenant_id -> Uuid,
+    }
+}
+
+diesel::table! {
+    sync_jobs (id) {
+        id -> Uuid,
+        repository_id -> Uuid,
+        status -> Text,
+        end_cursor -> Nullable<Text>,
+        pages_fetched -> Int4,
+        error -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        batch_id -> Nullable<Uuid>,
+        stars_fetched -> Int4,
+        estimated_total_stars -> Nullable<Int4>,
+        started_at -> Nullable<Timestamp>,
+        finished_at -> Nullable<Timestamp>,
+        stop_reason -> Nullable<Text>,
+        retries -> Int4,
+    }
+}
+
+diesel::table! {
+    sync_runs (id) {
+        id -> Uuid,
+        job_id -> Nullable<Uuid>,
+        repository_id -> Uuid,
+        trigger -> Text,
+        status -> Text,
+        pages_fetched -> Int4,
+        stars_added -> Int4,
+        error -> Nullable<Text>,
+        started_at -> Timestamp,
+        finished_at -> Nullable<Timestamp>,
+        fetched_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    tenants (id) {
+        id -> Uuid,
+        name -> Text,
+        created_at -> Timestamp,
+        digest_recipients -> Array<Text>,
+    }
+}
+
+diesel::table! {
+    traffic_snapshots (repository_id, kind, day) {
+        repository_id -> Uuid,
+        kind -> Text,
+        day -> Date,
+        count -> Int4,
+        uniques -> Int4,
+        recorded_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    webhook_outbox (id) {
+        id -> Uuid,
+        alert_rule_id -> Nullable<Uuid>,
+        job_id -> Nullable<Uuid>,
+        destination -> Text,
+        payload -> Jsonb,
+        status -> Text,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        next_attempt_at -> Timestamp,
+        delivered_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
     }
 }
 
+diesel::joinable!(activity_items -> repositories (repository_id));
+diesel::joinable!(alert_rules -> repositories (repository_id));
+diesel::joinable!(api_keys -> tenants (tenant_id));
+diesel::joinable!(contributor_tracking -> repositories (repository_id));
+diesel::joinable!(contributors -> repositories (repository_id));
+diesel::joinable!(daily_star_aggregates -> repositories (repository_id));
+diesel::joinable!(daily_star_counts -> repositories (repository_id));
+diesel::joinable!(milestones -> repositories (repository_id));
+diesel::joinable!(releases -> repositories (repository_id));
+diesel::joinable!(reports -> tenants (tenant_id));
+diesel::joinable!(repositories -> tenants (tenant_id));
+diesel::joinable!(repository_aliases -> repositories (repository_id));
+diesel::joinable!(repository_tags -> repositories (repository_id));
+diesel::joinable!(star_anomalies -> repositories (repository_id));
+diesel::joinable!(star_count_snapshots -> repositories (repository_id));
+diesel::joinable!(star_verifications -> repositories (repository_id));
 diesel::joinable!(stars -> repositories (repository_id));
+diesel::joinable!(sync_batches -> tenants (tenant_id));
+diesel::joinable!(sync_jobs -> repositories (repository_id));
+diesel::joinable!(sync_jobs -> sync_batches (batch_id));
+diesel::joinable!(sync_runs -> repositories (repository_id));
+diesel::joinable!(sync_runs -> sync_jobs (job_id));
+diesel::joinable!(traffic_snapshots -> repositories (repository_id));
+diesel::joinable!(webhook_outbox -> alert_rules (alert_rule_id));
+diesel::joinable!(webhook_outbox -> sync_jobs (job_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    activity_items,
+    alert_rules,
+    api_keys,
+    contributor_tracking,
+    contributors,
+    daily_star_aggregates,
+    daily_star_counts,
+    milestones,
+    releases,
+    reports,
     repositories,
+    repository_aliases,
+    repository_tags,
+    star_anomalies,
+    star_count_snapshots,
+    star_verifications,
+    stargazer_emails,
+    stargazer_profiles,
     stars,
+    sync_batches,
+    sync_jobs,
+    sync_runs,
+    tenants,
+    traffic_snapshots,
+    webhook_outbox,
 );
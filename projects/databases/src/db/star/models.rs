@@ -13,6 +13,10 @@ pub struct Star {
     pub stargazer: String,
     pub starred_at: NaiveDateTime,
     pub fetched_at: NaiveDateTime,
+    /// Unset for stars stored before it was recorded.
+    pub first_fetched_at: Option<NaiveDateTime>,
+    /// Stargazer looking like a bot, see [`crate::bots`].
+    pub suspected_bot: bool,
 }
 
 
@@ -23,4 +27,8 @@ pub struct NewStar<'a> {
     pub stargazer: &'a str,
     pub starred_at: NaiveDateTime,
     pub fetched_at: NaiveDateTime,
+    /// Same as `fetched_at`, stored stars keep theirs.
+    pub first_fetched_at: NaiveDateTime,
+    /// Kept once set on the stored star.
+    pub suspected_bot: bool,
 }
@@ -1,8 +1,8 @@
 use thiserror::Error;
 use uuid::Uuid;
-use chrono::NaiveDate;
-use diesel::{dsl::{count_star, sql}, prelude::*, sql_types::Date};
-use crate::db::{star::models::*, schema::stars::dsl::*};
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::{connection::SimpleConnection, dsl::count_star, pg::upsert::excluded, prelude::*};
+use crate::db::{star::models::*, schema::{daily_star_aggregates, daily_star_counts, repositories, repository_tags, stars::dsl::*}};
 
 #[derive(Debug, Error)]
 pub enum InsertStarError {
@@ -13,16 +13,290 @@ pub enum InsertStarError {
     },
 }
 
+/// Inserts a star, refreshing `fetched_at` when the stargazer is already stored, and flagging it when `new` is.
 pub fn insert_star(
     conn: &mut PgConnection,
     new: &NewStar
 ) -> Result<Star, InsertStarError> {
     diesel::insert_into(stars)
         .values(new)
+        .on_conflict((repository_id, stargazer))
+        .do_update()
+        .set((fetched_at.eq(excluded(fetched_at)), suspected_bot.eq(suspected_bot.or(excluded(suspected_bot)))))
         .get_result(conn)
         .map_err(|source| InsertStarError::InsertStar{ source })
 }
 
+#[derive(Debug, Error)]
+pub enum DeleteStarError {
+    #[error("DeleteStar: {source}")]
+    DeleteStar{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Removes the star of `stargazer_val`, returns when it was starred if one was stored.
+pub fn delete_star(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    stargazer_val: &str
+) -> Result<Option<NaiveDateTime>, DeleteStarError> {
+    diesel::delete(stars.filter(repository_id.eq(repo_id_val)).filter(stargazer.eq(stargazer_val)))
+        .returning(starred_at)
+        .get_result::<NaiveDateTime>(conn)
+        .optional()
+        .map_err(|source| DeleteStarError::DeleteStar{ source })
+}
+
+/// Rows per statement unless told otherwise.
+pub const DEFAULT_INSERT_CHUNK_SIZE: usize = 1_000;
+/// Largest chunk, keeps the 6 bind parameters per star under PostgreSQL's limit of 65535.
+pub const MAX_INSERT_CHUNK_SIZE: usize = 10_000;
+/// Rows from which [`StarInsertMethod::Auto`] switches to `COPY` unless told otherwise.
+/// Below it, the staging table costs more than `COPY` saves.
+pub const DEFAULT_COPY_THRESHOLD: usize = 20_000;
+
+/// How [`insert_stars_batch`] and [`insert_missing_stars`] write the stars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StarInsertMethod {
+    /// Multi-row `INSERT ... ON CONFLICT` per chunk.
+    Insert,
+    /// `COPY` of every chunk into a temporary table, merged with a single `INSERT ... SELECT`.
+    /// Faster for hundreds of thousands of rows.
+    Copy,
+    /// `Copy` for batches of at least `copy_threshold` rows, `Insert` for smaller ones.
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StarInsertConfig {
+    /// Rows per statement, clamped to `1..=MAX_INSERT_CHUNK_SIZE`.
+    pub chunk_size: usize,
+    pub method: StarInsertMethod,
+    /// Smallest batch [`StarInsertMethod::Auto`] copies.
+    pub copy_threshold: usize,
+}
+
+impl Default for StarInsertConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_INSERT_CHUNK_SIZE,
+            method: StarInsertMethod::Auto,
+            copy_threshold: DEFAULT_COPY_THRESHOLD,
+        }
+    }
+}
+
+impl StarInsertConfig {
+    fn chunk_size(&self) -> usize {
+        self.chunk_size.clamp(1, MAX_INSERT_CHUNK_SIZE)
+    }
+
+    /// `Insert` or `Copy`, the one used for a batch of `rows` stars.
+    pub fn method_for(&self, rows: usize) -> StarInsertMethod {
+        match self.method {
+            StarInsertMethod::Auto if rows >= self.copy_threshold => StarInsertMethod::Copy,
+            StarInsertMethod::Auto => StarInsertMethod::Insert,
+            method => method,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum InsertStarsBatchError {
+    #[error("InsertChunk: rows {first_row}..{end_row}: {source}")]
+    InsertChunk{
+        first_row: usize,
+        end_row: usize,
+        source: diesel::result::Error
+    },
+    #[error("CopyTransaction: {source}")]
+    CopyTransaction{
+        #[from]
+        source: diesel::result::Error
+    },
+    #[error("CopyStars: {source}")]
+    CopyStars{
+        #[from]
+        source: CopyStarsError
+    },
+}
+
+/// Inserts the stars, refreshing `fetched_at` of the stored ones, and returns how many rows were written.
+/// Stored stars are flagged as suspected bots when their new row is, never unflagged.
+///
+/// A stargazer must appear once per chunk, PostgreSQL can't update a row twice in a statement;
+/// once per batch when copied. Chunks inserted before a failing one are kept unless the caller
+/// runs in a transaction, a failing copy writes nothing.
+pub fn insert_stars_batch(
+    conn: &mut PgConnection,
+    new: &[NewStar],
+    config: &StarInsertConfig
+) -> Result<usize, InsertStarsBatchError> {
+    let chunk_size = config.chunk_size();
+    if config.method_for(new.len()) == StarInsertMethod::Copy {
+        return conn.transaction(|conn| Ok(copy_stars(conn, new, chunk_size, CopyConflict::RefreshFetchedAt)?));
+    }
+
+    let mut written = 0;
+    for (index, chunk) in new.chunks(chunk_size).enumerate() {
+        let first_row = index * chunk_size;
+        written += diesel::insert_into(stars)
+            .values(chunk)
+            .on_conflict((repository_id, stargazer))
+            .do_update()
+            .set((fetched_at.eq(excluded(fetched_at)), suspected_bot.eq(suspected_bot.or(excluded(suspected_bot)))))
+            .execute(conn)
+            .map_err(|source| InsertStarsBatchError::InsertChunk{ first_row, end_row: first_row + chunk.len(), source })?;
+    }
+    Ok(written)
+}
+
+diesel::table! {
+    /// Temporary copy of `stars` the COPY fast path loads into, dropped on commit.
+    stars_staging (repository_id, stargazer) {
+        repository_id -> Uuid,
+        stargazer -> Text,
+        starred_at -> Timestamp,
+        fetched_at -> Timestamp,
+        first_fetched_at -> Nullable<Timestamp>,
+        suspected_bot -> Bool,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum InsertMissingStarsError {
+    #[error("InsertMissingStars: {source}")]
+    InsertMissingStars{
+        #[from]
+        source: diesel::result::Error
+    },
+    #[error("InsertChunk: rows {first_row}..{end_row}: {source}")]
+    InsertChunk{
+        first_row: usize,
+        end_row: usize,
+        source: diesel::result::Error
+    },
+    #[error("CopyStars: {source}")]
+    CopyStars{
+        #[from]
+        source: CopyStarsError
+    },
+}
+
+/// Inserts the stars whose stargazer isn't stored yet, all or none, and returns how many were new.
+///
+/// Stored stars are left untouched, they come from GitHub and win over imported data.
+/// A failing chunk is reported with its row range and rolls back the earlier ones.
+pub fn insert_missing_stars(
+    conn: &mut PgConnection,
+    new: &[NewStar],
+    config: &StarInsertConfig
+) -> Result<usize, InsertMissingStarsError> {
+    let chunk_size = config.chunk_size();
+    conn.transaction(|conn| match config.method_for(new.len()) {
+        StarInsertMethod::Copy => Ok(copy_stars(conn, new, chunk_size, CopyConflict::KeepStored)?),
+        _ => {
+            let mut inserted = 0;
+            for (index, chunk) in new.chunks(chunk_size).enumerate() {
+                let first_row = index * chunk_size;
+                inserted += diesel::insert_into(stars)
+                    .values(chunk)
+                    .on_conflict((repository_id, stargazer))
+                    .do_nothing()
+                    .execute(conn)
+                    .map_err(|source| InsertMissingStarsError::InsertChunk{ first_row, end_row: first_row + chunk.len(), source })?;
+            }
+            Ok(inserted)
+        }
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum CopyStarsError {
+    #[error("CreateStagingTable: {source}")]
+    CreateStagingTable{
+        source: diesel::result::Error
+    },
+    #[error("InsertChunk: rows {first_row}..{end_row}: {source}")]
+    InsertChunk{
+        first_row: usize,
+        end_row: usize,
+        source: diesel::result::Error
+    },
+    #[error("MergeStagedStars: {source}")]
+    MergeStagedStars{
+        source: diesel::result::Error
+    },
+    #[error("DropStagingTable: {source}")]
+    DropStagingTable{
+        source: diesel::result::Error
+    },
+}
+
+/// What a copied star does to the stored one of the same stargazer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyConflict {
+    KeepStored,
+    RefreshFetchedAt,
+}
+
+/// COPY fast path, to be run in a transaction. COPY can't handle conflicting rows so they go
+/// through a staging table, dropped once merged so a caller's transaction can copy again.
+fn copy_stars(
+    conn: &mut PgConnection,
+    new: &[NewStar],
+    chunk_size: usize,
+    conflict: CopyConflict
+) -> Result<usize, CopyStarsError> {
+    conn.batch_execute("CREATE TEMPORARY TABLE stars_staging (LIKE stars) ON COMMIT DROP")
+        .map_err(|source| CopyStarsError::CreateStagingTable{ source })?;
+
+    for (index, chunk) in new.chunks(chunk_size).enumerate() {
+        let first_row = index * chunk_size;
+        let rows: Vec<_> = chunk
+            .iter()
+            .map(|star| (
+                stars_staging::repository_id.eq(star.repository_id),
+                stars_staging::stargazer.eq(star.stargazer),
+                stars_staging::starred_at.eq(star.starred_at),
+                stars_staging::fetched_at.eq(star.fetched_at),
+                stars_staging::first_fetched_at.eq(star.first_fetched_at),
+                stars_staging::suspected_bot.eq(star.suspected_bot),
+            ))
+            .collect();
+        diesel::copy_from(stars_staging::table)
+            .from_insertable(rows)
+            .execute(conn)
+            .map_err(|source| CopyStarsError::InsertChunk{ first_row, end_row: first_row + chunk.len(), source })?;
+    }
+
+    let staged = diesel::insert_into(stars)
+        .values(stars_staging::table.select((
+            stars_staging::repository_id,
+            stars_staging::stargazer,
+            stars_staging::starred_at,
+            stars_staging::fetched_at,
+            stars_staging::first_fetched_at,
+            stars_staging::suspected_bot,
+        )))
+        .into_columns((repository_id, stargazer, starred_at, fetched_at, first_fetched_at, suspected_bot))
+        .on_conflict((repository_id, stargazer));
+    let merged = match conflict {
+        CopyConflict::KeepStored => staged.do_nothing().execute(conn),
+        CopyConflict::RefreshFetchedAt => staged
+            .do_update()
+            .set((fetched_at.eq(excluded(fetched_at)), suspected_bot.eq(suspected_bot.or(excluded(suspected_bot)))))
+            .execute(conn),
+    }
+    .map_err(|source| CopyStarsError::MergeStagedStars{ source })?;
+
+    conn.batch_execute("DROP TABLE stars_staging")
+        .map_err(|source| CopyStarsError::DropStagingTable{ source })?;
+    Ok(merged)
+}
+
 #[derive(Debug, Error)]
 pub enum GetDailyStarCountError {
     #[error("GetDailyStarCount: {source}")]
@@ -32,19 +306,480 @@ pub enum GetDailyStarCountError {
     },
 }
 
+/// Stars per day of the repository, oldest first, compacted days included.
+///
+/// Reads the counts kept by [`refresh_daily_star_counts`](crate::db::daily_star_count::queries::refresh_daily_star_counts),
+/// stars stored by a sync show up once it ends.
 pub fn get_daily_star_count(
     conn: &mut PgConnection,
     repo_id_val: Uuid
 ) -> Result<Vec<(NaiveDate, i64)>, GetDailyStarCountError> {
+    get_filtered_daily_star_count(conn, repo_id_val, false)
+}
+
+/// [`get_daily_star_count`] without the stars of suspected bots when `exclude_bots` is set,
+/// days left with none being dropped.
+pub fn get_filtered_daily_star_count(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    exclude_bots: bool
+) -> Result<Vec<(NaiveDate, i64)>, GetDailyStarCountError> {
+    let mut days = daily_star_counts::table
+        .filter(daily_star_counts::repository_id.eq(repo_id_val))
+        .select((daily_star_counts::day, daily_star_counts::stars, daily_star_counts::bot_stars))
+        .order_by(daily_star_counts::day)
+        .into_boxed();
+    if exclude_bots {
+        days = days.filter(daily_star_counts::stars.gt(daily_star_counts::bot_stars));
+    }
+
+    days
+        .load::<(NaiveDate, i32, i32)>(conn)
+        .map(|days| days.into_iter().map(|(day, count, bots)| (day, counted_stars(count, bots, exclude_bots))).collect())
+        .map_err(|source| GetDailyStarCountError::GetDailyStarCount{ source })
+}
+
+/// Stars of a day, those of suspected bots left out when `exclude_bots` is set.
+fn counted_stars(count: i32, bots: i32, exclude_bots: bool) -> i64 {
+    match exclude_bots {
+        true => i64::from(count - bots),
+        false => i64::from(count),
+    }
+}
+
+
+#[derive(Debug, Error)]
+pub enum GetDailyStarCountPageError {
+    #[error("CountStarDays: {source}")]
+    CountStarDays{ 
+        source: diesel::result::Error 
+    },
+    #[error("GetDailyStarCountPage: {source}")]
+    GetDailyStarCountPage{ 
+        source: diesel::result::Error 
+    },
+}
+
+/// One page of [`get_filtered_daily_star_count`], along with the total number of days with stars.
+pub fn get_daily_star_count_page(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    exclude_bots: bool,
+    max: i64,
+    skip: i64
+) -> Result<(Vec<(NaiveDate, i64)>, i64), GetDailyStarCountPageError> {
+    let days = || {
+        let days = daily_star_counts::table
+            .filter(daily_star_counts::repository_id.eq(repo_id_val))
+            .into_boxed();
+        match exclude_bots {
+            true => days.filter(daily_star_counts::stars.gt(daily_star_counts::bot_stars)),
+            false => days,
+        }
+    };
+
+    let total = days()
+        .count()
+        .get_result::<i64>(conn)
+        .map_err(|source| GetDailyStarCountPageError::CountStarDays{ source })?;
+
+    let page = days()
+        .select((daily_star_counts::day, daily_star_counts::stars, daily_star_counts::bot_stars))
+        .order_by(daily_star_counts::day)
+        .limit(max)
+        .offset(skip)
+        .load::<(NaiveDate, i32, i32)>(conn)
+        .map_err(|source| GetDailyStarCountPageError::GetDailyStarCountPage{ source })?;
+
+    Ok((page.into_iter().map(|(day, count, bots)| (day, counted_stars(count, bots, exclude_bots))).collect(), total))
+}
+
+#[derive(Debug, Error)]
+pub enum GetEarliestStargazersError {
+    #[error("GetEarliestStargazers: {source}")]
+    GetEarliestStargazers{ 
+        #[from] 
+        source: diesel::result::Error 
+    },
+}
+
+/// First `max` stargazers of the repository with the time they starred it.
+pub fn get_earliest_stargazers(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    max: i64
+) -> Result<Vec<(String, NaiveDateTime)>, GetEarliestStargazersError> {
     stars
         .filter(repository_id.eq(repo_id_val))
-        .select((
-            sql::<Date>("DATE(starred_at)"),
-            count_star()
-        ))
-        .group_by(sql::<Date>("DATE(starred_at)"))
-        .order_by(sql::<Date>("DATE(starred_at)"))
-        .load::<(NaiveDate, i64)>(conn)
-        .map_err(|source| GetDailyStarCountError::GetDailyStarCount{ source })
+        .select((stargazer, starred_at))
+        .order_by((starred_at, stargazer))
+        .limit(max)
+        .load::<(String, NaiveDateTime)>(conn)
+        .map_err(|source| GetEarliestStargazersError::GetEarliestStargazers{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarsPageError {
+    #[error("GetStarsPage: {source}")]
+    GetStarsPage{ 
+        #[from] 
+        source: diesel::result::Error 
+    },
+}
+
+/// Last star of a page, `(starred_at, stargazer)`, the next page starting right after it.
+pub type StarCursor = (NaiveDateTime, String);
+
+/// Up to `max` stars of the repository after `after`, oldest first, stargazer breaking ties.
+///
+/// Keyset pagination: each page is an index range scan, as fast at the end of millions of
+/// stars as at the start, unlike an `OFFSET`.
+pub fn get_stars_page(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    after: Option<&StarCursor>,
+    max: i64
+) -> Result<Vec<(String, NaiveDateTime)>, GetStarsPageError> {
+    let mut query = stars
+        .filter(repository_id.eq(repo_id_val))
+        .select((stargazer, starred_at))
+        .into_boxed();
+    if let Some((after_starred_at, after_stargazer)) = after {
+        query = query.filter(
+            starred_at.gt(*after_starred_at)
+                .or(starred_at.eq(*after_starred_at).and(stargazer.gt(after_stargazer.clone())))
+        );
+    }
+
+    query
+        .order_by((starred_at, stargazer))
+        .limit(max)
+        .load::<(String, NaiveDateTime)>(conn)
+        .map_err(|source| GetStarsPageError::GetStarsPage{ source })
+}
+
+/// Every stored star of a repository, oldest first, a page of [`get_stars_page`] at a time.
+///
+/// Only one page is held at once, whatever the repository's size. Each page is its own
+/// query: stars stored meanwhile show up if they come after the cursor.
+pub struct StarPages<'a> {
+    conn: &'a mut PgConnection,
+    repository_id: Uuid,
+    page_size: i64,
+    after: Option<StarCursor>,
+    done: bool,
+}
+
+/// Iterates over the repository's stars in pages of `page_size`.
+pub fn star_pages(conn: &mut PgConnection, repo_id_val: Uuid, page_size: i64) -> StarPages<'_> {
+    StarPages { conn, repository_id: repo_id_val, page_size: page_size.max(1), after: None, done: false }
+}
+
+impl Iterator for StarPages<'_> {
+    type Item = Result<Vec<(String, NaiveDateTime)>, GetStarsPageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let page = match get_stars_page(self.conn, self.repository_id, self.after.as_ref(), self.page_size) {
+            Ok(page) => page,
+            Err(error) => {
+                self.done = true;
+                return Some(Err(error));
+            }
+        };
+        // A short page is the last one, sparing a query answering nothing
+        self.done = (page.len() as i64) < self.page_size;
+        match page.last() {
+            Some((last_stargazer, last_starred_at)) => self.after = Some((*last_starred_at, last_stargazer.clone())),
+            None => return None,
+        }
+        Some(Ok(page))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GetTopStargazersError {
+    #[error("GetTopStargazers: {source}")]
+    GetTopStargazers{ 
+        #[from] 
+        source: diesel::result::Error 
+    },
+}
+
+/// Users who starred the most repositories tracked by the tenant, with their repository count.
+///
+/// With `tag_val`, only repositories carrying that tag are counted.
+pub fn get_top_stargazers(
+    conn: &mut PgConnection,
+    tenant_id_val: Uuid,
+    max: i64,
+    tag_val: Option<&str>
+) -> Result<Vec<(String, i64)>, GetTopStargazersError> {
+    let owned = repositories::table
+        .filter(repositories::tenant_id.eq(tenant_id_val))
+        .select(repositories::id);
+
+    let mut query = stars
+        .filter(repository_id.eq_any(owned))
+        .group_by(stargazer)
+        .select((stargazer, count_star()))
+        .into_boxed();
+    if let Some(tag_val) = tag_val {
+        let tagged = repository_tags::table
+            .filter(repository_tags::tag.eq(tag_val))
+            .select(repository_tags::repository_id);
+        query = query.filter(repository_id.eq_any(tagged));
+    }
+
+    query
+        .order_by((count_star().desc(), stargazer))
+        .limit(max)
+        .load::<(String, i64)>(conn)
+        .map_err(|source| GetTopStargazersError::GetTopStargazers{ source })
 }
 
+#[derive(Debug, Error)]
+pub enum GetStarVersionError {
+    #[error("GetStarVersion: {source}")]
+    GetStarVersion{ 
+        #[from] 
+        source: diesel::result::Error 
+    },
+    #[error("SumDailyStarAggregates: {source}")]
+    SumDailyStarAggregates{
+        source: diesel::result::Error
+    },
+}
+
+/// Latest `fetched_at` and star count of the repository, which change with every sync.
+/// The count includes compacted stars.
+pub fn get_star_version(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<(Option<NaiveDateTime>, i64), GetStarVersionError> {
+    let (latest, stored) = stars
+        .filter(repository_id.eq(repo_id_val))
+        .select((diesel::dsl::max(fetched_at), count_star()))
+        .first::<(Option<NaiveDateTime>, i64)>(conn)
+        .map_err(|source| GetStarVersionError::GetStarVersion{ source })?;
+
+    let compacted = daily_star_aggregates::table
+        .filter(daily_star_aggregates::repository_id.eq(repo_id_val))
+        .select(diesel::dsl::sum(daily_star_aggregates::stars))
+        .first::<Option<i64>>(conn)
+        .map_err(|source| GetStarVersionError::SumDailyStarAggregates{ source })?;
+
+    Ok((latest, stored + compacted.unwrap_or(0)))
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarsFirstFetchedBetweenError {
+    #[error("CountStars: {source}")]
+    CountStars{
+        source: diesel::result::Error
+    },
+    #[error("LoadStars: {source}")]
+    LoadStars{
+        source: diesel::result::Error
+    },
+}
+
+/// Stars first stored after `after` and up to `until`, the first `max` of them oldest first, and how many there are.
+pub fn get_stars_first_fetched_between(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    after: NaiveDateTime,
+    until: NaiveDateTime,
+    max: i64
+) -> Result<(Vec<(String, NaiveDateTime)>, i64), GetStarsFirstFetchedBetweenError> {
+    let first_fetched = || {
+        repository_id.eq(repo_id_val)
+            .and(first_fetched_at.assume_not_null().gt(after))
+            .and(first_fetched_at.assume_not_null().le(until))
+    };
+
+    let total = stars
+        .filter(first_fetched())
+        .count()
+        .get_result::<i64>(conn)
+        .map_err(|source| GetStarsFirstFetchedBetweenError::CountStars{ source })?;
+    let listed = stars
+        .filter(first_fetched())
+        .select((stargazer, starred_at))
+        .order_by((starred_at, stargazer))
+        .limit(max)
+        .load::<(String, NaiveDateTime)>(conn)
+        .map_err(|source| GetStarsFirstFetchedBetweenError::LoadStars{ source })?;
+    Ok((listed, total))
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarsLastFetchedBetweenError {
+    #[error("CountStars: {source}")]
+    CountStars{
+        source: diesel::result::Error
+    },
+    #[error("LoadStars: {source}")]
+    LoadStars{
+        source: diesel::result::Error
+    },
+}
+
+/// Stars stored by `stored_by` and last fetched from `from` and before `before`, the first `max` of them oldest
+/// first, and how many there are. Stars stored before `first_fetched_at` was recorded count as stored by then.
+pub fn get_stars_last_fetched_between(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    stored_by: NaiveDateTime,
+    from: NaiveDateTime,
+    before: NaiveDateTime,
+    max: i64
+) -> Result<(Vec<(String, NaiveDateTime)>, i64), GetStarsLastFetchedBetweenError> {
+    let last_fetched = || {
+        repository_id.eq(repo_id_val)
+            .and(first_fetched_at.is_null().or(first_fetched_at.assume_not_null().le(stored_by)))
+            .and(fetched_at.ge(from))
+            .and(fetched_at.lt(before))
+    };
+
+    let total = stars
+        .filter(last_fetched())
+        .count()
+        .get_result::<i64>(conn)
+        .map_err(|source| GetStarsLastFetchedBetweenError::CountStars{ source })?;
+    let listed = stars
+        .filter(last_fetched())
+        .select((stargazer, starred_at))
+        .order_by((starred_at, stargazer))
+        .limit(max)
+        .load::<(String, NaiveDateTime)>(conn)
+        .map_err(|source| GetStarsLastFetchedBetweenError::LoadStars{ source })?;
+    Ok((listed, total))
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarsStarredAfterError {
+    #[error("CountStars: {source}")]
+    CountStars{
+        source: diesel::result::Error
+    },
+    #[error("LoadStars: {source}")]
+    LoadStars{
+        source: diesel::result::Error
+    },
+}
+
+/// Stars starred after `after`, the first `max` of them oldest first, and how many there are.
+pub fn get_stars_starred_after(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    after: NaiveDateTime,
+    max: i64
+) -> Result<(Vec<(String, NaiveDateTime)>, i64), GetStarsStarredAfterError> {
+    let total = stars
+        .filter(repository_id.eq(repo_id_val).and(starred_at.gt(after)))
+        .count()
+        .get_result::<i64>(conn)
+        .map_err(|source| GetStarsStarredAfterError::CountStars{ source })?;
+    let listed = stars
+        .filter(repository_id.eq(repo_id_val).and(starred_at.gt(after)))
+        .select((stargazer, starred_at))
+        .order_by((starred_at, stargazer))
+        .limit(max)
+        .load::<(String, NaiveDateTime)>(conn)
+        .map_err(|source| GetStarsStarredAfterError::LoadStars{ source })?;
+    Ok((listed, total))
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarCountAsOfError {
+    #[error("CountStars: {source}")]
+    CountStars{
+        source: diesel::result::Error
+    },
+    #[error("SumDailyStarAggregates: {source}")]
+    SumDailyStarAggregates{
+        source: diesel::result::Error
+    },
+}
+
+/// Stars of the repository starred up to the end of `day`, UTC, as `(stored, compacted)`, those of suspected
+/// bots left out when `exclude_bots` is set. The stored ones are counted over an index range of `starred_at`.
+pub fn get_star_count_as_of(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    day: NaiveDate,
+    exclude_bots: bool
+) -> Result<(i64, i64), GetStarCountAsOfError> {
+    let mut stored = stars
+        .filter(repository_id.eq(repo_id_val))
+        .filter(starred_at.lt(end_of_day(day)))
+        .into_boxed();
+    if exclude_bots {
+        stored = stored.filter(suspected_bot.eq(false));
+    }
+    let stored = stored
+        .count()
+        .get_result::<i64>(conn)
+        .map_err(|source| GetStarCountAsOfError::CountStars{ source })?;
+
+    let (compacted, compacted_bots) = daily_star_aggregates::table
+        .filter(daily_star_aggregates::repository_id.eq(repo_id_val))
+        .filter(daily_star_aggregates::day.le(day))
+        .select((diesel::dsl::sum(daily_star_aggregates::stars), diesel::dsl::sum(daily_star_aggregates::bot_stars)))
+        .first::<(Option<i64>, Option<i64>)>(conn)
+        .map_err(|source| GetStarCountAsOfError::SumDailyStarAggregates{ source })?;
+    let compacted = compacted.unwrap_or(0) - if exclude_bots { compacted_bots.unwrap_or(0) } else { 0 };
+
+    Ok((stored, compacted))
+}
+
+#[derive(Debug, Error)]
+pub enum GetStargazersAsOfError {
+    #[error("GetStargazersAsOf: {source}")]
+    GetStargazersAsOf{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Up to `max` stored stars of the repository starred up to the end of `day`, UTC, after `after`, oldest first,
+/// stargazer breaking ties, those of suspected bots left out when `exclude_bots` is set.
+///
+/// Keyset pagination like [`get_stars_page`], each page an index range scan.
+pub fn get_stargazers_as_of(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    day: NaiveDate,
+    after: Option<&StarCursor>,
+    exclude_bots: bool,
+    max: i64
+) -> Result<Vec<(String, NaiveDateTime)>, GetStargazersAsOfError> {
+    let mut query = stars
+        .filter(repository_id.eq(repo_id_val))
+        .filter(starred_at.lt(end_of_day(day)))
+        .select((stargazer, starred_at))
+        .into_boxed();
+    if let Some((after_starred_at, after_stargazer)) = after {
+        query = query.filter(
+            starred_at.gt(*after_starred_at)
+                .or(starred_at.eq(*after_starred_at).and(stargazer.gt(after_stargazer.clone())))
+        );
+    }
+    if exclude_bots {
+        query = query.filter(suspected_bot.eq(false));
+    }
+
+    query
+        .order_by((starred_at, stargazer))
+        .limit(max)
+        .load::<(String, NaiveDateTime)>(conn)
+        .map_err(|source| GetStargazersAsOfError::GetStargazersAsOf{ source })
+}
+
+/// Midnight after `day`, the first instant not in it.
+fn end_of_day(day: NaiveDate) -> NaiveDateTime {
+    day.succ_opt().unwrap_or(day).and_time(chrono::NaiveTime::MIN)
+}
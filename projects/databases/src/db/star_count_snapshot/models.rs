@@ -0,0 +1,23 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::star_count_snapshots;
+use crate::db::repository::models::Repository;
+
+/// GitHub's `stargazerCount` of a repository at a point in time, without its stargazers.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(primary_key(repository_id, taken_at))]
+#[diesel(table_name = star_count_snapshots)]
+pub struct StarCountSnapshot {
+    pub repository_id: Uuid,
+    pub taken_at: NaiveDateTime,
+    pub stargazer_count: i32,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = star_count_snapshots)]
+pub struct NewStarCountSnapshot {
+    pub repository_id: Uuid,
+    pub stargazer_count: i32,
+}
@@ -0,0 +1,44 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::{star_count_snapshot::models::*, schema::star_count_snapshots::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum InsertStarCountSnapshotError {
+    #[error("InsertStarCountSnapshot: {source}")]
+    InsertStarCountSnapshot{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn insert_star_count_snapshot(
+    conn: &mut PgConnection,
+    new: &NewStarCountSnapshot
+) -> Result<StarCountSnapshot, InsertStarCountSnapshotError> {
+    diesel::insert_into(star_count_snapshots)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertStarCountSnapshotError::InsertStarCountSnapshot{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarCountSnapshotsError {
+    #[error("GetStarCountSnapshots: {source}")]
+    GetStarCountSnapshots{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Every snapshot of the repository, oldest first.
+pub fn get_star_count_snapshots(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Vec<StarCountSnapshot>, GetStarCountSnapshotsError> {
+    star_count_snapshots
+        .filter(repository_id.eq(repo_id_val))
+        .order_by(taken_at)
+        .load::<StarCountSnapshot>(conn)
+        .map_err(|source| GetStarCountSnapshotsError::GetStarCountSnapshots{ source })
+}
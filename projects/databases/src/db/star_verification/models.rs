@@ -0,0 +1,36 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::star_verifications;
+use crate::db::repository::models::Repository;
+
+/// Stored star count of a repository checked against GitHub's `stargazerCount`.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = star_verifications)]
+pub struct StarVerification {
+    pub id: Uuid,
+    pub repository_id: Uuid,
+    pub github_stars: i32,
+    pub stored_stars: i32,
+    /// Whether the drift was large enough to queue a repair sync.
+    pub repair_queued: bool,
+    pub checked_at: NaiveDateTime,
+}
+
+impl StarVerification {
+    /// Stored stars minus GitHub's, negative when stars are missing.
+    pub fn drift(&self) -> i32 {
+        self.stored_stars - self.github_stars
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = star_verifications)]
+pub struct NewStarVerification {
+    pub id: Uuid,
+    pub repository_id: Uuid,
+    pub github_stars: i32,
+    pub stored_stars: i32,
+    pub repair_queued: bool,
+}
@@ -0,0 +1,46 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::{repository::models::Repository, star_verification::models::*, schema::{repositories, star_verifications::dsl::*}};
+
+#[derive(Debug, Error)]
+pub enum InsertStarVerificationError {
+    #[error("InsertStarVerification: {source}")]
+    InsertStarVerification{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn insert_star_verification(
+    conn: &mut PgConnection,
+    new: &NewStarVerification
+) -> Result<StarVerification, InsertStarVerificationError> {
+    diesel::insert_into(star_verifications)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertStarVerificationError::InsertStarVerification{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetLatestStarVerificationsError {
+    #[error("GetLatestStarVerifications: {source}")]
+    GetLatestStarVerifications{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Latest verification of every verified repository of the tenant, along with the repository.
+pub fn get_latest_star_verifications(
+    conn: &mut PgConnection,
+    tenant_id_val: Uuid
+) -> Result<Vec<(StarVerification, Repository)>, GetLatestStarVerificationsError> {
+    star_verifications
+        .inner_join(repositories::table)
+        .filter(repositories::tenant_id.eq(tenant_id_val))
+        .distinct_on(repository_id)
+        .order_by((repository_id, checked_at.desc()))
+        .load::<(StarVerification, Repository)>(conn)
+        .map_err(|source| GetLatestStarVerificationsError::GetLatestStarVerifications{ source })
+}
@@ -0,0 +1,29 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use crate::db::schema::stargazer_emails;
+
+/// Public email of a stargazer, as last seen by a sync. Shared by every repository they starred.
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[diesel(primary_key(login))]
+#[diesel(table_name = stargazer_emails)]
+pub struct StargazerEmail {
+    pub login: String,
+    pub email: String,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = stargazer_emails)]
+pub struct NewStargazerEmail<'a> {
+    pub login: &'a str,
+    pub email: &'a str,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Stargazer of a repository with a public email, as exported.
+#[derive(Debug, Clone, Queryable)]
+pub struct RepositoryStargazerEmail {
+    pub login: String,
+    pub email: String,
+    pub starred_at: NaiveDateTime,
+}
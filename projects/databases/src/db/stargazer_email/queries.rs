@@ -0,0 +1,100 @@
+use chrono::NaiveDateTime;
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{pg::upsert::excluded, prelude::*};
+use crate::db::{stargazer_email::models::*, schema::{stargazer_emails::dsl::*, stars}};
+
+#[derive(Debug, Error)]
+pub enum UpsertStargazerEmailsError {
+    #[error("UpsertStargazerEmails: {source}")]
+    UpsertStargazerEmails{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stores the emails, replacing the stored ones.
+pub fn upsert_stargazer_emails(
+    conn: &mut PgConnection,
+    new: &[NewStargazerEmail]
+) -> Result<usize, UpsertStargazerEmailsError> {
+    diesel::insert_into(stargazer_emails)
+        .values(new)
+        .on_conflict(login)
+        .do_update()
+        .set((
+            email.eq(excluded(email)),
+            updated_at.eq(excluded(updated_at)),
+        ))
+        .execute(conn)
+        .map_err(|source| UpsertStargazerEmailsError::UpsertStargazerEmails{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteStargazerEmailsError {
+    #[error("DeleteStargazerEmails: {source}")]
+    DeleteStargazerEmails{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Forgets the emails of the stargazers, who no longer make them public.
+pub fn delete_stargazer_emails(
+    conn: &mut PgConnection,
+    logins: &[&str]
+) -> Result<usize, DeleteStargazerEmailsError> {
+    diesel::delete(stargazer_emails.filter(login.eq_any(logins)))
+        .execute(conn)
+        .map_err(|source| DeleteStargazerEmailsError::DeleteStargazerEmails{ source })
+}
+
+/// Which stargazers [`get_stargazer_emails_by_repository`] returns.
+#[derive(Debug, Clone, Default)]
+pub struct StargazerEmailFilter<'a> {
+    /// Only stargazers who starred the repository at or after it.
+    pub starred_since: Option<NaiveDateTime>,
+    /// Only emails of this domain, case-insensitive. Mustn't contain `%` or `_`.
+    pub domain: Option<&'a str>,
+    /// Only stargazers whose login sorts after it, the last one of the previous page.
+    pub after: Option<&'a str>,
+}
+
+#[derive(Debug, Error)]
+pub enum GetStargazerEmailsByRepositoryError {
+    #[error("GetStargazerEmailsByRepository: {source}")]
+    GetStargazerEmailsByRepository{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Up to `max` stargazers of the repository with a stored email, by login.
+pub fn get_stargazer_emails_by_repository(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    filter: &StargazerEmailFilter,
+    max: i64
+) -> Result<Vec<RepositoryStargazerEmail>, GetStargazerEmailsByRepositoryError> {
+    let mut query = stargazer_emails
+        .inner_join(stars::table.on(stars::stargazer.eq(login)))
+        .filter(stars::repository_id.eq(repo_id_val))
+        .select((login, email, stars::starred_at))
+        .order_by(login.asc())
+        .limit(max)
+        .into_boxed();
+
+    if let Some(since) = filter.starred_since {
+        query = query.filter(stars::starred_at.ge(since));
+    }
+    if let Some(domain) = filter.domain {
+        query = query.filter(email.ilike(format!("%@{domain}")));
+    }
+    if let Some(after) = filter.after {
+        query = query.filter(login.gt(after));
+    }
+
+    query
+        .load::<RepositoryStargazerEmail>(conn)
+        .map_err(|source| GetStargazerEmailsByRepositoryError::GetStargazerEmailsByRepository{ source })
+}
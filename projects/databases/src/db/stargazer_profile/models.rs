@@ -0,0 +1,23 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use crate::db::schema::stargazer_profiles;
+
+/// Public profile fields of a stargazer, as last seen by a sync. Shared by every repository they starred.
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[diesel(primary_key(login))]
+#[diesel(table_name = stargazer_profiles)]
+pub struct StargazerProfile {
+    pub login: String,
+    pub company: Option<String>,
+    pub location: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = stargazer_profiles)]
+pub struct NewStargazerProfile<'a> {
+    pub login: &'a str,
+    pub company: Option<&'a str>,
+    pub location: Option<&'a str>,
+    pub updated_at: NaiveDateTime,
+}
@@ -0,0 +1,56 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{pg::upsert::excluded, prelude::*};
+use crate::db::{stargazer_profile::models::*, schema::{stargazer_profiles::dsl::*, stars}};
+
+#[derive(Debug, Error)]
+pub enum UpsertStargazerProfilesError {
+    #[error("UpsertStargazerProfiles: {source}")]
+    UpsertStargazerProfiles{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stores the profiles, replacing the stored fields so cleared ones are forgotten.
+pub fn upsert_stargazer_profiles(
+    conn: &mut PgConnection,
+    new: &[NewStargazerProfile]
+) -> Result<usize, UpsertStargazerProfilesError> {
+    diesel::insert_into(stargazer_profiles)
+        .values(new)
+        .on_conflict(login)
+        .do_update()
+        .set((
+            company.eq(excluded(company)),
+            location.eq(excluded(location)),
+            updated_at.eq(excluded(updated_at)),
+        ))
+        .execute(conn)
+        .map_err(|source| UpsertStargazerProfilesError::UpsertStargazerProfiles{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetStargazerProfilesByRepositoryError {
+    #[error("GetStargazerProfilesByRepository: {source}")]
+    GetStargazerProfilesByRepository{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Profiles of the repository's stargazers having a company or a location.
+pub fn get_stargazer_profiles_by_repository(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid
+) -> Result<Vec<StargazerProfile>, GetStargazerProfilesByRepositoryError> {
+    let stargazers = stars::table
+        .filter(stars::repository_id.eq(repo_id_val))
+        .select(stars::stargazer);
+
+    stargazer_profiles
+        .filter(login.eq_any(stargazers))
+        .filter(company.is_not_null().or(location.is_not_null()))
+        .load::<StargazerProfile>(conn)
+        .map_err(|source| GetStargazerProfilesByRepositoryError::GetStargazerProfilesByRepository{ source })
+}
@@ -0,0 +1,24 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::sync_batches;
+
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[diesel(table_name = sync_batches)]
+pub struct SyncBatch {
+    pub id: Uuid,
+    /// Unset for batches of listed repositories, which may belong to several owners.
+    pub owner: Option<String>,
+    pub total_repositories: i32,
+    pub created_at: NaiveDateTime,
+    pub tenant_id: Uuid,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = sync_batches)]
+pub struct NewSyncBatch<'a> {
+    pub id: Uuid,
+    pub owner: Option<&'a str>,
+    pub total_repositories: i32,
+    pub tenant_id: Uuid,
+}
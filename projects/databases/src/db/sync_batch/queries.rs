@@ -0,0 +1,65 @@
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{dsl::count_star, prelude::*};
+use crate::db::{sync_batch::models::*, schema::{sync_batches::dsl::*, sync_jobs}};
+
+#[derive(Debug, Error)]
+pub enum InsertSyncBatchError {
+    #[error("InsertSyncBatch: {source}")]
+    InsertSyncBatch{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn insert_sync_batch(
+    conn: &mut PgConnection,
+    new: &NewSyncBatch
+) -> Result<SyncBatch, InsertSyncBatchError> {
+    diesel::insert_into(sync_batches)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertSyncBatchError::InsertSyncBatch{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetSyncBatchByIdError {
+    #[error("GetSyncBatchById: {source}")]
+    GetSyncBatchById{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn get_sync_batch_by_id(
+    conn: &mut PgConnection,
+    batch_id_val: Uuid
+) -> Result<Option<SyncBatch>, GetSyncBatchByIdError> {
+    sync_batches
+        .find(batch_id_val)
+        .first::<SyncBatch>(conn)
+        .optional()
+        .map_err(|source| GetSyncBatchByIdError::GetSyncBatchById{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum CountBatchJobsByStatusError {
+    #[error("CountBatchJobsByStatus: {source}")]
+    CountBatchJobsByStatus{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Number of child jobs of a batch per status, statuses without jobs are omitted.
+pub fn count_batch_jobs_by_status(
+    conn: &mut PgConnection,
+    batch_id_val: Uuid
+) -> Result<Vec<(String, i64)>, CountBatchJobsByStatusError> {
+    sync_jobs::table
+        .filter(sync_jobs::batch_id.eq(batch_id_val))
+        .group_by(sync_jobs::status)
+        .select((sync_jobs::status, count_star()))
+        .load::<(String, i64)>(conn)
+        .map_err(|source| CountBatchJobsByStatusError::CountBatchJobsByStatus{ source })
+}
@@ -0,0 +1,153 @@
+use chrono::NaiveDateTime;
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::sync_jobs;
+use crate::db::repository::models::Repository;
+
+/// Lifecycle of a sync job, stored as text in `sync_jobs.status`.
+///
+/// `Pending` → `Running` → `Completed`, `PartiallyCompleted`, `Failed` or
+/// `Interrupted`, the last three going back to `Pending` when resumed. A shutdown
+/// interrupts pending jobs too, and so does a start of the service for the jobs
+/// left pending or running. Moves are made through [`SyncJobState::transition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncJobStatus {
+    /// Waiting for a sync worker, e.g. a resumed job still in the queue.
+    Pending,
+    Running,
+    Completed,
+    /// Stopped by a limit of [`crate::sync_limits`], the stars fetched until then stored, resumable
+    /// past it.
+    PartiallyCompleted,
+    Failed,
+    /// Stopped by a shutdown, or turned down while its repository was being synced, resumable
+    /// like a failed job.
+    Interrupted,
+}
+
+impl SyncJobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncJobStatus::Pending => "pending",
+            SyncJobStatus::Running => "running",
+            SyncJobStatus::Completed => "completed",
+            SyncJobStatus::PartiallyCompleted => "partially_completed",
+            SyncJobStatus::Failed => "failed",
+            SyncJobStatus::Interrupted => "interrupted",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(SyncJobStatus::Pending),
+            "running" => Some(SyncJobStatus::Running),
+            "completed" => Some(SyncJobStatus::Completed),
+            "partially_completed" => Some(SyncJobStatus::PartiallyCompleted),
+            "failed" => Some(SyncJobStatus::Failed),
+            "interrupted" => Some(SyncJobStatus::Interrupted),
+            _ => None,
+        }
+    }
+
+    /// Whether a job in this status may move to `next`.
+    pub fn can_become(self, next: SyncJobStatus) -> bool {
+        use SyncJobStatus::*;
+        matches!(
+            (self, next),
+            (Pending, Running | Interrupted) | (Running, Completed | PartiallyCompleted | Failed | Interrupted) | (PartiallyCompleted | Failed | Interrupted, Pending)
+        )
+    }
+
+    /// Whether the job is over, for good or until resumed.
+    pub fn is_finished(self) -> bool {
+        matches!(self, SyncJobStatus::Completed | SyncJobStatus::PartiallyCompleted | SyncJobStatus::Failed | SyncJobStatus::Interrupted)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("IllegalSyncJobTransition: {job_id} can't go from {from} to {to}")]
+pub struct IllegalSyncJobTransition {
+    pub job_id: Uuid,
+    pub from: &'static str,
+    pub to: &'static str,
+}
+
+/// Status of a sync job along with when it started and finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncJobState {
+    pub status: SyncJobStatus,
+    /// First time the job ran, kept across resumes.
+    pub started_at: Option<NaiveDateTime>,
+    /// End of the job's last run, unset while it's pending or running.
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+impl SyncJobState {
+    /// Moves job `job_id` to `next` at `now`, refusing moves its lifecycle doesn't allow.
+    pub fn transition(&mut self, job_id: Uuid, next: SyncJobStatus, now: NaiveDateTime) -> Result<(), IllegalSyncJobTransition> {
+        if !self.status.can_become(next) {
+            warn!(%job_id, from = self.status.as_str(), to = next.as_str(), "illegal sync job transition");
+            return Err(IllegalSyncJobTransition { job_id, from: self.status.as_str(), to: next.as_str() });
+        }
+
+        match next {
+            SyncJobStatus::Running => {
+                self.started_at.get_or_insert(now);
+            }
+            SyncJobStatus::Pending => self.finished_at = None,
+            _ => self.finished_at = Some(now),
+        }
+        info!(%job_id, from = self.status.as_str(), to = next.as_str(), "sync job transition");
+        self.status = next;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = sync_jobs)]
+pub struct SyncJob {
+    pub id: Uuid,
+    pub repository_id: Uuid,
+    pub status: String,
+    /// Cursor of the last page whose stars were stored.
+    pub end_cursor: Option<String>,
+    pub pages_fetched: i32,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// Set when the job was started as part of an owner-wide batch.
+    pub batch_id: Option<Uuid>,
+    pub stars_fetched: i32,
+    /// Repository's `stargazerCount` as of the last stored page.
+    pub estimated_total_stars: Option<i32>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    /// Limit that stopped a `partially_completed` job, see [`crate::sync_limits::SyncStopReason`].
+    pub stop_reason: Option<String>,
+    /// Transient forge failures retried so far.
+    pub retries: i32,
+}
+
+impl SyncJob {
+    /// Lifecycle state of the job, `None` for a status this version doesn't know.
+    pub fn state(&self) -> Option<SyncJobState> {
+        Some(SyncJobState {
+            status: SyncJobStatus::parse(&self.status)?,
+            started_at: self.started_at,
+            finished_at: self.finished_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = sync_jobs)]
+pub struct NewSyncJob<'a> {
+    pub id: Uuid,
+    pub repository_id: Uuid,
+    pub status: &'a str,
+    pub batch_id: Option<Uuid>,
+    pub started_at: Option<NaiveDateTime>,
+}
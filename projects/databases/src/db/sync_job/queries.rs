@@ -0,0 +1,232 @@
+use chrono::{NaiveDateTime, Utc};
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{prelude::*, sql_types::{Nullable, Text, Timestamp, Uuid as SqlUuid}};
+use crate::db::{sync_job::models::*, schema::sync_jobs::dsl::*};
+use crate::sync_limits::SyncStopReason;
+
+#[derive(Debug, Error)]
+pub enum InsertSyncJobError {
+    #[error("InsertSyncJob: {source}")]
+    InsertSyncJob{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn insert_sync_job(
+    conn: &mut PgConnection,
+    new: &NewSyncJob
+) -> Result<SyncJob, InsertSyncJobError> {
+    diesel::insert_into(sync_jobs)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertSyncJobError::InsertSyncJob{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetSyncJobByIdError {
+    #[error("GetSyncJobById: {source}")]
+    GetSyncJobById{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn get_sync_job_by_id(
+    conn: &mut PgConnection,
+    job_id: Uuid
+) -> Result<Option<SyncJob>, GetSyncJobByIdError> {
+    sync_jobs
+        .find(job_id)
+        .first::<SyncJob>(conn)
+        .optional()
+        .map_err(|source| GetSyncJobByIdError::GetSyncJobById{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetUnfinishedSyncJobsError {
+    #[error("GetUnfinishedSyncJobs: {source}")]
+    GetUnfinishedSyncJobs{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Jobs still pending or running, oldest first.
+pub fn get_unfinished_sync_jobs(
+    conn: &mut PgConnection
+) -> Result<Vec<SyncJob>, GetUnfinishedSyncJobsError> {
+    sync_jobs
+        .filter(status.eq_any([SyncJobStatus::Pending.as_str(), SyncJobStatus::Running.as_str()]))
+        .order(created_at.asc())
+        .load::<SyncJob>(conn)
+        .map_err(|source| GetUnfinishedSyncJobsError::GetUnfinishedSyncJobs{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateSyncJobProgressError {
+    #[error("UpdateSyncJobProgress: {source}")]
+    UpdateSyncJobProgress{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Records the cursor of the last stored page so the job can be resumed from it,
+/// along with the stars fetched so far out of the `estimated_total` GitHub reports.
+pub fn update_sync_job_progress(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    cursor: Option<&str>,
+    pages: i32,
+    stars: i32,
+    estimated_total: i32,
+    retry_count: i32,
+) -> Result<(), UpdateSyncJobProgressError> {
+    diesel::update(sync_jobs.find(job_id))
+        .set((
+            end_cursor.eq(cursor),
+            pages_fetched.eq(pages),
+            stars_fetched.eq(stars),
+            estimated_total_stars.eq(estimated_total),
+            retries.eq(retry_count),
+        ))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|source| UpdateSyncJobProgressError::UpdateSyncJobProgress{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum TransitionSyncJobError {
+    #[error("TransitionSyncJob: {source}")]
+    TransitionSyncJob{
+        #[from]
+        source: diesel::result::Error
+    },
+    #[error("SyncJobNotFound: {job_id}")]
+    SyncJobNotFound{
+        job_id: Uuid
+    },
+    #[error("UnknownSyncJobStatus: {value}")]
+    UnknownSyncJobStatus{
+        value: String
+    },
+    #[error("IllegalTransition: {source}")]
+    IllegalTransition{
+        #[from]
+        source: IllegalSyncJobTransition
+    },
+}
+
+/// Moves the job to `next`, see [`SyncJobState::transition`], recording `error_message` along.
+///
+/// The job's row is locked meanwhile, so of two concurrent moves from the same status only
+/// the first one goes through.
+pub fn transition_sync_job(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    next: SyncJobStatus,
+    error_message: Option<&str>
+) -> Result<SyncJob, TransitionSyncJobError> {
+    conn.transaction(|conn| {
+        let job = sync_jobs
+            .find(job_id)
+            .for_update()
+            .first::<SyncJob>(conn)
+            .optional()?
+            .ok_or(TransitionSyncJobError::SyncJobNotFound{ job_id })?;
+        let mut state = job.state()
+            .ok_or_else(|| TransitionSyncJobError::UnknownSyncJobStatus{ value: job.status.clone() })?;
+
+        state.transition(job_id, next, Utc::now().naive_utc())?;
+
+        diesel::update(sync_jobs.find(job_id))
+            .set((
+                status.eq(state.status.as_str()),
+                error.eq(error_message),
+                // A resumed job goes past the limit it stopped at, if any.
+                stop_reason.eq(job.stop_reason.as_deref().filter(|_| next != SyncJobStatus::Pending)),
+                started_at.eq(state.started_at),
+                finished_at.eq(state.finished_at),
+            ))
+            .get_result(conn)
+            .map_err(|source| TransitionSyncJobError::TransitionSyncJob{ source })
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum RecordSyncJobStopReasonError {
+    #[error("RecordSyncJobStopReason: {source}")]
+    RecordSyncJobStopReason{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Records the limit that stopped the job, before it is moved to `partially_completed`.
+pub fn record_sync_job_stop_reason(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    reason: SyncStopReason
+) -> Result<(), RecordSyncJobStopReasonError> {
+    diesel::update(sync_jobs.find(job_id))
+        .set(stop_reason.eq(reason.as_str()))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|source| RecordSyncJobStopReasonError::RecordSyncJobStopReason{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum PurgeFinishedSyncJobsError {
+    #[error("PurgeFinishedSyncJobs: {source}")]
+    PurgeFinishedSyncJobs{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Deletes the jobs finished before `before`, of `tenant` only when set, and returns how many.
+///
+/// Jobs of a batch go along with their batch, once every repository of it was synced and
+/// finished before `before`. Runs of the deleted jobs are kept in the sync history.
+pub fn purge_finished_sync_jobs(
+    conn: &mut PgConnection,
+    before: NaiveDateTime,
+    tenant: Option<Uuid>
+) -> Result<usize, PurgeFinishedSyncJobsError> {
+    conn.transaction(|conn| {
+        // Their jobs lose their batch, and are purged below like any other.
+        diesel::sql_query(
+            "DELETE FROM sync_batches
+            WHERE ($2::uuid IS NULL OR tenant_id = $2)
+            AND id IN (
+                SELECT sync_batches.id
+                FROM sync_batches
+                JOIN sync_jobs ON sync_jobs.batch_id = sync_batches.id
+                GROUP BY sync_batches.id, sync_batches.total_repositories
+                HAVING COUNT(*) >= sync_batches.total_repositories
+                AND BOOL_AND(sync_jobs.finished_at IS NOT NULL AND sync_jobs.finished_at < $1)
+            )"
+        )
+            .bind::<Timestamp, _>(before)
+            .bind::<Nullable<SqlUuid>, _>(tenant)
+            .execute(conn)?;
+
+        diesel::sql_query(
+            "DELETE FROM sync_jobs
+            WHERE status IN ($3, $4, $5, $6)
+            AND finished_at < $1
+            AND batch_id IS NULL
+            AND ($2::uuid IS NULL OR repository_id IN (SELECT id FROM repositories WHERE tenant_id = $2))"
+        )
+            .bind::<Timestamp, _>(before)
+            .bind::<Nullable<SqlUuid>, _>(tenant)
+            .bind::<Text, _>(SyncJobStatus::Completed.as_str())
+            .bind::<Text, _>(SyncJobStatus::PartiallyCompleted.as_str())
+            .bind::<Text, _>(SyncJobStatus::Failed.as_str())
+            .bind::<Text, _>(SyncJobStatus::Interrupted.as_str())
+            .execute(conn)
+            .map_err(|source| PurgeFinishedSyncJobsError::PurgeFinishedSyncJobs{ source })
+    })
+}
@@ -0,0 +1,76 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::sync_runs;
+use crate::db::repository::models::Repository;
+
+/// What started a sync run, stored as text in `sync_runs.trigger`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncTrigger {
+    /// `POST /github/repo_stars/update`
+    Api,
+    /// One repository of an owner-wide batch.
+    OwnerSync,
+    /// One repository of a batch listed by the caller.
+    Batch,
+    /// Continuation of a failed, interrupted or partially completed job.
+    Resume,
+    /// Queued by a star count verification that found stars missing.
+    Repair,
+    /// `star_tracker sync`
+    Cli,
+    /// Fork of a repository with fork tracking, synced along with it.
+    Fork,
+    /// First sync of a repository listed in `REPOS_FILE`, see [`crate::watch_list`].
+    WatchList,
+}
+
+impl SyncTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncTrigger::Api => "api",
+            SyncTrigger::OwnerSync => "owner_sync",
+            SyncTrigger::Batch => "batch",
+            SyncTrigger::Resume => "resume",
+            SyncTrigger::Repair => "repair",
+            SyncTrigger::Cli => "cli",
+            SyncTrigger::Fork => "fork",
+            SyncTrigger::WatchList => "watch_list",
+        }
+    }
+}
+
+/// One execution of a sync job. A resumed job gets a run per attempt.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = sync_runs)]
+pub struct SyncRun {
+    pub id: Uuid,
+    /// Unset once the job was purged, see [`crate::job_retention`].
+    pub job_id: Option<Uuid>,
+    pub repository_id: Uuid,
+    pub trigger: String,
+    /// Same values as `sync_jobs.status`.
+    pub status: String,
+    /// Pages fetched by this run only, not by earlier attempts of the job.
+    pub pages_fetched: i32,
+    /// Growth of the stored star count over the run.
+    pub stars_added: i32,
+    pub error: Option<String>,
+    pub started_at: NaiveDateTime,
+    /// Unset while the run goes on.
+    pub finished_at: Option<NaiveDateTime>,
+    /// `fetched_at` written to the stars the run fetched, unset for runs recorded before it was.
+    pub fetched_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = sync_runs)]
+pub struct NewSyncRun<'a> {
+    pub id: Uuid,
+    pub job_id: Option<Uuid>,
+    pub repository_id: Uuid,
+    pub trigger: &'a str,
+    pub status: &'a str,
+    pub fetched_at: Option<NaiveDateTime>,
+}
@@ -0,0 +1,128 @@
+use thiserror::Error;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::{dsl::now, prelude::*};
+use crate::db::{sync_job::models::SyncJobStatus, sync_run::models::*, schema::sync_runs::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum InsertSyncRunError {
+    #[error("InsertSyncRun: {source}")]
+    InsertSyncRun{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn insert_sync_run(
+    conn: &mut PgConnection,
+    new: &NewSyncRun
+) -> Result<SyncRun, InsertSyncRunError> {
+    diesel::insert_into(sync_runs)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertSyncRunError::InsertSyncRun{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum FinishSyncRunError {
+    #[error("FinishSyncRun: {source}")]
+    FinishSyncRun{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Records the outcome of the run and stamps `finished_at`.
+pub fn finish_sync_run(
+    conn: &mut PgConnection,
+    run_id: Uuid,
+    new_status: SyncJobStatus,
+    pages: i32,
+    stars: i32,
+    error_message: Option<&str>
+) -> Result<(), FinishSyncRunError> {
+    diesel::update(sync_runs.find(run_id))
+        .set((
+            status.eq(new_status.as_str()),
+            pages_fetched.eq(pages),
+            stars_added.eq(stars),
+            error.eq(error_message),
+            finished_at.eq(now.nullable()),
+        ))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|source| FinishSyncRunError::FinishSyncRun{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetSyncRunsByRepositoryError {
+    #[error("GetSyncRunsByRepository: {source}")]
+    GetSyncRunsByRepository{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Latest `max` runs of the repository, newest first.
+pub fn get_sync_runs_by_repository(
+    conn: &mut PgConnection,
+    repo_id: Uuid,
+    max: i64
+) -> Result<Vec<SyncRun>, GetSyncRunsByRepositoryError> {
+    sync_runs
+        .filter(repository_id.eq(repo_id))
+        .order_by(started_at.desc())
+        .limit(max)
+        .load::<SyncRun>(conn)
+        .map_err(|source| GetSyncRunsByRepositoryError::GetSyncRunsByRepository{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetSyncRunError {
+    #[error("GetSyncRun: {source}")]
+    GetSyncRun{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Run `run_id`, when it is one of the repository's.
+pub fn get_sync_run(
+    conn: &mut PgConnection,
+    repo_id: Uuid,
+    run_id: Uuid
+) -> Result<Option<SyncRun>, GetSyncRunError> {
+    sync_runs
+        .find(run_id)
+        .filter(repository_id.eq(repo_id))
+        .first::<SyncRun>(conn)
+        .optional()
+        .map_err(|source| GetSyncRunError::GetSyncRun{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetJobFirstFetchedAtError {
+    #[error("GetJobFirstFetchedAt: {source}")]
+    GetJobFirstFetchedAt{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Earliest `fetched_at` written by the runs of `run`'s job, the run's own once the job was purged.
+///
+/// A resumed job fetches from where the previous attempt stopped, the stars of its first
+/// pages carry the stamp of an earlier run.
+pub fn get_job_first_fetched_at(
+    conn: &mut PgConnection,
+    run: &SyncRun
+) -> Result<Option<NaiveDateTime>, GetJobFirstFetchedAtError> {
+    let Some(run_job_id) = run.job_id else {
+        return Ok(run.fetched_at);
+    };
+    sync_runs
+        .filter(job_id.eq(run_job_id))
+        .select(diesel::dsl::min(fetched_at))
+        .first::<Option<NaiveDateTime>>(conn)
+        .map_err(|source| GetJobFirstFetchedAtError::GetJobFirstFetchedAt{ source })
+}
@@ -0,0 +1,25 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::tenants;
+
+/// Tenant of anonymous requests, and of everything tracked before tenants existed.
+pub const DEFAULT_TENANT_ID: Uuid = Uuid::nil();
+
+/// A team sharing the deployment. Tenants only see their own repositories.
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[diesel(table_name = tenants)]
+pub struct Tenant {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    /// Addresses the weekly digest is emailed to.
+    pub digest_recipients: Vec<String>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = tenants)]
+pub struct NewTenant<'a> {
+    pub id: Uuid,
+    pub name: &'a str,
+}
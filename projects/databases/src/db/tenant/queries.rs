@@ -0,0 +1,81 @@
+use thiserror::Error;
+use diesel::prelude::*;
+use crate::db::{tenant::models::*, schema::tenants::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum InsertTenantError {
+    #[error("InsertTenant: {source}")]
+    InsertTenant{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn insert_tenant(
+    conn: &mut PgConnection,
+    new: &NewTenant
+) -> Result<Tenant, InsertTenantError> {
+    diesel::insert_into(tenants)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertTenantError::InsertTenant{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetTenantByNameError {
+    #[error("GetTenantByName: {source}")]
+    GetTenantByName{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn get_tenant_by_name(
+    conn: &mut PgConnection,
+    name_val: &str
+) -> Result<Option<Tenant>, GetTenantByNameError> {
+    tenants
+        .filter(name.eq(name_val))
+        .first::<Tenant>(conn)
+        .optional()
+        .map_err(|source| GetTenantByNameError::GetTenantByName{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum ListTenantsError {
+    #[error("ListTenants: {source}")]
+    ListTenants{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+pub fn list_tenants(
+    conn: &mut PgConnection
+) -> Result<Vec<Tenant>, ListTenantsError> {
+    tenants
+        .order_by(name)
+        .load::<Tenant>(conn)
+        .map_err(|source| ListTenantsError::ListTenants{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum SetDigestRecipientsError {
+    #[error("SetDigestRecipients: {source}")]
+    SetDigestRecipients{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Replaces the addresses the tenant's weekly digest is emailed to, none to stop emailing it.
+pub fn set_digest_recipients(
+    conn: &mut PgConnection,
+    tenant_id: uuid::Uuid,
+    recipients: &[String]
+) -> Result<Tenant, SetDigestRecipientsError> {
+    diesel::update(tenants.find(tenant_id))
+        .set(digest_recipients.eq(recipients))
+        .get_result(conn)
+        .map_err(|source| SetDigestRecipientsError::SetDigestRecipients{ source })
+}
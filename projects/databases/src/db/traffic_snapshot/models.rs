@@ -0,0 +1,30 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::traffic_snapshots;
+use crate::db::repository::models::Repository;
+
+#[derive(Debug, Clone, Queryable, Identifiable, Associations)]
+#[diesel(belongs_to(Repository))]
+#[diesel(table_name = traffic_snapshots)]
+#[diesel(primary_key(repository_id, kind, day))]
+pub struct TrafficSnapshot {
+    pub repository_id: Uuid,
+    /// `views` or `clones`.
+    pub kind: String,
+    pub day: NaiveDate,
+    pub count: i32,
+    pub uniques: i32,
+    pub recorded_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = traffic_snapshots)]
+pub struct NewTrafficSnapshot<'a> {
+    pub repository_id: Uuid,
+    pub kind: &'a str,
+    pub day: NaiveDate,
+    pub count: i32,
+    pub uniques: i32,
+    pub recorded_at: NaiveDateTime,
+}
@@ -0,0 +1,58 @@
+use chrono::NaiveDate;
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{pg::upsert::excluded, prelude::*};
+use crate::db::{traffic_snapshot::models::*, schema::traffic_snapshots::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum UpsertTrafficSnapshotsError {
+    #[error("UpsertTrafficSnapshots: {source}")]
+    UpsertTrafficSnapshots{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Stores the daily traffic, replacing the counts of known days: the current one keeps growing
+/// until it is over.
+pub fn upsert_traffic_snapshots(
+    conn: &mut PgConnection,
+    new: &[NewTrafficSnapshot]
+) -> Result<usize, UpsertTrafficSnapshotsError> {
+    diesel::insert_into(traffic_snapshots)
+        .values(new)
+        .on_conflict((repository_id, kind, day))
+        .do_update()
+        .set((
+            count.eq(excluded(count)),
+            uniques.eq(excluded(uniques)),
+            recorded_at.eq(excluded(recorded_at)),
+        ))
+        .execute(conn)
+        .map_err(|source| UpsertTrafficSnapshotsError::UpsertTrafficSnapshots{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetDailyTrafficError {
+    #[error("GetDailyTraffic: {source}")]
+    GetDailyTraffic{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// `(day, count)` of the repository's `kind_val` traffic, oldest first, days never recorded left out.
+pub fn get_daily_traffic(
+    conn: &mut PgConnection,
+    repo_id_val: Uuid,
+    kind_val: &str
+) -> Result<Vec<(NaiveDate, i64)>, GetDailyTrafficError> {
+    traffic_snapshots
+        .filter(repository_id.eq(repo_id_val))
+        .filter(kind.eq(kind_val))
+        .order_by(day)
+        .select((day, count))
+        .load::<(NaiveDate, i32)>(conn)
+        .map(|days| days.into_iter().map(|(date, total)| (date, i64::from(total))).collect())
+        .map_err(|source| GetDailyTrafficError::GetDailyTraffic{ source })
+}
@@ -0,0 +1,66 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use diesel::prelude::*;
+use crate::db::schema::webhook_outbox;
+
+/// State of a webhook delivery, stored as text in `webhook_outbox.status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookDeliveryStatus {
+    /// Waiting for its next attempt, the first one included.
+    Pending,
+    /// Answered with a 2xx.
+    Delivered,
+    /// Given up on, out of attempts or to a URL the webhook policy refuses.
+    Failed,
+}
+
+impl WebhookDeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookDeliveryStatus::Pending => "pending",
+            WebhookDeliveryStatus::Delivered => "delivered",
+            WebhookDeliveryStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(WebhookDeliveryStatus::Pending),
+            "delivered" => Some(WebhookDeliveryStatus::Delivered),
+            "failed" => Some(WebhookDeliveryStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A webhook to post, see [`crate::outbox`].
+#[derive(Debug, Clone, Queryable, QueryableByName, Identifiable)]
+#[diesel(table_name = webhook_outbox)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    /// Rule that fired the alert, unset once it is deleted.
+    pub alert_rule_id: Option<Uuid>,
+    /// Sync job whose end fired the alert, unset once it is purged.
+    pub job_id: Option<Uuid>,
+    pub destination: String,
+    /// JSON body posted, the same on every attempt.
+    pub payload: serde_json::Value,
+    pub status: String,
+    /// Attempts started so far, including one that may still be running.
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    /// When a pending delivery is due, or when the running attempt is given up for lost.
+    pub next_attempt_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = webhook_outbox)]
+pub struct NewWebhookDelivery<'a> {
+    pub id: Uuid,
+    pub alert_rule_id: Option<Uuid>,
+    pub job_id: Option<Uuid>,
+    pub destination: &'a str,
+    pub payload: serde_json::Value,
+}
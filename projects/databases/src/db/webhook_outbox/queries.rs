@@ -0,0 +1,132 @@
+use chrono::NaiveDateTime;
+use thiserror::Error;
+use uuid::Uuid;
+use diesel::{prelude::*, sql_types::{BigInt, Text, Timestamp}};
+use crate::db::{webhook_outbox::models::*, schema::webhook_outbox::dsl::*};
+
+#[derive(Debug, Error)]
+pub enum InsertWebhookDeliveryError {
+    #[error("InsertWebhookDelivery: {source}")]
+    InsertWebhookDelivery{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Queues a delivery, due right away.
+pub fn insert_webhook_delivery(
+    conn: &mut PgConnection,
+    new: &NewWebhookDelivery
+) -> Result<WebhookDelivery, InsertWebhookDeliveryError> {
+    diesel::insert_into(webhook_outbox)
+        .values(new)
+        .get_result(conn)
+        .map_err(|source| InsertWebhookDeliveryError::InsertWebhookDelivery{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum ClaimDueWebhookDeliveriesError {
+    #[error("ClaimDueWebhookDeliveries: {source}")]
+    ClaimDueWebhookDeliveries{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Starts an attempt of up to `max` pending deliveries due at `now`, oldest due first, and returns them.
+///
+/// They aren't due again before `lease_until`, when an attempt that never reported back is given up
+/// for lost. Deliveries claimed by another instance at the same time are skipped.
+pub fn claim_due_webhook_deliveries(
+    conn: &mut PgConnection,
+    now: NaiveDateTime,
+    lease_until: NaiveDateTime,
+    max: i64
+) -> Result<Vec<WebhookDelivery>, ClaimDueWebhookDeliveriesError> {
+    diesel::sql_query(
+        "UPDATE webhook_outbox
+        SET attempts = attempts + 1, next_attempt_at = $2
+        WHERE id IN (
+            SELECT id FROM webhook_outbox
+            WHERE status = $4 AND next_attempt_at <= $1
+            ORDER BY next_attempt_at
+            LIMIT $3
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *"
+    )
+        .bind::<Timestamp, _>(now)
+        .bind::<Timestamp, _>(lease_until)
+        .bind::<BigInt, _>(max)
+        .bind::<Text, _>(WebhookDeliveryStatus::Pending.as_str())
+        .load::<WebhookDelivery>(conn)
+        .map_err(|source| ClaimDueWebhookDeliveriesError::ClaimDueWebhookDeliveries{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum FinishWebhookAttemptError {
+    #[error("FinishWebhookAttempt: {source}")]
+    FinishWebhookAttempt{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Marks the delivery delivered at `at`.
+pub fn mark_webhook_delivered(
+    conn: &mut PgConnection,
+    delivery_id: Uuid,
+    at: NaiveDateTime
+) -> Result<(), FinishWebhookAttemptError> {
+    diesel::update(webhook_outbox.find(delivery_id))
+        .set((
+            status.eq(WebhookDeliveryStatus::Delivered.as_str()),
+            delivered_at.eq(at),
+            last_error.eq(None::<String>),
+        ))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(|source| FinishWebhookAttemptError::FinishWebhookAttempt{ source })
+}
+
+/// Records the failed attempt, the delivery being due again at `retry_at`, or given up on without it.
+pub fn record_webhook_failure(
+    conn: &mut PgConnection,
+    delivery_id: Uuid,
+    error_message: &str,
+    retry_at: Option<NaiveDateTime>
+) -> Result<(), FinishWebhookAttemptError> {
+    let delivery = webhook_outbox.find(delivery_id);
+    let updated = match retry_at {
+        Some(at) => diesel::update(delivery)
+            .set((last_error.eq(error_message), next_attempt_at.eq(at)))
+            .execute(conn),
+        None => diesel::update(delivery)
+            .set((last_error.eq(error_message), status.eq(WebhookDeliveryStatus::Failed.as_str())))
+            .execute(conn),
+    };
+    updated
+        .map(|_| ())
+        .map_err(|source| FinishWebhookAttemptError::FinishWebhookAttempt{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum GetWebhookDeliveriesByJobError {
+    #[error("GetWebhookDeliveriesByJob: {source}")]
+    GetWebhookDeliveriesByJob{
+        #[from]
+        source: diesel::result::Error
+    },
+}
+
+/// Deliveries of the alerts fired by the job, oldest first.
+pub fn get_webhook_deliveries_by_job(
+    conn: &mut PgConnection,
+    sync_job_id: Uuid
+) -> Result<Vec<WebhookDelivery>, GetWebhookDeliveriesByJobError> {
+    webhook_outbox
+        .filter(job_id.eq(sync_job_id))
+        .order_by(created_at.asc())
+        .load::<WebhookDelivery>(conn)
+        .map_err(|source| GetWebhookDeliveriesByJobError::GetWebhookDeliveriesByJob{ source })
+}
@@ -0,0 +1,528 @@
+//! Weekly digests
+//!
+//! Once a week, at the `DIGEST_SCHEDULE` time, every tenant tracking
+//! repositories gets a digest of the week before, Monday to Sunday: the stars
+//! each repository received compared to the week before it, and the top
+//! movers along with a chart of their last 90 days. Digests are stored as
+//! reports, once per tenant and week, then emailed to the tenant's digest
+//! recipients when SMTP is configured. The stored HTML inlines the charts as
+//! SVG, emails attach them, as PNG with the `charting` feature.
+
+use std::{fmt::Write, str::FromStr, time::Duration};
+
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use diesel::PgConnection;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::analytics::series::cumulative_series;
+use crate::chart::{escape, render_star_history_svg, AxisOptions, ChartTheme};
+use crate::db::{
+	    report::{
+	        models::{NewReport, Report, ReportKind},
+	        queries::{get_report_by_period, insert_report, mark_report_sent, GetReportByPeriodError, InsertReportError, MarkReportSentError},
+	    },
+	    repository::queries::{list_repositories, ListRepositoriesError},
+	    star::queries::{get_daily_star_count, GetDailyStarCountError},
+	    tenant::queries::{list_tenants, ListTenantsError},
+	    PgPool,
+	};
+use crate::email::{EmailSender, InlineImage, SendEmailError};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::notifier::Notifier;
+use crate::sync_queue::StopSignal;
+
+/// Repositories listed as top movers.
+pub const TOP_MOVERS: usize = 5;
+/// Days of history drawn in the chart of a top mover.
+const CHART_DAYS: u64 = 90;
+
+/// Weekly time digests are generated at, in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestSchedule {
+    pub weekday: Weekday,
+    pub time: NaiveTime,
+}
+
+#[derive(Debug, Error)]
+#[error("InvalidDigestSchedule: expected a weekday and a time such as `mon 08:00`, got {value}")]
+pub struct ParseDigestScheduleError {
+    value: String,
+}
+
+impl FromStr for DigestSchedule {
+    type Err = ParseDigestScheduleError;
+
+    /// A weekday and a 24-hour time, e.g. `mon 08:00`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || ParseDigestScheduleError { value: value.into() };
+        let (weekday, time) = value.trim().split_once(char::is_whitespace).ok_or_else(error)?;
+        Ok(Self {
+            weekday: weekday.parse().map_err(|_| error())?,
+            time: NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| error())?,
+        })
+    }
+}
+
+impl DigestSchedule {
+    /// First scheduled time strictly after `now`.
+    pub fn next_run(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let days_ahead = (7 + self.weekday.num_days_from_monday() - now.weekday().num_days_from_monday()) % 7;
+        let run = now.date().checked_add_days(Days::new(u64::from(days_ahead))).unwrap_or(now.date()).and_time(self.time);
+        if run > now {
+            run
+        } else {
+            run.checked_add_days(Days::new(7)).unwrap_or(run)
+        }
+    }
+}
+
+/// Monday of the week containing `day`.
+pub fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Days::new(u64::from(day.weekday().num_days_from_monday()))
+}
+
+/// Monday of the last full week before `day`, the week a digest generated on `day` covers.
+pub fn previous_week_start(day: NaiveDate) -> NaiveDate {
+    let monday = week_start(day);
+    monday.checked_sub_days(Days::new(7)).unwrap_or(monday)
+}
+
+/// Stars of a tenant's repositories over a week.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct WeeklyDigest {
+    /// Monday of the week.
+    pub week_start: NaiveDate,
+    /// Sunday of the week.
+    pub week_end: NaiveDate,
+    /// Stars received by every repository during the week.
+    pub stars: i64,
+    pub previous_week_stars: i64,
+    /// Repositories with the most stars received during the week, most first.
+    pub top_movers: Vec<RepositoryDelta>,
+    /// Every tracked repository, by owner and name.
+    pub repositories: Vec<RepositoryDelta>,
+}
+
+/// Stars of a repository during the week, compared to the week before.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct RepositoryDelta {
+    pub owner: String,
+    pub name: String,
+    /// Stars at the end of the week.
+    pub total_stars: i64,
+    pub stars: i64,
+    pub previous_week_stars: i64,
+    pub change: i64,
+    /// `change` as a percentage of `previous_week_stars`, `None` when it is 0.
+    pub percent: Option<f64>,
+}
+
+/// Star history drawn in a digest.
+#[derive(Debug, Clone)]
+pub struct DigestChart {
+    /// `owner/name`
+    pub title: String,
+    /// Cumulative stars over the 90 days ending with the week.
+    pub cumulative: Vec<(NaiveDate, i64)>,
+}
+
+#[derive(Debug, Error)]
+pub enum BuildWeeklyDigestError {
+    #[error("ListRepositories: {source}")]
+    ListRepositories {
+        #[from]
+        source: ListRepositoriesError,
+    },
+    #[error("GetDailyStarCount: {source}")]
+    GetDailyStarCount {
+        #[from]
+        source: GetDailyStarCountError,
+    },
+}
+
+/// Digest of the tenant's repositories for the week starting on `week_start`, with the charts of its top movers.
+pub fn build_weekly_digest(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    week_start: NaiveDate,
+) -> Result<(WeeklyDigest, Vec<DigestChart>), BuildWeeklyDigestError> {
+    let week_end = week_start + Days::new(6);
+    let previous_start = week_start - Days::new(7);
+
+    let repos = list_repositories(conn, tenant_id, None)
+        .map_err(|source| BuildWeeklyDigestError::ListRepositories { source })?;
+
+    let mut repositories = Vec::with_capacity(repos.len());
+    let mut histories = Vec::with_capacity(repos.len());
+    for repo in repos {
+        let daily_counts: Vec<(NaiveDate, i64)> = get_daily_star_count(conn, repo.id)
+            .map_err(|source| BuildWeeklyDigestError::GetDailyStarCount { source })?
+            .into_iter()
+            .filter(|&(day, _)| day <= week_end)
+            .collect();
+        let stars_between = |from: NaiveDate, to: NaiveDate| -> i64 {
+            daily_counts.iter().filter(|&&(day, _)| from <= day && day <= to).map(|&(_, stars)| stars).sum()
+        };
+
+        let stars = stars_between(week_start, week_end);
+        let previous_week_stars = stars_between(previous_start, week_start - Days::new(1));
+        repositories.push(RepositoryDelta {
+            total_stars: daily_counts.iter().map(|&(_, stars)| stars).sum(),
+            stars,
+            previous_week_stars,
+            change: stars - previous_week_stars,
+            percent: percent_change(stars, previous_week_stars),
+            owner: repo.owner,
+            name: repo.name,
+        });
+        histories.push(daily_counts);
+    }
+
+    let mut movers: Vec<usize> = (0..repositories.len()).filter(|&index| repositories[index].stars > 0).collect();
+    movers.sort_by_key(|&index| std::cmp::Reverse(repositories[index].stars));
+    movers.truncate(TOP_MOVERS);
+
+    let chart_start = week_end - Days::new(CHART_DAYS - 1);
+    let charts = movers
+        .iter()
+        .map(|&index| DigestChart {
+            title: format!("{}/{}", repositories[index].owner, repositories[index].name),
+            cumulative: cumulative_series(&histories[index])
+                .into_iter()
+                .filter(|&(day, _)| day >= chart_start)
+                .collect(),
+        })
+        .collect();
+
+    let stars = repositories.iter().map(|repo| repo.stars).sum();
+    let previous_week_stars = repositories.iter().map(|repo| repo.previous_week_stars).sum();
+    let digest = WeeklyDigest {
+        week_start,
+        week_end,
+        stars,
+        previous_week_stars,
+        top_movers: movers.iter().map(|&index| repositories[index].clone()).collect(),
+        repositories,
+    };
+    Ok((digest, charts))
+}
+
+fn percent_change(stars: i64, previous: i64) -> Option<f64> {
+    (previous != 0).then(|| ((stars - previous) as f64 / previous as f64 * 1000.0).round() / 10.0)
+}
+
+pub fn digest_subject(digest: &WeeklyDigest) -> String {
+    format!("Star digest, week of {}", digest.week_start)
+}
+
+/// The digest as a standalone HTML page, `charts` holding the markup of each top mover's chart.
+pub fn render_digest_html(digest: &WeeklyDigest, charts: &[String]) -> String {
+    let mut html = String::new();
+    // Writing to a String can't fail.
+    let _ = writeln!(html, r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>{}</title></head>"#, escape(&digest_subject(digest)));
+    let _ = writeln!(html, r#"<body style="font-family: sans-serif; color: #24292f; max-width: 820px; margin: auto">"#);
+    let _ = writeln!(html, "<h1>Stars, {} to {}</h1>", digest.week_start, digest.week_end);
+    let _ = writeln!(html, "<p>{} stars this week, {} the week before ({}).</p>", digest.stars, digest.previous_week_stars, change_text(digest.stars - digest.previous_week_stars, percent_change(digest.stars, digest.previous_week_stars)));
+
+    if !digest.top_movers.is_empty() {
+        let _ = writeln!(html, "<h2>Top movers</h2>");
+        for (index, mover) in digest.top_movers.iter().enumerate() {
+            let _ = writeln!(html, "<h3>{}/{}: +{} stars</h3>", escape(&mover.owner), escape(&mover.name), mover.stars);
+            if let Some(chart) = charts.get(index) {
+                let _ = writeln!(html, "<div>{chart}</div>");
+            }
+        }
+    }
+
+    let _ = writeln!(html, "<h2>Repositories</h2>");
+    let _ = writeln!(html, r#"<table style="border-collapse: collapse" cellpadding="6">"#);
+    let _ = writeln!(html, r#"<tr style="text-align: left"><th>Repository</th><th>Total</th><th>This week</th><th>Week before</th><th>Change</th></tr>"#);
+    for repo in &digest.repositories {
+        let _ = writeln!(
+            html,
+            r#"<tr style="border-top: 1px solid #d0d7de"><td>{}/{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>"#,
+            escape(&repo.owner), escape(&repo.name), repo.total_stars, repo.stars, repo.previous_week_stars, change_text(repo.change, repo.percent),
+        );
+    }
+    let _ = writeln!(html, "</table></body></html>");
+    html
+}
+
+/// Plain-text alternative of [`render_digest_html`].
+pub fn render_digest_text(digest: &WeeklyDigest) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "Stars, {} to {}", digest.week_start, digest.week_end);
+    let _ = writeln!(text, "{} stars this week, {} the week before ({}).", digest.stars, digest.previous_week_stars, change_text(digest.stars - digest.previous_week_stars, percent_change(digest.stars, digest.previous_week_stars)));
+
+    if !digest.top_movers.is_empty() {
+        let _ = writeln!(text, "\nTop movers:");
+        for mover in &digest.top_movers {
+            let _ = writeln!(text, "- {}/{}: +{} stars", mover.owner, mover.name, mover.stars);
+        }
+    }
+
+    let _ = writeln!(text, "\nRepositories:");
+    for repo in &digest.repositories {
+        let _ = writeln!(text, "- {}/{}: {} stars, {} this week ({})", repo.owner, repo.name, repo.total_stars, repo.stars, change_text(repo.change, repo.percent));
+    }
+    text
+}
+
+fn change_text(change: i64, percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!("{change:+}, {percent:+}%"),
+        None => format!("{change:+}"),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GenerateWeeklyDigestError {
+    #[error("GetReportByPeriod: {source}")]
+    GetReportByPeriod {
+        #[from]
+        source: GetReportByPeriodError,
+    },
+    #[error("BuildWeeklyDigest: {source}")]
+    BuildWeeklyDigest {
+        #[from]
+        source: BuildWeeklyDigestError,
+    },
+    #[error("SerializeDigest: {source}")]
+    SerializeDigest {
+        source: serde_json::Error,
+    },
+    #[error("InsertReport: {source}")]
+    InsertReport {
+        #[from]
+        source: InsertReportError,
+    },
+    #[error("ReportVanished: {tenant_id} {week_start}")]
+    ReportVanished {
+        tenant_id: Uuid,
+        week_start: NaiveDate,
+    },
+}
+
+/// A stored digest, along with its data and charts to email it.
+pub struct GeneratedDigest {
+    pub report: Report,
+    pub digest: WeeklyDigest,
+    pub charts: Vec<DigestChart>,
+    /// Whether the report was stored by this call rather than an earlier one.
+    pub created: bool,
+}
+
+/// Builds and stores the tenant's digest for the week starting on `week_start`, unless it already was.
+/// `None` when the tenant tracks no repository, nothing is stored then.
+pub fn generate_weekly_digest(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    week_start: NaiveDate,
+) -> Result<Option<GeneratedDigest>, GenerateWeeklyDigestError> {
+    let (digest, charts) = build_weekly_digest(conn, tenant_id, week_start)
+        .map_err(|source| GenerateWeeklyDigestError::BuildWeeklyDigest { source })?;
+    if digest.repositories.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(report) = get_report_by_period(conn, tenant_id, ReportKind::WeeklyDigest, week_start)
+        .map_err(|source| GenerateWeeklyDigestError::GetReportByPeriod { source })?
+    {
+        return Ok(Some(GeneratedDigest { report, digest, charts, created: false }));
+    }
+
+    let theme = ChartTheme::light();
+    let svgs: Vec<String> = charts
+        .iter()
+        .map(|chart| render_star_history_svg(&format!("{} stars", chart.title), &chart.cumulative, &theme, &AxisOptions::default()))
+        .collect();
+    let html = render_digest_html(&digest, &svgs);
+    let content = serde_json::to_value(&digest)
+        .map_err(|source| GenerateWeeklyDigestError::SerializeDigest { source })?;
+
+    let new_report = NewReport {
+        id: Uuid::new_v4(),
+        tenant_id,
+        kind: ReportKind::WeeklyDigest.as_str(),
+        period_start: digest.week_start,
+        period_end: digest.week_end,
+        content,
+        html: &html,
+    };
+    let (report, created) = match insert_report(conn, &new_report)
+        .map_err(|source| GenerateWeeklyDigestError::InsertReport { source })?
+    {
+        Some(report) => (report, true),
+        // Stored meanwhile by another instance.
+        None => {
+            let report = get_report_by_period(conn, tenant_id, ReportKind::WeeklyDigest, week_start)
+                .map_err(|source| GenerateWeeklyDigestError::GetReportByPeriod { source })?
+                .ok_or(GenerateWeeklyDigestError::ReportVanished { tenant_id, week_start })?;
+            (report, false)
+        }
+    };
+
+    Ok(Some(GeneratedDigest { report, digest, charts, created }))
+}
+
+#[derive(Debug, Error)]
+pub enum SendDigestError {
+    #[error("EveryRecipientFailed: {source}")]
+    EveryRecipientFailed {
+        source: SendEmailError,
+    },
+    #[error("MarkReportSent: {source}")]
+    MarkReportSent {
+        #[from]
+        source: MarkReportSentError,
+    },
+}
+
+/// Emails the digest to every recipient and records the ones it reached.
+/// A failing recipient is logged, the others still get it.
+pub async fn send_digest(
+    conn: &mut PgConnection,
+    email: &EmailSender,
+    generated: &GeneratedDigest,
+    recipients: &[String],
+) -> Result<Vec<String>, SendDigestError> {
+    let images = chart_images(&generated.charts);
+    let charts: Vec<String> = images
+        .iter()
+        .zip(&generated.charts)
+        .map(|(image, chart)| format!(r#"<img src="cid:{}" width="600" alt="{} stars">"#, image.content_id, escape(&chart.title)))
+        .collect();
+    let html = render_digest_html(&generated.digest, &charts);
+    let text = render_digest_text(&generated.digest);
+    let subject = digest_subject(&generated.digest);
+
+    let mut sent = Vec::with_capacity(recipients.len());
+    let mut last_error = None;
+    for recipient in recipients {
+        match email.send_html(recipient, &subject, text.clone(), html.clone(), &images).await {
+            Ok(()) => sent.push(recipient.clone()),
+            Err(error) => {
+                warn!(report_id = %generated.report.id, %recipient, %error, "digest email failed");
+                last_error = Some(error);
+            }
+        }
+    }
+    if let (true, Some(source)) = (sent.is_empty(), last_error) {
+        return Err(SendDigestError::EveryRecipientFailed { source });
+    }
+
+    mark_report_sent(conn, generated.report.id, &sent)
+        .map_err(|source| SendDigestError::MarkReportSent { source })?;
+    Ok(sent)
+}
+
+/// The charts as PNG cards with the `charting` feature, as SVG otherwise.
+fn chart_images(charts: &[DigestChart]) -> Vec<InlineImage> {
+    let theme = ChartTheme::light();
+    charts
+        .iter()
+        .enumerate()
+        .map(|(index, chart)| InlineImage {
+            content_id: format!("chart-{index}"),
+            ..chart_image(chart, &theme)
+        })
+        .collect()
+}
+
+#[cfg(feature = "charting")]
+fn chart_image(chart: &DigestChart, theme: &ChartTheme) -> InlineImage {
+    use crate::social_card::{render_social_card_png, SocialCard};
+
+    let total_stars = chart.cumulative.last().map_or(0, |&(_, stars)| stars);
+    let month_ago = chart.cumulative.iter().rev().nth(30).map_or(0, |&(_, stars)| stars);
+    let card = SocialCard { title: &chart.title, total_stars, last_30_days: total_stars - month_ago };
+    match render_social_card_png(&card, &chart.cumulative, theme) {
+        Ok(png) => InlineImage { content_id: String::new(), content_type: "image/png", bytes: png },
+        Err(error) => {
+            warn!(chart = %chart.title, %error, "digest chart rendering failed, sending it as SVG");
+            svg_chart_image(chart, theme)
+        }
+    }
+}
+
+#[cfg(not(feature = "charting"))]
+fn chart_image(chart: &DigestChart, theme: &ChartTheme) -> InlineImage {
+    svg_chart_image(chart, theme)
+}
+
+fn svg_chart_image(chart: &DigestChart, theme: &ChartTheme) -> InlineImage {
+    let svg = render_star_history_svg(&format!("{} stars", chart.title), &chart.cumulative, theme, &AxisOptions::default());
+    InlineImage { content_id: String::new(), content_type: "image/svg+xml", bytes: svg.into_bytes() }
+}
+
+#[derive(Debug, Error)]
+pub enum GenerateDigestsError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        #[from]
+        source: DbConnRejection,
+    },
+    #[error("ListTenants: {source}")]
+    ListTenants {
+        #[from]
+        source: ListTenantsError,
+    },
+}
+
+/// Generates the digest of every tenant for the week starting on `week_start`, emailing the ones
+/// not sent yet to their recipients. A failing tenant is logged and skipped.
+pub async fn generate_digests(pool: &PgPool, notifier: &Notifier, week_start: NaiveDate) -> Result<(), GenerateDigestsError> {
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+        .await
+        .map_err(|source| GenerateDigestsError::Checkout { source })?;
+
+    let tenants = list_tenants(&mut conn)
+        .map_err(|source| GenerateDigestsError::ListTenants { source })?;
+
+    for tenant in tenants {
+        let generated = match generate_weekly_digest(&mut conn, tenant.id, week_start) {
+            Ok(Some(generated)) => generated,
+            Ok(None) => continue,
+            Err(error) => {
+                warn!(tenant = %tenant.name, %week_start, %error, "digest generation failed");
+                continue;
+            }
+        };
+        if generated.created {
+            info!(tenant = %tenant.name, %week_start, report_id = %generated.report.id, "digest generated");
+        }
+        if generated.report.sent_at.is_some() {
+            continue;
+        }
+
+        let Some(email) = notifier.email().filter(|_| !tenant.digest_recipients.is_empty()) else {
+            continue;
+        };
+        match send_digest(&mut conn, email, &generated, &tenant.digest_recipients).await {
+            Ok(sent) => info!(tenant = %tenant.name, report_id = %generated.report.id, recipients = sent.len(), "digest sent"),
+            Err(error) => warn!(tenant = %tenant.name, report_id = %generated.report.id, %error, "digest sending failed"),
+        }
+    }
+    Ok(())
+}
+
+/// Generates digests at every scheduled time until `stop` fires.
+pub async fn run_digests(pool: PgPool, notifier: Notifier, schedule: DigestSchedule, stop: StopSignal) {
+    loop {
+        let now = Utc::now().naive_utc();
+        let wait = (schedule.next_run(now) - now).to_std().unwrap_or(Duration::ZERO);
+        tokio::select! {
+            _ = stop.stopped() => return,
+            _ = tokio::time::sleep(wait) => {}
+        }
+
+        let week_start = previous_week_start(Utc::now().date_naive());
+        if let Err(error) = generate_digests(&pool, &notifier, week_start).await {
+            warn!(%week_start, %error, "digest generation failed");
+        }
+    }
+}
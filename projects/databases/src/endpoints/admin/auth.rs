@@ -0,0 +1,73 @@
+//! Operator access
+//!
+//! Admin endpoints act on the whole service rather than on a tenant, so API
+//! keys don't open them. They require the token set in `ADMIN_TOKEN`, sent
+//! as `X-Admin-Token: <token>` or `Authorization: Bearer <token>`, and are
+//! disabled while it is unset. Only the token's SHA-256 is kept in memory.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::endpoints::tenant::hash_api_key;
+
+/// Token of the operators, from `ADMIN_TOKEN`.
+#[derive(Clone)]
+pub struct AdminToken(String);
+
+impl std::fmt::Debug for AdminToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AdminToken(..)")
+    }
+}
+
+impl AdminToken {
+    pub fn new(token: &str) -> Self {
+        Self(hash_api_key(token))
+    }
+
+    /// Whether `token` is the admin token, compared by hash.
+    pub fn verify(&self, token: &str) -> bool {
+        hash_api_key(token) == self.0
+    }
+}
+
+/// Axum middleware guarding admin routes, added with `from_fn_with_state`.
+/// Answers `403 Forbidden` while no token is configured, `401 Unauthorized` to a missing or wrong one.
+pub async fn admin_middleware(
+    State(token): State<Option<AdminToken>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = token else {
+        return ApiError::new(StatusCode::FORBIDDEN, ErrorCode::NotConfigured, "Admin endpoints are disabled, set ADMIN_TOKEN").into_response();
+    };
+
+    match admin_token(request.headers()) {
+        None => ApiError::new(StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, "Admin token required").into_response(),
+        Some(sent) if !token.verify(sent) => ApiError::new(StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, "Wrong admin token").into_response(),
+        Some(_) => next.run(request).await,
+    }
+}
+
+/// Token of `X-Admin-Token`, or else of a bearer `Authorization`.
+fn admin_token(headers: &HeaderMap) -> Option<&str> {
+    let bearer = || {
+        headers
+            .get(header::AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .strip_prefix("Bearer ")
+    };
+
+    headers
+        .get("x-admin-token")
+        .and_then(|value| value.to_str().ok())
+        .or_else(bearer)
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
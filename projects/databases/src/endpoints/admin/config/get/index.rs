@@ -0,0 +1,73 @@
+use axum::extract::{Extension, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::runtime_config::{AdjustableSettings, RuntimeConfig, ServiceSettings};
+
+/// Settings `PATCH /admin/config` may change, as currently applied.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct AdjustableSettingsResponse {
+	/// Rows per statement of star imports.
+	pub star_insert_chunk_size: usize,
+	/// Smallest star import loaded with `COPY`.
+	pub star_copy_threshold: usize,
+	/// Finished sync jobs are deleted once this old.
+	pub sync_job_retention_hours: u64,
+	pub sync_job_cleanup_interval_minutes: u64,
+	/// Limits of sync jobs whose request sets none, unset when unlimited.
+	pub sync_max_pages: Option<u32>,
+	pub sync_max_duration_seconds: Option<u64>,
+	pub sync_max_stars: Option<u64>,
+}
+
+impl From<AdjustableSettings> for AdjustableSettingsResponse {
+	fn from(settings: AdjustableSettings) -> Self {
+		Self {
+			star_insert_chunk_size: settings.star_insert.chunk_size,
+			star_copy_threshold: settings.star_insert.copy_threshold,
+			sync_job_retention_hours: settings.sync_job_retention.max_age.as_secs() / 3600,
+			sync_job_cleanup_interval_minutes: settings.sync_job_retention.interval.as_secs() / 60,
+			sync_max_pages: settings.sync_limits.max_pages,
+			sync_max_duration_seconds: settings.sync_limits.max_duration_seconds,
+			sync_max_stars: settings.sync_limits.max_stars,
+		}
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ConfigResponse {
+	/// Read from the environment at startup, changed by a restart only.
+	pub service: ServiceSettings,
+	pub adjustable: AdjustableSettingsResponse,
+}
+
+impl From<&RuntimeConfig> for ConfigResponse {
+	fn from(config: &RuntimeConfig) -> Self {
+		Self {
+			service: config.service().clone(),
+			adjustable: config.adjustable().into(),
+		}
+	}
+}
+
+/// Axum handler: GET /admin/config
+///
+/// Effective settings of the running service, adjustments made through
+/// `PATCH /admin/config` included.
+#[utoipa::path(
+    get,
+    path = "/admin/config",
+    operation_id = "get_admin_config",
+    tag = "admin",
+    summary = "Show the effective configuration",
+    responses(
+        (status = 200, description = "Current settings", body = ConfigResponse),
+        (status = 401, description = "Missing or wrong admin token"),
+        (status = 403, description = "ADMIN_TOKEN not set"),
+    ),
+)]
+pub async fn handler(
+    Extension(config): Extension<RuntimeConfig>,
+) -> Json<ConfigResponse> {
+    Json(ConfigResponse::from(&config))
+}
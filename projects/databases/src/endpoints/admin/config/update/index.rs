@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Extension, Json},
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::star::queries::MAX_INSERT_CHUNK_SIZE;
+use crate::endpoints::admin::config::get::index::{AdjustableSettingsResponse, ConfigResponse};
+use crate::runtime_config::RuntimeConfig;
+use crate::validation::ValidationErrors;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+		}
+	}
+}
+
+/// JSON payload expected by the endpoint, settings left out keep their value.
+/// Settings outside this list need a restart, naming one rejects the whole patch.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigPatch {
+	/// Between 1 and 10000.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub star_insert_chunk_size: Option<usize>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub star_copy_threshold: Option<usize>,
+	/// At least 1.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sync_job_retention_hours: Option<u64>,
+	/// At least 1.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sync_job_cleanup_interval_minutes: Option<u64>,
+	/// 0 lifts the limit.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sync_max_pages: Option<u32>,
+	/// 0 lifts the limit.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sync_max_duration_seconds: Option<u64>,
+	/// 0 lifts the limit.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sync_max_stars: Option<u64>,
+}
+
+/// Axum handler: PATCH /admin/config
+///
+/// Adjusts settings of the running service without a restart. Imports and sync
+/// jobs started afterwards and the next sync job cleanup use the new values,
+/// the cleanup being rescheduled right away. Adjustments are lost on restart, the
+/// environment applying again.
+#[utoipa::path(
+    patch,
+    path = "/admin/config",
+    operation_id = "update_admin_config",
+    tag = "admin",
+    summary = "Adjust settings at runtime",
+    request_body = ConfigPatch,
+    responses(
+        (status = 200, description = "Settings after the change", body = ConfigResponse),
+        (status = 401, description = "Missing or wrong admin token"),
+        (status = 403, description = "ADMIN_TOKEN not set"),
+        (status = 422, description = "Value out of range, or setting not adjustable at runtime"),
+    ),
+)]
+pub async fn handler(
+    Extension(config): Extension<RuntimeConfig>,
+    Json(patch): Json<ConfigPatch>,
+) -> Result<Json<ConfigResponse>, HandlerError> {
+    let mut errors = ValidationErrors::new();
+    if let Some(chunk_size) = patch.star_insert_chunk_size {
+        errors.check("star_insert_chunk_size", match chunk_size {
+            1..=MAX_INSERT_CHUNK_SIZE => Ok(()),
+            _ => Err(format!("must be between 1 and {MAX_INSERT_CHUNK_SIZE}")),
+        });
+    }
+    if patch.sync_job_retention_hours == Some(0) {
+        errors.check("sync_job_retention_hours", Err("must be at least 1".into()));
+    }
+    if patch.sync_job_cleanup_interval_minutes == Some(0) {
+        errors.check("sync_job_cleanup_interval_minutes", Err("must be at least 1".into()));
+    }
+    errors.into_result()?;
+
+    let adjusted = config.update(|settings| {
+        if let Some(chunk_size) = patch.star_insert_chunk_size {
+            settings.star_insert.chunk_size = chunk_size;
+        }
+        if let Some(threshold) = patch.star_copy_threshold {
+            settings.star_insert.copy_threshold = threshold;
+        }
+        if let Some(hours) = patch.sync_job_retention_hours {
+            settings.sync_job_retention.max_age = Duration::from_secs(hours.saturating_mul(3600));
+        }
+        if let Some(minutes) = patch.sync_job_cleanup_interval_minutes {
+            settings.sync_job_retention.interval = Duration::from_secs(minutes.saturating_mul(60));
+        }
+        if let Some(pages) = patch.sync_max_pages {
+            settings.sync_limits.max_pages = Some(pages).filter(|pages| *pages > 0);
+        }
+        if let Some(seconds) = patch.sync_max_duration_seconds {
+            settings.sync_limits.max_duration_seconds = Some(seconds).filter(|seconds| *seconds > 0);
+        }
+        if let Some(stars) = patch.sync_max_stars {
+            settings.sync_limits.max_stars = Some(stars).filter(|stars| *stars > 0);
+        }
+    });
+    info!(?patch, "runtime configuration adjusted");
+
+    Ok(Json(ConfigResponse {
+        service: config.service().clone(),
+        adjustable: AdjustableSettingsResponse::from(adjusted),
+    }))
+}
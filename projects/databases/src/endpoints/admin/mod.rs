@@ -0,0 +1,2 @@
+pub mod auth;
+pub mod config;
\ No newline at end of file
@@ -0,0 +1,184 @@
+//! Error responses
+//!
+//! Every error is answered with the same JSON body, an [`ApiError`]: a stable
+//! machine-readable `code` clients branch on, a human-readable `message` that
+//! may change between releases, optional structured `details`, and the
+//! `request_id` of the `X-Request-Id` header, filled in by
+//! [`crate::endpoints::envelope::envelope_middleware`].
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use interfaces_github_stargazers::forge::Forge;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+/// What went wrong, stable across releases unlike messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A parameter, path segment or body field is missing, malformed or out of range.
+    InvalidRequest,
+    /// Fields breaking naming rules, length limits or allow-lists, listed in `details.fields`.
+    ValidationFailed,
+    /// Missing or unknown API key, or invalid webhook signature.
+    Unauthorized,
+    Forbidden,
+    /// No such route.
+    NotFound,
+    MethodNotAllowed,
+    /// The tenant doesn't track the repository.
+    RepositoryNotFound,
+    /// The repository is archived, unarchive it to chart it again.
+    RepositoryArchived,
+    /// The tenant tracks a repository of that owner and name on another forge.
+    ForgeMismatch,
+    SyncJobNotFound,
+    /// No sync run of that ID for the repository.
+    SyncRunNotFound,
+    SyncBatchNotFound,
+    AlertRuleNotFound,
+    TagNotFound,
+    ReportNotFound,
+    /// Only failed, interrupted or partially completed sync jobs can be resumed.
+    SyncJobNotResumable,
+    /// Another worker or instance is syncing the repository, retry once it is done.
+    SyncInProgress,
+    UnsupportedMediaType,
+    PayloadTooLarge,
+    /// The handler ran past the request deadline.
+    Timeout,
+    /// Too many requests in flight, retry after `Retry-After`.
+    Overloaded,
+    /// The tenant made too many requests to a rate-limited route, retry after `Retry-After`.
+    RateLimited,
+    /// The repository doesn't have enough star history to answer.
+    NotEnoughHistory,
+    /// GitHub, or the repository's forge, doesn't know the repository or owner.
+    #[serde(rename = "github_not_found")]
+    GitHubNotFound,
+    /// GitHub, or the repository's forge, refused access to the repository or owner.
+    #[serde(rename = "github_forbidden")]
+    GitHubForbidden,
+    /// The forge's rate limit is exhausted, for every configured token on GitHub.
+    #[serde(rename = "github_rate_limited")]
+    GitHubRateLimited,
+    /// GitHub, or the repository's forge, failed or answered something unexpected.
+    #[serde(rename = "github_error")]
+    GitHubError,
+    /// The service is shutting down, or the sync was interrupted.
+    Unavailable,
+    /// No database connection was free in time, retry after `Retry-After`.
+    DatabaseUnavailable,
+    /// A setting the request needs isn't configured, e.g. GitHub credentials.
+    NotConfigured,
+    /// Database or other internal failure.
+    Internal,
+}
+
+impl ErrorCode {
+    /// Code of an error answered with nothing more specific than its status.
+    pub fn for_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => ErrorCode::InvalidRequest,
+            StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+            StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+            StatusCode::REQUEST_TIMEOUT => ErrorCode::Timeout,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+            StatusCode::BAD_GATEWAY => ErrorCode::GitHubError,
+            StatusCode::SERVICE_UNAVAILABLE => ErrorCode::Unavailable,
+            status if status.is_client_error() => ErrorCode::InvalidRequest,
+            _ => ErrorCode::Internal,
+        }
+    }
+
+    /// Code of a failed sync or GitHub call, given the status it maps to.
+    pub fn for_github_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::NOT_FOUND => ErrorCode::GitHubNotFound,
+            StatusCode::FORBIDDEN => ErrorCode::GitHubForbidden,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::GitHubRateLimited,
+            StatusCode::CONFLICT => ErrorCode::ForgeMismatch,
+            status => ErrorCode::for_status(status),
+        }
+    }
+}
+
+/// Body of every error response.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: StatusCode,
+    pub code: ErrorCode,
+    pub message: String,
+    /// Structured context, e.g. the rejected value and its bounds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+    /// Same as the `X-Request-Id` response header, to quote when reporting an issue.
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into(), details: None, request_id: None }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// `400 Bad Request` with [`ErrorCode::InvalidRequest`].
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidRequest, message)
+    }
+
+    /// `404 Not Found` for a repository the tenant doesn't track.
+    pub fn repository_not_found(owner: &str, name: &str) -> Self {
+        Self::new(StatusCode::NOT_FOUND, ErrorCode::RepositoryNotFound, format!("Repository {owner}/{name} not found in database"))
+            .with_details(serde_json::json!({ "owner": owner, "name": name }))
+    }
+
+    /// `409 Conflict` for an archived repository, hidden from charts.
+    pub fn repository_archived(owner: &str, name: &str) -> Self {
+        Self::new(StatusCode::CONFLICT, ErrorCode::RepositoryArchived, format!("Repository {owner}/{name} is archived"))
+            .with_details(serde_json::json!({ "owner": owner, "name": name }))
+    }
+
+    /// `500 Internal Server Error` when no GitHub credentials are configured.
+    pub fn missing_github_credentials() -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::NotConfigured, "No GitHub credentials configured, set GITHUB_TOKEN, GITHUB_TOKENS or GITHUB_APP_ID")
+    }
+
+    /// `500 Internal Server Error` when `forge` can't be called, see [`crate::forges::ForgeClients::is_available`].
+    pub fn forge_not_configured(forge: Forge) -> Self {
+        match forge {
+            Forge::GitHub => Self::missing_github_credentials(),
+            Forge::GitLab | Forge::Gitea => Self::new(StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::NotConfigured, format!("No {forge} instance configured, set {}_API_URL", forge.as_str().to_ascii_uppercase())),
+        }
+    }
+
+    /// Failed sync or GitHub call, keeping the status its error maps to.
+    pub fn github(status: StatusCode, error: impl std::fmt::Display) -> Self {
+        Self::new(status, ErrorCode::for_github_status(status), error.to_string())
+    }
+
+    /// `500 Internal Server Error` with [`ErrorCode::Internal`].
+    pub fn internal(error: impl std::fmt::Display) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
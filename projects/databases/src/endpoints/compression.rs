@@ -0,0 +1,41 @@
+//! Compressed bodies
+//!
+//! Daily series, exports and SVG charts of large repositories run to hundreds
+//! of kilobytes of text. Responses are compressed with Brotli or gzip,
+//! whichever the client's `Accept-Encoding` prefers, except small ones, images
+//! other than SVG, which are compressed already, and NDJSON: sync progress is
+//! streamed as NDJSON and its lines must reach the client as they are written,
+//! not once a compressed block fills up.
+//!
+//! Star imports may be sent compressed too, with `Content-Encoding: gzip` or
+//! `br`. The body limit applies to the decompressed body.
+
+use tower_http::compression::{
+    predicate::{NotForContentType, SizeAbove},
+    CompressionLayer, Predicate,
+};
+use tower_http::decompression::RequestDecompressionLayer;
+
+/// Responses smaller than that are sent as they are, compressing them saving next to nothing.
+pub const MIN_COMPRESSED_BYTES: u16 = 1024;
+
+/// Compression of responses, added around every route.
+pub fn compression_layer() -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(MIN_COMPRESSED_BYTES)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+        .and(NotForContentType::const_new("application/x-ndjson"));
+
+    CompressionLayer::new()
+        .br(true)
+        .gzip(true)
+        .compress_when(predicate)
+}
+
+/// Decompression of request bodies, for routes accepting large uploads.
+pub fn request_decompression_layer() -> RequestDecompressionLayer {
+    RequestDecompressionLayer::new()
+        .br(true)
+        .gzip(true)
+}
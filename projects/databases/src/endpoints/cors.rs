@@ -0,0 +1,58 @@
+//! Cross-origin requests
+//!
+//! Lets browser dashboards served from another origin call the API. Preflight
+//! `OPTIONS` requests, sent before a JSON `POST` such as the compare or
+//! per-day chart endpoints, are answered by the layer without reaching a
+//! handler.
+
+use std::time::Duration;
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::endpoints::envelope::{ENVELOPE_HEADER, REQUEST_ID_HEADER};
+
+/// How long browsers may reuse a preflight answer.
+const PREFLIGHT_MAX_AGE: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    /// Any origin, `*`.
+    Any,
+    /// Exact origins such as `https://dashboard.example.com`.
+    List(Vec<HeaderValue>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsConfig {
+    pub allowed_origins: AllowedOrigins,
+    pub allowed_methods: Vec<Method>,
+    /// Request headers scripts may set, beyond the ones browsers always allow.
+    pub allowed_headers: Vec<HeaderName>,
+}
+
+impl CorsConfig {
+    /// Methods and headers used by the API, for `allowed_origins`.
+    pub fn new(allowed_origins: AllowedOrigins) -> Self {
+        Self {
+            allowed_origins,
+            allowed_methods: vec![Method::GET, Method::POST, Method::PUT, Method::DELETE],
+            allowed_headers: vec![header::CONTENT_TYPE, header::IF_NONE_MATCH, header::AUTHORIZATION, HeaderName::from_static("x-api-key"), REQUEST_ID_HEADER, ENVELOPE_HEADER],
+        }
+    }
+
+    /// Layer answering preflights and adding the CORS headers, outermost so limits don't turn preflights away.
+    /// `ETag` and `Retry-After` are readable by scripts, for conditional reads and retries.
+    pub fn layer(&self) -> CorsLayer {
+        let origins = match &self.allowed_origins {
+            AllowedOrigins::Any => AllowOrigin::any(),
+            AllowedOrigins::List(origins) => AllowOrigin::list(origins.iter().cloned()),
+        };
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(self.allowed_methods.clone())
+            .allow_headers(self.allowed_headers.clone())
+            .expose_headers([header::ETAG, header::RETRY_AFTER, REQUEST_ID_HEADER])
+            .max_age(PREFLIGHT_MAX_AGE)
+    }
+}
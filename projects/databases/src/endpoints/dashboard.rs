@@ -0,0 +1,65 @@
+//! Bundled dashboard
+//!
+//! A single page, in `dashboard/`, listing the tenant's repositories,
+//! syncing them and charting their stars by calling the API like any other
+//! client. Its files are embedded in the binary at build time and served
+//! under `/ui`, unknown paths getting the page itself so that it can be
+//! reloaded anywhere. Builds without the `dashboard` feature don't serve it.
+
+use axum::{
+    extract::Path,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+};
+use rust_embed::RustEmbed;
+
+#[derive(RustEmbed)]
+#[folder = "dashboard/"]
+struct Assets;
+
+const INDEX: &str = "index.html";
+
+/// Axum handler: GET /ui
+pub async fn redirect_handler() -> Redirect {
+    Redirect::permanent("/ui/")
+}
+
+/// Axum handler: GET /ui/
+pub async fn index_handler(headers: HeaderMap) -> Response {
+    serve(INDEX, &headers)
+}
+
+/// Axum handler: GET /ui/{*path}
+pub async fn asset_handler(Path(path): Path<String>, headers: HeaderMap) -> Response {
+    if Assets::get(&path).is_some() {
+        serve(&path, &headers)
+    } else {
+        serve(INDEX, &headers)
+    }
+}
+
+/// The embedded file, or `304 Not Modified` when the client's copy is current. Browsers
+/// revalidate every time, so a new build is picked up on the next load.
+fn serve(path: &str, headers: &HeaderMap) -> Response {
+    let Some(file) = Assets::get(path) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let etag = format!("\"{}\"", file.metadata.sha256_hash().iter().map(|byte| format!("{byte:02x}")).collect::<String>());
+    let cached = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    let mut response = if cached {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        ([(header::CONTENT_TYPE, file.metadata.mimetype().to_string())], file.data).into_response()
+    };
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    if let Ok(etag) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, etag);
+    }
+    response
+}
@@ -0,0 +1,117 @@
+//! Pooled connections as handler arguments
+//!
+//! Handlers take a [`DbConn`], checked out from the primary, or a
+//! [`ReadConn`], checked out from the [`ReadPool`], instead of a pool. The
+//! checkout runs on a blocking thread and gives up after [`CHECKOUT_TIMEOUT`],
+//! answering `503` with [`ErrorCode::DatabaseUnavailable`] and a
+//! `Retry-After`: a busy pool fails fast rather than holding the request
+//! until its deadline. Middleware, handlers checking out once their request is
+//! vetted and async background tasks go through [`DbConn::checkout`] and
+//! [`ReadConn::checkout`] the same way. Star compaction and the purge of old
+//! sync jobs are blocking from start to end, and run with their checkout on
+//! a blocking thread.
+
+use std::time::Duration;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use diesel::{
+    r2d2::{ConnectionManager, PooledConnection},
+    PgConnection,
+};
+use thiserror::Error;
+use tokio::task::JoinError;
+use tracing::warn;
+
+use crate::db::{pool::ReadPool, PgPool};
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+
+/// Longest wait for a free connection.
+pub const CHECKOUT_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub type PooledPgConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Connection to the primary, for handlers that write. Given back to the pool when dropped.
+pub struct DbConn(pub PooledPgConnection);
+
+/// Connection for handlers that only read, from the replica if any, see [`ReadPool`].
+pub struct ReadConn(pub PooledPgConnection);
+
+#[derive(Debug, Error)]
+pub enum DbConnRejection {
+    #[error("MissingPool: {pool}")]
+    MissingPool {
+        pool: &'static str,
+    },
+    #[error("GetConnectionFromPool: {source}")]
+    GetConnectionFromPool {
+        source: r2d2::Error,
+    },
+    #[error("CheckoutTask: {source}")]
+    CheckoutTask {
+        source: JoinError,
+    },
+}
+
+impl IntoResponse for DbConnRejection {
+    fn into_response(self) -> Response {
+        match self {
+            DbConnRejection::GetConnectionFromPool{ source } => {
+                warn!(error = %source, "no database connection available");
+                (
+                    [(header::RETRY_AFTER, "1")],
+                    ApiError::new(StatusCode::SERVICE_UNAVAILABLE, ErrorCode::DatabaseUnavailable, format!("No database connection available within {}s, retry later", CHECKOUT_TIMEOUT.as_secs())),
+                )
+                    .into_response()
+            }
+            other => ApiError::internal(other).into_response(),
+        }
+    }
+}
+
+impl DbConn {
+    /// Checks out a connection of `pool` as the extractor does.
+    pub async fn checkout(pool: PgPool) -> Result<Self, DbConnRejection> {
+        checkout(move || pool.get_timeout(CHECKOUT_TIMEOUT)).await.map(DbConn)
+    }
+}
+
+impl ReadConn {
+    /// Checks out a connection of `pool` as the extractor does.
+    pub async fn checkout(pool: ReadPool) -> Result<Self, DbConnRejection> {
+        checkout(move || pool.get_timeout(CHECKOUT_TIMEOUT)).await.map(ReadConn)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for DbConn {
+    type Rejection = DbConnRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let pool = parts.extensions.get::<PgPool>().cloned()
+            .ok_or(DbConnRejection::MissingPool{ pool: "PgPool" })?;
+        DbConn::checkout(pool).await
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ReadConn {
+    type Rejection = DbConnRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let pool = parts.extensions.get::<ReadPool>().cloned()
+            .ok_or(DbConnRejection::MissingPool{ pool: "ReadPool" })?;
+        ReadConn::checkout(pool).await
+    }
+}
+
+/// Runs the blocking checkout off the async executor.
+async fn checkout(
+    get: impl FnOnce() -> Result<PooledPgConnection, r2d2::Error> + Send + 'static,
+) -> Result<PooledPgConnection, DbConnRejection> {
+    tokio::task::spawn_blocking(get)
+        .await
+        .map_err(|source| DbConnRejection::CheckoutTask{ source })?
+        .map_err(|source| DbConnRejection::GetConnectionFromPool{ source })
+}
@@ -0,0 +1,163 @@
+//! Request IDs and response envelopes
+//!
+//! Every response carries an `X-Request-Id` header, the caller's own when it
+//! sent a usable one, a new UUID otherwise. Errors not answered as an
+//! [`ApiError`] by their handler, e.g. malformed JSON bodies rejected by axum
+//! or unknown routes, are turned into one with a code derived from their
+//! status, and every error body gets the request ID.
+//!
+//! Clients sending `X-Response-Envelope: true` get every JSON answer wrapped:
+//! `{"data": …, "request_id": …}` on success and `{"error": …}` on failure,
+//! so one parser handles both. Other answers (images, CSV, WebSockets) are
+//! left as they are.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use tracing::warn;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+pub const ENVELOPE_HEADER: HeaderName = HeaderName::from_static("x-response-envelope");
+
+/// Longest caller-provided request ID kept, longer ones are replaced.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+/// Error bodies past this size aren't read back, their text is dropped from the message.
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Request ID, inserted as an extension by [`envelope_middleware`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Successful answer with `X-Response-Envelope: true`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SuccessEnvelope<T> {
+    pub data: T,
+    pub request_id: String,
+}
+
+/// Failed answer with `X-Response-Envelope: true`.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ErrorEnvelope {
+    pub error: ApiError,
+}
+
+/// Axum middleware giving every request an ID and shaping error bodies, and success ones
+/// when asked for, as described in the module documentation. Added with `from_fn`
+/// outside every other layer, so their errors are covered too.
+pub async fn envelope_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request_id(request.headers());
+    let enveloped = wants_envelope(request.headers());
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let response = next.run(request).await;
+    let status = response.status();
+    let mut response = if status.is_client_error() || status.is_server_error() {
+        error_response(response, &request_id, enveloped).await
+    } else if enveloped && is_json(response.headers()) {
+        success_response(response, &request_id).await
+    } else {
+        response
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    if enveloped {
+        response.headers_mut().append(header::VARY, HeaderValue::from_static("x-response-envelope"));
+    }
+    response
+}
+
+/// The caller's `X-Request-Id` when short and printable, a new UUID otherwise.
+fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LENGTH && id.chars().all(|c| c.is_ascii_graphic()))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+fn wants_envelope(headers: &HeaderMap) -> bool {
+    headers
+        .get(ENVELOPE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("true") || value.trim() == "1")
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// The error as an [`ApiError`] with the request ID, its status and other headers kept.
+async fn error_response(response: Response, request_id: &str, enveloped: bool) -> Response {
+    let (parts, body) = response.into_parts();
+    let json = is_json(&parts.headers);
+    let bytes = to_bytes(body, MAX_ERROR_BODY_BYTES).await.unwrap_or_default();
+
+    let mut error = json
+        .then(|| serde_json::from_slice::<ApiError>(&bytes).ok())
+        .flatten()
+        .unwrap_or_else(|| {
+            let text = String::from_utf8_lossy(&bytes).trim().to_string();
+            let message = if text.is_empty() {
+                parts.status.canonical_reason().unwrap_or("Error").to_string()
+            } else {
+                text
+            };
+            ApiError::new(parts.status, ErrorCode::for_status(parts.status), message)
+        });
+    error.status = parts.status;
+    error.request_id = Some(request_id.to_string());
+
+    let body = if enveloped {
+        serde_json::to_vec(&ErrorEnvelope { error })
+    } else {
+        serde_json::to_vec(&error)
+    };
+    json_response(parts, body)
+}
+
+/// The JSON answer wrapped in a [`SuccessEnvelope`].
+async fn success_response(response: Response, request_id: &str) -> Response {
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            warn!(%error, "could not read response body to envelope it");
+            return ApiError::internal(format!("Could not read response body: {error}")).into_response();
+        }
+    };
+
+    let body = match serde_json::from_slice::<&RawValue>(&bytes) {
+        Ok(data) => serde_json::to_vec(&SuccessEnvelope { data, request_id: request_id.to_string() }),
+        // Not actually JSON, handed back as is rather than wrapped in an invalid document.
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+    json_response(parts, body)
+}
+
+fn json_response(mut parts: axum::http::response::Parts, body: serde_json::Result<Vec<u8>>) -> Response {
+    match body {
+        Ok(body) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            Response::from_parts(parts, Body::from(body))
+        }
+        Err(error) => ApiError::internal(format!("Could not serialize response: {error}")).into_response(),
+    }
+}
@@ -0,0 +1,137 @@
+use axum::{
+    extract::{Extension, Json},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    alert_rule::{
+	        models::{AlertKind, AlertTarget, NewAlertRule},
+	        queries::{insert_alert_rule, InsertAlertRuleError},
+	    },
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+use crate::endpoints::github::alerts::list::index::AlertRuleResponse;
+use crate::notifier::Notifier;
+use crate::validation::{validate_email, validate_repository, ValidationErrors};
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+	#[error("InvalidThreshold: {threshold}")]
+	InvalidThreshold {
+		threshold: i32,
+	},
+	#[error("EmailNotConfigured")]
+	EmailNotConfigured,
+	#[error("InsertAlertRule: {source}")]
+	InsertAlertRule {
+		#[from]
+		source: InsertAlertRuleError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			HandlerError::InvalidThreshold{ threshold } => ApiError::invalid_request(format!("threshold must be positive, got {threshold}")).with_details(json!({ "threshold": threshold })).into_response(),
+			HandlerError::EmailNotConfigured => ApiError::new(StatusCode::BAD_REQUEST, ErrorCode::NotConfigured, "Email alerts need SMTP_URL and SMTP_FROM to be set").into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// JSON payload expected by the endpoint.
+#[derive(Deserialize, ToSchema)]
+pub struct CreateAlertRule {
+	owner: String,
+	name: String,
+	kind: AlertKind,
+	threshold: i32,
+	target: AlertTarget,
+	/// Webhook URL or email address, depending on `target`. Webhook URLs must use an
+	/// allowed scheme, `https` by default, and point to an allowed host, by default
+	/// any public one.
+	destination: String,
+}
+
+/// Axum handler: POST /github/alerts
+#[utoipa::path(
+    post,
+    path = "/github/alerts",
+    operation_id = "create_alert_rule",
+    tag = "alerts",
+    summary = "Create an alert rule",
+    request_body = CreateAlertRule,
+    responses(
+        (status = 201, description = "Rule created", body = AlertRuleResponse),
+        (status = 400, description = "Invalid threshold or email alerts not configured"),
+        (status = 404, description = "Repository not in database"),
+        (status = 422, description = "Invalid owner, name, webhook URL or email address"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(notifier): Extension<Notifier>,
+    Json(input): Json<CreateAlertRule>,
+) -> Result<(StatusCode, Json<AlertRuleResponse>), HandlerError> {
+    let mut errors = ValidationErrors::new();
+    validate_repository(&mut errors, "", &input.owner, &input.name);
+    errors.check("destination", match input.target {
+        AlertTarget::Webhook => notifier.webhook_policy().validate(&input.destination),
+        AlertTarget::Email => validate_email(&input.destination),
+    });
+    errors.into_result()?;
+
+    if input.threshold <= 0 {
+        return Err(HandlerError::InvalidThreshold{ threshold: input.threshold });
+    }
+    if input.target == AlertTarget::Email && !notifier.has_email() {
+        return Err(HandlerError::EmailNotConfigured);
+    }
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &input.owner, &input.name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner: input.owner.clone(), name: input.name.clone() })?;
+
+    let new_rule = NewAlertRule {
+        id: Uuid::new_v4(),
+        repository_id: repo.id,
+        kind: input.kind.as_str(),
+        threshold: input.threshold,
+        target: input.target.as_str(),
+        destination: &input.destination,
+    };
+
+    let rule = insert_alert_rule(&mut conn, &new_rule)
+		.map_err(|source| HandlerError::InsertAlertRule{ source })?;
+
+    Ok((StatusCode::CREATED, Json(AlertRuleResponse::new(rule, &repo))))
+}
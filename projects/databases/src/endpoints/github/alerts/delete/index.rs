@@ -0,0 +1,68 @@
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    alert_rule::queries::{delete_alert_rule, DeleteAlertRuleError},
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("DeleteAlertRule: {source}")]
+	DeleteAlertRule {
+		#[from]
+		source: DeleteAlertRuleError,
+	},
+	#[error("AlertRuleNotFound: {id}")]
+	AlertRuleNotFound {
+		id: Uuid,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::AlertRuleNotFound{ id } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::AlertRuleNotFound, format!("Alert rule {id} not found")).with_details(json!({ "id": id })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: DELETE /github/alerts/{id}
+#[utoipa::path(
+    delete,
+    path = "/github/alerts/{id}",
+    operation_id = "delete_alert_rule",
+    tag = "alerts",
+    summary = "Delete an alert rule",
+    params(
+        ("id" = Uuid, Path, description = "Alert rule id"),
+    ),
+    responses(
+        (status = 204, description = "Rule deleted"),
+        (status = 404, description = "Rule not found"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, HandlerError> {
+    let deleted = delete_alert_rule(&mut conn, tenant_id, id)
+		.map_err(|source| HandlerError::DeleteAlertRule{ source })?;
+
+    if !deleted {
+        return Err(HandlerError::AlertRuleNotFound{ id });
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Extension, Json},
+    response::IntoResponse,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    alert_rule::{
+	        models::AlertRule,
+	        queries::{list_alert_rules, ListAlertRulesError},
+	    },
+	    repository::models::Repository,
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("ListAlertRules: {source}")]
+	ListAlertRules {
+		#[from]
+		source: ListAlertRulesError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		ApiError::internal(self).into_response()
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct AlertRuleResponse {
+	pub id: Uuid,
+	pub owner: String,
+	pub name: String,
+	pub kind: String,
+	pub threshold: i32,
+	pub target: String,
+	pub destination: String,
+	pub last_triggered_on: Option<NaiveDate>,
+	pub created_at: NaiveDateTime,
+}
+
+impl AlertRuleResponse {
+	pub fn new(rule: AlertRule, repo: &Repository) -> Self {
+		Self {
+			id: rule.id,
+			owner: repo.owner.clone(),
+			name: repo.name.clone(),
+			kind: rule.kind,
+			threshold: rule.threshold,
+			target: rule.target,
+			destination: rule.destination,
+			last_triggered_on: rule.last_triggered_on,
+			created_at: rule.created_at,
+		}
+	}
+}
+
+/// Axum handler: GET /github/alerts
+#[utoipa::path(
+    get,
+    path = "/github/alerts",
+    operation_id = "list_alert_rules",
+    tag = "alerts",
+    summary = "Alert rules",
+    responses(
+        (status = 200, description = "Every alert rule", body = Vec<AlertRuleResponse>),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+) -> Result<Json<Vec<AlertRuleResponse>>, HandlerError> {
+    let rules = list_alert_rules(&mut conn, tenant_id)
+		.map_err(|source| HandlerError::ListAlertRules{ source })?;
+
+    Ok(Json(rules.into_iter().map(|(rule, repo)| AlertRuleResponse::new(rule, &repo)).collect()))
+}
@@ -0,0 +1,150 @@
+//! Conditional requests for read endpoints
+//!
+//! Star data only changes when a sync runs, so responses of `{owner}/{name}`
+//! routes are tagged with the repository's star count and latest `fetched_at`,
+//! and the day they are answered on, since series, forecasts and charts run up
+//! to today. A matching `If-None-Match` is answered with `304 Not Modified`
+//! without running the handler. The tag doesn't name the tenant, responses
+//! vary on the headers carrying its API key instead.
+//!
+//! Only `GET` routes are tagged, `304` being no answer to a `POST`. Daily
+//! counts are read with `GET /github/repo_stars/read_per_day/{owner}/{name}`
+//! for that. `POST /github/repo_stars/compare/chart` isn't: its request names
+//! several repositories and a chart's every option, more than a URL carries,
+//! and its renders are kept by the [`ChartCache`](crate::chart_cache::ChartCache)
+//! until one of them syncs.
+
+use axum::{
+    extract::{Extension, Path, Request},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use thiserror::Error;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_star_version, GetStarVersionError},
+	    pool::ReadPool,
+	};
+use crate::endpoints::db_conn::{DbConnRejection, ReadConn};
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+enum StarVersionError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        source: DbConnRejection,
+    },
+    #[error("GetRepositoryByName: {source}")]
+    GetRepositoryByName {
+        source: GetRepositoryByNameError,
+    },
+    #[error("GetStarVersion: {source}")]
+    GetStarVersion {
+        source: GetStarVersionError,
+    },
+}
+
+/// Axum middleware for routes with `{owner}/{name}` path parameters, added with `route_layer`
+/// inside the tenant middleware.
+pub async fn etag_middleware(
+    Extension(pool): Extension<ReadPool>,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let version = match star_version(&pool, tenant_id, &owner, &name).await {
+        Ok(Some(version)) => version,
+        // Unknown repositories and failures are left to the handler to report,
+        // a busy pool answered here rather than waited for twice.
+        Ok(None) => return next.run(request).await,
+        Err(StarVersionError::Checkout{ source }) => return source.into_response(),
+        Err(error) => {
+            warn!(%owner, %name, %error, "could not compute ETag");
+            return next.run(request).await;
+        }
+    };
+
+    let (last_fetched_at, count) = version;
+    let etag = etag(count, last_fetched_at, Utc::now().date_naive());
+
+    if if_none_match(request.headers(), &etag) {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        set_validators(response.headers_mut(), &etag, last_fetched_at);
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    if response.status().is_success() {
+        set_validators(response.headers_mut(), &etag, last_fetched_at);
+    }
+    response
+}
+
+async fn star_version(
+    pool: &ReadPool,
+    tenant_id: Uuid,
+    owner: &str,
+    name: &str,
+) -> Result<Option<(Option<NaiveDateTime>, i64)>, StarVersionError> {
+    let ReadConn(mut conn) = ReadConn::checkout(pool.clone())
+        .await
+        .map_err(|source| StarVersionError::Checkout { source })?;
+
+    let Some(repo) = get_repository_by_name(&mut conn, tenant_id, owner, name)
+        .await
+        .map_err(|source| StarVersionError::GetRepositoryByName { source })?
+    else {
+        return Ok(None);
+    };
+
+    get_star_version(&mut conn, repo.id)
+        .map(Some)
+        .map_err(|source| StarVersionError::GetStarVersion { source })
+}
+
+/// Weak tag of the stored stars as answered on `today`.
+fn etag(count: i64, last_fetched_at: Option<NaiveDateTime>, today: NaiveDate) -> String {
+    format!(
+        "W/\"{count}-{}-{}\"",
+        last_fetched_at.map_or(0, |fetched_at| fetched_at.and_utc().timestamp_micros()),
+        today.format("%Y%m%d"),
+    )
+}
+
+/// Whether any entity tag of the `If-None-Match` header matches `etag`.
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || weak_eq(candidate, etag))
+}
+
+/// Weak comparison, `W/"x"` and `"x"` are the same tag.
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+fn set_validators(headers: &mut HeaderMap, etag: &str, last_fetched_at: Option<NaiveDateTime>) {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, value);
+    }
+    // Another tenant's repository of the same name has another answer, added to what the
+    // handler already varies on
+    headers.append(header::VARY, HeaderValue::from_static("X-API-Key, Authorization"));
+
+    let last_modified = last_fetched_at
+        .map(|fetched_at| fetched_at.and_utc().format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+        .and_then(|date| HeaderValue::from_str(&date).ok());
+    if let Some(value) = last_modified {
+        headers.insert(header::LAST_MODIFIED, value);
+    }
+}
@@ -1 +1,9 @@
-pub mod repo_stars;
\ No newline at end of file
+pub mod alerts;
+pub mod etag;
+pub mod org_stars;
+pub mod repo_stars;
+pub mod reports;
+pub mod repositories;
+pub mod stargazers;
+pub mod status;
+pub mod webhooks;
\ No newline at end of file
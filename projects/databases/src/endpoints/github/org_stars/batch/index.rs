@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    sync_batch::queries::{
+	        count_batch_jobs_by_status, get_sync_batch_by_id, CountBatchJobsByStatusError,
+	        GetSyncBatchByIdError,
+	    },
+	    sync_job::models::SyncJobStatus,
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetSyncBatchById: {source}")]
+	GetSyncBatchById {
+		#[from]
+		source: GetSyncBatchByIdError,
+	},
+	#[error("SyncBatchNotFound: {id}")]
+	SyncBatchNotFound {
+		id: Uuid,
+	},
+	#[error("CountBatchJobsByStatus: {source}")]
+	CountBatchJobsByStatus {
+		#[from]
+		source: CountBatchJobsByStatusError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::SyncBatchNotFound{ id } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::SyncBatchNotFound, format!("Sync batch {id} not found")).with_details(json!({ "id": id })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Progress aggregated over the child sync jobs of a batch.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct BatchProgress {
+	pub id: Uuid,
+	/// Unset for batches started by `POST /github/repo_stars/update_batch`.
+	pub owner: Option<String>,
+	pub total: i64,
+	/// Repositories whose sync has not started yet, or waits for a worker to resume it.
+	pub pending: i64,
+	pub running: i64,
+	pub completed: i64,
+	/// Repositories whose sync stopped at one of its limits.
+	pub partially_completed: i64,
+	pub failed: i64,
+	pub interrupted: i64,
+}
+
+/// Axum handler: GET /github/org_stars/batches/{id}
+#[utoipa::path(
+    get,
+    path = "/github/org_stars/batches/{id}",
+    operation_id = "get_sync_batch",
+    tag = "org_stars",
+    summary = "Owner sync progress",
+    params(
+        ("id" = Uuid, Path, description = "Batch id"),
+    ),
+    responses(
+        (status = 200, description = "Progress over the batch's sync jobs", body = BatchProgress),
+        (status = 404, description = "Batch not found"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BatchProgress>, HandlerError> {
+    let batch = get_sync_batch_by_id(&mut conn, id)
+		.map_err(|source| HandlerError::GetSyncBatchById{ source })?
+		// Other tenants' batches are reported as missing, not forbidden.
+		.filter(|batch| batch.tenant_id == tenant_id)
+		.ok_or(HandlerError::SyncBatchNotFound{ id })?;
+
+    let counts = count_batch_jobs_by_status(&mut conn, id)
+		.map_err(|source| HandlerError::CountBatchJobsByStatus{ source })?;
+
+    let count = |status: SyncJobStatus| {
+        counts
+            .iter()
+            .find(|(name, _)| name == status.as_str())
+            .map_or(0, |(_, count)| *count)
+    };
+
+    let total = i64::from(batch.total_repositories);
+    let started: i64 = counts.iter().map(|(_, count)| count).sum();
+
+    Ok(Json(BatchProgress {
+        id: batch.id,
+        owner: batch.owner,
+        total,
+        pending: (total - started).max(0) + count(SyncJobStatus::Pending),
+        running: count(SyncJobStatus::Running),
+        completed: count(SyncJobStatus::Completed),
+        partially_completed: count(SyncJobStatus::PartiallyCompleted),
+        failed: count(SyncJobStatus::Failed),
+        interrupted: count(SyncJobStatus::Interrupted),
+    }))
+}
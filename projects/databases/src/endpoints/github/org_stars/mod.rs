@@ -0,0 +1,2 @@
+pub mod update;
+pub mod batch;
\ No newline at end of file
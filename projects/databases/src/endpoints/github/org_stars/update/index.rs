@@ -0,0 +1,228 @@
+use axum::{
+    extract::{Extension, Json},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use interfaces_github_stargazers::client::GitHubClient;
+use interfaces_github_stargazers::forge::Forge;
+use interfaces_github_stargazers::graphql::ParseGraphQLResponseError;
+use interfaces_github_stargazers::owner_repositories::{
+    fetch_owner_repositories, parse_owner_repositories_response, FetchOwnerRepositoriesError,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    sync_batch::{
+	        models::NewSyncBatch,
+	        queries::{insert_sync_batch, InsertSyncBatchError},
+	    },
+	    sync_run::models::SyncTrigger,
+	    PgPool,
+	};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::endpoints::tenant::TenantId;
+use crate::validation::{validate_owner, validate_sync_limits, ValidationErrors};
+use crate::endpoints::github::status::github_error_status_code;
+use crate::live::PageSink;
+use crate::runtime_config::RuntimeConfig;
+use crate::sync_limits::SyncLimits;
+use crate::sync_queue::{SyncPriority, SyncQueue, SyncTask};
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("Checkout: {source}")]
+	Checkout {
+		#[from]
+		source: DbConnRejection,
+	},
+	#[error("MissingGithubCredentials")]
+	MissingGithubCredentials,
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+	#[error("ListOwnerRepositories: {source}")]
+	ListOwnerRepositories {
+		#[from]
+		source: ListOwnerRepositoriesError,
+	},
+	#[error("InsertSyncBatch: {source}")]
+	InsertSyncBatch {
+		#[from]
+		source: InsertSyncBatchError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::Checkout{ source } => source.into_response(),
+			HandlerError::MissingGithubCredentials => ApiError::missing_github_credentials().into_response(),
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			HandlerError::ListOwnerRepositories{ source } => ApiError::github(source.status_code(), source).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// JSON payload expected by the endpoint.
+#[derive(Deserialize, ToSchema)]
+pub struct OrgQuery {
+	owner: String,
+	/// Archived repositories are skipped unless set.
+	#[serde(default)]
+	include_archived: bool,
+	/// Repositories with fewer stars on GitHub are skipped.
+	#[serde(default)]
+	min_stars: u32,
+	/// Position of the batch's syncs in the sync queue, `low` by default.
+	#[serde(default = "default_priority")]
+	priority: SyncPriority,
+	/// Limits of each repository's job, replacing the service's. Each at least 1.
+	#[serde(flatten)]
+	limits: SyncLimits,
+}
+
+fn default_priority() -> SyncPriority {
+	SyncPriority::Low
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct BatchCreated {
+	pub batch_id: Uuid,
+	pub repositories: Vec<String>,
+}
+
+/// Axum handler: POST /github/org_stars/update
+///
+/// Lists the owner's non-fork repositories and queues a sync for each of them,
+/// progress is reported by the batch endpoint.
+#[utoipa::path(
+    post,
+    path = "/github/org_stars/update",
+    operation_id = "sync_owner",
+    tag = "org_stars",
+    summary = "Sync every repository of an owner",
+    request_body = OrgQuery,
+    responses(
+        (status = 202, description = "Syncs queued", body = BatchCreated),
+        (status = 404, description = "Owner not found on GitHub"),
+        (status = 422, description = "Owner breaking GitHub's naming rules, or a limit of 0"),
+        (status = 502, description = "GitHub request failed"),
+        (status = 500, description = "Missing GitHub credentials or database failure"),
+    ),
+)]
+pub async fn handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(github): Extension<GitHubClient>,
+    Extension(queue): Extension<SyncQueue>,
+    Extension(config): Extension<RuntimeConfig>,
+    Json(input): Json<OrgQuery>,
+) -> Result<(StatusCode, Json<BatchCreated>), HandlerError> {
+    let mut errors = ValidationErrors::new();
+    errors.check("owner", validate_owner(&input.owner));
+    validate_sync_limits(&mut errors, &input.limits);
+    errors.into_result()?;
+    if !github.has_credentials() {
+        return Err(HandlerError::MissingGithubCredentials);
+    }
+
+    let repositories = list_owner_repositories(&github, &input)
+		.await
+		.map_err(|source| HandlerError::ListOwnerRepositories{ source })?;
+
+    // Checked out once the owner's repositories are listed, not held through the GitHub calls.
+    let DbConn(mut conn) = DbConn::checkout(pool)
+		.await
+		.map_err(|source| HandlerError::Checkout{ source })?;
+
+    let new_batch = NewSyncBatch {
+        id: Uuid::new_v4(),
+        owner: Some(&input.owner),
+        total_repositories: repositories.len() as i32,
+        tenant_id,
+    };
+
+    let batch = insert_sync_batch(&mut conn, &new_batch)
+		.map_err(|source| HandlerError::InsertSyncBatch{ source })?;
+
+    let limits = config.sync_limits().overridden_by(input.limits);
+    for name in &repositories {
+        let task = SyncTask::Repository { tenant_id, forge: Forge::GitHub, owner: input.owner.clone(), name: name.clone(), batch_id: Some(batch.id), trigger: SyncTrigger::OwnerSync, forks_min_stars: None, pages: PageSink::default(), limits };
+        // Progress is tracked through the batch's sync jobs, not the task outcome.
+        drop(queue.enqueue(task, input.priority));
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(BatchCreated { batch_id: batch.id, repositories })))
+}
+
+#[derive(Debug, Error)]
+pub enum ListOwnerRepositoriesError {
+	#[error("FetchOwnerRepositories: {source}")]
+	FetchOwnerRepositories {
+		#[from]
+		source: FetchOwnerRepositoriesError,
+	},
+	#[error("ParseOwnerRepositoriesResponse: {source}")]
+	ParseOwnerRepositoriesResponse {
+		#[from]
+		source: ParseGraphQLResponseError,
+	},
+	#[error("OwnerNotFound: {owner}")]
+	OwnerNotFound {
+		owner: String,
+	},
+}
+
+impl ListOwnerRepositoriesError {
+	pub fn status_code(&self) -> StatusCode {
+		match self {
+			ListOwnerRepositoriesError::ParseOwnerRepositoriesResponse{ source } => github_error_status_code(source),
+			ListOwnerRepositoriesError::OwnerNotFound{ .. } => StatusCode::NOT_FOUND,
+			ListOwnerRepositoriesError::FetchOwnerRepositories{ .. } => StatusCode::BAD_GATEWAY,
+		}
+	}
+}
+
+/// Names of the owner's repositories matching the query filters.
+async fn list_owner_repositories(
+    github: &GitHubClient,
+    q: &OrgQuery,
+) -> Result<Vec<String>, ListOwnerRepositoriesError> {
+    let mut names = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let result = fetch_owner_repositories(github, &q.owner, cursor.as_deref())
+			.await
+			.map_err(|source| ListOwnerRepositoriesError::FetchOwnerRepositories{ source })?;
+
+        let data = parse_owner_repositories_response(&result)
+			.map_err(|source| ListOwnerRepositoriesError::ParseOwnerRepositoriesResponse{ source })?;
+
+        let repositories = data
+            .repository_owner
+            .ok_or_else(|| ListOwnerRepositoriesError::OwnerNotFound{ owner: q.owner.clone() })?
+            .repositories;
+
+        names.extend(
+            repositories
+                .nodes
+                .into_iter()
+                .filter(|repo| q.include_archived || !repo.is_archived)
+                .filter(|repo| repo.stargazer_count >= q.min_stars)
+                .map(|repo| repo.name),
+        );
+
+        if !repositories.page_info.has_next_page {
+            return Ok(names);
+        }
+        cursor = repositories.page_info.end_cursor;
+    }
+}
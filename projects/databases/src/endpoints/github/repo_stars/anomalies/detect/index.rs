@@ -0,0 +1,167 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    response::IntoResponse,
+};
+use interfaces_github_stargazers::client::GitHubClient;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use tracing::warn;
+
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::{
+    anomalies::{detect_anomalies, AnomalyConfig, MAX_WINDOW},
+    series::daily_series,
+};
+use crate::endpoints::github::repo_stars::anomalies::list::index::AnomalyResponse;
+use crate::notifier::Notifier;
+use crate::validation::ValidationErrors;
+use crate::webhook::send_webhook;
+use crate::db::{
+	    anomaly::{
+	        models::NewStarAnomaly,
+	        queries::{insert_star_anomalies, InsertStarAnomaliesError},
+	    },
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_daily_star_count, GetDailyStarCountError},
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
+		#[from]
+		source: GetDailyStarCountError,
+	},
+	#[error("InsertStarAnomalies: {source}")]
+	InsertStarAnomalies {
+		#[from]
+		source: InsertStarAnomaliesError,
+	},
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Request body expected by the endpoint, every field is optional.
+#[derive(Deserialize, Default, ToSchema)]
+pub struct DetectRequest {
+	#[serde(flatten)]
+	config: AnomalyConfig,
+	/// Receives the newly detected anomalies, if any. Checked like alert webhooks.
+	webhook_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct DetectResponse {
+	/// Anomalies not detected by a previous run.
+	pub anomalies: Vec<AnomalyResponse>,
+	pub webhook_delivered: bool,
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+	owner: &'a str,
+	name: &'a str,
+	anomalies: &'a [AnomalyResponse],
+}
+
+/// Axum handler: POST /github/repo_stars/anomalies/{owner}/{name}/detect
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/anomalies/{owner}/{name}/detect",
+    operation_id = "detect_anomalies",
+    tag = "repo_stars",
+    summary = "Detect star spikes",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    request_body = DetectRequest,
+    responses(
+        (status = 200, description = "Newly detected spikes", body = DetectResponse),
+        (status = 404, description = "Repository not in database"),
+        (status = 422, description = "Invalid detection settings or webhook URL not allowed"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(github): Extension<GitHubClient>,
+    Extension(notifier): Extension<Notifier>,
+    Path((owner, name)): Path<(String, String)>,
+    Json(body): Json<DetectRequest>,
+) -> Result<Json<DetectResponse>, HandlerError> {
+    let mut errors = ValidationErrors::new();
+    errors
+        .check("multiple", if body.config.multiple > 0.0 { Ok(()) } else { Err("must be positive".into()) })
+        .check("window", match body.config.window {
+            1..=MAX_WINDOW => Ok(()),
+            _ => Err(format!("must be between 1 and {MAX_WINDOW}")),
+        });
+    if let Some(url) = &body.webhook_url {
+        errors.check("webhook_url", notifier.webhook_policy().validate(url));
+    }
+    errors.into_result()?;
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+
+    let daily_counts = get_daily_star_count(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetDailyStarCount{ source })?;
+
+    let detected: Vec<NewStarAnomaly> = detect_anomalies(&daily_series(&daily_counts), &body.config)
+        .into_iter()
+        .map(|anomaly| NewStarAnomaly {
+            repository_id: repo.id,
+            day: anomaly.day,
+            stars: i32::try_from(anomaly.stars).unwrap_or(i32::MAX),
+            trailing_average: anomaly.trailing_average,
+        })
+        .collect();
+
+    let anomalies: Vec<AnomalyResponse> = insert_star_anomalies(&mut conn, &detected)
+		.map_err(|source| HandlerError::InsertStarAnomalies{ source })?
+		.into_iter()
+		.map(AnomalyResponse::from)
+		.collect();
+
+    // The anomalies are stored at this point, a failed delivery is reported but not fatal.
+    let mut webhook_delivered = false;
+    if let Some(url) = body.webhook_url.as_deref().filter(|_| !anomalies.is_empty()) {
+        let payload = WebhookPayload { owner: &owner, name: &name, anomalies: &anomalies };
+        match send_webhook(github.http(), url, None, &payload).await {
+            Ok(()) => webhook_delivered = true,
+            Err(error) => warn!(%error, %url, "Anomaly webhook delivery failed"),
+        }
+    }
+
+    Ok(Json(DetectResponse { anomalies, webhook_delivered }))
+}
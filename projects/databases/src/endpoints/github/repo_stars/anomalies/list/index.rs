@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    response::IntoResponse,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    anomaly::{
+	        models::StarAnomaly,
+	        queries::{get_star_anomalies_by_repository, GetStarAnomaliesByRepositoryError},
+	    },
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetStarAnomaliesByRepository: {source}")]
+	GetStarAnomaliesByRepository {
+		#[from]
+		source: GetStarAnomaliesByRepositoryError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct AnomalyResponse {
+	pub day: NaiveDate,
+	pub stars: i32,
+	pub trailing_average: f64,
+}
+
+impl From<StarAnomaly> for AnomalyResponse {
+	fn from(anomaly: StarAnomaly) -> Self {
+		Self {
+			day: anomaly.day,
+			stars: anomaly.stars,
+			trailing_average: anomaly.trailing_average,
+		}
+	}
+}
+
+/// Axum handler: GET /github/repo_stars/anomalies/{owner}/{name}
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/anomalies/{owner}/{name}",
+    operation_id = "list_anomalies",
+    tag = "repo_stars",
+    summary = "Stored star spikes",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 200, description = "Detected spikes", body = Vec<AnomalyResponse>),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<Json<Vec<AnomalyResponse>>, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let anomalies = get_star_anomalies_by_repository(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetStarAnomaliesByRepository{ source })?;
+
+    Ok(Json(anomalies.into_iter().map(AnomalyResponse::from).collect()))
+}
@@ -0,0 +1,169 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::bots::BotFilter;
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_star_count_as_of, get_stargazers_as_of, GetStarCountAsOfError, GetStargazersAsOfError, StarCursor},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+use crate::endpoints::github::repo_stars::earliest_stargazers::index::StargazerResponse;
+
+/// Upper bound of `limit`, keeps a single answer reasonably small.
+const MAX_LIMIT: i64 = 10000;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidLimit: {limit}")]
+	InvalidLimit {
+		limit: i64,
+	},
+	#[error("IncompleteCursor")]
+	IncompleteCursor,
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetStarCountAsOf: {source}")]
+	GetStarCountAsOf {
+		#[from]
+		source: GetStarCountAsOfError,
+	},
+	#[error("GetStargazersAsOf: {source}")]
+	GetStargazersAsOf {
+		#[from]
+		source: GetStargazersAsOfError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidLimit{ limit } => ApiError::invalid_request(format!("limit must be between 1 and {MAX_LIMIT}, got {limit}")).with_details(json!({ "limit": limit, "max": MAX_LIMIT })).into_response(),
+			HandlerError::IncompleteCursor => ApiError::invalid_request("after_starred_at and after_stargazer go together").into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AsOfParams {
+	/// Stars starred up to the end of this day, UTC, are counted.
+	date: NaiveDate,
+	/// Lists the stargazers of that day as well, a page at a time.
+	#[serde(default)]
+	stargazers: bool,
+	#[serde(default = "default_limit")]
+	limit: i64,
+	/// `next` of the previous page, along with `after_stargazer`.
+	after_starred_at: Option<NaiveDateTime>,
+	after_stargazer: Option<String>,
+}
+
+fn default_limit() -> i64 {
+	1000
+}
+
+/// Last stargazer of a page, to pass as `after_starred_at` and `after_stargazer` for the next one.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StargazerCursor {
+	pub starred_at: NaiveDateTime,
+	pub stargazer: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct AsOfResponse {
+	pub date: NaiveDate,
+	/// Stars starred up to the end of `date`, compacted ones included.
+	pub stars: i64,
+	/// Compacted stars among them, counted but not listed.
+	pub compacted_stars: i64,
+	/// Stored stargazers of `date`, oldest first, unset unless asked for.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stargazers: Option<Vec<StargazerResponse>>,
+	/// Cursor of the next page, unset on the last one.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub next: Option<StargazerCursor>,
+}
+
+/// Axum handler: GET /github/repo_stars/as_of/{owner}/{name}?date=YYYY-MM-DD
+///
+/// Star count of the repository as of the end of `date`, and its stargazers then with
+/// `stargazers=true`. Both are range reads on the `starred_at` index, the same answer for the same
+/// stored stars whenever asked.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/as_of/{owner}/{name}",
+    operation_id = "get_stars_as_of",
+    tag = "repo_stars",
+    summary = "Star count and stargazers as of a date",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        AsOfParams,
+        BotFilter,
+    ),
+    responses(
+        (status = 200, description = "Star count as of the date, with a page of stargazers if asked for", body = AsOfResponse),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 400, description = "Invalid limit or incomplete cursor"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<AsOfParams>,
+    Query(bots): Query<BotFilter>,
+) -> Result<Json<AsOfResponse>, HandlerError> {
+    if !(1..=MAX_LIMIT).contains(&params.limit) {
+        return Err(HandlerError::InvalidLimit{ limit: params.limit });
+    }
+    let after: Option<StarCursor> = match (params.after_starred_at, params.after_stargazer) {
+        (Some(starred_at), Some(stargazer)) => Some((starred_at, stargazer)),
+        (None, None) => None,
+        _ => return Err(HandlerError::IncompleteCursor),
+    };
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let (stored, compacted_stars) = get_star_count_as_of(&mut conn, repo.id, params.date, bots.exclude_bots)
+		.map_err(|source| HandlerError::GetStarCountAsOf{ source })?;
+
+    let mut response = AsOfResponse { date: params.date, stars: stored + compacted_stars, compacted_stars, stargazers: None, next: None };
+    if params.stargazers {
+        // One more than asked tells whether a next page exists.
+        let mut page = get_stargazers_as_of(&mut conn, repo.id, params.date, after.as_ref(), bots.exclude_bots, params.limit + 1)
+			.map_err(|source| HandlerError::GetStargazersAsOf{ source })?;
+        if page.len() as i64 > params.limit {
+            page.truncate(params.limit as usize);
+            response.next = page.last().map(|(stargazer, starred_at)| StargazerCursor { starred_at: *starred_at, stargazer: stargazer.clone() });
+        }
+        response.stargazers = Some(page.into_iter().map(|(stargazer, starred_at)| StargazerResponse { stargazer, starred_at }).collect());
+    }
+
+    Ok(Json(response))
+}
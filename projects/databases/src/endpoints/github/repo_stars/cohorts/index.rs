@@ -0,0 +1,200 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::cohorts::{star_cohorts, CohortAnalysis};
+use crate::chart::{render_stacked_area_svg, AxisOptions, StackedBand, ThemeName, MAX_TICKS, MIN_TICKS};
+use crate::db::{
+	    daily_star_count::{
+	        models::GrowthPeriod,
+	        queries::{get_stars_per_period, GetStarsPerPeriodError},
+	    },
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+use crate::render_pool::{RenderError, RenderPool};
+use crate::validation::ValidationErrors;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("RepositoryArchived: {owner}/{name}")]
+	RepositoryArchived {
+		owner: String,
+		name: String,
+	},
+	#[error("GetStarsPerPeriod: {source}")]
+	GetStarsPerPeriod {
+		#[from]
+		source: GetStarsPerPeriodError,
+	},
+	#[error("Render: {source}")]
+	Render {
+		#[from]
+		source: RenderError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::RepositoryArchived{ owner, name } => ApiError::repository_archived(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CohortFormat {
+	#[default]
+	Json,
+	/// Stacked area chart, one band per cohort.
+	Svg,
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CohortParams {
+	/// Period stargazers are grouped by.
+	#[serde(default)]
+	period: GrowthPeriod,
+	/// Last day counted, today (UTC) by default.
+	as_of: Option<NaiveDate>,
+	#[serde(default)]
+	format: CohortFormat,
+	/// Colors of the SVG chart.
+	#[serde(default)]
+	theme: ThemeName,
+	/// Date labels of the SVG chart at most, 5 by default.
+	max_x_ticks: Option<usize>,
+	/// Star count labels of the SVG chart at most, 5 by default.
+	max_y_ticks: Option<usize>,
+	/// Writes the date labels of the SVG chart at an angle.
+	#[serde(default)]
+	rotate_x_labels: bool,
+}
+
+impl CohortParams {
+	fn axes(&self) -> Result<AxisOptions, ValidationErrors> {
+		let default = AxisOptions::default();
+		let axes = AxisOptions {
+			max_x_ticks: self.max_x_ticks.unwrap_or(default.max_x_ticks),
+			max_y_ticks: self.max_y_ticks.unwrap_or(default.max_y_ticks),
+			rotate_x_labels: self.rotate_x_labels,
+		};
+		let mut errors = ValidationErrors::new();
+		for (field, ticks) in [("max_x_ticks", axes.max_x_ticks), ("max_y_ticks", axes.max_y_ticks)] {
+			errors.check(field, match ticks {
+				MIN_TICKS..=MAX_TICKS => Ok(()),
+				_ => Err(format!("must be between {MIN_TICKS} and {MAX_TICKS}")),
+			});
+		}
+		errors.into_result()?;
+		Ok(axes)
+	}
+}
+
+/// Stargazers of a repository grouped by the period they starred in.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct CohortReport {
+	pub period: GrowthPeriod,
+	pub as_of: NaiveDate,
+	#[serde(flatten)]
+	pub analysis: CohortAnalysis,
+}
+
+/// Axum handler: GET /github/repo_stars/cohorts/{owner}/{name}
+///
+/// Not behind the ETag middleware, the default `as_of` changes with the date.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/cohorts/{owner}/{name}",
+    operation_id = "star_cohorts",
+    tag = "repo_stars",
+    summary = "Group stargazers into cohorts by the period they starred in",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        CohortParams,
+    ),
+    responses(
+        (status = 200, description = "Stars of every cohort and their running totals, days in UTC, or with `format=svg` a stacked area chart of them", content(
+            (CohortReport = "application/json"),
+            (String = "image/svg+xml"),
+        )),
+        (status = 422, description = "Invalid tick count"),
+        (status = 404, description = "Repository not in database"),
+        (status = 409, description = "Repository archived"),
+        (status = 500, description = "Database or rendering failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(renders): Extension<RenderPool>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<CohortParams>,
+) -> Result<Response, HandlerError> {
+    let axes = params.axes()?;
+    let as_of = params.as_of.unwrap_or_else(|| Utc::now().date_naive());
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+    if repo.is_archived() {
+        return Err(HandlerError::RepositoryArchived{ owner: repo.owner, name: repo.name });
+    }
+
+    let period_counts: Vec<(NaiveDate, i64)> = get_stars_per_period(&mut conn, repo.id, params.period, as_of)
+		.map_err(|source| HandlerError::GetStarsPerPeriod{ source })?
+		.into_iter()
+		.map(|period| (period.period_start, period.stars))
+		.collect();
+    drop(conn);
+
+    let analysis = star_cohorts(&period_counts);
+
+    Ok(match params.format {
+        CohortFormat::Json => Json(CohortReport { period: params.period, as_of, analysis }).into_response(),
+        CohortFormat::Svg => {
+            let bands: Vec<StackedBand> = analysis
+                .cohorts
+                .into_iter()
+                .map(|cohort| StackedBand { label: cohort.start.to_string(), values: cohort.cumulative })
+                .collect();
+            let title = format!("{}/{} stars by {} cohort", repo.owner, repo.name, params.period.as_str());
+            let theme = params.theme.theme();
+            let svg = renders.run(move || render_stacked_area_svg(&title, &analysis.periods, &bands, &theme, &axes))
+                .await
+                .map_err(|source| HandlerError::Render{ source })?;
+            ([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()
+        }
+    })
+}
@@ -0,0 +1,394 @@
+use axum::{
+    body::Bytes,
+    extract::{Extension, Json},
+    http::header,
+    response::IntoResponse,
+};
+use chrono::{NaiveDate, Utc};
+use diesel::PgConnection;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::compare::{compare_series, Alignment, CompareMetric, CompareOptions, Normalization};
+use crate::analytics::derivatives::DerivativeOptions;
+use crate::analytics::series::FillPolicy;
+use crate::chart_cache::{ChartCache, ChartKey, RepositoryVersion};
+use crate::chart::{AxisOptions, HexColor, ThemeName, MAX_TICKS, MIN_TICKS};
+use crate::db::{
+	    release::queries::{get_releases_by_repositories, GetReleasesByRepositoryError},
+	    repository::queries::{get_forks, get_repository_by_name, GetForksError, GetRepositoryByNameError},
+	    star::queries::{get_star_version, GetStarVersionError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::github::repo_stars::compare::index::{load_histories, ComparedRepository, HandlerError as CompareError, MAX_COMPARED_REPOSITORIES};
+use crate::endpoints::tenant::TenantId;
+use crate::multi_repo_chart::{generate_multi_repo_chart, ChartConfig, ChartType, DashStyle, GenerateMultiRepoChartError, ReleaseMarker, SeriesStyle, Watermark, MAX_LINE_WIDTH, MAX_WATERMARK_LENGTH};
+use crate::render_pool::{RenderError, RenderPool};
+use crate::validation::{validate_derivative_options, ValidationErrors};
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+	#[error("Compare: {source}")]
+	Compare {
+		#[from]
+		source: CompareError,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("GetForks: {source}")]
+	GetForks {
+		#[from]
+		source: GetForksError,
+	},
+	#[error("GetStarVersion: {source}")]
+	GetStarVersion {
+		#[from]
+		source: GetStarVersionError,
+	},
+	#[error("SerializeQuery: {source}")]
+	SerializeQuery {
+		#[from]
+		source: serde_json::Error,
+	},
+	#[error("GetReleasesByRepository: {source}")]
+	GetReleasesByRepository {
+		#[from]
+		source: GetReleasesByRepositoryError,
+	},
+	#[error("GenerateMultiRepoChart: {source}")]
+	GenerateMultiRepoChart {
+		#[from]
+		source: GenerateMultiRepoChartError,
+	},
+	#[error("Render: {source}")]
+	Render {
+		#[from]
+		source: RenderError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			HandlerError::Compare{ source } => source.into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// JSON payload expected by the endpoint, also sent by the client.
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct CompareChartQuery {
+	/// Drawn in this order, the first at the bottom of stacked charts.
+	pub repositories: Vec<ComparedRepository>,
+	#[serde(default = "default_metric")]
+	pub metric: CompareMetric,
+	/// Drawn as dashed lines against a right axis, e.g. `daily` along with `cumulative`, or `views`
+	/// along with `daily`.
+	pub secondary_metric: Option<CompareMetric>,
+	#[serde(default)]
+	pub alignment: Alignment,
+	/// Rescaling of the `normalized` metric, percent of each repository's total by default.
+	#[serde(default)]
+	pub normalization: Normalization,
+	/// Window and smoothing of the `velocity` and `acceleration` metrics, a 1-day difference by default.
+	#[serde(default)]
+	pub derivative: DerivativeOptions,
+	/// Filling of the days without data, `zero` by default.
+	#[serde(default)]
+	pub fill: FillPolicy,
+	#[serde(default)]
+	pub chart_type: ChartType,
+	#[serde(default)]
+	pub theme: ThemeName,
+	/// Labels of the x axis at most, 6 by default.
+	pub max_x_ticks: Option<usize>,
+	/// Labels of each y axis at most, 6 by default.
+	pub max_y_ticks: Option<usize>,
+	/// Writes the x labels vertically.
+	#[serde(default)]
+	pub rotate_x_labels: bool,
+	/// Looks of some of the repositories, e.g. their brand colors, the theme's for the others.
+	#[serde(default)]
+	pub series: Vec<SeriesStyleQuery>,
+	pub watermark: Option<Watermark>,
+	/// Marks the stored releases of the repositories on their series, see `/github/repo_stars/releases`.
+	#[serde(default)]
+	pub annotate_releases: bool,
+}
+
+/// Look of a compared repository's series.
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct SeriesStyleQuery {
+	pub owner: String,
+	pub name: String,
+	/// Hex color, e.g. `#dea584`.
+	pub color: Option<String>,
+	/// Line width in pixels, 2 by default, for line and area charts.
+	pub line_width: Option<u32>,
+	/// Dash style of the line, for line and area charts.
+	#[serde(default)]
+	pub dash: DashStyle,
+}
+
+fn default_metric() -> CompareMetric {
+	CompareMetric::Cumulative
+}
+
+impl CompareChartQuery {
+	/// A line chart of the running totals of `repositories`, the other fields left to their defaults.
+	pub fn new(repositories: &[(&str, &str)]) -> Self {
+		Self {
+			repositories: repositories
+				.iter()
+				.map(|(owner, name)| ComparedRepository { owner: owner.to_string(), name: name.to_string() })
+				.collect(),
+			metric: default_metric(),
+			secondary_metric: None,
+			alignment: Alignment::default(),
+			normalization: Normalization::default(),
+			derivative: DerivativeOptions::default(),
+			fill: FillPolicy::default(),
+			chart_type: ChartType::default(),
+			theme: ThemeName::default(),
+			max_x_ticks: None,
+			max_y_ticks: None,
+			rotate_x_labels: false,
+			series: Vec::new(),
+			watermark: None,
+			annotate_releases: false,
+		}
+	}
+
+	/// Styles of `series`, each matching a compared repository once.
+	fn series_styles(&self, errors: &mut ValidationErrors) -> Vec<SeriesStyle> {
+		let mut styles: Vec<SeriesStyle> = Vec::with_capacity(self.series.len());
+		for (index, series) in self.series.iter().enumerate() {
+			let repository = format!("{}/{}", series.owner, series.name);
+			let compared = self.repositories.iter().any(|compared| {
+				compared.owner.eq_ignore_ascii_case(&series.owner) && compared.name.eq_ignore_ascii_case(&series.name)
+			});
+			let listed = styles.iter().any(|style| style.repository.eq_ignore_ascii_case(&repository));
+			errors.check(format!("series[{index}]"), match (compared, listed) {
+				(false, _) => Err(format!("{repository} is not among the compared repositories")),
+				(true, true) => Err(format!("{repository} is styled twice")),
+				(true, false) => Ok(()),
+			});
+
+			let color = match series.color.as_deref().map(str::parse::<HexColor>) {
+				Some(Ok(color)) => Some(color),
+				Some(Err(_)) => {
+					errors.check(format!("series[{index}].color"), Err("must be a hex color, e.g. #dea584".into()));
+					None
+				}
+				None => None,
+			};
+			if let Some(width) = series.line_width {
+				errors.check(format!("series[{index}].line_width"), match width {
+					1..=MAX_LINE_WIDTH => Ok(()),
+					_ => Err(format!("must be between 1 and {MAX_LINE_WIDTH}")),
+				});
+			}
+			styles.push(SeriesStyle { repository, color, line_width: series.line_width, dash: series.dash });
+		}
+		styles
+	}
+
+	fn check_watermark(&self, errors: &mut ValidationErrors) {
+		if let Some(watermark) = &self.watermark {
+			let length = watermark.text.trim().chars().count();
+			errors
+				.check("watermark.text", match length {
+					1..=MAX_WATERMARK_LENGTH => Ok(()),
+					_ => Err(format!("must be 1 to {MAX_WATERMARK_LENGTH} characters long")),
+				})
+				.check("watermark.opacity", match watermark.opacity {
+					0.0..=1.0 => Ok(()),
+					_ => Err("must be between 0 and 1".into()),
+				});
+		}
+	}
+
+	fn axes(&self, default: AxisOptions, errors: &mut ValidationErrors) -> AxisOptions {
+		let axes = AxisOptions {
+			max_x_ticks: self.max_x_ticks.unwrap_or(default.max_x_ticks),
+			max_y_ticks: self.max_y_ticks.unwrap_or(default.max_y_ticks),
+			rotate_x_labels: self.rotate_x_labels,
+		};
+		for (field, ticks) in [("max_x_ticks", axes.max_x_ticks), ("max_y_ticks", axes.max_y_ticks)] {
+			errors.check(field, match ticks {
+				MIN_TICKS..=MAX_TICKS => Ok(()),
+				_ => Err(format!("must be between {MIN_TICKS} and {MAX_TICKS}")),
+			});
+		}
+		axes
+	}
+}
+
+/// Axum handler: POST /github/repo_stars/compare/chart
+///
+/// The comparison of `/github/repo_stars/compare` as a chart, for one metric or two on separate axes.
+/// Charts are served again from the [`ChartCache`] until the stars of a repository change.
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/compare/chart",
+    operation_id = "compare_repositories_chart",
+    tag = "repo_stars",
+    summary = "Chart the star histories of several repositories",
+    request_body = CompareChartQuery,
+    responses(
+        (status = 200, description = "SVG chart, dates in UTC", content_type = "image/svg+xml", body = String),
+        (status = 400, description = "No repository or too many"),
+        (status = 422, description = "Invalid tick count, series style, watermark or derivative options"),
+        (status = 404, description = "Repository not in database"),
+        (status = 409, description = "Repository archived"),
+        (status = 500, description = "Database or rendering failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(charts): Extension<ChartCache>,
+    Extension(renders): Extension<RenderPool>,
+    Json(input): Json<CompareChartQuery>,
+) -> Result<impl IntoResponse, HandlerError> {
+    let mut errors = ValidationErrors::new();
+    let axes = input.axes(ChartConfig::default().axes, &mut errors);
+    let series_styles = input.series_styles(&mut errors);
+    input.check_watermark(&mut errors);
+    validate_derivative_options(&mut errors, "derivative.", &input.derivative);
+    errors.into_result()?;
+    let today = Utc::now().date_naive();
+    let key = chart_key(&mut conn, tenant_id, &input, today).await?;
+    if let Some(svg) = key.as_ref().and_then(|key| charts.get(key)) {
+        return Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg));
+    }
+    let metrics: Vec<CompareMetric> = std::iter::once(input.metric).chain(input.secondary_metric).collect();
+    let with_forks = metrics.contains(&CompareMetric::WithForks);
+    let with_traffic = metrics.iter().any(CompareMetric::is_traffic);
+    let with_activity = metrics.iter().any(CompareMetric::is_activity);
+    let with_contributors = metrics.contains(&CompareMetric::Contributors);
+    let annotated = input.annotate_releases.then(|| input.repositories.clone());
+    let repositories = load_histories(&mut conn, tenant_id, input.repositories, with_forks, with_traffic, with_activity, with_contributors, input.fill).await?;
+    let releases = match annotated {
+        Some(compared) => release_markers(&mut conn, tenant_id, &compared).await?,
+        None => Vec::new(),
+    };
+    drop(conn);
+    let options = CompareOptions {
+        alignment: input.alignment,
+        normalization: input.normalization,
+        derivative: input.derivative,
+        fill: input.fill,
+    };
+    let comparison = compare_series(&repositories, &metrics, &options, today);
+
+    let config = ChartConfig {
+        chart_type: input.chart_type,
+        metric: input.metric,
+        secondary_metric: input.secondary_metric,
+        theme: input.theme.theme(),
+        axes,
+        series_styles,
+        watermark: input.watermark,
+        generated_at: Some(Utc::now()),
+        releases,
+        ..ChartConfig::default()
+    };
+    let svg = renders.run(move || generate_multi_repo_chart(&comparison, &config))
+        .await
+		.map_err(|source| HandlerError::Render{ source })?
+		.map_err(|source| HandlerError::GenerateMultiRepoChart{ source })?;
+    let svg = Bytes::from(svg);
+    if let Some(key) = key {
+        charts.insert(key, svg.clone());
+    }
+
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg))
+}
+
+/// What the chart of `input` is drawn from on `today`, `None` when a repository is unknown or
+/// archived, or there are too many of them, left to [`load_histories`] to report.
+///
+/// Charts of the fork metric add the stars of the tracked forks, which sync on their own, so
+/// their versions are part of the key too.
+async fn chart_key(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    input: &CompareChartQuery,
+    today: NaiveDate,
+) -> Result<Option<ChartKey>, HandlerError> {
+    if input.repositories.len() > MAX_COMPARED_REPOSITORIES {
+        return Ok(None);
+    }
+
+    let with_forks = input.metric == CompareMetric::WithForks || input.secondary_metric == Some(CompareMetric::WithForks);
+    let mut repositories = Vec::with_capacity(input.repositories.len());
+    for ComparedRepository { owner, name } in &input.repositories {
+        let Some(repo) = get_repository_by_name(conn, tenant_id, owner, name)
+			.await
+			.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+        else {
+            return Ok(None);
+        };
+        if repo.is_archived() {
+            return Ok(None);
+        }
+        let forks = if with_forks {
+            get_forks(conn, repo.id)
+				.map_err(|source| HandlerError::GetForks{ source })?
+        } else {
+            Vec::new()
+        };
+        for repository_id in std::iter::once(repo.id).chain(forks.into_iter().map(|fork| fork.id)) {
+            let (last_fetched_at, stars) = get_star_version(conn, repository_id)
+				.map_err(|source| HandlerError::GetStarVersion{ source })?;
+            repositories.push(RepositoryVersion { repository_id, last_fetched_at, stars });
+        }
+    }
+
+    let config = serde_json::to_string(input)
+		.map_err(|source| HandlerError::SerializeQuery{ source })?;
+    Ok(Some(ChartKey { tenant_id, repositories, config, day: today }))
+}
+
+/// Stored releases of the compared repositories, on the day they were published.
+async fn release_markers(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    compared: &[ComparedRepository],
+) -> Result<Vec<ReleaseMarker>, HandlerError> {
+    let mut repositories = Vec::with_capacity(compared.len());
+    for ComparedRepository { owner, name } in compared {
+        // Already loaded by `load_histories`, one deleted since has no release left to draw.
+        if let Some(repo) = get_repository_by_name(conn, tenant_id, owner, name)
+			.await
+			.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+        {
+            repositories.push(repo);
+        }
+    }
+
+    let ids: Vec<Uuid> = repositories.iter().map(|repo| repo.id).collect();
+    let releases = get_releases_by_repositories(conn, &ids)
+		.map_err(|source| HandlerError::GetReleasesByRepository{ source })?;
+    Ok(releases
+        .into_iter()
+        .filter_map(|release| {
+            let repo = repositories.iter().find(|repo| repo.id == release.repository_id)?;
+            Some(ReleaseMarker { repository: format!("{}/{}", repo.owner, repo.name), tag_name: release.tag_name, day: release.published_at.date() })
+        })
+        .collect())
+}
@@ -0,0 +1 @@
+pub mod index;
\ No newline at end of file
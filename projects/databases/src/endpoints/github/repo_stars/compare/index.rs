@@ -0,0 +1,252 @@
+use axum::{
+    extract::{Extension, Json},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use diesel::PgConnection;
+use interfaces_github_stargazers::activity::ActivityKind;
+use interfaces_github_stargazers::traffic::TrafficKind;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::compare::{compare_series, Alignment, CompareMetric, CompareOptions, Comparison, DailyActivity, DailyTraffic, Normalization, RepositoryHistory};
+use crate::analytics::derivatives::DerivativeOptions;
+use crate::analytics::series::FillPolicy;
+use crate::db::{
+	    activity_item::queries::{get_daily_activity, GetDailyActivityError},
+	    contributor::queries::{get_daily_first_contributions, GetDailyFirstContributionsError},
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    traffic_snapshot::queries::{get_daily_traffic, GetDailyTrafficError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+use crate::forks::{get_combined_daily_star_count, GetCombinedDailyStarCountError};
+use crate::snapshots::{get_star_history, GetStarHistoryError};
+use crate::validation::{validate_derivative_options, ValidationErrors};
+
+/// Upper bound of the repositories compared at once.
+pub const MAX_COMPARED_REPOSITORIES: usize = 10;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidRepositoryCount: {count}")]
+	InvalidRepositoryCount {
+		count: usize,
+	},
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("RepositoryArchived: {owner}/{name}")]
+	RepositoryArchived {
+		owner: String,
+		name: String,
+	},
+	#[error("GetStarHistory: {source}")]
+	GetStarHistory {
+		#[from]
+		source: GetStarHistoryError,
+	},
+	#[error("GetCombinedDailyStarCount: {source}")]
+	GetCombinedDailyStarCount {
+		#[from]
+		source: GetCombinedDailyStarCountError,
+	},
+	#[error("GetDailyTraffic: {source}")]
+	GetDailyTraffic {
+		#[from]
+		source: GetDailyTrafficError,
+	},
+	#[error("GetDailyActivity: {source}")]
+	GetDailyActivity {
+		#[from]
+		source: GetDailyActivityError,
+	},
+	#[error("GetDailyFirstContributions: {source}")]
+	GetDailyFirstContributions {
+		#[from]
+		source: GetDailyFirstContributionsError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidRepositoryCount{ count } => ApiError::invalid_request(format!("Expected 1 to {MAX_COMPARED_REPOSITORIES} repositories, got {count}")).with_details(json!({ "count": count, "max": MAX_COMPARED_REPOSITORIES })).into_response(),
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::RepositoryArchived{ owner, name } => ApiError::repository_archived(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// JSON payload expected by the endpoint.
+#[derive(Deserialize, ToSchema)]
+pub struct CompareQuery {
+	/// Series are returned in this order.
+	repositories: Vec<ComparedRepository>,
+	/// `["cumulative"]` by default.
+	#[serde(default = "default_metrics")]
+	metrics: Vec<CompareMetric>,
+	#[serde(default)]
+	alignment: Alignment,
+	/// Rescaling of the `normalized` metric, percent of each repository's total by default.
+	#[serde(default)]
+	normalization: Normalization,
+	/// Window and smoothing of the `velocity` and `acceleration` metrics, a 1-day difference by default.
+	#[serde(default)]
+	derivative: DerivativeOptions,
+	/// Filling of the days without data, `zero` by default.
+	#[serde(default)]
+	fill: FillPolicy,
+}
+
+#[derive(Clone, Deserialize, Serialize, ToSchema)]
+pub struct ComparedRepository {
+	pub owner: String,
+	pub name: String,
+}
+
+fn default_metrics() -> Vec<CompareMetric> {
+	vec![CompareMetric::Cumulative]
+}
+
+/// Axum handler: POST /github/repo_stars/compare
+///
+/// The numbers behind a comparison chart, for frontends drawing their own.
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/compare",
+    operation_id = "compare_repositories",
+    tag = "repo_stars",
+    summary = "Compare the star histories of several repositories",
+    request_body = CompareQuery,
+    responses(
+        (status = 200, description = "One aligned series per repository and metric, days in UTC", body = Comparison),
+        (status = 400, description = "No repository or too many"),
+        (status = 422, description = "Invalid derivative window or moving average span"),
+        (status = 404, description = "Repository not in database"),
+        (status = 409, description = "Repository archived"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Json(input): Json<CompareQuery>,
+) -> Result<Json<Comparison>, HandlerError> {
+    let mut errors = ValidationErrors::new();
+    validate_derivative_options(&mut errors, "derivative.", &input.derivative);
+    errors.into_result()?;
+    let with_forks = input.metrics.contains(&CompareMetric::WithForks);
+    let with_traffic = input.metrics.iter().any(CompareMetric::is_traffic);
+    let with_activity = input.metrics.iter().any(CompareMetric::is_activity);
+    let with_contributors = input.metrics.contains(&CompareMetric::Contributors);
+    let repositories = load_histories(&mut conn, tenant_id, input.repositories, with_forks, with_traffic, with_activity, with_contributors, input.fill).await?;
+
+    let options = CompareOptions {
+        alignment: input.alignment,
+        normalization: input.normalization,
+        derivative: input.derivative,
+        fill: input.fill,
+    };
+    Ok(Json(compare_series(&repositories, &input.metrics, &options, Utc::now().date_naive())))
+}
+
+/// Star histories of the repositories, in their order, along with their forks' when `with_forks`
+/// their recorded traffic when `with_traffic`, their issues and pull requests when
+/// `with_activity` and their first contributions when `with_contributors`. Snapshot gaps are
+/// filled as `fill` tells.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn load_histories(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    compared: Vec<ComparedRepository>,
+    with_forks: bool,
+    with_traffic: bool,
+    with_activity: bool,
+    with_contributors: bool,
+    fill: FillPolicy,
+) -> Result<Vec<RepositoryHistory>, HandlerError> {
+    if !(1..=MAX_COMPARED_REPOSITORIES).contains(&compared.len()) {
+        return Err(HandlerError::InvalidRepositoryCount{ count: compared.len() });
+    }
+
+    let mut repositories = Vec::with_capacity(compared.len());
+    for ComparedRepository { owner, name } in compared {
+        let repo = get_repository_by_name(conn, tenant_id, &owner, &name)
+			.await
+			.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+			.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+        if repo.is_archived() {
+            return Err(HandlerError::RepositoryArchived{ owner: repo.owner, name: repo.name });
+        }
+
+        // Repositories in snapshot mode have no stars, their snapshots draw their curve
+        let history = get_star_history(conn, repo.id, fill)
+			.map_err(|source| HandlerError::GetStarHistory{ source })?;
+
+        let with_forks = if with_forks {
+            Some(get_combined_daily_star_count(conn, repo.id)
+				.map_err(|source| HandlerError::GetCombinedDailyStarCount{ source })?)
+        } else {
+            None
+        };
+
+        let traffic = if with_traffic {
+            Some(DailyTraffic {
+                views: get_daily_traffic(conn, repo.id, TrafficKind::Views.as_str())
+					.map_err(|source| HandlerError::GetDailyTraffic{ source })?,
+                clones: get_daily_traffic(conn, repo.id, TrafficKind::Clones.as_str())
+					.map_err(|source| HandlerError::GetDailyTraffic{ source })?,
+            })
+        } else {
+            None
+        };
+
+        let activity = if with_activity {
+            Some(DailyActivity {
+                issues: get_daily_activity(conn, repo.id, ActivityKind::Issues.as_str())
+					.map_err(|source| HandlerError::GetDailyActivity{ source })?,
+                prs: get_daily_activity(conn, repo.id, ActivityKind::PullRequests.as_str())
+					.map_err(|source| HandlerError::GetDailyActivity{ source })?,
+            })
+        } else {
+            None
+        };
+
+        let first_contributions = if with_contributors {
+            Some(get_daily_first_contributions(conn, repo.id)
+				.map_err(|source| HandlerError::GetDailyFirstContributions{ source })?)
+        } else {
+            None
+        };
+
+        repositories.push(RepositoryHistory {
+            repository: format!("{}/{}", repo.owner, repo.name),
+            daily_counts: history.daily_counts,
+            from_snapshots: history.from_snapshots,
+            with_forks,
+            traffic,
+            activity,
+            first_contributions,
+        });
+    }
+    Ok(repositories)
+}
@@ -0,0 +1,3 @@
+pub mod index;
+#[cfg(feature = "charting")]
+pub mod chart;
\ No newline at end of file
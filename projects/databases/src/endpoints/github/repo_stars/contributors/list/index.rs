@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    response::IntoResponse,
+};
+use chrono::NaiveDateTime;
+use diesel::PgConnection;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    contributor::{
+	        models::Contributor,
+	        queries::{get_contributors, GetContributorsError},
+	    },
+	    contributor_tracking::{
+	        models::ContributorTracking,
+	        queries::{get_contributor_tracking, GetContributorTrackingError},
+	    },
+	    repository::{
+	        models::Repository,
+	        queries::{get_repository_by_name, GetRepositoryByNameError},
+	    },
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetContributorTracking: {source}")]
+	GetContributorTracking {
+		#[from]
+		source: GetContributorTrackingError,
+	},
+	#[error("GetContributors: {source}")]
+	GetContributors {
+		#[from]
+		source: GetContributorsError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ContributorResponse {
+	pub login: String,
+	/// UTC, authored date of the earliest commit fetched.
+	pub first_commit_at: NaiveDateTime,
+	/// UTC
+	pub last_commit_at: NaiveDateTime,
+}
+
+impl From<Contributor> for ContributorResponse {
+	fn from(contributor: Contributor) -> Self {
+		Self { login: contributor.login, first_commit_at: contributor.first_commit_at, last_commit_at: contributor.last_commit_at }
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ContributorsResponse {
+	/// Whether the contributors are still tracked on schedule.
+	pub tracking: bool,
+	/// Whether the history was walked back to the first commit, the earliest contributors may be
+	/// missing until then.
+	pub history_complete: bool,
+	/// Earliest commit date reached so far, UTC.
+	pub oldest_commit_at: Option<NaiveDateTime>,
+	/// First to contribute first.
+	pub contributors: Vec<ContributorResponse>,
+}
+
+/// Tracking status and stored contributors of `repo`.
+pub(crate) fn contributors_response(
+	conn: &mut PgConnection,
+	repo: &Repository,
+	tracking: Option<ContributorTracking>,
+) -> Result<ContributorsResponse, HandlerError> {
+    let contributors = get_contributors(conn, repo.id)
+		.map_err(|source| HandlerError::GetContributors{ source })?;
+
+    Ok(ContributorsResponse {
+        tracking: tracking.is_some(),
+        history_complete: tracking.as_ref().is_some_and(|tracking| tracking.history_complete),
+        oldest_commit_at: tracking.and_then(|tracking| tracking.oldest_commit_at),
+        contributors: contributors.into_iter().map(ContributorResponse::from).collect(),
+    })
+}
+
+/// Axum handler: GET /github/repo_stars/contributors/{owner}/{name}
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/contributors/{owner}/{name}",
+    operation_id = "list_contributors",
+    tag = "repo_stars",
+    summary = "Tracked contributors of a repository",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 200, description = "Every contributor stored, first to contribute first", body = ContributorsResponse),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<Json<ContributorsResponse>, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let tracking = get_contributor_tracking(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetContributorTracking{ source })?;
+
+    Ok(Json(contributors_response(&mut conn, &repo, tracking)?))
+}
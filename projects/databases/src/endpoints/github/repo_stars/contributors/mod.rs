@@ -0,0 +1,3 @@
+pub mod list;
+pub mod start;
+pub mod stop;
\ No newline at end of file
@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use interfaces_github_stargazers::forge::Forge;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::contributors::{track_contributors, TrackContributorsError};
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    contributor_tracking::queries::{start_contributor_tracking, StartContributorTrackingError},
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+use crate::endpoints::github::repo_stars::contributors::list::index::{contributors_response, ContributorsResponse, HandlerError as ListError};
+use crate::forges::ForgeClients;
+use crate::privacy::StargazerIds;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("NotOnGitHub: {owner}/{name} is tracked on {forge}")]
+	NotOnGitHub {
+		owner: String,
+		name: String,
+		forge: Forge,
+	},
+	#[error("ForgeNotConfigured: {forge}")]
+	ForgeNotConfigured {
+		forge: Forge,
+	},
+	#[error("StartContributorTracking: {source}")]
+	StartContributorTracking {
+		#[from]
+		source: StartContributorTrackingError,
+	},
+	#[error("TrackContributors: {source}")]
+	TrackContributors {
+		#[from]
+		source: TrackContributorsError,
+	},
+	#[error("RepositoryNotOnGitHub: {owner}/{name}")]
+	RepositoryNotOnGitHub {
+		owner: String,
+		name: String,
+	},
+	#[error("ContributorsResponse: {source}")]
+	ContributorsResponse {
+		#[from]
+		source: ListError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::NotOnGitHub{ owner, name, forge } => ApiError::invalid_request(format!("Contributors are only tracked on GitHub, {owner}/{name} is tracked on {forge}")).with_details(json!({ "forge": forge, "owner": owner, "name": name })).into_response(),
+			HandlerError::ForgeNotConfigured{ forge } => ApiError::forge_not_configured(forge).into_response(),
+			HandlerError::TrackContributors{ source: source @ TrackContributorsError::FetchCommitAuthors { .. } } => ApiError::github(StatusCode::BAD_GATEWAY, source).into_response(),
+			HandlerError::RepositoryNotOnGitHub{ owner, name } => ApiError::github(StatusCode::NOT_FOUND, format!("Repository {owner}/{name} not found on GitHub")).with_details(json!({ "forge": Forge::GitHub, "owner": owner, "name": name })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: POST /github/repo_stars/contributors/{owner}/{name}
+///
+/// Puts the repository in contributors mode and fetches a first batch of its history, the
+/// newest commits. The rest is walked back every `CONTRIBUTOR_TRACKING_INTERVAL_HOURS`, along
+/// with the commits pushed since.
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/contributors/{owner}/{name}",
+    operation_id = "start_contributor_tracking",
+    tag = "repo_stars",
+    summary = "Track the contributors of a repository on schedule",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 200, description = "Contributors of the first batch, or those stored when already tracked", body = ContributorsResponse),
+        (status = 400, description = "Repository not tracked on GitHub"),
+        (status = 404, description = "Repository not in database, or not found on GitHub"),
+        (status = 502, description = "GitHub request failed"),
+        (status = 500, description = "GitHub not configured or database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(forges): Extension<ForgeClients>,
+    Extension(stargazer_ids): Extension<StargazerIds>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<Json<ContributorsResponse>, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+    if repo.forge() != Forge::GitHub {
+        return Err(HandlerError::NotOnGitHub{ owner, name, forge: repo.forge() });
+    }
+    if !forges.is_available(Forge::GitHub) {
+        return Err(HandlerError::ForgeNotConfigured{ forge: Forge::GitHub });
+    }
+
+    let mut tracking = start_contributor_tracking(&mut conn, repo.id)
+		.map_err(|source| HandlerError::StartContributorTracking{ source })?;
+    // Tracked already, the schedule carries on.
+    if tracking.tracked_at.is_none() {
+        tracking = track_contributors(&mut conn, forges.github(), &stargazer_ids, &repo, &tracking)
+			.await
+			.map_err(|source| HandlerError::TrackContributors{ source })?
+			.ok_or(HandlerError::RepositoryNotOnGitHub{ owner, name })?;
+    }
+
+    Ok(Json(contributors_response(&mut conn, &repo, Some(tracking))?))
+}
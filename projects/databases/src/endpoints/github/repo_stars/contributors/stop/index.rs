@@ -0,0 +1,77 @@
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use thiserror::Error;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    contributor_tracking::queries::{stop_contributor_tracking, StopContributorTrackingError},
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("StopContributorTracking: {source}")]
+	StopContributorTracking {
+		#[from]
+		source: StopContributorTrackingError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: DELETE /github/repo_stars/contributors/{owner}/{name}
+///
+/// Stops tracking the contributors, the ones stored are kept.
+#[utoipa::path(
+    delete,
+    path = "/github/repo_stars/contributors/{owner}/{name}",
+    operation_id = "stop_contributor_tracking",
+    tag = "repo_stars",
+    summary = "Stop tracking the contributors of a repository",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 204, description = "Tracking stopped"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<StatusCode, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    stop_contributor_tracking(&mut conn, repo.id)
+		.map_err(|source| HandlerError::StopContributorTracking{ source })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
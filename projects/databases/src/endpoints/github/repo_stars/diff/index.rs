@@ -0,0 +1,225 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::endpoints::github::{
+	    repo_stars::earliest_stargazers::index::StargazerResponse,
+	    repositories::sync_history::index::SyncRunResponse,
+	};
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_stars_first_fetched_between, get_stars_last_fetched_between, GetStarsFirstFetchedBetweenError, GetStarsLastFetchedBetweenError},
+	    sync_job::models::SyncJobStatus,
+	    sync_run::{
+	        models::SyncRun,
+	        queries::{get_job_first_fetched_at, get_sync_run, GetJobFirstFetchedAtError, GetSyncRunError},
+	    },
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Upper bound of `limit`, keeps a single answer reasonably small.
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidLimit: {limit}")]
+	InvalidLimit {
+		limit: i64,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetSyncRun: {source}")]
+	GetSyncRun {
+		#[from]
+		source: GetSyncRunError,
+	},
+	#[error("SyncRunNotFound: {id}")]
+	SyncRunNotFound {
+		id: Uuid,
+	},
+	#[error("SyncRunNotFinished: {id}")]
+	SyncRunNotFinished {
+		id: Uuid,
+	},
+	#[error("SyncRunNotStamped: {id}")]
+	SyncRunNotStamped {
+		id: Uuid,
+	},
+	#[error("SyncRunsOutOfOrder: {from_run} started after {to_run}")]
+	SyncRunsOutOfOrder {
+		from_run: Uuid,
+		to_run: Uuid,
+	},
+	#[error("GetJobFirstFetchedAt: {source}")]
+	GetJobFirstFetchedAt {
+		#[from]
+		source: GetJobFirstFetchedAtError,
+	},
+	#[error("GetStarsFirstFetchedBetween: {source}")]
+	GetStarsFirstFetchedBetween {
+		#[from]
+		source: GetStarsFirstFetchedBetweenError,
+	},
+	#[error("GetStarsLastFetchedBetween: {source}")]
+	GetStarsLastFetchedBetween {
+		#[from]
+		source: GetStarsLastFetchedBetweenError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidLimit{ limit } => ApiError::invalid_request(format!("limit must be between 1 and {MAX_LIMIT}, got {limit}")).with_details(json!({ "limit": limit, "max": MAX_LIMIT })).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::SyncRunNotFound{ id } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::SyncRunNotFound, format!("Sync run {id} not found for this repository")).with_details(json!({ "id": id })).into_response(),
+			HandlerError::SyncRunNotFinished{ id } => ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, ErrorCode::InvalidRequest, format!("Sync run {id} is still running")).with_details(json!({ "id": id })).into_response(),
+			HandlerError::SyncRunNotStamped{ id } => ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, ErrorCode::InvalidRequest, format!("Sync run {id} was recorded before runs could be diffed")).with_details(json!({ "id": id })).into_response(),
+			HandlerError::SyncRunsOutOfOrder{ from_run, to_run } => ApiError::invalid_request(format!("from_run {from_run} must start before to_run {to_run}")).with_details(json!({ "from_run": from_run, "to_run": to_run })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DiffParams {
+	/// Earlier sync run of the repository.
+	from_run: Uuid,
+	/// Later sync run of the repository.
+	to_run: Uuid,
+	/// Stargazers listed per side at most, 100 by default. The counts cover them all.
+	#[serde(default = "default_limit")]
+	limit: i64,
+}
+
+fn default_limit() -> i64 {
+	100
+}
+
+/// Stargazers gained and lost between two sync runs.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StarDiffResponse {
+	pub from_run: SyncRunResponse,
+	pub to_run: SyncRunResponse,
+	/// Stargazers first stored after `from_run` and up to `to_run`, oldest star first.
+	pub added: Vec<StargazerResponse>,
+	pub added_count: i64,
+	/// Stargazers stored at `from_run` that `to_run`'s job no longer fetched, oldest star first.
+	/// Unset unless `to_run` completed, a partial sync can't tell a removed star from an unfetched one.
+	pub removed: Option<Vec<StargazerResponse>>,
+	pub removed_count: Option<i64>,
+}
+
+/// `fetched_at` of `run`, which must be finished and recorded with one.
+fn finished_fetched_at(run: &SyncRun) -> Result<chrono::NaiveDateTime, HandlerError> {
+    if run.finished_at.is_none() {
+        return Err(HandlerError::SyncRunNotFinished{ id: run.id });
+    }
+    run.fetched_at.ok_or(HandlerError::SyncRunNotStamped{ id: run.id })
+}
+
+fn stargazers(listed: Vec<(String, chrono::NaiveDateTime)>) -> Vec<StargazerResponse> {
+    listed
+        .into_iter()
+        .map(|(stargazer, starred_at)| StargazerResponse { stargazer, starred_at })
+        .collect()
+}
+
+/// Axum handler: GET /github/repo_stars/diff/{owner}/{name}?from_run=ID&to_run=ID&limit=N
+///
+/// Audits what a refresh changed: stars carry when they were first and last fetched, and each
+/// run the `fetched_at` it wrote, so stars first stored between the runs were added, and stars
+/// stored at `from_run` that `to_run`'s job didn't fetch again were removed on the forge.
+/// Stars deleted by unstar webhooks or compacted are gone from the database and not listed.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/diff/{owner}/{name}",
+    operation_id = "diff_sync_runs",
+    tag = "repo_stars",
+    summary = "Stargazers added and removed between two sync runs",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        DiffParams,
+    ),
+    responses(
+        (status = 200, description = "Stargazers added and removed between the runs", body = StarDiffResponse),
+        (status = 400, description = "Invalid limit, or from_run not starting before to_run"),
+        (status = 404, description = "Repository not in database, or sync run not one of its"),
+        (status = 422, description = "Sync run still running, or recorded before runs could be diffed"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<DiffParams>,
+) -> Result<Json<StarDiffResponse>, HandlerError> {
+    if !(1..=MAX_LIMIT).contains(&params.limit) {
+        return Err(HandlerError::InvalidLimit{ limit: params.limit });
+    }
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let from_run = get_sync_run(&mut conn, repo.id, params.from_run)
+		.map_err(|source| HandlerError::GetSyncRun{ source })?
+		.ok_or(HandlerError::SyncRunNotFound{ id: params.from_run })?;
+    let to_run = get_sync_run(&mut conn, repo.id, params.to_run)
+		.map_err(|source| HandlerError::GetSyncRun{ source })?
+		.ok_or(HandlerError::SyncRunNotFound{ id: params.to_run })?;
+    if from_run.started_at >= to_run.started_at {
+        return Err(HandlerError::SyncRunsOutOfOrder{ from_run: from_run.id, to_run: to_run.id });
+    }
+    let from_fetched_at = finished_fetched_at(&from_run)?;
+    let to_fetched_at = finished_fetched_at(&to_run)?;
+
+    let (added, added_count) = get_stars_first_fetched_between(&mut conn, repo.id, from_fetched_at, to_fetched_at, params.limit)
+		.map_err(|source| HandlerError::GetStarsFirstFetchedBetween{ source })?;
+
+    let removed = if to_run.status == SyncJobStatus::Completed.as_str() {
+        // Every run of a job fetches part of the stars, the job's first stamp is where its fetching started.
+        let from_job_fetched_at = get_job_first_fetched_at(&mut conn, &from_run)
+			.map_err(|source| HandlerError::GetJobFirstFetchedAt{ source })?
+			.unwrap_or(from_fetched_at);
+        let to_job_fetched_at = get_job_first_fetched_at(&mut conn, &to_run)
+			.map_err(|source| HandlerError::GetJobFirstFetchedAt{ source })?
+			.unwrap_or(to_fetched_at);
+        Some(get_stars_last_fetched_between(&mut conn, repo.id, from_fetched_at, from_job_fetched_at, to_job_fetched_at, params.limit)
+			.map_err(|source| HandlerError::GetStarsLastFetchedBetween{ source })?)
+    } else {
+        None
+    };
+    let (removed, removed_count) = removed.map(|(removed, count)| (stargazers(removed), count)).unzip();
+
+    Ok(Json(StarDiffResponse {
+        from_run: from_run.into(),
+        to_run: to_run.into(),
+        added: stargazers(added),
+        added_count,
+        removed,
+        removed_count,
+    }))
+}
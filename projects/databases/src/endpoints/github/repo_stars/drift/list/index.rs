@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Extension, Json},
+    response::IntoResponse,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::models::Repository,
+	    star_verification::{
+	        models::StarVerification,
+	        queries::{get_latest_star_verifications, GetLatestStarVerificationsError},
+	    },
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetLatestStarVerifications: {source}")]
+	GetLatestStarVerifications {
+		#[from]
+		source: GetLatestStarVerificationsError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		ApiError::internal(self).into_response()
+	}
+}
+
+/// Stored stars of a repository checked against its forge.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StarDriftResponse {
+	pub owner: String,
+	pub name: String,
+	/// Star count reported by the repository's forge, GitHub or not.
+	pub github_stars: i32,
+	pub stored_stars: i32,
+	/// Stored stars minus the forge's. Negative when stars are missing, positive
+	/// when stargazers unstarred since they were stored.
+	pub drift: i32,
+	pub repair_queued: bool,
+	pub checked_at: NaiveDateTime,
+}
+
+impl From<(StarVerification, Repository)> for StarDriftResponse {
+	fn from((verification, repo): (StarVerification, Repository)) -> Self {
+		Self {
+			owner: repo.owner,
+			name: repo.name,
+			github_stars: verification.github_stars,
+			stored_stars: verification.stored_stars,
+			drift: verification.drift(),
+			repair_queued: verification.repair_queued,
+			checked_at: verification.checked_at,
+		}
+	}
+}
+
+/// Largest drift first, then by repository.
+pub(crate) fn sort_by_drift(drifts: &mut [StarDriftResponse]) {
+    drifts.sort_by(|a, b| {
+        b.drift.abs()
+            .cmp(&a.drift.abs())
+            .then_with(|| (&a.owner, &a.name).cmp(&(&b.owner, &b.name)))
+    });
+}
+
+/// Axum handler: GET /github/repo_stars/drift
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/drift",
+    operation_id = "list_star_drift",
+    tag = "repo_stars",
+    summary = "Latest star count verification of every repository",
+    responses(
+        (status = 200, description = "Latest verification per verified repository, largest drift first", body = Vec<StarDriftResponse>),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+) -> Result<Json<Vec<StarDriftResponse>>, HandlerError> {
+    let verifications = get_latest_star_verifications(&mut conn, tenant_id)
+		.map_err(|source| HandlerError::GetLatestStarVerifications{ source })?;
+
+    let mut drifts: Vec<StarDriftResponse> = verifications.into_iter().map(StarDriftResponse::from).collect();
+    sort_by_drift(&mut drifts);
+
+    Ok(Json(drifts))
+}
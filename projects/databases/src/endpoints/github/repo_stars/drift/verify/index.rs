@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Extension, Json},
+    response::IntoResponse,
+};
+use interfaces_github_stargazers::forge::Forge;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{list_repositories, ListRepositoriesError},
+	    star_verification::{
+	        models::NewStarVerification,
+	        queries::{insert_star_verification, InsertStarVerificationError},
+	    },
+	    sync_run::models::SyncTrigger,
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+use crate::endpoints::github::repo_stars::drift::list::index::{sort_by_drift, StarDriftResponse};
+use crate::forges::{ForgeClientError, ForgeClients};
+use crate::live::PageSink;
+use crate::runtime_config::RuntimeConfig;
+use crate::sync_queue::{SyncPriority, SyncQueue, SyncTask};
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("ForgeNotConfigured: {forge}")]
+	ForgeNotConfigured {
+		forge: Forge,
+	},
+	#[error("ListRepositories: {source}")]
+	ListRepositories {
+		#[from]
+		source: ListRepositoriesError,
+	},
+	#[error("FetchStargazerCount: {source}")]
+	FetchStargazerCount {
+		#[from]
+		source: ForgeClientError,
+	},
+	#[error("InsertStarVerification: {source}")]
+	InsertStarVerification {
+		#[from]
+		source: InsertStarVerificationError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::ForgeNotConfigured{ forge } => ApiError::forge_not_configured(forge).into_response(),
+			HandlerError::FetchStargazerCount{ source } => ApiError::github(source.status_code(), source).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// JSON payload expected by the endpoint.
+#[derive(Deserialize, ToSchema)]
+pub struct VerifyQuery {
+	/// Only repositories carrying this tag, every tracked one when unset.
+	tag: Option<String>,
+	/// Queues a sync of the repositories missing more than this many stars, none when unset.
+	repair_threshold: Option<u32>,
+	/// Position of the repair syncs in the sync queue, `low` by default.
+	#[serde(default = "default_priority")]
+	priority: SyncPriority,
+}
+
+fn default_priority() -> SyncPriority {
+	SyncPriority::Low
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct VerificationReport {
+	/// Largest drift first.
+	pub verified: Vec<StarDriftResponse>,
+	/// Tracked repositories their forge doesn't know anymore, e.g. deleted or renamed.
+	pub not_found: Vec<String>,
+}
+
+/// Axum handler: POST /github/repo_stars/drift/verify
+///
+/// Compares the stored star count of every tracked repository with the one
+/// its forge reports, one cheap query each, and records the outcome.
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/drift/verify",
+    operation_id = "verify_star_counts",
+    tag = "repo_stars",
+    summary = "Check stored star counts against their forge",
+    request_body = VerifyQuery,
+    responses(
+        (status = 200, description = "Drift of every verified repository", body = VerificationReport),
+        (status = 502, description = "Forge request failed"),
+        (status = 500, description = "Forge of a repository not configured or database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(forges): Extension<ForgeClients>,
+    Extension(queue): Extension<SyncQueue>,
+    Extension(config): Extension<RuntimeConfig>,
+    Json(input): Json<VerifyQuery>,
+) -> Result<Json<VerificationReport>, HandlerError> {
+    let tag = input.tag.map(|tag| tag.to_ascii_lowercase());
+    let repositories = list_repositories(&mut conn, tenant_id, tag.as_deref())
+		.map_err(|source| HandlerError::ListRepositories{ source })?;
+
+    if let Some(forge) = repositories.iter().map(|repo| repo.forge()).find(|forge| !forges.is_available(*forge)) {
+        return Err(HandlerError::ForgeNotConfigured{ forge });
+    }
+
+    let mut report = VerificationReport { verified: Vec::new(), not_found: Vec::new() };
+    for repo in repositories {
+        let Some(forge_stars) = forges.stargazer_count(repo.forge(), &repo.owner, &repo.name)
+			.await
+			.map_err(|source| HandlerError::FetchStargazerCount{ source })?
+        else {
+            report.not_found.push(format!("{}/{}", repo.owner, repo.name));
+            continue;
+        };
+
+        let stored_stars = repo.total_stars;
+        let forge_stars = i64::from(forge_stars);
+        // Syncs only add stars, unstarred ones stored too many can't be repaired that way.
+        let repair_queued = input.repair_threshold
+            .is_some_and(|threshold| forge_stars - stored_stars > i64::from(threshold));
+
+        let new_verification = NewStarVerification {
+            id: Uuid::new_v4(),
+            repository_id: repo.id,
+            github_stars: forge_stars as i32,
+            stored_stars: stored_stars as i32,
+            repair_queued,
+        };
+        let verification = insert_star_verification(&mut conn, &new_verification)
+			.map_err(|source| HandlerError::InsertStarVerification{ source })?;
+
+        if repair_queued {
+            let task = SyncTask::Repository { tenant_id, forge: repo.forge(), owner: repo.owner.clone(), name: repo.name.clone(), batch_id: None, trigger: SyncTrigger::Repair, forks_min_stars: None, pages: PageSink::default(), limits: config.sync_limits() };
+            // The repair is tracked through its sync job, not the task outcome.
+            drop(queue.enqueue(task, input.priority));
+        }
+
+        report.verified.push(StarDriftResponse::from((verification, repo)));
+    }
+
+    sort_by_drift(&mut report.verified);
+    Ok(Json(report))
+}
@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_earliest_stargazers, GetEarliestStargazersError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Upper bound of `limit`, keeps a single answer reasonably small.
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetEarliestStargazers: {source}")]
+	GetEarliestStargazers {
+		#[from]
+		source: GetEarliestStargazersError,
+	},
+	#[error("InvalidLimit: {limit}")]
+	InvalidLimit {
+		limit: i64,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::InvalidLimit{ limit } => ApiError::invalid_request(format!("limit must be between 1 and {MAX_LIMIT}, got {limit}")).with_details(json!({ "limit": limit, "max": MAX_LIMIT })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EarliestParams {
+	#[serde(default = "default_limit")]
+	limit: i64,
+}
+
+fn default_limit() -> i64 {
+	100
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StargazerResponse {
+	pub stargazer: String,
+	pub starred_at: NaiveDateTime,
+}
+
+/// Axum handler: GET /github/repo_stars/earliest_stargazers/{owner}/{name}?limit=N
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/earliest_stargazers/{owner}/{name}",
+    operation_id = "list_earliest_stargazers",
+    tag = "repo_stars",
+    summary = "Earliest stargazers",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        EarliestParams,
+    ),
+    responses(
+        (status = 200, description = "First stargazers, oldest first", body = Vec<StargazerResponse>),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 400, description = "Invalid limit"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<EarliestParams>,
+) -> Result<Json<Vec<StargazerResponse>>, HandlerError> {
+    if !(1..=MAX_LIMIT).contains(&params.limit) {
+        return Err(HandlerError::InvalidLimit{ limit: params.limit });
+    }
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let stargazers = get_earliest_stargazers(&mut conn, repo.id, params.limit)
+		.map_err(|source| HandlerError::GetEarliestStargazers{ source })?;
+
+    Ok(Json(
+        stargazers
+            .into_iter()
+            .map(|(stargazer, starred_at)| StargazerResponse { stargazer, starred_at })
+            .collect(),
+    ))
+}
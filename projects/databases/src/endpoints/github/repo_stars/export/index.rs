@@ -0,0 +1,204 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Extension, Path, Query},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::warn;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{star_pages, GetStarsPageError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Stars read per query.
+const PAGE_SIZE: i64 = 5000;
+/// Encoded pages waiting for the client at most, reading pauses past that.
+const BUFFERED_PAGES: usize = 4;
+const CSV_HEADER: &[u8] = b"stargazer,starred_at\n";
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Failure once the answer has started, which ends it early.
+#[derive(Debug, Error)]
+pub enum ExportStreamError {
+	#[error("GetStarsPage: {source}")]
+	GetStarsPage {
+		#[from]
+		source: GetStarsPageError,
+	},
+	#[error("CsvWrite: {source}")]
+	CsvWrite {
+		#[from]
+		source: csv::Error,
+	},
+	#[error("CsvFlush: {source}")]
+	CsvFlush {
+		source: std::io::Error,
+	},
+	#[error("JsonWrite: {source}")]
+	JsonWrite {
+		#[from]
+		source: serde_json::Error,
+	},
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+	#[default]
+	Csv,
+	Ndjson,
+}
+
+impl ExportFormat {
+	fn content_type(self) -> &'static str {
+		match self {
+			ExportFormat::Csv => "text/csv",
+			ExportFormat::Ndjson => "application/x-ndjson",
+		}
+	}
+
+	fn extension(self) -> &'static str {
+		match self {
+			ExportFormat::Csv => "csv",
+			ExportFormat::Ndjson => "ndjson",
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportParams {
+	/// `csv` with a header row, or `ndjson`, both readable by the import.
+	#[serde(default)]
+	format: ExportFormat,
+}
+
+#[derive(Serialize)]
+struct ExportedStar<'a> {
+	stargazer: &'a str,
+	starred_at: DateTime<Utc>,
+}
+
+/// Axum handler: GET /github/repo_stars/export/{owner}/{name}?format=csv
+///
+/// Streams every stored star of the repository, oldest first, in the format the import
+/// reads. Stars are read a keyset page at a time on a blocking thread and sent as they
+/// are encoded, so memory stays flat whatever the repository's size and a slow client
+/// pauses the reads. A database failure midway cuts the answer short rather than
+/// completing it, the status having been sent already.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/export/{owner}/{name}",
+    operation_id = "export_stars",
+    tag = "repo_stars",
+    summary = "Stream every stored star as CSV or NDJSON",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        ExportParams,
+    ),
+    responses(
+        (status = 200, description = "`stargazer,starred_at` CSV, or NDJSON objects with the same fields, oldest star first", body = String, content_type = "text/csv"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let format = params.format;
+    let (sender, mut receiver) = mpsc::channel::<Result<Bytes, ExportStreamError>>(BUFFERED_PAGES);
+    let repository_id = repo.id;
+    tokio::task::spawn_blocking(move || {
+        if matches!(format, ExportFormat::Csv) && sender.blocking_send(Ok(Bytes::from_static(CSV_HEADER))).is_err() {
+            return;
+        }
+        for page in star_pages(&mut conn, repository_id, PAGE_SIZE) {
+            let chunk = page
+                .map_err(ExportStreamError::from)
+                .and_then(|page| encode_page(format, &page));
+            if let Err(error) = &chunk {
+                warn!(%repository_id, %error, "star export cut short");
+            }
+            let failed = chunk.is_err();
+            // An error send fails too when the client is gone, nothing left to do either way
+            if sender.blocking_send(chunk).is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    let filename = format!("{}-{}-stars.{}", repo.owner, repo.name, format.extension());
+    let mut response = Body::from_stream(stream::poll_fn(move |cx| receiver.poll_recv(cx))).into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{filename}\"")) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    Ok(response)
+}
+
+/// One page of stars in the export format, the CSV header excluded.
+fn encode_page(format: ExportFormat, page: &[(String, NaiveDateTime)]) -> Result<Bytes, ExportStreamError> {
+    let rows = page.iter().map(|(stargazer, starred_at)| ExportedStar { stargazer, starred_at: starred_at.and_utc() });
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            let bytes = writer.into_inner()
+                .map_err(|error| ExportStreamError::CsvFlush{ source: error.into_error() })?;
+            Ok(Bytes::from(bytes))
+        }
+        ExportFormat::Ndjson => {
+            let mut bytes = Vec::new();
+            for row in rows {
+                serde_json::to_writer(&mut bytes, &row)?;
+                bytes.push(b'\n');
+            }
+            Ok(Bytes::from(bytes))
+        }
+    }
+}
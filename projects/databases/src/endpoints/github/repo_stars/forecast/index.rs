@@ -0,0 +1,115 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use utoipa::IntoParams;
+
+use crate::bots::BotFilter;
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::analytics::{
+    forecast::{forecast, Forecast, ForecastModel},
+    series::cumulative_series,
+};
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_filtered_daily_star_count, GetDailyStarCountError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Days ahead reported by every forecast.
+const HORIZONS: [u32; 3] = [30, 90, 365];
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
+		#[from]
+		source: GetDailyStarCountError,
+	},
+	#[error("NotEnoughHistory: {owner}/{name}")]
+	NotEnoughHistory {
+		owner: String,
+		name: String,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::NotEnoughHistory{ owner, name } => ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, ErrorCode::NotEnoughHistory, format!("Repository {owner}/{name} needs at least two days of star history")).with_details(json!({ "owner": owner, "name": name })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ForecastParams {
+	#[serde(default)]
+	model: ForecastModel,
+	/// Trailing days of history the model is fitted on.
+	#[serde(default = "default_window")]
+	window: usize,
+}
+
+fn default_window() -> usize {
+	90
+}
+
+/// Axum handler: GET /github/repo_stars/forecast/{owner}/{name}
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/forecast/{owner}/{name}",
+    operation_id = "forecast_stars",
+    tag = "repo_stars",
+    summary = "Forecast star growth",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        ForecastParams,
+        BotFilter,
+    ),
+    responses(
+        (status = 200, description = "Projected star counts", body = Forecast),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 404, description = "Repository not in database"),
+        (status = 422, description = "Not enough history to fit the model"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<ForecastParams>,
+    Query(bots): Query<BotFilter>,
+) -> Result<Json<Forecast>, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+
+    let daily_counts = get_filtered_daily_star_count(&mut conn, repo.id, bots.exclude_bots)
+		.map_err(|source| HandlerError::GetDailyStarCount{ source })?;
+
+    forecast(&cumulative_series(&daily_counts), params.model, params.window, &HORIZONS)
+		.map(Json)
+		.ok_or(HandlerError::NotEnoughHistory{ owner, name })
+}
@@ -0,0 +1,203 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{Datelike, Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::ApiError;
+use crate::chart::{render_heatmap_svg, HeatmapWeek, ThemeName};
+use crate::db::{
+	    daily_star_count::queries::{get_weekday_stars, GetWeekdayStarsError},
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+use crate::render_pool::{RenderError, RenderPool};
+
+/// Weeks shown by default, a year as on GitHub profiles.
+const DEFAULT_WEEKS: u32 = 52;
+/// Weeks shown at most, five years.
+const MAX_WEEKS: u32 = 260;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidWeeks: {weeks}")]
+	InvalidWeeks {
+		weeks: u32,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("RepositoryArchived: {owner}/{name}")]
+	RepositoryArchived {
+		owner: String,
+		name: String,
+	},
+	#[error("GetWeekdayStars: {source}")]
+	GetWeekdayStars {
+		#[from]
+		source: GetWeekdayStarsError,
+	},
+	#[error("Render: {source}")]
+	Render {
+		#[from]
+		source: RenderError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidWeeks{ weeks } => ApiError::invalid_request(format!("weeks must be between 1 and {MAX_WEEKS}, got {weeks}")).with_details(json!({ "weeks": weeks, "min": 1, "max": MAX_WEEKS })).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::RepositoryArchived{ owner, name } => ApiError::repository_archived(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HeatmapFormat {
+	#[default]
+	Json,
+	/// Grid of shaded cells, one column per week.
+	Svg,
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HeatmapParams {
+	/// Weeks shown, up to the one containing `as_of`, 52 by default.
+	weeks: Option<u32>,
+	/// Last day counted, today (UTC) by default.
+	as_of: Option<NaiveDate>,
+	#[serde(default)]
+	format: HeatmapFormat,
+	/// Colors of the SVG heatmap.
+	#[serde(default)]
+	theme: ThemeName,
+}
+
+/// Stars of every day of a week.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct WeekRow {
+	/// Monday of the week.
+	pub start: NaiveDate,
+	/// Seven entries, Monday first, `null` for days after `as_of`.
+	pub stars: Vec<Option<i64>>,
+}
+
+/// Daily stars of a repository laid out by week and weekday.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StarHeatmap {
+	pub as_of: NaiveDate,
+	pub total_stars: i64,
+	/// Stars of the busiest day.
+	pub max_day_stars: i64,
+	/// Stars received on each weekday over every week, Monday first.
+	pub weekday_totals: Vec<i64>,
+	/// Oldest first.
+	pub weeks: Vec<WeekRow>,
+}
+
+/// Axum handler: GET /github/repo_stars/heatmap/{owner}/{name}
+///
+/// Not behind the ETag middleware, the default `as_of` changes with the date.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/heatmap/{owner}/{name}",
+    operation_id = "star_heatmap",
+    tag = "repo_stars",
+    summary = "Lay out daily stars by week and weekday",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        HeatmapParams,
+    ),
+    responses(
+        (status = 200, description = "Stars of every day of the last weeks, days in UTC, or with `format=svg` a heatmap of them", content(
+            (StarHeatmap = "application/json"),
+            (String = "image/svg+xml"),
+        )),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 400, description = "Invalid week count"),
+        (status = 404, description = "Repository not in database"),
+        (status = 409, description = "Repository archived"),
+        (status = 500, description = "Database or rendering failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(renders): Extension<RenderPool>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<HeatmapParams>,
+) -> Result<Response, HandlerError> {
+    let weeks = params.weeks.unwrap_or(DEFAULT_WEEKS);
+    if !(1..=MAX_WEEKS).contains(&weeks) {
+        return Err(HandlerError::InvalidWeeks{ weeks });
+    }
+    let as_of = params.as_of.unwrap_or_else(|| Utc::now().date_naive());
+    // Monday of the first week, so it is whole.
+    let from = as_of - Days::new(u64::from(as_of.weekday().num_days_from_monday()) + 7 * u64::from(weeks - 1));
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+    if repo.is_archived() {
+        return Err(HandlerError::RepositoryArchived{ owner: repo.owner, name: repo.name });
+    }
+
+    let rows = get_weekday_stars(&mut conn, repo.id, from, as_of)
+		.map_err(|source| HandlerError::GetWeekdayStars{ source })?;
+    drop(conn);
+
+    let mut weekday_totals = vec![0; 7];
+    let mut max_day_stars = 0;
+    for row in &rows {
+        for (weekday, stars) in row.stars.iter().enumerate() {
+            let stars = stars.unwrap_or(0);
+            weekday_totals[weekday] += stars;
+            max_day_stars = max_day_stars.max(stars);
+        }
+    }
+    let heatmap = StarHeatmap {
+        as_of,
+        total_stars: weekday_totals.iter().sum(),
+        max_day_stars,
+        weekday_totals,
+        weeks: rows.into_iter().map(|row| WeekRow { start: row.week_start, stars: row.stars }).collect(),
+    };
+
+    Ok(match params.format {
+        HeatmapFormat::Json => Json(heatmap).into_response(),
+        HeatmapFormat::Svg => {
+            let weeks: Vec<HeatmapWeek> = heatmap
+                .weeks
+                .into_iter()
+                .map(|week| HeatmapWeek { start: week.start, days: week.stars })
+                .collect();
+            let title = format!("{}/{} stars by weekday", repo.owner, repo.name);
+            let theme = params.theme.theme();
+            let svg = renders.run(move || render_heatmap_svg(&title, &weeks, &theme))
+                .await
+                .map_err(|source| HandlerError::Render{ source })?;
+            ([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()
+        }
+    })
+}
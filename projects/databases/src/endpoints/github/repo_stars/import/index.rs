@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+
+use axum::{
+    body::Bytes,
+    extract::{Extension, Json, Query},
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use interfaces_github_stargazers::forge::Forge;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::endpoints::github::repo_stars::update::index::{record_milestones, RecordMilestonesError};
+use crate::privacy::StargazerIds;
+use crate::runtime_config::RuntimeConfig;
+use crate::db::{
+	    daily_star_count::queries::{refresh_daily_star_counts, RefreshDailyStarCountsError},
+	    repository::{
+	        models::NewRepository,
+	        queries::{get_repository_by_name, insert_repository, GetRepositoryByNameError, InsertRepositoryError},
+	    },
+	    star::{
+	        models::NewStar,
+	        queries::{
+	            insert_missing_stars, InsertMissingStarsError, StarInsertConfig, StarInsertMethod,
+	            MAX_INSERT_CHUNK_SIZE,
+	        },
+	    },
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+use crate::validation::{validate_repository, ValidationErrors};
+
+/// Largest accepted upload, about a million CSV rows.
+pub const MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("UnsupportedContentType: {content_type}")]
+	UnsupportedContentType {
+		content_type: String,
+	},
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+	#[error("InvalidChunkSize: {chunk_size}")]
+	InvalidChunkSize {
+		chunk_size: usize,
+	},
+	#[error("InvalidRow: line {line}: {message}")]
+	InvalidRow {
+		line: usize,
+		message: String,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("InsertRepository: {source}")]
+	InsertRepository {
+		#[from]
+		source: InsertRepositoryError,
+	},
+	#[error("InsertMissingStars: {source}")]
+	InsertMissingStars {
+		#[from]
+		source: InsertMissingStarsError,
+	},
+	#[error("RefreshDailyStarCounts: {source}")]
+	RefreshDailyStarCounts {
+		#[from]
+		source: RefreshDailyStarCountsError,
+	},
+	#[error("RecordMilestones: {source}")]
+	RecordMilestones {
+		#[from]
+		source: RecordMilestonesError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::UnsupportedContentType{ content_type } => ApiError::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, ErrorCode::UnsupportedMediaType, format!("Expected text/csv or application/x-ndjson, got {content_type}")).with_details(json!({ "content_type": content_type })).into_response(),
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			HandlerError::InvalidChunkSize{ chunk_size } => ApiError::invalid_request(format!("chunk_size must be between 1 and {MAX_INSERT_CHUNK_SIZE}, got {chunk_size}")).with_details(json!({ "chunk_size": chunk_size, "max": MAX_INSERT_CHUNK_SIZE })).into_response(),
+			HandlerError::InvalidRow{ line, message } => ApiError::invalid_request(format!("Line {line}: {message}")).with_details(json!({ "line": line })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportParams {
+	owner: String,
+	name: String,
+	/// Rows per statement, `STAR_INSERT_CHUNK_SIZE` (1000) by default.
+	chunk_size: Option<usize>,
+	/// `copy` loads the rows with PostgreSQL's COPY, faster for large dumps; `auto` picks it
+	/// once the dump reaches `STAR_COPY_THRESHOLD` rows.
+	#[serde(default)]
+	method: ImportMethod,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMethod {
+	#[default]
+	Auto,
+	Insert,
+	Copy,
+}
+
+impl From<ImportMethod> for StarInsertMethod {
+	fn from(method: ImportMethod) -> Self {
+		match method {
+			ImportMethod::Auto => StarInsertMethod::Auto,
+			ImportMethod::Insert => StarInsertMethod::Insert,
+			ImportMethod::Copy => StarInsertMethod::Copy,
+		}
+	}
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct ImportSummary {
+	/// Stargazers read from the upload, each counted once.
+	pub stargazers: usize,
+	pub inserted: usize,
+	/// Stargazers already stored, left as they were.
+	pub already_stored: usize,
+	/// Stargazers of compacted days, already counted in the repository's daily aggregates.
+	pub compacted: usize,
+	/// NDJSON lines that aren't stars of the repository, e.g. other GH Archive events.
+	pub ignored: usize,
+}
+
+/// CSV row, `login` and `date` are accepted for exports naming the columns that way.
+#[derive(Deserialize)]
+struct CsvRow {
+	#[serde(alias = "login")]
+	stargazer: String,
+	#[serde(alias = "date")]
+	starred_at: String,
+}
+
+/// NDJSON line, either a plain star or a GH Archive event.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NdjsonRow {
+	Star {
+		#[serde(alias = "login")]
+		stargazer: String,
+		starred_at: String,
+	},
+	GhArchiveEvent {
+		#[serde(rename = "type")]
+		kind: String,
+		actor: GhArchiveActor,
+		repo: GhArchiveRepo,
+		created_at: String,
+	},
+}
+
+#[derive(Deserialize)]
+struct GhArchiveActor {
+	login: String,
+}
+
+#[derive(Deserialize)]
+struct GhArchiveRepo {
+	/// `owner/name`
+	name: String,
+}
+
+/// Axum handler: POST /github/repo_stars/import?owner=O&name=N
+///
+/// Seeds a repository's stars from data gathered elsewhere, without calling GitHub.
+/// `chunk_size` and `method` tune how the rows are written, large dumps going through COPY
+/// unless `method=insert` is asked for.
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/import",
+    operation_id = "import_stars",
+    tag = "repo_stars",
+    summary = "Import stars from a CSV or NDJSON dump",
+    params(
+        ImportParams,
+    ),
+    request_body(
+        description = "`text/csv` with `stargazer,starred_at` columns, or `application/x-ndjson` of `{\"stargazer\", \"starred_at\"}` objects or GH Archive events",
+        content = String,
+        content_type = "text/csv",
+    ),
+    responses(
+        (status = 200, description = "Import done", body = ImportSummary),
+        (status = 400, description = "Invalid row or chunk size, nothing imported"),
+        (status = 413, description = "Upload too large"),
+        (status = 415, description = "Neither CSV nor NDJSON"),
+        (status = 422, description = "Owner or name breaking GitHub's naming rules"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(stargazer_ids): Extension<StargazerIds>,
+    Extension(runtime_config): Extension<RuntimeConfig>,
+    Query(params): Query<ImportParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ImportSummary>, HandlerError> {
+    let mut errors = ValidationErrors::new();
+    validate_repository(&mut errors, "", &params.owner, &params.name);
+    errors.into_result()?;
+
+    let insert_config = runtime_config.star_insert();
+    let chunk_size = params.chunk_size.unwrap_or(insert_config.chunk_size);
+    if !(1..=MAX_INSERT_CHUNK_SIZE).contains(&chunk_size) {
+        return Err(HandlerError::InvalidChunkSize{ chunk_size });
+    }
+
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    // Parameters such as `; charset=utf-8` don't matter, both formats are UTF-8.
+    let (starred, ignored) = match content_type.split(';').next().unwrap_or_default().trim() {
+        "text/csv" => (parse_csv(&body)?, 0),
+        "application/x-ndjson" | "application/jsonl" => parse_ndjson(&body, &params.owner, &params.name)?,
+        other => return Err(HandlerError::UnsupportedContentType{ content_type: other.into() }),
+    };
+
+    let repo = match get_repository_by_name(&mut conn, tenant_id, &params.owner, &params.name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+    {
+        Some(repo) => repo,
+        // Metadata stays empty until GitHub is queried, the import itself doesn't.
+        None => insert_repository(&mut conn, &NewRepository {
+            id: Uuid::new_v4(),
+            owner: &params.owner,
+            name: &params.name,
+            description: None,
+            primary_language: None,
+            license: None,
+            topics: Vec::new(),
+            github_created_at: None,
+            tenant_id,
+            github_node_id: None,
+            forge: Forge::GitHub.as_str(),
+        })
+		.map_err(|source| HandlerError::InsertRepository{ source })?,
+    };
+
+    let starred = stored_ids(starred, &stargazer_ids);
+    let fetched_at = Utc::now().naive_utc();
+    let new_stars: Vec<NewStar> = starred
+        .iter()
+        .filter(|(_, starred_at)| !repo.is_compacted(**starred_at))
+        .map(|(stargazer, starred_at)| NewStar { repository_id: repo.id, stargazer, starred_at: *starred_at, fetched_at, first_fetched_at: fetched_at, suspected_bot: false })
+        .collect();
+
+    let config = StarInsertConfig { chunk_size, method: params.method.into(), copy_threshold: insert_config.copy_threshold };
+    let inserted = insert_missing_stars(&mut conn, &new_stars, &config)
+		.map_err(|source| HandlerError::InsertMissingStars{ source })?;
+
+    if let Some(since) = new_stars.iter().map(|star| star.starred_at.date()).min() {
+        refresh_daily_star_counts(&mut conn, repo.id, Some(since))
+			.map_err(|source| HandlerError::RefreshDailyStarCounts{ source })?;
+    }
+
+    record_milestones(&mut conn, repo.id)
+		.map_err(|source| HandlerError::RecordMilestones{ source })?;
+
+    Ok(Json(ImportSummary {
+        stargazers: starred.len(),
+        inserted,
+        already_stored: new_stars.len() - inserted,
+        compacted: starred.len() - new_stars.len(),
+        ignored,
+    }))
+}
+
+/// Stars of a CSV upload with a header row, by stargazer.
+fn parse_csv(body: &[u8]) -> Result<HashMap<String, NaiveDateTime>, HandlerError> {
+    let mut starred = HashMap::new();
+    for (index, row) in csv::Reader::from_reader(body).deserialize::<CsvRow>().enumerate() {
+        // Line 1 is the header.
+        let line = index + 2;
+        let row = row.map_err(|error| HandlerError::InvalidRow{ line, message: error.to_string() })?;
+        add_star(&mut starred, line, row.stargazer, &row.starred_at)?;
+    }
+    Ok(starred)
+}
+
+/// Stars of an NDJSON upload by stargazer, along with the number of lines that aren't stars of `owner/name`.
+fn parse_ndjson(body: &[u8], owner: &str, name: &str) -> Result<(HashMap<String, NaiveDateTime>, usize), HandlerError> {
+    let repo = format!("{owner}/{name}");
+    let (mut starred, mut ignored) = (HashMap::new(), 0);
+    for (index, text) in body.split(|&byte| byte == b'\n').enumerate() {
+        let line = index + 1;
+        if text.trim_ascii().is_empty() {
+            continue;
+        }
+
+        let row = serde_json::from_slice::<NdjsonRow>(text)
+            .map_err(|_| HandlerError::InvalidRow{ line, message: "expected a stargazer and starred_at, or a GH Archive event".into() })?;
+        match row {
+            NdjsonRow::Star { stargazer, starred_at } => add_star(&mut starred, line, stargazer, &starred_at)?,
+            // GitHub names are case-insensitive, the dump may not match the requested case.
+            NdjsonRow::GhArchiveEvent { kind, actor, repo: event_repo, created_at } if kind == "WatchEvent" && event_repo.name.eq_ignore_ascii_case(&repo) => {
+                add_star(&mut starred, line, actor.login, &created_at)?
+            }
+            NdjsonRow::GhArchiveEvent { .. } => ignored += 1,
+        }
+    }
+    Ok((starred, ignored))
+}
+
+/// Stars keyed by the stored identifier of their stargazer. Hashes ignore case, so case variants of a login merge.
+fn stored_ids(starred: HashMap<String, NaiveDateTime>, ids: &StargazerIds) -> HashMap<String, NaiveDateTime> {
+    if !ids.is_hashed() {
+        return starred;
+    }
+    let mut stored = HashMap::with_capacity(starred.len());
+    for (login, starred_at) in starred {
+        stored
+            .entry(ids.stargazer_id(&login).into_owned())
+            .and_modify(|earliest: &mut NaiveDateTime| *earliest = (*earliest).min(starred_at))
+            .or_insert(starred_at);
+    }
+    stored
+}
+
+/// Records the star, keeping the earliest date when the stargazer appears twice, e.g. after unstarring.
+fn add_star(starred: &mut HashMap<String, NaiveDateTime>, line: usize, stargazer: String, starred_at: &str) -> Result<(), HandlerError> {
+    let stargazer = stargazer.trim();
+    if stargazer.is_empty() || stargazer.contains(char::is_whitespace) {
+        return Err(HandlerError::InvalidRow{ line, message: format!("invalid stargazer {stargazer:?}") });
+    }
+    let starred_at = parse_timestamp(starred_at)
+        .ok_or_else(|| HandlerError::InvalidRow{ line, message: format!("invalid starred_at {starred_at:?}") })?;
+
+    starred
+        .entry(stargazer.to_string())
+        .and_modify(|earliest: &mut NaiveDateTime| *earliest = (*earliest).min(starred_at))
+        .or_insert(starred_at);
+    Ok(())
+}
+
+/// RFC 3339 timestamps are converted to UTC, naive ones taken as UTC and bare dates as midnight.
+fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f"))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|day| day.and_hms_opt(0, 0, 0)))
+}
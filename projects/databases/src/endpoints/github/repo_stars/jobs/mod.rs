@@ -0,0 +1,4 @@
+pub mod purge;
+pub mod resume;
+pub mod queue;
+pub mod status;
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Extension, Json, Query},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::PgPool;
+use crate::endpoints::tenant::TenantId;
+use crate::job_retention::{purge_cutoff, purge_old_sync_jobs, PurgeOldSyncJobsError};
+use crate::runtime_config::RuntimeConfig;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("PurgeOldSyncJobs: {source}")]
+	PurgeOldSyncJobs {
+		#[from]
+		source: PurgeOldSyncJobsError,
+	},
+	#[error("PurgeTaskJoin: {source}")]
+	PurgeTaskJoin {
+		#[from]
+		source: tokio::task::JoinError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		ApiError::internal(self).into_response()
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PurgeParams {
+	/// Jobs finished longer ago than this are deleted, `0` for every finished job.
+	/// `SYNC_JOB_RETENTION_HOURS` by default.
+	older_than_hours: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct PurgeResponse {
+	/// Sync jobs deleted.
+	pub purged: usize,
+}
+
+/// Axum handler: DELETE /github/repo_stars/jobs
+///
+/// Deletes the tenant's sync jobs finished before the cutoff right away, rather than
+/// waiting for the scheduled cleanup. Pending and running jobs are left alone, batch
+/// jobs until their whole batch is over, and the runs of deleted jobs stay in the
+/// sync history.
+#[utoipa::path(
+    delete,
+    path = "/github/repo_stars/jobs",
+    operation_id = "purge_sync_jobs",
+    tag = "repo_stars",
+    summary = "Delete finished sync jobs",
+    params(PurgeParams),
+    responses(
+        (status = 200, description = "Number of sync jobs deleted", body = PurgeResponse),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(config): Extension<RuntimeConfig>,
+    Query(params): Query<PurgeParams>,
+) -> Result<Json<PurgeResponse>, HandlerError> {
+    let max_age = params.older_than_hours
+        .map_or(config.sync_job_retention().max_age, |hours| Duration::from_secs(hours.saturating_mul(3600)));
+    let before = purge_cutoff(Utc::now().naive_utc(), max_age);
+
+    let purged = tokio::task::spawn_blocking(move || purge_old_sync_jobs(&pool, before, Some(tenant_id)))
+		.await
+		.map_err(|source| HandlerError::PurgeTaskJoin{ source })?
+		.map_err(|source| HandlerError::PurgeOldSyncJobs{ source })?;
+
+    Ok(Json(PurgeResponse { purged }))
+}
@@ -0,0 +1,25 @@
+use axum::extract::{Extension, Json};
+
+use crate::endpoints::tenant::TenantId;
+use crate::sync_queue::{SyncQueue, SyncQueueStats};
+
+/// Axum handler: GET /github/repo_stars/jobs/queue
+///
+/// Reports the number of workers, the syncs they are running and the tenant's
+/// ones waiting, in the order they will be picked up.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/jobs/queue",
+    operation_id = "get_sync_queue",
+    tag = "repo_stars",
+    summary = "Sync queue state",
+    responses(
+        (status = 200, description = "Workers, running and waiting syncs", body = SyncQueueStats),
+    ),
+)]
+pub async fn handler(
+    Extension(queue): Extension<SyncQueue>,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+) -> Json<SyncQueueStats> {
+    Json(queue.tenant_stats(tenant_id))
+}
@@ -0,0 +1,308 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use interfaces_github_stargazers::forge::Forge;
+use interfaces_github_stargazers::page_size::AdaptivePageSize;
+use diesel::PgConnection;
+use serde_json::json;
+use thiserror::Error;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    repository::{
+	        models::Repository,
+	        queries::{get_repository_by_id, GetRepositoryByIdError},
+	    },
+	    sync_job::{
+	        models::{SyncJob, SyncJobStatus},
+	        queries::{get_sync_job_by_id, transition_sync_job, GetSyncJobByIdError, TransitionSyncJobError},
+	    },
+	    sync_run::models::SyncTrigger,
+	    PgPool,
+	};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::endpoints::tenant::TenantId;
+use crate::forges::ForgeClients;
+use crate::endpoints::github::repo_stars::update::index::{
+    fetch_chunk_of_stars_from_repo, publish_started, store_pages, FetchChunkOfStarsFromRepoError,
+    StorePagesError, SyncSummary,
+};
+use crate::live::PageSink;
+use crate::runtime_config::RuntimeConfig;
+use crate::sync_limits::SyncLimits;
+use crate::sync_lock::{RepositoryLock, SyncLockError};
+use crate::sync_queue::{RunSyncTaskError, SyncContext, SyncPriority, SyncQueue, SyncTask};
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("Checkout: {source}")]
+	Checkout {
+		#[from]
+		source: DbConnRejection,
+	},
+	#[error("ForgeNotConfigured: {forge}")]
+	ForgeNotConfigured {
+		forge: Forge,
+	},
+	#[error("GetSyncJobById: {source}")]
+	GetSyncJobById {
+		#[from]
+		source: GetSyncJobByIdError,
+	},
+	#[error("SyncJobNotFound: {id}")]
+	SyncJobNotFound {
+		id: Uuid,
+	},
+	#[error("SyncJobNotResumable: {id} is {status}")]
+	SyncJobNotResumable {
+		id: Uuid,
+		status: &'static str,
+	},
+	#[error("GetRepositoryById: {source}")]
+	GetRepositoryById {
+		#[from]
+		source: GetRepositoryByIdError,
+	},
+	#[error("RepositoryNotInDatabase: {id}")]
+	RepositoryNotInDatabase {
+		id: Uuid,
+	},
+	#[error("TransitionSyncJob: {source}")]
+	TransitionSyncJob {
+		#[from]
+		source: TransitionSyncJobError,
+	},
+	#[error("RunSyncTask: {source}")]
+	RunSyncTask {
+		#[from]
+		source: RunSyncTaskError,
+	},
+	#[error("SyncWorkerGone")]
+	SyncWorkerGone,
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::Checkout{ source } => source.into_response(),
+			HandlerError::ForgeNotConfigured{ forge } => ApiError::forge_not_configured(forge).into_response(),
+			HandlerError::SyncJobNotFound{ id } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::SyncJobNotFound, format!("Sync job {id} not found")).with_details(json!({ "id": id })).into_response(),
+			HandlerError::SyncJobNotResumable{ id, status } => ApiError::new(StatusCode::CONFLICT, ErrorCode::SyncJobNotResumable, format!("Sync job {id} is {status}, only failed, interrupted or partially completed jobs can be resumed")).with_details(json!({ "id": id, "status": status })).into_response(),
+			HandlerError::RepositoryNotInDatabase{ id } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::RepositoryNotFound, format!("Repository {id} not found in database")).with_details(json!({ "id": id })).into_response(),
+			HandlerError::RunSyncTask{ source } if source.is_sync_in_progress() => ApiError::new(StatusCode::CONFLICT, ErrorCode::SyncInProgress, source.to_string()).into_response(),
+			HandlerError::RunSyncTask{ source } => ApiError::github(source.status_code(), source).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: POST /github/repo_stars/jobs/{id}/resume
+///
+/// Continues a failed, interrupted or partially completed sync from the last page it stored, once a sync worker is free.
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/jobs/{id}/resume",
+    operation_id = "resume_sync_job",
+    tag = "repo_stars",
+    summary = "Resume a failed, interrupted or partially completed sync",
+    params(
+        ("id" = Uuid, Path, description = "Sync job id"),
+    ),
+    responses(
+        (status = 200, description = "Sync completed", body = SyncSummary),
+        (status = 404, description = "Sync job or repository not found"),
+        (status = 409, description = "Sync job is neither failed nor interrupted, or its repository is being synced, leaving it interrupted"),
+        (status = 502, description = "Forge request failed"),
+        (status = 503, description = "Service shutting down or sync interrupted"),
+    ),
+)]
+pub async fn handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(forges): Extension<ForgeClients>,
+    Extension(queue): Extension<SyncQueue>,
+    Extension(config): Extension<RuntimeConfig>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SyncSummary>, HandlerError> {
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+		.await
+		.map_err(|source| HandlerError::Checkout{ source })?;
+
+    let job = get_sync_job_by_id(&mut conn, id)
+		.map_err(|source| HandlerError::GetSyncJobById{ source })?
+		.ok_or(HandlerError::SyncJobNotFound{ id })?;
+
+    let repo = get_repository_by_id(&mut conn, job.repository_id)
+		.map_err(|source| HandlerError::GetRepositoryById{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ id: job.repository_id })?;
+
+    // Other tenants' jobs are reported as missing, not forbidden.
+    if repo.tenant_id != tenant_id {
+        return Err(HandlerError::SyncJobNotFound{ id });
+    }
+    if !forges.is_available(repo.forge()) {
+        return Err(HandlerError::ForgeNotConfigured{ forge: repo.forge() });
+    }
+
+    // Stopped by a limit, the job gets the same budget again from where it stopped.
+    let limits = if job.status == SyncJobStatus::PartiallyCompleted.as_str() {
+        config.sync_limits().past(u32::try_from(job.pages_fetched).unwrap_or(0), u64::try_from(job.stars_fetched).unwrap_or(0))
+    } else {
+        config.sync_limits()
+    };
+
+    // Marked right away so the job can't be resumed twice while it waits in the queue.
+    let job = match transition_sync_job(&mut conn, job.id, SyncJobStatus::Pending, None) {
+        Ok(job) => job,
+        Err(TransitionSyncJobError::IllegalTransition{ source }) => return Err(HandlerError::SyncJobNotResumable{ id, status: source.from }),
+        Err(source) => return Err(HandlerError::TransitionSyncJob{ source }),
+    };
+    drop(conn);
+
+    let result = match queue.enqueue(SyncTask::Resume { job: Box::new(job), repo: Box::new(repo), limits }, SyncPriority::Normal).await {
+        Ok(result) => result,
+        Err(_) => {
+            interrupt_pending_job(pool, id).await;
+            return Err(HandlerError::SyncWorkerGone);
+        }
+    };
+
+    // Turned down by a shutdown, or failing before it ran, e.g. without a connection for the worker.
+    if result.is_err() {
+        interrupt_pending_job(pool, id).await;
+    }
+
+    let summary = result.map_err(|source| HandlerError::RunSyncTask{ source })?;
+    Ok(Json(summary))
+}
+
+/// Interrupts job `id` if it's still pending, turned down before it ran, so it can be resumed again.
+async fn interrupt_pending_job(pool: PgPool, id: Uuid) {
+    let interrupted = match DbConn::checkout(pool).await {
+        Ok(DbConn(mut conn)) => match get_sync_job_by_id(&mut conn, id) {
+            Ok(Some(job)) if job.status == SyncJobStatus::Pending.as_str() => {
+                transition_sync_job(&mut conn, id, SyncJobStatus::Interrupted, None).map(drop).map_err(|error| error.to_string())
+            }
+            Ok(_) => Ok(()),
+            Err(error) => Err(error.to_string()),
+        },
+        Err(error) => Err(error.to_string()),
+    };
+    if let Err(error) = interrupted {
+        warn!(%id, %error, "turned down sync job left pending");
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ResumeSyncJobError {
+	#[error("TransitionSyncJob: {source}")]
+	TransitionSyncJob {
+		#[from]
+		source: TransitionSyncJobError,
+	},
+	#[error("FetchChunkOfStarsFromRepo: {source}")]
+	FetchChunkOfStarsFromRepo {
+		#[from]
+		source: FetchChunkOfStarsFromRepoError,
+	},
+	#[error("StorePages: {source}")]
+	StorePages {
+		#[from]
+		source: StorePagesError,
+	},
+	#[error("LockRepository: {source}")]
+	LockRepository {
+		#[from]
+		source: SyncLockError,
+	},
+	#[error("SyncInProgress: {owner}/{name} is being synced by another worker or instance")]
+	SyncInProgress {
+		owner: String,
+		name: String,
+	},
+	#[error("Interrupted")]
+	Interrupted,
+}
+
+impl ResumeSyncJobError {
+	pub fn status_code(&self) -> StatusCode {
+		match self {
+			ResumeSyncJobError::FetchChunkOfStarsFromRepo{ source } => source.status_code(),
+			ResumeSyncJobError::StorePages{ source } => source.status_code(),
+			ResumeSyncJobError::SyncInProgress{ .. } => StatusCode::CONFLICT,
+			ResumeSyncJobError::Interrupted => StatusCode::SERVICE_UNAVAILABLE,
+			_ => StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
+
+/// Starts the pending job back, fetching the page after its stored cursor and storing it
+/// along with every following one until one of `limits` is reached.
+///
+/// While another worker or instance syncs the repository, the job is interrupted instead,
+/// to be resumed once that sync is over.
+pub(crate) async fn resume_sync_job(
+    conn: &mut PgConnection,
+    context: &SyncContext,
+    job: &SyncJob,
+    repo: &Repository,
+    limits: SyncLimits,
+) -> Result<SyncSummary, ResumeSyncJobError> {
+    let Some(mut lock) = RepositoryLock::try_acquire(conn, repo.id)
+		.map_err(|source| ResumeSyncJobError::LockRepository{ source })? else {
+        transition_sync_job(conn, job.id, SyncJobStatus::Interrupted, None)
+			.map_err(|source| ResumeSyncJobError::TransitionSyncJob{ source })?;
+        return Err(ResumeSyncJobError::SyncInProgress{ owner: repo.owner.clone(), name: repo.name.clone() });
+    };
+
+    resume_locked_sync_job(&mut lock, context, job, repo, limits).await
+}
+
+async fn resume_locked_sync_job(
+    conn: &mut PgConnection,
+    context: &SyncContext,
+    job: &SyncJob,
+    repo: &Repository,
+    limits: SyncLimits,
+) -> Result<SyncSummary, ResumeSyncJobError> {
+    transition_sync_job(conn, job.id, SyncJobStatus::Running, None)
+		.map_err(|source| ResumeSyncJobError::TransitionSyncJob{ source })?;
+
+    let forge = repo.forge();
+    // Nothing is stored before the first page, a shutdown leaves the job to be resumed again.
+    let fetched = tokio::select! {
+        fetched = fetch_chunk_of_stars_from_repo(&context.forges, forge, &repo.owner, &repo.name, job.end_cursor.as_deref(), AdaptivePageSize::new(context.forges.page_size()), context.collects_profiles(forge)) => fetched,
+        _ = context.stop.stopped() => {
+            transition_sync_job(conn, job.id, SyncJobStatus::Interrupted, None)
+				.map_err(|source| ResumeSyncJobError::TransitionSyncJob{ source })?;
+            return Err(ResumeSyncJobError::Interrupted);
+        }
+    };
+    let page = match fetched {
+        Ok(page) => page,
+        Err(source) => {
+            transition_sync_job(conn, job.id, SyncJobStatus::Failed, Some(&source.to_string()))
+				.map_err(|source| ResumeSyncJobError::TransitionSyncJob{ source })?;
+            return Err(ResumeSyncJobError::FetchChunkOfStarsFromRepo{ source });
+        }
+    };
+
+    let summary = SyncSummary {
+        job_id: job.id,
+        repository_id: repo.id,
+        pages: job.pages_fetched as u32,
+        stars: job.stars_fetched as u64,
+        retries: job.retries as u32,
+        ..SyncSummary::default()
+    };
+
+    publish_started(context, repo, job.id);
+
+    store_pages(conn, context, repo, SyncTrigger::Resume, page, summary, &mut PageSink::default(), limits)
+		.await
+		.map_err(|source| ResumeSyncJobError::StorePages{ source })
+}
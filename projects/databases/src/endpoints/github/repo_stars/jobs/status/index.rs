@@ -0,0 +1,158 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    repository::queries::{get_repository_by_id, GetRepositoryByIdError},
+	    sync_job::{
+	        models::{SyncJob, SyncJobStatus},
+	        queries::{get_sync_job_by_id, GetSyncJobByIdError},
+	    },
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+use crate::sync_queue::{JobProgress, SyncPace};
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetSyncJobById: {source}")]
+	GetSyncJobById {
+		#[from]
+		source: GetSyncJobByIdError,
+	},
+	#[error("SyncJobNotFound: {id}")]
+	SyncJobNotFound {
+		id: Uuid,
+	},
+	#[error("GetRepositoryById: {source}")]
+	GetRepositoryById {
+		#[from]
+		source: GetRepositoryByIdError,
+	},
+	#[error("RepositoryNotInDatabase: {id}")]
+	RepositoryNotInDatabase {
+		id: Uuid,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::SyncJobNotFound{ id } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::SyncJobNotFound, format!("Sync job {id} not found")).with_details(json!({ "id": id })).into_response(),
+			HandlerError::RepositoryNotInDatabase{ id } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::RepositoryNotFound, format!("Repository {id} not found in database")).with_details(json!({ "id": id })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// State of a sync job and the repository it syncs.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct SyncJobResponse {
+	pub id: Uuid,
+	pub owner: String,
+	pub name: String,
+	/// `pending`, `running`, `completed`, `partially_completed`, `failed` or `interrupted`.
+	pub status: String,
+	pub pages_fetched: i32,
+	#[serde(flatten)]
+	pub progress: JobProgress,
+	/// Why the job failed, if it did.
+	pub error: Option<String>,
+	/// Limit that stopped a `partially_completed` job: `max_pages`, `max_duration` or `max_stars`.
+	pub stop_reason: Option<String>,
+	pub batch_id: Option<Uuid>,
+	pub created_at: NaiveDateTime,
+	pub updated_at: NaiveDateTime,
+	/// First time the job ran, kept across resumes.
+	pub started_at: Option<NaiveDateTime>,
+	/// End of the job's last run, unset while it's pending or running.
+	pub finished_at: Option<NaiveDateTime>,
+}
+
+/// Axum handler: GET /github/repo_stars/jobs/{id}
+///
+/// The ETA of a running job assumes the pace since it was created, including any time spent waiting for a resume.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/jobs/{id}",
+    operation_id = "get_sync_job",
+    tag = "repo_stars",
+    summary = "Sync job state",
+    params(
+        ("id" = Uuid, Path, description = "Sync job id"),
+    ),
+    responses(
+        (status = 200, description = "The job and its repository", body = SyncJobResponse),
+        (status = 404, description = "Sync job or repository not found"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SyncJobResponse>, HandlerError> {
+    let job = get_sync_job_by_id(&mut conn, id)
+		.map_err(|source| HandlerError::GetSyncJobById{ source })?
+		.ok_or(HandlerError::SyncJobNotFound{ id })?;
+
+    let repo = get_repository_by_id(&mut conn, job.repository_id)
+		.map_err(|source| HandlerError::GetRepositoryById{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ id: job.repository_id })?;
+
+    // Other tenants' jobs are reported as missing, not forbidden.
+    if repo.tenant_id != tenant_id {
+        return Err(HandlerError::SyncJobNotFound{ id });
+    }
+
+    let progress = job_progress(&job);
+    Ok(Json(SyncJobResponse {
+        id: job.id,
+        owner: repo.owner,
+        name: repo.name,
+        status: job.status,
+        pages_fetched: job.pages_fetched,
+        progress,
+        error: job.error,
+        stop_reason: job.stop_reason,
+        batch_id: job.batch_id,
+        created_at: job.created_at,
+        updated_at: job.updated_at,
+        started_at: job.started_at,
+        finished_at: job.finished_at,
+    }))
+}
+
+/// Progress of `job`, complete once it is, without ETA unless it is running.
+fn job_progress(job: &SyncJob) -> JobProgress {
+    let pace = if job.status == SyncJobStatus::Running.as_str() {
+        SyncPace {
+            stars: job.stars_fetched.into(),
+            pages: job.pages_fetched.into(),
+            elapsed: job.updated_at - job.created_at,
+        }
+    } else {
+        SyncPace::default()
+    };
+
+    let mut progress = JobProgress::new(
+        job.stars_fetched.into(),
+        job.estimated_total_stars.map(i64::from),
+        pace,
+        Utc::now().naive_utc(),
+    );
+    progress.retries = job.retries.into();
+    if job.status == SyncJobStatus::Completed.as_str() {
+        progress.percent_complete = Some(100.0);
+    }
+    progress
+}
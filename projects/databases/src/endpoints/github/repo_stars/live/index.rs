@@ -0,0 +1,92 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Extension, Path,
+    },
+    response::Response,
+};
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::{sync::broadcast::error::RecvError, time::Instant};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::endpoints::tenant::TenantId;
+use crate::live::LiveUpdates;
+use crate::sync_queue::{StopSignal, SyncQueue};
+
+/// Time left after a shutdown starts for running syncs to report their interruption.
+const INTERRUPTION_WAIT: Duration = Duration::from_secs(1);
+
+/// Axum handler: GET /github/repo_stars/live/{owner}/{name}
+///
+/// Upgrades to a WebSocket receiving a JSON message per event of the
+/// repository's syncs: `started`, `page` with the stored stargazers and
+/// running totals, then `completed`, `failed` or `interrupted`.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/live/{owner}/{name}",
+    operation_id = "live_sync_events",
+    tag = "repo_stars",
+    summary = "Live sync progress over WebSocket",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 101, description = "Switched to WebSocket, every message is a JSON sync event"),
+        (status = 400, description = "Not a WebSocket upgrade request"),
+    ),
+)]
+pub async fn handler(
+    Extension(live): Extension<LiveUpdates>,
+    Extension(queue): Extension<SyncQueue>,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let stop = queue.stop_signal();
+    ws.on_upgrade(move |socket| forward_sync_events(socket, live, stop, tenant_id, owner, name))
+}
+
+/// Sends the repository's sync events until the client leaves or the service shuts down.
+async fn forward_sync_events(mut socket: WebSocket, live: LiveUpdates, stop: StopSignal, tenant_id: Uuid, owner: String, name: String) {
+    let mut events = live.subscribe();
+    let mut closing_at: Option<Instant> = None;
+
+    loop {
+        let message = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if event.is_for(tenant_id, &owner, &name) => match serde_json::to_string(&*event) {
+                    Ok(text) => text,
+                    Err(error) => {
+                        warn!(%owner, %name, %error, "sync event serialization failed");
+                        continue;
+                    }
+                },
+                Ok(_) => continue,
+                // Tells the client its totals may have missed pages, the next page event catches up.
+                Err(RecvError::Lagged(skipped)) => json!({ "event": "lagged", "skipped": skipped }).to_string(),
+                Err(RecvError::Closed) => return,
+            },
+            incoming = socket.recv() => match incoming {
+                // Clients only listen, anything but a close is ignored.
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+            _ = stop.stopped(), if closing_at.is_none() => {
+                closing_at = Some(Instant::now() + INTERRUPTION_WAIT);
+                continue;
+            }
+            _ = tokio::time::sleep_until(closing_at.unwrap_or_else(Instant::now)), if closing_at.is_some() => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            }
+        };
+
+        if socket.send(Message::Text(message.into())).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use thiserror::Error;
+use utoipa::IntoParams;
+
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::{
+    milestones::{milestone_eta, MilestoneEta},
+    series::cumulative_series,
+};
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_daily_star_count, GetDailyStarCountError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+use crate::validation::ValidationErrors;
+
+/// Longest velocity window, in days.
+pub const MAX_WINDOW_DAYS: i64 = 365;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
+		#[from]
+		source: GetDailyStarCountError,
+	},
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EtaParams {
+	target: i64,
+	/// Trailing days the star velocity is averaged over, 1 to 365.
+	#[serde(default = "default_window")]
+	window: i64,
+}
+
+fn default_window() -> i64 {
+	30
+}
+
+/// Axum handler: GET /github/repo_stars/milestones/{owner}/{name}/eta?target=N
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/milestones/{owner}/{name}/eta",
+    operation_id = "estimate_milestone",
+    tag = "repo_stars",
+    summary = "Estimated day a star count is reached",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        EtaParams,
+    ),
+    responses(
+        (status = 200, description = "Estimated day", body = MilestoneEta),
+        (status = 422, description = "Invalid window"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<EtaParams>,
+) -> Result<Json<MilestoneEta>, HandlerError> {
+    let mut errors = ValidationErrors::new();
+    errors.check("window", match params.window {
+        1..=MAX_WINDOW_DAYS => Ok(()),
+        _ => Err(format!("must be between 1 and {MAX_WINDOW_DAYS}")),
+    });
+    errors.into_result().map_err(|source| HandlerError::Validation{ source })?;
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let daily_counts = get_daily_star_count(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetDailyStarCount{ source })?;
+
+    let today = Utc::now().date_naive();
+
+    Ok(Json(milestone_eta(&cumulative_series(&daily_counts), params.target, today, params.window)))
+}
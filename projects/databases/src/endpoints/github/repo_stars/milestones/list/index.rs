@@ -0,0 +1,89 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    response::IntoResponse,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    milestone::queries::{get_milestones_by_repository, GetMilestonesByRepositoryError},
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetMilestonesByRepository: {source}")]
+	GetMilestonesByRepository {
+		#[from]
+		source: GetMilestonesByRepositoryError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MilestoneResponse {
+	pub stars: i32,
+	pub reached_on: NaiveDate,
+}
+
+/// Axum handler: GET /github/repo_stars/milestones/{owner}/{name}
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/milestones/{owner}/{name}",
+    operation_id = "list_milestones",
+    tag = "repo_stars",
+    summary = "Star milestones reached",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 200, description = "Milestones reached, oldest first", body = Vec<MilestoneResponse>),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<Json<Vec<MilestoneResponse>>, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let milestones = get_milestones_by_repository(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetMilestonesByRepository{ source })?;
+
+    Ok(Json(
+        milestones
+            .into_iter()
+            .map(|milestone| MilestoneResponse { stars: milestone.stars, reached_on: milestone.reached_on })
+            .collect(),
+    ))
+}
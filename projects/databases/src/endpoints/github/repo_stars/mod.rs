@@ -1,2 +1,24 @@
 pub mod update;
-pub mod read_per_day;
\ No newline at end of file
+pub mod update_batch;
+pub mod read_per_day;
+pub mod jobs;
+pub mod forecast;
+pub mod milestones;
+pub mod anomalies;
+pub mod earliest_stargazers;
+pub mod as_of;
+pub mod releases;
+pub mod live;
+pub mod import;
+pub mod export;
+pub mod snapshots;
+pub mod contributors;
+pub mod drift;
+pub mod stats;
+pub mod trends;
+pub mod cohorts;
+pub mod heatmap;
+#[cfg(feature = "charting")]
+pub mod social_card;
+pub mod compare;
+pub mod diff;
\ No newline at end of file
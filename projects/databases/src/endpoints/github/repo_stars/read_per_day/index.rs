@@ -1,25 +1,28 @@
 use axum::{
-    extract::{Extension, Json},
+    extract::{Extension, Json, Path, Query},
     http::StatusCode,
     response::IntoResponse,
 };
 
-use serde::Deserialize;
+use chrono::NaiveDate;
+use diesel::PgConnection;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
+use crate::bots::BotFilter;
+use crate::endpoints::api_error::ApiError;
 use crate::db::{
 	    repository::queries::get_repository_by_name,
-	    star::queries::get_daily_star_count,
-	    PgPool,
+	    star::queries::{get_daily_star_count_page, get_filtered_daily_star_count},
 	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+use crate::validation::ValidationErrors;
 
 #[derive(Debug, Error)]
 pub enum HandlerError {
-	#[error("GetConnectionFromPool: {source}")]
-	GetConnectionFromPool {
-		#[from]
-		source: r2d2::Error,
-	},
 	#[error("GetRepositoryByName: {source}")]
 	GetRepositoryByName {
 		#[from]
@@ -35,50 +38,174 @@ pub enum HandlerError {
 		#[from] 
 		source: crate::db::star::queries::GetDailyStarCountError 
 	},
+	#[error("GetDailyStarCountPage: {source}")]
+	GetDailyStarCountPage {
+		#[from]
+		source: crate::db::star::queries::GetDailyStarCountPageError,
+	},
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
 }
 
 impl IntoResponse for HandlerError {
 	fn into_response(self) -> axum::response::Response {
 		match self {
-			HandlerError::GetConnectionFromPool{ source } => (StatusCode::INTERNAL_SERVER_ERROR, source.to_string()).into_response(),
-			HandlerError::GetRepositoryByName{ source } => (StatusCode::INTERNAL_SERVER_ERROR, source.to_string()).into_response(),
-			HandlerError::RepositoryNotInDatabase{ owner, name } => (StatusCode::NOT_FOUND, format!("Repository {owner}/{name} not found in database")).into_response(),
-			HandlerError::GetDailyStarCount{ source } => (StatusCode::INTERNAL_SERVER_ERROR, source.to_string()).into_response(),
+			HandlerError::GetRepositoryByName{ source } => ApiError::internal(source).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::GetDailyStarCount{ source } => ApiError::internal(source).into_response(),
+			HandlerError::GetDailyStarCountPage{ source } => ApiError::internal(source).into_response(),
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
         }
     }
 }
 
 /// JSON payload expected by the endpoint.
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+#[schema(as = DailyStarCountQuery)]
 pub struct RepoQuery {
 	owner: String,
 	name:  String,
+	/// Days per page, the whole series is returned as a bare array when unset.
+	limit:  Option<i64>,
+	offset: Option<i64>,
+}
+
+/// Query parameters of `GET /github/repo_stars/read_per_day/{owner}/{name}`.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DailyStarCountParams {
+	/// Days per page, the whole series is returned as a bare array when unset.
+	limit:  Option<i64>,
+	offset: Option<i64>,
+}
+
+/// Paginated answer, returned when `limit` or `offset` is given.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct DailyStarCountPage {
+	pub data:   Vec<(NaiveDate, i64)>,
+	/// Days with at least one star, over the whole history.
+	pub total:  i64,
+	pub limit:  i64,
+	pub offset: i64,
 }
 
+fn default_limit() -> i64 {
+	365
+}
+
+/// Upper bound of `limit`, a little under three years of days per page.
+const MAX_LIMIT: i64 = 1000;
 
-/// Axum handler: POST /sync-stars
+/// `limit` and `offset` of the page asked for, `None` for the whole series.
+fn page_bounds(limit: Option<i64>, offset: Option<i64>) -> Result<Option<(i64, i64)>, ValidationErrors> {
+	if limit.is_none() && offset.is_none() {
+	    return Ok(None);
+	}
+	let limit = limit.unwrap_or_else(default_limit);
+	let offset = offset.unwrap_or(0);
+	let mut errors = ValidationErrors::new();
+	errors
+	    .check("limit", if limit < 1 {
+	        Err("must be at least 1".into())
+	    } else if limit > MAX_LIMIT {
+	        Err(format!("cannot be over {MAX_LIMIT}"))
+	    } else {
+	        Ok(())
+	    })
+	    .check("offset", if offset >= 0 { Ok(()) } else { Err("cannot be negative".into()) });
+	errors.into_result().map(|()| Some((limit, offset)))
+}
+
+/// Axum handler: POST /github/repo_stars/read_per_day
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/read_per_day",
+    operation_id = "read_daily_star_count",
+    tag = "repo_stars",
+    summary = "Daily star counts",
+    params(BotFilter),
+    request_body = RepoQuery,
+    responses(
+        (status = 200, description = "Array of `[day, stars]` pairs, or one page of it when `limit` or `offset` is given", body = DailyStarCountPage),
+        (status = 422, description = "Invalid pagination"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
 pub async fn handler(
-    Extension(pool): Extension<PgPool>,
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Query(bots): Query<BotFilter>,
     Json(input): Json<RepoQuery>,
 ) -> impl IntoResponse {
- 	let mut conn = match pool.get() {
-    	Ok(c) => c,
-    	Err(source) => return HandlerError::GetConnectionFromPool { source }.into_response(),
+    read_daily(&mut conn, tenant_id, input.owner, input.name, input.limit, input.offset, bots).await
+}
+
+/// Axum handler: GET /github/repo_stars/read_per_day/{owner}/{name}
+///
+/// The same answer as the POST, tagged with an ETag for conditional requests.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/read_per_day/{owner}/{name}",
+    operation_id = "get_daily_star_count",
+    tag = "repo_stars",
+    summary = "Daily star counts, cacheable",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        DailyStarCountParams,
+        BotFilter,
+    ),
+    responses(
+        (status = 200, description = "Array of `[day, stars]` pairs, or one page of it when `limit` or `offset` is given", body = DailyStarCountPage),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 422, description = "Invalid pagination"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn get_handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<DailyStarCountParams>,
+    Query(bots): Query<BotFilter>,
+) -> impl IntoResponse {
+    read_daily(&mut conn, tenant_id, owner, name, params.limit, params.offset, bots).await
+}
+
+async fn read_daily(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    owner: String,
+    name: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    bots: BotFilter,
+) -> axum::response::Response {
+    // Checked first, a bad page is reported whether the repository exists or not.
+    let page = match page_bounds(limit, offset) {
+	    Ok(page) => page,
+	    Err(source) => return HandlerError::Validation { source }.into_response(),
 	};
 
-    let repo = match get_repository_by_name(&mut conn, &input.owner, &input.name).await {
+    let repo = match get_repository_by_name(conn, tenant_id, &owner, &name).await {
 	    Ok(Some(repo)) => repo,
-	    Ok(None) => {
-	        return HandlerError::RepositoryNotInDatabase {
-	            owner: input.owner.clone(),
-	            name: input.name.clone(),
-	        }
-	        .into_response()
-	    }
+	    Ok(None) => return HandlerError::RepositoryNotInDatabase { owner, name }.into_response(),
 	    Err(source) => return HandlerError::GetRepositoryByName { source }.into_response(),
 	};
 	
-	let star_counts = match get_daily_star_count(&mut conn, repo.id) {
+	if let Some((limit, offset)) = page {
+	    return match get_daily_star_count_page(conn, repo.id, bots.exclude_bots, limit, offset) {
+	        Ok((data, total)) => (StatusCode::OK, Json(DailyStarCountPage { data, total, limit, offset })).into_response(),
+	        Err(source) => HandlerError::GetDailyStarCountPage { source }.into_response(),
+	    };
+	}
+
+	let star_counts = match get_filtered_daily_star_count(conn, repo.id, bots.exclude_bots) {
 	    Ok(data) => data,
 	    Err(source) => return HandlerError::GetDailyStarCount { source }.into_response(),
 	};
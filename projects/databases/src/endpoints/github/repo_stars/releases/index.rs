@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use utoipa::IntoParams;
+
+use crate::bots::BotFilter;
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::releases::{release_correlation, ReleaseCorrelation};
+use crate::db::{
+	    release::queries::{get_releases_by_repository, GetReleasesByRepositoryError},
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_filtered_daily_star_count, GetDailyStarCountError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Longest window around a release, in days.
+pub const MAX_WINDOW_DAYS: i64 = 90;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidWindow: {window_days}")]
+	InvalidWindow {
+		window_days: i64,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetReleasesByRepository: {source}")]
+	GetReleasesByRepository {
+		#[from]
+		source: GetReleasesByRepositoryError,
+	},
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
+		#[from]
+		source: GetDailyStarCountError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidWindow{ window_days } => ApiError::invalid_request(format!("window_days must be between 1 and {MAX_WINDOW_DAYS}, got {window_days}")).with_details(json!({ "window_days": window_days, "min": 1, "max": MAX_WINDOW_DAYS })).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReleasesParams {
+	/// Days before and after each release weighed against each other, 7 by default.
+	#[serde(default = "default_window_days")]
+	window_days: i64,
+}
+
+fn default_window_days() -> i64 {
+	7
+}
+
+/// Axum handler: GET /github/repo_stars/releases/{owner}/{name}
+///
+/// Not behind the ETag middleware, the windows of recent releases grow with the date.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/releases/{owner}/{name}",
+    operation_id = "release_star_velocity",
+    tag = "repo_stars",
+    summary = "Correlate star velocity with releases",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        ReleasesParams,
+        BotFilter,
+    ),
+    responses(
+        (status = 200, description = "Star velocity around each release", body = ReleaseCorrelation),
+        (status = 400, description = "Window out of range"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<ReleasesParams>,
+    Query(bots): Query<BotFilter>,
+) -> Result<Json<ReleaseCorrelation>, HandlerError> {
+    if !(1..=MAX_WINDOW_DAYS).contains(&params.window_days) {
+        return Err(HandlerError::InvalidWindow{ window_days: params.window_days });
+    }
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+
+    let releases: Vec<_> = get_releases_by_repository(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetReleasesByRepository{ source })?
+		.into_iter()
+		.map(|release| (release.tag_name, release.published_at.date()))
+		.collect();
+    let daily_counts = get_filtered_daily_star_count(&mut conn, repo.id, bots.exclude_bots)
+		.map_err(|source| HandlerError::GetDailyStarCount{ source })?;
+
+    Ok(Json(release_correlation(&daily_counts, &releases, params.window_days, Utc::now().date_naive())))
+}
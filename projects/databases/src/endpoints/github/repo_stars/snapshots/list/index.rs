@@ -0,0 +1,105 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    response::IntoResponse,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star_count_snapshot::{
+	        models::StarCountSnapshot,
+	        queries::{get_star_count_snapshots, GetStarCountSnapshotsError},
+	    },
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetStarCountSnapshots: {source}")]
+	GetStarCountSnapshots {
+		#[from]
+		source: GetStarCountSnapshotsError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StarCountSnapshotResponse {
+	/// UTC
+	pub taken_at: NaiveDateTime,
+	/// GitHub's `stargazerCount` at that time.
+	pub stargazer_count: i32,
+}
+
+impl From<StarCountSnapshot> for StarCountSnapshotResponse {
+	fn from(snapshot: StarCountSnapshot) -> Self {
+		Self { taken_at: snapshot.taken_at, stargazer_count: snapshot.stargazer_count }
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StarCountSnapshotsResponse {
+	/// Whether the star count is still snapshotted on schedule.
+	pub snapshot_mode: bool,
+	/// Oldest first.
+	pub snapshots: Vec<StarCountSnapshotResponse>,
+}
+
+/// Axum handler: GET /github/repo_stars/snapshots/{owner}/{name}
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/snapshots/{owner}/{name}",
+    operation_id = "list_star_count_snapshots",
+    tag = "repo_stars",
+    summary = "Star count snapshots of a repository",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 200, description = "Every snapshot, oldest first", body = StarCountSnapshotsResponse),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<Json<StarCountSnapshotsResponse>, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let snapshots = get_star_count_snapshots(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetStarCountSnapshots{ source })?;
+
+    Ok(Json(StarCountSnapshotsResponse {
+        snapshot_mode: repo.snapshot_mode,
+        snapshots: snapshots.into_iter().map(StarCountSnapshotResponse::from).collect(),
+    }))
+}
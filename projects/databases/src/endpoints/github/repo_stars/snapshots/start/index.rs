@@ -0,0 +1,184 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use interfaces_github_stargazers::forge::Forge;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    repository::{
+	        models::NewRepository,
+	        queries::{get_repository_by_name, insert_repository, set_snapshot_mode, GetRepositoryByNameError, InsertRepositoryError, SetSnapshotModeError},
+	    },
+	    star_count_snapshot::{
+	        models::NewStarCountSnapshot,
+	        queries::{insert_star_count_snapshot, InsertStarCountSnapshotError},
+	    },
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+use crate::endpoints::github::repo_stars::snapshots::list::index::StarCountSnapshotResponse;
+use crate::forges::{ForgeClientError, ForgeClients};
+use crate::validation::{validate_forge_repository, ValidationErrors};
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+	#[error("ForgeNotConfigured: {forge}")]
+	ForgeNotConfigured {
+		forge: Forge,
+	},
+	#[error("FetchStargazerCount: {source}")]
+	FetchStargazerCount {
+		#[from]
+		source: ForgeClientError,
+	},
+	#[error("RepositoryNotOnForge: {owner}/{name} on {forge}")]
+	RepositoryNotOnForge {
+		forge: Forge,
+		owner: String,
+		name:  String,
+	},
+	#[error("ForgeMismatch: {owner}/{name} is tracked on {tracked}")]
+	ForgeMismatch {
+		owner:   String,
+		name:    String,
+		tracked: Forge,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("InsertRepository: {source}")]
+	InsertRepository {
+		#[from]
+		source: InsertRepositoryError,
+	},
+	#[error("SetSnapshotMode: {source}")]
+	SetSnapshotMode {
+		#[from]
+		source: SetSnapshotModeError,
+	},
+	#[error("InsertStarCountSnapshot: {source}")]
+	InsertStarCountSnapshot {
+		#[from]
+		source: InsertStarCountSnapshotError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			HandlerError::ForgeNotConfigured{ forge } => ApiError::forge_not_configured(forge).into_response(),
+			HandlerError::FetchStargazerCount{ source } => ApiError::github(source.status_code(), source).into_response(),
+			HandlerError::RepositoryNotOnForge{ forge, owner, name } => ApiError::github(StatusCode::NOT_FOUND, format!("Repository {owner}/{name} not found on {forge}")).with_details(json!({ "forge": forge, "owner": owner, "name": name })).into_response(),
+			HandlerError::ForgeMismatch{ owner, name, tracked } => ApiError::new(StatusCode::CONFLICT, ErrorCode::ForgeMismatch, format!("Repository {owner}/{name} is tracked on {tracked}")).with_details(json!({ "forge": tracked, "owner": owner, "name": name })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SnapshotParams {
+	/// Forge hosting the repository, `github` by default.
+	#[serde(default)]
+	#[param(value_type = String, example = "github")]
+	forge: Forge,
+}
+
+/// Axum handler: POST /github/repo_stars/snapshots/{owner}/{name}
+///
+/// Puts the repository in snapshot mode, tracking it first if needed, and records its
+/// current star count. Its star count is then snapshotted every
+/// `STAR_SNAPSHOT_INTERVAL_HOURS`, without enumerating its stargazers.
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/snapshots/{owner}/{name}",
+    operation_id = "start_star_count_snapshots",
+    tag = "repo_stars",
+    summary = "Snapshot a repository's star count on schedule",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        SnapshotParams,
+    ),
+    responses(
+        (status = 200, description = "First snapshot taken", body = StarCountSnapshotResponse),
+        (status = 404, description = "Repository not found on its forge"),
+        (status = 409, description = "Repository tracked on another forge"),
+        (status = 422, description = "Owner or name breaking the forge's naming rules"),
+        (status = 502, description = "Forge request failed"),
+        (status = 500, description = "Forge not configured or database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(forges): Extension<ForgeClients>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(SnapshotParams { forge }): Query<SnapshotParams>,
+) -> Result<Json<StarCountSnapshotResponse>, HandlerError> {
+    let mut errors = ValidationErrors::new();
+    validate_forge_repository(&mut errors, "", forge, &owner, &name);
+    errors.into_result()?;
+
+    if !forges.is_available(forge) {
+        return Err(HandlerError::ForgeNotConfigured{ forge });
+    }
+
+    let tracked = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?;
+    if let Some(repo) = tracked.as_ref().filter(|repo| repo.forge() != forge) {
+        return Err(HandlerError::ForgeMismatch{ owner, name, tracked: repo.forge() });
+    }
+
+    let stargazer_count = forges.stargazer_count(forge, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::FetchStargazerCount{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotOnForge{ forge, owner: owner.clone(), name: name.clone() })?;
+
+    let repo = match tracked {
+        Some(repo) => repo,
+        // Metadata stays empty until a sync, snapshots only ask for the star count.
+        None => insert_repository(&mut conn, &NewRepository {
+            id: Uuid::new_v4(),
+            owner: &owner,
+            name: &name,
+            description: None,
+            primary_language: None,
+            license: None,
+            topics: Vec::new(),
+            github_created_at: None,
+            tenant_id,
+            github_node_id: None,
+            forge: forge.as_str(),
+        })
+		.map_err(|source| HandlerError::InsertRepository{ source })?,
+    };
+
+    set_snapshot_mode(&mut conn, repo.id, true)
+		.map_err(|source| HandlerError::SetSnapshotMode{ source })?;
+
+    let snapshot = insert_star_count_snapshot(&mut conn, &NewStarCountSnapshot {
+        repository_id: repo.id,
+        stargazer_count: i32::try_from(stargazer_count).unwrap_or(i32::MAX),
+    })
+		.map_err(|source| HandlerError::InsertStarCountSnapshot{ source })?;
+
+    Ok(Json(StarCountSnapshotResponse::from(snapshot)))
+}
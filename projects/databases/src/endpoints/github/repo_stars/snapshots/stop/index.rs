@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use thiserror::Error;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{get_repository_by_name, set_snapshot_mode, GetRepositoryByNameError, SetSnapshotModeError},
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("SetSnapshotMode: {source}")]
+	SetSnapshotMode {
+		#[from]
+		source: SetSnapshotModeError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: DELETE /github/repo_stars/snapshots/{owner}/{name}
+///
+/// Stops the scheduled snapshots, the ones taken so far are kept.
+#[utoipa::path(
+    delete,
+    path = "/github/repo_stars/snapshots/{owner}/{name}",
+    operation_id = "stop_star_count_snapshots",
+    tag = "repo_stars",
+    summary = "Stop snapshotting a repository's star count",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 204, description = "Snapshots stopped"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<StatusCode, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    set_snapshot_mode(&mut conn, repo.id, false)
+		.map_err(|source| HandlerError::SetSnapshotMode{ source })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,189 @@
+use axum::{
+    body::Bytes,
+    extract::{Extension, Path, Query},
+    http::header,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::{series::{cumulative_series, FillPolicy}, stats::star_stats};
+use crate::chart::ChartTheme;
+use crate::chart_cache::{ChartCache, ChartKey, RepositoryVersion};
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_star_version, GetStarVersionError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+use crate::render_pool::{RenderError, RenderPool};
+use crate::snapshots::{get_star_history, GetStarHistoryError};
+use crate::social_card::{render_social_card_png, RenderSocialCardError, SocialCard};
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("RepositoryArchived: {owner}/{name}")]
+	RepositoryArchived {
+		owner: String,
+		name: String,
+	},
+	#[error("GetStarVersion: {source}")]
+	GetStarVersion {
+		#[from]
+		source: GetStarVersionError,
+	},
+	#[error("GetStarHistory: {source}")]
+	GetStarHistory {
+		#[from]
+		source: GetStarHistoryError,
+	},
+	#[error("Render: {source}")]
+	Render {
+		#[from]
+		source: RenderError,
+	},
+	#[error("RenderSocialCard: {source}")]
+	RenderSocialCard {
+		#[from]
+		source: RenderSocialCardError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::RepositoryArchived{ owner, name } => ApiError::repository_archived(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CardTheme {
+	#[default]
+	Light,
+	Dark,
+}
+
+impl CardTheme {
+	fn as_str(&self) -> &'static str {
+		match self {
+			CardTheme::Light => "light",
+			CardTheme::Dark => "dark",
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SocialCardParams {
+	#[serde(default)]
+	theme: CardTheme,
+}
+
+/// Axum handler: GET /github/repo_stars/social_card/{owner}/{name}
+///
+/// Not behind the ETag middleware, the 30-day delta changes with the date.
+/// Crawlers may keep the image for an hour instead. Cards are served again from the
+/// [`ChartCache`] until the repository's stars change.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/social_card/{owner}/{name}",
+    operation_id = "social_card",
+    tag = "repo_stars",
+    summary = "Render a social card PNG",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        SocialCardParams,
+    ),
+    responses(
+        (status = 200, description = "1200x630 PNG with the star total, 30-day delta and sparkline", content_type = "image/png", body = Vec<u8>),
+        (status = 304, description = "Unchanged since the `If-None-Match` ETag"),
+        (status = 404, description = "Repository not in database"),
+        (status = 409, description = "Repository archived"),
+        (status = 500, description = "Database or rendering failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(charts): Extension<ChartCache>,
+    Extension(renders): Extension<RenderPool>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<SocialCardParams>,
+) -> Result<impl IntoResponse, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+    if repo.is_archived() {
+        return Err(HandlerError::RepositoryArchived{ owner: repo.owner, name: repo.name });
+    }
+
+    let today = Utc::now().date_naive();
+    let (last_fetched_at, stars) = get_star_version(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetStarVersion{ source })?;
+    let key = ChartKey {
+        tenant_id,
+        repositories: vec![RepositoryVersion { repository_id: repo.id, last_fetched_at, stars }],
+        config: format!("social_card/{}/{}?theme={}", repo.owner, repo.name, params.theme.as_str()),
+        day: today,
+    };
+    if let Some(png) = charts.get(&key) {
+        return Ok(card_response(png));
+    }
+
+    let daily_counts = get_star_history(&mut conn, repo.id, FillPolicy::default())
+		.map_err(|source| HandlerError::GetStarHistory{ source })?
+		.daily_counts;
+    drop(conn);
+
+    let stats = star_stats(&daily_counts, today);
+    let theme = match params.theme {
+        CardTheme::Light => ChartTheme::light(),
+        CardTheme::Dark => ChartTheme::dark(),
+    };
+    let title = format!("{}/{}", repo.owner, repo.name);
+
+    let png = renders
+        .run(move || {
+            let card = SocialCard {
+                title: &title,
+                total_stars: stats.total_stars,
+                last_30_days: stats.last_30_days,
+            };
+            render_social_card_png(&card, &cumulative_series(&daily_counts), &theme)
+        })
+        .await
+		.map_err(|source| HandlerError::Render{ source })?
+		.map_err(|source| HandlerError::RenderSocialCard{ source })?;
+
+    let png = Bytes::from(png);
+    charts.insert(key, png.clone());
+
+    Ok(card_response(png))
+}
+
+fn card_response(png: Bytes) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "image/png"), (header::CACHE_CONTROL, "public, max-age=3600")],
+        png,
+    )
+}
@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use thiserror::Error;
+
+use crate::bots::BotFilter;
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::stats::{star_stats, StarStats};
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_filtered_daily_star_count, GetDailyStarCountError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
+		#[from]
+		source: GetDailyStarCountError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: GET /github/repo_stars/stats/{owner}/{name}
+///
+/// Not behind the ETag middleware, the trailing windows change with the date.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/stats/{owner}/{name}",
+    operation_id = "star_stats",
+    tag = "repo_stars",
+    summary = "Summarize a repository's star history",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        BotFilter,
+    ),
+    responses(
+        (status = 200, description = "Star history summary, days in UTC", body = StarStats),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(bots): Query<BotFilter>,
+) -> Result<Json<StarStats>, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+
+    let daily_counts = get_filtered_daily_star_count(&mut conn, repo.id, bots.exclude_bots)
+		.map_err(|source| HandlerError::GetDailyStarCount{ source })?;
+
+    let stats = star_stats(&daily_counts, Utc::now().date_naive());
+    if bots.exclude_bots {
+        return Ok(Json(stats));
+    }
+    // Same sum as the daily counts, read from the repository rather than added up again.
+    Ok(Json(StarStats { total_stars: repo.total_stars, ..stats }))
+}
@@ -0,0 +1,177 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+use chrono::{Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    daily_star_count::{
+	        models::{GrowthPeriod, PeriodStarGrowth},
+	        queries::{get_period_star_growth, GetPeriodStarGrowthError},
+	    },
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Upper bound of the periods of a single report.
+pub const MAX_TREND_PERIODS: u32 = 120;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidPeriods: {periods}")]
+	InvalidPeriods {
+		periods: u32,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetPeriodStarGrowth: {source}")]
+	GetPeriodStarGrowth {
+		#[from]
+		source: GetPeriodStarGrowthError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidPeriods{ periods } => ApiError::invalid_request(format!("periods must be between 1 and {MAX_TREND_PERIODS}, got {periods}")).with_details(json!({ "periods": periods, "max": MAX_TREND_PERIODS })).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TrendParams {
+	#[serde(default)]
+	period: GrowthPeriod,
+	/// Periods reported, the one containing `as_of` and the ones before it.
+	#[serde(default = "default_periods")]
+	periods: u32,
+	/// Last day counted, today (UTC) by default.
+	as_of: Option<NaiveDate>,
+}
+
+fn default_periods() -> u32 {
+	2
+}
+
+/// Star growth of a repository period over period.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct TrendReport {
+	pub period: GrowthPeriod,
+	pub as_of: NaiveDate,
+	/// Most recent first.
+	pub periods: Vec<PeriodTrend>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct PeriodTrend {
+	pub start: NaiveDate,
+	/// Last day counted, `as_of` for the period in progress.
+	pub end: NaiveDate,
+	/// Whether the period ended by `as_of`, an incomplete one is compared to whole periods.
+	pub complete: bool,
+	pub stars: i64,
+	/// Compared to the period before.
+	pub previous_period: GrowthChange,
+	/// Compared to the same period a year earlier.
+	pub previous_year: GrowthChange,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct GrowthChange {
+	/// Stars of the period compared to.
+	pub stars: i64,
+	/// Difference in stars, negative when growth slowed down.
+	pub change: i64,
+	/// Difference as a percentage of `stars`, `None` when it is 0.
+	pub percent: Option<f64>,
+}
+
+impl PeriodTrend {
+	fn new(growth: PeriodStarGrowth, period: GrowthPeriod, as_of: NaiveDate) -> Self {
+		let last_day = period
+			.next_start(growth.period_start)
+			.and_then(|next| next.checked_sub_days(Days::new(1)))
+			.unwrap_or(as_of);
+		PeriodTrend {
+			start: growth.period_start,
+			end: last_day.min(as_of),
+			complete: last_day <= as_of,
+			stars: growth.stars,
+			previous_period: GrowthChange {
+				stars: growth.previous_stars,
+				change: growth.change,
+				percent: growth.change_percent,
+			},
+			previous_year: GrowthChange {
+				stars: growth.year_ago_stars,
+				change: growth.year_over_year_change,
+				percent: growth.year_over_year_percent,
+			},
+		}
+	}
+}
+
+/// Axum handler: GET /github/repo_stars/trends/{owner}/{name}
+///
+/// Not behind the ETag middleware, the default `as_of` changes with the date.
+#[utoipa::path(
+    get,
+    path = "/github/repo_stars/trends/{owner}/{name}",
+    operation_id = "star_trends",
+    tag = "repo_stars",
+    summary = "Compare star growth across periods",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        TrendParams,
+    ),
+    responses(
+        (status = 200, description = "Stars per period with their changes, days in UTC", body = TrendReport),
+        (status = 400, description = "Invalid number of periods"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<TrendParams>,
+) -> Result<Json<TrendReport>, HandlerError> {
+    if !(1..=MAX_TREND_PERIODS).contains(&params.periods) {
+        return Err(HandlerError::InvalidPeriods{ periods: params.periods });
+    }
+    let as_of = params.as_of.unwrap_or_else(|| Utc::now().date_naive());
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+
+    let periods = get_period_star_growth(&mut conn, repo.id, params.period, as_of, params.periods as i32)
+		.map_err(|source| HandlerError::GetPeriodStarGrowth{ source })?
+		.into_iter()
+		.map(|growth| PeriodTrend::new(growth, params.period, as_of))
+		.collect();
+
+    Ok(Json(TrendReport { period: params.period, as_of, periods }))
+}
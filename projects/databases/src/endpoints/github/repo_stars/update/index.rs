@@ -1,78 +1,264 @@
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::time::Instant;
+
 use axum::{
+    body::Body,
     extract::{Extension, Json},
-    http::StatusCode,
-    response::IntoResponse,
-};
-use chrono::{NaiveDateTime, Utc};
-use interfaces_github_stargazers::index::{
-    fetch_repo_stargazers, FetchRepoStargazersError, GitHubGraphQLResult, GraphQLResponse,
-    PageInfo, StargazerEdge,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
 };
-use serde::Deserialize;
+use futures_util::stream;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use interfaces_github_stargazers::forge::{Forge, ForgeErrorKind, ForgeRepository, ForgeStargazer};
+use interfaces_github_stargazers::page_size::AdaptivePageSize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use utoipa::ToSchema;
 use uuid::Uuid;
-use diesel::PgConnection;
-use std::env;
+use diesel::{Connection, PgConnection};
+use tracing::{info, warn};
 
+use crate::activity::store_activity;
+use crate::bots::flag_suspected_bots;
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::endpoints::tenant::TenantId;
+use crate::forges::{ForgeClientError, ForgeClients};
+use crate::live::{LiveStargazer, PageSink, SyncEvent, SyncEventKind, SyncNotification};
+use crate::notifier::{AlertNotification, Notifier};
+use crate::privacy::StargazerIds;
+use crate::releases::store_releases;
+use crate::runtime_config::RuntimeConfig;
+use crate::sync_limits::{SyncLimits, SyncStopReason};
+use crate::sync_lock::{RepositoryLock, SyncLockError};
+use crate::validation::{validate_forge_repository, validate_sync_limits, ValidationErrors};
+use crate::sync_queue::{JobProgress, RunSyncTaskError, SyncContext, SyncPace, SyncPriority, SyncQueue, SyncTask};
+use crate::analytics::{
+    milestones::{milestone_crossings, MILESTONES},
+    series::{cumulative_series, daily_series},
+};
 use crate::db::{
+	    alert_rule::{
+	        models::{AlertKind, AlertRule, AlertTarget},
+	        queries::{get_alert_rules_by_repository, update_alert_rule_triggered, GetAlertRulesByRepositoryError, UpdateAlertRuleTriggeredError},
+	    },
+	    daily_star_count::queries::{refresh_daily_star_counts, RefreshDailyStarCountsError},
+	    milestone::{
+	        models::NewMilestone,
+	        queries::{insert_milestones, InsertMilestonesError},
+	    },
 	    repository::{
-	        models::NewRepository,
-	        queries::{insert_repository, InsertRepositoryError},
+	        models::{NewRepository, Repository, RepositoryMetadata},
+	        queries::{
+	            get_compacted_before, get_repository_by_name, get_repository_by_node_id, insert_repository, record_github_identity, update_repository_metadata,
+	            GetCompactedBeforeError, GetRepositoryByNameError, GetRepositoryByNodeIdError, InsertRepositoryError, RecordGitHubIdentityError, UpdateRepositoryMetadataError,
+	        },
 	    },
 	    star::{
 	        models::NewStar,
-	        queries::{insert_star, InsertStarError},
-	    }, PgPool,
+	        queries::{get_daily_star_count, get_star_version, insert_stars_batch, GetDailyStarCountError, InsertStarsBatchError, StarInsertConfig},
+	    },
+	    star::queries::GetStarVersionError,
+	    stargazer_email::{
+	        models::NewStargazerEmail,
+	        queries::{delete_stargazer_emails, upsert_stargazer_emails, DeleteStargazerEmailsError, UpsertStargazerEmailsError},
+	    },
+	    stargazer_profile::{
+	        models::NewStargazerProfile,
+	        queries::{upsert_stargazer_profiles, UpsertStargazerProfilesError},
+	    },
+	    sync_job::{
+	        models::{NewSyncJob, SyncJobStatus},
+	        queries::{
+	            insert_sync_job, record_sync_job_stop_reason, transition_sync_job, update_sync_job_progress, InsertSyncJobError,
+	            RecordSyncJobStopReasonError, TransitionSyncJobError, UpdateSyncJobProgressError,
+	        },
+	    },
+	    sync_run::{
+	        models::{NewSyncRun, SyncRun, SyncTrigger},
+	        queries::{finish_sync_run, insert_sync_run, FinishSyncRunError, InsertSyncRunError},
+	    },
+	    webhook_outbox::{
+	        models::NewWebhookDelivery,
+	        queries::{insert_webhook_delivery, InsertWebhookDeliveryError},
+	    },
 	};
 
 #[derive(Debug, Error)]
 pub enum HandlerError {
-	#[error("GetConnectionFromPool: {source}")]
-	GetConnectionFromPool {
-		#[from]
-		source: r2d2::Error,
-	},
     #[error(transparent)]
-    SyncRepoStargazers{ 
+    RunSyncTask{ 
 		#[from] 
-		source: SyncRepoStargazersError 
+		source: RunSyncTaskError 
+	},
+    #[error("ForgeNotConfigured: {forge}")]
+    ForgeNotConfigured {
+        forge: Forge,
+    },
+    #[error("Validation: {source}")]
+    Validation{
+		#[from]
+		source: ValidationErrors
 	},
-    #[error("MissingGithubToken")]
-    MissingGithubToken,
+    #[error("SyncWorkerGone")]
+    SyncWorkerGone,
 }
 
-impl IntoResponse for HandlerError {
-	fn into_response(self) -> axum::response::Response {
-		match self {
-			HandlerError::SyncRepoStargazers{ source } => (StatusCode::INTERNAL_SERVER_ERROR, source.to_string()).into_response(),
-            HandlerError::MissingGithubToken => (StatusCode::INTERNAL_SERVER_ERROR, "GITHUB_TOKEN environment variable is not set").into_response(),
-            _ => StatusCode::NOT_FOUND.into_response(),
+impl From<HandlerError> for ApiError {
+	fn from(error: HandlerError) -> Self {
+		match error {
+			HandlerError::RunSyncTask{ source } if source.is_sync_in_progress() => ApiError::new(StatusCode::CONFLICT, ErrorCode::SyncInProgress, source.to_string()),
+			HandlerError::RunSyncTask{ source } => ApiError::github(source.status_code(), source),
+            HandlerError::ForgeNotConfigured{ forge } => ApiError::forge_not_configured(forge),
+            HandlerError::Validation{ source } => ApiError::from(source),
+            HandlerError::SyncWorkerGone => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal, "The sync worker stopped before finishing the sync"),
         }
     }
 }
 
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> Response {
+		ApiError::from(self).into_response()
+	}
+}
+
 /// JSON payload expected by the endpoint.
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct RepoQuery {
 	owner: String,
 	name:  String,
+	/// `github` unless set, `gitlab` or `gitea` for projects of those forges.
+	#[serde(default)]
+	#[schema(value_type = String, example = "github")]
+	forge: Forge,
+	/// Position in the sync queue relative to other waiting syncs.
+	#[serde(default)]
+	priority: SyncPriority,
+	/// Tracks the repository's forks with at least this many stars on GitHub too, from now on.
+	/// Ignored on other forges.
+	forks_min_stars: Option<u32>,
+	/// Answers with NDJSON instead, see [`SyncStreamLine`].
+	#[serde(default)]
+	stream: bool,
+	/// Limits of this sync's job, replacing the service's. Each at least 1.
+	#[serde(flatten)]
+	limits: SyncLimits,
+}
+
+/// Line of the answer of a streamed sync.
+///
+/// A `page` line per page as it is fetched, stored meanwhile, then `completed` or `failed`.
+/// The status is `200` once the sync is queued, a failure after that only shows in the last line.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SyncStreamLine {
+	Page {
+		/// From 1.
+		page: u32,
+		stargazers: Vec<LiveStargazer>,
+		/// Stars of the repository on its forge.
+		stargazer_count: i64,
+	},
+	Completed(SyncSummary),
+	Failed {
+		/// Status the answer would have had without streaming.
+		status: u16,
+		error: ApiError,
+	},
 }
 
 
 /// Axum handler: POST /sync-stars
+///
+/// Waits for a sync worker to run the sync and answers with its outcome. With `stream`, the
+/// pages are sent back as NDJSON while being stored, the sync slowing down to the caller's
+/// reading pace rather than buffering, see [`PageSink`].
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/update",
+    operation_id = "sync_repository",
+    tag = "repo_stars",
+    summary = "Sync a repository's stargazers",
+    request_body = RepoQuery,
+    responses(
+        (status = 200, description = "Sync completed, or stopped by a limit with `stop_reason` set", body = SyncSummary),
+        (status = 200, description = "Sync queued, with `stream`: `page` lines, then `completed` or `failed`", body = String, content_type = "application/x-ndjson"),
+        (status = 409, description = "Owner and name tracked on another forge, or repository being synced already"),
+        (status = 422, description = "Owner or name breaking the forge's naming rules, or a limit of 0"),
+        (status = 502, description = "Forge request failed"),
+        (status = 503, description = "Service shutting down or sync interrupted"),
+        (status = 500, description = "Forge not configured, e.g. missing GitHub credentials, or database failure"),
+    ),
+)]
 pub async fn handler(
-    Extension(pool): Extension<PgPool>,
+    Extension(forges): Extension<ForgeClients>,
+    Extension(queue): Extension<SyncQueue>,
+    Extension(config): Extension<RuntimeConfig>,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
     Json(input): Json<RepoQuery>,
-) -> impl IntoResponse {
-    let token = env::var("GITHUB_TOKEN")
-        .map_err(|_| HandlerError::MissingGithubToken)?;
+) -> Result<Response, HandlerError> {
+    let mut errors = ValidationErrors::new();
+    validate_forge_repository(&mut errors, "", input.forge, &input.owner, &input.name);
+    validate_sync_limits(&mut errors, &input.limits);
+    errors.into_result()?;
+
+    if !forges.is_available(input.forge) {
+        return Err(HandlerError::ForgeNotConfigured{ forge: input.forge });
+    }
 
-    let mut conn = pool.get()
-		.map_err(|source| { 
-			HandlerError::GetConnectionFromPool{ source }
-		})?;
+    let (pages, lines) = match input.stream {
+        true => {
+            let (pages, lines) = PageSink::channel();
+            (pages, Some(lines))
+        }
+        false => (PageSink::default(), None),
+    };
+    let task = SyncTask::Repository { tenant_id, forge: input.forge, owner: input.owner, name: input.name, batch_id: None, trigger: SyncTrigger::Api, forks_min_stars: input.forks_min_stars, pages: pages.clone(), limits: config.sync_limits().overridden_by(input.limits) };
+    let outcome = queue.enqueue(task, input.priority);
+    let outcome = async move {
+        outcome
+            .await
+            .map_err(|_| HandlerError::SyncWorkerGone)?
+            .map_err(|source| HandlerError::RunSyncTask{ source })
+    };
 
-    sync_repo_stargazers(&mut conn, &token, &input).await.map_err(|source| { HandlerError::SyncRepoStargazers{ source } })
+    let Some(mut lines) = lines else {
+        return Ok(Json(outcome.await?).into_response());
+    };
+
+    // The sync's own sink closes once it is done, this one ends the stream after the outcome.
+    let mut last = pages;
+    tokio::spawn(async move {
+        let line = match outcome.await {
+            Ok(summary) => SyncStreamLine::Completed(summary),
+            Err(error) => {
+                let error = ApiError::from(error);
+                SyncStreamLine::Failed { status: error.status.as_u16(), error }
+            }
+        };
+        last.send(&line).await;
+    });
+
+    let mut response = Body::from_stream(stream::poll_fn(move |cx| lines.poll_recv(cx).map(|line| line.map(Ok::<_, Infallible>)))).into_response();
+    response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"));
+    Ok(response)
+}
+
+/// Outcome of a completed sync, returned to the caller.
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct SyncSummary {
+	pub job_id: Uuid,
+	pub repository_id: Uuid,
+	pub pages: u32,
+	pub stars: u64,
+	/// Transient forge failures that were retried along the way.
+	pub retries: u32,
+	/// Forks synced along by fork tracking, `owner/name`.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub forks: Vec<String>,
+	/// Limit that stopped the sync before the last page, its job being `partially_completed`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub stop_reason: Option<SyncStopReason>,
 }
 
 #[derive(Debug, Error)]
@@ -82,69 +268,840 @@ pub enum SyncRepoStargazersError {
 		#[from] 
 		source: FetchChunkOfStarsFromRepoError
 	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName{
+		#[from] 
+		source: GetRepositoryByNameError
+	},
+	#[error("GetRepositoryByNodeId: {source}")]
+	GetRepositoryByNodeId{
+		#[from] 
+		source: GetRepositoryByNodeIdError
+	},
 	#[error("InsertRepository: {source}")]
 	InsertRepository{
 		#[from] 
 		source: InsertRepositoryError
 	},
-	#[error("UpsertStars: {source}")]
-	UpsertStars {
+	#[error("RecordGitHubIdentity: {source}")]
+	RecordGitHubIdentity{
 		#[from] 
-		source: UpsertStarsError
+		source: RecordGitHubIdentityError
+	},
+	#[error("UpdateRepositoryMetadata: {source}")]
+	UpdateRepositoryMetadata{
+		#[from] 
+		source: UpdateRepositoryMetadataError
+	},
+	#[error("InsertSyncJob: {source}")]
+	InsertSyncJob{
+		#[from] 
+		source: InsertSyncJobError
+	},
+	#[error("StorePages: {source}")]
+	StorePages {
+		#[from] 
+		source: StorePagesError
+	},
+	#[error("ForgeMismatch: {owner}/{name} is tracked on {tracked}")]
+	ForgeMismatch {
+		owner:   String,
+		name:    String,
+		tracked: Forge,
 	},
+	#[error("LockRepository: {source}")]
+	LockRepository {
+		#[from]
+		source: SyncLockError
+	},
+	#[error("SyncInProgress: {owner}/{name} is being synced by another worker or instance")]
+	SyncInProgress {
+		owner: String,
+		name:  String,
+	},
+	#[error("Interrupted")]
+	Interrupted,
+}
+
+impl SyncRepoStargazersError {
+	/// HTTP status reported to the caller, forge failures keep their meaning.
+	pub fn status_code(&self) -> StatusCode {
+		match self {
+			SyncRepoStargazersError::FetchChunkOfStarsFromRepo{ source } => source.status_code(),
+			SyncRepoStargazersError::StorePages{ source } => source.status_code(),
+			SyncRepoStargazersError::ForgeMismatch{ .. } | SyncRepoStargazersError::SyncInProgress{ .. } => StatusCode::CONFLICT,
+			SyncRepoStargazersError::Interrupted => StatusCode::SERVICE_UNAVAILABLE,
+			_ => StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
+
+/// Fetches and stores all stars for a repository of `forge`, until one of `limits` is reached.
+/// Turned down while another worker or instance syncs the repository, see [`crate::sync_lock`].
+#[allow(clippy::too_many_arguments)]
+pub async fn sync_repo_stargazers(
+    conn: &mut PgConnection,
+    context: &SyncContext,
+    tenant_id: Uuid,
+    forge: Forge,
+    owner: &str,
+    name: &str,
+    batch_id: Option<Uuid>,
+    trigger: SyncTrigger,
+    pages: &mut PageSink,
+    limits: SyncLimits,
+) -> Result<SyncSummary, SyncRepoStargazersError> {
+    // First page guarantees repo's existence, nothing is stored yet if shutdown interrupts it.
+    let first = tokio::select! {
+        fetched = fetch_chunk_of_stars_from_repo(&context.forges, forge, owner, name, None, AdaptivePageSize::new(context.forges.page_size()), context.collects_profiles(forge)) => fetched
+			.map_err(|source| SyncRepoStargazersError::FetchChunkOfStarsFromRepo{ source })?,
+        _ = context.stop.stopped() => return Err(SyncRepoStargazersError::Interrupted),
+    };
+
+    let repo = resolve_repository(conn, tenant_id, forge, owner, name, &first).await?;
+
+    // Taken before the job is created, a turned down sync leaves nothing behind.
+    let Some(mut lock) = RepositoryLock::try_acquire(conn, repo.id)
+		.map_err(|source| SyncRepoStargazersError::LockRepository{ source })? else {
+        return Err(SyncRepoStargazersError::SyncInProgress{ owner: repo.owner, name: repo.name });
+    };
+
+    sync_locked_repository(&mut lock, context, &repo, batch_id, trigger, first, pages, limits).await
 }
 
-/// Fetches and stores all stars for a GitHub repository
-pub async fn sync_repo_stargazers(conn: &mut PgConnection, token: &str, q: &RepoQuery) -> Result<(), SyncRepoStargazersError> {
-    // First page guarantees repo's existence.
-    let first = fetch_chunk_of_stars_from_repo(token, &q.owner, &q.name, None)
+/// Creates the sync job of `repo`, locked by the caller, and stores `first` and the following pages.
+#[allow(clippy::too_many_arguments)]
+async fn sync_locked_repository(
+    conn: &mut PgConnection,
+    context: &SyncContext,
+    repo: &Repository,
+    batch_id: Option<Uuid>,
+    trigger: SyncTrigger,
+    first: Page,
+    pages: &mut PageSink,
+    limits: SyncLimits,
+) -> Result<SyncSummary, SyncRepoStargazersError> {
+    let new_job = NewSyncJob {
+        id: Uuid::new_v4(),
+        repository_id: repo.id,
+        status: SyncJobStatus::Running.as_str(),
+        batch_id,
+        started_at: Some(Utc::now().naive_utc()),
+    };
+
+    let job = insert_sync_job(conn, &new_job)
+		.map_err(|source| SyncRepoStargazersError::InsertSyncJob{ source })?;
+
+    let summary = SyncSummary { job_id: job.id, repository_id: repo.id, ..SyncSummary::default() };
+    publish_started(context, repo, job.id);
+
+    store_pages(conn, context, repo, trigger, first, summary, pages, limits)
 		.await
-		.map_err(|source| SyncRepoStargazersError::FetchChunkOfStarsFromRepo{ source })?;
+		.map_err(|source| SyncRepoStargazersError::StorePages{ source })
+}
 
+/// The tenant's repository the forge answered for, found by its ID on the forge, then by its
+/// current name, then by the requested one. Renames and transfers are recorded in place, the
+/// former name becoming an alias, so the star history isn't split. Owners and names are unique
+/// per tenant whatever the forge, one tracked on another forge is a conflict. Its description,
+/// language, license, topics and creation date are refreshed from the first page.
+async fn resolve_repository(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    forge: Forge,
+    owner: &str,
+    name: &str,
+    first: &Page,
+) -> Result<Repository, SyncRepoStargazersError> {
+    let metadata = &first.repository;
+    let by_node_id = get_repository_by_node_id(conn, tenant_id, forge.as_str(), &metadata.id)
+		.map_err(|source| SyncRepoStargazersError::GetRepositoryByNodeId{ source })?;
+    let current = get_repository_by_name(conn, tenant_id, &metadata.owner, &metadata.name)
+		.await
+		.map_err(|source| SyncRepoStargazersError::GetRepositoryByName{ source })?;
+    // A repository tracked under the new name before node IDs were stored, e.g. imported, keeps it.
+    let (repo, taken) = match (by_node_id, current) {
+        (Some(repo), Some(other)) => {
+            let taken = other.id != repo.id && other.owner.eq_ignore_ascii_case(&metadata.owner) && other.name.eq_ignore_ascii_case(&metadata.name);
+            (repo, taken)
+        }
+        (Some(repo), None) | (None, Some(repo)) => (repo, false),
+        (None, None) => match get_repository_by_name(conn, tenant_id, owner, name)
+			.await
+			.map_err(|source| SyncRepoStargazersError::GetRepositoryByName{ source })? {
+            Some(repo) => (repo, false),
+            None => return insert_repository_from_page(conn, tenant_id, forge, first).and_then(|repo| same_forge(repo, forge)),
+        },
+    };
+    let repo = same_forge(repo, forge)?;
+
+    let unchanged = repo.github_node_id.as_deref() == Some(metadata.id.as_str())
+        && repo.owner == metadata.owner
+        && repo.name == metadata.name;
+    let repo = if unchanged { repo } else { record_identity(conn, forge, repo, taken, first)? };
+
+    // Repositories first tracked without them, e.g. imported, get them too, and edits on the forge are picked up.
+    update_repository_metadata(conn, repo.id, &repository_metadata(first))
+		.map_err(|source| SyncRepoStargazersError::UpdateRepositoryMetadata{ source })
+}
+
+/// Records the forge's node ID, owner and name of `repo`, keeping its former name when `taken`.
+fn record_identity(
+    conn: &mut PgConnection,
+    forge: Forge,
+    repo: Repository,
+    taken: bool,
+    first: &Page,
+) -> Result<Repository, SyncRepoStargazersError> {
+    let metadata = &first.repository;
+    let renamed_to = format!("{}/{}", metadata.owner, metadata.name);
+    let (new_owner, new_name) = if taken {
+        warn!(owner = %repo.owner, name = %repo.name, %renamed_to, "renamed repository tracked twice, keeping its former name");
+        (repo.owner.as_str(), repo.name.as_str())
+    } else {
+        if !repo.owner.eq_ignore_ascii_case(&metadata.owner) || !repo.name.eq_ignore_ascii_case(&metadata.name) {
+            info!(owner = %repo.owner, name = %repo.name, %renamed_to, %forge, "repository renamed on its forge");
+        }
+        (metadata.owner.as_str(), metadata.name.as_str())
+    };
+
+    record_github_identity(conn, &repo, &metadata.id, new_owner, new_name)
+		.map_err(|source| SyncRepoStargazersError::RecordGitHubIdentity{ source })
+}
 
+/// `repo` when it is tracked on `forge`.
+fn same_forge(repo: Repository, forge: Forge) -> Result<Repository, SyncRepoStargazersError> {
+    let tracked = repo.forge();
+    if tracked == forge {
+        return Ok(repo);
+    }
+    Err(SyncRepoStargazersError::ForgeMismatch{ owner: repo.owner, name: repo.name, tracked })
+}
+
+fn repository_metadata(first: &Page) -> RepositoryMetadata<'_> {
+    let metadata = &first.repository;
+    RepositoryMetadata {
+        description:       metadata.description.as_deref(),
+        primary_language:  metadata.primary_language.as_deref(),
+        license:           metadata.license.as_deref(),
+        topics:            metadata.topics.iter().map(String::as_str).collect(),
+        github_created_at: Some(metadata.created_at.naive_utc()),
+    }
+}
+
+fn insert_repository_from_page(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    forge: Forge,
+    first: &Page,
+) -> Result<Repository, SyncRepoStargazersError> {
+	let metadata = &first.repository;
 	let new_repo = NewRepository {
         id: Uuid::new_v4(),
-        owner: &q.owner,
-        name:  &q.name,
+        owner:             &metadata.owner,
+        name:              &metadata.name,
+        description:       metadata.description.as_deref(),
+        primary_language:  metadata.primary_language.as_deref(),
+        license:           metadata.license.as_deref(),
+        topics:            metadata.topics.iter().map(String::as_str).collect(),
+        github_created_at: Some(metadata.created_at.naive_utc()),
+        tenant_id,
+        github_node_id:    Some(&metadata.id),
+        forge:             forge.as_str(),
     };
 
-    let repo = insert_repository(conn, &new_repo)
-		.map_err(|source| SyncRepoStargazersError::InsertRepository{ source })?;
+    insert_repository(conn, &new_repo)
+		.map_err(|source| SyncRepoStargazersError::InsertRepository{ source })
+}
+
+#[derive(Debug, Error)]
+pub enum StorePagesError {
+	#[error("FetchChunkOfStarsFromRepo: {source}")]
+	FetchChunkOfStarsFromRepo{
+		#[from] 
+		source: FetchChunkOfStarsFromRepoError
+	},
+	#[error("UpsertStars: {source}")]
+	UpsertStars {
+		#[from] 
+		source: UpsertStarsError
+	},
+	#[error("UpsertStargazerProfiles: {source}")]
+	UpsertStargazerProfiles {
+		#[from] 
+		source: UpsertStargazerProfilesError
+	},
+	#[error("StoreStargazerEmails: {source}")]
+	StoreStargazerEmails {
+		#[from] 
+		source: StoreStargazerEmailsError
+	},
+	#[error("UpdateSyncJobProgress: {source}")]
+	UpdateSyncJobProgress {
+		#[from] 
+		source: UpdateSyncJobProgressError
+	},
+	#[error("RefreshDailyStarCounts: {source}")]
+	RefreshDailyStarCounts {
+		#[from] 
+		source: RefreshDailyStarCountsError
+	},
+	#[error("RecordMilestones: {source}")]
+	RecordMilestones {
+		#[from] 
+		source: RecordMilestonesError
+	},
+	#[error("RecordSyncJobStopReason: {source}")]
+	RecordSyncJobStopReason {
+		#[from]
+		source: RecordSyncJobStopReasonError
+	},
+	#[error("FinishSyncJob: {source}")]
+	FinishSyncJob {
+		#[from]
+		source: FinishSyncJobError
+	},
+	#[error("Interrupted")]
+	Interrupted,
+}
+
+impl StorePagesError {
+	pub fn status_code(&self) -> StatusCode {
+		match self {
+			StorePagesError::FetchChunkOfStarsFromRepo{ source } => source.status_code(),
+			StorePagesError::Interrupted => StatusCode::SERVICE_UNAVAILABLE,
+			_ => StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
 
-    // Persist every page of stars.
+/// Persists `page` and every following one, checkpointing the job after each page.
+///
+/// The job ends up `completed`, `partially_completed` when one of `limits` is reached,
+/// `interrupted` when `stop` fires, or `failed` with the error. The last two can be
+/// resumed from the last stored cursor.
+/// Each call is recorded as a sync run of the job, started by `trigger`, its end announced on the
+/// notify channel of [`crate::live`].
+/// Alert rules of the repository are evaluated unless the job failed or was interrupted,
+/// webhooks queued in the outbox along with its final status, emails sent once it is recorded.
+/// The releases of a GitHub repository are refreshed after a sync storing every page it meant to.
+/// Each page is sent to `pages` before being stored.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn store_pages(
+    conn: &mut PgConnection,
+    context: &SyncContext,
+    repo: &Repository,
+    trigger: SyncTrigger,
+    page: Page,
+    mut summary: SyncSummary,
+    pages: &mut PageSink,
+    limits: SyncLimits,
+) -> Result<SyncSummary, StorePagesError> {
+    let job_id = summary.job_id;
+    let pages_before = summary.pages;
+    // Written to every star of the run, and to the run itself so it can be diffed with another.
     let fetched_at = Utc::now().naive_utc();
-    upsert_stars(conn, &repo.id, &first.stars, fetched_at).map_err(|source| SyncRepoStargazersError::UpsertStars{ source })?;
+    // The sync history is an audit trail, failing to write it must not fail the sync.
+    let run = start_sync_run(conn, repo, job_id, trigger, fetched_at)
+        .inspect_err(|error| warn!(owner = %repo.owner, name = %repo.name, %error, "sync run not recorded"))
+        .ok();
 
-    let mut info = first.page_info;
-    let mut cursor = info.end_cursor;
+    // Days before the earliest star the run stored are left as they are. GitHub lists stargazers
+    // oldest first, it is on the first page there.
+    let mut since = None;
+    let result = match paginate_and_store(conn, context, repo, page, &mut summary, &mut since, fetched_at, pages, limits).await {
+        Ok(()) => refresh_counts(conn, repo, since)
+            .and_then(|()| record_milestones(conn, repo.id)
+				.map_err(|source| StorePagesError::RecordMilestones{ source })),
+        Err(source) => {
+            // The pages stored before the failure count too, a resume only refreshes from its own.
+            if let Err(error) = refresh_counts(conn, repo, since) {
+                warn!(owner = %repo.owner, name = %repo.name, %error, "daily star counts not refreshed");
+            }
+            Err(source)
+        }
+    };
 
-    while info.has_next_page {
-        let page = fetch_chunk_of_stars_from_repo(token, &q.owner, &q.name, cursor.as_deref()).await?;
-        upsert_stars(conn, &repo.id, &page.stars, fetched_at).map_err(|source| SyncRepoStargazersError::UpsertStars{ source })?;
+    let (status, error) = match &result {
+        Ok(_) if summary.stop_reason.is_some() => (SyncJobStatus::PartiallyCompleted, None),
+        Ok(_) => (SyncJobStatus::Completed, None),
+        Err(StorePagesError::Interrupted) => (SyncJobStatus::Interrupted, None),
+        Err(source) => (SyncJobStatus::Failed, Some(source.to_string())),
+    };
 
-        info = page.page_info;
-        cursor = info.end_cursor;
+    // Only the stars of a sync that stored every page it meant to fire alerts, a failing rule
+    // must not fail the sync.
+    let alerts = match &result {
+        Ok(()) => fired_alerts(conn, repo).unwrap_or_else(|error| {
+            warn!(owner = %repo.owner, name = %repo.name, %error, "alert rule evaluation failed");
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    };
+    let (webhooks, emails): (Vec<_>, Vec<_>) = alerts.into_iter().partition(|alert| alert.target == AlertTarget::Webhook);
+
+    if let Some(reason) = summary.stop_reason {
+        info!(owner = %repo.owner, name = %repo.name, %job_id, reason = reason.as_str(), pages = summary.pages, stars = summary.stars, "sync stopped by a limit");
+        record_sync_job_stop_reason(conn, job_id, reason)
+			.map_err(|source| StorePagesError::RecordSyncJobStopReason{ source })?;
     }
-    Ok(())
+    finish_sync_job(conn, repo, job_id, status, error.as_deref(), &webhooks)
+		.map_err(|source| StorePagesError::FinishSyncJob{ source })?;
+
+    let stars_added = run.and_then(|(run, stars_before)| {
+        let pages = summary.pages - pages_before;
+        end_sync_run(conn, repo, &run, stars_before, status, pages, error.as_deref())
+            .inspect_err(|error| warn!(owner = %repo.owner, name = %repo.name, %error, "sync run outcome not recorded"))
+            .ok()
+    });
+
+    let notification = SyncNotification {
+        repository_id: repo.id,
+        tenant_id: repo.tenant_id,
+        owner: repo.owner.clone(),
+        name: repo.name.clone(),
+        job_id,
+        status: status.as_str().to_string(),
+        stars_added,
+    };
+    if let Err(error) = context.live.notify_sync_ended(conn, &notification) {
+        warn!(owner = %repo.owner, name = %repo.name, %error, "sync end not notified");
+    }
+
+    let ended = match (&result, error) {
+        (Ok(()), _) => SyncEventKind::Completed { pages: summary.pages, stars: summary.stars },
+        (Err(_), Some(error)) => SyncEventKind::Failed { error },
+        (Err(_), None) => SyncEventKind::Interrupted,
+    };
+    publish(context, repo, job_id, ended);
+    context.charts.invalidate(repo.id);
+
+    result?;
+    if summary.retries > 0 {
+        warn!(owner = %repo.owner, name = %repo.name, retries = summary.retries, "sync needed retries");
+    }
+
+    if let Err(error) = send_alert_emails(conn, &context.notifier, repo, emails).await {
+        warn!(owner = %repo.owner, name = %repo.name, %error, "alert emails not recorded");
+    }
+    if context.fetches_releases(repo.forge()) {
+        if let Err(error) = store_releases(conn, context.forges.github(), repo, fetched_at).await {
+            warn!(owner = %repo.owner, name = %repo.name, %error, "releases not refreshed");
+        }
+    }
+    if context.collects_activity(repo.forge()) {
+        if let Err(error) = store_activity(conn, context.forges.github(), repo, fetched_at).await {
+            warn!(owner = %repo.owner, name = %repo.name, %error, "activity not refreshed");
+        }
+    }
+    // Charts drawn since the job ended lack the releases and activity refreshed after it.
+    context.charts.invalidate(repo.id);
+    Ok(summary)
 }
 
-struct Page {
-    stars:     Vec<StargazerEdge>,
-    page_info: PageInfo,
+/// Recomputes the daily star counts from `since`, the first day stored by the run.
+fn refresh_counts(conn: &mut PgConnection, repo: &Repository, since: Option<NaiveDate>) -> Result<(), StorePagesError> {
+    let Some(since) = since else {
+        return Ok(());
+    };
+    refresh_daily_star_counts(conn, repo.id, Some(since))
+        .map(drop)
+        .map_err(|source| StorePagesError::RefreshDailyStarCounts{ source })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn paginate_and_store(
+    conn: &mut PgConnection,
+    context: &SyncContext,
+    repo: &Repository,
+    mut page: Page,
+    summary: &mut SyncSummary,
+    since: &mut Option<NaiveDate>,
+    fetched_at: NaiveDateTime,
+    pages: &mut PageSink,
+    limits: SyncLimits,
+) -> Result<(), StorePagesError> {
+    let forge = repo.forge();
+    let started = Instant::now();
+    // The pace is measured on this run, time spent before a resume doesn't count.
+    let (stars_before, pages_before) = (summary.stars, summary.pages);
+
+    loop {
+        // Waits while the reader is behind, a page streamed before it is stored is still shown.
+        if pages.is_open() {
+            pages.send(&SyncStreamLine::Page {
+                page: summary.pages + 1,
+                stargazers: live_stargazers(context, &page),
+                stargazer_count: page.stargazer_count,
+            }).await;
+        }
+        let suspected_bots = flag_suspected_bots(context, forge, &page.stars).await;
+        upsert_stars(conn, &repo.id, &page.stars, &suspected_bots, &context.stargazer_ids, fetched_at).map_err(|source| StorePagesError::UpsertStars{ source })?;
+        *since = since.iter().copied().chain(page.stars.iter().map(|star| star.starred_at.date_naive())).min();
+        if context.collects_profiles(forge) {
+            upsert_profiles(conn, &page.stars, fetched_at).map_err(|source| StorePagesError::UpsertStargazerProfiles{ source })?;
+        }
+        if context.collects_emails(forge) {
+            store_emails(conn, &page.stars, fetched_at).map_err(|source| StorePagesError::StoreStargazerEmails{ source })?;
+        }
+        summary.record(&page);
+        let now = Utc::now().naive_utc();
+        let progress = JobProgress {
+            retries: summary.retries.into(),
+            ..JobProgress::new(
+                summary.stars as i64,
+                Some(page.stargazer_count),
+                SyncPace {
+                    stars: (summary.stars - stars_before) as i64,
+                    pages: (summary.pages - pages_before).into(),
+                    elapsed: now - fetched_at,
+                },
+                now,
+            )
+        };
+        publish_page(conn, context, repo, &page, summary, progress);
+
+        let cursor = page.end_cursor;
+        update_sync_job_progress(conn, summary.job_id, cursor.as_deref(), summary.pages as i32, summary.stars as i32, page.stargazer_count as i32, summary.retries as i32)
+			.map_err(|source| StorePagesError::UpdateSyncJobProgress{ source })?;
+
+        if !page.has_next_page {
+            return Ok(());
+        }
+        // Stops short of the next page, the stored cursor tells where the job got to.
+        if let Some(reason) = limits.reached(summary.pages, summary.stars, started.elapsed()) {
+            summary.stop_reason = Some(reason);
+            return Ok(());
+        }
+
+        // The cursor is stored, a fetch in flight can be dropped without losing anything.
+        page = tokio::select! {
+            fetched = fetch_chunk_of_stars_from_repo(&context.forges, forge, &repo.owner, &repo.name, cursor.as_deref(), page.page_size, context.collects_profiles(forge)) => fetched
+				.map_err(|source| StorePagesError::FetchChunkOfStarsFromRepo{ source })?,
+            _ = context.stop.stopped() => return Err(StorePagesError::Interrupted),
+        };
+    }
 }
 
 #[derive(Debug, Error)]
-pub enum FetchChunkOfStarsFromRepoError {
-	#[error("FetchRepoStargazers: {source}")]
-	FetchRepoStargazers{
+pub enum StartSyncRunError {
+	#[error("GetStarVersion: {source}")]
+	GetStarVersion {
+		#[from]
+		source: GetStarVersionError
+	},
+	#[error("InsertSyncRun: {source}")]
+	InsertSyncRun {
+		#[from]
+		source: InsertSyncRunError
+	},
+}
+
+/// Records a running sync run of job `job_id` stamping its stars `fetched_at`, along with the stored star count it starts from.
+fn start_sync_run(
+    conn: &mut PgConnection,
+    repo: &Repository,
+    job_id: Uuid,
+    trigger: SyncTrigger,
+    fetched_at: NaiveDateTime,
+) -> Result<(SyncRun, i64), StartSyncRunError> {
+    let (_, stars_before) = get_star_version(conn, repo.id)
+		.map_err(|source| StartSyncRunError::GetStarVersion{ source })?;
+
+    let new_run = NewSyncRun {
+        id: Uuid::new_v4(),
+        job_id: Some(job_id),
+        repository_id: repo.id,
+        trigger: trigger.as_str(),
+        status: SyncJobStatus::Running.as_str(),
+        fetched_at: Some(fetched_at),
+    };
+
+    let run = insert_sync_run(conn, &new_run)
+		.map_err(|source| StartSyncRunError::InsertSyncRun{ source })?;
+    Ok((run, stars_before))
+}
+
+#[derive(Debug, Error)]
+pub enum EndSyncRunError {
+	#[error("GetStarVersion: {source}")]
+	GetStarVersion {
+		#[from]
+		source: GetStarVersionError
+	},
+	#[error("FinishSyncRun: {source}")]
+	FinishSyncRun {
+		#[from]
+		source: FinishSyncRunError
+	},
+}
+
+/// Records the outcome of `run`, the stars it added being the growth of the stored count since it
+/// started, and returns them.
+fn end_sync_run(
+    conn: &mut PgConnection,
+    repo: &Repository,
+    run: &SyncRun,
+    stars_before: i64,
+    status: SyncJobStatus,
+    pages: u32,
+    error: Option<&str>,
+) -> Result<i64, EndSyncRunError> {
+    let (_, stars_after) = get_star_version(conn, repo.id)
+		.map_err(|source| EndSyncRunError::GetStarVersion{ source })?;
+
+    let stars_added = stars_after - stars_before;
+    finish_sync_run(conn, run.id, status, pages as i32, stars_added as i32, error)
+		.map_err(|source| EndSyncRunError::FinishSyncRun{ source })?;
+    Ok(stars_added)
+}
+
+/// Tells live subscribers that job `job_id` started fetching the pages of `repo`.
+pub(crate) fn publish_started(context: &SyncContext, repo: &Repository, job_id: Uuid) {
+    publish(context, repo, job_id, SyncEventKind::Started);
+}
+
+fn publish(context: &SyncContext, repo: &Repository, job_id: Uuid, kind: SyncEventKind) {
+    context.live.publish(SyncEvent { tenant_id: repo.tenant_id, owner: repo.owner.clone(), name: repo.name.clone(), job_id, kind });
+}
+
+/// Sends the stored page to live subscribers, along with the job's progress and the repository's new star total.
+fn publish_page(conn: &mut PgConnection, context: &SyncContext, repo: &Repository, page: &Page, summary: &SyncSummary, progress: JobProgress) {
+    // Saves the count query on every page when nobody watches.
+    if !context.live.has_subscribers() {
+        return;
+    }
+
+    // Live updates are best effort, the sync goes on without them.
+    let total_stars = match get_star_version(conn, repo.id) {
+        Ok((_, count)) => count,
+        Err(error) => {
+            warn!(owner = %repo.owner, name = %repo.name, %error, "live star total unavailable");
+            return;
+        }
+    };
+
+    publish(context, repo, summary.job_id, SyncEventKind::Page {
+        page: summary.pages,
+        stargazers: live_stargazers(context, page),
+        progress,
+        total_stars,
+    });
+}
+
+/// Stargazers of `page` as subscribers see them, hashed when logins are.
+fn live_stargazers(context: &SyncContext, page: &Page) -> Vec<LiveStargazer> {
+    page.stars
+        .iter()
+        .map(|star| LiveStargazer {
+            login:      context.stargazer_ids.stargazer_id(&star.login).into_owned(),
+            starred_at: star.starred_at.naive_utc(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Error)]
+pub enum RecordMilestonesError {
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
+		#[from] 
+		source: GetDailyStarCountError
+	},
+	#[error("InsertMilestones: {source}")]
+	InsertMilestones {
+		#[from] 
+		source: InsertMilestonesError
+	},
+}
+
+/// Stores the milestones the repository's star history has crossed so far.
+pub(crate) fn record_milestones(conn: &mut PgConnection, repo_id: Uuid) -> Result<(), RecordMilestonesError> {
+    let daily_counts = get_daily_star_count(conn, repo_id)
+		.map_err(|source| RecordMilestonesError::GetDailyStarCount{ source })?;
+
+    let new_milestones: Vec<NewMilestone> = milestone_crossings(&cumulative_series(&daily_counts), &MILESTONES)
+        .into_iter()
+        .map(|(stars, reached_on)| NewMilestone { repository_id: repo_id, stars: stars as i32, reached_on })
+        .collect();
+
+    insert_milestones(conn, &new_milestones)
+		.map_err(|source| RecordMilestonesError::InsertMilestones{ source })?;
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum EvaluateAlertRulesError {
+	#[error("GetAlertRulesByRepository: {source}")]
+	GetAlertRulesByRepository {
+		#[from] 
+		source: GetAlertRulesByRepositoryError
+	},
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
 		#[from] 
-		source: FetchRepoStargazersError
+		source: GetDailyStarCountError
 	},
-	#[error("ResponseBodyDeserialization: {source}")]
-	ResponseBodyDeserialization{
+	#[error("UpdateAlertRuleTriggered: {source}")]
+	UpdateAlertRuleTriggered {
 		#[from] 
+		source: UpdateAlertRuleTriggeredError
+	},
+}
+
+/// Alert fired by a rule of the repository, delivered once the job's final status is recorded.
+struct FiredAlert {
+    rule: AlertRule,
+    kind: AlertKind,
+    target: AlertTarget,
+    day: NaiveDate,
+    stars: i64,
+}
+
+impl FiredAlert {
+    fn notification<'a>(&'a self, repo: &'a Repository) -> AlertNotification<'a> {
+        AlertNotification {
+            owner: &repo.owner,
+            name: &repo.name,
+            kind: self.kind,
+            threshold: self.rule.threshold,
+            day: self.day,
+            stars: self.stars,
+        }
+    }
+}
+
+/// Every rule of the repository whose condition is met since it last fired.
+fn fired_alerts(conn: &mut PgConnection, repo: &Repository) -> Result<Vec<FiredAlert>, EvaluateAlertRulesError> {
+    let rules = get_alert_rules_by_repository(conn, repo.id)
+		.map_err(|source| EvaluateAlertRulesError::GetAlertRulesByRepository{ source })?;
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let daily_counts = get_daily_star_count(conn, repo.id)
+		.map_err(|source| EvaluateAlertRulesError::GetDailyStarCount{ source })?;
+    let daily = daily_series(&daily_counts);
+    let cumulative = cumulative_series(&daily_counts);
+
+    let mut fired = Vec::new();
+    for rule in rules {
+        let (Some(kind), Some(target)) = (AlertKind::parse(&rule.kind), AlertTarget::parse(&rule.target)) else {
+            warn!(rule_id = %rule.id, kind = %rule.kind, target = %rule.target, "alert rule has an unknown kind or target");
+            continue;
+        };
+
+        if let Some((day, stars)) = rule.trigger(kind, &daily, &cumulative) {
+            fired.push(FiredAlert { rule, kind, target, day, stars });
+        }
+    }
+
+    Ok(fired)
+}
+
+#[derive(Debug, Error)]
+pub enum FinishSyncJobError {
+	#[error("TransitionSyncJob: {source}")]
+	TransitionSyncJob {
+		#[from]
+		source: TransitionSyncJobError
+	},
+	#[error("EncodeAlertNotification: {source}")]
+	EncodeAlertNotification {
+		#[from]
 		source: serde_json::Error
 	},
+	#[error("InsertWebhookDelivery: {source}")]
+	InsertWebhookDelivery {
+		#[from]
+		source: InsertWebhookDeliveryError
+	},
+	#[error("UpdateAlertRuleTriggered: {source}")]
+	UpdateAlertRuleTriggered {
+		#[from]
+		source: UpdateAlertRuleTriggeredError
+	},
+	#[error("Transaction: {source}")]
+	Transaction {
+		#[from]
+		source: diesel::result::Error
+	},
+}
+
+/// Moves the job to its final `status` and queues the deliveries of its `webhooks` alerts in the
+/// same transaction, their rules marked fired. See [`crate::outbox`].
+fn finish_sync_job(
+    conn: &mut PgConnection,
+    repo: &Repository,
+    job_id: Uuid,
+    status: SyncJobStatus,
+    error: Option<&str>,
+    webhooks: &[FiredAlert],
+) -> Result<(), FinishSyncJobError> {
+    conn.transaction(|conn| {
+        transition_sync_job(conn, job_id, status, error)?;
+        for alert in webhooks {
+            insert_webhook_delivery(conn, &NewWebhookDelivery {
+                id: Uuid::new_v4(),
+                alert_rule_id: Some(alert.rule.id),
+                job_id: Some(job_id),
+                destination: &alert.rule.destination,
+                payload: serde_json::to_value(alert.notification(repo))?,
+            })?;
+            update_alert_rule_triggered(conn, alert.rule.id, alert.day)?;
+        }
+        Ok(())
+    })
+}
+
+/// Emails the `alerts` fired by a rule with an email target.
+///
+/// A rule whose email fails is left untouched, so it fires again after the next sync.
+async fn send_alert_emails(
+    conn: &mut PgConnection,
+    notifier: &Notifier,
+    repo: &Repository,
+    alerts: Vec<FiredAlert>,
+) -> Result<(), EvaluateAlertRulesError> {
+    for alert in alerts {
+        match notifier.notify(alert.target, &alert.rule.destination, &alert.notification(repo)).await {
+            Ok(()) => update_alert_rule_triggered(conn, alert.rule.id, alert.day)
+				.map_err(|source| EvaluateAlertRulesError::UpdateAlertRuleTriggered{ source })?,
+            Err(error) => warn!(rule_id = %alert.rule.id, %error, "alert delivery failed"),
+        }
+    }
+
+    Ok(())
+}
+
+impl SyncSummary {
+    fn record(&mut self, page: &Page) {
+        self.pages += 1;
+        self.stars += page.stars.len() as u64;
+        self.retries += page.retries;
+    }
+}
+
+pub(crate) struct Page {
+    stars:           Vec<ForgeStargazer>,
+    /// Cursor to fetch the next page with, stored to resume the job.
+    end_cursor:      Option<String>,
+    has_next_page:   bool,
+    retries:         u32,
+    /// Page size to fetch the next page with.
+    page_size:       AdaptivePageSize,
+    /// Stars of the repository on its forge, the job's estimated total.
+    stargazer_count: i64,
+    repository:      ForgeRepository,
+}
+
+#[derive(Debug, Error)]
+pub enum FetchChunkOfStarsFromRepoError {
+	#[error("FetchStargazers: {source}")]
+	FetchStargazers{
+		#[from] 
+		source: ForgeClientError
+	},
 	#[error("RepositoryNotFound: {owner}/{name}")]
 	RepositoryNotFound {
 		owner: String,
@@ -152,56 +1109,180 @@ pub enum FetchChunkOfStarsFromRepoError {
 	},
 }
 
-async fn fetch_chunk_of_stars_from_repo(
-    token: &str,
+impl FetchChunkOfStarsFromRepoError {
+	pub fn status_code(&self) -> StatusCode {
+		match self {
+			FetchChunkOfStarsFromRepoError::FetchStargazers{ source } => source.status_code(),
+			FetchChunkOfStarsFromRepoError::RepositoryNotFound{ .. } => StatusCode::NOT_FOUND,
+		}
+	}
+}
+
+/// Page of stars of the repository on `forge` after `cursor`, sized by `page_size`.
+///
+/// A failure a smaller page may avoid, such as a GitHub secondary rate limit or a query
+/// GitHub gave up on, is retried with a page half the size, as long as it can shrink and
+/// retries are left. The page carries the adjusted size on, for the next fetch of the same sync.
+pub(crate) async fn fetch_chunk_of_stars_from_repo(
+    forges: &ForgeClients,
+    forge: Forge,
     owner: &str,
     name:  &str,
     cursor: Option<&str>,
+    mut page_size: AdaptivePageSize,
+    with_profiles: bool,
 ) -> Result<Page, FetchChunkOfStarsFromRepoError> {
-    let GitHubGraphQLResult { body, .. } =
-        fetch_repo_stargazers(token, owner, name, cursor).await.map_err(|source| FetchChunkOfStarsFromRepoError::FetchRepoStargazers{ source })?;
+    let retry = forges.retry(forge);
+    let mut retries = 0;
+    let page = loop {
+        match forges.fetch_stargazers(forge, owner, name, cursor, page_size.current(), with_profiles).await {
+            Ok(page) => break page,
+            Err(source) if source.kind() == ForgeErrorKind::Overloaded && retries + 1 < retry.max_attempts && page_size.shrink() => {
+                warn!(owner, name, %forge, %source, page_size = page_size.current(), "forge struggled with the page, shrinking it");
+                tokio::time::sleep(retry.backoff_delay(retries)).await;
+                retries += 1;
+            }
+            Err(source) => return Err(FetchChunkOfStarsFromRepoError::FetchStargazers{ source }),
+        }
+    };
+    page_size.record_success();
 
-    let parsed: GraphQLResponse = serde_json::from_str(&body).map_err(|source| FetchChunkOfStarsFromRepoError::ResponseBodyDeserialization{ source })?;
-    let repo = parsed
-        .data
-        .repository
+    let page = page
         .ok_or_else(|| FetchChunkOfStarsFromRepoError::RepositoryNotFound {
             owner: owner.into(),
             name:  name.into(),
         })?;
 
     Ok(Page {
-        stars: repo.stargazers.edges,
-        page_info: repo.stargazers.page_info,
+        stars: page.stargazers,
+        end_cursor: page.end_cursor,
+        has_next_page: page.has_next_page,
+        retries: retries + page.retries,
+        page_size,
+        stargazer_count: page.repository.stargazer_count,
+        repository: page.repository,
     })
 }
 
 #[derive(Debug, Error)]
 pub enum UpsertStarsError {
-	#[error("InsertStar: {source}")]
-	InsertStar{
+	#[error("GetCompactedBefore: {source}")]
+	GetCompactedBefore{
+		#[from]
+		source: GetCompactedBeforeError
+	},
+	#[error("InsertStarsBatch: {source}")]
+	InsertStarsBatch{
 		#[from] 
-		source: InsertStarError
+		source: InsertStarsBatchError
 	},
 }
 
+/// Stores a page of stargazers under their `ids`, flagging the stars of the `suspected_bots` logins,
+/// a single statement whatever size the adaptive pagination settled on, pages never exceeding
+/// [`GITHUB_MAX_PAGE_SIZE`](interfaces_github_stargazers::page_size::GITHUB_MAX_PAGE_SIZE).
+///
+/// Stars of compacted days are skipped, they are already counted in the daily aggregates.
 #[inline]
 fn upsert_stars(
     conn: &mut PgConnection,
     repo_id: &Uuid,
-    stars: &[StargazerEdge],
+    stars: &[ForgeStargazer],
+    suspected_bots: &HashSet<String>,
+    ids: &StargazerIds,
     fetched_at: NaiveDateTime,
 ) -> Result<(), UpsertStarsError> {
-    for star in stars {
-        let new_star = NewStar {
+    // Read on every page, compaction may run while the sync does.
+    let compacted_before = get_compacted_before(conn, *repo_id).map_err(|source| UpsertStarsError::GetCompactedBefore { source })?;
+    let stars: Vec<_> = stars
+        .iter()
+        .filter(|star| compacted_before.is_none_or(|before| star.starred_at.date_naive() >= before))
+        .collect();
+
+    let stargazers: Vec<_> = stars.iter().map(|star| ids.stargazer_id(&star.login)).collect();
+    let new_stars: Vec<NewStar> = stars
+        .iter()
+        .zip(&stargazers)
+        .map(|(star, stargazer)| NewStar {
             repository_id: *repo_id,
-            stargazer:     &star.node.login,
+            stargazer,
             starred_at:    star.starred_at.naive_utc(),
             fetched_at,
-        };
+            first_fetched_at: fetched_at,
+            suspected_bot: suspected_bots.contains(&star.login),
+        })
+        .collect();
 
-        insert_star(conn, &new_star).map_err(|source|UpsertStarsError::InsertStar { source })?;
-    }
+    // Pages are far below the COPY threshold, always inserted.
+    insert_stars_batch(conn, &new_stars, &StarInsertConfig::default()).map_err(|source| UpsertStarsError::InsertStarsBatch { source })?;
+
+    Ok(())
+}
+
+/// Stores the company and location of a page of stargazers, blank fields as unset.
+fn upsert_profiles(
+    conn: &mut PgConnection,
+    stars: &[ForgeStargazer],
+    fetched_at: NaiveDateTime,
+) -> Result<usize, UpsertStargazerProfilesError> {
+    let profiles: Vec<NewStargazerProfile> = stars
+        .iter()
+        .map(|star| NewStargazerProfile {
+            login:      &star.login,
+            company:    non_blank(&star.company),
+            location:   non_blank(&star.location),
+            updated_at: fetched_at,
+        })
+        .collect();
 
+    upsert_stargazer_profiles(conn, &profiles)
+}
+
+#[derive(Debug, Error)]
+pub enum StoreStargazerEmailsError {
+	#[error("UpsertStargazerEmails: {source}")]
+	UpsertStargazerEmails {
+		#[from] 
+		source: UpsertStargazerEmailsError
+	},
+	#[error("DeleteStargazerEmails: {source}")]
+	DeleteStargazerEmails {
+		#[from] 
+		source: DeleteStargazerEmailsError
+	},
+}
+
+/// Stores the public emails of a page of stargazers, forgetting those of the ones showing none.
+/// GitHub's no-reply addresses don't reach anyone and count as none.
+fn store_emails(
+    conn: &mut PgConnection,
+    stars: &[ForgeStargazer],
+    fetched_at: NaiveDateTime,
+) -> Result<(), StoreStargazerEmailsError> {
+    let (public, hidden): (Vec<_>, Vec<_>) = stars
+        .iter()
+        .map(|star| (star.login.as_str(), non_blank(&star.email).filter(|email| is_reachable_email(email))))
+        .partition(|(_, email)| email.is_some());
+
+    let emails: Vec<NewStargazerEmail> = public
+        .into_iter()
+        .filter_map(|(login, email)| Some(NewStargazerEmail { login, email: email?, updated_at: fetched_at }))
+        .collect();
+    let hidden: Vec<&str> = hidden.into_iter().map(|(login, _)| login).collect();
+
+    if !emails.is_empty() {
+        upsert_stargazer_emails(conn, &emails)?;
+    }
+    if !hidden.is_empty() {
+        delete_stargazer_emails(conn, &hidden)?;
+    }
     Ok(())
 }
+
+fn is_reachable_email(email: &str) -> bool {
+    email.contains('@') && !email.to_ascii_lowercase().ends_with("@users.noreply.github.com")
+}
+
+fn non_blank(field: &Option<String>) -> Option<&str> {
+    field.as_deref().map(str::trim).filter(|field| !field.is_empty())
+}
@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Extension, Json},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use interfaces_github_stargazers::forge::Forge;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    sync_batch::{
+	        models::NewSyncBatch,
+	        queries::{insert_sync_batch, InsertSyncBatchError},
+	    },
+	    sync_run::models::SyncTrigger,
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+use crate::forges::ForgeClients;
+use crate::validation::{validate_forge_repository, validate_sync_limits, ValidationErrors};
+use crate::endpoints::github::org_stars::update::index::BatchCreated;
+use crate::live::PageSink;
+use crate::runtime_config::RuntimeConfig;
+use crate::sync_limits::SyncLimits;
+use crate::sync_queue::{SyncPriority, SyncQueue, SyncTask};
+
+/// Upper bound of the repositories of a single batch.
+pub const MAX_BATCH_REPOSITORIES: usize = 500;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("ForgeNotConfigured: {forge}")]
+	ForgeNotConfigured {
+		forge: Forge,
+	},
+	#[error("InvalidRepositoryCount: {count}")]
+	InvalidRepositoryCount {
+		count: usize,
+	},
+	#[error("Validation: {source}")]
+	Validation {
+		#[from]
+		source: ValidationErrors,
+	},
+	#[error("InsertSyncBatch: {source}")]
+	InsertSyncBatch {
+		#[from]
+		source: InsertSyncBatchError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::ForgeNotConfigured{ forge } => ApiError::forge_not_configured(forge).into_response(),
+			HandlerError::InvalidRepositoryCount{ count } => ApiError::invalid_request(format!("Expected 1 to {MAX_BATCH_REPOSITORIES} repositories, got {count}")).with_details(json!({ "count": count, "max": MAX_BATCH_REPOSITORIES })).into_response(),
+			HandlerError::Validation{ source } => ApiError::from(source).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// JSON payload expected by the endpoint.
+#[derive(Deserialize, ToSchema)]
+pub struct BatchQuery {
+	/// Repositories listed twice, in any case, are synced once.
+	repositories: Vec<BatchRepository>,
+	/// Position of the batch's syncs in the sync queue, `low` by default.
+	#[serde(default = "default_priority")]
+	priority: SyncPriority,
+	/// Limits of each repository's job, replacing the service's. Each at least 1.
+	#[serde(flatten)]
+	limits: SyncLimits,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BatchRepository {
+	owner: String,
+	name: String,
+	/// `github` unless set.
+	#[serde(default)]
+	#[schema(value_type = String, example = "github")]
+	forge: Forge,
+}
+
+fn default_priority() -> SyncPriority {
+	SyncPriority::Low
+}
+
+/// Axum handler: POST /github/repo_stars/update_batch
+///
+/// Queues a sync for each listed repository and answers right away, progress
+/// is reported by the batch endpoint. The sync queue bounds how many run at once.
+#[utoipa::path(
+    post,
+    path = "/github/repo_stars/update_batch",
+    operation_id = "sync_repositories",
+    tag = "repo_stars",
+    summary = "Sync many repositories",
+    request_body = BatchQuery,
+    responses(
+        (status = 202, description = "Syncs queued, repositories as `owner/name`", body = BatchCreated),
+        (status = 400, description = "No repository or too many"),
+        (status = 422, description = "Owner or name breaking the forge's naming rules, or a limit of 0"),
+        (status = 500, description = "Forge not configured, e.g. missing GitHub credentials, or database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(forges): Extension<ForgeClients>,
+    Extension(queue): Extension<SyncQueue>,
+    Extension(config): Extension<RuntimeConfig>,
+    Json(input): Json<BatchQuery>,
+) -> Result<(StatusCode, Json<BatchCreated>), HandlerError> {
+    let repositories = unique_repositories(input.repositories, &input.limits)?;
+    if let Some(repo) = repositories.iter().find(|repo| !forges.is_available(repo.forge)) {
+        return Err(HandlerError::ForgeNotConfigured{ forge: repo.forge });
+    }
+
+    let new_batch = NewSyncBatch {
+        id: Uuid::new_v4(),
+        owner: None,
+        total_repositories: repositories.len() as i32,
+        tenant_id,
+    };
+
+    let batch = insert_sync_batch(&mut conn, &new_batch)
+		.map_err(|source| HandlerError::InsertSyncBatch{ source })?;
+
+    let limits = config.sync_limits().overridden_by(input.limits);
+    for repo in &repositories {
+        let task = SyncTask::Repository { tenant_id, forge: repo.forge, owner: repo.owner.clone(), name: repo.name.clone(), batch_id: Some(batch.id), trigger: SyncTrigger::Batch, forks_min_stars: None, pages: PageSink::default(), limits };
+        // Progress is tracked through the batch's sync jobs, not the task outcome.
+        drop(queue.enqueue(task, input.priority));
+    }
+
+    let repositories = repositories
+        .into_iter()
+        .map(|repo| format!("{}/{}", repo.owner, repo.name))
+        .collect();
+
+    Ok((StatusCode::ACCEPTED, Json(BatchCreated { batch_id: batch.id, repositories })))
+}
+
+/// Checks the listed repositories and the limits, dropping the repositories listed more than once.
+fn unique_repositories(repositories: Vec<BatchRepository>, limits: &SyncLimits) -> Result<Vec<BatchRepository>, HandlerError> {
+    if !(1..=MAX_BATCH_REPOSITORIES).contains(&repositories.len()) {
+        return Err(HandlerError::InvalidRepositoryCount{ count: repositories.len() });
+    }
+
+    let mut errors = ValidationErrors::new();
+    let mut seen = HashSet::new();
+    let mut unique = Vec::with_capacity(repositories.len());
+    for (index, repo) in repositories.iter().enumerate() {
+        let (owner, name) = (repo.owner.trim(), repo.name.trim());
+        validate_forge_repository(&mut errors, &format!("repositories[{index}]."), repo.forge, owner, name);
+
+        // Names are case-insensitive, and unique across forges.
+        if seen.insert((owner.to_ascii_lowercase(), name.to_ascii_lowercase())) {
+            unique.push(BatchRepository { owner: owner.into(), name: name.into(), forge: repo.forge });
+        }
+    }
+    validate_sync_limits(&mut errors, limits);
+    errors.into_result()?;
+    Ok(unique)
+}
@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    report::queries::{get_report, GetReportError},
+	};
+use crate::endpoints::github::reports::list::index::ReportSummary;
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetReport: {source}")]
+	GetReport {
+		#[from]
+		source: GetReportError,
+	},
+	#[error("ReportNotFound: {id}")]
+	ReportNotFound {
+		id: Uuid,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::ReportNotFound{ id } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::ReportNotFound, format!("Report {id} not found")).with_details(json!({ "id": id })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+	#[default]
+	Json,
+	/// The report as emailed, charts inlined as SVG.
+	Html,
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportParams {
+	#[serde(default)]
+	format: ReportFormat,
+}
+
+/// JSON representation of a report.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ReportResponse {
+	#[serde(flatten)]
+	pub summary: ReportSummary,
+	/// A `WeeklyDigest` for weekly digests.
+	#[schema(value_type = Object)]
+	pub content: serde_json::Value,
+}
+
+/// Axum handler: GET /github/reports/{id}?format=json|html
+#[utoipa::path(
+    get,
+    path = "/github/reports/{id}",
+    operation_id = "get_report",
+    tag = "reports",
+    summary = "Fetch a generated report",
+    params(
+        ("id" = Uuid, Path, description = "Report ID"),
+        ReportParams,
+    ),
+    responses(
+        (status = 200, description = "The report, as JSON or as an HTML page", body = ReportResponse),
+        (status = 404, description = "No such report"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ReportParams>,
+) -> Result<Response, HandlerError> {
+    let report = get_report(&mut conn, tenant_id, id)
+		.map_err(|source| HandlerError::GetReport{ source })?
+		.ok_or(HandlerError::ReportNotFound{ id })?;
+
+    Ok(match params.format {
+        ReportFormat::Json => Json(ReportResponse { summary: ReportSummary::from(&report), content: report.content }).into_response(),
+        ReportFormat::Html => Html(report.html).into_response(),
+    })
+}
@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Extension, Json, Query},
+    response::IntoResponse,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    report::{
+	        models::Report,
+	        queries::{list_reports, ListReportsError},
+	    },
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Upper bound of `limit`, keeps a single answer reasonably small.
+const MAX_LIMIT: i64 = 500;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidLimit: {limit}")]
+	InvalidLimit {
+		limit: i64,
+	},
+	#[error("ListReports: {source}")]
+	ListReports {
+		#[from]
+		source: ListReportsError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidLimit{ limit } => ApiError::invalid_request(format!("limit must be between 1 and {MAX_LIMIT}, got {limit}")).with_details(json!({ "limit": limit, "max": MAX_LIMIT })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+	#[serde(default = "default_limit")]
+	limit: i64,
+}
+
+fn default_limit() -> i64 {
+	52
+}
+
+/// JSON representation of a report, without its content.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ReportSummary {
+	pub id: Uuid,
+	/// `weekly_digest`
+	pub kind: String,
+	pub period_start: NaiveDate,
+	/// Last day covered.
+	pub period_end: NaiveDate,
+	/// Addresses the report was emailed to.
+	pub sent_to: Vec<String>,
+	/// Unset until the report is emailed.
+	pub sent_at: Option<NaiveDateTime>,
+	pub created_at: NaiveDateTime,
+}
+
+impl From<&Report> for ReportSummary {
+	fn from(report: &Report) -> Self {
+		Self {
+			id: report.id,
+			kind: report.kind.clone(),
+			period_start: report.period_start,
+			period_end: report.period_end,
+			sent_to: report.sent_to.clone(),
+			sent_at: report.sent_at,
+			created_at: report.created_at,
+		}
+	}
+}
+
+/// Axum handler: GET /github/reports?limit=N
+#[utoipa::path(
+    get,
+    path = "/github/reports",
+    operation_id = "list_reports",
+    tag = "reports",
+    summary = "List generated reports",
+    params(
+        ListParams,
+    ),
+    responses(
+        (status = 200, description = "Reports, most recent period first", body = Vec<ReportSummary>),
+        (status = 400, description = "Invalid limit"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<ReportSummary>>, HandlerError> {
+    if !(1..=MAX_LIMIT).contains(&params.limit) {
+        return Err(HandlerError::InvalidLimit{ limit: params.limit });
+    }
+
+    let reports = list_reports(&mut conn, tenant_id, params.limit)
+		.map_err(|source| HandlerError::ListReports{ source })?;
+
+    Ok(Json(reports.iter().map(ReportSummary::from).collect()))
+}
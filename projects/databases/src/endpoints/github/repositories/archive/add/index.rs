@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use thiserror::Error;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::repository::queries::{get_repository_by_name, set_repository_archived, GetRepositoryByNameError, SetRepositoryArchivedError};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("SetRepositoryArchived: {source}")]
+	SetRepositoryArchived {
+		#[from]
+		source: SetRepositoryArchivedError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: PUT /github/repositories/{owner}/{name}/archive
+///
+/// Hides the repository from listings and charts and leaves it out of scheduled jobs, its stars
+/// and everything else stored being kept. Archiving an archived repository succeeds too, keeping
+/// its archival date.
+#[utoipa::path(
+    put,
+    path = "/github/repositories/{owner}/{name}/archive",
+    operation_id = "archive_repository",
+    tag = "repositories",
+    summary = "Archive a repository",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 204, description = "Repository archived"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<StatusCode, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    set_repository_archived(&mut conn, repo.id, true)
+		.map_err(|source| HandlerError::SetRepositoryArchived{ source })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,219 @@
+use axum::{
+    extract::{Extension, Json, Path},
+    response::IntoResponse,
+};
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::analytics::gaps::{zero_star_gaps, ZeroStarGap};
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    star::queries::{get_daily_star_count, get_stars_starred_after, GetDailyStarCountError, GetStarsStarredAfterError},
+	    sync_run::{
+	        models::SyncRun,
+	        queries::{get_sync_runs_by_repository, GetSyncRunsByRepositoryError},
+	    },
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+use crate::forges::{ForgeClientError, ForgeClients};
+
+/// Stars a gap has to be expected to get to be reported, see [`crate::analytics::gaps`].
+const MIN_EXPECTED_STARS: f64 = 5.0;
+
+/// Latest sync runs checked for overlaps.
+const MAX_SYNC_RUNS: i64 = 500;
+
+/// Future stars listed, all of them being counted.
+const MAX_FUTURE_STARS: i64 = 20;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetDailyStarCount: {source}")]
+	GetDailyStarCount {
+		#[from]
+		source: GetDailyStarCountError,
+	},
+	#[error("GetSyncRunsByRepository: {source}")]
+	GetSyncRunsByRepository {
+		#[from]
+		source: GetSyncRunsByRepositoryError,
+	},
+	#[error("GetStarsStarredAfter: {source}")]
+	GetStarsStarredAfter {
+		#[from]
+		source: GetStarsStarredAfterError,
+	},
+	#[error("FetchStargazerCount: {source}")]
+	FetchStargazerCount {
+		#[from]
+		source: ForgeClientError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::FetchStargazerCount{ source } => ApiError::github(source.status_code(), source).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Two sync runs of the repository fetching stars at the same time.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct OverlappingSyncRuns {
+	/// Run started first.
+	pub first_run_id: Uuid,
+	pub second_run_id: Uuid,
+	/// Start of the overlap, when the second run started.
+	pub from: NaiveDateTime,
+	/// End of the overlap, now while both still run.
+	pub until: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct FutureStar {
+	pub stargazer: String,
+	/// UTC
+	pub starred_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct FutureStars {
+	pub count: i64,
+	/// The earliest ones, at most 20.
+	pub stars: Vec<FutureStar>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StarCountCheck {
+	/// Stars stored and compacted.
+	pub stored_stars: i64,
+	/// Unset when its forge isn't configured or doesn't know the repository anymore.
+	pub forge_stars: Option<i64>,
+	/// Stored stars minus the forge's, negative when stars are missing.
+	pub drift: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct DataQualityResponse {
+	/// Findings of every check: gaps, overlapping runs, future stars, and 1 for a star count
+	/// drift. 0 when the stored data looks right.
+	pub issues: i64,
+	/// Runs of days without stars the days around them make unlikely, oldest first.
+	pub zero_star_gaps: Vec<ZeroStarGap>,
+	/// Among the latest 500 runs, oldest first.
+	pub overlapping_sync_runs: Vec<OverlappingSyncRuns>,
+	/// Stars claiming to be starred after now.
+	pub future_stars: FutureStars,
+	pub star_count: StarCountCheck,
+}
+
+/// Pairs of `runs` running at the same time, oldest first, those still running lasting until `now`.
+fn overlapping_sync_runs(runs: &[SyncRun], now: NaiveDateTime) -> Vec<OverlappingSyncRuns> {
+	let mut runs: Vec<&SyncRun> = runs.iter().collect();
+	runs.sort_by_key(|run| run.started_at);
+
+	let mut overlaps = Vec::new();
+	for (index, first) in runs.iter().enumerate() {
+		let first_end = first.finished_at.unwrap_or(now);
+		for second in runs[index + 1..].iter().take_while(|second| second.started_at < first_end) {
+			overlaps.push(OverlappingSyncRuns {
+				first_run_id: first.id,
+				second_run_id: second.id,
+				from: second.started_at,
+				until: second.finished_at.unwrap_or(now).min(first_end),
+			});
+		}
+	}
+	overlaps
+}
+
+/// Axum handler: GET /github/repositories/{owner}/{name}/data_quality
+///
+/// Looks for what the stored stars can't be trusted on: suspicious days without stars, syncs
+/// fetching at the same time, stars starred in the future and a star count drifting from the
+/// forge's, checked live.
+#[utoipa::path(
+    get,
+    path = "/github/repositories/{owner}/{name}/data_quality",
+    operation_id = "get_data_quality",
+    tag = "repositories",
+    summary = "Detectable issues in the stored stars of a repository",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+    ),
+    responses(
+        (status = 200, description = "Findings of every check", body = DataQualityResponse),
+        (status = 404, description = "Repository not in database"),
+        (status = 502, description = "Forge request failed"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Extension(forges): Extension<ForgeClients>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<Json<DataQualityResponse>, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+    let now = Utc::now().naive_utc();
+
+    let mut daily_counts = get_daily_star_count(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetDailyStarCount{ source })?;
+    // Future stars are reported apart, the days up to them aren't a gap.
+    daily_counts.retain(|(day, _)| *day <= now.date());
+    let zero_star_gaps = zero_star_gaps(&daily_counts, MIN_EXPECTED_STARS);
+
+    let runs = get_sync_runs_by_repository(&mut conn, repo.id, MAX_SYNC_RUNS)
+		.map_err(|source| HandlerError::GetSyncRunsByRepository{ source })?;
+    let overlapping_sync_runs = overlapping_sync_runs(&runs, now);
+
+    let (stars, count) = get_stars_starred_after(&mut conn, repo.id, now, MAX_FUTURE_STARS)
+		.map_err(|source| HandlerError::GetStarsStarredAfter{ source })?;
+    let future_stars = FutureStars {
+        count,
+        stars: stars.into_iter().map(|(stargazer, starred_at)| FutureStar { stargazer, starred_at }).collect(),
+    };
+    drop(conn);
+
+    let forge_stars = if forges.is_available(repo.forge()) {
+        forges.stargazer_count(repo.forge(), &repo.owner, &repo.name)
+			.await
+			.map_err(|source| HandlerError::FetchStargazerCount{ source })?
+			.map(i64::from)
+    } else {
+        None
+    };
+    let star_count = StarCountCheck {
+        stored_stars: repo.total_stars,
+        forge_stars,
+        drift: forge_stars.map(|forge_stars| repo.total_stars - forge_stars),
+    };
+
+    let issues = zero_star_gaps.len() as i64
+        + overlapping_sync_runs.len() as i64
+        + future_stars.count
+        + i64::from(star_count.drift.is_some_and(|drift| drift != 0));
+    Ok(Json(DataQualityResponse { issues, zero_star_gaps, overlapping_sync_runs, future_stars, star_count }))
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Extension, Json, Query},
+    response::IntoResponse,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::{
+	        models::Repository,
+	        queries::{list_archived_repositories, list_repositories, ListArchivedRepositoriesError, ListRepositoriesError},
+	    },
+	    repository_alias::queries::{get_aliases_by_repositories, GetAliasesByRepositoriesError},
+	    repository_tag::queries::{get_tags_by_repositories, GetTagsByRepositoriesError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("ListRepositories: {source}")]
+	ListRepositories {
+		#[from]
+		source: ListRepositoriesError,
+	},
+	#[error("ListArchivedRepositories: {source}")]
+	ListArchivedRepositories {
+		#[from]
+		source: ListArchivedRepositoriesError,
+	},
+	#[error("GetTagsByRepositories: {source}")]
+	GetTagsByRepositories {
+		#[from]
+		source: GetTagsByRepositoriesError,
+	},
+	#[error("GetAliasesByRepositories: {source}")]
+	GetAliasesByRepositories {
+		#[from]
+		source: GetAliasesByRepositoriesError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		ApiError::internal(self).into_response()
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+	/// Only repositories carrying this tag.
+	tag: Option<String>,
+	/// Lists the archived repositories instead, most recently archived first, whatever their tags.
+	#[serde(default)]
+	archived: bool,
+}
+
+/// JSON representation of a tracked repository.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct RepositoryResponse {
+	pub id: Uuid,
+	pub owner: String,
+	pub name: String,
+	/// `github`, `gitlab` or `gitea`.
+	pub forge: String,
+	pub description: Option<String>,
+	pub primary_language: Option<String>,
+	pub license: Option<String>,
+	pub topics: Vec<String>,
+	pub github_created_at: Option<NaiveDateTime>,
+	pub tracked_since: NaiveDateTime,
+	/// Stars stored for the repository, compacted ones included, as of its last sync or webhook delivery.
+	pub total_stars: i64,
+	pub tags: Vec<String>,
+	/// GitHub's node ID, unset until synced.
+	pub github_node_id: Option<String>,
+	/// `owner/name` the repository had before being renamed or transferred, still accepted by the API.
+	pub former_names: Vec<String>,
+	/// Set on archived repositories.
+	pub archived_at: Option<NaiveDateTime>,
+}
+
+impl RepositoryResponse {
+	fn new(repo: Repository, tags: Vec<String>, former_names: Vec<String>) -> Self {
+		Self {
+			id: repo.id,
+			owner: repo.owner,
+			name: repo.name,
+			forge: repo.forge,
+			description: repo.description,
+			primary_language: repo.primary_language,
+			license: repo.license,
+			topics: repo.topics,
+			github_created_at: repo.github_created_at,
+			tracked_since: repo.created_at,
+			total_stars: repo.total_stars,
+			tags,
+			github_node_id: repo.github_node_id,
+			former_names,
+			archived_at: repo.archived_at,
+		}
+	}
+}
+
+/// Axum handler: GET /github/repositories?tag=T
+///
+/// Archived repositories are only listed with `archived=true`.
+#[utoipa::path(
+    get,
+    path = "/github/repositories",
+    operation_id = "list_repositories",
+    tag = "repositories",
+    summary = "Tracked repositories",
+    params(
+        ListParams,
+    ),
+    responses(
+        (status = 200, description = "Every tracked repository but the archived ones, or the ones with the tag, or the archived ones", body = Vec<RepositoryResponse>),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<RepositoryResponse>>, HandlerError> {
+    // Tags are stored lowercase, the filter matches whatever the case.
+    let tag = params.tag.map(|tag| tag.to_ascii_lowercase());
+
+    let repositories = if params.archived {
+        list_archived_repositories(&mut conn, tenant_id)
+			.map_err(|source| HandlerError::ListArchivedRepositories{ source })?
+    } else {
+        list_repositories(&mut conn, tenant_id, tag.as_deref())
+			.map_err(|source| HandlerError::ListRepositories{ source })?
+    };
+
+    let ids: Vec<Uuid> = repositories.iter().map(|repo| repo.id).collect();
+    let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for repo_tag in get_tags_by_repositories(&mut conn, &ids)
+		.map_err(|source| HandlerError::GetTagsByRepositories{ source })?
+    {
+        tags.entry(repo_tag.repository_id).or_default().push(repo_tag.tag);
+    }
+
+    let mut former_names: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for alias in get_aliases_by_repositories(&mut conn, &ids)
+		.map_err(|source| HandlerError::GetAliasesByRepositories{ source })?
+    {
+        former_names.entry(alias.repository_id).or_default().push(format!("{}/{}", alias.owner, alias.name));
+    }
+
+    Ok(Json(
+        repositories
+            .into_iter()
+            .map(|repo| {
+                let repo_tags = tags.remove(&repo.id).unwrap_or_default();
+                let repo_former_names = former_names.remove(&repo.id).unwrap_or_default();
+                RepositoryResponse::new(repo, repo_tags, repo_former_names)
+            })
+            .collect(),
+    ))
+}
@@ -0,0 +1,5 @@
+pub mod archive;
+pub mod data_quality;
+pub mod list;
+pub mod sync_history;
+pub mod tags;
\ No newline at end of file
@@ -0,0 +1,147 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    sync_run::{
+	        models::SyncRun,
+	        queries::{get_sync_runs_by_repository, GetSyncRunsByRepositoryError},
+	    },
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Upper bound of `limit`, keeps a single answer reasonably small.
+const MAX_LIMIT: i64 = 500;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidLimit: {limit}")]
+	InvalidLimit {
+		limit: i64,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetSyncRunsByRepository: {source}")]
+	GetSyncRunsByRepository {
+		#[from]
+		source: GetSyncRunsByRepositoryError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidLimit{ limit } => ApiError::invalid_request(format!("limit must be between 1 and {MAX_LIMIT}, got {limit}")).with_details(json!({ "limit": limit, "max": MAX_LIMIT })).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryParams {
+	#[serde(default = "default_limit")]
+	limit: i64,
+}
+
+fn default_limit() -> i64 {
+	50
+}
+
+/// JSON representation of a sync run.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct SyncRunResponse {
+	pub id: Uuid,
+	/// Unset once the job was purged, its runs are kept.
+	pub job_id: Option<Uuid>,
+	/// `api`, `owner_sync`, `batch`, `resume`, `repair` or `cli`.
+	pub trigger: String,
+	/// `running`, `completed`, `failed` or `interrupted`.
+	pub status: String,
+	pub started_at: NaiveDateTime,
+	/// Unset while the run goes on.
+	pub finished_at: Option<NaiveDateTime>,
+	/// Pages fetched by this run, a resumed job starts from its earlier pages.
+	pub pages_fetched: i32,
+	/// Growth of the stored star count over the run.
+	pub stars_added: i32,
+	pub error: Option<String>,
+}
+
+impl From<SyncRun> for SyncRunResponse {
+	fn from(run: SyncRun) -> Self {
+		Self {
+			id: run.id,
+			job_id: run.job_id,
+			trigger: run.trigger,
+			status: run.status,
+			started_at: run.started_at,
+			finished_at: run.finished_at,
+			pages_fetched: run.pages_fetched,
+			stars_added: run.stars_added,
+			error: run.error,
+		}
+	}
+}
+
+/// Axum handler: GET /github/repositories/{owner}/{name}/sync_history?limit=N
+///
+/// Every run of the repository's syncs, newest first, to tell when its data was last refreshed.
+#[utoipa::path(
+    get,
+    path = "/github/repositories/{owner}/{name}/sync_history",
+    operation_id = "get_sync_history",
+    tag = "repositories",
+    summary = "Sync runs of a repository",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        HistoryParams,
+    ),
+    responses(
+        (status = 200, description = "Sync runs, newest first", body = Vec<SyncRunResponse>),
+        (status = 400, description = "Invalid limit"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<Vec<SyncRunResponse>>, HandlerError> {
+    if !(1..=MAX_LIMIT).contains(&params.limit) {
+        return Err(HandlerError::InvalidLimit{ limit: params.limit });
+    }
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let runs = get_sync_runs_by_repository(&mut conn, repo.id, params.limit)
+		.map_err(|source| HandlerError::GetSyncRunsByRepository{ source })?;
+
+    Ok(Json(runs.into_iter().map(SyncRunResponse::from).collect()))
+}
@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    repository_tag::{
+	        models::{parse_tag, NewRepositoryTag, MAX_TAG_LENGTH},
+	        queries::{insert_repository_tag, InsertRepositoryTagError},
+	    },
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("InvalidTag: {tag}")]
+	InvalidTag {
+		tag: String,
+	},
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("InsertRepositoryTag: {source}")]
+	InsertRepositoryTag {
+		#[from]
+		source: InsertRepositoryTagError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidTag{ tag } => ApiError::invalid_request(format!("Tag must be 1 to {MAX_TAG_LENGTH} letters, digits, '-', '_' or '.', got {tag}")).with_details(json!({ "tag": tag })).into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: PUT /github/repositories/{owner}/{name}/tags/{tag}
+///
+/// Tags are lowercased, adding a tag the repository already has succeeds too.
+#[utoipa::path(
+    put,
+    path = "/github/repositories/{owner}/{name}/tags/{tag}",
+    operation_id = "add_repository_tag",
+    tag = "repositories",
+    summary = "Tag a repository",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        ("tag" = String, Path, description = "Tag, e.g. `competitors`"),
+    ),
+    responses(
+        (status = 204, description = "Repository tagged"),
+        (status = 400, description = "Invalid tag"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name, tag)): Path<(String, String, String)>,
+) -> Result<StatusCode, HandlerError> {
+    let tag = parse_tag(&tag).ok_or(HandlerError::InvalidTag{ tag })?;
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    insert_repository_tag(&mut conn, &NewRepositoryTag { repository_id: repo.id, tag: &tag })
+		.map_err(|source| HandlerError::InsertRepositoryTag{ source })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
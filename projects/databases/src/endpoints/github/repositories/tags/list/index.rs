@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Extension, Json},
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    repository_tag::queries::{count_repositories_by_tag, CountRepositoriesByTagError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("CountRepositoriesByTag: {source}")]
+	CountRepositoriesByTag {
+		#[from]
+		source: CountRepositoriesByTagError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		ApiError::internal(self).into_response()
+	}
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct TagResponse {
+	pub tag: String,
+	/// Repositories carrying the tag.
+	pub repositories: i64,
+}
+
+/// Axum handler: GET /github/repositories/tags
+#[utoipa::path(
+    get,
+    path = "/github/repositories/tags",
+    operation_id = "list_repository_tags",
+    tag = "repositories",
+    summary = "Tags in use",
+    responses(
+        (status = 200, description = "Every tag with its repository count, sorted by tag", body = Vec<TagResponse>),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+) -> Result<Json<Vec<TagResponse>>, HandlerError> {
+    let tags = count_repositories_by_tag(&mut conn, tenant_id)
+		.map_err(|source| HandlerError::CountRepositoriesByTag{ source })?;
+
+    Ok(Json(
+        tags
+            .into_iter()
+            .map(|(tag, repositories)| TagResponse { tag, repositories })
+            .collect(),
+    ))
+}
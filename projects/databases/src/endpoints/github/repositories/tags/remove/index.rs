@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    repository_tag::queries::{delete_repository_tag, DeleteRepositoryTagError},
+	};
+use crate::endpoints::db_conn::DbConn;
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("DeleteRepositoryTag: {source}")]
+	DeleteRepositoryTag {
+		#[from]
+		source: DeleteRepositoryTagError,
+	},
+	#[error("TagNotFound: {tag}")]
+	TagNotFound {
+		tag: String,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::TagNotFound{ tag } => ApiError::new(StatusCode::NOT_FOUND, ErrorCode::TagNotFound, format!("Repository has no tag {tag}")).with_details(json!({ "tag": tag })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Axum handler: DELETE /github/repositories/{owner}/{name}/tags/{tag}
+#[utoipa::path(
+    delete,
+    path = "/github/repositories/{owner}/{name}/tags/{tag}",
+    operation_id = "remove_repository_tag",
+    tag = "repositories",
+    summary = "Remove a tag from a repository",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        ("tag" = String, Path, description = "Tag to remove"),
+    ),
+    responses(
+        (status = 204, description = "Tag removed"),
+        (status = 404, description = "Repository not in database or without the tag"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    DbConn(mut conn): DbConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name, tag)): Path<(String, String, String)>,
+) -> Result<StatusCode, HandlerError> {
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or(HandlerError::RepositoryNotInDatabase{ owner, name })?;
+
+    let deleted = delete_repository_tag(&mut conn, repo.id, &tag.to_ascii_lowercase())
+		.map_err(|source| HandlerError::DeleteRepositoryTag{ source })?;
+
+    if !deleted {
+        return Err(HandlerError::TagNotFound{ tag });
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
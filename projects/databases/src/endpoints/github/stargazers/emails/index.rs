@@ -0,0 +1,177 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use tracing::info;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    stargazer_email::queries::{get_stargazer_emails_by_repository, GetStargazerEmailsByRepositoryError, StargazerEmailFilter},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::{ApiKeyAuthenticated, TenantId};
+
+/// Upper bound of `limit`.
+const MAX_LIMIT: i64 = 1000;
+
+/// Pages each tenant may read per hour, past it the route answers `429`.
+pub const EXPORT_REQUESTS_PER_HOUR: u32 = 60;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("ApiKeyRequired")]
+	ApiKeyRequired,
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetStargazerEmailsByRepository: {source}")]
+	GetStargazerEmailsByRepository {
+		#[from]
+		source: GetStargazerEmailsByRepositoryError,
+	},
+	#[error("InvalidLimit: {limit}")]
+	InvalidLimit {
+		limit: i64,
+	},
+	#[error("InvalidDomain: {domain}")]
+	InvalidDomain {
+		domain: String,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::ApiKeyRequired => ApiError::new(StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, "Stargazer emails are only exported to callers with an API key").into_response(),
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::InvalidLimit{ limit } => ApiError::invalid_request(format!("limit must be between 1 and {MAX_LIMIT}, got {limit}")).with_details(json!({ "limit": limit, "max": MAX_LIMIT })).into_response(),
+			HandlerError::InvalidDomain{ domain } => ApiError::invalid_request(format!("domain must be a host name, got {domain:?}")).with_details(json!({ "domain": domain })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EmailsParams {
+	/// Only stargazers who starred the repository on or after this day.
+	since: Option<NaiveDate>,
+	/// Only emails of this domain, e.g. `mozilla.com`, ignoring case.
+	domain: Option<String>,
+	/// `next_after` of the previous page.
+	after: Option<String>,
+	/// Stargazers per page, 100 by default.
+	#[serde(default = "default_limit")]
+	limit: i64,
+}
+
+fn default_limit() -> i64 {
+	100
+}
+
+/// Stargazer showing a public email.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StargazerEmail {
+    pub login: String,
+    pub email: String,
+    pub starred_at: NaiveDateTime,
+}
+
+/// Page of stargazers with a public email, by login.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct StargazerEmails {
+    pub stargazers: Vec<StargazerEmail>,
+    /// Pass it as `after` to get the next page, unset on the last one.
+    pub next_after: Option<String>,
+}
+
+/// Axum handler: GET /github/stargazers/emails/{owner}/{name}?since=2025-01-01&domain=example.com&after=login&limit=N
+///
+/// Only covers stargazers synced while email collection was enabled, and only answers callers
+/// with an API key. Answers are never cached, and each one is logged.
+#[utoipa::path(
+    get,
+    path = "/github/stargazers/emails/{owner}/{name}",
+    operation_id = "stargazer_emails",
+    tag = "stargazers",
+    summary = "Stargazers of a repository showing a public email, for community outreach",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        EmailsParams,
+    ),
+    responses(
+        (status = 200, description = "Page of stargazers with their public email", body = StargazerEmails),
+        (status = 400, description = "Invalid limit or domain"),
+        (status = 401, description = "No API key"),
+        (status = 404, description = "Repository not in database"),
+        (status = 429, description = "Tenant over its hourly exports"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    authenticated: Option<Extension<ApiKeyAuthenticated>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<EmailsParams>,
+) -> Result<impl IntoResponse, HandlerError> {
+    if authenticated.is_none() {
+        return Err(HandlerError::ApiKeyRequired);
+    }
+    if !(1..=MAX_LIMIT).contains(&params.limit) {
+        return Err(HandlerError::InvalidLimit{ limit: params.limit });
+    }
+    if let Some(domain) = params.domain.as_deref().filter(|domain| !is_host_name(domain)) {
+        return Err(HandlerError::InvalidDomain{ domain: domain.to_string() });
+    }
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+
+    let filter = StargazerEmailFilter {
+        starred_since: params.since.map(|day| day.and_time(chrono::NaiveTime::MIN)),
+        domain: params.domain.as_deref(),
+        after: params.after.as_deref(),
+    };
+    // One more than asked tells whether another page follows.
+    let mut rows = get_stargazer_emails_by_repository(&mut conn, repo.id, &filter, params.limit + 1)
+		.map_err(|source| HandlerError::GetStargazerEmailsByRepository{ source })?;
+
+    let more = rows.len() as i64 > params.limit;
+    rows.truncate(params.limit as usize);
+    let next_after = rows.last().filter(|_| more).map(|row| row.login.clone());
+    info!(%tenant_id, repository_id = %repo.id, exported = rows.len(), "stargazer emails exported");
+
+    let stargazers = rows
+        .into_iter()
+        .map(|row| StargazerEmail { login: row.login, email: row.email, starred_at: row.starred_at })
+        .collect();
+
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(StargazerEmails { stargazers, next_after })))
+}
+
+fn is_host_name(domain: &str) -> bool {
+    domain.len() <= 253
+        && domain.split('.').all(|label| {
+            !label.is_empty() && !label.starts_with('-') && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
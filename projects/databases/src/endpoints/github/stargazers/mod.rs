@@ -0,0 +1,3 @@
+pub mod emails;
+pub mod profiles;
+pub mod top;
\ No newline at end of file
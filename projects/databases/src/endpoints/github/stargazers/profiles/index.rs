@@ -0,0 +1,113 @@
+use axum::{
+    extract::{Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use utoipa::IntoParams;
+
+use crate::endpoints::api_error::ApiError;
+use crate::analytics::profiles::{profile_breakdown, ProfileBreakdown};
+use crate::db::{
+	    repository::queries::{get_repository_by_name, GetRepositoryByNameError},
+	    stargazer_profile::queries::{get_stargazer_profiles_by_repository, GetStargazerProfilesByRepositoryError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Upper bound of `limit`, past it the counts are mostly ones.
+const MAX_LIMIT: usize = 100;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetRepositoryByName: {source}")]
+	GetRepositoryByName {
+		#[from]
+		source: GetRepositoryByNameError,
+	},
+	#[error("RepositoryNotInDatabase: {owner}/{name}")]
+	RepositoryNotInDatabase {
+		owner: String,
+		name: String,
+	},
+	#[error("GetStargazerProfilesByRepository: {source}")]
+	GetStargazerProfilesByRepository {
+		#[from]
+		source: GetStargazerProfilesByRepositoryError,
+	},
+	#[error("InvalidLimit: {limit}")]
+	InvalidLimit {
+		limit: usize,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::RepositoryNotInDatabase{ owner, name } => ApiError::repository_not_found(&owner, &name).into_response(),
+			HandlerError::InvalidLimit{ limit } => ApiError::invalid_request(format!("limit must be between 1 and {MAX_LIMIT}, got {limit}")).with_details(json!({ "limit": limit, "max": MAX_LIMIT })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProfilesParams {
+	/// Companies and countries listed, each.
+	#[serde(default = "default_limit")]
+	limit: usize,
+}
+
+fn default_limit() -> usize {
+	10
+}
+
+/// Axum handler: GET /github/stargazers/profiles/{owner}/{name}?limit=N
+///
+/// Only covers stargazers synced while profile collection was enabled.
+#[utoipa::path(
+    get,
+    path = "/github/stargazers/profiles/{owner}/{name}",
+    operation_id = "stargazer_profiles",
+    tag = "stargazers",
+    summary = "Top companies and countries among a repository's stargazers",
+    params(
+        ("owner" = String, Path, description = "Repository owner"),
+        ("name" = String, Path, description = "Repository name"),
+        ProfilesParams,
+    ),
+    responses(
+        (status = 200, description = "Stargazer companies and countries", body = ProfileBreakdown),
+        (status = 400, description = "Invalid limit"),
+        (status = 404, description = "Repository not in database"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<ProfilesParams>,
+) -> Result<Json<ProfileBreakdown>, HandlerError> {
+    if !(1..=MAX_LIMIT).contains(&params.limit) {
+        return Err(HandlerError::InvalidLimit{ limit: params.limit });
+    }
+
+    let repo = get_repository_by_name(&mut conn, tenant_id, &owner, &name)
+		.await
+		.map_err(|source| HandlerError::GetRepositoryByName{ source })?
+		.ok_or_else(|| HandlerError::RepositoryNotInDatabase{ owner: owner.clone(), name: name.clone() })?;
+
+    let profiles = get_stargazer_profiles_by_repository(&mut conn, repo.id)
+		.map_err(|source| HandlerError::GetStargazerProfilesByRepository{ source })?;
+
+    let profiles: Vec<_> = profiles
+        .into_iter()
+        .map(|profile| (profile.company, profile.location))
+        .collect();
+
+    Ok(Json(profile_breakdown(&profiles, params.limit)))
+}
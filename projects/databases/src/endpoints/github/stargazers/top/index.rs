@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Extension, Json, Query},
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::endpoints::api_error::ApiError;
+use crate::db::{
+	    star::queries::{get_top_stargazers, GetTopStargazersError},
+	};
+use crate::endpoints::db_conn::ReadConn;
+use crate::endpoints::tenant::TenantId;
+
+/// Upper bound of `limit`, keeps a single answer reasonably small.
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("GetTopStargazers: {source}")]
+	GetTopStargazers {
+		#[from]
+		source: GetTopStargazersError,
+	},
+	#[error("InvalidLimit: {limit}")]
+	InvalidLimit {
+		limit: i64,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::InvalidLimit{ limit } => ApiError::invalid_request(format!("limit must be between 1 and {MAX_LIMIT}, got {limit}")).with_details(json!({ "limit": limit, "max": MAX_LIMIT })).into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Query parameters accepted by the endpoint.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TopParams {
+	#[serde(default = "default_limit")]
+	limit: i64,
+	/// Only count repositories carrying this tag.
+	tag: Option<String>,
+}
+
+fn default_limit() -> i64 {
+	20
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct TopStargazerResponse {
+	pub stargazer: String,
+	/// Tracked repositories starred by this user.
+	pub repositories: i64,
+}
+
+/// Axum handler: GET /github/stargazers/top?limit=N&tag=T
+#[utoipa::path(
+    get,
+    path = "/github/stargazers/top",
+    operation_id = "list_top_stargazers",
+    tag = "stargazers",
+    summary = "Users starring the most tracked repositories",
+    params(
+        TopParams,
+    ),
+    responses(
+        (status = 200, description = "Top stargazers", body = Vec<TopStargazerResponse>),
+        (status = 400, description = "Invalid limit"),
+        (status = 500, description = "Database failure"),
+    ),
+)]
+pub async fn handler(
+    ReadConn(mut conn): ReadConn,
+    Extension(TenantId(tenant_id)): Extension<TenantId>,
+    Query(params): Query<TopParams>,
+) -> Result<Json<Vec<TopStargazerResponse>>, HandlerError> {
+    if !(1..=MAX_LIMIT).contains(&params.limit) {
+        return Err(HandlerError::InvalidLimit{ limit: params.limit });
+    }
+
+    let tag = params.tag.map(|tag| tag.to_ascii_lowercase());
+
+    let stargazers = get_top_stargazers(&mut conn, tenant_id, params.limit, tag.as_deref())
+		.map_err(|source| HandlerError::GetTopStargazers{ source })?;
+
+    Ok(Json(
+        stargazers
+            .into_iter()
+            .map(|(stargazer, repositories)| TopStargazerResponse { stargazer, repositories })
+            .collect(),
+    ))
+}
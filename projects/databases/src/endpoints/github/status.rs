@@ -0,0 +1,13 @@
+use axum::http::StatusCode;
+use interfaces_github_stargazers::graphql::ParseGraphQLResponseError;
+
+/// HTTP status reported to the caller for a failed GitHub answer.
+pub fn github_error_status_code(source: &ParseGraphQLResponseError) -> StatusCode {
+    match source {
+        ParseGraphQLResponseError::NotFound{ .. } => StatusCode::NOT_FOUND,
+        ParseGraphQLResponseError::Forbidden{ .. } => StatusCode::FORBIDDEN,
+        ParseGraphQLResponseError::RateLimited{ .. } => StatusCode::TOO_MANY_REQUESTS,
+        // A rejected token is the service's, not the caller's, as is any other failure of GitHub.
+        _ => StatusCode::BAD_GATEWAY,
+    }
+}
@@ -0,0 +1,2 @@
+pub mod signature;
+pub mod star;
\ No newline at end of file
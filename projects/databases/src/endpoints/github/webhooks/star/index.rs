@@ -0,0 +1,240 @@
+use axum::{
+    body::Bytes,
+    extract::{Extension, Json},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use interfaces_github_stargazers::forge::Forge;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::bots::login_looks_automated;
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::db::{
+	    daily_star_count::queries::{refresh_daily_star_counts, RefreshDailyStarCountsError},
+	    repository::queries::{get_repositories_by_name, GetRepositoriesByNameError},
+	    star::{
+	        models::NewStar,
+	        queries::{delete_star, insert_star, DeleteStarError, InsertStarError},
+	    },
+	    PgPool,
+	};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::endpoints::github::webhooks::signature::{WebhookSecret, SIGNATURE_HEADER};
+use crate::privacy::StargazerIds;
+use crate::runtime_config::RuntimeConfig;
+
+/// Header naming the kind of event delivered.
+const EVENT_HEADER: &str = "x-github-event";
+
+#[derive(Debug, Error)]
+pub enum HandlerError {
+	#[error("MissingWebhookSecret")]
+	MissingWebhookSecret,
+	#[error("InvalidSignature")]
+	InvalidSignature,
+	#[error("InvalidPayload: {source}")]
+	InvalidPayload {
+		source: serde_json::Error,
+	},
+	#[error("Checkout: {source}")]
+	Checkout {
+		#[from]
+		source: DbConnRejection,
+	},
+	#[error("GetRepositoriesByName: {source}")]
+	GetRepositoriesByName {
+		#[from]
+		source: GetRepositoriesByNameError,
+	},
+	#[error("InsertStar: {source}")]
+	InsertStar {
+		#[from]
+		source: InsertStarError,
+	},
+	#[error("DeleteStar: {source}")]
+	DeleteStar {
+		#[from]
+		source: DeleteStarError,
+	},
+	#[error("RefreshDailyStarCounts: {source}")]
+	RefreshDailyStarCounts {
+		#[from]
+		source: RefreshDailyStarCountsError,
+	},
+}
+
+impl IntoResponse for HandlerError {
+	fn into_response(self) -> axum::response::Response {
+		match self {
+			HandlerError::MissingWebhookSecret => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::NotConfigured, "No webhook secret configured, set GITHUB_WEBHOOK_SECRET").into_response(),
+			HandlerError::InvalidSignature => ApiError::new(StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, format!("Missing or invalid {SIGNATURE_HEADER}")).into_response(),
+			HandlerError::InvalidPayload{ source } => ApiError::invalid_request(format!("Invalid star event: {source}")).into_response(),
+			HandlerError::Checkout{ source } => source.into_response(),
+			other => ApiError::internal(other).into_response(),
+		}
+	}
+}
+
+/// Payload of a `star` event, only the fields used here.
+#[derive(Deserialize)]
+struct StarEvent {
+	action: StarAction,
+	/// Unset for `deleted`.
+	starred_at: Option<DateTime<Utc>>,
+	repository: EventRepository,
+	sender: EventUser,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StarAction {
+	Created,
+	Deleted,
+}
+
+#[derive(Deserialize)]
+struct EventRepository {
+	node_id: Option<String>,
+	name: String,
+	owner: EventUser,
+}
+
+#[derive(Deserialize)]
+struct EventUser {
+	login: String,
+}
+
+/// What the delivery changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookOutcome {
+	/// The star was stored.
+	Stored,
+	/// The star was removed, or wasn't stored.
+	Deleted,
+	/// No tenant tracks the repository, nothing was stored.
+	Untracked,
+	/// Every tenant tracking the repository archived it, nothing was stored.
+	Archived,
+	/// Not a `star` event, e.g. GitHub's `ping` when the webhook is created.
+	Ignored,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct WebhookReceipt {
+	pub outcome: WebhookOutcome,
+}
+
+/// Axum handler: POST /github/webhooks/star
+///
+/// Applies GitHub's `star` events as they happen, keeping tracked
+/// repositories current between syncs. Milestones and alerts still wait for
+/// the next sync. Events aren't tied to a tenant, every tenant tracking the
+/// repository gets the star, unless it archived the repository.
+#[utoipa::path(
+    post,
+    path = "/github/webhooks/star",
+    operation_id = "receive_star_webhook",
+    tag = "webhooks",
+    summary = "Receive a GitHub star event",
+    params(
+        ("X-Hub-Signature-256" = String, Header, description = "`sha256=` HMAC of the body with the webhook secret"),
+        ("X-GitHub-Event" = String, Header, description = "Event kind, only `star` is applied"),
+    ),
+    request_body(
+        description = "GitHub `star` event payload",
+        content = String,
+        content_type = "application/json",
+    ),
+    responses(
+        (status = 200, description = "Event handled", body = WebhookReceipt),
+        (status = 400, description = "Invalid star event"),
+        (status = 401, description = "Missing or invalid signature"),
+        (status = 500, description = "No webhook secret configured or database failure"),
+    ),
+)]
+pub async fn handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(secret): Extension<Option<WebhookSecret>>,
+    Extension(stargazer_ids): Extension<StargazerIds>,
+    Extension(config): Extension<RuntimeConfig>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<WebhookReceipt>, HandlerError> {
+    let secret = secret.ok_or(HandlerError::MissingWebhookSecret)?;
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !secret.verify(&body, signature) {
+        return Err(HandlerError::InvalidSignature);
+    }
+
+    let event_kind = headers
+        .get(EVENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if event_kind != "star" {
+        return Ok(Json(WebhookReceipt { outcome: WebhookOutcome::Ignored }));
+    }
+
+    let event = serde_json::from_slice::<StarEvent>(&body)
+        .map_err(|source| HandlerError::InvalidPayload{ source })?;
+
+    // Checked out once the event is vetted, forged deliveries don't hold connections.
+    let DbConn(mut conn) = DbConn::checkout(pool)
+		.await
+		.map_err(|source| HandlerError::Checkout{ source })?;
+
+    let repositories = get_repositories_by_name(&mut conn, Forge::GitHub.as_str(), event.repository.node_id.as_deref(), &event.repository.owner.login, &event.repository.name)
+		.map_err(|source| HandlerError::GetRepositoriesByName{ source })?;
+    if repositories.is_empty() {
+        return Ok(Json(WebhookReceipt { outcome: WebhookOutcome::Untracked }));
+    }
+    // Archived repositories are left alone, as scheduled jobs leave them.
+    let repositories: Vec<_> = repositories.into_iter().filter(|repo| !repo.is_archived()).collect();
+    if repositories.is_empty() {
+        return Ok(Json(WebhookReceipt { outcome: WebhookOutcome::Archived }));
+    }
+
+    let stargazer = stargazer_ids.stargazer_id(&event.sender.login);
+    // The account's age is left to the next sync, the event doesn't tell it.
+    let suspected_bot = config.service().flag_suspected_bots && login_looks_automated(&event.sender.login);
+    let now = Utc::now().naive_utc();
+    for repo in &repositories {
+        match event.action {
+            StarAction::Created => {
+                let new_star = NewStar {
+                    repository_id: repo.id,
+                    stargazer: &stargazer,
+                    starred_at: event.starred_at.map_or(now, |starred_at| starred_at.naive_utc()),
+                    fetched_at: now,
+                    first_fetched_at: now,
+                    suspected_bot,
+                };
+                let star = insert_star(&mut conn, &new_star)
+					.map_err(|source| HandlerError::InsertStar{ source })?;
+                refresh_daily_star_counts(&mut conn, repo.id, Some(star.starred_at.date()))
+					.map_err(|source| HandlerError::RefreshDailyStarCounts{ source })?;
+            }
+            StarAction::Deleted => {
+                // Compacted stars have no row left to delete, their day keeps counting them.
+                if let Some(starred_at) = delete_star(&mut conn, repo.id, &stargazer)
+					.map_err(|source| HandlerError::DeleteStar{ source })?
+                {
+                    refresh_daily_star_counts(&mut conn, repo.id, Some(starred_at.date()))
+						.map_err(|source| HandlerError::RefreshDailyStarCounts{ source })?;
+                }
+            }
+        }
+    }
+
+    let outcome = match event.action {
+        StarAction::Created => WebhookOutcome::Stored,
+        StarAction::Deleted => WebhookOutcome::Deleted,
+    };
+    Ok(Json(WebhookReceipt { outcome }))
+}
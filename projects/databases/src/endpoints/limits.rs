@@ -0,0 +1,162 @@
+//! Request limits
+//!
+//! Guards keeping one slow or misbehaving client from tying up the service:
+//! a deadline per request, a cap on request bodies and on the requests
+//! handled at once, and a rate per tenant on the most sensitive routes.
+//! Requests over a limit get a `408`, `413`, `429` or `503` [`ApiError`]
+//! whose code says which one.
+
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::endpoints::tenant::TenantId;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RequestLimits {
+    /// Deadline of routes answering right away, syncs waited on are exempt.
+    pub timeout: Duration,
+    /// Largest body of routes without their own limit, e.g. JSON payloads.
+    pub max_body_bytes: usize,
+    /// Requests handled at once, more are turned away with `503`.
+    pub max_concurrent_requests: usize,
+    /// Charts rendered at once, more wait for their turn, see [`crate::render_pool`].
+    pub max_concurrent_renders: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_body_bytes: 2 * 1024 * 1024,
+            max_concurrent_requests: 256,
+            max_concurrent_renders: std::thread::available_parallelism().map_or(4, NonZeroUsize::get),
+        }
+    }
+}
+
+/// Axum middleware answering `408 Request Timeout` when the handler runs past the deadline, added with `from_fn_with_state`.
+pub async fn timeout_middleware(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => ApiError::new(StatusCode::REQUEST_TIMEOUT, ErrorCode::Timeout, format!("Request not handled within {}s", timeout.as_secs_f64())).into_response(),
+    }
+}
+
+/// Axum middleware answering `413 Payload Too Large` when `Content-Length` is over `max_body_bytes`,
+/// added with `from_fn_with_state` next to a `DefaultBodyLimit` catching bodies without a length.
+pub async fn body_limit_middleware(
+    State(max_body_bytes): State<usize>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if length.is_some_and(|length| length > max_body_bytes as u64) {
+        return ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, ErrorCode::PayloadTooLarge, format!("Request body over {max_body_bytes} bytes"))
+            .with_details(serde_json::json!({ "max_body_bytes": max_body_bytes }))
+            .into_response();
+    }
+    next.run(request).await
+}
+
+/// Permits of [`concurrency_limit_middleware`], shared by clones.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    permits: Arc<Semaphore>,
+    max: usize,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(max: usize) -> Self {
+        Self { permits: Arc::new(Semaphore::new(max)), max }
+    }
+}
+
+/// Axum middleware answering `503 Service Unavailable` while the limit of requests in flight is reached,
+/// added with `from_fn_with_state`. Requests aren't queued, clients retry after `Retry-After`.
+pub async fn concurrency_limit_middleware(
+    State(limit): State<ConcurrencyLimit>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Ok(_permit) = limit.permits.clone().try_acquire_owned() else {
+        return (
+            [(header::RETRY_AFTER, "1")],
+            ApiError::new(StatusCode::SERVICE_UNAVAILABLE, ErrorCode::Overloaded, format!("More than {} requests in flight, retry later", limit.max)),
+        )
+            .into_response();
+    };
+    next.run(request).await
+}
+
+/// Requests let through per tenant in fixed windows, shared by clones.
+#[derive(Debug, Clone)]
+pub struct TenantRateLimit {
+    max: u32,
+    window: Duration,
+    /// Start of each tenant's current window, and the requests it let through.
+    windows: Arc<Mutex<HashMap<Uuid, (Instant, u32)>>>,
+}
+
+impl TenantRateLimit {
+    pub fn new(max: u32, window: Duration) -> Self {
+        Self { max, window, windows: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// Counts a request of the tenant at `now`, or tells how long until its next window when it's used up.
+    pub fn acquire(&self, tenant_id: Uuid, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (started, used) = windows.entry(tenant_id).or_insert((now, 0));
+        if now.duration_since(*started) >= self.window {
+            (*started, *used) = (now, 0);
+        }
+        if *used >= self.max {
+            return Err(self.window.saturating_sub(now.duration_since(*started)));
+        }
+        *used += 1;
+        Ok(())
+    }
+}
+
+/// Axum middleware answering `429 Too Many Requests` once the tenant used up its window,
+/// added with `from_fn_with_state` inside [`crate::endpoints::tenant::tenant_middleware`].
+pub async fn tenant_rate_limit_middleware(
+    State(limit): State<TenantRateLimit>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(TenantId(tenant_id)) = request.extensions().get::<TenantId>().copied() else {
+        return next.run(request).await;
+    };
+    if let Err(wait) = limit.acquire(tenant_id, Instant::now()) {
+        let retry_after = wait.as_secs().max(1);
+        return (
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            ApiError::new(StatusCode::TOO_MANY_REQUESTS, ErrorCode::RateLimited, format!("More than {} requests in {}s, retry later", limit.max, limit.window.as_secs()))
+                .with_details(serde_json::json!({ "retry_after_seconds": retry_after })),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
@@ -0,0 +1,40 @@
+use axum::extract::{Extension, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::db::{pool::{PoolMetrics, PoolStats, ReadPool}, PgPool};
+use crate::sync_queue::{SyncQueue, SyncQueueStats};
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct MetricsResponse {
+	pub db_pool: PoolStats,
+	/// Pool of the read replica, when `DATABASE_URL_RO` is set.
+	pub replica_pool: Option<PoolStats>,
+	pub sync_queue: SyncQueueStats,
+}
+
+/// Axum handler: GET /metrics
+///
+/// Database pool usage and sync queue depth, to tell where latency comes from.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    operation_id = "get_metrics",
+    tag = "metrics",
+    summary = "Database pool and sync queue metrics",
+    responses(
+        (status = 200, description = "Current metrics", body = MetricsResponse),
+    ),
+)]
+pub async fn handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(pool_metrics): Extension<PoolMetrics>,
+    Extension(read_pool): Extension<ReadPool>,
+    Extension(queue): Extension<SyncQueue>,
+) -> Json<MetricsResponse> {
+    Json(MetricsResponse {
+        db_pool: pool_metrics.stats(&pool),
+        replica_pool: read_pool.replica_stats(),
+        sync_queue: queue.stats(),
+    })
+}
@@ -1 +1,13 @@
-pub mod github;
\ No newline at end of file
+pub mod admin;
+pub mod api_error;
+pub mod compression;
+pub mod cors;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+pub mod db_conn;
+pub mod envelope;
+pub mod github;
+pub mod limits;
+pub mod metrics;
+pub mod openapi;
+pub mod tenant;
\ No newline at end of file
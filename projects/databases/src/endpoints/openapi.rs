@@ -0,0 +1,109 @@
+//! OpenAPI description of the REST API
+//!
+//! Served as JSON at `/api-docs/openapi.json` and browsable at `/swagger-ui`.
+//! New handlers need their `#[utoipa::path]` listed in `paths` below, or in
+//! `ChartingApi` when they only exist with the `charting` feature.
+
+use utoipa::OpenApi;
+
+use crate::digest::WeeklyDigest;
+use crate::endpoints::admin;
+use crate::endpoints::github::{alerts, org_stars, repo_stars, reports, repositories, stargazers, webhooks};
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::endpoints::envelope::ErrorEnvelope;
+use crate::endpoints::metrics;
+use crate::validation::FieldError;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "GitHub Star Tracker",
+        description = "Tracks and analyzes GitHub repository stars over time. Errors are answered as an `ApiError`, branch on its `code`; send `X-Response-Envelope: true` to get JSON answers wrapped in `{\"data\", \"request_id\"}` or `{\"error\"}`.",
+    ),
+    components(schemas(ApiError, ErrorCode, ErrorEnvelope, FieldError, WeeklyDigest)),
+    paths(
+        repo_stars::update::index::handler,
+        repo_stars::update_batch::index::handler,
+        repo_stars::read_per_day::index::handler,
+        repo_stars::read_per_day::index::get_handler,
+        repo_stars::compare::index::handler,
+        repo_stars::import::index::handler,
+        repo_stars::export::index::handler,
+        repo_stars::snapshots::start::index::handler,
+        repo_stars::snapshots::list::index::handler,
+        repo_stars::snapshots::stop::index::handler,
+        repo_stars::contributors::start::index::handler,
+        repo_stars::contributors::list::index::handler,
+        repo_stars::contributors::stop::index::handler,
+        repo_stars::jobs::resume::index::handler,
+        repo_stars::jobs::queue::index::handler,
+        repo_stars::jobs::purge::index::handler,
+        repo_stars::jobs::status::index::handler,
+        repo_stars::forecast::index::handler,
+        repo_stars::stats::index::handler,
+        repo_stars::trends::index::handler,
+        repo_stars::cohorts::index::handler,
+        repo_stars::heatmap::index::handler,
+        repo_stars::diff::index::handler,
+        repo_stars::milestones::list::index::handler,
+        repo_stars::milestones::eta::index::handler,
+        repo_stars::anomalies::list::index::handler,
+        repo_stars::anomalies::detect::index::handler,
+        repo_stars::earliest_stargazers::index::handler,
+        repo_stars::as_of::index::handler,
+        repo_stars::releases::index::handler,
+        repo_stars::live::index::handler,
+        repo_stars::drift::list::index::handler,
+        repo_stars::drift::verify::index::handler,
+        stargazers::top::index::handler,
+        stargazers::profiles::index::handler,
+        stargazers::emails::index::handler,
+        repositories::list::index::handler,
+        repositories::tags::list::index::handler,
+        repositories::tags::add::index::handler,
+        repositories::tags::remove::index::handler,
+        repositories::sync_history::index::handler,
+        repositories::data_quality::index::handler,
+        repositories::archive::add::index::handler,
+        repositories::archive::remove::index::handler,
+        org_stars::update::index::handler,
+        org_stars::batch::index::handler,
+        alerts::create::index::handler,
+        alerts::list::index::handler,
+        alerts::delete::index::handler,
+        reports::list::index::handler,
+        reports::get::index::handler,
+        webhooks::star::index::handler,
+        metrics::index::handler,
+        admin::config::get::index::handler,
+        admin::config::update::index::handler,
+    ),
+    tags(
+        (name = "repo_stars", description = "Star syncs and analysis of a single repository"),
+        (name = "org_stars", description = "Syncs of every repository of an owner"),
+        (name = "stargazers", description = "Stargazers across tracked repositories"),
+        (name = "repositories", description = "Tracked repositories, their tags and sync history"),
+        (name = "alerts", description = "Alert rules evaluated after every sync"),
+        (name = "reports", description = "Weekly digests, stored as they are generated"),
+        (name = "webhooks", description = "Events pushed by GitHub"),
+        (name = "metrics", description = "Service health"),
+        (name = "admin", description = "Configuration of the running service, behind ADMIN_TOKEN"),
+    ),
+)]
+pub struct ApiDoc;
+
+/// Routes of the `charting` feature, merged by [`ApiDoc::build`].
+#[cfg(feature = "charting")]
+#[derive(OpenApi)]
+#[openapi(paths(repo_stars::social_card::index::handler, repo_stars::compare::chart::index::handler))]
+struct ChartingApi;
+
+impl ApiDoc {
+    /// Description of every route of this build.
+    pub fn build() -> utoipa::openapi::OpenApi {
+        let doc = Self::openapi();
+        #[cfg(feature = "charting")]
+        let doc = doc.merge_from(ChartingApi::openapi());
+        doc
+    }
+}
@@ -0,0 +1,130 @@
+//! Tenants
+//!
+//! One deployment serves several teams, each seeing only the repositories it
+//! tracks. Requests name their tenant with an API key, sent as
+//! `Authorization: Bearer <key>` or `X-API-Key: <key>`. Keys are created with
+//! `star-tracker tenant`, only their SHA-256 is stored. Requests without a key
+//! belong to the default tenant unless `REQUIRE_API_KEY` is set.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ring::{digest, rand::{SecureRandom, SystemRandom}};
+use thiserror::Error;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::db::{
+	    api_key::queries::{get_tenant_id_by_key_hash, GetTenantIdByKeyHashError},
+	    tenant::models::DEFAULT_TENANT_ID,
+	    PgPool,
+	};
+
+/// Tenant of the request, inserted as an extension by [`tenant_middleware`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenantId(pub Uuid);
+
+/// Inserted next to [`TenantId`] when the request named its tenant with an API key,
+/// for routes the default tenant's anonymous callers mustn't reach.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyAuthenticated;
+
+/// State of [`tenant_middleware`].
+#[derive(Debug, Clone)]
+pub struct TenantAuth {
+    pub pool: PgPool,
+    /// Turns away requests without a key instead of serving the default tenant.
+    pub require_api_key: bool,
+}
+
+#[derive(Debug, Error)]
+enum TenantOfKeyError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        source: DbConnRejection,
+    },
+    #[error("GetTenantIdByKeyHash: {source}")]
+    GetTenantIdByKeyHash {
+        source: GetTenantIdByKeyHashError,
+    },
+}
+
+/// Axum middleware resolving the API key to a [`TenantId`] extension, added with `from_fn_with_state`.
+/// Unknown keys, and missing ones when required, get `401 Unauthorized`, keys looked up while
+/// no connection is free `503` as from [`DbConn`].
+pub async fn tenant_middleware(
+    State(auth): State<TenantAuth>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let key = api_key(request.headers());
+    let tenant_id = match key {
+        None if auth.require_api_key => return ApiError::new(StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, "API key required").into_response(),
+        None => DEFAULT_TENANT_ID,
+        Some(key) => match tenant_of_key(&auth.pool, key).await {
+            Ok(Some(tenant_id)) => tenant_id,
+            Ok(None) => return ApiError::new(StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized, "Unknown API key").into_response(),
+            Err(TenantOfKeyError::Checkout{ source }) => return source.into_response(),
+            Err(error) => {
+                warn!(%error, "could not look up API key");
+                return ApiError::internal(format!("Could not look up API key: {error}")).into_response();
+            }
+        },
+    };
+
+    let authenticated = key.is_some();
+    request.extensions_mut().insert(TenantId(tenant_id));
+    if authenticated {
+        request.extensions_mut().insert(ApiKeyAuthenticated);
+    }
+    next.run(request).await
+}
+
+/// Key of `X-API-Key`, or else of a bearer `Authorization`.
+fn api_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = || {
+        headers
+            .get(header::AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .strip_prefix("Bearer ")
+    };
+
+    headers
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(bearer)
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+async fn tenant_of_key(pool: &PgPool, key: &str) -> Result<Option<Uuid>, TenantOfKeyError> {
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+        .await
+        .map_err(|source| TenantOfKeyError::Checkout { source })?;
+
+    get_tenant_id_by_key_hash(&mut conn, &hash_api_key(key))
+        .map_err(|source| TenantOfKeyError::GetTenantIdByKeyHash { source })
+}
+
+/// Random 256-bit key, hex encoded with a `stk_` prefix making it easy to spot in configs.
+pub fn generate_api_key() -> Result<String, ring::error::Unspecified> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new().fill(&mut bytes)?;
+
+    Ok(format!("stk_{}", hex(&bytes)))
+}
+
+/// Hex SHA-256 of the key, as stored.
+pub fn hash_api_key(key: &str) -> String {
+    hex(digest::digest(&digest::SHA256, key.as_bytes()).as_ref())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,844 @@
+//! Service dependencies built from environment variables
+//!
+//! Shared by the HTTP server and the `star-tracker` CLI, so both read the
+//! same `.env`.
+
+use std::{num::{NonZeroU32, NonZeroU64, NonZeroUsize}, path::PathBuf, time::Duration};
+
+use interfaces_github_stargazers::{
+    api_url::{GitHubApiUrl, ParseGitHubApiUrlError},
+    app_auth::{GitHubAppAuth, NewGitHubAppAuthError},
+    client::{GitHubAuth, GitHubClient, GitHubClientConfig, NewGitHubClientError},
+    gitea::GiteaClient,
+    gitlab::{GitLabClient, GITLAB_COM_API_URL},
+    page_size::PageSizeConfig,
+    rest::{ParseRestApiUrlError, RestApiUrl},
+    retry::RetryConfig,
+    token_pool::TokenPool,
+    traffic::TRAFFIC_DAYS,
+};
+use thiserror::Error;
+
+use crate::chart_cache::ChartCacheConfig;
+use crate::db::{pool::{build_lazy_pg_pool, build_pg_pool, PoolConfig, PoolMetrics, ReadPool}, star::queries::{StarInsertConfig, MAX_INSERT_CHUNK_SIZE}, PgPool};
+use crate::digest::{DigestSchedule, ParseDigestScheduleError};
+use crate::email::{EmailSender, NewEmailSenderError};
+use crate::forges::ForgeClients;
+use crate::endpoints::{admin::auth::AdminToken, cors::{AllowedOrigins, CorsConfig}, github::webhooks::signature::WebhookSecret, limits::RequestLimits};
+use crate::notifier::Notifier;
+use crate::privacy::StargazerIds;
+use crate::job_retention::SyncJobRetention;
+use crate::live::is_notify_channel;
+use crate::sync_limits::SyncLimits;
+use crate::retention::StarRetention;
+use crate::storage::{ParseStorageBackendError, StorageBackend};
+use crate::validation::WebhookUrlPolicy;
+use crate::webhook::webhook_client;
+
+#[derive(Debug, Error)]
+pub enum DbPoolFromEnvError {
+    #[error("DbEnvVar: {source}")]
+    DbEnvVar {
+        source: std::env::VarError,
+    },
+    #[error("SlowQueryThresholdEnvVar: {source}")]
+    SlowQueryThresholdEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("DbPoolBuild: {source}")]
+    DbPoolBuild {
+        source: r2d2::Error,
+    },
+}
+
+/// Pool of `DATABASE_URL` connections, `SLOW_QUERY_THRESHOLD_MS` overriding the threshold of `config`.
+pub fn db_pool_from_env(mut config: PoolConfig) -> Result<(PgPool, PoolMetrics), DbPoolFromEnvError> {
+    let database_url = std::env::var("DATABASE_URL")
+        .map_err(|source| DbPoolFromEnvError::DbEnvVar { source })?;
+
+    if let Some(threshold) = slow_query_threshold_from_env()
+        .map_err(|source| DbPoolFromEnvError::SlowQueryThresholdEnvVar { source })? {
+        config.slow_query_threshold = threshold;
+    }
+
+    build_pg_pool(&database_url, &config)
+        .map_err(|source| DbPoolFromEnvError::DbPoolBuild { source })
+}
+
+#[derive(Debug, Error)]
+pub enum StorageBackendFromEnvError {
+    #[error("DbEnvVar: {source}")]
+    DbEnvVar {
+        source: std::env::VarError,
+    },
+    #[error("ParseStorageBackend: {source}")]
+    ParseStorageBackend {
+        source: ParseStorageBackendError,
+    },
+}
+
+/// Backend `DATABASE_URL` points to.
+pub fn storage_backend_from_env() -> Result<StorageBackend, StorageBackendFromEnvError> {
+    let database_url = std::env::var("DATABASE_URL")
+        .map_err(|source| StorageBackendFromEnvError::DbEnvVar { source })?;
+
+    StorageBackend::from_url(&database_url)
+        .map_err(|source| StorageBackendFromEnvError::ParseStorageBackend { source })
+}
+
+#[derive(Debug, Error)]
+pub enum ReadPoolFromEnvError {
+    #[error("SlowQueryThresholdEnvVar: {source}")]
+    SlowQueryThresholdEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// Pool of read-only queries: `DATABASE_URL_RO` connections when set, falling back to `primary`,
+/// or `primary` alone. The replica is only connected to once used, it may be down at startup.
+pub fn read_pool_from_env(primary: PgPool, mut config: PoolConfig) -> Result<ReadPool, ReadPoolFromEnvError> {
+    let Ok(replica_url) = std::env::var("DATABASE_URL_RO") else {
+        return Ok(ReadPool::new(primary, None));
+    };
+
+    if let Some(threshold) = slow_query_threshold_from_env()
+        .map_err(|source| ReadPoolFromEnvError::SlowQueryThresholdEnvVar { source })? {
+        config.slow_query_threshold = threshold;
+    }
+
+    Ok(ReadPool::new(primary, Some(build_lazy_pg_pool(&replica_url, &config))))
+}
+
+fn slow_query_threshold_from_env() -> Result<Option<Duration>, std::num::ParseIntError> {
+    std::env::var("SLOW_QUERY_THRESHOLD_MS")
+        .ok()
+        .map(|millis| millis.parse().map(Duration::from_millis))
+        .transpose()
+}
+
+#[derive(Debug, Error)]
+pub enum GitHubClientFromEnvError {
+    #[error("GitHubApiUrl: {source}")]
+    GitHubApiUrl {
+        source: ParseGitHubApiUrlError,
+    },
+    #[error("GitHubRetryEnvVar: {source}")]
+    GitHubRetryEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("GitHubPageSizeEnvVar: {source}")]
+    GitHubPageSizeEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("GitHubAppEnvVar: {source}")]
+    GitHubAppEnvVar {
+        source: std::env::VarError,
+    },
+    #[error("GitHubAppInstallationId: {source}")]
+    GitHubAppInstallationId {
+        source: std::num::ParseIntError,
+    },
+    #[error("GitHubAppPrivateKeyRead: {source}")]
+    GitHubAppPrivateKeyRead {
+        source: std::io::Error,
+    },
+    #[error("GitHubAppAuth: {source}")]
+    GitHubAppAuth {
+        source: NewGitHubAppAuthError,
+    },
+    #[error("GitHubClientBuild: {source}")]
+    GitHubClientBuild {
+        source: NewGitHubClientError,
+    },
+}
+
+/// GitHub client configured by `GITHUB_API_URL`, `GITHUB_PROXY`, `GITHUB_RETRY_MAX_ATTEMPTS`,
+/// `GITHUB_PAGE_SIZE`, `GITHUB_MIN_PAGE_SIZE` and the credential variables.
+pub fn github_client_from_env() -> Result<GitHubClient, GitHubClientFromEnvError> {
+    let api_url = match std::env::var("GITHUB_API_URL") {
+        Ok(api_url) => api_url
+            .parse()
+            .map_err(|source| GitHubClientFromEnvError::GitHubApiUrl { source })?,
+        Err(_) => GitHubApiUrl::default(),
+    };
+
+    let mut retry = RetryConfig::default();
+    if let Ok(max_attempts) = std::env::var("GITHUB_RETRY_MAX_ATTEMPTS") {
+        retry.max_attempts = max_attempts
+            .parse()
+            .map_err(|source| GitHubClientFromEnvError::GitHubRetryEnvVar { source })?;
+    }
+
+    let mut page_size = PageSizeConfig::default();
+    if let Ok(max) = std::env::var("GITHUB_PAGE_SIZE") {
+        page_size.max = max
+            .parse()
+            .map_err(|source| GitHubClientFromEnvError::GitHubPageSizeEnvVar { source })?;
+    }
+    if let Ok(min) = std::env::var("GITHUB_MIN_PAGE_SIZE") {
+        page_size.min = min
+            .parse()
+            .map_err(|source| GitHubClientFromEnvError::GitHubPageSizeEnvVar { source })?;
+    }
+
+    GitHubClient::new(&GitHubClientConfig {
+        api_url,
+        proxy: std::env::var("GITHUB_PROXY").ok(),
+        retry,
+        page_size,
+        ..GitHubClientConfig::default()
+    }, github_auth_from_env()?)
+        .map_err(|source| GitHubClientFromEnvError::GitHubClientBuild { source })
+}
+
+/// GitHub App credentials when `GITHUB_APP_ID` is set, personal access tokens otherwise.
+fn github_auth_from_env() -> Result<GitHubAuth, GitHubClientFromEnvError> {
+    let Ok(app_id) = std::env::var("GITHUB_APP_ID") else {
+        return Ok(GitHubAuth::Tokens(TokenPool::from_env()));
+    };
+
+    let installation_id = std::env::var("GITHUB_APP_INSTALLATION_ID")
+        .map_err(|source| GitHubClientFromEnvError::GitHubAppEnvVar { source })?
+        .parse()
+        .map_err(|source| GitHubClientFromEnvError::GitHubAppInstallationId { source })?;
+
+    let private_key_path = std::env::var("GITHUB_APP_PRIVATE_KEY_PATH")
+        .map_err(|source| GitHubClientFromEnvError::GitHubAppEnvVar { source })?;
+    let private_key = std::fs::read(private_key_path)
+        .map_err(|source| GitHubClientFromEnvError::GitHubAppPrivateKeyRead { source })?;
+
+    GitHubAppAuth::new(app_id, installation_id, &private_key)
+        .map(GitHubAuth::App)
+        .map_err(|source| GitHubClientFromEnvError::GitHubAppAuth { source })
+}
+
+#[derive(Debug, Error)]
+pub enum ForgeClientsFromEnvError {
+    #[error("GitLabApiUrl: {source}")]
+    GitLabApiUrl {
+        source: ParseRestApiUrlError,
+    },
+    #[error("GiteaApiUrl: {source}")]
+    GiteaApiUrl {
+        source: ParseRestApiUrlError,
+    },
+}
+
+/// Clients of every forge, GitLab and Gitea sharing the connections and retries of `github`.
+/// GitLab is gitlab.com unless `GITLAB_API_URL` is set, Gitea is only reachable once
+/// `GITEA_API_URL` is. `GITLAB_TOKEN` and `GITEA_TOKEN` authenticate their requests.
+pub fn forge_clients_from_env(github: GitHubClient) -> Result<ForgeClients, ForgeClientsFromEnvError> {
+    let token = |var| std::env::var(var).ok().filter(|token: &String| !token.is_empty());
+
+    let gitlab_url = std::env::var("GITLAB_API_URL").unwrap_or_else(|_| GITLAB_COM_API_URL.into());
+    let gitlab_url: RestApiUrl = gitlab_url
+        .parse()
+        .map_err(|source| ForgeClientsFromEnvError::GitLabApiUrl { source })?;
+    let gitlab = GitLabClient::new(github.http().clone(), gitlab_url, token("GITLAB_TOKEN"), github.retry().clone());
+
+    let gitea = match std::env::var("GITEA_API_URL") {
+        Ok(gitea_url) => {
+            let gitea_url = gitea_url
+                .parse()
+                .map_err(|source| ForgeClientsFromEnvError::GiteaApiUrl { source })?;
+            Some(GiteaClient::new(github.http().clone(), gitea_url, token("GITEA_TOKEN"), github.retry().clone()))
+        }
+        Err(_) => None,
+    };
+
+    Ok(ForgeClients::new(github, gitlab, gitea))
+}
+
+#[derive(Debug, Error)]
+pub enum NotifierFromEnvError {
+    #[error("NotifierHttpClientBuild: {source}")]
+    NotifierHttpClientBuild {
+        source: reqwest::Error,
+    },
+    #[error("SmtpFromEnvVar: {source}")]
+    SmtpFromEnvVar {
+        source: std::env::VarError,
+    },
+    #[error("EmailSender: {source}")]
+    EmailSender {
+        source: NewEmailSenderError,
+    },
+    #[error("InvalidWebhookScheme: {value}")]
+    InvalidWebhookScheme {
+        value: String,
+    },
+}
+
+/// Alert notifier, sending emails only when `SMTP_URL` is set.
+/// Webhooks are posted by a [`webhook_client`] and follow `WEBHOOK_ALLOWED_SCHEMES` and `WEBHOOK_ALLOWED_HOSTS`, see [`webhook_url_policy_from_env`].
+pub fn notifier_from_env() -> Result<Notifier, NotifierFromEnvError> {
+    let policy = webhook_url_policy_from_env()?;
+    let http = webhook_client(&policy, Duration::from_secs(10))
+        .map_err(|source| NotifierFromEnvError::NotifierHttpClientBuild { source })?;
+
+    Ok(Notifier::new(http, email_sender_from_env()?, policy))
+}
+
+/// Webhook URL policy. `WEBHOOK_ALLOWED_SCHEMES` replaces the default `https` with a comma-separated
+/// list of `http` and `https`, `WEBHOOK_ALLOWED_HOSTS` restricts webhooks to the listed hosts.
+pub fn webhook_url_policy_from_env() -> Result<WebhookUrlPolicy, NotifierFromEnvError> {
+    let mut policy = WebhookUrlPolicy::default();
+    if let Ok(schemes) = std::env::var("WEBHOOK_ALLOWED_SCHEMES") {
+        policy.allowed_schemes = comma_separated(&schemes)
+            .map(|scheme| match scheme.to_ascii_lowercase().as_str() {
+                scheme @ ("http" | "https") => Ok(scheme.to_string()),
+                _ => Err(NotifierFromEnvError::InvalidWebhookScheme { value: scheme.into() }),
+            })
+            .collect::<Result<_, _>>()?;
+    }
+    if let Ok(hosts) = std::env::var("WEBHOOK_ALLOWED_HOSTS") {
+        policy.allowed_hosts = comma_separated(&hosts).map(str::to_ascii_lowercase).collect();
+    }
+    Ok(policy)
+}
+
+/// Email sender for `SMTP_URL`, which also requires `SMTP_FROM`.
+fn email_sender_from_env() -> Result<Option<EmailSender>, NotifierFromEnvError> {
+    let Ok(smtp_url) = std::env::var("SMTP_URL") else {
+        return Ok(None);
+    };
+
+    let from = std::env::var("SMTP_FROM")
+        .map_err(|source| NotifierFromEnvError::SmtpFromEnvVar { source })?;
+
+    EmailSender::new(&smtp_url, &from)
+        .map(Some)
+        .map_err(|source| NotifierFromEnvError::EmailSender { source })
+}
+
+#[derive(Debug, Error)]
+pub enum ChartCacheFromEnvError {
+    #[error("ChartCacheTtlEnvVar: {source}")]
+    ChartCacheTtlEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("ChartCacheMaxBytesEnvVar: {source}")]
+    ChartCacheMaxBytesEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// Bounds of the chart cache, `CHART_CACHE_TTL_SECONDS` (0 turning it off) and `CHART_CACHE_MAX_BYTES`
+/// overriding the defaults.
+pub fn chart_cache_config_from_env() -> Result<ChartCacheConfig, ChartCacheFromEnvError> {
+    let mut config = ChartCacheConfig::default();
+    if let Ok(seconds) = std::env::var("CHART_CACHE_TTL_SECONDS") {
+        config.ttl = Duration::from_secs(seconds
+            .trim()
+            .parse()
+            .map_err(|source| ChartCacheFromEnvError::ChartCacheTtlEnvVar { source })?);
+    }
+    if let Ok(bytes) = std::env::var("CHART_CACHE_MAX_BYTES") {
+        config.max_bytes = bytes
+            .trim()
+            .parse()
+            .map_err(|source| ChartCacheFromEnvError::ChartCacheMaxBytesEnvVar { source })?;
+    }
+    Ok(config)
+}
+
+/// Secret of the GitHub star webhook, `None` when `GITHUB_WEBHOOK_SECRET` is unset or empty.
+pub fn webhook_secret_from_env() -> Option<WebhookSecret> {
+    std::env::var("GITHUB_WEBHOOK_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .map(|secret| WebhookSecret::new(&secret))
+}
+
+/// Token opening the admin endpoints, `None` when `ADMIN_TOKEN` is unset or empty.
+pub fn admin_token_from_env() -> Option<AdminToken> {
+    std::env::var("ADMIN_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .map(|token| AdminToken::new(&token))
+}
+
+/// Watch list loaded at startup, `None` when `REPOS_FILE` is unset or empty.
+pub fn repos_file_from_env() -> Option<PathBuf> {
+    std::env::var("REPOS_FILE")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Stargazers hashed under `STARGAZER_HASH_SECRET` when it is set and not empty, stored as logins otherwise.
+pub fn stargazer_ids_from_env() -> StargazerIds {
+    std::env::var("STARGAZER_HASH_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .map_or(StargazerIds::Logins, |secret| StargazerIds::hashed(&secret))
+}
+
+#[derive(Debug, Error)]
+pub enum CollectStargazerProfilesFromEnvError {
+    #[error("CollectStargazerProfilesEnvVar: {source}")]
+    CollectStargazerProfilesEnvVar {
+        source: std::str::ParseBoolError,
+    },
+}
+
+/// Whether syncs store stargazers' company and location, unless `COLLECT_STARGAZER_PROFILES` is `false`.
+pub fn collect_stargazer_profiles_from_env() -> Result<bool, CollectStargazerProfilesFromEnvError> {
+    match std::env::var("COLLECT_STARGAZER_PROFILES") {
+        Ok(collect) => collect
+            .trim()
+            .parse()
+            .map_err(|source| CollectStargazerProfilesFromEnvError::CollectStargazerProfilesEnvVar { source }),
+        Err(_) => Ok(true),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CollectStargazerEmailsFromEnvError {
+    #[error("CollectStargazerEmailsEnvVar: {source}")]
+    CollectStargazerEmailsEnvVar {
+        source: std::str::ParseBoolError,
+    },
+}
+
+/// Whether syncs store stargazers' public emails, only when `COLLECT_STARGAZER_EMAILS` is `true`.
+pub fn collect_stargazer_emails_from_env() -> Result<bool, CollectStargazerEmailsFromEnvError> {
+    match std::env::var("COLLECT_STARGAZER_EMAILS") {
+        Ok(collect) => collect
+            .trim()
+            .parse()
+            .map_err(|source| CollectStargazerEmailsFromEnvError::CollectStargazerEmailsEnvVar { source }),
+        Err(_) => Ok(false),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FlagSuspectedBotsFromEnvError {
+    #[error("FlagSuspectedBotsEnvVar: {source}")]
+    FlagSuspectedBotsEnvVar {
+        source: std::str::ParseBoolError,
+    },
+}
+
+/// Whether syncs flag the stars of suspected bots, only when `FLAG_SUSPECTED_BOTS` is `true`.
+pub fn flag_suspected_bots_from_env() -> Result<bool, FlagSuspectedBotsFromEnvError> {
+    match std::env::var("FLAG_SUSPECTED_BOTS") {
+        Ok(flag) => flag
+            .trim()
+            .parse()
+            .map_err(|source| FlagSuspectedBotsFromEnvError::FlagSuspectedBotsEnvVar { source }),
+        Err(_) => Ok(false),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CollectRepositoryActivityFromEnvError {
+    #[error("CollectRepositoryActivityEnvVar: {source}")]
+    CollectRepositoryActivityEnvVar {
+        source: std::str::ParseBoolError,
+    },
+}
+
+/// Whether syncs store the issue and pull request activity of GitHub repositories, only when
+/// `COLLECT_REPOSITORY_ACTIVITY` is `true`.
+pub fn collect_repository_activity_from_env() -> Result<bool, CollectRepositoryActivityFromEnvError> {
+    match std::env::var("COLLECT_REPOSITORY_ACTIVITY") {
+        Ok(collect) => collect
+            .trim()
+            .parse()
+            .map_err(|source| CollectRepositoryActivityFromEnvError::CollectRepositoryActivityEnvVar { source }),
+        Err(_) => Ok(false),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RequireApiKeyFromEnvError {
+    #[error("RequireApiKeyEnvVar: {source}")]
+    RequireApiKeyEnvVar {
+        source: std::str::ParseBoolError,
+    },
+}
+
+/// Whether requests without an API key are turned away, when `REQUIRE_API_KEY` is `true`.
+/// They belong to the default tenant otherwise.
+pub fn require_api_key_from_env() -> Result<bool, RequireApiKeyFromEnvError> {
+    match std::env::var("REQUIRE_API_KEY") {
+        Ok(require) => require
+            .trim()
+            .parse()
+            .map_err(|source| RequireApiKeyFromEnvError::RequireApiKeyEnvVar { source }),
+        Err(_) => Ok(false),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StarRetentionFromEnvError {
+    #[error("StarRetentionMonthsEnvVar: {source}")]
+    StarRetentionMonthsEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("StarCompactionIntervalEnvVar: {source}")]
+    StarCompactionIntervalEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// Star rows older than `STAR_RETENTION_MONTHS` are compacted every `STAR_COMPACTION_INTERVAL_HOURS`, 24 by default.
+/// `None` while `STAR_RETENTION_MONTHS` is unset or empty, every star is then kept.
+pub fn star_retention_from_env() -> Result<Option<StarRetention>, StarRetentionFromEnvError> {
+    let Some(months) = std::env::var("STAR_RETENTION_MONTHS").ok().filter(|months| !months.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let months = months
+        .trim()
+        .parse::<NonZeroU32>()
+        .map_err(|source| StarRetentionFromEnvError::StarRetentionMonthsEnvVar { source })?
+        .get();
+
+    let hours = match std::env::var("STAR_COMPACTION_INTERVAL_HOURS") {
+        Ok(hours) => hours
+            .trim()
+            .parse::<NonZeroU64>()
+            .map_err(|source| StarRetentionFromEnvError::StarCompactionIntervalEnvVar { source })?
+            .get(),
+        Err(_) => 24,
+    };
+
+    Ok(Some(StarRetention { months, interval: Duration::from_secs(hours * 3600) }))
+}
+
+#[derive(Debug, Error)]
+pub enum StarSnapshotIntervalFromEnvError {
+    #[error("StarSnapshotIntervalEnvVar: {source}")]
+    StarSnapshotIntervalEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// Repositories in snapshot mode get their star count recorded every `STAR_SNAPSHOT_INTERVAL_HOURS`, 24 by default.
+pub fn star_snapshot_interval_from_env() -> Result<Duration, StarSnapshotIntervalFromEnvError> {
+    let hours = match std::env::var("STAR_SNAPSHOT_INTERVAL_HOURS") {
+        Ok(hours) => hours
+            .trim()
+            .parse::<NonZeroU64>()
+            .map_err(|source| StarSnapshotIntervalFromEnvError::StarSnapshotIntervalEnvVar { source })?
+            .get(),
+        Err(_) => 24,
+    };
+    Ok(Duration::from_secs(hours * 3600))
+}
+
+#[derive(Debug, Error)]
+pub enum ContributorTrackingIntervalFromEnvError {
+    #[error("ContributorTrackingIntervalEnvVar: {source}")]
+    ContributorTrackingIntervalEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// Repositories in contributors mode get their commit authors tracked every
+/// `CONTRIBUTOR_TRACKING_INTERVAL_HOURS`, 24 by default.
+pub fn contributor_tracking_interval_from_env() -> Result<Duration, ContributorTrackingIntervalFromEnvError> {
+    let hours = match std::env::var("CONTRIBUTOR_TRACKING_INTERVAL_HOURS") {
+        Ok(hours) => hours
+            .trim()
+            .parse::<NonZeroU64>()
+            .map_err(|source| ContributorTrackingIntervalFromEnvError::ContributorTrackingIntervalEnvVar { source })?
+            .get(),
+        Err(_) => 24,
+    };
+    Ok(Duration::from_secs(hours * 3600))
+}
+
+#[derive(Debug, Error)]
+pub enum TrafficSnapshotIntervalFromEnvError {
+    #[error("TrafficSnapshotIntervalEnvVar: {source}")]
+    TrafficSnapshotIntervalEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("TrafficSnapshotIntervalTooLong: {hours}")]
+    TrafficSnapshotIntervalTooLong {
+        hours: u64,
+    },
+}
+
+/// GitHub repositories get their views and clones recorded every `TRAFFIC_SNAPSHOT_INTERVAL_HOURS`,
+/// `None` while it is unset. GitHub keeps 14 days of them, longer intervals would leave gaps.
+pub fn traffic_snapshot_interval_from_env() -> Result<Option<Duration>, TrafficSnapshotIntervalFromEnvError> {
+    let Ok(hours) = std::env::var("TRAFFIC_SNAPSHOT_INTERVAL_HOURS") else {
+        return Ok(None);
+    };
+    let hours = hours
+        .trim()
+        .parse::<NonZeroU64>()
+        .map_err(|source| TrafficSnapshotIntervalFromEnvError::TrafficSnapshotIntervalEnvVar { source })?
+        .get();
+    if hours > u64::from(TRAFFIC_DAYS) * 24 {
+        return Err(TrafficSnapshotIntervalFromEnvError::TrafficSnapshotIntervalTooLong { hours });
+    }
+    Ok(Some(Duration::from_secs(hours * 3600)))
+}
+
+#[derive(Debug, Error)]
+pub enum SyncNotifyChannelFromEnvError {
+    #[error("InvalidSyncNotifyChannel: {channel}")]
+    InvalidSyncNotifyChannel {
+        channel: String,
+    },
+}
+
+/// Channel told of every sync job ending, `None` when `SYNC_NOTIFY_CHANNEL` is unset or empty.
+/// It must be a lowercase identifier, so that `LISTEN` finds it without quoting.
+pub fn sync_notify_channel_from_env() -> Result<Option<String>, SyncNotifyChannelFromEnvError> {
+    let Some(channel) = std::env::var("SYNC_NOTIFY_CHANNEL").ok().filter(|channel| !channel.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let channel = channel.trim().to_string();
+    if !is_notify_channel(&channel) {
+        return Err(SyncNotifyChannelFromEnvError::InvalidSyncNotifyChannel { channel });
+    }
+    Ok(Some(channel))
+}
+
+#[derive(Debug, Error)]
+pub enum SyncJobRetentionFromEnvError {
+    #[error("SyncJobRetentionHoursEnvVar: {source}")]
+    SyncJobRetentionHoursEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("SyncJobCleanupIntervalEnvVar: {source}")]
+    SyncJobCleanupIntervalEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// Finished sync jobs are kept `SYNC_JOB_RETENTION_HOURS` (24 by default) and older ones deleted
+/// every `SYNC_JOB_CLEANUP_INTERVAL_MINUTES` (60 by default).
+pub fn sync_job_retention_from_env() -> Result<SyncJobRetention, SyncJobRetentionFromEnvError> {
+    let mut retention = SyncJobRetention::default();
+    if let Ok(hours) = std::env::var("SYNC_JOB_RETENTION_HOURS") {
+        let hours = hours
+            .trim()
+            .parse::<NonZeroU64>()
+            .map_err(|source| SyncJobRetentionFromEnvError::SyncJobRetentionHoursEnvVar { source })?;
+        retention.max_age = Duration::from_secs(hours.get() * 3600);
+    }
+    if let Ok(minutes) = std::env::var("SYNC_JOB_CLEANUP_INTERVAL_MINUTES") {
+        let minutes = minutes
+            .trim()
+            .parse::<NonZeroU64>()
+            .map_err(|source| SyncJobRetentionFromEnvError::SyncJobCleanupIntervalEnvVar { source })?;
+        retention.interval = Duration::from_secs(minutes.get() * 60);
+    }
+    Ok(retention)
+}
+
+#[derive(Debug, Error)]
+pub enum SyncLimitsFromEnvError {
+    #[error("SyncMaxPagesEnvVar: {source}")]
+    SyncMaxPagesEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("SyncMaxDurationSecondsEnvVar: {source}")]
+    SyncMaxDurationSecondsEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("SyncMaxStarsEnvVar: {source}")]
+    SyncMaxStarsEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// Limits of every sync job, `SYNC_MAX_PAGES`, `SYNC_MAX_DURATION_SECONDS` and `SYNC_MAX_STARS`,
+/// each unlimited unless set. See [`crate::sync_limits`].
+pub fn sync_limits_from_env() -> Result<SyncLimits, SyncLimitsFromEnvError> {
+    let mut limits = SyncLimits::default();
+    if let Ok(pages) = std::env::var("SYNC_MAX_PAGES") {
+        let pages = pages
+            .trim()
+            .parse::<NonZeroU32>()
+            .map_err(|source| SyncLimitsFromEnvError::SyncMaxPagesEnvVar { source })?;
+        limits.max_pages = Some(pages.get());
+    }
+    if let Ok(seconds) = std::env::var("SYNC_MAX_DURATION_SECONDS") {
+        let seconds = seconds
+            .trim()
+            .parse::<NonZeroU64>()
+            .map_err(|source| SyncLimitsFromEnvError::SyncMaxDurationSecondsEnvVar { source })?;
+        limits.max_duration_seconds = Some(seconds.get());
+    }
+    if let Ok(stars) = std::env::var("SYNC_MAX_STARS") {
+        let stars = stars
+            .trim()
+            .parse::<NonZeroU64>()
+            .map_err(|source| SyncLimitsFromEnvError::SyncMaxStarsEnvVar { source })?;
+        limits.max_stars = Some(stars.get());
+    }
+    Ok(limits)
+}
+
+#[derive(Debug, Error)]
+pub enum StarInsertConfigFromEnvError {
+    #[error("StarInsertChunkSizeEnvVar: {source}")]
+    StarInsertChunkSizeEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("StarInsertChunkSizeTooLarge: {chunk_size}")]
+    StarInsertChunkSizeTooLarge {
+        chunk_size: usize,
+    },
+    #[error("StarCopyThresholdEnvVar: {source}")]
+    StarCopyThresholdEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// How large batches of stars are written, `STAR_INSERT_CHUNK_SIZE` (rows per statement) and
+/// `STAR_COPY_THRESHOLD` (smallest batch loaded with `COPY`) overriding the defaults.
+pub fn star_insert_config_from_env() -> Result<StarInsertConfig, StarInsertConfigFromEnvError> {
+    let mut config = StarInsertConfig::default();
+    if let Ok(chunk_size) = std::env::var("STAR_INSERT_CHUNK_SIZE") {
+        config.chunk_size = chunk_size
+            .trim()
+            .parse::<NonZeroUsize>()
+            .map_err(|source| StarInsertConfigFromEnvError::StarInsertChunkSizeEnvVar { source })?
+            .get();
+        if config.chunk_size > MAX_INSERT_CHUNK_SIZE {
+            return Err(StarInsertConfigFromEnvError::StarInsertChunkSizeTooLarge { chunk_size: config.chunk_size });
+        }
+    }
+    if let Ok(threshold) = std::env::var("STAR_COPY_THRESHOLD") {
+        config.copy_threshold = threshold
+            .trim()
+            .parse()
+            .map_err(|source| StarInsertConfigFromEnvError::StarCopyThresholdEnvVar { source })?;
+    }
+    Ok(config)
+}
+
+#[derive(Debug, Error)]
+pub enum DigestScheduleFromEnvError {
+    #[error("DigestScheduleEnvVar: {source}")]
+    DigestScheduleEnvVar {
+        source: ParseDigestScheduleError,
+    },
+}
+
+/// Weekly digests are generated at `DIGEST_SCHEDULE`, e.g. `mon 08:00` (UTC).
+/// `None` while it is unset or empty, digests are then only generated from the CLI.
+pub fn digest_schedule_from_env() -> Result<Option<DigestSchedule>, DigestScheduleFromEnvError> {
+    let Some(schedule) = std::env::var("DIGEST_SCHEDULE").ok().filter(|schedule| !schedule.trim().is_empty()) else {
+        return Ok(None);
+    };
+    schedule
+        .parse()
+        .map(Some)
+        .map_err(|source| DigestScheduleFromEnvError::DigestScheduleEnvVar { source })
+}
+
+#[derive(Debug, Error)]
+pub enum RequestLimitsFromEnvError {
+    #[error("RequestTimeoutEnvVar: {source}")]
+    RequestTimeoutEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("MaxRequestBodyEnvVar: {source}")]
+    MaxRequestBodyEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("MaxConcurrentRequestsEnvVar: {source}")]
+    MaxConcurrentRequestsEnvVar {
+        source: std::num::ParseIntError,
+    },
+    #[error("MaxConcurrentRendersEnvVar: {source}")]
+    MaxConcurrentRendersEnvVar {
+        source: std::num::ParseIntError,
+    },
+}
+
+/// Request limits, `REQUEST_TIMEOUT_SECONDS`, `MAX_REQUEST_BODY_BYTES`, `MAX_CONCURRENT_REQUESTS` and `MAX_CONCURRENT_RENDERS`
+/// overriding the defaults.
+pub fn request_limits_from_env() -> Result<RequestLimits, RequestLimitsFromEnvError> {
+    let mut limits = RequestLimits::default();
+    if let Ok(seconds) = std::env::var("REQUEST_TIMEOUT_SECONDS") {
+        limits.timeout = Duration::from_secs(seconds
+            .parse::<NonZeroU64>()
+            .map_err(|source| RequestLimitsFromEnvError::RequestTimeoutEnvVar { source })?
+            .get());
+    }
+    if let Ok(bytes) = std::env::var("MAX_REQUEST_BODY_BYTES") {
+        limits.max_body_bytes = bytes
+            .parse()
+            .map_err(|source| RequestLimitsFromEnvError::MaxRequestBodyEnvVar { source })?;
+    }
+    if let Ok(requests) = std::env::var("MAX_CONCURRENT_REQUESTS") {
+        limits.max_concurrent_requests = requests
+            .parse::<NonZeroUsize>()
+            .map_err(|source| RequestLimitsFromEnvError::MaxConcurrentRequestsEnvVar { source })?
+            .get();
+    }
+    if let Ok(renders) = std::env::var("MAX_CONCURRENT_RENDERS") {
+        limits.max_concurrent_renders = renders
+            .parse::<NonZeroUsize>()
+            .map_err(|source| RequestLimitsFromEnvError::MaxConcurrentRendersEnvVar { source })?
+            .get();
+    }
+    Ok(limits)
+}
+
+
+#[derive(Debug, Error)]
+pub enum CorsFromEnvError {
+    #[error("InvalidCorsOrigin: {value}")]
+    InvalidCorsOrigin {
+        value: String,
+    },
+    #[error("InvalidCorsMethod: {value}")]
+    InvalidCorsMethod {
+        value: String,
+    },
+    #[error("InvalidCorsHeader: {value}")]
+    InvalidCorsHeader {
+        value: String,
+    },
+}
+
+/// CORS settings, `None` while `CORS_ALLOWED_ORIGINS` is unset or empty so cross-origin calls stay refused.
+/// `CORS_ALLOWED_ORIGINS` is `*` or a comma-separated list, `CORS_ALLOWED_METHODS` and `CORS_ALLOWED_HEADERS` replace the defaults.
+pub fn cors_from_env() -> Result<Option<CorsConfig>, CorsFromEnvError> {
+    let Some(origins) = std::env::var("CORS_ALLOWED_ORIGINS").ok().filter(|origins| !origins.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    let allowed_origins = if origins.trim() == "*" {
+        AllowedOrigins::Any
+    } else {
+        AllowedOrigins::List(comma_separated(&origins)
+            .map(|origin| origin
+                .parse()
+                .map_err(|_| CorsFromEnvError::InvalidCorsOrigin { value: origin.into() }))
+            .collect::<Result<_, _>>()?)
+    };
+
+    let mut config = CorsConfig::new(allowed_origins);
+    if let Ok(methods) = std::env::var("CORS_ALLOWED_METHODS") {
+        config.allowed_methods = comma_separated(&methods)
+            .map(|method| method
+                .to_ascii_uppercase()
+                .parse()
+                .map_err(|_| CorsFromEnvError::InvalidCorsMethod { value: method.into() }))
+            .collect::<Result<_, _>>()?;
+    }
+    if let Ok(headers) = std::env::var("CORS_ALLOWED_HEADERS") {
+        config.allowed_headers = comma_separated(&headers)
+            .map(|header| header
+                .parse()
+                .map_err(|_| CorsFromEnvError::InvalidCorsHeader { value: header.into() }))
+            .collect::<Result<_, _>>()?;
+    }
+    Ok(Some(config))
+}
+
+fn comma_separated(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|item| !item.is_empty())
+}
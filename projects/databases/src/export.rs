@@ -0,0 +1,116 @@
+//! Star history exports of the CLI
+//!
+//! `star-tracker export` names repositories as `owner/name` and writes their
+//! stars per day as CSV or JSON rows, every day from the first star to the
+//! last with the running total, zero-star days included.
+
+use std::io::Write;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::analytics::series::daily_series;
+
+/// Repository given as `owner/name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoName {
+    pub owner: String,
+    pub name: String,
+}
+
+impl FromStr for RepoName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+                Ok(Self { owner: owner.into(), name: name.into() })
+            }
+            _ => Err(format!("expected owner/name, got {s}")),
+        }
+    }
+}
+
+impl std::fmt::Display for RepoName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// One exported day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayRow {
+    pub day: NaiveDate,
+    pub stars: i64,
+    pub total: i64,
+}
+
+/// One exported day of a repository in a tag export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepoDayRow {
+    pub repo: String,
+    pub day: NaiveDate,
+    pub stars: i64,
+    pub total: i64,
+}
+
+impl RepoDayRow {
+    pub fn new(repo: &RepoName, row: DayRow) -> Self {
+        Self { repo: repo.to_string(), day: row.day, stars: row.stars, total: row.total }
+    }
+}
+
+/// Rows of `daily`, one entry per day with stars in ascending order as loaded by `get_daily_star_count`.
+pub fn day_rows(daily: &[(NaiveDate, i64)]) -> Vec<DayRow> {
+    let mut total = 0;
+    daily_series(daily)
+        .into_iter()
+        .map(|(day, stars)| {
+            total += stars;
+            DayRow { day, stars, total }
+        })
+        .collect()
+}
+
+#[derive(Debug, Error)]
+pub enum WriteRowsError {
+    #[error("CsvWrite: {source}")]
+    CsvWrite {
+        source: csv::Error,
+    },
+    #[error("JsonWrite: {source}")]
+    JsonWrite {
+        source: serde_json::Error,
+    },
+    #[error("OutputWrite: {source}")]
+    OutputWrite {
+        source: std::io::Error,
+    },
+}
+
+/// Writes `rows` to `out`, as CSV with a header line or as a pretty-printed JSON array.
+pub fn write_rows<T: Serialize>(rows: &[T], format: ExportFormat, mut out: impl Write) -> Result<(), WriteRowsError> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(&mut out);
+            for row in rows {
+                writer.serialize(row).map_err(|source| WriteRowsError::CsvWrite { source })?;
+            }
+            writer.flush().map_err(|source| WriteRowsError::OutputWrite { source })?;
+        }
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, rows).map_err(|source| WriteRowsError::JsonWrite { source })?;
+            writeln!(out).map_err(|source| WriteRowsError::OutputWrite { source })?;
+        }
+    }
+    out.flush().map_err(|source| WriteRowsError::OutputWrite { source })
+}
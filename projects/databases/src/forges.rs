@@ -0,0 +1,164 @@
+//! Clients of the forges repositories are tracked on
+//!
+//! Every repository has a forge, GitHub unless told otherwise, and its syncs
+//! and star counts go through that forge's client. GitLab is reached
+//! anonymously unless `GITLAB_TOKEN` is set, which only gives access to
+//! public projects, while Gitea has no default instance and needs
+//! `GITEA_API_URL`. Forks, stargazer profiles and star webhooks remain
+//! GitHub only.
+//!
+//! Syncs only see each forge as a [`StargazerFetcher`], which tests replace
+//! with [`ForgeClients::with_fetcher`] to script pages and failures.
+
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use interfaces_github_stargazers::client::GitHubClient;
+use interfaces_github_stargazers::fetcher::{StargazerFetchError, StargazerFetcher};
+use interfaces_github_stargazers::forge::{Forge, ForgeApiError, ForgeClient, ForgeErrorKind, GitHubForgeError, StargazerPage};
+use interfaces_github_stargazers::gitea::GiteaClient;
+use interfaces_github_stargazers::gitlab::GitLabClient;
+use interfaces_github_stargazers::page_size::PageSizeConfig;
+use interfaces_github_stargazers::retry::RetryConfig;
+use thiserror::Error;
+
+use crate::endpoints::github::status::github_error_status_code;
+
+/// One client per forge, cheap to clone.
+#[derive(Clone)]
+pub struct ForgeClients {
+    /// For what only GitHub offers, syncs go through `github_fetcher`.
+    github: GitHubClient,
+    /// Unset without GitHub credentials.
+    github_fetcher: Option<Arc<dyn StargazerFetcher>>,
+    gitlab: Arc<dyn StargazerFetcher>,
+    /// Unset without `GITEA_API_URL`.
+    gitea: Option<Arc<dyn StargazerFetcher>>,
+}
+
+impl std::fmt::Debug for ForgeClients {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ForgeClients")
+            .field("github", &self.github)
+            .field("github_fetcher", &self.github_fetcher.is_some())
+            .field("gitea", &self.gitea.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ForgeClientError {
+    #[error("{forge}: {source}")]
+    Fetch {
+        forge: Forge,
+        source: StargazerFetchError,
+    },
+    #[error("NotConfigured: {forge}")]
+    NotConfigured {
+        forge: Forge,
+    },
+}
+
+impl ForgeClientError {
+    pub fn kind(&self) -> ForgeErrorKind {
+        match self {
+            ForgeClientError::Fetch { source, .. } => source.kind(),
+            ForgeClientError::NotConfigured { .. } => ForgeErrorKind::Other,
+        }
+    }
+
+    /// HTTP status reported to the caller, forge failures keep their meaning.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ForgeClientError::Fetch { source, .. } => match source.downcast_ref::<GitHubForgeError>() {
+                Some(GitHubForgeError::ParseResponse { source }) => github_error_status_code(source),
+                _ => match source.kind() {
+                    ForgeErrorKind::NotFound => StatusCode::NOT_FOUND,
+                    ForgeErrorKind::Forbidden => StatusCode::FORBIDDEN,
+                    ForgeErrorKind::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+                    // Rejected credentials are the service's, not the caller's.
+                    _ => StatusCode::BAD_GATEWAY,
+                },
+            },
+            ForgeClientError::NotConfigured { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl ForgeClients {
+    pub fn new(github: GitHubClient, gitlab: GitLabClient, gitea: Option<GiteaClient>) -> Self {
+        let github_fetcher = github
+            .has_credentials()
+            .then(|| Arc::new(github.clone()) as Arc<dyn StargazerFetcher>);
+        Self {
+            github,
+            github_fetcher,
+            gitlab: Arc::new(gitlab),
+            gitea: gitea.map(|gitea| Arc::new(gitea) as Arc<dyn StargazerFetcher>),
+        }
+    }
+
+    /// Syncs of `fetcher`'s forge go through it instead of the client.
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn StargazerFetcher>) -> Self {
+        match fetcher.forge() {
+            Forge::GitHub => self.github_fetcher = Some(fetcher),
+            Forge::GitLab => self.gitlab = fetcher,
+            Forge::Gitea => self.gitea = Some(fetcher),
+        }
+        self
+    }
+
+    pub fn github(&self) -> &GitHubClient {
+        &self.github
+    }
+
+    /// Whether `forge` can be called at all: GitHub needs credentials, Gitea an instance.
+    pub fn is_available(&self, forge: Forge) -> bool {
+        self.fetcher(forge).is_ok()
+    }
+
+    pub fn retry(&self, forge: Forge) -> &RetryConfig {
+        match self.fetcher(forge) {
+            Ok(fetcher) => fetcher.retry(),
+            Err(_) => ForgeClient::retry(&self.github),
+        }
+    }
+
+    /// Page sizes of syncs, shared by every forge.
+    pub fn page_size(&self) -> &PageSizeConfig {
+        self.github.page_size()
+    }
+
+    /// See [`StargazerFetcher::fetch_stargazers`].
+    pub async fn fetch_stargazers(
+        &self,
+        forge: Forge,
+        owner: &str,
+        name: &str,
+        cursor: Option<&str>,
+        page_size: u32,
+        with_profiles: bool,
+    ) -> Result<Option<StargazerPage>, ForgeClientError> {
+        self.fetcher(forge)?
+            .fetch_stargazers(owner, name, cursor, page_size, with_profiles)
+            .await
+            .map_err(|source| ForgeClientError::Fetch { forge, source })
+    }
+
+    /// See [`StargazerFetcher::stargazer_count`].
+    pub async fn stargazer_count(&self, forge: Forge, owner: &str, name: &str) -> Result<Option<u32>, ForgeClientError> {
+        self.fetcher(forge)?
+            .stargazer_count(owner, name)
+            .await
+            .map_err(|source| ForgeClientError::Fetch { forge, source })
+    }
+
+    fn fetcher(&self, forge: Forge) -> Result<&dyn StargazerFetcher, ForgeClientError> {
+        match forge {
+            Forge::GitHub => self.github_fetcher.as_deref(),
+            Forge::GitLab => Some(self.gitlab.as_ref()),
+            Forge::Gitea => self.gitea.as_deref(),
+        }
+            .ok_or(ForgeClientError::NotConfigured { forge })
+    }
+}
@@ -0,0 +1,197 @@
+//! Fork tracking
+//!
+//! A repository synced once with `forks_min_stars` keeps that threshold:
+//! every sync of it then lists its forks on GitHub and syncs those with at
+//! least that many stars right after it, as repositories of their own linked
+//! to it.
+//! Their histories added to the repository's make its combined series, the
+//! `with_forks` metric of the compare endpoint. Forks falling below the
+//! threshold stay tracked but aren't synced anymore. Forks are only tracked
+//! on GitHub, the threshold of a repository of another forge is ignored.
+
+use chrono::NaiveDate;
+use diesel::PgConnection;
+use interfaces_github_stargazers::client::GitHubClient;
+use interfaces_github_stargazers::forge::Forge;
+use interfaces_github_stargazers::graphql::ParseGraphQLResponseError;
+use interfaces_github_stargazers::repository_forks::{
+    fetch_repository_forks, parse_repository_forks_response, FetchRepositoryForksError,
+};
+use thiserror::Error;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::analytics::series::normalize_daily_counts;
+use crate::db::{
+	    repository::queries::{
+	        get_forks, get_repository_by_id, link_fork, set_forks_min_stars,
+	        GetForksError, GetRepositoryByIdError, LinkForkError, SetForksMinStarsError,
+	    },
+	    star::queries::{get_daily_star_count, GetDailyStarCountError},
+	    sync_run::models::SyncTrigger,
+	};
+use crate::endpoints::github::repo_stars::update::index::{sync_repo_stargazers, SyncRepoStargazersError};
+use crate::live::PageSink;
+use crate::sync_limits::SyncLimits;
+use crate::sync_queue::SyncContext;
+
+#[derive(Debug, Error)]
+pub enum SyncForksError {
+    #[error("SetForksMinStars: {source}")]
+    SetForksMinStars {
+        #[from]
+        source: SetForksMinStarsError,
+    },
+    #[error("GetRepositoryById: {source}")]
+    GetRepositoryById {
+        #[from]
+        source: GetRepositoryByIdError,
+    },
+    #[error("RepositoryNotInDatabase: {id}")]
+    RepositoryNotInDatabase {
+        id: Uuid,
+    },
+    #[error("ListForks: {source}")]
+    ListForks {
+        #[from]
+        source: ListForksError,
+    },
+    #[error("LinkFork: {source}")]
+    LinkFork {
+        #[from]
+        source: LinkForkError,
+    },
+}
+
+/// Syncs the forks of the repository above its threshold, first setting it to `forks_min_stars` when given.
+///
+/// Answers with the forks synced as `owner/name`. One failing fork is logged and skipped,
+/// the others are still synced. Each fork's job gets the repository's `limits`.
+pub async fn sync_forks(
+    conn: &mut PgConnection,
+    context: &SyncContext,
+    repo_id: Uuid,
+    forks_min_stars: Option<u32>,
+    limits: SyncLimits,
+) -> Result<Vec<String>, SyncForksError> {
+    let repo = match forks_min_stars {
+        Some(min_stars) => set_forks_min_stars(conn, repo_id, Some(i32::try_from(min_stars).unwrap_or(i32::MAX)))
+			.map_err(|source| SyncForksError::SetForksMinStars{ source })?,
+        None => get_repository_by_id(conn, repo_id)
+			.map_err(|source| SyncForksError::GetRepositoryById{ source })?
+			.ok_or(SyncForksError::RepositoryNotInDatabase{ id: repo_id })?,
+    };
+    let (Some(min_stars), Forge::GitHub) = (repo.forks_min_stars, repo.forge()) else {
+        return Ok(Vec::new());
+    };
+
+    let forks = list_forks(context.forges.github(), &repo.owner, &repo.name, u32::try_from(min_stars).unwrap_or(0))
+		.await
+		.map_err(|source| SyncForksError::ListForks{ source })?;
+
+    let mut synced = Vec::with_capacity(forks.len());
+    for (owner, name) in forks {
+        if context.stop.is_stopped() {
+            break;
+        }
+        match sync_repo_stargazers(conn, context, repo.tenant_id, Forge::GitHub, &owner, &name, None, SyncTrigger::Fork, &mut PageSink::default(), limits).await {
+            Ok(summary) => {
+                link_fork(conn, summary.repository_id, repo.id)
+					.map_err(|source| SyncForksError::LinkFork{ source })?;
+                synced.push(format!("{owner}/{name}"));
+            }
+            Err(SyncRepoStargazersError::Interrupted) => break,
+            Err(error) => warn!(fork_of = %format!("{}/{}", repo.owner, repo.name), %owner, %name, %error, "fork sync failed"),
+        }
+    }
+
+    Ok(synced)
+}
+
+#[derive(Debug, Error)]
+pub enum ListForksError {
+    #[error("FetchRepositoryForks: {source}")]
+    FetchRepositoryForks {
+        #[from]
+        source: FetchRepositoryForksError,
+    },
+    #[error("ParseRepositoryForksResponse: {source}")]
+    ParseRepositoryForksResponse {
+        #[from]
+        source: ParseGraphQLResponseError,
+    },
+}
+
+/// `(owner, name)` of the repository's forks with at least `min_stars` stars, most starred first.
+/// Empty when GitHub doesn't know the repository.
+async fn list_forks(
+    github: &GitHubClient,
+    owner: &str,
+    name: &str,
+    min_stars: u32,
+) -> Result<Vec<(String, String)>, ListForksError> {
+    let mut forks = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let result = fetch_repository_forks(github, owner, name, cursor.as_deref())
+			.await
+			.map_err(|source| ListForksError::FetchRepositoryForks{ source })?;
+
+        let data = parse_repository_forks_response(&result)
+			.map_err(|source| ListForksError::ParseRepositoryForksResponse{ source })?;
+
+        let Some(repository) = data.repository else {
+            return Ok(forks);
+        };
+        let page = repository.forks;
+        // Forks come most starred first, once one is below the threshold the next pages are too.
+        let exhausted = page.nodes.last().is_none_or(|fork| fork.stargazer_count < min_stars);
+
+        forks.extend(
+            page.nodes
+                .into_iter()
+                .filter(|fork| fork.stargazer_count >= min_stars)
+                .map(|fork| (fork.owner.login, fork.name)),
+        );
+
+        if exhausted || !page.page_info.has_next_page {
+            return Ok(forks);
+        }
+        cursor = page.page_info.end_cursor;
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GetCombinedDailyStarCountError {
+    #[error("GetForks: {source}")]
+    GetForks {
+        #[from]
+        source: GetForksError,
+    },
+    #[error("GetDailyStarCount: {source}")]
+    GetDailyStarCount {
+        #[from]
+        source: GetDailyStarCountError,
+    },
+}
+
+/// Daily star counts of the repository and its tracked forks added together.
+pub fn get_combined_daily_star_count(
+    conn: &mut PgConnection,
+    repo_id: Uuid,
+) -> Result<Vec<(NaiveDate, i64)>, GetCombinedDailyStarCountError> {
+    let forks = get_forks(conn, repo_id)
+		.map_err(|source| GetCombinedDailyStarCountError::GetForks{ source })?;
+
+    let mut daily_counts = get_daily_star_count(conn, repo_id)
+		.map_err(|source| GetCombinedDailyStarCountError::GetDailyStarCount{ source })?;
+    for fork in forks {
+        daily_counts.extend(
+            get_daily_star_count(conn, fork.id)
+				.map_err(|source| GetCombinedDailyStarCountError::GetDailyStarCount{ source })?,
+        );
+    }
+
+    Ok(normalize_daily_counts(daily_counts))
+}
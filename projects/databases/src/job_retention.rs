@@ -0,0 +1,92 @@
+//! Sync job retention
+//!
+//! Every sync leaves a job behind, polled while it runs and resumed when it
+//! fails. Jobs finished more than `SYNC_JOB_RETENTION_HOURS` ago (24 by
+//! default) are deleted every `SYNC_JOB_CLEANUP_INTERVAL_MINUTES` (60 by
+//! default), or on `DELETE /github/repo_stars/jobs`. A failed or interrupted
+//! job can only be resumed until then. Batch jobs are kept until the whole
+//! batch is over, and the runs of deleted jobs stay in the sync history.
+//! Both durations can be adjusted at runtime through `PATCH /admin/config`.
+
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::{
+	    sync_job::queries::{purge_finished_sync_jobs, PurgeFinishedSyncJobsError},
+	    PgPool,
+	};
+use crate::runtime_config::RuntimeConfig;
+use crate::sync_queue::StopSignal;
+
+/// How long finished sync jobs are kept, and how often older ones are deleted.
+#[derive(Debug, Clone, Copy)]
+pub struct SyncJobRetention {
+    pub max_age: Duration,
+    pub interval: Duration,
+}
+
+impl Default for SyncJobRetention {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(24 * 3600),
+            interval: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Jobs finished before the returned time are past `max_age` at `now`.
+pub fn purge_cutoff(now: NaiveDateTime, max_age: Duration) -> NaiveDateTime {
+    TimeDelta::from_std(max_age)
+        .ok()
+        .and_then(|age| now.checked_sub_signed(age))
+        .unwrap_or(NaiveDateTime::MIN)
+}
+
+#[derive(Debug, Error)]
+pub enum PurgeOldSyncJobsError {
+    #[error("GetConnectionFromPool: {source}")]
+    GetConnectionFromPool {
+        #[from]
+        source: r2d2::Error,
+    },
+    #[error("PurgeFinishedSyncJobs: {source}")]
+    PurgeFinishedSyncJobs {
+        #[from]
+        source: PurgeFinishedSyncJobsError,
+    },
+}
+
+/// Deletes the sync jobs finished before `before`, of `tenant` only when set, and returns how many.
+pub fn purge_old_sync_jobs(pool: &PgPool, before: NaiveDateTime, tenant: Option<Uuid>) -> Result<usize, PurgeOldSyncJobsError> {
+    let mut conn = pool.get()
+        .map_err(|source| PurgeOldSyncJobsError::GetConnectionFromPool { source })?;
+
+    purge_finished_sync_jobs(&mut conn, before, tenant)
+        .map_err(|source| PurgeOldSyncJobsError::PurgeFinishedSyncJobs { source })
+}
+
+/// Deletes old finished sync jobs every `interval` of the current retention, starting right away,
+/// until `stop` fires. Adjusting the configuration runs it again right away, with the new retention.
+pub async fn run_job_cleanup(pool: PgPool, config: RuntimeConfig, stop: StopSignal) {
+    let mut changes = config.changed();
+    loop {
+        let retention = config.sync_job_retention();
+        let before = purge_cutoff(Utc::now().naive_utc(), retention.max_age);
+        let pool = pool.clone();
+        match tokio::task::spawn_blocking(move || purge_old_sync_jobs(&pool, before, None)).await {
+            Ok(Ok(jobs)) => info!(%before, jobs, "purged finished sync jobs"),
+            Ok(Err(error)) => warn!(%error, "sync job cleanup failed"),
+            Err(error) => warn!(%error, "sync job cleanup panicked"),
+        }
+
+        tokio::select! {
+            _ = stop.stopped() => return,
+            _ = tokio::time::sleep(retention.interval) => {}
+            changed = changes.changed() => if changed.is_err() { return },
+        }
+    }
+}
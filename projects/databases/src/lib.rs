@@ -1,8 +1,87 @@
 //! GitHub repository star tracking service
 //! 
-//! - REST API endpoints in `endpoints/`
-//! - PostgreSQL models and queries in `db/`
-//! - Requires GITHUB_TOKEN env var for API access
+//! - REST API endpoints in `endpoints/`, assembled into the service's routes
+//!   in `router`
+//! - PostgreSQL models and queries in `db/`, and in `storage` the few the CLI
+//!   needs, also backed by a SQLite file with the `sqlite` feature
+//! - Star series analysis in `analytics/`, SVG charts in `chart`, interactive
+//!   Vega-Lite ones in `vega`, PNG social cards in `social_card` and
+//!   comparison charts in `multi_repo_chart` with the `charting` feature (on by
+//!   default), comparison charts and cards kept for their next request in `chart_cache`,
+//!   rendered by handlers off the async executor through `render_pool`
+//! - Alert delivery in `notifier`, over `webhook` or `email`, webhooks fired
+//!   by syncs retried from the `outbox`
+//! - Weekly digests of every tenant's repositories in `digest`, stored as reports
+//! - CSV and JSON star history rows of the `star-tracker` CLI in `export`
+//! - Optional hashing of stargazer logins in `privacy`, flagging of suspected
+//!   bot stargazers in `bots`
+//! - Compaction of old star rows into daily counts in `retention`
+//! - Deletion of finished sync jobs past their retention in `job_retention`
+//! - Settings of the running service, some adjustable without a restart, in
+//!   `runtime_config`
+//! - Scheduled star count snapshots of repositories too large to enumerate in `snapshots`,
+//!   of the views and clones of the repositories pushed to in `traffic`
+//! - Scheduled tracking of the commit authors of repositories in contributors
+//!   mode in `contributors`
+//! - Syncs of a repository's popular forks along with it in `forks`, of its
+//!   releases in `releases`, of its issue and pull request activity in
+//!   `activity`
+//! - Repositories declared in `REPOS_FILE`, registered at startup, in `watch_list`
+//! - Clients of GitHub, GitLab and Gitea, each repository being synced from
+//!   its own forge, in `forges`
+//! - Checks of caller-supplied names and webhook URLs in `validation`
+//! - Tenants resolved from API keys in `endpoints::tenant`, operators from
+//!   `ADMIN_TOKEN` in `endpoints::admin`
+//! - Syncs run by a bounded worker pool in `sync_queue`, their progress
+//!   pushed to WebSocket clients and their end notified to PostgreSQL
+//!   listeners through `live`, cut short by the limits of
+//!   `sync_limits`, one sync of a repository at a time across instances
+//!   through `sync_lock`
+//! - Typed REST client in `client`, with the `client` feature
+//! - Harness of the integration tests in `testing`, with the `test-utils`
+//!   feature
+//! - Requires GITHUB_TOKEN (or comma-separated GITHUB_TOKENS, or GitHub App
+//!   credentials) env vars for API access
 
+pub mod activity;
+pub mod analytics;
+pub mod bots;
+pub mod chart;
+pub mod chart_cache;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod contributors;
 pub mod endpoints;
-pub mod db;
\ No newline at end of file
+pub mod db;
+pub mod digest;
+pub mod email;
+pub mod env;
+pub mod export;
+pub mod forges;
+pub mod forks;
+pub mod job_retention;
+pub mod live;
+#[cfg(feature = "charting")]
+pub mod multi_repo_chart;
+pub mod notifier;
+pub mod outbox;
+pub mod privacy;
+pub mod releases;
+pub mod render_pool;
+pub mod retention;
+pub mod router;
+pub mod runtime_config;
+pub mod snapshots;
+#[cfg(feature = "charting")]
+pub mod social_card;
+pub mod storage;
+pub mod sync_limits;
+pub mod sync_lock;
+pub mod sync_queue;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+pub mod traffic;
+pub mod validation;
+pub mod vega;
+pub mod watch_list;
+pub mod webhook;
\ No newline at end of file
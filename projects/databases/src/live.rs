@@ -0,0 +1,229 @@
+//! Live progress of running syncs
+//!
+//! Syncs publish an event per stored page and one when they end, the live
+//! WebSocket endpoint forwards the events of its repository. Nothing is
+//! buffered for later: subscribers only see what happens while connected.
+//!
+//! The caller starting a sync can also have its pages streamed back through a
+//! [`PageSink`], which drops nothing and holds the sync back for a slow reader.
+//!
+//! Other services connected to the same database hear of every job ending
+//! through a PostgreSQL `NOTIFY` on the channel named by `SYNC_NOTIFY_CHANNEL`,
+//! with a [`SyncNotification`] as JSON payload. Only the connections
+//! listening when the job ends get it.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use chrono::NaiveDateTime;
+use diesel::pg::PgConnection;
+use diesel::sql_types::Text;
+use diesel::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{broadcast, mpsc};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::sync_queue::JobProgress;
+
+/// Events kept for subscribers slower than the syncs, older ones are dropped.
+const CAPACITY: usize = 256;
+/// Lines a [`PageSink`] holds for a reader behind, the sync waits past it.
+const PAGE_SINK_CAPACITY: usize = 4;
+/// Wait for a reader before a [`PageSink`] gives up on it.
+const PAGE_SINK_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncEvent {
+    /// Tenant tracking the repository, only its subscribers get the event.
+    #[serde(skip)]
+    pub tenant_id: Uuid,
+    pub owner: String,
+    pub name: String,
+    pub job_id: Uuid,
+    #[serde(flatten)]
+    pub kind: SyncEventKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SyncEventKind {
+    /// A new or resumed job started fetching pages.
+    Started,
+    /// A page of stargazers was stored.
+    Page {
+        page: u32,
+        stargazers: Vec<LiveStargazer>,
+        /// Stars fetched by the job so far, against the repository's count on GitHub.
+        #[serde(flatten)]
+        progress: JobProgress,
+        /// Stars stored for the repository, over every sync.
+        total_stars: i64,
+    },
+    Completed {
+        pages: u32,
+        stars: u64,
+    },
+    Failed {
+        error: String,
+    },
+    /// Stopped by a shutdown, the job can be resumed.
+    Interrupted,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LiveStargazer {
+    pub login: String,
+    pub starred_at: NaiveDateTime,
+}
+
+impl SyncEvent {
+    pub fn is_for(&self, tenant_id: Uuid, owner: &str, name: &str) -> bool {
+        // GitHub names are case-insensitive, the URL may not match the stored case.
+        self.tenant_id == tenant_id && self.owner.eq_ignore_ascii_case(owner) && self.name.eq_ignore_ascii_case(name)
+    }
+}
+
+/// Payload of the `NOTIFY` sent when a sync job ends.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SyncNotification {
+    pub repository_id: Uuid,
+    pub tenant_id: Uuid,
+    pub owner: String,
+    pub name: String,
+    pub job_id: Uuid,
+    /// `completed`, `partially_completed`, `failed` or `interrupted`.
+    pub status: String,
+    /// Growth of the stored star count over the run, unset when it couldn't be measured.
+    pub stars_added: Option<i64>,
+}
+
+#[derive(Debug, Error)]
+pub enum NotifySyncEndedError {
+    #[error("SerializePayload: {source}")]
+    SerializePayload {
+        #[from]
+        source: serde_json::Error,
+    },
+    #[error("PgNotify: {source}")]
+    PgNotify {
+        #[from]
+        source: diesel::result::Error,
+    },
+}
+
+/// Whether `channel` can be listened to as it is: a lowercase identifier, at most 63 bytes as
+/// PostgreSQL truncates longer ones.
+pub fn is_notify_channel(channel: &str) -> bool {
+    let mut chars = channel.chars();
+    let starts_well = chars.next().is_some_and(|first| first.is_ascii_lowercase() || first == '_');
+    starts_well && channel.len() <= 63 && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Fan-out of sync events to every live connection. Cloning is cheap, clones share subscribers.
+#[derive(Clone)]
+pub struct LiveUpdates {
+    sender: broadcast::Sender<Arc<SyncEvent>>,
+    /// Channel told of every job ending, none when unset.
+    notify_channel: Option<Arc<str>>,
+}
+
+impl Default for LiveUpdates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveUpdates {
+    pub fn new() -> Self {
+        Self { sender: broadcast::Sender::new(CAPACITY), notify_channel: None }
+    }
+
+    /// Also tells the listeners of `channel` when a job ends, see [`is_notify_channel`].
+    pub fn with_notify_channel(mut self, channel: &str) -> Self {
+        self.notify_channel = Some(channel.into());
+        self
+    }
+
+    pub fn notify_channel(&self) -> Option<&str> {
+        self.notify_channel.as_deref()
+    }
+
+    /// Whether anyone listens, lets syncs skip building events nobody reads.
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    pub fn publish(&self, event: SyncEvent) {
+        // Fails only without subscribers, which is the common case.
+        let _ = self.sender.send(Arc::new(event));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<SyncEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Sends `notification` to the listeners of the notify channel, nothing without one. Inside a
+    /// transaction, it is only delivered once committed.
+    pub fn notify_sync_ended(&self, conn: &mut PgConnection, notification: &SyncNotification) -> Result<(), NotifySyncEndedError> {
+        let Some(channel) = &self.notify_channel else {
+            return Ok(());
+        };
+        let payload = serde_json::to_string(notification)?;
+        diesel::sql_query("SELECT pg_notify($1, $2)")
+            .bind::<Text, _>(&**channel)
+            .bind::<Text, _>(payload)
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+/// NDJSON lines of one sync, read by the caller that started it.
+///
+/// Unlike [`LiveUpdates`], nothing is dropped: sending waits while the reader is
+/// [`PAGE_SINK_CAPACITY`] lines behind, pacing the sync to the reader. A reader gone, or
+/// stalled past [`PAGE_SINK_STALL_TIMEOUT`], only ends the stream, the sync goes on without
+/// it. The default sink has no reader.
+#[derive(Debug, Clone, Default)]
+pub struct PageSink {
+    sender: Option<mpsc::Sender<Bytes>>,
+}
+
+impl PageSink {
+    /// A sink and its reader, which ends once every clone of the sink is dropped.
+    pub fn channel() -> (Self, mpsc::Receiver<Bytes>) {
+        let (sender, receiver) = mpsc::channel(PAGE_SINK_CAPACITY);
+        (Self { sender: Some(sender) }, receiver)
+    }
+
+    /// Whether a reader is still there, lets syncs skip building lines nobody reads.
+    pub fn is_open(&self) -> bool {
+        self.sender.as_ref().is_some_and(|sender| !sender.is_closed())
+    }
+
+    /// Sends `line` as JSON on a line of its own, once the reader has room for it.
+    pub async fn send<T: Serialize>(&mut self, line: &T) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        let mut encoded = match serde_json::to_vec(line) {
+            Ok(encoded) => encoded,
+            Err(error) => {
+                warn!(%error, "streamed line not encoded");
+                return;
+            }
+        };
+        encoded.push(b'\n');
+
+        match sender.send_timeout(Bytes::from(encoded), PAGE_SINK_STALL_TIMEOUT).await {
+            Ok(()) => {}
+            Err(mpsc::error::SendTimeoutError::Timeout(_)) => {
+                warn!(timeout = ?PAGE_SINK_STALL_TIMEOUT, "stream reader stalled, no longer streaming");
+                self.sender = None;
+            }
+            Err(mpsc::error::SendTimeoutError::Closed(_)) => self.sender = None,
+        }
+    }
+}
@@ -0,0 +1,601 @@
+//! Comparison charts
+//!
+//! The aligned series of `analytics::compare` drawn together as an SVG
+//! chart: lines, filled or stacked areas, or grouped or stacked bars, with
+//! an optional second metric drawn as dashed lines against a right axis, e.g.
+//! the running total (position) along with the daily stars (speed), or the
+//! daily stars along with the views to weigh one against the other. Issues
+//! and pull requests are drawn by the number opened each day, contributors by
+//! their running total, e.g. against the stars. Any
+//! series can get its own color, line width and dash style, e.g. a brand color.
+//! A watermark and a "generated at … from … data points" footer make
+//! exported charts self-describing, release markers tie bumps to versions.
+//! Calendar-aligned comparisons get a date axis, its labels shortened to the
+//! month or year over long spans, relative ones count the days, months or
+//! years since each repository's first star. Drawn with plotters' SVG
+//! backend, text measured with the font embedded for social cards.
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use plotters::{chart::SeriesAnno, coord::{types::RangedCoordf64, Shift}, prelude::*, style::text_anchor::{HPos, Pos, VPos}};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::analytics::compare::{Alignment, CompareMetric, ComparedSeries, Comparison};
+use crate::chart::{date_label_format, AxisOptions, ChartTheme, HexColor};
+use crate::social_card::{color, embedded_font_registered};
+
+pub const DEFAULT_CHART_WIDTH: u32 = 1000;
+pub const DEFAULT_CHART_HEIGHT: u32 = 500;
+/// Bars drawn per series at most, days are grouped into wider bars past it.
+const MAX_BARS: usize = 60;
+/// Longest watermark text.
+pub const MAX_WATERMARK_LENGTH: usize = 100;
+/// Width of lines without a style giving theirs.
+pub const DEFAULT_LINE_WIDTH: u32 = 2;
+pub const MAX_LINE_WIDTH: u32 = 10;
+/// Relative axes longer than this many days count months instead.
+const MAX_DAYS_AXIS: usize = 180;
+/// Relative axes longer than this many days count years instead.
+const MAX_MONTHS_AXIS: usize = 1096;
+/// Pixels between two release labels at least, closer ones are left unlabeled.
+const RELEASE_LABEL_SPACING: i32 = 14;
+
+/// Backend errors carry no lifetime, any one stands for the buffer's.
+type DrawError = DrawingAreaErrorKind<<SVGBackend<'static> as DrawingBackend>::ErrorType>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartType {
+    #[default]
+    Line,
+    /// Lines filled down to the axis, overlapping.
+    Area,
+    /// Areas piled on each other, the top one showing the total.
+    StackedArea,
+    /// Bars of every repository side by side.
+    Bar,
+    /// Bars of every repository piled on each other.
+    StackedBar,
+}
+
+impl ChartType {
+    fn is_stacked(self) -> bool {
+        matches!(self, ChartType::StackedArea | ChartType::StackedBar)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DashStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl DashStyle {
+    /// Dash and gap lengths of a line `width` pixels wide, `None` when solid.
+    fn pattern(self, width: u32) -> Option<(u32, u32)> {
+        match self {
+            DashStyle::Solid => None,
+            DashStyle::Dashed => Some((3 * width, 2 * width)),
+            DashStyle::Dotted => Some((width, 2 * width)),
+        }
+    }
+}
+
+/// Look of one series, the theme's where unset. The width and dash style apply to lines,
+/// those of line and area charts; the secondary metric's stay dashed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeriesStyle {
+    /// `owner/name` of the series, in any case.
+    pub repository: String,
+    pub color: Option<HexColor>,
+    /// In pixels, [`DEFAULT_LINE_WIDTH`] when unset.
+    pub line_width: Option<u32>,
+    pub dash: DashStyle,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    /// Large, across the middle of the chart.
+    Center,
+}
+
+/// Text written over the chart, e.g. the company or the source of the data.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct Watermark {
+    /// At most [`MAX_WATERMARK_LENGTH`] characters.
+    pub text: String,
+    #[serde(default)]
+    pub position: WatermarkPosition,
+    /// From 0 (invisible) to 1 (opaque).
+    #[serde(default = "default_watermark_opacity")]
+    pub opacity: f64,
+}
+
+fn default_watermark_opacity() -> f64 {
+    0.5
+}
+
+/// Release of a compared repository, drawn as a dashed vertical line labeled with its tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseMarker {
+    /// `owner/name` of the series, in any case.
+    pub repository: String,
+    pub tag_name: String,
+    pub day: NaiveDate,
+}
+
+/// How a comparison is drawn.
+#[derive(Debug, Clone)]
+pub struct ChartConfig {
+    pub chart_type: ChartType,
+    /// Series drawn, it must be among the comparison's metrics.
+    pub metric: CompareMetric,
+    /// Drawn as dashed lines against a right axis, it must be among the comparison's metrics too.
+    pub secondary_metric: Option<CompareMetric>,
+    pub theme: ChartTheme,
+    pub width: u32,
+    pub height: u32,
+    /// Written above the chart when set.
+    pub title: Option<String>,
+    pub axes: AxisOptions,
+    /// Overrides of the look of some series, matched by repository.
+    pub series_styles: Vec<SeriesStyle>,
+    pub watermark: Option<Watermark>,
+    /// Written in a bottom corner along with the number of values drawn when set.
+    pub generated_at: Option<DateTime<Utc>>,
+    /// Releases marked over the series, those outside of the chart or of repositories not compared
+    /// left out.
+    pub releases: Vec<ReleaseMarker>,
+}
+
+impl Default for ChartConfig {
+    fn default() -> Self {
+        Self {
+            chart_type: ChartType::default(),
+            metric: CompareMetric::Cumulative,
+            secondary_metric: None,
+            theme: ChartTheme::light(),
+            width: DEFAULT_CHART_WIDTH,
+            height: DEFAULT_CHART_HEIGHT,
+            title: None,
+            axes: AxisOptions { max_x_ticks: 6, max_y_ticks: 6, rotate_x_labels: false },
+            series_styles: Vec::new(),
+            watermark: None,
+            generated_at: None,
+            releases: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GenerateMultiRepoChartError {
+    #[error("InvalidEmbeddedFont")]
+    InvalidEmbeddedFont,
+    #[error("MetricNotCompared: {metric:?}")]
+    MetricNotCompared {
+        metric: CompareMetric,
+    },
+    #[error("Draw: {source}")]
+    Draw {
+        source: DrawError,
+    },
+}
+
+/// A drawn series, one value per day from index 0 of the comparison.
+struct Plotted<'a> {
+    repository: &'a str,
+    /// Days before the series' own start, padded with 0 in `values` and left undrawn unless stacked.
+    offset: usize,
+    values: Vec<f64>,
+    /// Values of the secondary metric.
+    secondary: Option<Vec<f64>>,
+    style: Option<&'a SeriesStyle>,
+}
+
+/// SVG chart of `config.metric` for every series of `comparison`.
+///
+/// Stacked charts stop at the shortest series, as relative series end on different days.
+pub fn generate_multi_repo_chart(comparison: &Comparison, config: &ChartConfig) -> Result<String, GenerateMultiRepoChartError> {
+    if !embedded_font_registered() {
+        return Err(GenerateMultiRepoChartError::InvalidEmbeddedFont);
+    }
+
+    // Calendar-aligned series start on different days when the fill policy skips the days
+    // before their data, they're drawn from the earliest.
+    let chart_start = comparison.series.iter().filter_map(|series| series.start).min();
+    let mut plotted = Vec::with_capacity(comparison.series.len());
+    for series in &comparison.series {
+        let offset = match (comparison.alignment, chart_start, series.start) {
+            (Alignment::Calendar, Some(chart_start), Some(start)) => usize::try_from((start - chart_start).num_days()).unwrap_or(0),
+            _ => 0,
+        };
+        let padded = |values: Vec<f64>| std::iter::repeat_n(0.0, offset).chain(values).collect::<Vec<f64>>();
+        let values = metric_values(series, config.metric)
+            .ok_or(GenerateMultiRepoChartError::MetricNotCompared { metric: config.metric })?;
+        let secondary = match config.secondary_metric {
+            Some(metric) => Some(metric_values(series, metric).ok_or(GenerateMultiRepoChartError::MetricNotCompared { metric })?),
+            None => None,
+        };
+        let style = config.series_styles.iter().find(|style| style.repository.eq_ignore_ascii_case(&series.repository));
+        plotted.push(Plotted { repository: &series.repository, offset, values: padded(values), secondary: secondary.map(padded), style });
+    }
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (config.width, config.height)).into_drawing_area();
+        draw_chart(&root, comparison, &plotted, config)
+            .map_err(|source| GenerateMultiRepoChartError::Draw { source })?;
+        draw_footer(&root, &plotted, config)
+            .map_err(|source| GenerateMultiRepoChartError::Draw { source })?;
+        root.present()
+            .map_err(|source| GenerateMultiRepoChartError::Draw { source })?;
+    }
+    Ok(svg)
+}
+
+fn metric_values(series: &ComparedSeries, metric: CompareMetric) -> Option<Vec<f64>> {
+    let as_f64 = |values: &Vec<i64>| values.iter().map(|&value| value as f64).collect();
+    match metric {
+        CompareMetric::Daily => series.daily.as_ref().map(as_f64),
+        CompareMetric::Cumulative => series.cumulative.as_ref().map(as_f64),
+        CompareMetric::RollingAverage => series.rolling_average.clone(),
+        CompareMetric::WithForks => series.with_forks.as_ref().map(as_f64),
+        CompareMetric::Normalized => series.normalized.clone(),
+        CompareMetric::Velocity => series.velocity.clone(),
+        CompareMetric::Acceleration => series.acceleration.clone(),
+        CompareMetric::Views => series.views.as_ref().map(as_f64),
+        CompareMetric::Clones => series.clones.as_ref().map(as_f64),
+        CompareMetric::Issues => series.issues.as_ref().map(|issues| as_f64(&issues.opened)),
+        CompareMetric::Prs => series.prs.as_ref().map(|prs| as_f64(&prs.opened)),
+        CompareMetric::Contributors => series.contributors.as_ref().map(as_f64),
+    }
+}
+
+fn draw_chart(
+    root: &DrawingArea<SVGBackend, Shift>,
+    comparison: &Comparison,
+    plotted: &[Plotted],
+    config: &ChartConfig,
+) -> Result<(), DrawError> {
+    let theme = &config.theme;
+    let axes = config.axes.clamped();
+    let text = color(&theme.text);
+    root.fill(&color(&theme.background))?;
+
+    let days = if config.chart_type.is_stacked() {
+        plotted.iter().map(|series| series.values.len()).min().unwrap_or(0)
+    } else {
+        plotted.iter().map(|series| series.values.len()).max().unwrap_or(0)
+    };
+    if days == 0 {
+        let (width, height) = root.dim_in_pixel();
+        let style = ("sans-serif", 16).into_font().color(&text).pos(Pos::new(HPos::Center, VPos::Center));
+        root.draw_text("No stars yet", &style, (width as i32 / 2, height as i32 / 2))?;
+        return Ok(());
+    }
+
+    let bars = matches!(config.chart_type, ChartType::Bar | ChartType::StackedBar);
+    // Days per bar, and the values drawn: one per bar or per day.
+    let width = if bars { days.div_ceil(MAX_BARS) } else { 1 };
+    let values: Vec<Vec<f64>> = plotted
+        .iter()
+        .map(|series| {
+            let values = &series.values[..series.values.len().min(days)];
+            if bars { bucket(values, width, config.metric) } else { values.to_vec() }
+        })
+        .collect();
+    let stacked = stack(&values, config.chart_type.is_stacked());
+
+    let max_value = stacked.iter().flatten().fold(0.0_f64, |max, &value| max.max(value)).max(1.0) * 1.05;
+    // Below 0 only for series that can go down, e.g. the acceleration.
+    let min_value = stacked.iter().flatten().fold(0.0_f64, |min, &value| min.min(value)) * 1.05;
+    let x_days = if bars { values.iter().map(Vec::len).max().unwrap_or(0) * width } else { (days - 1).max(1) };
+
+    // Relative axes are drawn in the unit counted, so plotters picks round ticks of it.
+    let start = comparison.series.iter().filter_map(|series| series.start).min();
+    let (unit, x_desc) = match comparison.alignment {
+        Alignment::Calendar => (1.0, None),
+        Alignment::Relative => relative_unit(x_days),
+    };
+    let x = |day: usize| day as f64 / unit;
+    let x_end = x(x_days);
+    let y_label = |value: &f64| value_label(config.metric, *value);
+
+    let secondary_values = || plotted.iter().filter_map(|series| series.secondary.as_ref()).flat_map(|values| values.iter().take(days));
+    let secondary_max = secondary_values().fold(0.0_f64, |max, &value| max.max(value)).max(1.0) * 1.05;
+    let secondary_min = secondary_values().fold(0.0_f64, |min, &value| min.min(value)) * 1.05;
+    let secondary_label = |value: &f64| config.secondary_metric.map_or_else(String::new, |metric| value_label(metric, *value));
+
+    let mut builder = ChartBuilder::on(root);
+    builder
+        .margin(20)
+        .x_label_area_size(if axes.rotate_x_labels { 90 } else { 50 })
+        .y_label_area_size(70)
+        .right_y_label_area_size(if config.secondary_metric.is_some() { 70 } else { 0 });
+    if let Some(title) = &config.title {
+        builder.caption(title, ("sans-serif", 20).into_font().color(&text));
+    }
+    // The secondary coordinates go unused without a secondary metric.
+    let mut chart = builder
+        .build_cartesian_2d(0.0..x_end, min_value..max_value)?
+        .set_secondary_coord(0.0..x_end, secondary_min..secondary_max);
+
+    // Dates are formatted for the ticks plotters picks, at most `max_x_ticks` round numbers of days.
+    let x_ticks = chart.as_coord_spec().x_spec().key_points(axes.max_x_ticks);
+    let tick_step = match x_ticks.as_slice() {
+        [first, second, ..] => second - first,
+        _ => x_end,
+    };
+    let date_format = date_label_format(tick_step as i64);
+    let x_label = |position: &f64| match (comparison.alignment, start) {
+        (Alignment::Calendar, Some(start)) => start
+            .checked_add_days(Days::new(position.round().max(0.0) as u64))
+            .map_or_else(String::new, |date: NaiveDate| date.format(date_format).to_string()),
+        _ => format!("{}", (position * 10.0).round() / 10.0),
+    };
+
+    let label_style = ("sans-serif", 13).into_font().color(&text);
+    let mut mesh = chart.configure_mesh();
+    mesh.bold_line_style(color(&theme.grid))
+        .light_line_style(TRANSPARENT)
+        .axis_style(color(&theme.grid))
+        .label_style(label_style.clone())
+        .axis_desc_style(label_style.clone())
+        .x_labels(axes.max_x_ticks)
+        .y_labels(axes.max_y_ticks)
+        .x_label_formatter(&x_label)
+        .y_label_formatter(&y_label)
+        .y_desc(metric_description(config.metric));
+    if let Some(x_desc) = x_desc {
+        mesh.x_desc(x_desc);
+    }
+    // plotters centers rotated labels on their tick, half of them over the plot: its own are
+    // left transparent, after `label_style` which sets both axes, and drawn below instead.
+    if axes.rotate_x_labels {
+        mesh.x_label_style(label_style.clone().color(&TRANSPARENT));
+    }
+    mesh.draw()?;
+    if axes.rotate_x_labels {
+        let style = label_style.clone().transform(FontTransform::Rotate270).pos(Pos::new(HPos::Right, VPos::Center));
+        let (_, plot_rows) = chart.plotting_area().get_pixel_range();
+        for position in x_ticks {
+            let (column, _) = chart.backend_coord(&(position, 0.0));
+            root.draw_text(&x_label(&position), &style, (column, plot_rows.end + 8))?;
+        }
+    }
+
+    if let Some(metric) = config.secondary_metric {
+        chart
+            .configure_secondary_axes()
+            .axis_style(color(&theme.grid))
+            .label_style(label_style.clone())
+            .axis_desc_style(label_style.clone())
+            .y_labels(axes.max_y_ticks)
+            .y_label_formatter(&secondary_label)
+            .y_desc(metric_description(metric))
+            .draw()?;
+    }
+
+    let count = plotted.len().max(1) as f64;
+    let color_of = |index: usize| plotted[index].style.and_then(|style| style.color.as_ref()).map_or_else(|| series_color(theme, index), color);
+    for (index, series) in plotted.iter().enumerate() {
+        let series_color = color_of(index);
+        let line_width = series.style.and_then(|style| style.line_width).unwrap_or(DEFAULT_LINE_WIDTH);
+        let dash = series.style.map_or(DashStyle::Solid, |style| style.dash);
+        let upper = &stacked[index + 1];
+        let lower = &stacked[index];
+        let first_drawn = if config.chart_type.is_stacked() { 0 } else { series.offset };
+        let points = || upper.iter().enumerate().skip(first_drawn).map(|(day, &value)| (x(day), value));
+        let drawn = match config.chart_type {
+            ChartType::Line => draw_line(&mut chart, points(), series_color.stroke_width(line_width), dash.pattern(line_width))?,
+            // The area unbordered, its line drawn over it so it can be dashed.
+            ChartType::Area => {
+                chart.draw_series(AreaSeries::new(points(), 0.0, series_color.mix(0.2)))?;
+                draw_line(&mut chart, points(), series_color.stroke_width(line_width), dash.pattern(line_width))?
+            }
+            ChartType::StackedArea => chart.draw_series(std::iter::once(Polygon::new(
+                points()
+                    .chain(lower.iter().enumerate().rev().map(|(day, &value)| (x(day), value)))
+                    .collect::<Vec<_>>(),
+                series_color.mix(0.85).filled(),
+            )))?,
+            // Empty bars are left out rather than drawn flat.
+            ChartType::Bar => chart.draw_series(upper.iter().enumerate().filter(|&(_, &value)| value != 0.0).map(|(bar, &value)| {
+                let left = x(bar * width) + x(width) * (0.1 + 0.8 * index as f64 / count);
+                let right = left + x(width) * 0.8 / count;
+                Rectangle::new([(left, 0.0), (right, value)], series_color.filled())
+            }))?,
+            ChartType::StackedBar => chart.draw_series(upper.iter().zip(lower).enumerate().filter(|&(_, (top, bottom))| top > bottom).map(|(bar, (&top, &bottom))| {
+                let left = x(bar * width) + x(width) * 0.1;
+                Rectangle::new([(left, bottom), (left + x(width) * 0.8, top)], series_color.filled())
+            }))?,
+        };
+        drawn
+            .label(series.repository)
+            .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], series_color.filled()));
+
+        if let (Some(metric), Some(secondary)) = (config.secondary_metric, &series.secondary) {
+            chart
+                .draw_secondary_series(DashedLineSeries::new(
+                    secondary.iter().take(days).enumerate().skip(series.offset).map(|(day, &value)| (x(day), value)),
+                    3 * line_width,
+                    2 * line_width,
+                    series_color.stroke_width(line_width),
+                ))?
+                .label(format!("{} ({})", series.repository, metric_description(metric).to_lowercase()))
+                .legend(move |(x, y)| PathElement::new([(x, y), (x + 10, y)], series_color.stroke_width(line_width)));
+        }
+    }
+
+    // Tags are written down from the top of their line, skipped where they'd run into the previous one.
+    let (_, plot_rows) = chart.plotting_area().get_pixel_range();
+    let mut last_label: Option<i32> = None;
+    for (day, index, tag_name) in release_positions(comparison, &config.releases, x_days) {
+        let marker_color = color_of(index);
+        chart.draw_series(DashedLineSeries::new([(x(day), min_value), (x(day), max_value)], 4, 3, marker_color.mix(0.7).stroke_width(1)))?;
+        let (column, _) = chart.backend_coord(&(x(day), 0.0));
+        if last_label.is_none_or(|last| column - last >= RELEASE_LABEL_SPACING) {
+            let style = ("sans-serif", 11).into_font().color(&marker_color).transform(FontTransform::Rotate270).pos(Pos::new(HPos::Right, VPos::Top));
+            root.draw_text(tag_name, &style, (column + 2, plot_rows.start + 4))?;
+            last_label = Some(column);
+        }
+    }
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(color(&theme.background).mix(0.8))
+        .border_style(color(&theme.grid))
+        .label_font(label_style)
+        .draw()?;
+    Ok(())
+}
+
+/// The watermark and the generation note, in opposite bottom corners unless the watermark is elsewhere.
+fn draw_footer(root: &DrawingArea<SVGBackend, Shift>, plotted: &[Plotted], config: &ChartConfig) -> Result<(), DrawError> {
+    const PADDING: i32 = 6;
+    let text = color(&config.theme.text);
+    let (width, height) = root.dim_in_pixel();
+    let (right, bottom) = (width as i32 - PADDING, height as i32 - PADDING);
+
+    let mut note_on_right = false;
+    if let Some(watermark) = &config.watermark {
+        let (size, h_pos, v_pos, at) = match watermark.position {
+            WatermarkPosition::TopLeft => (13, HPos::Left, VPos::Top, (PADDING, PADDING)),
+            WatermarkPosition::TopRight => (13, HPos::Right, VPos::Top, (right, PADDING)),
+            WatermarkPosition::BottomLeft => (13, HPos::Left, VPos::Bottom, (PADDING, bottom)),
+            WatermarkPosition::BottomRight => (13, HPos::Right, VPos::Bottom, (right, bottom)),
+            WatermarkPosition::Center => (40, HPos::Center, VPos::Center, (width as i32 / 2, height as i32 / 2)),
+        };
+        note_on_right = watermark.position == WatermarkPosition::BottomLeft;
+        let style = ("sans-serif", size).into_font().color(&text.mix(watermark.opacity.clamp(0.0, 1.0))).pos(Pos::new(h_pos, v_pos));
+        root.draw_text(&watermark.text, &style, at)?;
+    }
+
+    if let Some(generated_at) = config.generated_at {
+        let points: usize = plotted
+            .iter()
+            .map(|series| series.values.len() + series.secondary.as_ref().map_or(0, Vec::len) - series.offset * (1 + usize::from(series.secondary.is_some())))
+            .sum();
+        let note = format!("Generated at {} from {points} data points", generated_at.format("%Y-%m-%d %H:%M UTC"));
+        let (h_pos, x) = if note_on_right { (HPos::Right, right) } else { (HPos::Left, PADDING) };
+        let style = ("sans-serif", 11).into_font().color(&text.mix(0.6)).pos(Pos::new(h_pos, VPos::Bottom));
+        root.draw_text(&note, &style, (x, bottom))?;
+    }
+    Ok(())
+}
+
+/// Solid line through `points`, or dashed with a `(dash, gap)` pattern.
+fn draw_line<'c, 'a, 'b>(
+    chart: &'c mut ChartContext<'a, SVGBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    points: impl Iterator<Item = (f64, f64)> + Clone,
+    style: ShapeStyle,
+    pattern: Option<(u32, u32)>,
+) -> Result<&'c mut SeriesAnno<'a, SVGBackend<'b>>, DrawError> {
+    match pattern {
+        None => chart.draw_series(LineSeries::new(points, style)),
+        Some((dash, gap)) => chart.draw_series(DashedLineSeries::new(points, dash, gap, style)),
+    }
+}
+
+/// Day index of each of `releases` drawn on an axis of `x_days`, the index of its series and its tag,
+/// by day. Calendar-aligned series share the earliest start, relative ones start on their own.
+fn release_positions<'a>(comparison: &Comparison, releases: &'a [ReleaseMarker], x_days: usize) -> Vec<(usize, usize, &'a str)> {
+    let chart_start = comparison.series.iter().filter_map(|series| series.start).min();
+    let mut positions: Vec<(usize, usize, &str)> = releases
+        .iter()
+        .filter_map(|release| {
+            let index = comparison.series.iter().position(|series| series.repository.eq_ignore_ascii_case(&release.repository))?;
+            let start = match comparison.alignment {
+                Alignment::Calendar => chart_start?,
+                Alignment::Relative => comparison.series[index].start?,
+            };
+            let day = usize::try_from((release.day - start).num_days()).ok().filter(|day| *day <= x_days)?;
+            Some((day, index, release.tag_name.as_str()))
+        })
+        .collect();
+    positions.sort_unstable();
+    positions
+}
+
+/// Days per unit of a relative axis spanning `days`, and the axis description:
+/// days for half a year or less, months for three years or less, years past it.
+fn relative_unit(days: usize) -> (f64, Option<&'static str>) {
+    if days <= MAX_DAYS_AXIS {
+        (1.0, Some("Days since first star"))
+    } else if days <= MAX_MONTHS_AXIS {
+        (30.44, Some("Months since first star"))
+    } else {
+        (365.25, Some("Years since first star"))
+    }
+}
+
+/// Values grouped by `width` days: summed for daily stars, traffic and activity, averaged for rolling averages,
+/// the last one for running totals.
+fn bucket(values: &[f64], width: usize, metric: CompareMetric) -> Vec<f64> {
+    values
+        .chunks(width)
+        .map(|chunk| match metric {
+            CompareMetric::Daily | CompareMetric::Views | CompareMetric::Clones | CompareMetric::Issues | CompareMetric::Prs => chunk.iter().sum(),
+            CompareMetric::RollingAverage | CompareMetric::Velocity | CompareMetric::Acceleration => chunk.iter().sum::<f64>() / chunk.len() as f64,
+            CompareMetric::Cumulative | CompareMetric::WithForks | CompareMetric::Normalized | CompareMetric::Contributors => chunk.last().copied().unwrap_or(0.0),
+        })
+        .collect()
+}
+
+/// Lower and upper bound of every series, the upper bound of series `n` being row `n + 1`.
+/// Unstacked series all start from 0.
+fn stack(values: &[Vec<f64>], stacked: bool) -> Vec<Vec<f64>> {
+    let mut rows = vec![vec![0.0; values.first().map_or(0, Vec::len)]];
+    for series in values {
+        let row = if stacked {
+            series.iter().zip(&rows[rows.len() - 1]).map(|(value, below)| value + below).collect()
+        } else {
+            series.clone()
+        };
+        rows.push(row);
+    }
+    rows
+}
+
+fn value_label(metric: CompareMetric, value: f64) -> String {
+    match metric {
+        CompareMetric::RollingAverage | CompareMetric::Normalized | CompareMetric::Velocity => format!("{value:.1}"),
+        CompareMetric::Acceleration => format!("{value:.2}"),
+        _ => format!("{value:.0}"),
+    }
+}
+
+fn metric_description(metric: CompareMetric) -> &'static str {
+    match metric {
+        CompareMetric::Daily => "Stars per day",
+        CompareMetric::Cumulative => "Stars",
+        CompareMetric::RollingAverage => "Stars per day, 7-day average",
+        CompareMetric::WithForks => "Stars with forks",
+        CompareMetric::Normalized => "Stars, normalized to 100",
+        CompareMetric::Velocity => "Stars per day",
+        CompareMetric::Acceleration => "Change in stars per day",
+        CompareMetric::Views => "Views per day",
+        CompareMetric::Clones => "Clones per day",
+        CompareMetric::Issues => "Issues opened per day",
+        CompareMetric::Prs => "Pull requests opened per day",
+        CompareMetric::Contributors => "Contributors",
+    }
+}
+
+fn series_color(theme: &ChartTheme, index: usize) -> RGBColor {
+    match theme.series.len() {
+        0 => RGBColor(0xf1, 0xc4, 0x0f),
+        len => color(&theme.series[index % len]),
+    }
+}
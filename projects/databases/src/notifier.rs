@@ -0,0 +1,124 @@
+//! Delivery of alert notifications
+//!
+//! Routes an alert to its webhook or email target. Email is only available
+//! when an SMTP server is configured. Webhook URLs are checked against the
+//! [`WebhookUrlPolicy`] again before every delivery, so rules created under a
+//! looser policy can't reach hosts it now refuses. Webhook alerts fired by a
+//! sync go through [`crate::outbox`] rather than [`Notifier::notify`].
+
+use reqwest::Client;
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::db::alert_rule::models::{AlertKind, AlertTarget};
+use crate::email::{EmailSender, SendEmailError};
+use crate::validation::WebhookUrlPolicy;
+use crate::webhook::{send_webhook, SendWebhookError};
+
+#[derive(Clone)]
+pub struct Notifier {
+    http: Client,
+    email: Option<EmailSender>,
+    webhook_policy: WebhookUrlPolicy,
+}
+
+/// Alert fired by a rule, sent as JSON to webhooks and as text by email.
+#[derive(Debug, Serialize)]
+pub struct AlertNotification<'a> {
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub kind: AlertKind,
+    pub threshold: i32,
+    pub day: chrono::NaiveDate,
+    pub stars: i64,
+}
+
+impl AlertNotification<'_> {
+    fn subject(&self) -> String {
+        format!("{}/{}: {} alert", self.owner, self.name, self.kind.as_str())
+    }
+
+    fn text(&self) -> String {
+        match self.kind {
+            AlertKind::DailyStars => format!(
+                "{}/{} received {} stars on {}, above the threshold of {}.",
+                self.owner, self.name, self.stars, self.day, self.threshold
+            ),
+            AlertKind::TotalStars => format!(
+                "{}/{} reached {} stars on {} (threshold {}).",
+                self.owner, self.name, self.stars, self.day, self.threshold
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    #[error("Webhook: {source}")]
+    Webhook {
+        source: SendWebhookError,
+    },
+    #[error("Email: {source}")]
+    Email {
+        source: SendEmailError,
+    },
+    #[error("EmailNotConfigured")]
+    EmailNotConfigured,
+    #[error("WebhookUrlRejected: {reason}")]
+    WebhookUrlRejected {
+        reason: String,
+    },
+}
+
+impl Notifier {
+    pub fn new(http: Client, email: Option<EmailSender>, webhook_policy: WebhookUrlPolicy) -> Self {
+        Self { http, email, webhook_policy }
+    }
+
+    pub fn has_email(&self) -> bool {
+        self.email.is_some()
+    }
+
+    /// Sender of alert emails, `None` without `SMTP_URL`.
+    pub fn email(&self) -> Option<&EmailSender> {
+        self.email.as_ref()
+    }
+
+    pub fn webhook_policy(&self) -> &WebhookUrlPolicy {
+        &self.webhook_policy
+    }
+
+    /// Posts `payload` to the webhook at `destination`, once the policy allows it.
+    pub async fn deliver_webhook<T: Serialize>(
+        &self,
+        destination: &str,
+        delivery_id: Option<Uuid>,
+        payload: &T,
+    ) -> Result<(), NotifyError> {
+        self.webhook_policy
+            .validate(destination)
+            .map_err(|reason| NotifyError::WebhookUrlRejected { reason })?;
+        send_webhook(&self.http, destination, delivery_id, payload)
+            .await
+            .map_err(|source| NotifyError::Webhook { source })
+    }
+
+    pub async fn notify(
+        &self,
+        target: AlertTarget,
+        destination: &str,
+        notification: &AlertNotification<'_>,
+    ) -> Result<(), NotifyError> {
+        match target {
+            AlertTarget::Webhook => self.deliver_webhook(destination, None, notification).await,
+            AlertTarget::Email => {
+                let email = self.email.as_ref().ok_or(NotifyError::EmailNotConfigured)?;
+                email
+                    .send(destination, &notification.subject(), notification.text())
+                    .await
+                    .map_err(|source| NotifyError::Email { source })
+            }
+        }
+    }
+}
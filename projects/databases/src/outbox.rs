@@ -0,0 +1,140 @@
+//! Outbox of webhook deliveries
+//!
+//! Webhook alerts fired by a sync are written to `webhook_outbox` in the
+//! transaction recording the job's final status, rather than posted by the
+//! sync's worker, so a process dying after the job ended can't lose them. A
+//! dispatcher on every instance claims the due deliveries every few seconds,
+//! see [`claim_due_webhook_deliveries`], and posts them through the
+//! [`Notifier`].
+//!
+//! A failed attempt is retried after a backoff doubling from 30 seconds up to
+//! an hour, and given up on after [`MAX_ATTEMPTS`] attempts, or right away
+//! when the webhook policy refuses the URL. An instance dying mid-attempt
+//! leaves the delivery claimed until its lease runs out, another dispatcher
+//! then retries it: a webhook is delivered at least once, receivers can drop
+//! duplicates by their [`crate::webhook::DELIVERY_ID_HEADER`].
+
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::db::{
+	    webhook_outbox::{
+	        models::WebhookDelivery,
+	        queries::{claim_due_webhook_deliveries, mark_webhook_delivered, record_webhook_failure, ClaimDueWebhookDeliveriesError},
+	    },
+	    PgPool,
+	};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::notifier::{Notifier, NotifyError};
+use crate::sync_queue::StopSignal;
+
+/// Attempts of a delivery before it is given up on.
+pub const MAX_ATTEMPTS: i32 = 8;
+
+/// How often due deliveries are looked for.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Deliveries claimed at once, posted one after the other.
+const BATCH_SIZE: i64 = 20;
+
+/// How long claimed deliveries are left to their dispatcher, well over a batch of timed out posts.
+const LEASE: TimeDelta = TimeDelta::minutes(10);
+
+/// Wait before the attempt following the `attempts`th one.
+pub fn retry_backoff(attempts: i32) -> TimeDelta {
+    let doublings = attempts.clamp(1, 8) - 1;
+    TimeDelta::seconds(30 << doublings).min(TimeDelta::hours(1))
+}
+
+/// Outcome of [`dispatch_due_deliveries`].
+#[derive(Debug, Default)]
+pub struct DispatchSummary {
+    pub delivered: usize,
+    /// Deliveries due again later.
+    pub retried: usize,
+    /// Deliveries given up on.
+    pub failed: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum DispatchDueDeliveriesError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        #[from]
+        source: DbConnRejection,
+    },
+    #[error("ClaimDueWebhookDeliveries: {source}")]
+    ClaimDueWebhookDeliveries {
+        #[from]
+        source: ClaimDueWebhookDeliveriesError,
+    },
+}
+
+/// Posts the deliveries due at `now`, batch after batch until none is left.
+/// A delivery whose outcome can't be recorded is attempted again once its lease runs out.
+pub async fn dispatch_due_deliveries(pool: &PgPool, notifier: &Notifier, now: NaiveDateTime) -> Result<DispatchSummary, DispatchDueDeliveriesError> {
+    // Every few seconds on the runtime, the checkout must not block it while the pool is busy.
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+        .await
+        .map_err(|source| DispatchDueDeliveriesError::Checkout { source })?;
+
+    let mut summary = DispatchSummary::default();
+    loop {
+        let deliveries = claim_due_webhook_deliveries(&mut conn, now, now + LEASE, BATCH_SIZE)
+            .map_err(|source| DispatchDueDeliveriesError::ClaimDueWebhookDeliveries { source })?;
+        if deliveries.is_empty() {
+            return Ok(summary);
+        }
+
+        for delivery in deliveries {
+            let outcome = notifier.deliver_webhook(&delivery.destination, Some(delivery.id), &delivery.payload).await;
+            let recorded = match outcome {
+                Ok(()) => {
+                    summary.delivered += 1;
+                    mark_webhook_delivered(&mut conn, delivery.id, Utc::now().naive_utc())
+                }
+                Err(error) => {
+                    let retry_at = next_attempt(&delivery, &error);
+                    match retry_at {
+                        Some(_) => summary.retried += 1,
+                        None => summary.failed += 1,
+                    }
+                    warn!(id = %delivery.id, attempts = delivery.attempts, %error, retrying = retry_at.is_some(), "webhook delivery failed");
+                    record_webhook_failure(&mut conn, delivery.id, &error.to_string(), retry_at)
+                }
+            };
+            if let Err(error) = recorded {
+                warn!(id = %delivery.id, %error, "webhook delivery outcome not recorded");
+            }
+        }
+    }
+}
+
+/// When `delivery` is attempted again after failing with `error`, `None` to give up on it.
+fn next_attempt(delivery: &WebhookDelivery, error: &NotifyError) -> Option<NaiveDateTime> {
+    let refused = matches!(error, NotifyError::WebhookUrlRejected { .. });
+    if refused || delivery.attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    Some(Utc::now().naive_utc() + retry_backoff(delivery.attempts))
+}
+
+/// Posts due webhook deliveries every few seconds until `stop` fires.
+pub async fn run_outbox_dispatcher(pool: PgPool, notifier: Notifier, stop: StopSignal) {
+    let mut ticks = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = stop.stopped() => return,
+            _ = ticks.tick() => {}
+        }
+
+        match dispatch_due_deliveries(&pool, &notifier, Utc::now().naive_utc()).await {
+            Ok(DispatchSummary { delivered: 0, retried: 0, failed: 0 }) => {}
+            Ok(summary) => info!(delivered = summary.delivered, retried = summary.retried, failed = summary.failed, "dispatched webhook deliveries"),
+            Err(error) => warn!(%error, "webhook outbox dispatch failed"),
+        }
+    }
+}
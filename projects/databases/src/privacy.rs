@@ -0,0 +1,53 @@
+//! Stargazer privacy
+//!
+//! Deployments that can't keep personal data set `STARGAZER_HASH_SECRET`:
+//! every login is then replaced by its HMAC-SHA256 under that secret before
+//! being stored or published, by syncs, the star webhook and imports alike,
+//! and so are the commit authors of contributor tracking.
+//! The same stargazer always gets the same identifier, so stars are still
+//! deduplicated and counted across repositories, but the login can't be
+//! recovered without the secret. Profiles aren't collected in that mode.
+
+use std::borrow::Cow;
+
+use ring::hmac;
+
+/// How stargazers are identified in storage. Cloning is cheap.
+#[derive(Clone, Default)]
+pub enum StargazerIds {
+    /// GitHub logins as they are.
+    #[default]
+    Logins,
+    /// Hex HMAC-SHA256 of the lowercased login.
+    Hashed(hmac::Key),
+}
+
+impl std::fmt::Debug for StargazerIds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StargazerIds::Logins => f.write_str("Logins"),
+            StargazerIds::Hashed(_) => f.write_str("Hashed(..)"),
+        }
+    }
+}
+
+impl StargazerIds {
+    pub fn hashed(secret: &str) -> Self {
+        Self::Hashed(hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()))
+    }
+
+    pub fn is_hashed(&self) -> bool {
+        matches!(self, StargazerIds::Hashed(_))
+    }
+
+    /// Identifier stored for `login`. Logins are case-insensitive, so are their hashes.
+    pub fn stargazer_id<'a>(&self, login: &'a str) -> Cow<'a, str> {
+        match self {
+            StargazerIds::Logins => Cow::Borrowed(login),
+            StargazerIds::Hashed(key) => {
+                let tag = hmac::sign(key, login.to_lowercase().as_bytes());
+                Cow::Owned(tag.as_ref().iter().map(|byte| format!("{byte:02x}")).collect())
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Releases of the tracked repositories
+//!
+//! Every completed sync of a GitHub repository also fetches its latest
+//! releases, and its tags without a release, one extra GraphQL query. They
+//! annotate comparison charts with `annotate_releases=true` and are weighed
+//! against the star velocity around them by
+//! `GET /github/repo_stars/releases/{owner}/{name}`, see
+//! [`crate::analytics::releases`].
+//!
+//! A failed fetch is logged and leaves the stored releases as they were, the
+//! next sync tries again. Releases deleted on GitHub are kept.
+
+use chrono::NaiveDateTime;
+use interfaces_github_stargazers::client::GitHubClient;
+use interfaces_github_stargazers::releases::{fetch_repository_releases, FetchRepositoryReleasesError};
+use diesel::PgConnection;
+use thiserror::Error;
+
+use crate::db::{
+    release::{
+        models::NewRelease,
+        queries::{upsert_releases, UpsertReleasesError},
+    },
+    repository::models::Repository,
+};
+
+#[derive(Debug, Error)]
+pub enum StoreReleasesError {
+    #[error("FetchRepositoryReleases: {source}")]
+    FetchRepositoryReleases {
+        #[from]
+        source: FetchRepositoryReleasesError,
+    },
+    #[error("UpsertReleases: {source}")]
+    UpsertReleases {
+        #[from]
+        source: UpsertReleasesError,
+    },
+}
+
+/// Fetches the releases of `repo` from GitHub and stores them, the number stored. None when GitHub
+/// no longer knows the repository.
+pub async fn store_releases(
+    conn: &mut PgConnection,
+    github: &GitHubClient,
+    repo: &Repository,
+    fetched_at: NaiveDateTime,
+) -> Result<usize, StoreReleasesError> {
+    let Some(releases) = fetch_repository_releases(github, &repo.owner, &repo.name).await? else {
+        return Ok(0);
+    };
+
+    let new_releases: Vec<NewRelease> = releases
+        .into_iter()
+        .map(|release| NewRelease {
+            repository_id: repo.id,
+            tag_name:      release.tag_name,
+            name:          release.name,
+            kind:          release.kind.as_str().to_string(),
+            prerelease:    release.prerelease,
+            url:           release.url,
+            published_at:  release.published_at.naive_utc(),
+            fetched_at,
+        })
+        .collect();
+    if new_releases.is_empty() {
+        return Ok(0);
+    }
+    Ok(upsert_releases(conn, &new_releases)?)
+}
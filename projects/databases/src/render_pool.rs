@@ -0,0 +1,53 @@
+//! Chart rendering off the async executor
+//!
+//! Drawing a chart is CPU-bound work, a PNG social card or a plotters chart
+//! of years of daily values taking long enough to hold up every request
+//! waiting on I/O behind it on a runtime worker. Handlers hand it to the
+//! [`RenderPool`] instead, which runs it on tokio's blocking threads, at most
+//! `MAX_CONCURRENT_RENDERS` at once. Renders past the cap wait for a free
+//! slot, within the deadline of their request.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use tokio::sync::Semaphore;
+use tokio::task::JoinError;
+
+#[derive(Debug, Error)]
+pub enum RenderError {
+    #[error("RenderPoolClosed")]
+    RenderPoolClosed,
+    #[error("RenderTask: {source}")]
+    RenderTask {
+        source: JoinError,
+    },
+}
+
+/// Blocking threads allowed to render at once, shared by clones.
+#[derive(Debug, Clone)]
+pub struct RenderPool {
+    permits: Arc<Semaphore>,
+}
+
+impl RenderPool {
+    pub fn new(max_concurrent_renders: usize) -> Self {
+        Self { permits: Arc::new(Semaphore::new(max_concurrent_renders)) }
+    }
+
+    /// Result of `render`, run on a blocking thread once a slot is free. The slot is held until
+    /// `render` returns, even when the request waiting on it is given up, e.g. past its deadline.
+    pub async fn run<T, F>(&self, render: F) -> Result<T, RenderError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = self.permits.clone().acquire_owned().await
+            .map_err(|_| RenderError::RenderPoolClosed)?;
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            render()
+        })
+            .await
+            .map_err(|source| RenderError::RenderTask { source })
+    }
+}
@@ -0,0 +1,102 @@
+//! Star retention
+//!
+//! Every star is a row, which adds up for popular repositories. With
+//! `STAR_RETENTION_MONTHS` set, stars older than that are rolled up into
+//! per-day counts of `daily_star_aggregates` and their rows deleted, every
+//! `STAR_COMPACTION_INTERVAL_HOURS` (24 by default) or on `star-tracker
+//! compact`. Star series and counts read both sources, but compacted stars
+//! lose their stargazer: earliest and top stargazers, profiles and star
+//! webhook deletions only see the stars still stored, and syncs and imports
+//! skip the compacted days.
+
+use std::time::Duration;
+
+use chrono::{Months, NaiveDate, Utc};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::db::{
+	    daily_star_aggregate::queries::{compact_stars, get_repositories_with_stars_before, GetRepositoriesWithStarsBeforeError},
+	    PgPool,
+	};
+use crate::sync_queue::StopSignal;
+
+/// How long star rows are kept, and how often older ones are compacted.
+#[derive(Debug, Clone, Copy)]
+pub struct StarRetention {
+    pub months: u32,
+    pub interval: Duration,
+}
+
+/// First day whose stars are kept as rows when keeping `months` of them.
+pub fn compaction_cutoff(today: NaiveDate, months: u32) -> NaiveDate {
+    today.checked_sub_months(Months::new(months)).unwrap_or(NaiveDate::MIN)
+}
+
+/// Outcome of [`compact_old_stars`].
+#[derive(Debug, Default)]
+pub struct CompactionSummary {
+    pub repositories: usize,
+    /// Star rows rolled up and deleted.
+    pub stars: usize,
+    /// Repositories whose compaction failed and was rolled back.
+    pub failed: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum CompactOldStarsError {
+    #[error("GetConnectionFromPool: {source}")]
+    GetConnectionFromPool {
+        #[from]
+        source: r2d2::Error,
+    },
+    #[error("GetRepositoriesWithStarsBefore: {source}")]
+    GetRepositoriesWithStarsBefore {
+        #[from]
+        source: GetRepositoriesWithStarsBeforeError,
+    },
+}
+
+/// Compacts the stars starred before `before` of every repository, one transaction each.
+/// A failing repository is logged and left for the next run.
+pub fn compact_old_stars(pool: &PgPool, before: NaiveDate) -> Result<CompactionSummary, CompactOldStarsError> {
+    let mut conn = pool.get()
+        .map_err(|source| CompactOldStarsError::GetConnectionFromPool { source })?;
+
+    let repo_ids = get_repositories_with_stars_before(&mut conn, before)
+        .map_err(|source| CompactOldStarsError::GetRepositoriesWithStarsBefore { source })?;
+
+    let mut summary = CompactionSummary::default();
+    for repo_id in repo_ids {
+        match compact_stars(&mut conn, repo_id, before) {
+            Ok(stars) => {
+                summary.repositories += 1;
+                summary.stars += stars;
+            }
+            Err(error) => {
+                warn!(%repo_id, %error, "star compaction failed");
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Compacts old stars every `retention.interval`, starting right away, until `stop` fires.
+pub async fn run_compaction(pool: PgPool, retention: StarRetention, stop: StopSignal) {
+    let mut ticks = tokio::time::interval(retention.interval);
+    loop {
+        tokio::select! {
+            _ = stop.stopped() => return,
+            _ = ticks.tick() => {}
+        }
+
+        let before = compaction_cutoff(Utc::now().date_naive(), retention.months);
+        let pool = pool.clone();
+        match tokio::task::spawn_blocking(move || compact_old_stars(&pool, before)).await {
+            Ok(Ok(summary)) => info!(%before, repositories = summary.repositories, stars = summary.stars, failed = summary.failed, "compacted old stars"),
+            Ok(Err(error)) => warn!(%error, "star compaction failed"),
+            Err(error) => warn!(%error, "star compaction panicked"),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Routes of the service
+//!
+//! Assembled from the clients, pools and settings the server builds from the
+//! environment, so the integration tests serve the very same router.
+
+use std::{convert::Infallible, time::Duration};
+
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post, put},
+    Extension, Router,
+};
+use interfaces_github_stargazers::client::GitHubClient;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::endpoints::github::repo_stars::{update::index::handler as github_repo_stars_update_handler, update_batch::index::handler as github_repo_stars_update_batch_handler, read_per_day::index::{handler as github_repo_stars_read_per_day_handler, get_handler as github_repo_stars_read_per_day_get_handler}, jobs::{purge::index::handler as github_repo_stars_jobs_purge_handler, resume::index::handler as github_repo_stars_jobs_resume_handler, queue::index::handler as github_repo_stars_jobs_queue_handler, status::index::handler as github_repo_stars_jobs_status_handler}, forecast::index::handler as github_repo_stars_forecast_handler, stats::index::handler as github_repo_stars_stats_handler, trends::index::handler as github_repo_stars_trends_handler, cohorts::index::handler as github_repo_stars_cohorts_handler, heatmap::index::handler as github_repo_stars_heatmap_handler, compare::index::handler as github_repo_stars_compare_handler, diff::index::handler as github_repo_stars_diff_handler, milestones::{list::index::handler as github_repo_stars_milestones_list_handler, eta::index::handler as github_repo_stars_milestones_eta_handler}, anomalies::{list::index::handler as github_repo_stars_anomalies_list_handler, detect::index::handler as github_repo_stars_anomalies_detect_handler}, earliest_stargazers::index::handler as github_repo_stars_earliest_stargazers_handler, as_of::index::handler as github_repo_stars_as_of_handler, releases::index::handler as github_repo_stars_releases_handler, live::index::handler as github_repo_stars_live_handler, import::index::{handler as github_repo_stars_import_handler, MAX_IMPORT_BYTES}, export::index::handler as github_repo_stars_export_handler, snapshots::{start::index::handler as github_repo_stars_snapshots_start_handler, list::index::handler as github_repo_stars_snapshots_list_handler, stop::index::handler as github_repo_stars_snapshots_stop_handler}, contributors::{start::index::handler as github_repo_stars_contributors_start_handler, list::index::handler as github_repo_stars_contributors_list_handler, stop::index::handler as github_repo_stars_contributors_stop_handler}, drift::{list::index::handler as github_repo_stars_drift_list_handler, verify::index::handler as github_repo_stars_drift_verify_handler}};
+use crate::endpoints::github::repositories::{archive::{add::index::handler as github_repositories_archive_add_handler, remove::index::handler as github_repositories_archive_remove_handler}, data_quality::index::handler as github_repositories_data_quality_handler, list::index::handler as github_repositories_list_handler, sync_history::index::handler as github_repositories_sync_history_handler, tags::{add::index::handler as github_repositories_tags_add_handler, remove::index::handler as github_repositories_tags_remove_handler, list::index::handler as github_repositories_tags_list_handler}};
+use crate::endpoints::github::etag::etag_middleware;
+#[cfg(feature = "charting")]
+use crate::endpoints::github::repo_stars::{social_card::index::handler as github_repo_stars_social_card_handler, compare::chart::index::handler as github_repo_stars_compare_chart_handler};
+#[cfg(feature = "dashboard")]
+use crate::endpoints::dashboard::{asset_handler as dashboard_asset_handler, index_handler as dashboard_index_handler, redirect_handler as dashboard_redirect_handler};
+use crate::endpoints::admin::{auth::admin_middleware, config::{get::index::handler as admin_config_get_handler, update::index::handler as admin_config_update_handler}};
+use crate::endpoints::{compression::{compression_layer, request_decompression_layer}, cors::CorsConfig, envelope::envelope_middleware, limits::{body_limit_middleware, concurrency_limit_middleware, tenant_rate_limit_middleware, timeout_middleware, ConcurrencyLimit, RequestLimits, TenantRateLimit}, metrics::index::handler as metrics_handler, openapi::ApiDoc, tenant::{tenant_middleware, TenantAuth}};
+use crate::endpoints::github::stargazers::{top::index::handler as github_stargazers_top_handler, profiles::index::handler as github_stargazers_profiles_handler, emails::index::{handler as github_stargazers_emails_handler, EXPORT_REQUESTS_PER_HOUR}};
+use crate::endpoints::github::alerts::{create::index::handler as github_alerts_create_handler, list::index::handler as github_alerts_list_handler, delete::index::handler as github_alerts_delete_handler};
+use crate::endpoints::github::webhooks::star::index::handler as github_webhooks_star_handler;
+use crate::endpoints::github::reports::{list::index::handler as github_reports_list_handler, get::index::handler as github_reports_get_handler};
+use crate::endpoints::github::org_stars::{update::index::handler as github_org_stars_update_handler, batch::index::handler as github_org_stars_batch_handler};
+use crate::endpoints::{admin::auth::AdminToken, github::webhooks::signature::WebhookSecret};
+use crate::chart_cache::ChartCache;
+use crate::db::{pool::{PoolMetrics, ReadPool}, PgPool};
+use crate::forges::ForgeClients;
+use crate::live::LiveUpdates;
+use crate::notifier::Notifier;
+use crate::privacy::StargazerIds;
+use crate::render_pool::RenderPool;
+use crate::runtime_config::RuntimeConfig;
+use crate::sync_queue::SyncQueue;
+
+/// What the routes are handed, one clone per request.
+pub struct RouterDependencies {
+    pub db_pool: PgPool,
+    pub pool_metrics: PoolMetrics,
+    pub read_pool: ReadPool,
+    pub github_client: GitHubClient,
+    pub forge_clients: ForgeClients,
+    pub notifier: Notifier,
+    /// Star webhook deliveries are refused while unset.
+    pub webhook_secret: Option<WebhookSecret>,
+    pub stargazer_ids: StargazerIds,
+    pub runtime_config: RuntimeConfig,
+    pub sync_queue: SyncQueue,
+    pub live_updates: LiveUpdates,
+    /// Comparison charts, shared with the sync queue dropping those of synced repositories.
+    pub chart_cache: ChartCache,
+    pub request_limits: RequestLimits,
+    /// Browsers can't call the API while unset.
+    pub cors: Option<CorsConfig>,
+    pub tenant_auth: TenantAuth,
+    /// Admin routes are disabled while unset.
+    pub admin_token: Option<AdminToken>,
+}
+
+/// Every route of the service with its middlewares, the Swagger UI included.
+pub fn build_router(dependencies: RouterDependencies) -> Router {
+    let RouterDependencies {
+        db_pool,
+        pool_metrics,
+        read_pool,
+        github_client,
+        forge_clients,
+        notifier,
+        webhook_secret,
+        stargazer_ids,
+        runtime_config,
+        sync_queue,
+        live_updates,
+        chart_cache,
+        request_limits,
+        cors,
+        tenant_auth,
+        admin_token,
+    } = dependencies;
+
+    // Read endpoints whose answer only changes when stars are synced, or the day after
+    let cached_reads = Router::new()
+        .route("/github/repo_stars/read_per_day/{owner}/{name}", get(github_repo_stars_read_per_day_get_handler))
+        .route("/github/repo_stars/forecast/{owner}/{name}", get(github_repo_stars_forecast_handler))
+        .route("/github/repo_stars/milestones/{owner}/{name}", get(github_repo_stars_milestones_list_handler))
+        .route("/github/repo_stars/earliest_stargazers/{owner}/{name}", get(github_repo_stars_earliest_stargazers_handler))
+        .route("/github/repo_stars/as_of/{owner}/{name}", get(github_repo_stars_as_of_handler))
+        .route("/github/repo_stars/heatmap/{owner}/{name}", get(github_repo_stars_heatmap_handler));
+    #[cfg(feature = "charting")]
+    let cached_reads = cached_reads
+        .route("/github/repo_stars/social_card/{owner}/{name}", get(github_repo_stars_social_card_handler));
+    let cached_reads = cached_reads.route_layer(middleware::from_fn(etag_middleware));
+
+    // Rendered images, only in builds with the charting feature
+    let charts = Router::new();
+    #[cfg(feature = "charting")]
+    let charts = charts
+        .route("/github/repo_stars/compare/chart", post(github_repo_stars_compare_chart_handler));
+
+    // Files of the dashboard, whose calls to the API carry their tenant's key
+    let dashboard = Router::new();
+    #[cfg(feature = "dashboard")]
+    let dashboard = dashboard
+        .route("/ui", get(dashboard_redirect_handler))
+        .route("/ui/", get(dashboard_index_handler))
+        .route("/ui/{*path}", get(dashboard_asset_handler));
+
+    // Calls from GitHub and from monitoring, which aren't tied to a tenant
+    let untenanted = Router::new()
+        .route("/github/webhooks/star", post(github_webhooks_star_handler))
+        .route("/metrics", get(metrics_handler))
+        .route_layer(middleware::from_fn_with_state(request_limits.timeout, timeout_middleware))
+        .route_layer(middleware::from_fn_with_state(request_limits.max_body_bytes, body_limit_middleware))
+        .layer(DefaultBodyLimit::max(request_limits.max_body_bytes));
+
+    // Settings of the whole service, neither tenanted nor open to API keys
+    let admin = Router::new()
+        .route("/admin/config", get(admin_config_get_handler).patch(admin_config_update_handler))
+        .route_layer(middleware::from_fn_with_state(request_limits.timeout, timeout_middleware))
+        .route_layer(middleware::from_fn_with_state(request_limits.max_body_bytes, body_limit_middleware))
+        .route_layer(middleware::from_fn_with_state(admin_token, admin_middleware))
+        .layer(DefaultBodyLimit::max(request_limits.max_body_bytes));
+
+    // Set up the router
+    let mut app = Router::new()
+        .route("/github/repo_stars/update_batch", post(github_repo_stars_update_batch_handler))
+        .route("/github/repo_stars/read_per_day", post(github_repo_stars_read_per_day_handler))
+        .route("/github/repo_stars/compare", post(github_repo_stars_compare_handler))
+        .route("/github/repo_stars/jobs", delete(github_repo_stars_jobs_purge_handler))
+        .route("/github/repo_stars/jobs/queue", get(github_repo_stars_jobs_queue_handler))
+        .route("/github/repo_stars/jobs/{id}", get(github_repo_stars_jobs_status_handler))
+        .route("/github/repo_stars/stats/{owner}/{name}", get(github_repo_stars_stats_handler))
+        .route("/github/repo_stars/releases/{owner}/{name}", get(github_repo_stars_releases_handler))
+        .route("/github/repo_stars/trends/{owner}/{name}", get(github_repo_stars_trends_handler))
+        .route("/github/repo_stars/cohorts/{owner}/{name}", get(github_repo_stars_cohorts_handler))
+        .route("/github/repo_stars/diff/{owner}/{name}", get(github_repo_stars_diff_handler))
+        .route("/github/repo_stars/milestones/{owner}/{name}/eta", get(github_repo_stars_milestones_eta_handler))
+        .route("/github/repo_stars/drift", get(github_repo_stars_drift_list_handler))
+        .route("/github/repo_stars/anomalies/{owner}/{name}", get(github_repo_stars_anomalies_list_handler))
+        .route("/github/repo_stars/anomalies/{owner}/{name}/detect", post(github_repo_stars_anomalies_detect_handler))
+        .route("/github/repo_stars/snapshots/{owner}/{name}", get(github_repo_stars_snapshots_list_handler).post(github_repo_stars_snapshots_start_handler).delete(github_repo_stars_snapshots_stop_handler))
+        .route("/github/repo_stars/contributors/{owner}/{name}", get(github_repo_stars_contributors_list_handler).post(github_repo_stars_contributors_start_handler).delete(github_repo_stars_contributors_stop_handler))
+        .route("/github/alerts", get(github_alerts_list_handler).post(github_alerts_create_handler))
+        .route("/github/alerts/{id}", delete(github_alerts_delete_handler))
+        .route("/github/stargazers/top", get(github_stargazers_top_handler))
+        .route("/github/stargazers/profiles/{owner}/{name}", get(github_stargazers_profiles_handler))
+        // Personal data, read at a limited pace by each tenant
+        .route("/github/stargazers/emails/{owner}/{name}", get(github_stargazers_emails_handler).layer(middleware::from_fn_with_state(TenantRateLimit::new(EXPORT_REQUESTS_PER_HOUR, Duration::from_secs(3600)), tenant_rate_limit_middleware)))
+        .route("/github/repositories", get(github_repositories_list_handler))
+        .route("/github/repositories/tags", get(github_repositories_tags_list_handler))
+        .route("/github/repositories/{owner}/{name}/tags/{tag}", put(github_repositories_tags_add_handler).delete(github_repositories_tags_remove_handler))
+        .route("/github/repositories/{owner}/{name}/sync_history", get(github_repositories_sync_history_handler))
+        .route("/github/repositories/{owner}/{name}/data_quality", get(github_repositories_data_quality_handler))
+        .route("/github/repositories/{owner}/{name}/archive", put(github_repositories_archive_add_handler).delete(github_repositories_archive_remove_handler))
+        .route("/github/reports", get(github_reports_list_handler))
+        .route("/github/reports/{id}", get(github_reports_get_handler))
+        .route("/github/org_stars/update", post(github_org_stars_update_handler))
+        .route("/github/org_stars/batches/{id}", get(github_org_stars_batch_handler))
+        .merge(cached_reads)
+        .merge(charts)
+        .route_layer(middleware::from_fn_with_state(request_limits.timeout, timeout_middleware))
+        // Routes waiting on a sync or GitHub for as long as it takes, or holding a WebSocket open
+        .route("/github/repo_stars/update", post(github_repo_stars_update_handler))
+        .route("/github/repo_stars/jobs/{id}/resume", post(github_repo_stars_jobs_resume_handler))
+        .route("/github/repo_stars/drift/verify", post(github_repo_stars_drift_verify_handler))
+        .route("/github/repo_stars/live/{owner}/{name}", get(github_repo_stars_live_handler))
+        // Streamed for as long as the client reads, its answer starting right away
+        .route("/github/repo_stars/export/{owner}/{name}", get(github_repo_stars_export_handler))
+        .route_layer(middleware::from_fn_with_state(request_limits.max_body_bytes, body_limit_middleware))
+        .layer(DefaultBodyLimit::max(request_limits.max_body_bytes))
+        // Dumps are far larger than any other body, and slow to store, so they may come compressed
+        .route("/github/repo_stars/import", post(github_repo_stars_import_handler).layer::<_, Infallible>(request_decompression_layer()).layer::<_, Infallible>(DefaultBodyLimit::max(MAX_IMPORT_BYTES)))
+        // Every route above only sees the data of the API key's tenant
+        .route_layer(middleware::from_fn_with_state(tenant_auth, tenant_middleware))
+        .merge(untenanted)
+        .merge(admin)
+        .merge(dashboard)
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::build()))
+        .layer(Extension(db_pool.clone()))
+        .layer(Extension(pool_metrics))
+        .layer(Extension(read_pool))
+        .layer(Extension(github_client))
+        .layer(Extension(forge_clients))
+        .layer(Extension(notifier))
+        .layer(Extension(webhook_secret))
+        .layer(Extension(stargazer_ids))
+        .layer(Extension(runtime_config))
+        .layer(Extension(sync_queue))
+        .layer(Extension(live_updates))
+        .layer(Extension(chart_cache))
+        .layer(Extension(RenderPool::new(request_limits.max_concurrent_renders)))
+        .layer(middleware::from_fn_with_state(ConcurrencyLimit::new(request_limits.max_concurrent_requests), concurrency_limit_middleware))
+        // Request IDs, and errors of every layer above answered the same way
+        .layer(middleware::from_fn(envelope_middleware))
+        // Bodies as the envelope leaves them
+        .layer(compression_layer());
+
+    // Preflights are answered before any limit applies
+    if let Some(cors) = cors.as_ref().map(CorsConfig::layer) {
+        app = app.layer(cors);
+    }
+    app
+}
@@ -0,0 +1,104 @@
+//! Runtime configuration
+//!
+//! Settings are read from the environment at startup. The ones of
+//! [`AdjustableSettings`] can be changed afterwards through
+//! `PATCH /admin/config`, and apply from the next request or cleanup run.
+//! Changes are kept in memory only, a restart goes back to the environment.
+//! The others, reported by `GET /admin/config`, need a restart.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use utoipa::ToSchema;
+
+use crate::db::star::queries::StarInsertConfig;
+use crate::job_retention::SyncJobRetention;
+use crate::sync_limits::SyncLimits;
+
+/// Settings fixed for the lifetime of the process.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ServiceSettings {
+    pub sync_workers: usize,
+    pub shutdown_grace_seconds: u64,
+    pub request_timeout_seconds: u64,
+    pub max_request_body_bytes: usize,
+    pub max_concurrent_requests: usize,
+    pub max_concurrent_renders: usize,
+    /// 0 when charts are drawn for every request.
+    pub chart_cache_ttl_seconds: u64,
+    pub chart_cache_max_bytes: usize,
+    pub require_api_key: bool,
+    pub github_retry_max_attempts: u32,
+    /// Stargazers per page of a sync, shrunk down to `github_min_page_size` on secondary rate limits.
+    pub github_page_size: u32,
+    pub github_min_page_size: u32,
+    /// Unset when every star row is kept.
+    pub star_retention_months: Option<u32>,
+    pub star_compaction_interval_hours: Option<u64>,
+    pub star_snapshot_interval_hours: u64,
+    pub contributor_tracking_interval_hours: u64,
+    /// Unset when traffic isn't recorded.
+    pub traffic_snapshot_interval_hours: Option<u64>,
+    pub collect_stargazer_profiles: bool,
+    pub collect_stargazer_emails: bool,
+    pub hashed_stargazer_ids: bool,
+    pub flag_suspected_bots: bool,
+    pub collect_repository_activity: bool,
+    /// Unset when sync job endings aren't notified.
+    pub sync_notify_channel: Option<String>,
+}
+
+/// Settings `PATCH /admin/config` may change.
+#[derive(Debug, Clone, Copy)]
+pub struct AdjustableSettings {
+    /// Chunk size and `COPY` threshold of star imports, their method being picked per request.
+    pub star_insert: StarInsertConfig,
+    pub sync_job_retention: SyncJobRetention,
+    /// Limits of sync jobs whose request doesn't set its own.
+    pub sync_limits: SyncLimits,
+}
+
+/// Settings of the running service, shared by clones.
+#[derive(Debug, Clone)]
+pub struct RuntimeConfig {
+    service: Arc<ServiceSettings>,
+    adjustable: Arc<watch::Sender<AdjustableSettings>>,
+}
+
+impl RuntimeConfig {
+    pub fn new(service: ServiceSettings, adjustable: AdjustableSettings) -> Self {
+        Self { service: Arc::new(service), adjustable: Arc::new(watch::Sender::new(adjustable)) }
+    }
+
+    pub fn service(&self) -> &ServiceSettings {
+        &self.service
+    }
+
+    pub fn adjustable(&self) -> AdjustableSettings {
+        *self.adjustable.borrow()
+    }
+
+    pub fn star_insert(&self) -> StarInsertConfig {
+        self.adjustable.borrow().star_insert
+    }
+
+    pub fn sync_job_retention(&self) -> SyncJobRetention {
+        self.adjustable.borrow().sync_job_retention
+    }
+
+    pub fn sync_limits(&self) -> SyncLimits {
+        self.adjustable.borrow().sync_limits
+    }
+
+    /// Applies `change` and wakes the tasks waiting in [`RuntimeConfig::changed`], returns the new settings.
+    pub fn update(&self, change: impl FnOnce(&mut AdjustableSettings)) -> AdjustableSettings {
+        self.adjustable.send_modify(change);
+        self.adjustable()
+    }
+
+    /// Receiver notified of every [`RuntimeConfig::update`].
+    pub fn changed(&self) -> watch::Receiver<AdjustableSettings> {
+        self.adjustable.subscribe()
+    }
+}
@@ -0,0 +1,181 @@
+//! Star count snapshots
+//!
+//! Enumerating the stargazers of a repository with hundreds of thousands of
+//! stars takes thousands of GitHub queries. Repositories in snapshot mode get
+//! the star count their forge reports recorded instead, one cheap query every
+//! `STAR_SNAPSHOT_INTERVAL_HOURS` (24 by default), in `star_count_snapshots`.
+//! Repositories whose forge isn't configured are skipped.
+//! Star series of a repository without stored stars are then derived from
+//! its snapshots, see [`get_star_history`]: positions rather than the day
+//! each star was given, and nothing before the first snapshot.
+
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use diesel::PgConnection;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::analytics::series::{snapshot_daily_counts, FillPolicy};
+use crate::db::{
+	    repository::{
+	        models::Repository,
+	        queries::{list_snapshot_repositories, ListSnapshotRepositoriesError},
+	    },
+	    star::queries::{get_daily_star_count, GetDailyStarCountError},
+	    star_count_snapshot::{
+	        models::{NewStarCountSnapshot, StarCountSnapshot},
+	        queries::{get_star_count_snapshots, insert_star_count_snapshot, GetStarCountSnapshotsError, InsertStarCountSnapshotError},
+	    },
+	    PgPool,
+	};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::forges::{ForgeClientError, ForgeClients};
+use crate::sync_queue::StopSignal;
+
+#[derive(Debug, Error)]
+pub enum TakeStarCountSnapshotError {
+    #[error("FetchStargazerCount: {source}")]
+    FetchStargazerCount {
+        #[from]
+        source: ForgeClientError,
+    },
+    #[error("InsertStarCountSnapshot: {source}")]
+    InsertStarCountSnapshot {
+        #[from]
+        source: InsertStarCountSnapshotError,
+    },
+}
+
+/// Records the current star count of the repository on its forge, `None` when the forge doesn't know it anymore.
+pub async fn take_star_count_snapshot(
+    conn: &mut PgConnection,
+    forges: &ForgeClients,
+    repo: &Repository,
+) -> Result<Option<StarCountSnapshot>, TakeStarCountSnapshotError> {
+    let Some(stargazer_count) = forges.stargazer_count(repo.forge(), &repo.owner, &repo.name)
+        .await
+        .map_err(|source| TakeStarCountSnapshotError::FetchStargazerCount { source })?
+    else {
+        return Ok(None);
+    };
+
+    let snapshot = insert_star_count_snapshot(conn, &NewStarCountSnapshot {
+        repository_id: repo.id,
+        stargazer_count: i32::try_from(stargazer_count).unwrap_or(i32::MAX),
+    })
+        .map_err(|source| TakeStarCountSnapshotError::InsertStarCountSnapshot { source })?;
+    Ok(Some(snapshot))
+}
+
+/// Outcome of [`snapshot_star_counts`].
+#[derive(Debug, Default)]
+pub struct SnapshotSummary {
+    pub repositories: usize,
+    /// Repositories their forge doesn't know anymore, e.g. deleted or renamed.
+    pub not_found: usize,
+    /// Repositories whose forge isn't configured, e.g. GitHub without credentials.
+    pub skipped: usize,
+    /// Repositories whose snapshot failed, left for the next run.
+    pub failed: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum SnapshotStarCountsError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        #[from]
+        source: DbConnRejection,
+    },
+    #[error("ListSnapshotRepositories: {source}")]
+    ListSnapshotRepositories {
+        #[from]
+        source: ListSnapshotRepositoriesError,
+    },
+}
+
+/// Snapshots the star count of every repository in snapshot mode, of every tenant.
+/// A failing repository is logged and left for the next run.
+pub async fn snapshot_star_counts(pool: &PgPool, forges: &ForgeClients) -> Result<SnapshotSummary, SnapshotStarCountsError> {
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+        .await
+        .map_err(|source| SnapshotStarCountsError::Checkout { source })?;
+
+    let repositories = list_snapshot_repositories(&mut conn)
+        .map_err(|source| SnapshotStarCountsError::ListSnapshotRepositories { source })?;
+
+    let mut summary = SnapshotSummary::default();
+    for repo in repositories {
+        if !forges.is_available(repo.forge()) {
+            summary.skipped += 1;
+            continue;
+        }
+        match take_star_count_snapshot(&mut conn, forges, &repo).await {
+            Ok(Some(_)) => summary.repositories += 1,
+            Ok(None) => {
+                warn!(owner = %repo.owner, name = %repo.name, forge = %repo.forge(), "snapshot repository not found on its forge");
+                summary.not_found += 1;
+            }
+            Err(error) => {
+                warn!(owner = %repo.owner, name = %repo.name, %error, "star count snapshot failed");
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Snapshots star counts every `interval`, starting right away, until `stop` fires.
+pub async fn run_snapshots(pool: PgPool, forges: ForgeClients, interval: Duration, stop: StopSignal) {
+    let mut ticks = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = stop.stopped() => return,
+            _ = ticks.tick() => {}
+        }
+
+        match snapshot_star_counts(&pool, &forges).await {
+            Ok(summary) => info!(repositories = summary.repositories, not_found = summary.not_found, skipped = summary.skipped, failed = summary.failed, "snapshotted star counts"),
+            Err(error) => warn!(%error, "star count snapshots failed"),
+        }
+    }
+}
+
+/// Daily star counts of a repository, from its stars or, when none are stored, its snapshots.
+#[derive(Debug, Clone, Default)]
+pub struct StarHistory {
+    pub daily_counts: Vec<(NaiveDate, i64)>,
+    /// Whether `daily_counts` were derived from star count snapshots.
+    pub from_snapshots: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum GetStarHistoryError {
+    #[error("GetDailyStarCount: {source}")]
+    GetDailyStarCount {
+        #[from]
+        source: GetDailyStarCountError,
+    },
+    #[error("GetStarCountSnapshots: {source}")]
+    GetStarCountSnapshots {
+        #[from]
+        source: GetStarCountSnapshotsError,
+    },
+}
+
+/// Stored daily star counts of the repository, falling back to the position curve of its
+/// snapshots when it has no stars, their gaps filled as `fill` tells, see [`snapshot_daily_counts`].
+pub fn get_star_history(conn: &mut PgConnection, repo_id: uuid::Uuid, fill: FillPolicy) -> Result<StarHistory, GetStarHistoryError> {
+    let daily_counts = get_daily_star_count(conn, repo_id)
+        .map_err(|source| GetStarHistoryError::GetDailyStarCount { source })?;
+    if !daily_counts.is_empty() {
+        return Ok(StarHistory { daily_counts, from_snapshots: false });
+    }
+
+    let snapshots: Vec<_> = get_star_count_snapshots(conn, repo_id)
+        .map_err(|source| GetStarHistoryError::GetStarCountSnapshots { source })?
+        .into_iter()
+        .map(|snapshot| (snapshot.taken_at, i64::from(snapshot.stargazer_count)))
+        .collect();
+    Ok(StarHistory { from_snapshots: !snapshots.is_empty(), daily_counts: snapshot_daily_counts(&snapshots, fill) })
+}
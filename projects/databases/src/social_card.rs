@@ -0,0 +1,149 @@
+//! Social card images
+//!
+//! 1200x630 PNG previews for OpenGraph and Twitter cards: the repository
+//! name, its star total and 30-day delta over a sparkline of the cumulative
+//! series. Drawn with plotters' bitmap backend; DejaVu Sans Bold is embedded
+//! so the text renders the same whatever fonts the host has.
+
+use std::sync::OnceLock;
+
+use chrono::NaiveDate;
+use plotters::{
+    coord::Shift,
+    prelude::*,
+    style::{register_font, FontStyle},
+};
+use thiserror::Error;
+
+use crate::chart::{ChartTheme, HexColor};
+
+pub const CARD_WIDTH: u32 = 1200;
+pub const CARD_HEIGHT: u32 = 630;
+const MARGIN: i32 = 60;
+/// Top of the sparkline, below the text.
+const SPARKLINE_TOP: i32 = 330;
+
+const FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+
+/// Backend errors carry no lifetime, any one stands for the buffer's.
+type DrawError = DrawingAreaErrorKind<<BitMapBackend<'static> as DrawingBackend>::ErrorType>;
+
+#[derive(Debug, Error)]
+pub enum RenderSocialCardError {
+    #[error("InvalidEmbeddedFont")]
+    InvalidEmbeddedFont,
+    #[error("Draw: {source}")]
+    Draw {
+        source: DrawError,
+    },
+    #[error("EncodePng: {source}")]
+    EncodePng {
+        source: png::EncodingError,
+    },
+}
+
+/// What the card shows besides the sparkline.
+pub struct SocialCard<'a> {
+    /// `owner/name`
+    pub title: &'a str,
+    pub total_stars: i64,
+    pub last_30_days: i64,
+}
+
+/// PNG card of `card`, `cumulative` being one point per day as built by `analytics::series::cumulative_series`.
+pub fn render_social_card_png(card: &SocialCard, cumulative: &[(NaiveDate, i64)], theme: &ChartTheme) -> Result<Vec<u8>, RenderSocialCardError> {
+    register_embedded_font()?;
+
+    let mut pixels = vec![0; CARD_WIDTH as usize * CARD_HEIGHT as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, (CARD_WIDTH, CARD_HEIGHT)).into_drawing_area();
+        draw_card(&root, card, cumulative, theme)
+            .map_err(|source| RenderSocialCardError::Draw { source })?;
+        root.present()
+            .map_err(|source| RenderSocialCardError::Draw { source })?;
+    }
+
+    encode_png(&pixels)
+}
+
+fn register_embedded_font() -> Result<(), RenderSocialCardError> {
+    if !embedded_font_registered() {
+        return Err(RenderSocialCardError::InvalidEmbeddedFont);
+    }
+    Ok(())
+}
+
+/// Registers the embedded font as `sans-serif`, `false` when it is invalid.
+/// Fonts are registered process-wide, once is enough.
+pub(crate) fn embedded_font_registered() -> bool {
+    static REGISTERED: OnceLock<bool> = OnceLock::new();
+    *REGISTERED.get_or_init(|| register_font("sans-serif", FontStyle::Normal, FONT).is_ok())
+}
+
+fn draw_card(
+    root: &DrawingArea<BitMapBackend, Shift>,
+    card: &SocialCard,
+    cumulative: &[(NaiveDate, i64)],
+    theme: &ChartTheme,
+) -> Result<(), DrawError> {
+    let text = color(&theme.text);
+    let series = theme
+        .series
+        .first()
+        .map_or(RGBColor(0xf1, 0xc4, 0x0f), color);
+
+    root.fill(&color(&theme.background))?;
+    root.draw_text(card.title, &("sans-serif", 52).into_font().color(&text), (MARGIN, MARGIN))?;
+    root.draw_text(&format!("★ {} stars", group_thousands(card.total_stars)), &("sans-serif", 96).into_font().color(&series), (MARGIN, 140))?;
+    root.draw_text(&format!("{:+} in the last 30 days", card.last_30_days), &("sans-serif", 36).into_font().color(&text.mix(0.7)), (MARGIN, 260))?;
+
+    let Some(&(_, max_stars)) = cumulative.last() else {
+        return Ok(());
+    };
+    let sparkline = root.margin(SPARKLINE_TOP, MARGIN, MARGIN, MARGIN);
+    let mut chart = ChartBuilder::on(&sparkline)
+        .build_cartesian_2d(0..cumulative.len().max(2) - 1, 0..max_stars.max(1))?;
+    chart.draw_series(
+        AreaSeries::new(cumulative.iter().enumerate().map(|(day, &(_, stars))| (day, stars)), 0, series.mix(0.2))
+            .border_style(series.stroke_width(5)),
+    )?;
+    Ok(())
+}
+
+pub(crate) fn color(hex: &HexColor) -> RGBColor {
+    let (red, green, blue) = hex.rgb();
+    RGBColor(red, green, blue)
+}
+
+fn encode_png(pixels: &[u8]) -> Result<Vec<u8>, RenderSocialCardError> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, CARD_WIDTH, CARD_HEIGHT);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|source| RenderSocialCardError::EncodePng { source })?;
+    writer
+        .write_image_data(pixels)
+        .map_err(|source| RenderSocialCardError::EncodePng { source })?;
+    writer
+        .finish()
+        .map_err(|source| RenderSocialCardError::EncodePng { source })?;
+    Ok(png)
+}
+
+/// `12345` as `12,345`.
+fn group_thousands(value: i64) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    if value < 0 {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
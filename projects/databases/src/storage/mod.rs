@@ -0,0 +1,219 @@
+//! Storage backends of the star history
+//!
+//! The service runs on PostgreSQL, the queries of `db` need it. A personal
+//! deployment running the `star-tracker` CLI alone can keep its stars in a
+//! SQLite file instead: [`Storage::from_url`] picks the backend from the scheme
+//! of `DATABASE_URL`, `postgres://` or `sqlite://`, and both offer what syncing,
+//! exporting and graphing a repository need through [`StarStorage`].
+//!
+//! PostgreSQL is always built, SQLite only with the `sqlite` feature. A SQLite
+//! file holds a single tenant, and none of the tags, forks, syncs history or
+//! compaction of the PostgreSQL schema.
+
+pub mod postgres;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use std::future::Future;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use interfaces_github_stargazers::forge::Forge;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::db::{
+    repository::queries::{GetRepositoryByIdError, GetRepositoryByNameError, InsertRepositoryError},
+    star::queries::{GetDailyStarCountError, InsertStarsBatchError},
+};
+use crate::db::daily_star_count::queries::RefreshDailyStarCountsError;
+use crate::db::pool::{build_pg_pool, PoolConfig};
+use crate::storage::postgres::PgStorage;
+#[cfg(feature = "sqlite")]
+use crate::storage::sqlite::{OpenSqliteStorageError, SqliteStorage};
+
+/// Repository as every backend stores it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredRepository {
+    pub id: Uuid,
+    pub owner: String,
+    pub name: String,
+    pub forge: Forge,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NewStoredRepository<'a> {
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub forge: Forge,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NewStoredStar<'a> {
+    pub stargazer: &'a str,
+    pub starred_at: NaiveDateTime,
+}
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("GetConnection: {source}")]
+    GetConnection {
+        source: r2d2::Error,
+    },
+    #[error("InsertRepository: {source}")]
+    InsertRepository {
+        source: InsertRepositoryError,
+    },
+    #[error("GetRepositoryByName: {source}")]
+    GetRepositoryByName {
+        source: GetRepositoryByNameError,
+    },
+    #[error("GetRepositoryById: {source}")]
+    GetRepositoryById {
+        source: GetRepositoryByIdError,
+    },
+    #[error("RepositoryNotFound: {repository_id}")]
+    RepositoryNotFound {
+        repository_id: Uuid,
+    },
+    #[error("InsertStarsBatch: {source}")]
+    InsertStarsBatch {
+        source: InsertStarsBatchError,
+    },
+    #[error("RefreshDailyStarCounts: {source}")]
+    RefreshDailyStarCounts {
+        source: RefreshDailyStarCountsError,
+    },
+    #[error("GetDailyStarCount: {source}")]
+    GetDailyStarCount {
+        source: GetDailyStarCountError,
+    },
+}
+
+/// Repositories and stars of one tenant, whatever the database.
+pub trait StarStorage {
+    /// Inserts the repository, or returns the stored one of the same owner and name whatever their case.
+    fn insert_repository(&self, new: NewStoredRepository<'_>) -> impl Future<Output = Result<StoredRepository, StorageError>> + Send;
+
+    /// The repository, owners and names being case-insensitive.
+    fn get_repository_by_name(&self, owner: &str, name: &str) -> impl Future<Output = Result<Option<StoredRepository>, StorageError>> + Send;
+
+    /// Inserts the stars, refreshing `fetched_at` of the stored ones, and returns how many rows were written.
+    fn insert_stars_batch(&self, repository_id: Uuid, stars: &[NewStoredStar<'_>]) -> impl Future<Output = Result<usize, StorageError>> + Send;
+
+    /// Stars per day of the repository, oldest first, days without stars left out.
+    fn get_daily_star_count(&self, repository_id: Uuid) -> impl Future<Output = Result<Vec<(NaiveDate, i64)>, StorageError>> + Send;
+}
+
+/// Database a `DATABASE_URL` points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageBackend {
+    Postgres,
+    /// Path of the database file, `:memory:` for one dropped with its connection.
+    #[cfg(feature = "sqlite")]
+    Sqlite { path: String },
+}
+
+#[derive(Debug, Error)]
+pub enum ParseStorageBackendError {
+    #[error("UnsupportedScheme: {scheme}, expected postgres:// or sqlite://")]
+    UnsupportedScheme {
+        scheme: String,
+    },
+    #[error("BackendNotCompiled: {scheme}, build with the `{feature}` feature")]
+    BackendNotCompiled {
+        scheme: String,
+        feature: &'static str,
+    },
+}
+
+impl StorageBackend {
+    pub fn from_url(database_url: &str) -> Result<Self, ParseStorageBackendError> {
+        let scheme = database_url.split_once(':').map_or(database_url, |(scheme, _)| scheme);
+        match scheme {
+            "postgres" | "postgresql" => Ok(StorageBackend::Postgres),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(StorageBackend::Sqlite {
+                path: database_url.trim_start_matches("sqlite:").trim_start_matches("//").into(),
+            }),
+            #[cfg(not(feature = "sqlite"))]
+            "sqlite" => Err(ParseStorageBackendError::BackendNotCompiled { scheme: scheme.into(), feature: "sqlite" }),
+            _ => Err(ParseStorageBackendError::UnsupportedScheme { scheme: scheme.into() }),
+        }
+    }
+}
+
+/// Storage of the backend picked by `DATABASE_URL`, see [`StorageBackend`].
+pub enum Storage {
+    Postgres(PgStorage),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteStorage),
+}
+
+#[derive(Debug, Error)]
+pub enum OpenStorageError {
+    #[error("ParseStorageBackend: {source}")]
+    ParseStorageBackend {
+        source: ParseStorageBackendError,
+    },
+    #[error("PgPoolBuild: {source}")]
+    PgPoolBuild {
+        source: r2d2::Error,
+    },
+    #[cfg(feature = "sqlite")]
+    #[error("OpenSqliteStorage: {source}")]
+    OpenSqliteStorage {
+        source: OpenSqliteStorageError,
+    },
+}
+
+impl Storage {
+    /// Storage of `tenant_id` in the database of `database_url`, ignored by SQLite. SQLite files
+    /// are created and migrated when needed, PostgreSQL databases by the service.
+    pub fn from_url(database_url: &str, tenant_id: Uuid) -> Result<Self, OpenStorageError> {
+        match StorageBackend::from_url(database_url).map_err(|source| OpenStorageError::ParseStorageBackend { source })? {
+            StorageBackend::Postgres => {
+                let (pool, _) = build_pg_pool(database_url, &PoolConfig { max_size: 2, ..PoolConfig::default() })
+                    .map_err(|source| OpenStorageError::PgPoolBuild { source })?;
+                Ok(Storage::Postgres(PgStorage::new(pool, tenant_id)))
+            }
+            #[cfg(feature = "sqlite")]
+            StorageBackend::Sqlite { path } => SqliteStorage::open(&path)
+                .map(Storage::Sqlite)
+                .map_err(|source| OpenStorageError::OpenSqliteStorage { source }),
+        }
+    }
+}
+
+impl StarStorage for Storage {
+    async fn insert_repository(&self, new: NewStoredRepository<'_>) -> Result<StoredRepository, StorageError> {
+        match self {
+            Storage::Postgres(storage) => storage.insert_repository(new).await,
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(storage) => storage.insert_repository(new).await,
+        }
+    }
+
+    async fn get_repository_by_name(&self, owner: &str, name: &str) -> Result<Option<StoredRepository>, StorageError> {
+        match self {
+            Storage::Postgres(storage) => storage.get_repository_by_name(owner, name).await,
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(storage) => storage.get_repository_by_name(owner, name).await,
+        }
+    }
+
+    async fn insert_stars_batch(&self, repository_id: Uuid, stars: &[NewStoredStar<'_>]) -> Result<usize, StorageError> {
+        match self {
+            Storage::Postgres(storage) => storage.insert_stars_batch(repository_id, stars).await,
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(storage) => storage.insert_stars_batch(repository_id, stars).await,
+        }
+    }
+
+    async fn get_daily_star_count(&self, repository_id: Uuid) -> Result<Vec<(NaiveDate, i64)>, StorageError> {
+        match self {
+            Storage::Postgres(storage) => storage.get_daily_star_count(repository_id).await,
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(storage) => storage.get_daily_star_count(repository_id).await,
+        }
+    }
+}
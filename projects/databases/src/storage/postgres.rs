@@ -0,0 +1,97 @@
+//! PostgreSQL storage, through the queries of `db`
+
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::db::{
+    daily_star_count::queries::refresh_daily_star_counts,
+    repository::{
+        models::{NewRepository, Repository},
+        queries::{get_repository_by_id, get_repository_by_name, insert_repository},
+    },
+    star::{
+        models::NewStar,
+        queries::{get_daily_star_count, insert_stars_batch, StarInsertConfig},
+    },
+    PgPool,
+};
+use crate::storage::{NewStoredRepository, NewStoredStar, StarStorage, StorageError, StoredRepository};
+
+/// Repositories and stars of a tenant in the service's database. Cloning is cheap.
+#[derive(Clone)]
+pub struct PgStorage {
+    pool: PgPool,
+    tenant_id: Uuid,
+}
+
+impl PgStorage {
+    pub fn new(pool: PgPool, tenant_id: Uuid) -> Self {
+        Self { pool, tenant_id }
+    }
+}
+
+impl From<Repository> for StoredRepository {
+    fn from(repo: Repository) -> Self {
+        let forge = repo.forge();
+        StoredRepository { id: repo.id, owner: repo.owner, name: repo.name, forge }
+    }
+}
+
+impl StarStorage for PgStorage {
+    async fn insert_repository(&self, new: NewStoredRepository<'_>) -> Result<StoredRepository, StorageError> {
+        let mut conn = self.pool.get().map_err(|source| StorageError::GetConnection { source })?;
+        insert_repository(&mut conn, &NewRepository {
+            id: Uuid::new_v4(),
+            owner: new.owner,
+            name: new.name,
+            description: None,
+            primary_language: None,
+            license: None,
+            topics: Vec::new(),
+            github_created_at: None,
+            tenant_id: self.tenant_id,
+            github_node_id: None,
+            forge: new.forge.as_str(),
+        })
+            .map(StoredRepository::from)
+            .map_err(|source| StorageError::InsertRepository { source })
+    }
+
+    async fn get_repository_by_name(&self, owner: &str, name: &str) -> Result<Option<StoredRepository>, StorageError> {
+        let mut conn = self.pool.get().map_err(|source| StorageError::GetConnection { source })?;
+        get_repository_by_name(&mut conn, self.tenant_id, owner, name)
+            .await
+            .map(|repo| repo.map(StoredRepository::from))
+            .map_err(|source| StorageError::GetRepositoryByName { source })
+    }
+
+    /// Leaves out the stars of compacted days, already counted, and refreshes the daily star
+    /// counts afterwards, reads only see those.
+    async fn insert_stars_batch(&self, repository_id: Uuid, stars: &[NewStoredStar<'_>]) -> Result<usize, StorageError> {
+        let mut conn = self.pool.get().map_err(|source| StorageError::GetConnection { source })?;
+        let repo = get_repository_by_id(&mut conn, repository_id)
+            .map_err(|source| StorageError::GetRepositoryById { source })?
+            .ok_or(StorageError::RepositoryNotFound { repository_id })?;
+        let fetched_at = Utc::now().naive_utc();
+        let new: Vec<NewStar> = stars
+            .iter()
+            .filter(|star| !repo.is_compacted(star.starred_at))
+            .map(|star| NewStar { repository_id, stargazer: star.stargazer, starred_at: star.starred_at, fetched_at, first_fetched_at: fetched_at, suspected_bot: false })
+            .collect();
+        let written = insert_stars_batch(&mut conn, &new, &StarInsertConfig::default())
+            .map_err(|source| StorageError::InsertStarsBatch { source })?;
+
+        let since = new.iter().map(|star| star.starred_at.date()).min();
+        if since.is_some() {
+            refresh_daily_star_counts(&mut conn, repository_id, since)
+                .map_err(|source| StorageError::RefreshDailyStarCounts { source })?;
+        }
+        Ok(written)
+    }
+
+    async fn get_daily_star_count(&self, repository_id: Uuid) -> Result<Vec<(NaiveDate, i64)>, StorageError> {
+        let mut conn = self.pool.get().map_err(|source| StorageError::GetConnection { source })?;
+        get_daily_star_count(&mut conn, repository_id)
+            .map_err(|source| StorageError::GetDailyStarCount { source })
+    }
+}
@@ -0,0 +1,183 @@
+//! SQLite storage, a single file holding one tenant's repositories and stars
+//!
+//! Its schema is its own, in `migrations_sqlite/`, applied when the file is
+//! opened. Daily star counts are grouped from the stars on read, the tables
+//! stay small enough for it.
+
+use chrono::NaiveDate;
+use diesel::{
+    dsl::{count_star, sql},
+    prelude::*,
+    r2d2::{ConnectionManager, Pool},
+    sql_types::{Date, Text},
+    upsert::excluded,
+    SqliteConnection,
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use interfaces_github_stargazers::forge::Forge;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::db::{
+    repository::queries::{GetRepositoryByNameError, InsertRepositoryError},
+    star::queries::{GetDailyStarCountError, InsertStarsBatchError},
+};
+use crate::storage::{NewStoredRepository, NewStoredStar, StarStorage, StorageError, StoredRepository};
+
+/// SQL migrations of `migrations_sqlite/`, bundled into the binary.
+pub const SQLITE_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations_sqlite");
+
+/// Rows per INSERT, SQLite binds at most 32766 parameters.
+const INSERT_CHUNK_SIZE: usize = 5000;
+
+diesel::table! {
+    repositories (id) {
+        /// UUID as text, SQLite has no such type.
+        id -> Text,
+        owner -> Text,
+        name -> Text,
+        forge -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    stars (repository_id, stargazer) {
+        repository_id -> Text,
+        stargazer -> Text,
+        starred_at -> Timestamp,
+        fetched_at -> Timestamp,
+    }
+}
+
+diesel::allow_tables_to_appear_in_same_query!(repositories, stars);
+
+diesel::define_sql_function! {
+    /// SQLite's `lower`, the unique index on repositories is built with it.
+    fn lower(value: Text) -> Text;
+}
+
+#[derive(Debug, Error)]
+pub enum OpenSqliteStorageError {
+    #[error("PoolBuild: {source}")]
+    PoolBuild {
+        source: r2d2::Error,
+    },
+    #[error("RunPendingMigrations: {source}")]
+    RunPendingMigrations {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+/// Repositories and stars of the database file. Cloning is cheap.
+#[derive(Clone)]
+pub struct SqliteStorage {
+    pool: Pool<ConnectionManager<SqliteConnection>>,
+}
+
+impl SqliteStorage {
+    /// Opens the database file at `path`, created when missing, and applies its pending migrations.
+    pub fn open(path: &str) -> Result<Self, OpenSqliteStorageError> {
+        // A single connection: writers lock the whole file, and `:memory:` databases live in their connection.
+        let pool = Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(path))
+            .map_err(|source| OpenSqliteStorageError::PoolBuild { source })?;
+
+        let mut conn = pool.get().map_err(|source| OpenSqliteStorageError::PoolBuild { source })?;
+        conn.run_pending_migrations(SQLITE_MIGRATIONS)
+            .map_err(|source| OpenSqliteStorageError::RunPendingMigrations { source })?;
+        drop(conn);
+
+        Ok(Self { pool })
+    }
+}
+
+fn stored_repository((id, owner, name, forge): (String, String, String, String)) -> StoredRepository {
+    StoredRepository {
+        // Only ever written from a `Uuid`
+        id: Uuid::parse_str(&id).unwrap_or_default(),
+        owner,
+        name,
+        forge: Forge::parse(&forge).unwrap_or_default(),
+    }
+}
+
+fn find_repository(conn: &mut SqliteConnection, owner: &str, name: &str) -> QueryResult<Option<StoredRepository>> {
+    repositories::table
+        .filter(lower(repositories::owner).eq(lower(owner.trim())))
+        .filter(lower(repositories::name).eq(lower(name.trim())))
+        .select((repositories::id, repositories::owner, repositories::name, repositories::forge))
+        .first::<(String, String, String, String)>(conn)
+        .optional()
+        .map(|repo| repo.map(stored_repository))
+}
+
+impl StarStorage for SqliteStorage {
+    async fn insert_repository(&self, new: NewStoredRepository<'_>) -> Result<StoredRepository, StorageError> {
+        let mut conn = self.pool.get().map_err(|source| StorageError::GetConnection { source })?;
+        diesel::insert_into(repositories::table)
+            .values((
+                repositories::id.eq(Uuid::new_v4().to_string()),
+                repositories::owner.eq(new.owner.trim()),
+                repositories::name.eq(new.name.trim()),
+                repositories::forge.eq(new.forge.as_str()),
+            ))
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .map_err(|source| StorageError::InsertRepository { source: InsertRepositoryError::InsertRepository { source } })?;
+
+        find_repository(&mut conn, new.owner, new.name)
+            .and_then(|repo| repo.ok_or(diesel::result::Error::NotFound))
+            .map_err(|source| StorageError::InsertRepository { source: InsertRepositoryError::InsertRepository { source } })
+    }
+
+    async fn get_repository_by_name(&self, owner: &str, name: &str) -> Result<Option<StoredRepository>, StorageError> {
+        let mut conn = self.pool.get().map_err(|source| StorageError::GetConnection { source })?;
+        find_repository(&mut conn, owner, name)
+            .map_err(|source| StorageError::GetRepositoryByName { source: GetRepositoryByNameError::GetRepositoryByName { source } })
+    }
+
+    /// A stargazer must appear once per batch. Chunks are written in a transaction, all or none.
+    async fn insert_stars_batch(&self, repository_id: Uuid, new: &[NewStoredStar<'_>]) -> Result<usize, StorageError> {
+        let mut conn = self.pool.get().map_err(|source| StorageError::GetConnection { source })?;
+        let repository_id = repository_id.to_string();
+        let fetched_at = chrono::Utc::now().naive_utc();
+
+        conn.transaction(|conn| {
+            let mut written = 0;
+            for (index, chunk) in new.chunks(INSERT_CHUNK_SIZE).enumerate() {
+                let first_row = index * INSERT_CHUNK_SIZE;
+                let rows: Vec<_> = chunk
+                    .iter()
+                    .map(|star| (
+                        stars::repository_id.eq(&repository_id),
+                        stars::stargazer.eq(star.stargazer),
+                        stars::starred_at.eq(star.starred_at),
+                        stars::fetched_at.eq(fetched_at),
+                    ))
+                    .collect();
+                written += diesel::insert_into(stars::table)
+                    .values(rows)
+                    .on_conflict((stars::repository_id, stars::stargazer))
+                    .do_update()
+                    .set(stars::fetched_at.eq(excluded(stars::fetched_at)))
+                    .execute(conn)
+                    .map_err(|source| InsertStarsBatchError::InsertChunk { first_row, end_row: first_row + chunk.len(), source })?;
+            }
+            Ok(written)
+        })
+        .map_err(|source| StorageError::InsertStarsBatch { source })
+    }
+
+    async fn get_daily_star_count(&self, repository_id: Uuid) -> Result<Vec<(NaiveDate, i64)>, StorageError> {
+        let mut conn = self.pool.get().map_err(|source| StorageError::GetConnection { source })?;
+        stars::table
+            .filter(stars::repository_id.eq(repository_id.to_string()))
+            .group_by(sql::<Date>("DATE(starred_at)"))
+            .select((sql::<Date>("DATE(starred_at)"), count_star()))
+            .order_by(sql::<Date>("DATE(starred_at)"))
+            .load::<(NaiveDate, i64)>(&mut conn)
+            .map_err(|source| StorageError::GetDailyStarCount { source: GetDailyStarCountError::GetDailyStarCount { source } })
+    }
+}
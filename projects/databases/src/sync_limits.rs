@@ -0,0 +1,95 @@
+//! Sync job limits
+//!
+//! Safety limits on a single sync job, so a request against a huge repository
+//! can't keep a worker busy for days. The defaults come from `SYNC_MAX_PAGES`,
+//! `SYNC_MAX_DURATION_SECONDS` and `SYNC_MAX_STARS`, none unless set, and can
+//! be adjusted at runtime through `PATCH /admin/config`. A sync request may set
+//! its own, replacing the defaults for its job.
+//!
+//! Limits are checked once a page is stored, a job reaching one stops there and
+//! ends `partially_completed` with the limit as its stop reason. Pages and stars
+//! count from the start of the job, resumed attempts included, the duration from
+//! the start of the current attempt. A `partially_completed` job resumed gets the
+//! same budget again, counted from where it stopped.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Limits of a sync job, unset ones don't apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct SyncLimits {
+    /// Pages fetched by the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_pages: Option<u32>,
+    /// Seconds spent fetching and storing pages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_seconds: Option<u64>,
+    /// Stars fetched by the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_stars: Option<u64>,
+}
+
+impl SyncLimits {
+    /// These limits, replaced by the ones `overrides` sets.
+    pub fn overridden_by(self, overrides: SyncLimits) -> SyncLimits {
+        SyncLimits {
+            max_pages: overrides.max_pages.or(self.max_pages),
+            max_duration_seconds: overrides.max_duration_seconds.or(self.max_duration_seconds),
+            max_stars: overrides.max_stars.or(self.max_stars),
+        }
+    }
+
+    /// These limits counted from a job's `pages` pages and `stars` stars on, for a job resumed
+    /// past the limit it stopped at.
+    pub fn past(self, pages: u32, stars: u64) -> SyncLimits {
+        SyncLimits {
+            max_pages: self.max_pages.map(|max| max.saturating_add(pages)),
+            max_duration_seconds: self.max_duration_seconds,
+            max_stars: self.max_stars.map(|max| max.saturating_add(stars)),
+        }
+    }
+
+    /// First limit reached by a job at `pages` pages and `stars` stars, running for `elapsed`.
+    pub fn reached(&self, pages: u32, stars: u64, elapsed: Duration) -> Option<SyncStopReason> {
+        if self.max_pages.is_some_and(|max| pages >= max) {
+            return Some(SyncStopReason::MaxPages);
+        }
+        if self.max_stars.is_some_and(|max| stars >= max) {
+            return Some(SyncStopReason::MaxStars);
+        }
+        if self.max_duration_seconds.is_some_and(|max| elapsed >= Duration::from_secs(max)) {
+            return Some(SyncStopReason::MaxDuration);
+        }
+        None
+    }
+}
+
+/// Limit that stopped a `partially_completed` job, stored as text in `sync_jobs.stop_reason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStopReason {
+    MaxPages,
+    MaxDuration,
+    MaxStars,
+}
+
+impl SyncStopReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncStopReason::MaxPages => "max_pages",
+            SyncStopReason::MaxDuration => "max_duration",
+            SyncStopReason::MaxStars => "max_stars",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "max_pages" => Some(SyncStopReason::MaxPages),
+            "max_duration" => Some(SyncStopReason::MaxDuration),
+            "max_stars" => Some(SyncStopReason::MaxStars),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Locks of the repositories being synced, across instances
+//!
+//! Replicas behind a load balancer share the database but not their sync
+//! queues, two of them could sync the same repository at once. A sync holds a
+//! PostgreSQL session advisory lock keyed by its repository's ID while it
+//! runs, a sync of a repository locked by another session is turned down
+//! rather than waited for, keeping its worker free.
+//!
+//! The lock is held by the sync's connection through a [`RepositoryLock`],
+//! released once the sync is over, panics and dropped futures included, so a
+//! pooled connection never goes back to the pool still holding it. An instance
+//! going away releases its locks along with its sessions.
+
+use std::ops::{Deref, DerefMut};
+
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Bool},
+    PgConnection, QueryableByName, RunQueryDsl,
+};
+use thiserror::Error;
+use tracing::warn;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum SyncLockError {
+    #[error("TryAdvisoryLock: {source}")]
+    TryAdvisoryLock {
+        source: diesel::result::Error,
+    },
+    #[error("AdvisoryUnlock: {source}")]
+    AdvisoryUnlock {
+        source: diesel::result::Error,
+    },
+    #[error("NotLocked: repository {repository_id}")]
+    NotLocked {
+        repository_id: Uuid,
+    },
+}
+
+#[derive(QueryableByName)]
+struct Locked {
+    #[diesel(sql_type = Bool)]
+    locked: bool,
+}
+
+/// Advisory lock key of repository `repository_id`, its ID folded into 64 bits.
+pub fn lock_key(repository_id: Uuid) -> i64 {
+    let (high, low) = repository_id.as_u64_pair();
+    (high ^ low) as i64
+}
+
+/// Locks the repository for the session of `conn`, `false` when another session holds it.
+pub fn try_lock_repository(conn: &mut PgConnection, repository_id: Uuid) -> Result<bool, SyncLockError> {
+    sql_query("SELECT pg_try_advisory_lock($1) AS locked")
+        .bind::<BigInt, _>(lock_key(repository_id))
+        .get_result::<Locked>(conn)
+        .map(|row| row.locked)
+        .map_err(|source| SyncLockError::TryAdvisoryLock { source })
+}
+
+/// Releases the lock [`try_lock_repository`] took on the same connection.
+pub fn unlock_repository(conn: &mut PgConnection, repository_id: Uuid) -> Result<(), SyncLockError> {
+    let released = sql_query("SELECT pg_advisory_unlock($1) AS locked")
+        .bind::<BigInt, _>(lock_key(repository_id))
+        .get_result::<Locked>(conn)
+        .map_err(|source| SyncLockError::AdvisoryUnlock { source })?;
+
+    if !released.locked {
+        return Err(SyncLockError::NotLocked { repository_id });
+    }
+    Ok(())
+}
+
+/// Lock of a repository on the session of the connection it borrows, used in its place
+/// while held and released when dropped.
+pub struct RepositoryLock<'a> {
+    conn: &'a mut PgConnection,
+    repository_id: Uuid,
+}
+
+impl<'a> RepositoryLock<'a> {
+    /// Locks the repository for the session of `conn`, `None` when another session holds it.
+    pub fn try_acquire(conn: &'a mut PgConnection, repository_id: Uuid) -> Result<Option<Self>, SyncLockError> {
+        if !try_lock_repository(conn, repository_id)? {
+            return Ok(None);
+        }
+        Ok(Some(Self { conn, repository_id }))
+    }
+}
+
+impl Deref for RepositoryLock<'_> {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &PgConnection {
+        self.conn
+    }
+}
+
+impl DerefMut for RepositoryLock<'_> {
+    fn deref_mut(&mut self) -> &mut PgConnection {
+        self.conn
+    }
+}
+
+impl Drop for RepositoryLock<'_> {
+    fn drop(&mut self) {
+        if let Err(error) = unlock_repository(self.conn, self.repository_id) {
+            warn!(repository_id = %self.repository_id, %error, "repository lock not released");
+        }
+    }
+}
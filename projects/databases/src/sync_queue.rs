@@ -0,0 +1,572 @@
+//! Queue of sync tasks run by a fixed pool of workers
+//!
+//! Every sync goes through the queue, so at most `workers` of them hit the
+//! forges' APIs at once whatever the number of incoming requests. Tasks are
+//! taken by priority, then in submission order.
+//!
+//! On shutdown, waiting tasks are turned down and running ones stop after
+//! their current page, leaving their job `interrupted` and resumable.
+//!
+//! A task panicking fails alone with a `500`, its worker going on with the
+//! next one. Its job stays `running` until the service restarts.
+
+use std::{
+    any::Any,
+    cmp::Ordering,
+    collections::BinaryHeap,
+    panic::AssertUnwindSafe,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use axum::http::StatusCode;
+use chrono::{NaiveDateTime, TimeDelta};
+use diesel::PgConnection;
+use futures_util::FutureExt;
+use interfaces_github_stargazers::forge::Forge;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+use tokio::{
+    sync::{oneshot, watch, Semaphore},
+    task::JoinHandle,
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::chart_cache::ChartCache;
+use crate::db::{
+    repository::models::Repository,
+    sync_job::{
+        models::{SyncJob, SyncJobStatus},
+        queries::{get_unfinished_sync_jobs, transition_sync_job, GetUnfinishedSyncJobsError, TransitionSyncJobError},
+    },
+    sync_run::models::SyncTrigger,
+    PgPool,
+};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::endpoints::github::repo_stars::{
+    jobs::resume::index::{resume_sync_job, ResumeSyncJobError},
+    update::index::{sync_repo_stargazers, SyncRepoStargazersError, SyncSummary},
+};
+use crate::forges::ForgeClients;
+use crate::forks::sync_forks;
+use crate::live::{LiveUpdates, PageSink};
+use crate::notifier::Notifier;
+use crate::privacy::StargazerIds;
+use crate::sync_limits::SyncLimits;
+use crate::sync_lock::{RepositoryLock, SyncLockError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+pub enum SyncTask {
+    /// Full sync of a repository tracked by the tenant, `batch_id` is set for owner-wide batches.
+    Repository {
+        tenant_id: Uuid,
+        forge: Forge,
+        owner: String,
+        name: String,
+        batch_id: Option<Uuid>,
+        /// Recorded in the repository's sync history.
+        trigger: SyncTrigger,
+        /// Starts or changes fork tracking of the repository, see [`crate::forks`].
+        forks_min_stars: Option<u32>,
+        /// Where the fetched pages are streamed as they are stored, the caller's.
+        pages: PageSink,
+        /// Limits of the job, the request's over the service's, see [`crate::sync_limits`].
+        limits: SyncLimits,
+    },
+    /// Continuation of a failed, interrupted or partially completed job, already marked as pending.
+    Resume {
+        job: Box<SyncJob>,
+        repo: Box<Repository>,
+        limits: SyncLimits,
+    },
+}
+
+impl SyncTask {
+    fn repository(&self) -> (&str, &str) {
+        match self {
+            SyncTask::Repository { owner, name, .. } => (owner, name),
+            SyncTask::Resume { repo, .. } => (&repo.owner, &repo.name),
+        }
+    }
+
+    fn tenant_id(&self) -> Uuid {
+        match self {
+            SyncTask::Repository { tenant_id, .. } => *tenant_id,
+            SyncTask::Resume { repo, .. } => repo.tenant_id,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RunSyncTaskError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        source: DbConnRejection,
+    },
+    #[error("SyncRepoStargazers: {source}")]
+    SyncRepoStargazers {
+        source: SyncRepoStargazersError,
+    },
+    #[error("ResumeSyncJob: {source}")]
+    ResumeSyncJob {
+        source: ResumeSyncJobError,
+    },
+    #[error("ShuttingDown")]
+    ShuttingDown,
+    #[error("Panicked: {message}")]
+    Panicked {
+        message: String,
+    },
+}
+
+impl RunSyncTaskError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            RunSyncTaskError::Checkout { source: DbConnRejection::GetConnectionFromPool { .. } } => StatusCode::SERVICE_UNAVAILABLE,
+            RunSyncTaskError::Checkout { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            RunSyncTaskError::SyncRepoStargazers { source } => source.status_code(),
+            RunSyncTaskError::ResumeSyncJob { source } => source.status_code(),
+            RunSyncTaskError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+            RunSyncTaskError::Panicked { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Whether another worker or instance was syncing the repository, see [`crate::sync_lock`].
+    pub fn is_sync_in_progress(&self) -> bool {
+        matches!(
+            self,
+            RunSyncTaskError::SyncRepoStargazers { source: SyncRepoStargazersError::SyncInProgress { .. } }
+                | RunSyncTaskError::ResumeSyncJob { source: ResumeSyncJobError::SyncInProgress { .. } }
+        )
+    }
+}
+
+pub type SyncTaskResult = Result<SyncSummary, RunSyncTaskError>;
+
+struct QueuedTask {
+    task: SyncTask,
+    priority: SyncPriority,
+    /// Submission order, breaks ties between tasks of the same priority.
+    seq: u64,
+    responder: oneshot::Sender<SyncTaskResult>,
+}
+
+impl Ord for QueuedTask {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for QueuedTask {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for QueuedTask {
+    fn eq(&self, other: &Self) -> bool {
+        self.seq == other.seq
+    }
+}
+
+impl Eq for QueuedTask {}
+
+struct SyncQueueInner {
+    pending: Mutex<BinaryHeap<QueuedTask>>,
+    /// One permit per pending task, workers wait on it.
+    available: Semaphore,
+    next_seq: AtomicU64,
+    running: AtomicUsize,
+    workers: usize,
+    /// Set once on shutdown, only while holding the `pending` lock.
+    stop: watch::Sender<bool>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Tells running syncs that the service is shutting down.
+#[derive(Clone)]
+pub struct StopSignal {
+    receiver: watch::Receiver<bool>,
+}
+
+impl StopSignal {
+    pub fn is_stopped(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Resolves once shutdown has started.
+    pub async fn stopped(&self) {
+        let mut receiver = self.receiver.clone();
+        // The sender lives as long as the queue, an error means it is gone anyway.
+        let _ = receiver.wait_for(|stopped| *stopped).await;
+    }
+}
+
+/// What a sync stores besides the stars.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StargazerDetails {
+    /// Company and location, see [`SyncContext::collects_profiles`].
+    pub profiles: bool,
+    /// Public email, see [`SyncContext::collects_emails`].
+    pub emails: bool,
+    /// Whether it looks like a bot, see [`crate::bots`].
+    pub bots: bool,
+    /// Opened and closed issues and pull requests of the repository, see [`crate::activity`].
+    pub activity: bool,
+}
+
+/// Services a running sync uses besides its database connection.
+pub struct SyncContext {
+    pub forges: ForgeClients,
+    pub notifier: Notifier,
+    pub stop: StopSignal,
+    pub live: LiveUpdates,
+    pub collect: StargazerDetails,
+    pub stargazer_ids: StargazerIds,
+    /// Charts of the synced repositories, dropped once their sync ends.
+    pub charts: ChartCache,
+}
+
+impl SyncContext {
+    /// Profiles are asked for on GitHub, unless stargazers are hashed for privacy. They are stored
+    /// by login, which other forges' users would share.
+    pub fn collects_profiles(&self, forge: Forge) -> bool {
+        self.collect.profiles && !self.stargazer_ids.is_hashed() && forge == Forge::GitHub
+    }
+
+    /// Emails are stored on the same terms as profiles, and only when asked for explicitly.
+    pub fn collects_emails(&self, forge: Forge) -> bool {
+        self.collect.emails && !self.stargazer_ids.is_hashed() && forge == Forge::GitHub
+    }
+
+    /// Bots are flagged from the logins before they are hashed, so on every forge and in every mode.
+    pub fn flags_bots(&self) -> bool {
+        self.collect.bots
+    }
+
+    /// Accounts' creation dates are only looked up on GitHub.
+    pub fn checks_account_age(&self, forge: Forge) -> bool {
+        self.collect.bots && forge == Forge::GitHub
+    }
+
+    /// Activity is only fetched from GitHub, once it can be called, and when asked for explicitly.
+    pub fn collects_activity(&self, forge: Forge) -> bool {
+        self.collect.activity && self.fetches_releases(forge)
+    }
+
+    /// Releases are only fetched from GitHub, once it can be called.
+    pub fn fetches_releases(&self, forge: Forge) -> bool {
+        forge == Forge::GitHub && self.forges.is_available(Forge::GitHub)
+    }
+}
+
+#[derive(Clone)]
+pub struct SyncQueue {
+    inner: Arc<SyncQueueInner>,
+}
+
+/// Snapshot of the queue, served by the jobs API.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct SyncQueueStats {
+    pub workers: usize,
+    pub running: usize,
+    pub queued: Vec<QueuedTaskInfo>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct QueuedTaskInfo {
+    pub owner: String,
+    pub name: String,
+    pub priority: SyncPriority,
+}
+
+/// How far a sync job got, against the repository's star count reported by its forge.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct JobProgress {
+    pub stars_fetched: i64,
+    /// Star count the forge reported with the last stored page, unset before the first one.
+    pub estimated_total_stars: Option<i64>,
+    /// Capped at 100, stars given during the sync can make it fetch more than estimated.
+    pub percent_complete: Option<f64>,
+    /// When the remaining stars should be fetched at the pace so far, unset until a pace is measured.
+    pub eta: Option<NaiveDateTime>,
+    /// Seconds the pace was measured over, unset until a pace is measured like the rates below.
+    pub elapsed_seconds: Option<f64>,
+    pub stars_per_second: Option<f64>,
+    pub pages_per_minute: Option<f64>,
+    /// Transient forge failures retried so far, left at 0 by [`JobProgress::new`].
+    pub retries: i64,
+}
+
+/// Stars and pages fetched over `elapsed`, what progress is measured against.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncPace {
+    pub stars: i64,
+    pub pages: i64,
+    pub elapsed: TimeDelta,
+}
+
+impl JobProgress {
+    /// Progress of `stars_fetched` out of `estimated_total_stars`, the ETA assuming
+    /// `pace` holds until the end.
+    pub fn new(stars_fetched: i64, estimated_total_stars: Option<i64>, pace: SyncPace, now: NaiveDateTime) -> Self {
+        let percent_complete = estimated_total_stars.map(|total| match total {
+            ..=0 => 100.0,
+            total => (stars_fetched as f64 / total as f64 * 100.0).min(100.0),
+        });
+
+        // A pace needs a page fetched over some time.
+        let seconds = Some(pace.elapsed.num_milliseconds() as f64 / 1000.0)
+            .filter(|seconds| pace.pages > 0 && *seconds > 0.0);
+        let stars_per_second = seconds.map(|seconds| pace.stars as f64 / seconds);
+        let pages_per_minute = seconds.map(|seconds| pace.pages as f64 * 60.0 / seconds);
+
+        let eta = estimated_total_stars
+            .zip(stars_per_second.filter(|rate| *rate > 0.0))
+            .map(|(total, rate)| {
+                let remaining = (total - stars_fetched).max(0);
+                now + TimeDelta::milliseconds((remaining as f64 / rate * 1000.0) as i64)
+            });
+
+        Self { stars_fetched, estimated_total_stars, percent_complete, eta, elapsed_seconds: seconds, stars_per_second, pages_per_minute, retries: 0 }
+    }
+}
+
+impl SyncQueue {
+    /// Creates the queue and spawns its `workers` workers.
+    #[allow(clippy::too_many_arguments)]
+    pub fn start(workers: usize, pool: PgPool, forges: ForgeClients, notifier: Notifier, live: LiveUpdates, collect: StargazerDetails, stargazer_ids: StargazerIds, charts: ChartCache) -> Self {
+        let queue = Self {
+            inner: Arc::new(SyncQueueInner {
+                pending: Mutex::new(BinaryHeap::new()),
+                available: Semaphore::new(0),
+                next_seq: AtomicU64::new(0),
+                running: AtomicUsize::new(0),
+                workers,
+                stop: watch::Sender::new(false),
+                handles: Mutex::new(Vec::new()),
+            }),
+        };
+
+        let handles = (0..workers)
+            .map(|_| {
+                let context = SyncContext {
+                    forges: forges.clone(),
+                    notifier: notifier.clone(),
+                    stop: queue.stop_signal(),
+                    live: live.clone(),
+                    collect,
+                    stargazer_ids: stargazer_ids.clone(),
+                    charts: charts.clone(),
+                };
+                tokio::spawn(run_worker(queue.clone(), pool.clone(), context))
+            })
+            .collect();
+        *queue.lock_handles() = handles;
+
+        queue
+    }
+
+    pub fn stop_signal(&self) -> StopSignal {
+        StopSignal { receiver: self.inner.stop.subscribe() }
+    }
+
+    /// Turns down waiting tasks and asks running ones to stop after their current page.
+    pub fn shutdown(&self) {
+        let mut pending = self.lock_pending();
+        self.inner.stop.send_replace(true);
+
+        for queued in pending.drain() {
+            let _ = queued.responder.send(Err(RunSyncTaskError::ShuttingDown));
+        }
+    }
+
+    /// Waits up to `grace` for the workers to finish, returns whether they all did.
+    pub async fn drain(&self, grace: Duration) -> bool {
+        let handles = std::mem::take(&mut *self.lock_handles());
+
+        tokio::time::timeout(grace, async {
+            for handle in handles {
+                let _ = handle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    /// Queues `task`, its outcome is sent on the returned channel once a worker ran it.
+    ///
+    /// Dropping the receiver doesn't cancel the task.
+    pub fn enqueue(&self, task: SyncTask, priority: SyncPriority) -> oneshot::Receiver<SyncTaskResult> {
+        let (responder, receiver) = oneshot::channel();
+        let seq = self.inner.next_seq.fetch_add(1, AtomicOrdering::Relaxed);
+
+        let mut pending = self.lock_pending();
+        if *self.inner.stop.borrow() {
+            let _ = responder.send(Err(RunSyncTaskError::ShuttingDown));
+            return receiver;
+        }
+
+        pending.push(QueuedTask { task, priority, seq, responder });
+        self.inner.available.add_permits(1);
+
+        receiver
+    }
+
+    pub fn stats(&self) -> SyncQueueStats {
+        self.stats_matching(|_| true)
+    }
+
+    /// Like [`SyncQueue::stats`], but only listing the tenant's queued tasks. Workers are shared by tenants.
+    pub fn tenant_stats(&self, tenant_id: Uuid) -> SyncQueueStats {
+        self.stats_matching(|task| task.tenant_id() == tenant_id)
+    }
+
+    fn stats_matching(&self, listed: impl Fn(&SyncTask) -> bool) -> SyncQueueStats {
+        let pending = self.lock_pending();
+        let mut queued: Vec<&QueuedTask> = pending.iter().filter(|queued| listed(&queued.task)).collect();
+        queued.sort_by(|a, b| b.cmp(a));
+
+        SyncQueueStats {
+            workers: self.inner.workers,
+            running: self.inner.running.load(AtomicOrdering::Relaxed),
+            queued: queued
+                .into_iter()
+                .map(|queued| {
+                    let (owner, name) = queued.task.repository();
+                    QueuedTaskInfo { owner: owner.into(), name: name.into(), priority: queued.priority }
+                })
+                .collect(),
+        }
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, BinaryHeap<QueuedTask>> {
+        // A worker panicking while holding the lock leaves the heap itself intact.
+        self.inner.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lock_handles(&self) -> std::sync::MutexGuard<'_, Vec<JoinHandle<()>>> {
+        self.inner.handles.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum InterruptAbandonedSyncJobsError {
+    #[error("GetUnfinishedSyncJobs: {source}")]
+    GetUnfinishedSyncJobs {
+        source: GetUnfinishedSyncJobsError,
+    },
+    #[error("LockRepository: {source}")]
+    LockRepository {
+        source: SyncLockError,
+    },
+    #[error("TransitionSyncJob: {source}")]
+    TransitionSyncJob {
+        source: TransitionSyncJobError,
+    },
+}
+
+/// Interrupts the jobs a previous run of the service left pending or running, e.g. killed
+/// mid-sync or never picked up by a worker, so they can be resumed. Returns how many were.
+///
+/// Jobs of a repository another instance is syncing are left alone. A replica starting
+/// while another still has resumed jobs queued interrupts them too, their resume then fails.
+pub fn interrupt_abandoned_sync_jobs(conn: &mut PgConnection) -> Result<usize, InterruptAbandonedSyncJobsError> {
+    let jobs = get_unfinished_sync_jobs(conn)
+        .map_err(|source| InterruptAbandonedSyncJobsError::GetUnfinishedSyncJobs { source })?;
+
+    let mut interrupted = 0;
+    for job in jobs {
+        let Some(mut lock) = RepositoryLock::try_acquire(conn, job.repository_id)
+            .map_err(|source| InterruptAbandonedSyncJobsError::LockRepository { source })? else {
+            continue;
+        };
+        transition_sync_job(&mut lock, job.id, SyncJobStatus::Interrupted, None)
+            .map_err(|source| InterruptAbandonedSyncJobsError::TransitionSyncJob { source })?;
+        interrupted += 1;
+    }
+    Ok(interrupted)
+}
+
+async fn run_worker(queue: SyncQueue, pool: PgPool, context: SyncContext) {
+    loop {
+        let permit = tokio::select! {
+            permit = queue.inner.available.acquire() => permit,
+            _ = context.stop.stopped() => return,
+        };
+        let Ok(permit) = permit else {
+            return;
+        };
+        permit.forget();
+
+        let Some(queued) = queue.lock_pending().pop() else {
+            continue;
+        };
+
+        queue.inner.running.fetch_add(1, AtomicOrdering::Relaxed);
+        let (owner, name) = {
+            let (owner, name) = queued.task.repository();
+            (owner.to_string(), name.to_string())
+        };
+
+        // A panicking task fails alone, the worker goes on with the next one.
+        let result = AssertUnwindSafe(run_sync_task(&pool, &context, queued.task))
+            .catch_unwind()
+            .await
+            .unwrap_or_else(|panic| Err(RunSyncTaskError::Panicked { message: panic_message(&*panic) }));
+        queue.inner.running.fetch_sub(1, AtomicOrdering::Relaxed);
+
+        match &result {
+            Ok(summary) => info!(%owner, %name, job_id = %summary.job_id, stars = summary.stars, "sync task done"),
+            Err(error) => error!(%owner, %name, %error, "sync task failed"),
+        }
+
+        // The submitter may have stopped waiting, the outcome is logged above.
+        let _ = queued.responder.send(result);
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+async fn run_sync_task(pool: &PgPool, context: &SyncContext, task: SyncTask) -> SyncTaskResult {
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+        .await
+        .map_err(|source| RunSyncTaskError::Checkout { source })?;
+
+    match task {
+        SyncTask::Repository { tenant_id, forge, owner, name, batch_id, trigger, forks_min_stars, mut pages, limits } => {
+            let mut summary = sync_repo_stargazers(&mut conn, context, tenant_id, forge, &owner, &name, batch_id, trigger, &mut pages, limits)
+                .await
+                .map_err(|source| RunSyncTaskError::SyncRepoStargazers { source })?;
+
+            // The repository itself is synced, failing forks don't fail its sync.
+            match sync_forks(&mut conn, context, summary.repository_id, forks_min_stars, limits).await {
+                Ok(forks) => summary.forks = forks,
+                Err(error) => warn!(%owner, %name, %error, "fork tracking failed"),
+            }
+            Ok(summary)
+        }
+        SyncTask::Resume { job, repo, limits } => resume_sync_job(&mut conn, context, &job, &repo, limits)
+            .await
+            .map_err(|source| RunSyncTaskError::ResumeSyncJob { source }),
+    }
+}
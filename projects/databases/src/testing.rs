@@ -0,0 +1,874 @@
+//! Harness of the integration tests, with the `test-utils` feature
+//!
+//! [`TestDatabase`] is a migrated PostgreSQL database of its own: a fresh
+//! database on the server of `TEST_DATABASE_URL` when it is set, else a
+//! `postgres:16` container started through testcontainers, which needs
+//! Docker. [`MockGitHub`] answers the GraphQL API from a wiremock server with
+//! the stargazers it is given, the GitLab and Gitea clients pointing at it
+//! too. [`TestApp`] serves the router of the service over both, requests
+//! going through it in-process, without a socket. [`ScriptedFetcher`] skips
+//! HTTP altogether, for unit tests of pagination and forge failures.
+//!
+//! Downstream crates enable the feature in their dev-dependencies to test
+//! against the real routes:
+//!
+//! ```no_run
+//! # async fn sync() -> Result<(), Box<dyn std::error::Error>> {
+//! use projects_databases::testing::{MockGitHub, MockRepository, TestApp, TestDatabase};
+//!
+//! let database = TestDatabase::start().await?;
+//! let github = MockGitHub::start().await;
+//! github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars("2025-01-01".parse()?, &[3, 5])).await;
+//!
+//! let app = TestApp::spawn(&database, &github)?;
+//! let response = app.post_json("/github/repo_stars/update", &serde_json::json!({ "owner": "acme", "name": "rocket" })).await?;
+//! assert_eq!(response.status, 200);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use diesel::{sql_query, Connection, PgConnection, RunQueryDsl};
+use interfaces_github_stargazers::api_url::{GitHubApiUrl, ParseGitHubApiUrlError};
+use interfaces_github_stargazers::client::{GitHubAuth, GitHubClient, GitHubClientConfig, NewGitHubClientError};
+use interfaces_github_stargazers::fetcher::{FetchFuture, StargazerFetchError, StargazerFetcher};
+use interfaces_github_stargazers::forge::{Forge, ForgeErrorKind, ForgeRepository, ForgeStargazer, StargazerPage};
+use interfaces_github_stargazers::gitea::GiteaClient;
+use interfaces_github_stargazers::gitlab::GitLabClient;
+use interfaces_github_stargazers::rest::ParseRestApiUrlError;
+use interfaces_github_stargazers::retry::RetryConfig;
+use interfaces_github_stargazers::token_pool::TokenPool;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt, TestcontainersError};
+use thiserror::Error;
+use tower::ServiceExt;
+use tracing::warn;
+use uuid::Uuid;
+use wiremock::{
+    matchers::{body_partial_json, body_string_contains, method, path, path_regex},
+    Mock, MockServer, Respond, ResponseTemplate,
+};
+
+use crate::chart_cache::{ChartCache, ChartCacheConfig};
+use crate::db::{
+    migrations::{run_pending_migrations, RunPendingMigrationsError},
+    pool::{build_pg_pool, PoolConfig, PoolMetrics, ReadPool},
+    star::queries::StarInsertConfig,
+    PgPool,
+};
+use crate::endpoints::{limits::RequestLimits, tenant::TenantAuth};
+use crate::forges::ForgeClients;
+use crate::job_retention::SyncJobRetention;
+use crate::live::LiveUpdates;
+use crate::notifier::Notifier;
+use crate::privacy::StargazerIds;
+use crate::router::{build_router, RouterDependencies};
+use crate::runtime_config::{AdjustableSettings, RuntimeConfig, ServiceSettings};
+use crate::sync_limits::SyncLimits;
+use crate::sync_queue::{StargazerDetails, SyncQueue};
+use crate::validation::WebhookUrlPolicy;
+
+/// Tag of the PostgreSQL image, the version `scripts/database/run_postgres.sh` runs.
+const POSTGRES_TAG: &str = "16-alpine";
+
+#[derive(Debug, Error)]
+pub enum TestDatabaseError {
+    #[error("StartContainer: {source}")]
+    StartContainer {
+        source: TestcontainersError,
+    },
+    #[error("InvalidServerUrl: {source}")]
+    InvalidServerUrl {
+        source: url::ParseError,
+    },
+    #[error("ConnectToServer: {source}")]
+    ConnectToServer {
+        source: diesel::ConnectionError,
+    },
+    #[error("CreateDatabase: {source}")]
+    CreateDatabase {
+        source: diesel::result::Error,
+    },
+    #[error("BuildPool: {source}")]
+    BuildPool {
+        source: r2d2::Error,
+    },
+    #[error("GetConnectionFromPool: {source}")]
+    GetConnectionFromPool {
+        source: r2d2::Error,
+    },
+    #[error("RunPendingMigrations: {source}")]
+    RunPendingMigrations {
+        source: RunPendingMigrationsError,
+    },
+}
+
+/// Migrated database used by a single test, gone once dropped.
+pub struct TestDatabase {
+    url: String,
+    pool: PgPool,
+    pool_metrics: PoolMetrics,
+    /// Database created on the server of `TEST_DATABASE_URL`, dropped along.
+    created: Option<CreatedDatabase>,
+    /// Stopped and removed when dropped, after the pool.
+    _container: Option<ContainerAsync<Postgres>>,
+}
+
+struct CreatedDatabase {
+    server_url: String,
+    name: String,
+}
+
+impl TestDatabase {
+    /// A database on `TEST_DATABASE_URL` when set, in a new container otherwise.
+    pub async fn start() -> Result<Self, TestDatabaseError> {
+        match std::env::var("TEST_DATABASE_URL") {
+            Ok(server_url) => Self::create_on(&server_url),
+            Err(_) => Self::start_container().await,
+        }
+    }
+
+    /// Creates a database with a unique name on the server at `server_url`, whose user may create databases.
+    pub fn create_on(server_url: &str) -> Result<Self, TestDatabaseError> {
+        let name = format!("star_tracker_test_{}", Uuid::new_v4().simple());
+        let mut url = url::Url::parse(server_url)
+            .map_err(|source| TestDatabaseError::InvalidServerUrl { source })?;
+        url.set_path(&name);
+
+        let mut conn = PgConnection::establish(server_url)
+            .map_err(|source| TestDatabaseError::ConnectToServer { source })?;
+        sql_query(format!("CREATE DATABASE \"{name}\""))
+            .execute(&mut conn)
+            .map_err(|source| TestDatabaseError::CreateDatabase { source })?;
+
+        let created = CreatedDatabase { server_url: server_url.into(), name };
+        Self::migrated(url.into(), Some(created), None)
+    }
+
+    /// Starts a PostgreSQL container of its own, through the Docker daemon of the environment.
+    pub async fn start_container() -> Result<Self, TestDatabaseError> {
+        let container = Postgres::default()
+            .with_tag(POSTGRES_TAG)
+            .start()
+            .await
+            .map_err(|source| TestDatabaseError::StartContainer { source })?;
+        let host = container.get_host()
+            .await
+            .map_err(|source| TestDatabaseError::StartContainer { source })?;
+        let port = container.get_host_port_ipv4(5432)
+            .await
+            .map_err(|source| TestDatabaseError::StartContainer { source })?;
+
+        Self::migrated(format!("postgres://postgres:postgres@{host}:{port}/postgres"), None, Some(container))
+    }
+
+    fn migrated(url: String, created: Option<CreatedDatabase>, container: Option<ContainerAsync<Postgres>>) -> Result<Self, TestDatabaseError> {
+        let (pool, pool_metrics) = build_pg_pool(&url, &PoolConfig { max_size: 4, ..PoolConfig::default() })
+            .map_err(|source| TestDatabaseError::BuildPool { source })?;
+
+        let mut conn = pool.get()
+            .map_err(|source| TestDatabaseError::GetConnectionFromPool { source })?;
+        run_pending_migrations(&mut conn)
+            .map_err(|source| TestDatabaseError::RunPendingMigrations { source })?;
+        drop(conn);
+
+        Ok(Self { url, pool, pool_metrics, created, _container: container })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        // Containers go away by themselves, databases of a shared server would pile up.
+        let Some(created) = &self.created else {
+            return;
+        };
+        let dropped = PgConnection::establish(&created.server_url)
+            .map_err(|error| error.to_string())
+            .and_then(|mut conn| {
+                sql_query(format!("DROP DATABASE IF EXISTS \"{}\" WITH (FORCE)", created.name))
+                    .execute(&mut conn)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = dropped {
+            warn!(database = %created.name, %error, "test database could not be dropped");
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MockGitHubError {
+    #[error("ParseApiUrl: {source}")]
+    ParseApiUrl {
+        source: ParseGitHubApiUrlError,
+    },
+    #[error("NewGitHubClient: {source}")]
+    NewGitHubClient {
+        source: NewGitHubClientError,
+    },
+    #[error("ParseRestApiUrl: {source}")]
+    ParseRestApiUrl {
+        source: ParseRestApiUrlError,
+    },
+}
+
+/// GitHub GraphQL API answering for the repositories mounted on it.
+pub struct MockGitHub {
+    server: MockServer,
+}
+
+impl MockGitHub {
+    pub async fn start() -> Self {
+        Self { server: MockServer::start().await }
+    }
+
+    /// The wiremock server, to mount other answers or look at the requests received.
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    pub fn api_url(&self) -> Result<GitHubApiUrl, MockGitHubError> {
+        self.server.uri()
+            .parse()
+            .map_err(|source| MockGitHubError::ParseApiUrl { source })
+    }
+
+    /// Client of the mock with a dummy token, failures not retried.
+    pub fn client(&self) -> Result<GitHubClient, MockGitHubError> {
+        let config = GitHubClientConfig {
+            api_url: self.api_url()?,
+            retry: RetryConfig { max_attempts: 1, ..RetryConfig::default() },
+            ..GitHubClientConfig::default()
+        };
+
+        GitHubClient::new(&config, GitHubAuth::Tokens(TokenPool::new(["test-token".to_string()])))
+            .map_err(|source| MockGitHubError::NewGitHubClient { source })
+    }
+
+    /// Clients of every forge: the GitHub one of [`MockGitHub::client`], GitLab at `/api/v4` and
+    /// Gitea at `/api/v1` of the mock, anonymous.
+    pub fn forge_clients(&self) -> Result<ForgeClients, MockGitHubError> {
+        let github = self.client()?;
+        let rest_url = |path: &str| format!("{}{path}", self.server.uri())
+            .parse()
+            .map_err(|source| MockGitHubError::ParseRestApiUrl { source });
+
+        let gitlab = GitLabClient::new(github.http().clone(), rest_url("/api/v4")?, None, github.retry().clone());
+        let gitea = GiteaClient::new(github.http().clone(), rest_url("/api/v1")?, None, github.retry().clone());
+        Ok(ForgeClients::new(github, gitlab, Some(gitea)))
+    }
+
+    /// Answers the stargazer pages, and star counts, of `repository`.
+    pub async fn mount_repository(&self, repository: MockRepository) {
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .and(body_partial_json(json!({ "variables": { "owner": repository.owner, "name": repository.name } })))
+            .respond_with(repository)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answers `repository: null` for `owner/name`, as GitHub does for repositories it doesn't know.
+    pub async fn mount_missing_repository(&self, owner: &str, name: &str) {
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .and(body_partial_json(json!({ "variables": { "owner": owner, "name": name } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": { "repository": null } })))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answers `repository` where it lives now, and `owner/name` as GitHub does once the repository
+    /// is transferred: unknown to GraphQL, redirected by REST to the repository's ID-based URL.
+    pub async fn mount_moved_repository(&self, owner: &str, name: &str, repository: MockRepository) {
+        let moved_to = format!("/repositories/R_{}_{}", repository.owner, repository.name);
+        Mock::given(method("GET"))
+            .and(path(format!("/repos/{owner}/{name}")))
+            .respond_with(ResponseTemplate::new(301).insert_header("Location", format!("{}{moved_to}", self.server.uri())))
+            .mount(&self.server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(moved_to))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "name": repository.name, "owner": { "login": repository.owner } })))
+            .mount(&self.server)
+            .await;
+        self.mount_missing_repository(owner, name).await;
+        self.mount_repository(repository).await;
+    }
+
+    /// Answers the traffic of `owner/name`, `(day, count)` views and clones with as many unique
+    /// visitors, as GitHub does for a token with push access to the repository.
+    pub async fn mount_traffic(&self, owner: &str, name: &str, views: &[(NaiveDate, u32)], clones: &[(NaiveDate, u32)]) {
+        let days = |traffic: &[(NaiveDate, u32)]| -> Vec<Value> {
+            traffic
+                .iter()
+                .map(|(day, count)| json!({ "timestamp": format!("{day}T00:00:00Z"), "count": count, "uniques": count }))
+                .collect()
+        };
+        for (kind, traffic) in [("views", views), ("clones", clones)] {
+            let total: u32 = traffic.iter().map(|(_, count)| count).sum();
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/{owner}/{name}/traffic/{kind}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "count": total, "uniques": total, kind: days(traffic) })))
+                .mount(&self.server)
+                .await;
+        }
+    }
+
+    /// Refuses the traffic of `owner/name`, as GitHub does for a token without push access.
+    pub async fn mount_traffic_forbidden(&self, owner: &str, name: &str) {
+        Mock::given(method("GET"))
+            .and(path_regex(format!("^/repos/{owner}/{name}/traffic/")))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({ "message": "Must have push access to repository" })))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answers the creation dates of the accounts looked up for bot flagging, as GitHub does for
+    /// logins it doesn't know: `null`, with a `NOT_FOUND` error.
+    pub async fn mount_accounts(&self, accounts: &[(&str, DateTime<Utc>)]) {
+        let accounts = MockAccounts(accounts.iter().map(|(login, created_at)| (login.to_string(), *created_at)).collect());
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .and(body_string_contains("getAccountCreationDates"))
+            .respond_with(accounts)
+            .mount(&self.server)
+            .await;
+    }
+}
+
+/// Accounts served by [`MockGitHub::mount_accounts`], creation dates by login.
+struct MockAccounts(HashMap<String, DateTime<Utc>>);
+
+impl Respond for MockAccounts {
+    fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+        let Ok(query) = request.body_json::<Value>() else {
+            return ResponseTemplate::new(400);
+        };
+        let Some(variables) = query["variables"].as_object() else {
+            return ResponseTemplate::new(400);
+        };
+
+        let mut data = serde_json::Map::new();
+        let mut errors = Vec::new();
+        for (variable, login) in variables {
+            let alias = variable.replacen('l', "a", 1);
+            let login = login.as_str().unwrap_or_default();
+            let account = self.0.get(login).map(|created_at| json!({ "login": login, "createdAt": created_at.to_rfc3339() }));
+            if account.is_none() {
+                errors.push(json!({ "type": "NOT_FOUND", "path": [alias], "message": format!("Could not resolve to a User with the login of '{login}'.") }));
+            }
+            data.insert(alias, account.unwrap_or(Value::Null));
+        }
+        ResponseTemplate::new(200).set_body_json(json!({ "data": data, "errors": errors }))
+    }
+}
+
+/// Repository served by [`MockGitHub`], its stargazers oldest first.
+#[derive(Debug, Clone)]
+pub struct MockRepository {
+    pub owner: String,
+    pub name: String,
+    pub stargazers: Vec<(String, DateTime<Utc>)>,
+    /// Public emails by login, the other stargazers showing none.
+    pub emails: HashMap<String, String>,
+    /// Tags of its releases, and when they were published.
+    pub releases: Vec<(String, DateTime<Utc>)>,
+    /// Issues and pull requests by kind, `issues` or `prs`.
+    pub activity: HashMap<&'static str, Vec<MockActivityItem>>,
+    /// Commits of the default branch, their author's login if any and when they were committed.
+    pub commits: Vec<(Option<String>, DateTime<Utc>)>,
+    pub description: Option<String>,
+    pub topics: Vec<String>,
+}
+
+/// Number of an issue or pull request of a [`MockRepository`], when it was opened and when closed.
+pub type MockActivityItem = (u32, DateTime<Utc>, Option<DateTime<Utc>>);
+
+impl MockRepository {
+    pub fn new(owner: &str, name: &str) -> Self {
+        Self { owner: owner.into(), name: name.into(), stargazers: Vec::new(), emails: HashMap::new(), releases: Vec::new(), activity: HashMap::new(), commits: Vec::new(), description: None, topics: Vec::new() }
+    }
+
+    pub fn with_description(mut self, description: &str, topics: &[&str]) -> Self {
+        self.description = Some(description.into());
+        self.topics = topics.iter().map(|&topic| topic.into()).collect();
+        self
+    }
+
+    pub fn with_email(mut self, login: &str, email: &str) -> Self {
+        self.emails.insert(login.into(), email.into());
+        self
+    }
+
+    pub fn with_release(mut self, tag_name: &str, published_at: DateTime<Utc>) -> Self {
+        self.releases.push((tag_name.into(), published_at));
+        self
+    }
+
+    /// Issue `number`, closed at `closed_at` if set, last updated then or when opened.
+    pub fn with_issue(mut self, number: u32, opened_at: DateTime<Utc>, closed_at: Option<DateTime<Utc>>) -> Self {
+        self.activity.entry("issues").or_default().push((number, opened_at, closed_at));
+        self
+    }
+
+    /// Pull request `number`, like [`MockRepository::with_issue`].
+    pub fn with_pull_request(mut self, number: u32, opened_at: DateTime<Utc>, closed_at: Option<DateTime<Utc>>) -> Self {
+        self.activity.entry("prs").or_default().push((number, opened_at, closed_at));
+        self
+    }
+
+    /// Commit of `login`, or of an email no account claims, authored when committed.
+    pub fn with_commit(mut self, login: Option<&str>, committed_at: DateTime<Utc>) -> Self {
+        self.commits.push((login.map(Into::into), committed_at));
+        self
+    }
+
+    pub fn with_stargazer(mut self, login: &str, starred_at: DateTime<Utc>) -> Self {
+        self.stargazers.push((login.into(), starred_at));
+        self
+    }
+
+    /// `stars[n]` stargazers on the `n`th day from `first_day`, at noon.
+    pub fn with_daily_stars(mut self, first_day: NaiveDate, stars: &[u32]) -> Self {
+        for (day, count) in first_day.iter_days().zip(stars) {
+            let noon = Utc.from_utc_datetime(&day.and_time(chrono::NaiveTime::MIN)) + chrono::Duration::hours(12);
+            for n in 0..*count {
+                let login = format!("stargazer-{}", self.stargazers.len());
+                self.stargazers.push((login, noon + chrono::Duration::seconds(n.into())));
+            }
+        }
+        self
+    }
+
+    /// Answer of the activity query, see `interfaces_github_stargazers::activity`, in a single page
+    /// most recently updated first.
+    fn activity_response(&self, query: &str) -> ResponseTemplate {
+        let (kind, connection) = if query.contains("pullRequests(") { ("prs", "pullRequests") } else { ("issues", "issues") };
+        let mut items = self.activity.get(kind).cloned().unwrap_or_default();
+        items.sort_by_key(|&(_, opened_at, closed_at)| std::cmp::Reverse(closed_at.unwrap_or(opened_at)));
+        ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                "repository": {
+                    connection: {
+                        "nodes": items.iter().map(|(number, opened_at, closed_at)| json!({
+                            "number": number,
+                            "createdAt": opened_at.to_rfc3339(),
+                            "closedAt": closed_at.map(|closed_at| closed_at.to_rfc3339()),
+                            "updatedAt": closed_at.unwrap_or(*opened_at).to_rfc3339(),
+                        })).collect::<Vec<_>>(),
+                        "pageInfo": { "hasNextPage": false, "endCursor": null },
+                    },
+                },
+            },
+        }))
+    }
+
+    /// Answer of the commit authors query, see `interfaces_github_stargazers::contributors`, in a
+    /// single page newest first.
+    fn commits_response(&self, variables: &StargazersVariables) -> ResponseTemplate {
+        let mut commits: Vec<_> = self.commits
+            .iter()
+            .filter(|(_, committed_at)| variables.since.is_none_or(|since| *committed_at >= since))
+            .filter(|(_, committed_at)| variables.until.is_none_or(|until| *committed_at <= until))
+            .collect();
+        commits.sort_by_key(|(_, committed_at)| std::cmp::Reverse(*committed_at));
+        ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                "repository": {
+                    "defaultBranchRef": {
+                        "target": {
+                            "history": {
+                                "nodes": commits.iter().map(|(login, committed_at)| json!({
+                                    "authoredDate": committed_at.to_rfc3339(),
+                                    "committedDate": committed_at.to_rfc3339(),
+                                    "author": { "user": login.as_ref().map(|login| json!({ "login": login })) },
+                                })).collect::<Vec<_>>(),
+                                "pageInfo": { "hasNextPage": false, "endCursor": null },
+                            },
+                        },
+                    },
+                },
+            },
+        }))
+    }
+
+    /// Answer of the releases query, see `interfaces_github_stargazers::releases`, without tags.
+    fn releases_response(&self) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                "repository": {
+                    "releases": {
+                        "nodes": self.releases.iter().map(|(tag_name, published_at)| json!({
+                            "tagName": tag_name,
+                            "name": tag_name,
+                            "publishedAt": published_at.to_rfc3339(),
+                            "isDraft": false,
+                            "isPrerelease": false,
+                            "url": format!("https://github.com/{}/{}/releases/tag/{tag_name}", self.owner, self.name),
+                        })).collect::<Vec<_>>(),
+                    },
+                    "refs": { "nodes": [] },
+                },
+            },
+        }))
+    }
+}
+
+/// Variables of the stargazers query, see `interfaces_github_stargazers::index`, the releases,
+/// activity and commit authors queries being told apart by their name.
+#[derive(Deserialize)]
+struct StargazersQuery {
+    query: String,
+    variables: StargazersVariables,
+}
+
+#[derive(Deserialize)]
+struct StargazersVariables {
+    /// Offset of the page, stringified, absent on the first page.
+    cursor: Option<String>,
+    first: Option<usize>,
+    /// Bounds of the commit authors query.
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+impl Respond for MockRepository {
+    fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+        let Ok(query) = request.body_json::<StargazersQuery>() else {
+            return ResponseTemplate::new(400);
+        };
+        if query.query.contains("getRepositoryReleases") {
+            return self.releases_response();
+        }
+        if query.query.contains("getRepositoryActivity") {
+            return self.activity_response(&query.query);
+        }
+        if query.query.contains("getCommitAuthors") {
+            return self.commits_response(&query.variables);
+        }
+        let offset = query.variables.cursor.as_deref().and_then(|cursor| cursor.parse().ok()).unwrap_or(0);
+        let end = query.variables.first.map_or(offset, |first| offset + first).min(self.stargazers.len());
+        let page = self.stargazers.get(offset..end).unwrap_or_default();
+
+        ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                "repository": {
+                    "id": format!("R_{}_{}", self.owner, self.name),
+                    "name": self.name,
+                    "owner": { "login": self.owner },
+                    "description": self.description,
+                    "createdAt": "2024-01-01T00:00:00Z",
+                    "stargazerCount": self.stargazers.len(),
+                    "primaryLanguage": null,
+                    "licenseInfo": null,
+                    "repositoryTopics": { "nodes": self.topics.iter().map(|topic| json!({ "topic": { "name": topic } })).collect::<Vec<_>>() },
+                    "stargazers": {
+                        "edges": page.iter().map(|(login, starred_at)| json!({
+                            "starredAt": starred_at.to_rfc3339(),
+                            "node": { "login": login, "email": self.emails.get(login) },
+                        })).collect::<Vec<_>>(),
+                        "pageInfo": { "hasNextPage": end < self.stargazers.len(), "endCursor": end.to_string() },
+                    },
+                },
+            },
+        }))
+    }
+}
+
+/// [`StargazerFetcher`] serving a [`MockRepository`] in-process, the cursor being an offset as with
+/// [`MockGitHub`], with failures scripted by call. Hand it to [`ForgeClients::with_fetcher`].
+#[derive(Debug)]
+pub struct ScriptedFetcher {
+    forge: Forge,
+    /// `None` answers that the repository doesn't exist.
+    repository: Option<MockRepository>,
+    retry: RetryConfig,
+    failures: HashMap<usize, ForgeErrorKind>,
+    panics_on: Option<usize>,
+    calls: Mutex<Vec<ScriptedCall>>,
+}
+
+/// [`ScriptedFetcher::fetch_stargazers`] call, as received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptedCall {
+    pub cursor: Option<String>,
+    pub page_size: u32,
+}
+
+impl ScriptedFetcher {
+    /// Fetcher of `repository` on `forge`, retrying up to 5 times without waiting.
+    pub fn new(forge: Forge, repository: MockRepository) -> Self {
+        Self {
+            forge,
+            repository: Some(repository),
+            retry: RetryConfig { base_delay: Duration::ZERO, max_delay: Duration::ZERO, ..RetryConfig::default() },
+            failures: HashMap::new(),
+            panics_on: None,
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Fetcher of `forge` where no repository exists.
+    pub fn missing(forge: Forge) -> Self {
+        Self { repository: None, ..Self::new(forge, MockRepository::new("", "")) }
+    }
+
+    /// Fails fetch number `call`, starting at 0, with an error of `kind`.
+    pub fn failing_on(mut self, call: usize, kind: ForgeErrorKind) -> Self {
+        self.failures.insert(call, kind);
+        self
+    }
+
+    /// Panics on fetch number `call`, starting at 0, like a bug in the fetcher.
+    pub fn panicking_on(mut self, call: usize) -> Self {
+        self.panics_on = Some(call);
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Fetches received so far, failed ones included.
+    pub fn calls(&self) -> Vec<ScriptedCall> {
+        self.calls.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    fn page(&self, cursor: Option<&str>, page_size: u32) -> Result<Option<StargazerPage>, StargazerFetchError> {
+        let call = {
+            let mut calls = self.calls.lock().unwrap_or_else(PoisonError::into_inner);
+            calls.push(ScriptedCall { cursor: cursor.map(Into::into), page_size });
+            calls.len() - 1
+        };
+        if self.panics_on == Some(call) {
+            panic!("scripted panic on call {call}");
+        }
+        if let Some(kind) = self.failures.get(&call) {
+            return Err(StargazerFetchError::with_kind(*kind, format!("scripted {kind:?} on call {call}")));
+        }
+        let Some(repository) = &self.repository else {
+            return Ok(None);
+        };
+
+        let offset: usize = cursor.and_then(|cursor| cursor.parse().ok()).unwrap_or(0);
+        let end = offset.saturating_add(page_size as usize).min(repository.stargazers.len());
+        Ok(Some(StargazerPage {
+            repository: ForgeRepository {
+                id: format!("R_{}_{}", repository.owner, repository.name),
+                owner: repository.owner.clone(),
+                name: repository.name.clone(),
+                description: repository.description.clone(),
+                primary_language: None,
+                license: None,
+                topics: repository.topics.clone(),
+                created_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).single().unwrap_or_default(),
+                stargazer_count: repository.stargazers.len() as i64,
+            },
+            stargazers: repository.stargazers.get(offset..end).unwrap_or_default()
+                .iter()
+                .map(|(login, starred_at)| ForgeStargazer { login: login.clone(), starred_at: *starred_at, company: None, location: None, email: None })
+                .collect(),
+            end_cursor: Some(end.to_string()),
+            has_next_page: end < repository.stargazers.len(),
+            retries: 0,
+        }))
+    }
+}
+
+impl StargazerFetcher for ScriptedFetcher {
+    fn forge(&self) -> Forge {
+        self.forge
+    }
+
+    fn retry(&self) -> &RetryConfig {
+        &self.retry
+    }
+
+    fn fetch_stargazers<'a>(
+        &'a self,
+        _owner: &'a str,
+        _name: &'a str,
+        cursor: Option<&'a str>,
+        page_size: u32,
+        _with_profiles: bool,
+    ) -> FetchFuture<'a, Option<StargazerPage>> {
+        Box::pin(std::future::ready(self.page(cursor, page_size)))
+    }
+
+    fn stargazer_count<'a>(&'a self, _owner: &'a str, _name: &'a str) -> FetchFuture<'a, Option<u32>> {
+        let count = self.repository.as_ref().map(|repository| repository.stargazers.len() as u32);
+        Box::pin(std::future::ready(Ok(count)))
+    }
+}
+
+/// Dependencies of the router over `database` and `forges`, as the server builds them with an empty
+/// environment: a single sync worker, anonymous requests served as the default tenant, no admin token.
+pub fn test_dependencies(database: &TestDatabase, forge_clients: ForgeClients) -> RouterDependencies {
+    let github = forge_clients.github().clone();
+    let notifier = Notifier::new(github.http().clone(), None, WebhookUrlPolicy::default());
+    let live_updates = LiveUpdates::new();
+    let stargazer_ids = StargazerIds::default();
+    let request_limits = RequestLimits::default();
+    let chart_cache = ChartCache::default();
+    let sync_queue = SyncQueue::start(1, database.pool.clone(), forge_clients.clone(), notifier.clone(), live_updates.clone(), StargazerDetails::default(), stargazer_ids.clone(), chart_cache.clone());
+
+    let runtime_config = RuntimeConfig::new(
+        ServiceSettings {
+            sync_workers: 1,
+            shutdown_grace_seconds: 0,
+            request_timeout_seconds: request_limits.timeout.as_secs(),
+            max_request_body_bytes: request_limits.max_body_bytes,
+            max_concurrent_requests: request_limits.max_concurrent_requests,
+            max_concurrent_renders: request_limits.max_concurrent_renders,
+            chart_cache_ttl_seconds: ChartCacheConfig::default().ttl.as_secs(),
+            chart_cache_max_bytes: ChartCacheConfig::default().max_bytes,
+            require_api_key: false,
+            github_retry_max_attempts: github.retry().max_attempts,
+            github_page_size: github.page_size().max,
+            github_min_page_size: github.page_size().min,
+            star_retention_months: None,
+            star_compaction_interval_hours: None,
+            star_snapshot_interval_hours: 0,
+            contributor_tracking_interval_hours: 0,
+            traffic_snapshot_interval_hours: None,
+            collect_stargazer_profiles: false,
+            collect_stargazer_emails: false,
+            hashed_stargazer_ids: false,
+            flag_suspected_bots: false,
+            collect_repository_activity: false,
+            sync_notify_channel: None,
+        },
+        AdjustableSettings { star_insert: StarInsertConfig::default(), sync_job_retention: SyncJobRetention::default(), sync_limits: SyncLimits::default() },
+    );
+
+    RouterDependencies {
+        db_pool: database.pool.clone(),
+        pool_metrics: database.pool_metrics.clone(),
+        read_pool: ReadPool::new(database.pool.clone(), None),
+        github_client: github,
+        forge_clients,
+        notifier,
+        webhook_secret: None,
+        stargazer_ids,
+        runtime_config,
+        sync_queue,
+        live_updates,
+        chart_cache,
+        request_limits,
+        cors: None,
+        tenant_auth: TenantAuth { pool: database.pool.clone(), require_api_key: false },
+        admin_token: None,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TestAppError {
+    #[error("BuildRequest: {source}")]
+    BuildRequest {
+        source: axum::http::Error,
+    },
+    #[error("EncodeBody: {source}")]
+    EncodeBody {
+        source: serde_json::Error,
+    },
+    #[error("ReadBody: {source}")]
+    ReadBody {
+        source: axum::Error,
+    },
+}
+
+/// The router of the service, called in-process.
+#[derive(Clone)]
+pub struct TestApp {
+    router: Router,
+}
+
+/// Answer of [`TestApp`], its body read in full.
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl TestResponse {
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+impl TestApp {
+    pub fn new(dependencies: RouterDependencies) -> Self {
+        Self { router: build_router(dependencies) }
+    }
+
+    /// App answering through `router` alone, for middleware tried on routes of their own.
+    pub fn from_router(router: Router) -> Self {
+        Self { router }
+    }
+
+    /// Router over `database` and the forges of `github`, with [`test_dependencies`].
+    pub fn spawn(database: &TestDatabase, github: &MockGitHub) -> Result<Self, MockGitHubError> {
+        Ok(Self::new(test_dependencies(database, github.forge_clients()?)))
+    }
+
+    pub async fn request(&self, request: Request<Body>) -> Result<TestResponse, TestAppError> {
+        let Ok(response): Result<Response, _> = self.router.clone().oneshot(request).await;
+        let (parts, body) = response.into_parts();
+        let body = to_bytes(body, usize::MAX)
+            .await
+            .map_err(|source| TestAppError::ReadBody { source })?;
+
+        Ok(TestResponse { status: parts.status, headers: parts.headers, body })
+    }
+
+    pub async fn get(&self, uri: &str) -> Result<TestResponse, TestAppError> {
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(uri)
+            .body(Body::empty())
+            .map_err(|source| TestAppError::BuildRequest { source })?;
+        self.request(request).await
+    }
+
+    pub async fn post_json(&self, uri: &str, body: &Value) -> Result<TestResponse, TestAppError> {
+        let body = serde_json::to_vec(body)
+            .map_err(|source| TestAppError::EncodeBody { source })?;
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .map_err(|source| TestAppError::BuildRequest { source })?;
+        self.request(request).await
+    }
+}
+
+/// Outcome of an integration test, any error failing it.
+pub type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+/// Day of a `YYYY-MM-DD` fixture date.
+pub fn day(value: &str) -> Result<NaiveDate, chrono::ParseError> {
+    value.parse()
+}
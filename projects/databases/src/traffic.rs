@@ -0,0 +1,166 @@
+//! Repository traffic snapshots
+//!
+//! GitHub shows the daily views and clones of a repository, over the last 14
+//! days, to tokens with push access to it. Once
+//! `TRAFFIC_SNAPSHOT_INTERVAL_HOURS` is set, every GitHub repository of every
+//! tenant gets them recorded at that interval in `traffic_snapshots`, the
+//! days already stored updated, so the history outlives GitHub's two weeks.
+//! Repositories the token can't push to are skipped on each run.
+//!
+//! The `views` and `clones` metrics of the comparison endpoints draw them
+//! along with the stars, see [`crate::analytics::compare`].
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use diesel::PgConnection;
+use interfaces_github_stargazers::forge::Forge;
+use interfaces_github_stargazers::traffic::{fetch_repository_traffic, FetchRepositoryTrafficError, TrafficKind};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::db::{
+	    repository::{
+	        models::Repository,
+	        queries::{list_forge_repositories, ListForgeRepositoriesError},
+	    },
+	    traffic_snapshot::{
+	        models::NewTrafficSnapshot,
+	        queries::{upsert_traffic_snapshots, UpsertTrafficSnapshotsError},
+	    },
+	    PgPool,
+	};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::forges::ForgeClients;
+use crate::sync_queue::StopSignal;
+
+#[derive(Debug, Error)]
+pub enum TakeTrafficSnapshotError {
+    #[error("FetchRepositoryTraffic: {source}")]
+    FetchRepositoryTraffic {
+        #[from]
+        source: FetchRepositoryTrafficError,
+    },
+    #[error("UpsertTrafficSnapshots: {source}")]
+    UpsertTrafficSnapshots {
+        #[from]
+        source: UpsertTrafficSnapshotsError,
+    },
+}
+
+/// Records the views and clones of the last days of `repos`, tracked by several tenants but the
+/// same on GitHub, the days recorded. `None` when GitHub doesn't know the repository anymore.
+pub async fn take_traffic_snapshot(
+    conn: &mut PgConnection,
+    forges: &ForgeClients,
+    repos: &[Repository],
+) -> Result<Option<usize>, TakeTrafficSnapshotError> {
+    let Some(repo) = repos.first() else {
+        return Ok(Some(0));
+    };
+
+    let recorded_at = Utc::now().naive_utc();
+    let mut recorded = 0;
+    for kind in TrafficKind::ALL {
+        let Some(days) = fetch_repository_traffic(forges.github(), &repo.owner, &repo.name, kind).await? else {
+            return Ok(None);
+        };
+        let snapshots: Vec<NewTrafficSnapshot> = repos
+            .iter()
+            .flat_map(|repo| days.iter().map(move |traffic| NewTrafficSnapshot {
+                repository_id: repo.id,
+                kind:          kind.as_str(),
+                day:           traffic.day,
+                count:         i32::try_from(traffic.count).unwrap_or(i32::MAX),
+                uniques:       i32::try_from(traffic.uniques).unwrap_or(i32::MAX),
+                recorded_at,
+            }))
+            .collect();
+        if !snapshots.is_empty() {
+            upsert_traffic_snapshots(conn, &snapshots)?;
+        }
+        recorded += days.len();
+    }
+    Ok(Some(recorded))
+}
+
+/// Outcome of [`snapshot_traffic`], each repository of GitHub counted once however many tenants
+/// track it.
+#[derive(Debug, Default)]
+pub struct TrafficSummary {
+    pub repositories: usize,
+    /// Repositories the token has no push access to.
+    pub no_access: usize,
+    /// Repositories GitHub doesn't know anymore, e.g. deleted or renamed.
+    pub not_found: usize,
+    /// Repositories whose snapshot failed, left for the next run.
+    pub failed: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum SnapshotTrafficError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        #[from]
+        source: DbConnRejection,
+    },
+    #[error("ListForgeRepositories: {source}")]
+    ListForgeRepositories {
+        #[from]
+        source: ListForgeRepositoriesError,
+    },
+}
+
+/// Snapshots the traffic of every GitHub repository, of every tenant, none without GitHub
+/// credentials. A failing repository is logged and left for the next run.
+pub async fn snapshot_traffic(pool: &PgPool, forges: &ForgeClients) -> Result<TrafficSummary, SnapshotTrafficError> {
+    let mut summary = TrafficSummary::default();
+    if !forges.is_available(Forge::GitHub) {
+        return Ok(summary);
+    }
+
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+        .await
+        .map_err(|source| SnapshotTrafficError::Checkout { source })?;
+    let repositories = list_forge_repositories(&mut conn, Forge::GitHub.as_str())
+        .map_err(|source| SnapshotTrafficError::ListForgeRepositories { source })?;
+
+    // GitHub compares names case-insensitively.
+    let mut on_github: BTreeMap<(String, String), Vec<Repository>> = BTreeMap::new();
+    for repo in repositories {
+        on_github.entry((repo.owner.to_lowercase(), repo.name.to_lowercase())).or_default().push(repo);
+    }
+
+    for ((owner, name), repos) in on_github {
+        match take_traffic_snapshot(&mut conn, forges, &repos).await {
+            Ok(Some(_)) => summary.repositories += 1,
+            Ok(None) => {
+                warn!(%owner, %name, "traffic repository not found on GitHub");
+                summary.not_found += 1;
+            }
+            Err(TakeTrafficSnapshotError::FetchRepositoryTraffic { source }) if source.lacks_push_access() => summary.no_access += 1,
+            Err(error) => {
+                warn!(%owner, %name, %error, "traffic snapshot failed");
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Snapshots traffic every `interval`, starting right away, until `stop` fires.
+pub async fn run_traffic_snapshots(pool: PgPool, forges: ForgeClients, interval: Duration, stop: StopSignal) {
+    let mut ticks = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = stop.stopped() => return,
+            _ = ticks.tick() => {}
+        }
+
+        match snapshot_traffic(&pool, &forges).await {
+            Ok(summary) => info!(repositories = summary.repositories, no_access = summary.no_access, not_found = summary.not_found, failed = summary.failed, "snapshotted traffic"),
+            Err(error) => warn!(%error, "traffic snapshots failed"),
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! Input validation
+//!
+//! Caller input is checked before it reaches a forge or the database: owner
+//! and repository names must follow their forge's naming rules, webhook URLs must
+//! use an allowed scheme and point to an allowed host, never a private or
+//! loopback address unless listed, and every string has a length limit.
+//!
+//! Checks of one request are collected into [`ValidationErrors`], answered
+//! with `422 Unprocessable Entity` listing every rejected field at once.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use axum::http::StatusCode;
+use interfaces_github_stargazers::forge::Forge;
+use reqwest::Url;
+use serde::Serialize;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::analytics::derivatives::{DerivativeOptions, MAX_WINDOW_DAYS};
+use crate::endpoints::api_error::{ApiError, ErrorCode};
+use crate::sync_limits::SyncLimits;
+
+/// Longest GitHub user or organization login.
+pub const MAX_OWNER_LENGTH: usize = 39;
+/// Longest GitHub repository name.
+pub const MAX_NAME_LENGTH: usize = 100;
+pub const MAX_WEBHOOK_URL_LENGTH: usize = 2048;
+pub const MAX_EMAIL_LENGTH: usize = 254;
+
+/// Rejected field of a request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the field, e.g. `owner` or `repositories[2].name`.
+    pub field: String,
+    pub message: String,
+}
+
+/// Every rejected field of a request, empty when it's valid.
+#[derive(Debug, Default, Error)]
+#[error("InvalidFields: {}", self.fields().join(", "))]
+pub struct ValidationErrors {
+    errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the field as rejected when the check failed.
+    pub fn check(&mut self, field: impl Into<String>, result: Result<(), String>) -> &mut Self {
+        if let Err(message) = result {
+            self.errors.push(FieldError { field: field.into(), message });
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    fn fields(&self) -> Vec<&str> {
+        self.errors.iter().map(|error| error.field.as_str()).collect()
+    }
+
+    /// `Err` with the rejected fields, if any.
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        let message = format!("Invalid {}", errors.fields().join(", "));
+        ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, ErrorCode::ValidationFailed, message)
+            .with_details(serde_json::json!({ "fields": errors.errors }))
+    }
+}
+
+/// Checks both parts of a GitHub repository, `prefix` being prepended to the field names.
+pub fn validate_repository(errors: &mut ValidationErrors, prefix: &str, owner: &str, name: &str) {
+    errors
+        .check(format!("{prefix}owner"), validate_owner(owner))
+        .check(format!("{prefix}name"), validate_name(name));
+}
+
+/// Checks both parts of a repository on `forge`, see [`validate_repository`] for GitHub. GitLab and
+/// Gitea owners follow the rules of names, GitLab subgroups being left out.
+pub fn validate_forge_repository(errors: &mut ValidationErrors, prefix: &str, forge: Forge, owner: &str, name: &str) {
+    match forge {
+        Forge::GitHub => validate_repository(errors, prefix, owner, name),
+        Forge::GitLab | Forge::Gitea => {
+            errors
+                .check(format!("{prefix}owner"), validate_name(owner))
+                .check(format!("{prefix}name"), validate_name(name));
+        }
+    }
+}
+
+/// Checks that the sync limits a request sets are at least 1.
+pub fn validate_sync_limits(errors: &mut ValidationErrors, limits: &SyncLimits) {
+    let at_least_one = |zero: bool| if zero { Err("must be at least 1".to_string()) } else { Ok(()) };
+    errors
+        .check("max_pages", at_least_one(limits.max_pages == Some(0)))
+        .check("max_duration_seconds", at_least_one(limits.max_duration_seconds == Some(0)))
+        .check("max_stars", at_least_one(limits.max_stars == Some(0)));
+}
+
+/// Checks that a derivative window is odd and that it and the moving average span are at most
+/// [`MAX_WINDOW_DAYS`], `prefix` being prepended to the field names.
+pub fn validate_derivative_options(errors: &mut ValidationErrors, prefix: &str, options: &DerivativeOptions) {
+    errors.check(format!("{prefix}window_days"), match options.window_days {
+        days if days % 2 == 1 && days <= MAX_WINDOW_DAYS => Ok(()),
+        _ => Err(format!("must be odd, between 1 and {MAX_WINDOW_DAYS}")),
+    });
+    if let Some(span) = options.ema_span_days {
+        errors.check(format!("{prefix}ema_span_days"), match span {
+            2..=MAX_WINDOW_DAYS => Ok(()),
+            _ => Err(format!("must be between 2 and {MAX_WINDOW_DAYS}")),
+        });
+    }
+}
+
+/// GitHub login: 1 to 39 ASCII letters, digits or hyphens, not starting or ending with a hyphen.
+pub fn validate_owner(owner: &str) -> Result<(), String> {
+    if owner.is_empty() || owner.len() > MAX_OWNER_LENGTH {
+        return Err(format!("must be 1 to {MAX_OWNER_LENGTH} characters long"));
+    }
+    if !owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err("may only contain ASCII letters, digits and hyphens".into());
+    }
+    if owner.starts_with('-') || owner.ends_with('-') {
+        return Err("cannot start or end with a hyphen".into());
+    }
+    Ok(())
+}
+
+/// GitHub repository name: 1 to 100 ASCII letters, digits, `.`, `-` or `_`, other than `.` and `..`.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(format!("must be 1 to {MAX_NAME_LENGTH} characters long"));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')) {
+        return Err("may only contain ASCII letters, digits, '.', '-' and '_'".into());
+    }
+    if name == "." || name == ".." {
+        return Err("cannot be '.' or '..'".into());
+    }
+    Ok(())
+}
+
+/// Email address, only checked for a non-empty local part and domain.
+pub fn validate_email(email: &str) -> Result<(), String> {
+    if email.len() > MAX_EMAIL_LENGTH {
+        return Err(format!("must be at most {MAX_EMAIL_LENGTH} characters long"));
+    }
+    match email.rsplit_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') && !email.chars().any(|c| c.is_whitespace() || c.is_control()) => Ok(()),
+        _ => Err("must be an email address".into()),
+    }
+}
+
+/// Where alert webhooks may be sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookUrlPolicy {
+    /// Lowercase URL schemes, `https` only by default.
+    pub allowed_schemes: Vec<String>,
+    /// Lowercase hosts, `*.example.com` also matching its subdomains. Any public
+    /// host when empty. Listed hosts are allowed even when private.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for WebhookUrlPolicy {
+    fn default() -> Self {
+        Self { allowed_schemes: vec!["https".into()], allowed_hosts: Vec::new() }
+    }
+}
+
+impl WebhookUrlPolicy {
+    /// Checks a webhook URL against the policy.
+    pub fn validate(&self, url: &str) -> Result<(), String> {
+        if url.len() > MAX_WEBHOOK_URL_LENGTH {
+            return Err(format!("must be at most {MAX_WEBHOOK_URL_LENGTH} characters long"));
+        }
+        let url = Url::parse(url).map_err(|error| format!("must be an absolute URL: {error}"))?;
+
+        if !self.allowed_schemes.iter().any(|scheme| scheme == url.scheme()) {
+            return Err(format!("scheme must be one of {}", self.allowed_schemes.join(", ")));
+        }
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err("cannot contain credentials".into());
+        }
+
+        let host = url.host_str().ok_or("must have a host")?.to_ascii_lowercase();
+        let host = host.trim_start_matches('[').trim_end_matches(']').trim_end_matches('.');
+        if !self.allowed_hosts.is_empty() {
+            return if self.lists_host(host) {
+                Ok(())
+            } else {
+                Err(format!("host {host} is not allowed"))
+            };
+        }
+        if is_internal_host(host) {
+            return Err(format!("host {host} is a private or local address"));
+        }
+        Ok(())
+    }
+
+    /// Whether `host` is one of the allowed hosts, which may then be private.
+    pub fn lists_host(&self, host: &str) -> bool {
+        self.allowed_hosts.iter().any(|allowed| host_matches(allowed, host))
+    }
+}
+
+fn host_matches(allowed: &str, host: &str) -> bool {
+    match allowed.strip_prefix("*.") {
+        Some(domain) => host == domain || host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.')),
+        None => host == allowed,
+    }
+}
+
+/// Loopback, private, link-local and other non-public addresses, and names only resolving on the local network.
+fn is_internal_host(host: &str) -> bool {
+    match host.parse::<IpAddr>() {
+        Ok(ip) => is_internal_ip(ip),
+        Err(_) => {
+            host == "localhost"
+                || [".localhost", ".local", ".internal", ".localdomain"].iter().any(|suffix| host.ends_with(suffix))
+                || !host.contains('.')
+        }
+    }
+}
+
+/// Any address but the globally routable ones, following the rules of the standard library's unstable
+/// `is_global`, multicast included.
+pub fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => !is_global_ipv4(ip),
+        IpAddr::V6(ip) => !is_global_ipv6(ip),
+    }
+}
+
+fn is_global_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, d] = ip.octets();
+    !(
+        // This network, 0.0.0.0/8
+        a == 0
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        // Shared address space, 100.64.0.0/10
+        || (a == 100 && (b & 0xc0) == 64)
+        // IETF protocol assignments, 192.0.0.0/24, but for the PCP and TURN anycast addresses
+        || (a == 192 && b == 0 && c == 0 && d != 9 && d != 10)
+        || ip.is_documentation()
+        // Deprecated 6to4 relay anycast, 192.88.99.0/24
+        || (a == 192 && b == 88 && c == 99)
+        // Benchmarking, 198.18.0.0/15
+        || (a == 198 && (b & 0xfe) == 18)
+        // Multicast, 224.0.0.0/4, then reserved, 240.0.0.0/4, broadcast included
+        || a >= 224
+    )
+}
+
+fn is_global_ipv6(ip: Ipv6Addr) -> bool {
+    // Reaching the IPv4 address it embeds, through a NAT64 or 6to4 gateway for those.
+    if let Some(ipv4) = embedded_ipv4(ip) {
+        return is_global_ipv4(ipv4);
+    }
+    let segments = ip.segments();
+    let [first, second, third, ..] = segments;
+    !(
+        // Deprecated IPv4-compatible, ::/96, unspecified and loopback included
+        segments[..6] == [0; 6]
+        // Local-use IPv4/IPv6 translation, 64:ff9b:1::/48
+        || (first == 0x64 && second == 0xff9b && third == 1)
+        // Discard-only, 100::/64
+        || segments[..4] == [0x100, 0, 0, 0]
+        // IETF protocol assignments, 2001::/23, Teredo included, but for the globally reachable ones
+        || (first == 0x2001 && second < 0x200 && !is_global_protocol_assignment(ip))
+        // Documentation, 2001:db8::/32 and 3fff::/20
+        || (first == 0x2001 && second == 0xdb8)
+        || (first == 0x3fff && second < 0x1000)
+        // Segment routing, 5f00::/16
+        || first == 0x5f00
+        // Unique local, fc00::/7
+        || (first & 0xfe00) == 0xfc00
+        // Link-local, fe80::/10, then deprecated site-local, fec0::/10
+        || (first & 0xff80) == 0xfe80
+        || ip.is_multicast()
+    )
+}
+
+/// Port Control Protocol and TURN anycast, AMT, AS112 and ORCHIDv2 addresses of 2001::/23.
+fn is_global_protocol_assignment(ip: Ipv6Addr) -> bool {
+    matches!(ip.segments(), [0x2001, 1, 0, 0, 0, 0, 0, 1 | 2] | [0x2001, 3, ..] | [0x2001, 4, 0x112, ..] | [0x2001, 0x20..=0x3f, ..])
+}
+
+/// IPv4 address of an IPv4-mapped, NAT64 (64:ff9b::/96) or 6to4 (2002::/16) address.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let octets = ip.octets();
+    match ip.segments() {
+        [0, 0, 0, 0, 0, 0xffff, ..] | [0x64, 0xff9b, 0, 0, 0, 0, ..] => {
+            Some(Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]))
+        }
+        [0x2002, ..] => Some(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])),
+        _ => None,
+    }
+}
@@ -0,0 +1,98 @@
+//! Interactive star history charts
+//!
+//! The same cumulative series as the SVG charts of `chart`, as a Vega-Lite
+//! spec with hover tooltips and drag/scroll zooming. Either the spec alone,
+//! for a dashboard embedding it, or an HTML page rendering it with
+//! `vega-embed` loaded from jsDelivr.
+
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+
+use crate::chart::{escape, ChartTheme};
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 400;
+
+/// Vega-Lite spec of `cumulative`, one point per day as built by `analytics::series::cumulative_series`.
+pub fn star_history_vega_lite(title: &str, cumulative: &[(NaiveDate, i64)], theme: &ChartTheme) -> Value {
+    let values: Vec<Value> = cumulative
+        .iter()
+        .map(|(day, stars)| json!({ "day": day, "stars": stars }))
+        .collect();
+    let color = theme.series_color(0);
+
+    json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "title": title,
+        "width": WIDTH,
+        "height": HEIGHT,
+        "background": theme.background.as_str(),
+        "data": { "values": values },
+        "encoding": {
+            "x": { "field": "day", "type": "temporal", "title": null },
+            "y": { "field": "stars", "type": "quantitative", "title": "Stars" },
+        },
+        "layer": [
+            {
+                "params": [{ "name": "zoom", "select": "interval", "bind": "scales" }],
+                "mark": { "type": "line", "color": color, "strokeWidth": 2 },
+            },
+            {
+                // Invisible points, shown one at a time under the pointer to carry the tooltip.
+                "params": [{
+                    "name": "hover",
+                    "select": { "type": "point", "fields": ["day"], "nearest": true, "on": "pointerover", "clear": "pointerout" },
+                }],
+                "mark": { "type": "point", "color": color, "filled": true, "size": 60 },
+                "encoding": {
+                    "opacity": { "condition": { "param": "hover", "empty": false, "value": 1 }, "value": 0 },
+                    "tooltip": [
+                        { "field": "day", "type": "temporal", "title": "Day" },
+                        { "field": "stars", "type": "quantitative", "title": "Stars" },
+                    ],
+                },
+            },
+        ],
+        "config": {
+            "axis": {
+                "gridColor": theme.grid.as_str(),
+                "domainColor": theme.grid.as_str(),
+                "tickColor": theme.grid.as_str(),
+                "labelColor": theme.text.as_str(),
+                "titleColor": theme.text.as_str(),
+            },
+            "title": { "color": theme.text.as_str() },
+            "view": { "stroke": null },
+        },
+    })
+}
+
+/// HTML page rendering [`star_history_vega_lite`], the data is inlined so only the Vega scripts are fetched.
+pub fn render_star_history_html(title: &str, cumulative: &[(NaiveDate, i64)], theme: &ChartTheme) -> String {
+    // `<` escaped so nothing in the data can close the script element.
+    let spec = star_history_vega_lite(title, cumulative, theme)
+        .to_string()
+        .replace('<', "\\u003c");
+
+    format!(
+        r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<script src="https://cdn.jsdelivr.net/npm/vega@5"></script>
+<script src="https://cdn.jsdelivr.net/npm/vega-lite@5"></script>
+<script src="https://cdn.jsdelivr.net/npm/vega-embed@6"></script>
+</head>
+<body style="margin: 0; background: {background}">
+<div id="chart"></div>
+<script>
+vegaEmbed("#chart", {spec}, {{ actions: {{ export: true, source: false, compiled: false, editor: false }} }});
+</script>
+</body>
+</html>
+"##,
+        title = escape(title),
+        background = theme.background,
+    )
+}
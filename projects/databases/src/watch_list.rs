@@ -0,0 +1,238 @@
+//! Repository watch list
+//!
+//! Deployments can declare the repositories they track in a TOML file named by
+//! `REPOS_FILE`, read at startup:
+//!
+//! ```toml
+//! # Queue a first sync of the listed repositories never synced
+//! sync = true
+//!
+//! [[repositories]]
+//! owner = "tokio-rs"
+//! name = "tokio"
+//!
+//! [[repositories]]
+//! owner = "gitlab-org"
+//! name = "gitaly"
+//! forge = "gitlab"
+//! tenant = "acme"
+//! sync = false
+//! ```
+//!
+//! Repositories the tenant (the default one unless `tenant` names another)
+//! doesn't track yet are registered, their metadata left empty until a sync.
+//! Those never synced get a `low` priority sync with the service's limits when
+//! `sync` is set, for the file or the repository. Loading is idempotent, and a
+//! repository removed from the file stays tracked.
+//!
+//! An unreadable file, an invalid entry or an unknown tenant stops the service
+//! from starting, a misspelled list shouldn't go unnoticed.
+
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+use interfaces_github_stargazers::forge::Forge;
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::{
+    repository::{
+        models::NewRepository,
+        queries::{get_repository_by_name, insert_repository, GetRepositoryByNameError, InsertRepositoryError},
+    },
+    sync_run::models::SyncTrigger,
+    tenant::{
+        models::DEFAULT_TENANT_ID,
+        queries::{get_tenant_by_name, GetTenantByNameError},
+    },
+    PgPool,
+};
+use crate::endpoints::db_conn::{DbConn, DbConnRejection};
+use crate::live::PageSink;
+use crate::sync_limits::SyncLimits;
+use crate::sync_queue::{SyncPriority, SyncQueue, SyncTask};
+use crate::validation::{validate_forge_repository, ValidationErrors};
+
+/// Contents of `REPOS_FILE`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchList {
+    /// Queue a first sync of the repositories never synced, unless one says otherwise.
+    #[serde(default)]
+    pub sync: bool,
+    #[serde(default)]
+    pub repositories: Vec<WatchedRepository>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchedRepository {
+    pub owner: String,
+    pub name: String,
+    #[serde(default)]
+    pub forge: Forge,
+    /// Name of the tenant tracking it, the default tenant when unset.
+    pub tenant: Option<String>,
+    /// Overrides the list's `sync` for this repository.
+    pub sync: Option<bool>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReadWatchListError {
+    #[error("ReadFile: {path}: {source}")]
+    ReadFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("ParseToml: {path}: {source}")]
+    ParseToml {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("InvalidRepositories: {path}: {source}")]
+    InvalidRepositories {
+        path: PathBuf,
+        #[source]
+        source: ValidationErrors,
+    },
+}
+
+/// Reads and checks the watch list at `path`, rejecting every invalid owner and name at once.
+pub fn read_watch_list(path: &Path) -> Result<WatchList, ReadWatchListError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|source| ReadWatchListError::ReadFile { path: path.to_path_buf(), source })?;
+    let list: WatchList = toml::from_str(&contents)
+        .map_err(|source| ReadWatchListError::ParseToml { path: path.to_path_buf(), source })?;
+
+    let mut errors = ValidationErrors::new();
+    for (index, repo) in list.repositories.iter().enumerate() {
+        validate_forge_repository(&mut errors, &format!("repositories[{index}]."), repo.forge, repo.owner.trim(), repo.name.trim());
+    }
+    errors.into_result()
+        .map_err(|source| ReadWatchListError::InvalidRepositories { path: path.to_path_buf(), source })?;
+    Ok(list)
+}
+
+/// Outcome of [`load_watch_list`].
+#[derive(Debug, Default)]
+pub struct WatchListSummary {
+    /// Repositories registered by this load.
+    pub registered: usize,
+    /// Repositories the tenant tracked already, possibly under a former name.
+    pub already_tracked: usize,
+    /// First syncs queued.
+    pub syncs_queued: usize,
+    /// Repositories tracked from another forge than the listed one, left as they are.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum LoadWatchListError {
+    #[error("Checkout: {source}")]
+    Checkout {
+        #[from]
+        source: DbConnRejection,
+    },
+    #[error("GetTenantByName: {source}")]
+    GetTenantByName {
+        #[from]
+        source: GetTenantByNameError,
+    },
+    #[error("TenantNotFound: {name}")]
+    TenantNotFound {
+        name: String,
+    },
+    #[error("GetRepositoryByName: {source}")]
+    GetRepositoryByName {
+        #[from]
+        source: GetRepositoryByNameError,
+    },
+    #[error("InsertRepository: {source}")]
+    InsertRepository {
+        #[from]
+        source: InsertRepositoryError,
+    },
+}
+
+/// Registers the repositories of `list` their tenant doesn't track, and queues the first syncs it asks for.
+pub async fn load_watch_list(
+    pool: &PgPool,
+    queue: &SyncQueue,
+    list: &WatchList,
+    limits: SyncLimits,
+) -> Result<WatchListSummary, LoadWatchListError> {
+    let DbConn(mut conn) = DbConn::checkout(pool.clone())
+        .await
+        .map_err(|source| LoadWatchListError::Checkout { source })?;
+    let mut tenants: HashMap<&str, Uuid> = HashMap::new();
+    let mut summary = WatchListSummary::default();
+
+    for watched in &list.repositories {
+        let tenant_id = match watched.tenant.as_deref() {
+            None => DEFAULT_TENANT_ID,
+            Some(name) => match tenants.get(name) {
+                Some(tenant_id) => *tenant_id,
+                None => {
+                    let tenant = get_tenant_by_name(&mut conn, name)
+                        .map_err(|source| LoadWatchListError::GetTenantByName { source })?
+                        .ok_or_else(|| LoadWatchListError::TenantNotFound { name: name.to_string() })?;
+                    *tenants.entry(name).or_insert(tenant.id)
+                }
+            },
+        };
+
+        let (owner, name) = (watched.owner.trim(), watched.name.trim());
+        let tracked = get_repository_by_name(&mut conn, tenant_id, owner, name)
+            .await
+            .map_err(|source| LoadWatchListError::GetRepositoryByName { source })?;
+        let repo = match tracked {
+            Some(repo) if repo.forge() != watched.forge => {
+                warn!(owner, name, forge = watched.forge.as_str(), tracked = repo.forge().as_str(), "watched repository tracked from another forge, skipped");
+                summary.skipped += 1;
+                continue;
+            }
+            Some(repo) => {
+                summary.already_tracked += 1;
+                repo
+            }
+            // Metadata stays empty until a sync.
+            None => {
+                summary.registered += 1;
+                insert_repository(&mut conn, &NewRepository {
+                    id: Uuid::new_v4(),
+                    owner,
+                    name,
+                    description: None,
+                    primary_language: None,
+                    license: None,
+                    topics: Vec::new(),
+                    github_created_at: None,
+                    tenant_id,
+                    github_node_id: None,
+                    forge: watched.forge.as_str(),
+                })
+                    .map_err(|source| LoadWatchListError::InsertRepository { source })?
+            }
+        };
+
+        // The forge's ID is recorded by the first successful sync.
+        if watched.sync.unwrap_or(list.sync) && repo.github_node_id.is_none() {
+            let task = SyncTask::Repository { tenant_id, forge: watched.forge, owner: repo.owner.clone(), name: repo.name.clone(), batch_id: None, trigger: SyncTrigger::WatchList, forks_min_stars: None, pages: PageSink::default(), limits };
+            // Nobody waits on it, failures are logged by the worker.
+            drop(queue.enqueue(task, SyncPriority::Low));
+            summary.syncs_queued += 1;
+        }
+    }
+
+    info!(
+        registered = summary.registered,
+        already_tracked = summary.already_tracked,
+        syncs_queued = summary.syncs_queued,
+        skipped = summary.skipped,
+        "Loaded the repository watch list"
+    );
+    Ok(summary)
+}
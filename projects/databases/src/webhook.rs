@@ -0,0 +1,130 @@
+//! Outgoing webhook notifications
+//!
+//! Posts a JSON payload to a caller-supplied URL through the client of
+//! [`webhook_client`]. Any non-2xx answer is reported as a failure, redirects
+//! included, since following one could reach a host the [`WebhookUrlPolicy`]
+//! refuses. The client also checks the addresses hosts resolve to, so a public
+//! name pointing at a private address is refused at connect time. Deliveries
+//! retried from the outbox carry their ID in [`DELIVERY_ID_HEADER`].
+
+use std::{io, net::{IpAddr, SocketAddr}, sync::Arc, time::Duration};
+
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect, Client, StatusCode,
+};
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::validation::{is_internal_ip, WebhookUrlPolicy};
+
+/// Header naming an outbox delivery, the same on every attempt so receivers can drop duplicates.
+pub const DELIVERY_ID_HEADER: &str = "X-Webhook-Delivery";
+
+#[derive(Debug, Error)]
+pub enum SendWebhookError {
+    #[error("RequestSend: {source}")]
+    RequestSend {
+        source: reqwest::Error,
+    },
+    #[error("UnexpectedStatus: {status}")]
+    UnexpectedStatus {
+        status: StatusCode,
+    },
+}
+
+pub async fn send_webhook<T: Serialize>(
+    http: &Client,
+    url: &str,
+    delivery_id: Option<Uuid>,
+    payload: &T,
+) -> Result<(), SendWebhookError> {
+    let mut request = http.post(url).json(payload);
+    if let Some(id) = delivery_id {
+        request = request.header(DELIVERY_ID_HEADER, id.to_string());
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|source| SendWebhookError::RequestSend { source })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(SendWebhookError::UnexpectedStatus { status });
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum ResolveWebhookHostError {
+    #[error("Lookup: {source}")]
+    Lookup {
+        source: io::Error,
+    },
+    #[error("PrivateAddress: {host} resolves to {address}")]
+    PrivateAddress {
+        host: String,
+        address: IpAddr,
+    },
+}
+
+/// HTTP client of webhook deliveries, not following redirects, ignoring proxies and only
+/// connecting to private addresses for hosts the `policy` lists.
+pub fn webhook_client(policy: &WebhookUrlPolicy, timeout: Duration) -> Result<Client, reqwest::Error> {
+    webhook_client_resolving_with(policy, timeout, Arc::new(SystemResolver))
+}
+
+/// [`webhook_client`] looking hosts up with `lookup` rather than the system resolver.
+pub fn webhook_client_resolving_with(
+    policy: &WebhookUrlPolicy,
+    timeout: Duration,
+    lookup: Arc<dyn Resolve>,
+) -> Result<Client, reqwest::Error> {
+    Client::builder()
+        .timeout(timeout)
+        .redirect(redirect::Policy::none())
+        // A proxy would look hosts up itself, past the resolver's check.
+        .no_proxy()
+        .dns_resolver(Arc::new(PublicAddressResolver { policy: policy.clone(), lookup }))
+        .build()
+}
+
+struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses = tokio::net::lookup_host((host, 0))
+                .await
+                .map_err(|source| ResolveWebhookHostError::Lookup { source })?;
+            Ok(Box::new(addresses) as Addrs)
+        })
+    }
+}
+
+/// Refuses hosts resolving to any private address, unless the policy lists them.
+struct PublicAddressResolver {
+    policy: WebhookUrlPolicy,
+    lookup: Arc<dyn Resolve>,
+}
+
+impl Resolve for PublicAddressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_ascii_lowercase();
+        let listed = self.policy.lists_host(host.trim_end_matches('.'));
+        let resolving = self.lookup.resolve(name);
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = resolving.await?.collect();
+            if !listed {
+                if let Some(address) = addresses.iter().map(SocketAddr::ip).find(|&ip| is_internal_ip(ip)) {
+                    return Err(ResolveWebhookHostError::PrivateAddress { host, address }.into());
+                }
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
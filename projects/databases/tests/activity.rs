@@ -0,0 +1,71 @@
+//! Issues and pull requests stored by syncs, counted per day along with the stars
+//!
+//! Opened and closed counts are kept apart per day, and a sync stores the
+//! activity of the issues and pull requests it pages through.
+
+use axum::http::StatusCode;
+use chrono::{TimeDelta, Utc};
+use projects_databases::analytics::compare::{compare_series, ActivitySeries, CompareMetric, CompareOptions, DailyActivity, RepositoryHistory};
+use projects_databases::sync_queue::{StargazerDetails, SyncQueue};
+use projects_databases::testing::{day, test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+
+#[test]
+fn opened_and_closed_are_counted_apart() -> TestResult {
+    let history = RepositoryHistory {
+        repository: "acme/rocket".into(),
+        daily_counts: vec![(day("2025-03-01")?, 1)],
+        from_snapshots: false,
+        with_forks: None,
+        traffic: None,
+        activity: Some(DailyActivity {
+            issues: vec![(day("2025-03-01")?, 2, 0), (day("2025-03-03")?, 1, 2)],
+            prs: vec![(day("2025-03-02")?, 1, 1)],
+        }),
+        first_contributions: None,
+    };
+
+    let comparison = compare_series(&[history], &[CompareMetric::Issues, CompareMetric::Prs], &CompareOptions::default(), day("2025-03-03")?);
+
+    let series = &comparison.series[0];
+    assert_eq!(series.issues, Some(ActivitySeries { opened: vec![2, 0, 1], closed: vec![0, 0, 2] }));
+    assert_eq!(series.prs, Some(ActivitySeries { opened: vec![0, 1, 0], closed: vec![0, 1, 0] }));
+    assert!(series.cumulative.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn syncs_store_issue_and_pull_request_activity() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let now = Utc::now();
+    let days_ago = |days| now - TimeDelta::days(days);
+    let first_day = days_ago(4).date_naive();
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_daily_stars(first_day, &[1, 1])
+        .with_issue(1, days_ago(4), Some(days_ago(2)))
+        .with_issue(2, days_ago(3), None)
+        // Last updated before the history fetched on a first sync.
+        .with_issue(3, days_ago(400), Some(days_ago(200)))
+        .with_pull_request(4, days_ago(4), Some(days_ago(3)))).await;
+
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    let collect = StargazerDetails { activity: true, ..StargazerDetails::default() };
+    dependencies.sync_queue = SyncQueue::start(1, database.pool().clone(), dependencies.forge_clients.clone(), dependencies.notifier.clone(), dependencies.live_updates.clone(), collect, dependencies.stargazer_ids.clone(), dependencies.chart_cache.clone());
+    let app = TestApp::new(dependencies);
+
+    // The second sync only fetches what was updated since, storing nothing twice.
+    for _ in 0..2 {
+        let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+        assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    }
+
+    let query = json!({ "repositories": [{ "owner": "acme", "name": "rocket" }], "metrics": ["daily", "issues", "prs"] });
+    let comparison: Value = app.post_json("/github/repo_stars/compare", &query).await?.json()?;
+    let series = &comparison["series"][0];
+    assert_eq!(series["start"], json!(first_day));
+    assert_eq!(series["issues"], json!({ "opened": [1, 1, 0, 0, 0], "closed": [0, 0, 1, 0, 0] }));
+    assert_eq!(series["prs"], json!({ "opened": [1, 0, 0, 0, 0], "closed": [0, 1, 0, 0, 0] }));
+    Ok(())
+}
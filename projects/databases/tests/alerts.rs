@@ -0,0 +1,84 @@
+//! Alert rules firing on daily and total star counts, once per day of activity
+//!
+//! No database needed.
+
+use chrono::NaiveDate;
+use projects_databases::db::alert_rule::models::{AlertKind, AlertRule};
+use projects_databases::testing::{day, TestResult};
+use uuid::Uuid;
+
+fn rule(threshold: i32, created_on: &str, last_triggered_on: Option<&str>) -> Result<AlertRule, chrono::ParseError> {
+    Ok(AlertRule {
+        id: Uuid::nil(),
+        repository_id: Uuid::nil(),
+        kind: String::new(),
+        threshold,
+        target: "webhook".into(),
+        destination: "https://hooks.example.com/stars".into(),
+        last_triggered_on: last_triggered_on.map(day).transpose()?,
+        created_at: day(created_on)?.and_time(chrono::NaiveTime::MIN),
+    })
+}
+
+/// 5, 12, 3 then 15 stars from 2025-01-01 on.
+fn daily() -> Result<Vec<(NaiveDate, i64)>, chrono::ParseError> {
+    Ok(vec![(day("2025-01-01")?, 5), (day("2025-01-02")?, 12), (day("2025-01-03")?, 3), (day("2025-01-04")?, 15)])
+}
+
+fn cumulative() -> Result<Vec<(NaiveDate, i64)>, chrono::ParseError> {
+    Ok(vec![(day("2025-01-01")?, 5), (day("2025-01-02")?, 17), (day("2025-01-03")?, 20), (day("2025-01-04")?, 35)])
+}
+
+#[test]
+fn daily_rules_fire_on_the_latest_day_above_the_threshold() -> TestResult {
+    let fired = rule(10, "2025-01-01", None)?.trigger(AlertKind::DailyStars, &daily()?, &cumulative()?);
+    assert_eq!(fired, Some((day("2025-01-04")?, 15)));
+
+    // Reaching the threshold isn't enough.
+    assert_eq!(rule(15, "2025-01-01", None)?.trigger(AlertKind::DailyStars, &daily()?, &cumulative()?), None);
+    Ok(())
+}
+
+#[test]
+fn daily_rules_only_fire_after_the_day_they_last_fired() -> TestResult {
+    let fired = rule(10, "2025-01-01", Some("2025-01-02"))?.trigger(AlertKind::DailyStars, &daily()?, &cumulative()?);
+    assert_eq!(fired, Some((day("2025-01-04")?, 15)));
+
+    assert_eq!(rule(10, "2025-01-01", Some("2025-01-04"))?.trigger(AlertKind::DailyStars, &daily()?, &cumulative()?), None);
+    assert_eq!(rule(10, "2025-01-01", Some("2025-01-05"))?.trigger(AlertKind::DailyStars, &daily()?, &cumulative()?), None);
+    assert_eq!(rule(10, "2025-01-01", Some(&NaiveDate::MAX.to_string()))?.trigger(AlertKind::DailyStars, &daily()?, &cumulative()?), None);
+    Ok(())
+}
+
+#[test]
+fn daily_rules_ignore_the_days_before_they_were_created() -> TestResult {
+    let daily = &daily()?[..3];
+    assert_eq!(rule(10, "2025-01-03", None)?.trigger(AlertKind::DailyStars, daily, &cumulative()?), None);
+    assert_eq!(rule(10, "2025-01-02", None)?.trigger(AlertKind::DailyStars, daily, &cumulative()?), Some((day("2025-01-02")?, 12)));
+    Ok(())
+}
+
+#[test]
+fn total_rules_fire_on_the_day_the_total_is_reached() -> TestResult {
+    let fired = rule(20, "2025-01-01", None)?.trigger(AlertKind::TotalStars, &daily()?, &cumulative()?);
+    assert_eq!(fired, Some((day("2025-01-03")?, 20)));
+
+    assert_eq!(rule(36, "2025-01-01", None)?.trigger(AlertKind::TotalStars, &daily()?, &cumulative()?), None);
+    Ok(())
+}
+
+#[test]
+fn total_rules_reached_before_they_were_created_never_fire() -> TestResult {
+    assert_eq!(rule(20, "2025-01-04", None)?.trigger(AlertKind::TotalStars, &daily()?, &cumulative()?), None);
+    assert_eq!(rule(30, "2025-01-04", None)?.trigger(AlertKind::TotalStars, &daily()?, &cumulative()?), Some((day("2025-01-04")?, 35)));
+    Ok(())
+}
+
+#[test]
+fn total_rules_fire_once() -> TestResult {
+    let mut extended = cumulative()?;
+    extended.push((day("2025-01-05")?, 50));
+    assert_eq!(rule(20, "2025-01-01", Some("2025-01-03"))?.trigger(AlertKind::TotalStars, &daily()?, &extended), None);
+    assert_eq!(rule(40, "2025-01-01", Some("2025-01-03"))?.trigger(AlertKind::TotalStars, &daily()?, &extended), None);
+    Ok(())
+}
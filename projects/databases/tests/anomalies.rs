@@ -0,0 +1,72 @@
+//! Days flagged as star spikes against the average of the days before them
+//!
+//! Fixed series but for the settings check, answered through the endpoint.
+
+use axum::http::StatusCode;
+use chrono::{Duration, NaiveDate};
+use projects_databases::analytics::anomalies::{detect_anomalies, AnomalyConfig};
+use projects_databases::testing::{day, MockGitHub, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+
+/// Daily series starting on 2025-01-01.
+fn series(stars: &[i64]) -> Result<Vec<(NaiveDate, i64)>, chrono::ParseError> {
+    let first = day("2025-01-01")?;
+    Ok((0..).zip(stars).map(|(x, &count)| (first + Duration::days(x), count)).collect())
+}
+
+fn config(window: usize) -> AnomalyConfig {
+    AnomalyConfig { window, ..AnomalyConfig::default() }
+}
+
+#[test]
+fn flat_series_have_no_spike() -> TestResult {
+    assert!(detect_anomalies(&series(&[20; 30])?, &config(7)).is_empty());
+    Ok(())
+}
+
+#[test]
+fn spikes_are_days_over_the_multiple_of_the_trailing_average() -> TestResult {
+    let daily = series(&[10, 10, 10, 10, 31, 30, 10])?;
+    let spikes = detect_anomalies(&daily, &config(4));
+    assert_eq!(spikes.len(), 1);
+    assert_eq!((spikes[0].day, spikes[0].stars), (day("2025-01-05")?, 31));
+    assert!((spikes[0].trailing_average - 10.0).abs() < 1e-9, "{}", spikes[0].trailing_average);
+    Ok(())
+}
+
+#[test]
+fn days_under_min_stars_are_never_spikes() -> TestResult {
+    let daily = series(&[0, 0, 0, 9, 0, 0, 0, 12])?;
+    let spikes = detect_anomalies(&daily, &config(3));
+    assert_eq!(spikes.iter().map(|spike| spike.day).collect::<Vec<_>>(), [day("2025-01-08")?]);
+    Ok(())
+}
+
+#[test]
+fn days_without_a_full_window_before_are_not_judged() -> TestResult {
+    let daily = series(&[0, 50, 0, 0])?;
+    assert!(detect_anomalies(&daily, &config(3)).is_empty());
+    assert!(detect_anomalies(&daily, &config(usize::MAX)).is_empty());
+    assert!(detect_anomalies(&[], &config(3)).is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn settings_out_of_range_are_rejected() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let uri = "/github/repo_stars/anomalies/acme/rocket/detect";
+    let response = app.post_json(uri, &json!({ "window": 18446744073709551615u64, "multiple": 0.0 })).await?;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY, "{}", String::from_utf8_lossy(&response.body));
+    assert_eq!(response.json::<Value>()?["details"]["fields"], json!([
+        { "field": "multiple", "message": "must be positive" },
+        { "field": "window", "message": "must be between 1 and 365" },
+    ]));
+
+    let response = app.post_json(uri, &json!({ "window": 365 })).await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! Archived repositories, hidden from listings, charts and scheduled jobs with their data kept
+//!
+//! Archiving and unarchiving through the API, and star events of an archived
+//! repository dropped by the webhook.
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use chrono::{TimeZone, Utc};
+use projects_databases::db::repository::queries::list_forge_repositories;
+use projects_databases::endpoints::github::webhooks::signature::WebhookSecret;
+use projects_databases::testing::{test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use ring::hmac;
+use serde_json::{json, Value};
+
+fn archive(method: Method) -> Result<Request<Body>, axum::http::Error> {
+    Request::builder()
+        .method(method)
+        .uri("/github/repositories/acme/rocket/archive")
+        .body(Body::empty())
+}
+
+/// `star` event of `login` starring acme/rocket, signed with `secret`.
+fn star_event(secret: &str, login: &str) -> Result<Request<Body>, axum::http::Error> {
+    let body = json!({
+        "action": "created",
+        "starred_at": "2025-03-02T10:00:00Z",
+        "repository": { "name": "rocket", "owner": { "login": "acme" } },
+        "sender": { "login": login },
+    })
+    .to_string();
+    let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()), body.as_bytes());
+    let signature: String = tag.as_ref().iter().map(|byte| format!("{byte:02x}")).collect();
+    Request::builder()
+        .method(Method::POST)
+        .uri("/github/webhooks/star")
+        .header("content-type", "application/json")
+        .header("x-github-event", "star")
+        .header("x-hub-signature-256", format!("sha256={signature}"))
+        .body(Body::from(body))
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn archived_repositories_are_hidden_until_unarchived() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let starred_at = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).single().ok_or("invalid date")?;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_stargazer("ada", starred_at)).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    assert_eq!(app.request(archive(Method::PUT)?).await?.status, StatusCode::NOT_FOUND);
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+
+    // Archiving twice keeps the first date.
+    assert_eq!(app.request(archive(Method::PUT)?).await?.status, StatusCode::NO_CONTENT);
+    let archived: Vec<Value> = app.get("/github/repositories?archived=true").await?.json()?;
+    let archived_at = archived[0]["archived_at"].clone();
+    assert!(archived_at.is_string());
+    assert_eq!(app.request(archive(Method::PUT)?).await?.status, StatusCode::NO_CONTENT);
+
+    let listed: Vec<Value> = app.get("/github/repositories").await?.json()?;
+    assert!(listed.is_empty());
+    let archived: Vec<Value> = app.get("/github/repositories?archived=true").await?.json()?;
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0]["archived_at"], archived_at);
+    let mut conn = database.pool().get()?;
+    assert!(list_forge_repositories(&mut conn, "github")?.is_empty());
+
+    let query = json!({ "repositories": [{ "owner": "acme", "name": "rocket" }] });
+    let response = app.post_json("/github/repo_stars/compare", &query).await?;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    let error: Value = response.json()?;
+    assert_eq!(error["code"], json!("repository_archived"));
+    for chart in ["/github/repo_stars/heatmap/acme/rocket?format=svg", "/github/repo_stars/cohorts/acme/rocket?format=svg"] {
+        let response = app.get(chart).await?;
+        assert_eq!(response.status, StatusCode::CONFLICT, "{chart}");
+        let error: Value = response.json()?;
+        assert_eq!(error["code"], json!("repository_archived"));
+    }
+    let response = app.post_json("/github/repo_stars/compare/chart", &query).await?;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+
+    // The stored stars are still there, and back in comparisons once unarchived.
+    let as_of: Value = app.get("/github/repo_stars/as_of/acme/rocket?date=2025-03-01").await?.json()?;
+    assert_eq!(as_of["stars"], json!(1));
+    assert_eq!(app.request(archive(Method::DELETE)?).await?.status, StatusCode::NO_CONTENT);
+    let listed: Vec<Value> = app.get("/github/repositories").await?.json()?;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["archived_at"], Value::Null);
+    let response = app.post_json("/github/repo_stars/compare", &query).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let response = app.get("/github/repo_stars/heatmap/acme/rocket?format=svg").await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn star_events_of_archived_repositories_are_not_stored() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let starred_at = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).single().ok_or("invalid date")?;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_stargazer("ada", starred_at)).await;
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    dependencies.webhook_secret = Some(WebhookSecret::new("hush"));
+    let app = TestApp::new(dependencies);
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+
+    assert_eq!(app.request(archive(Method::PUT)?).await?.status, StatusCode::NO_CONTENT);
+    let response = app.request(star_event("hush", "grace")?).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    assert_eq!(response.json::<Value>()?, json!({ "outcome": "archived" }));
+    let as_of: Value = app.get("/github/repo_stars/as_of/acme/rocket?date=2025-03-02").await?.json()?;
+    assert_eq!(as_of["stars"], json!(1));
+
+    // Stored again once unarchived.
+    assert_eq!(app.request(archive(Method::DELETE)?).await?.status, StatusCode::NO_CONTENT);
+    let response = app.request(star_event("hush", "grace")?).await?;
+    assert_eq!(response.json::<Value>()?, json!({ "outcome": "stored" }));
+    let as_of: Value = app.get("/github/repo_stars/as_of/acme/rocket?date=2025-03-02").await?.json()?;
+    assert_eq!(as_of["stars"], json!(2));
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Star count and stargazers of a repository as of a past date
+//!
+//! Stargazers are listed a page at a time, and stars compacted into daily
+//! aggregates still count without being listed.
+
+use axum::http::StatusCode;
+use chrono::{TimeZone, Utc};
+use projects_databases::db::daily_star_aggregate::queries::compact_stars;
+use projects_databases::sync_queue::{StargazerDetails, SyncQueue};
+use projects_databases::testing::{test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn star_count_and_stargazers_as_of_a_date() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let day = |day, hour| Utc.with_ymd_and_hms(2025, 3, day, hour, 0, 0).single().ok_or("invalid date");
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_stargazer("ada", day(1, 9)?)
+        .with_stargazer("star-bot", day(2, 10)?)
+        .with_stargazer("grace", day(2, 23)?)
+        .with_stargazer("linus", day(3, 0)?)
+        .with_stargazer("ken", day(5, 12)?)).await;
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    let collect = StargazerDetails { bots: true, ..StargazerDetails::default() };
+    dependencies.sync_queue = SyncQueue::start(1, database.pool().clone(), dependencies.forge_clients.clone(), dependencies.notifier.clone(), dependencies.live_updates.clone(), collect, dependencies.stargazer_ids.clone(), dependencies.chart_cache.clone());
+    let app = TestApp::new(dependencies);
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    let repository_id: Uuid = summary["repository_id"].as_str().ok_or("summary without a repository ID")?.parse()?;
+
+    let as_of: Value = app.get("/github/repo_stars/as_of/acme/rocket?date=2025-03-02").await?.json()?;
+    assert_eq!(as_of, json!({ "date": "2025-03-02", "stars": 3, "compacted_stars": 0 }));
+    let as_of: Value = app.get("/github/repo_stars/as_of/acme/rocket?date=2025-03-02&exclude_bots=true").await?.json()?;
+    assert_eq!(as_of["stars"], json!(2));
+
+    // The stargazers of the day, a page at a time.
+    let first: Value = app.get("/github/repo_stars/as_of/acme/rocket?date=2025-03-04&stargazers=true&limit=2").await?.json()?;
+    assert_eq!(first["stars"], json!(4));
+    assert_eq!(first["stargazers"].as_array().map(Vec::len), Some(2));
+    assert_eq!(first["next"], json!({ "starred_at": "2025-03-02T10:00:00", "stargazer": "star-bot" }));
+    let uri = "/github/repo_stars/as_of/acme/rocket?date=2025-03-04&stargazers=true&limit=2&after_starred_at=2025-03-02T10:00:00&after_stargazer=star-bot";
+    let second: Value = app.get(uri).await?.json()?;
+    let logins: Vec<&Value> = second["stargazers"].as_array().into_iter().flatten().map(|star| &star["stargazer"]).collect();
+    assert_eq!(logins, [&json!("grace"), &json!("linus")]);
+    assert!(second.get("next").is_none());
+
+    // Compacted stars still count, without being listed.
+    let mut conn = database.pool().get()?;
+    compact_stars(&mut conn, repository_id, day(3, 0)?.date_naive())?;
+    let as_of: Value = app.get("/github/repo_stars/as_of/acme/rocket?date=2025-03-04&stargazers=true").await?.json()?;
+    assert_eq!((&as_of["stars"], &as_of["compacted_stars"]), (&json!(4), &json!(3)));
+    assert_eq!(as_of["stargazers"], json!([{ "stargazer": "linus", "starred_at": "2025-03-03T00:00:00" }]));
+
+    let response = app.get("/github/repo_stars/as_of/acme/rocket?date=2025-03-04&after_stargazer=grace").await?;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    let response = app.get("/github/repo_stars/as_of/acme/unknown?date=2025-03-04").await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    Ok(())
+}
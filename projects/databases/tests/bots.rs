@@ -0,0 +1,67 @@
+//! Stars of suspected bots, flagged by syncs and left out of reads on demand
+//!
+//! Logins are judged on their look and on the account's age when starring,
+//! then `exclude_bots` drops the flagged stars from daily counts and stats.
+
+use axum::http::StatusCode;
+use chrono::{TimeDelta, TimeZone, Utc};
+use projects_databases::bots::{is_throwaway_account, login_looks_automated};
+use projects_databases::sync_queue::{StargazerDetails, SyncQueue};
+use projects_databases::testing::{test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+
+#[test]
+fn automated_looking_logins_are_suspected() {
+    for login in ["dependabot[bot]", "star-bot", "Bot_farm", "acme-bots", "user20240611", "5f2a9c0e7b1d4e3a"] {
+        assert!(login_looks_automated(login), "{login}");
+    }
+    for login in ["octocat", "robotics", "abbott", "ada1984", "deadbeef", "cafe-1234"] {
+        assert!(!login_looks_automated(login), "{login}");
+    }
+}
+
+#[test]
+fn accounts_starring_in_their_first_week_are_throwaway() {
+    let starred_at = Utc::now();
+    assert!(is_throwaway_account(starred_at - TimeDelta::days(2), starred_at));
+    assert!(!is_throwaway_account(starred_at - TimeDelta::days(8), starred_at));
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn stars_of_suspected_bots_are_flagged_and_excluded_on_demand() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let day = |day| Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).single().ok_or("invalid date");
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_stargazer("ada", day(1)?)
+        .with_stargazer("star-bot", day(1)?)
+        .with_stargazer("fresh", day(2)?)
+        .with_stargazer("gone", day(2)?)
+        .with_stargazer("user20250303", day(3)?)).await;
+    // Gone's account was deleted since, only its login is judged.
+    github.mount_accounts(&[("ada", day(1)? - TimeDelta::days(900)), ("fresh", day(1)?), ("user20250303", day(1)? - TimeDelta::days(900))]).await;
+
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    let collect = StargazerDetails { bots: true, ..StargazerDetails::default() };
+    dependencies.sync_queue = SyncQueue::start(1, database.pool().clone(), dependencies.forge_clients.clone(), dependencies.notifier.clone(), dependencies.live_updates.clone(), collect, dependencies.stargazer_ids.clone(), dependencies.chart_cache.clone());
+    let app = TestApp::new(dependencies);
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+
+    let repository = json!({ "owner": "acme", "name": "rocket" });
+    let every_star: Value = app.post_json("/github/repo_stars/read_per_day", &repository).await?.json()?;
+    assert_eq!(every_star, json!([["2025-03-01", 2], ["2025-03-02", 2], ["2025-03-03", 1]]));
+    let humans: Value = app.post_json("/github/repo_stars/read_per_day?exclude_bots=true", &repository).await?.json()?;
+    assert_eq!(humans, json!([["2025-03-01", 1], ["2025-03-02", 1]]));
+
+    let page: Value = app.post_json("/github/repo_stars/read_per_day?exclude_bots=true", &json!({ "owner": "acme", "name": "rocket", "limit": 1 })).await?.json()?;
+    assert_eq!((&page["data"], &page["total"]), (&json!([["2025-03-01", 1]]), &json!(2)));
+
+    let stats: Value = app.get("/github/repo_stars/stats/acme/rocket").await?.json()?;
+    assert_eq!(stats["total_stars"], 5);
+    let stats: Value = app.get("/github/repo_stars/stats/acme/rocket?exclude_bots=true").await?.json()?;
+    assert_eq!(stats["total_stars"], 2);
+    Ok(())
+}
@@ -0,0 +1,163 @@
+//! Rendered charts served again until they expire or their size bound is hit,
+//! and comparison charts and social cards drawn again once a sync of one of
+//! their repositories ends
+
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::http::StatusCode;
+use chrono::NaiveDate;
+use projects_databases::chart_cache::{ChartCache, ChartCacheConfig, ChartKey, RepositoryVersion};
+use projects_databases::db::repository::queries::link_fork;
+use projects_databases::testing::{day, test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn key(repository_id: Uuid, config: &str) -> ChartKey {
+    ChartKey {
+        tenant_id: Uuid::nil(),
+        repositories: vec![RepositoryVersion { repository_id, last_fetched_at: None, stars: 3 }],
+        config: config.into(),
+        day: NaiveDate::MIN,
+    }
+}
+
+#[tokio::test]
+async fn charts_expire_and_the_least_recently_served_go_first() {
+    let charts = ChartCache::new(ChartCacheConfig { ttl: Duration::from_secs(60), max_bytes: 10 });
+    let repository_id = Uuid::new_v4();
+    charts.insert(key(repository_id, "a"), Bytes::from("aaaa"));
+    charts.insert(key(repository_id, "b"), Bytes::from("bbbb"));
+    assert_eq!(charts.get(&key(repository_id, "a")), Some(Bytes::from("aaaa")));
+
+    // `b` was served last before `a`.
+    charts.insert(key(repository_id, "c"), Bytes::from("cccc"));
+    assert_eq!(charts.get(&key(repository_id, "b")), None);
+    assert!(charts.get(&key(repository_id, "a")).is_some());
+    assert_eq!(charts.bytes(), 8);
+
+    // Larger than the whole bound, it isn't kept.
+    charts.insert(key(repository_id, "d"), Bytes::from("d".repeat(11)));
+    assert_eq!((charts.len(), charts.bytes()), (2, 8));
+
+    let charts = ChartCache::new(ChartCacheConfig { ttl: Duration::from_millis(20), max_bytes: 10 });
+    charts.insert(key(repository_id, "a"), Bytes::from("aaaa"));
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert_eq!(charts.get(&key(repository_id, "a")), None);
+    assert!(charts.is_empty());
+}
+
+#[test]
+fn charts_of_a_repository_are_invalidated_together() {
+    let charts = ChartCache::default();
+    let (synced, other) = (Uuid::new_v4(), Uuid::new_v4());
+    let mut compared = key(other, "both");
+    compared.repositories.push(RepositoryVersion { repository_id: synced, last_fetched_at: None, stars: 1 });
+    charts.insert(key(synced, "alone"), Bytes::from("alone"));
+    charts.insert(compared, Bytes::from("both"));
+    charts.insert(key(other, "other"), Bytes::from("other"));
+
+    charts.invalidate(synced);
+    assert_eq!(charts.len(), 1);
+    assert!(charts.get(&key(other, "other")).is_some());
+
+    // A TTL of 0 keeps nothing.
+    let charts = ChartCache::new(ChartCacheConfig { ttl: Duration::ZERO, ..ChartCacheConfig::default() });
+    charts.insert(key(synced, "alone"), Bytes::from("alone"));
+    assert!(charts.is_empty());
+}
+
+#[cfg(feature = "charting")]
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn compare_charts_are_drawn_again_once_a_sync_ends() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 1])).await;
+    let dependencies = test_dependencies(&database, github.forge_clients()?);
+    let charts = dependencies.chart_cache.clone();
+    let app = TestApp::new(dependencies);
+    let sync = json!({ "owner": "acme", "name": "rocket" });
+    assert_eq!(app.post_json("/github/repo_stars/update", &sync).await?.status, StatusCode::OK);
+
+    let chart = json!({ "repositories": [{ "owner": "acme", "name": "rocket" }] });
+    let first = app.post_json("/github/repo_stars/compare/chart", &chart).await?;
+    assert_eq!(first.status, StatusCode::OK, "{}", String::from_utf8_lossy(&first.body));
+    let second = app.post_json("/github/repo_stars/compare/chart", &chart).await?;
+    assert_eq!(second.body, first.body);
+    assert_eq!(charts.len(), 1);
+
+    // Another look is another chart.
+    let bars = json!({ "repositories": [{ "owner": "acme", "name": "rocket" }], "chart_type": "bar" });
+    assert_eq!(app.post_json("/github/repo_stars/compare/chart", &bars).await?.status, StatusCode::OK);
+    assert_eq!(charts.len(), 2);
+
+    assert_eq!(app.post_json("/github/repo_stars/update", &sync).await?.status, StatusCode::OK);
+    assert!(charts.is_empty());
+    assert_eq!(app.post_json("/github/repo_stars/compare/chart", &chart).await?.status, StatusCode::OK);
+    assert_eq!(charts.len(), 1);
+
+    // Unknown repositories are still reported, and nothing is kept for them.
+    let unknown = json!({ "repositories": [{ "owner": "acme", "name": "missing" }] });
+    assert_eq!(app.post_json("/github/repo_stars/compare/chart", &unknown).await?.status, StatusCode::NOT_FOUND);
+    assert_eq!(charts.len(), 1);
+    Ok(())
+}
+
+#[cfg(feature = "charting")]
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn social_cards_are_drawn_again_once_a_sync_ends() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 1])).await;
+    let dependencies = test_dependencies(&database, github.forge_clients()?);
+    let charts = dependencies.chart_cache.clone();
+    let app = TestApp::new(dependencies);
+    let sync = json!({ "owner": "acme", "name": "rocket" });
+    assert_eq!(app.post_json("/github/repo_stars/update", &sync).await?.status, StatusCode::OK);
+
+    let first = app.get("/github/repo_stars/social_card/acme/rocket").await?;
+    assert_eq!(first.status, StatusCode::OK, "{}", String::from_utf8_lossy(&first.body));
+    let second = app.get("/github/repo_stars/social_card/acme/rocket").await?;
+    assert_eq!(second.body, first.body);
+    assert_eq!(second.headers["content-type"], "image/png");
+    assert_eq!(charts.len(), 1);
+
+    assert_eq!(app.get("/github/repo_stars/social_card/acme/rocket?theme=dark").await?.status, StatusCode::OK);
+    assert_eq!(charts.len(), 2);
+
+    assert_eq!(app.post_json("/github/repo_stars/update", &sync).await?.status, StatusCode::OK);
+    assert!(charts.is_empty());
+    Ok(())
+}
+
+#[cfg(feature = "charting")]
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn charts_with_forks_are_drawn_again_once_a_fork_syncs() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 1])).await;
+    github.mount_repository(MockRepository::new("fork", "rocket").with_daily_stars(day("2025-01-02")?, &[2])).await;
+    let dependencies = test_dependencies(&database, github.forge_clients()?);
+    let charts = dependencies.chart_cache.clone();
+    let app = TestApp::new(dependencies);
+    let mut ids = Vec::new();
+    for owner in ["acme", "fork"] {
+        let response = app.post_json("/github/repo_stars/update", &json!({ "owner": owner, "name": "rocket" })).await?;
+        assert_eq!(response.status, StatusCode::OK);
+        ids.push(response.json::<Value>()?["repository_id"].as_str().ok_or("no repository id")?.parse::<Uuid>()?);
+    }
+    let mut conn = database.pool().get()?;
+    link_fork(&mut conn, ids[1], ids[0])?;
+
+    let chart = json!({ "repositories": [{ "owner": "acme", "name": "rocket" }], "metric": "with_forks" });
+    assert_eq!(app.post_json("/github/repo_stars/compare/chart", &chart).await?.status, StatusCode::OK);
+    assert_eq!(charts.len(), 1);
+
+    let sync = json!({ "owner": "fork", "name": "rocket" });
+    assert_eq!(app.post_json("/github/repo_stars/update", &sync).await?.status, StatusCode::OK);
+    assert!(charts.is_empty());
+    Ok(())
+}
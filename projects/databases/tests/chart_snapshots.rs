@@ -0,0 +1,185 @@
+//! Comparison charts of fixed series, checked against the SVG stored in `tests/snapshots`
+//!
+//! Any change to the drawing, axes, colors or legends included, shows up as a
+//! failing test. When the change is wanted, write the snapshots again and
+//! review them along with the code:
+//!
+//! ```sh
+//! UPDATE_SNAPSHOTS=1 cargo test -p projects_databases --test chart_snapshots
+//! ```
+//!
+//! Decimals are rounded before comparing, so float noise across platforms
+//! isn't a change.
+
+#![cfg(feature = "charting")]
+
+use std::path::PathBuf;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use projects_databases::analytics::compare::{compare_series, Alignment, CompareMetric, CompareOptions, Comparison, RepositoryHistory};
+use projects_databases::chart::{AxisOptions, ChartTheme};
+use projects_databases::multi_repo_chart::{
+    generate_multi_repo_chart, ChartConfig, ChartType, DashStyle, ReleaseMarker, SeriesStyle, Watermark, WatermarkPosition,
+};
+use projects_databases::testing::{day, TestResult};
+
+const METRICS: [CompareMetric; 3] = [CompareMetric::Daily, CompareMetric::Cumulative, CompareMetric::RollingAverage];
+
+/// `stars[n]` stars on the `n`th day from `first_day`, days without stars left out as the database does.
+fn history(repository: &str, first_day: NaiveDate, stars: &[i64]) -> RepositoryHistory {
+    RepositoryHistory {
+        repository: repository.into(),
+        daily_counts: first_day.iter_days().zip(stars).filter(|(_, stars)| **stars > 0).map(|(day, stars)| (day, *stars)).collect(),
+        from_snapshots: false,
+        with_forks: None,
+        traffic: None,
+        activity: None,
+        first_contributions: None,
+    }
+}
+
+/// Three repositories over the first weeks of 2025: a steady one, a launch spike and a late starter.
+fn comparison(alignment: Alignment) -> Result<Comparison, chrono::ParseError> {
+    let repositories = [
+        history("acme/rocket", day("2025-01-01")?, &[3, 4, 2, 5, 3, 4, 6, 2, 3, 5, 4, 3, 2, 4, 5, 3, 4, 6, 3, 2, 4]),
+        history("acme/comet", day("2025-01-04")?, &[40, 25, 12, 8, 5, 3, 0, 2, 1, 4, 0, 1, 2, 0, 1, 3, 0, 1]),
+        history("orbit/probe", day("2025-01-12")?, &[1, 0, 2, 1, 3, 2, 4, 3, 5, 6]),
+    ];
+    Ok(compare_series(&repositories, &METRICS, &CompareOptions { alignment, ..CompareOptions::default() }, day("2025-01-21")?))
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/multi_repo_chart").join(format!("{name}.svg"))
+}
+
+/// `svg` with decimals rounded to one place and trailing spaces dropped.
+fn normalize(svg: &str) -> String {
+    let chars: Vec<char> = svg.chars().collect();
+    let mut normalized = String::with_capacity(svg.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        while chars.get(i).is_some_and(char::is_ascii_digit) {
+            i += 1;
+        }
+        if i == start {
+            normalized.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        // A dot ending a sentence or a host name isn't a decimal point.
+        if chars.get(i) != Some(&'.') || !chars.get(i + 1).is_some_and(char::is_ascii_digit) {
+            normalized.extend(&chars[start..i]);
+            continue;
+        }
+        i += 1;
+        while chars.get(i).is_some_and(char::is_ascii_digit) {
+            i += 1;
+        }
+        let number: String = chars[start..i].iter().collect();
+        match number.parse::<f64>() {
+            Ok(value) => normalized.push_str(&format!("{value:.1}")),
+            Err(_) => normalized.push_str(&number),
+        }
+    }
+    normalized.lines().map(str::trim_end).collect::<Vec<_>>().join("\n")
+}
+
+/// Compares `svg` with the snapshot `name`, or stores it with `UPDATE_SNAPSHOTS` set.
+fn assert_snapshot(name: &str, svg: &str) -> TestResult {
+    let path = snapshot_path(name);
+    let actual = normalize(svg);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, actual + "\n")?;
+        return Ok(());
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .map_err(|error| format!("{}: {error}, run with UPDATE_SNAPSHOTS=1 to create it", path.display()))?;
+    if let Some((line, (expected, actual))) = expected.lines().zip(actual.lines()).enumerate().find(|(_, (expected, actual))| expected != actual) {
+        panic!("{name} differs from {} at line {}:\n- {expected}\n+ {actual}\nrun with UPDATE_SNAPSHOTS=1 if the change is wanted", path.display(), line + 1);
+    }
+    assert_eq!(expected.lines().count(), actual.lines().count(), "{name} differs from {} in length", path.display());
+    Ok(())
+}
+
+#[test]
+fn calendar_cumulative_lines() -> TestResult {
+    let config = ChartConfig { metric: CompareMetric::Cumulative, ..ChartConfig::default() };
+    assert_snapshot("calendar_cumulative_lines", &generate_multi_repo_chart(&comparison(Alignment::Calendar)?, &config)?)
+}
+
+#[test]
+fn relative_daily_bars_with_secondary_metric() -> TestResult {
+    let config = ChartConfig {
+        chart_type: ChartType::Bar,
+        metric: CompareMetric::Daily,
+        secondary_metric: Some(CompareMetric::Cumulative),
+        title: Some("Launches compared".into()),
+        ..ChartConfig::default()
+    };
+    assert_snapshot("relative_daily_bars_with_secondary_metric", &generate_multi_repo_chart(&comparison(Alignment::Relative)?, &config)?)
+}
+
+#[test]
+fn stacked_areas_on_dark_theme() -> TestResult {
+    let config = ChartConfig {
+        chart_type: ChartType::StackedArea,
+        metric: CompareMetric::RollingAverage,
+        theme: ChartTheme::dark(),
+        width: 800,
+        height: 400,
+        ..ChartConfig::default()
+    };
+    assert_snapshot("stacked_areas_on_dark_theme", &generate_multi_repo_chart(&comparison(Alignment::Calendar)?, &config)?)
+}
+
+#[test]
+fn styled_series_with_watermark_and_footer() -> TestResult {
+    let config = ChartConfig {
+        chart_type: ChartType::Area,
+        axes: AxisOptions { max_x_ticks: 10, max_y_ticks: 4, rotate_x_labels: true },
+        series_styles: vec![SeriesStyle {
+            repository: "ACME/Comet".into(),
+            color: Some("#8e44ad".parse()?),
+            line_width: Some(4),
+            dash: DashStyle::Dashed,
+        }],
+        watermark: Some(Watermark { text: "example.com".into(), position: WatermarkPosition::TopLeft, opacity: 0.3 }),
+        generated_at: Utc.with_ymd_and_hms(2025, 1, 21, 12, 30, 0).single(),
+        ..ChartConfig::default()
+    };
+    assert_snapshot("styled_series_with_watermark_and_footer", &generate_multi_repo_chart(&comparison(Alignment::Calendar)?, &config)?)
+}
+
+#[test]
+fn release_markers_over_calendar_lines() -> TestResult {
+    let release = |repository: &str, tag_name: &str, on: &str| -> Result<ReleaseMarker, chrono::ParseError> {
+        Ok(ReleaseMarker { repository: repository.into(), tag_name: tag_name.into(), day: day(on)? })
+    };
+    let config = ChartConfig {
+        metric: CompareMetric::Daily,
+        // Probe's release comes after the last day and the unknown repository isn't compared,
+        // neither is drawn.
+        releases: vec![
+            release("acme/rocket", "v1.0", "2025-01-09")?,
+            release("acme/comet", "v0.1", "2025-01-04")?,
+            release("ACME/Comet", "v0.2", "2025-01-05")?,
+            release("orbit/probe", "v3", "2025-02-01")?,
+            release("acme/unknown", "v9", "2025-01-10")?,
+        ],
+        ..ChartConfig::default()
+    };
+    assert_snapshot("release_markers_over_calendar_lines", &generate_multi_repo_chart(&comparison(Alignment::Calendar)?, &config)?)
+}
+
+#[test]
+fn normalize_rounds_decimals_only() {
+    assert_eq!(
+        normalize("<svg xmlns=\"http://www.w3.org/2000/svg\">  \n<path d=\"M 10.04 2.0000001 L 3 4\"/>\n<text>2025-01-21.</text>"),
+        "<svg xmlns=\"http://www.w3.org/2000/svg\">\n<path d=\"M 10.0 2.0 L 3 4\"/>\n<text>2025-01-21.</text>",
+    );
+}
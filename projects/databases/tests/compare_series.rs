@@ -0,0 +1,134 @@
+//! Series of compared repositories, rescaled to percentages or filled before their data
+
+use chrono::{NaiveDate, NaiveDateTime};
+use projects_databases::analytics::compare::{compare_series, Alignment, CompareMetric, CompareOptions, Normalization, RepositoryHistory};
+use projects_databases::analytics::series::{snapshot_daily_counts, FillPolicy};
+use projects_databases::testing::{day, TestResult};
+
+/// A small repository and one a hundred times larger, growing alike.
+fn histories() -> Result<Vec<RepositoryHistory>, chrono::ParseError> {
+    let history = |repository: &str, scale: i64| -> Result<RepositoryHistory, chrono::ParseError> {
+        Ok(RepositoryHistory {
+            repository: repository.into(),
+            daily_counts: vec![(day("2025-01-01")?, 2 * scale), (day("2025-01-03")?, 2 * scale)],
+            from_snapshots: false,
+            with_forks: None,
+            traffic: None,
+            activity: None,
+            first_contributions: None,
+        })
+    };
+    Ok(vec![history("acme/small", 1)?, history("acme/large", 100)?])
+}
+
+#[test]
+fn percent_of_total_ends_at_100() -> TestResult {
+    let comparison = compare_series(&histories()?, &[CompareMetric::Normalized], &CompareOptions { alignment: Alignment::Calendar, normalization: Normalization::PercentOfTotal, ..CompareOptions::default() }, day("2025-01-04")?);
+    assert_eq!(comparison.normalization, Some(Normalization::PercentOfTotal));
+    for series in &comparison.series {
+        assert_eq!(series.normalized.as_deref(), Some(&[50.0, 50.0, 100.0, 100.0][..]), "{}", series.repository);
+        assert!(series.cumulative.is_none());
+    }
+    Ok(())
+}
+
+#[test]
+fn index_is_100_at_the_base_date() -> TestResult {
+    let normalization = Normalization::Index { base: day("2025-01-02")? };
+    let comparison = compare_series(&histories()?, &[CompareMetric::Normalized], &CompareOptions { alignment: Alignment::Relative, normalization, ..CompareOptions::default() }, day("2025-01-04")?);
+    assert_eq!(comparison.series[1].normalized.as_deref(), Some(&[100.0, 100.0, 200.0, 200.0][..]));
+
+    // Nothing to be relative to before the first star.
+    let normalization = Normalization::Index { base: day("2024-12-31")? };
+    let comparison = compare_series(&histories()?, &[CompareMetric::Normalized], &CompareOptions { alignment: Alignment::Calendar, normalization, ..CompareOptions::default() }, day("2025-01-04")?);
+    assert_eq!(comparison.series[0].normalized.as_deref(), Some(&[][..]));
+    Ok(())
+}
+
+#[test]
+fn normalization_is_left_out_unless_asked_for() -> TestResult {
+    let comparison = compare_series(&histories()?, &[CompareMetric::Cumulative], &CompareOptions { alignment: Alignment::Calendar, normalization: Normalization::PercentOfTotal, ..CompareOptions::default() }, day("2025-01-04")?);
+    assert_eq!(comparison.normalization, None);
+    assert!(comparison.series.iter().all(|series| series.normalized.is_none()));
+    Ok(())
+}
+
+/// A repository starred since 2025-01-01 and one tracked by snapshots from 2025-01-03, 500 stars
+/// then and 520 the day after.
+fn partial_histories() -> Result<Vec<RepositoryHistory>, chrono::ParseError> {
+    Ok(vec![
+        RepositoryHistory {
+            repository: "acme/stored".into(),
+            daily_counts: vec![(day("2025-01-01")?, 2), (day("2025-01-04")?, 1)],
+            from_snapshots: false,
+            with_forks: None,
+            traffic: None,
+            activity: None,
+            first_contributions: None,
+        },
+        RepositoryHistory {
+            repository: "acme/snapshots".into(),
+            daily_counts: vec![(day("2025-01-03")?, 500), (day("2025-01-04")?, 20)],
+            from_snapshots: true,
+            with_forks: None,
+            traffic: None,
+            activity: None,
+            first_contributions: None,
+        },
+    ])
+}
+
+/// Start, daily stars and running total of a series.
+type Filled = (Option<NaiveDate>, Vec<i64>, Vec<i64>);
+
+fn filled(fill: FillPolicy) -> Result<Vec<Filled>, Box<dyn std::error::Error>> {
+    let options = CompareOptions { fill, ..CompareOptions::default() };
+    let comparison = compare_series(&partial_histories()?, &[CompareMetric::Daily, CompareMetric::Cumulative], &options, day("2025-01-04")?);
+    assert_eq!(comparison.fill, fill);
+    Ok(comparison
+        .series
+        .into_iter()
+        .map(|series| (series.start, series.daily.unwrap_or_default(), series.cumulative.unwrap_or_default()))
+        .collect())
+}
+
+#[test]
+fn zero_fill_counts_no_stars_before_the_tracking_start() -> TestResult {
+    let series = filled(FillPolicy::Zero)?;
+    assert_eq!(series[1], (Some(day("2025-01-01")?), vec![0, 0, 500, 20], vec![0, 0, 500, 520]));
+    Ok(())
+}
+
+#[test]
+fn skip_starts_each_series_on_its_own_data() -> TestResult {
+    let series = filled(FillPolicy::Skip)?;
+    assert_eq!(series[0], (Some(day("2025-01-01")?), vec![2, 0, 0, 1], vec![2, 2, 2, 3]));
+    assert_eq!(series[1], (Some(day("2025-01-03")?), vec![500, 20], vec![500, 520]));
+    Ok(())
+}
+
+#[test]
+fn clamp_takes_the_first_day_as_a_baseline() -> TestResult {
+    let series = filled(FillPolicy::ClampToTrackingStart)?;
+    assert_eq!(series[1], (Some(day("2025-01-03")?), vec![0, 20], vec![500, 520]));
+
+    let options = CompareOptions { fill: FillPolicy::ClampToTrackingStart, ..CompareOptions::default() };
+    let comparison = compare_series(&partial_histories()?, &[CompareMetric::Velocity], &options, day("2025-01-04")?);
+    assert_eq!(comparison.series[1].velocity.as_deref(), Some(&[0.0, 20.0][..]));
+    Ok(())
+}
+
+#[test]
+fn forward_fill_holds_the_count_between_snapshots() -> TestResult {
+    let at = |value: &str| -> Result<NaiveDateTime, chrono::ParseError> { Ok(day(value)?.and_hms_opt(12, 0, 0).unwrap_or_default()) };
+    let snapshots = [(at("2025-01-01")?, 100), (at("2025-01-05")?, 140)];
+    assert_eq!(snapshot_daily_counts(&snapshots, FillPolicy::Zero), vec![
+        (day("2025-01-01")?, 100),
+        (day("2025-01-02")?, 10),
+        (day("2025-01-03")?, 10),
+        (day("2025-01-04")?, 10),
+        (day("2025-01-05")?, 10),
+    ]);
+    assert_eq!(snapshot_daily_counts(&snapshots, FillPolicy::ForwardFill), vec![(day("2025-01-01")?, 100), (day("2025-01-05")?, 40)]);
+    Ok(())
+}
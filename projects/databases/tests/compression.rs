@@ -0,0 +1,67 @@
+//! Compressed responses and uploads
+//!
+//! Responses are encoded as `Accept-Encoding` prefers once large enough, and
+//! CSV imports are read gzipped.
+
+use std::io::Write;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use flate2::{write::GzEncoder, Compression};
+use projects_databases::testing::{MockGitHub, TestApp, TestDatabase, TestResult};
+use serde_json::Value;
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn responses_are_compressed_as_the_client_prefers() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let get = |encodings: Option<&'static str>| {
+        let mut request = Request::get("/api-docs/openapi.json");
+        if let Some(encodings) = encodings {
+            request = request.header(header::ACCEPT_ENCODING, encodings);
+        }
+        request.body(Body::empty())
+    };
+
+    let plain = app.request(get(None)?).await?;
+    assert_eq!(plain.status, StatusCode::OK);
+    assert!(plain.headers.get(header::CONTENT_ENCODING).is_none());
+
+    for (accepted, expected) in [("gzip", "gzip"), ("gzip;q=0.5, br", "br")] {
+        let compressed = app.request(get(Some(accepted))?).await?;
+        assert_eq!(compressed.status, StatusCode::OK);
+        assert_eq!(compressed.headers.get(header::CONTENT_ENCODING).and_then(|value| value.to_str().ok()), Some(expected));
+        assert!(compressed.body.len() < plain.body.len() / 2, "{} bytes of {} compressed", compressed.body.len(), plain.body.len());
+    }
+
+    // Too small to be worth it.
+    let small = Request::get("/github/repositories").header(header::ACCEPT_ENCODING, "gzip").body(Body::empty())?;
+    let small = app.request(small).await?;
+    assert_eq!(small.status, StatusCode::OK);
+    assert!(small.headers.get(header::CONTENT_ENCODING).is_none());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn imports_may_be_uploaded_gzipped() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"stargazer,starred_at\nada,2025-03-01T12:00:00Z\nbob,2025-03-02T12:00:00Z\n")?;
+    let request = Request::post("/github/repo_stars/import?owner=acme&name=rocket")
+        .header(header::CONTENT_TYPE, "text/csv")
+        .header(header::CONTENT_ENCODING, "gzip")
+        .body(Body::from(encoder.finish()?))?;
+
+    let response = app.request(request).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    assert_eq!(summary["inserted"], 2);
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Contributors tracked from the commit history, charted by their first commit along with the stars
+//!
+//! Contributors from before the first star start the total, scheduled runs only
+//! store the commits pushed since, and emails are hashed like stargazers'.
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use chrono::{TimeDelta, Utc};
+use projects_databases::analytics::compare::{compare_series, CompareMetric, CompareOptions, RepositoryHistory};
+use projects_databases::contributors::track_all_contributors;
+use projects_databases::privacy::StargazerIds;
+use projects_databases::testing::{day, test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+
+#[test]
+fn contributors_before_the_first_star_start_the_total() -> TestResult {
+    let history = RepositoryHistory {
+        repository: "acme/rocket".into(),
+        daily_counts: vec![(day("2025-03-02")?, 1)],
+        from_snapshots: false,
+        with_forks: None,
+        traffic: None,
+        activity: None,
+        first_contributions: Some(vec![(day("2025-03-01")?, 1), (day("2025-03-03")?, 2)]),
+    };
+
+    let comparison = compare_series(&[history], &[CompareMetric::Contributors], &CompareOptions::default(), day("2025-03-04")?);
+
+    let series = &comparison.series[0];
+    assert_eq!(series.contributors, Some(vec![1, 3, 3]));
+    assert!(series.cumulative.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn tracked_contributors_are_charted_with_the_stars() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let now = Utc::now();
+    let days_ago = |days| now - TimeDelta::days(days);
+    let first_day = days_ago(4).date_naive();
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_daily_stars(first_day, &[1, 1])
+        .with_commit(Some("alice"), days_ago(10))
+        .with_commit(Some("bob"), days_ago(3))
+        // No account claims its email, left out.
+        .with_commit(None, days_ago(2))
+        .with_commit(Some("alice"), days_ago(1))).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let response = app.post_json("/github/repo_stars/contributors/acme/rocket", &json!({})).await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+
+    let response = app.post_json("/github/repo_stars/contributors/acme/rocket", &json!({})).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let tracked: Value = response.json()?;
+    assert_eq!(tracked["tracking"], json!(true));
+    assert_eq!(tracked["history_complete"], json!(true));
+    let logins: Vec<&Value> = tracked["contributors"].as_array().into_iter().flatten().map(|contributor| &contributor["login"]).collect();
+    assert_eq!(logins, [&json!("alice"), &json!("bob")]);
+
+    // The scheduled run only picks up the commits pushed since, storing nobody twice.
+    let summary = track_all_contributors(database.pool(), &github.forge_clients()?, &StargazerIds::default()).await?;
+    assert_eq!((summary.repositories, summary.complete, summary.failed), (1, 1, 0));
+    let listed: Value = app.get("/github/repo_stars/contributors/acme/rocket").await?.json()?;
+    assert_eq!(listed["contributors"].as_array().map(Vec::len), Some(2));
+
+    let query = json!({ "repositories": [{ "owner": "acme", "name": "rocket" }], "metrics": ["cumulative", "contributors"] });
+    let comparison: Value = app.post_json("/github/repo_stars/compare", &query).await?.json()?;
+    let series = &comparison["series"][0];
+    assert_eq!(series["start"], json!(first_day));
+    assert_eq!(series["contributors"], json!([1, 2, 2, 2, 2]));
+
+    let stop = Request::builder()
+        .method(Method::DELETE)
+        .uri("/github/repo_stars/contributors/acme/rocket")
+        .body(Body::empty())?;
+    assert_eq!(app.request(stop).await?.status, StatusCode::NO_CONTENT);
+    let listed: Value = app.get("/github/repo_stars/contributors/acme/rocket").await?.json()?;
+    assert_eq!(listed["tracking"], json!(false));
+    assert_eq!(listed["contributors"].as_array().map(Vec::len), Some(2));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn contributors_are_hashed_like_stargazers() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let now = Utc::now();
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_daily_stars(now.date_naive(), &[1])
+        .with_commit(Some("alice"), now - TimeDelta::days(3))
+        .with_commit(Some("Alice"), now - TimeDelta::days(2))
+        .with_commit(Some("bob"), now - TimeDelta::days(1))).await;
+    let stargazer_ids = StargazerIds::hashed("secret");
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    dependencies.stargazer_ids = stargazer_ids.clone();
+    let app = TestApp::new(dependencies);
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+
+    let response = app.post_json("/github/repo_stars/contributors/acme/rocket", &json!({})).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let tracked: Value = response.json()?;
+    let mut logins: Vec<&str> = tracked["contributors"].as_array().into_iter().flatten().filter_map(|contributor| contributor["login"].as_str()).collect();
+    logins.sort_unstable();
+    let mut expected = [stargazer_ids.stargazer_id("alice").into_owned(), stargazer_ids.stargazer_id("bob").into_owned()];
+    expected.sort_unstable();
+    assert_eq!(logins, expected);
+
+    // The scheduled run hashes them the same way, storing nobody twice.
+    track_all_contributors(database.pool(), &github.forge_clients()?, &stargazer_ids).await?;
+    let listed: Value = app.get("/github/repo_stars/contributors/acme/rocket").await?.json()?;
+    assert_eq!(listed["contributors"].as_array().map(Vec::len), Some(2));
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Files of the bundled dashboard
+//!
+//! `/ui` redirects to the index, and every file is served with its content type.
+
+#![cfg(feature = "dashboard")]
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use projects_databases::testing::{MockGitHub, TestApp, TestDatabase, TestResult};
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn dashboard_files_are_served_under_ui() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let redirect = app.get("/ui").await?;
+    assert_eq!(redirect.status, StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(redirect.headers.get(header::LOCATION).and_then(|value| value.to_str().ok()), Some("/ui/"));
+
+    let index = app.get("/ui/").await?;
+    assert_eq!(index.status, StatusCode::OK);
+    assert_eq!(index.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()), Some("text/html"));
+    assert!(String::from_utf8_lossy(&index.body).contains("<title>Star Tracker</title>"));
+
+    let script = app.get("/ui/app.js").await?;
+    assert_eq!(script.status, StatusCode::OK);
+    assert!(script.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()).is_some_and(|content_type| content_type.contains("javascript")));
+
+    // Any other path gets the page itself.
+    let deep_link = app.get("/ui/repositories/acme/rocket").await?;
+    assert_eq!((deep_link.status, deep_link.body.clone()), (StatusCode::OK, index.body.clone()));
+
+    let etag = index.headers.get(header::ETAG).ok_or("no ETag")?.clone();
+    let revalidation = Request::get("/ui/").header(header::IF_NONE_MATCH, etag).body(Body::empty())?;
+    let revalidation = app.request(revalidation).await?;
+    assert_eq!(revalidation.status, StatusCode::NOT_MODIFIED);
+    assert!(revalidation.body.is_empty());
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! Checks of the stored stars: suspicious gaps, overlapping syncs, future stars and drift
+//!
+//! Gaps are judged against the days around them, then the report of a synced
+//! repository lists every check.
+
+use axum::http::StatusCode;
+use chrono::{NaiveDate, TimeDelta, Utc};
+use projects_databases::analytics::gaps::{zero_star_gaps, ZeroStarGap};
+use projects_databases::db::sync_run::{models::NewSyncRun, queries::insert_sync_run};
+use projects_databases::testing::{day, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+#[test]
+fn empty_days_are_flagged_when_the_days_around_make_them_unlikely() -> TestResult {
+    // 2 stars a day but from the 5th to the 8th.
+    let gap = day("2025-03-05")?..=day("2025-03-08")?;
+    let daily_counts: Vec<(NaiveDate, i64)> = day("2025-03-01")?
+        .iter_days()
+        .take(12)
+        .filter(|date| !gap.contains(date))
+        .map(|date| (date, 2))
+        .collect();
+
+    assert_eq!(zero_star_gaps(&daily_counts, 5.0), vec![ZeroStarGap {
+        first_day: day("2025-03-05")?,
+        last_day: day("2025-03-08")?,
+        days: 4,
+        expected_stars: 8.0,
+    }]);
+    assert!(zero_star_gaps(&daily_counts, 10.0).is_empty());
+    Ok(())
+}
+
+#[test]
+fn sparse_stars_leave_no_suspicious_gap() -> TestResult {
+    let daily_counts = vec![(day("2025-03-01")?, 1), (day("2025-03-09")?, 1), (day("2025-03-20")?, 2)];
+
+    assert!(zero_star_gaps(&daily_counts, 5.0).is_empty());
+    assert!(zero_star_gaps(&[], 5.0).is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn data_quality_reports_every_check() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let now = Utc::now();
+    let first_day = (now - TimeDelta::days(30)).date_naive();
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_daily_stars(first_day, &[2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 2, 2, 2, 2, 2])
+        .with_stargazer("time-traveller", now + TimeDelta::days(2))).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let response = app.get("/github/repositories/acme/rocket/data_quality").await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    let repository_id: Uuid = summary["repository_id"].as_str().ok_or("summary without a repository ID")?.parse()?;
+
+    // Two syncs left running at the same time.
+    let mut conn = database.pool().get()?;
+    for _ in 0..2 {
+        insert_sync_run(&mut conn, &NewSyncRun { id: Uuid::new_v4(), job_id: None, repository_id, trigger: "api", status: "running", fetched_at: None })?;
+    }
+
+    let response = app.get("/github/repositories/acme/rocket/data_quality").await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let report: Value = response.json()?;
+    assert_eq!(report["issues"], json!(3));
+    assert_eq!(report["zero_star_gaps"].as_array().map(Vec::len), Some(1));
+    assert_eq!(report["zero_star_gaps"][0]["first_day"], json!(first_day + TimeDelta::days(5)));
+    assert_eq!(report["zero_star_gaps"][0]["days"], json!(6));
+    assert_eq!(report["overlapping_sync_runs"].as_array().map(Vec::len), Some(1));
+    assert_eq!(report["future_stars"]["count"], json!(1));
+    assert_eq!(report["future_stars"]["stars"][0]["stargazer"], json!("time-traveller"));
+    assert_eq!(report["star_count"], json!({ "stored_stars": 21, "forge_stars": 21, "drift": 0 }));
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Connections checked out by the `DbConn` extractor, and by middleware the same way
+
+use axum::{body::Body, http::{header, Request, StatusCode}, middleware, routing::get, Extension, Router};
+use projects_databases::db::{pool::{build_lazy_pg_pool, PoolConfig}, PgPool};
+use projects_databases::endpoints::db_conn::DbConn;
+use projects_databases::endpoints::tenant::{tenant_middleware, TenantAuth};
+use serde_json::Value;
+use tower::ServiceExt;
+
+async fn connected(DbConn(_conn): DbConn) -> &'static str {
+    "connected"
+}
+
+fn unreachable_pool() -> PgPool {
+    // Nothing listens on the discard port.
+    let (pool, _) = build_lazy_pg_pool("postgres://postgres@127.0.0.1:9/postgres", &PoolConfig { max_size: 1, ..PoolConfig::default() });
+    pool
+}
+
+#[tokio::test]
+async fn unreachable_database_is_answered_with_503() -> Result<(), Box<dyn std::error::Error>> {
+    let router = Router::new().route("/", get(connected)).layer(Extension(unreachable_pool()));
+
+    let response = router.oneshot(Request::get("/").body(Body::empty())?).await?;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers().get(header::RETRY_AFTER).and_then(|value| value.to_str().ok()), Some("1"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    let error: Value = serde_json::from_slice(&body)?;
+    assert_eq!(error["code"], "database_unavailable");
+    Ok(())
+}
+
+#[tokio::test]
+async fn api_keys_looked_up_without_a_connection_are_answered_with_503() -> Result<(), Box<dyn std::error::Error>> {
+    let auth = TenantAuth { pool: unreachable_pool(), require_api_key: false };
+    let router = Router::new()
+        .route("/", get(|| async { "tenant resolved" }))
+        .layer(middleware::from_fn_with_state(auth, tenant_middleware));
+
+    let response = router.oneshot(Request::get("/").header("x-api-key", "stk_unknown").body(Body::empty())?).await?;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers().get(header::RETRY_AFTER).and_then(|value| value.to_str().ok()), Some("1"));
+    Ok(())
+}
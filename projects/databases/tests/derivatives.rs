@@ -0,0 +1,35 @@
+//! Velocity and acceleration of running totals over centered windows
+
+use projects_databases::analytics::derivatives::{acceleration, centered_difference, exponential_moving_average, velocity, DerivativeOptions};
+
+fn rounded(values: &[f64]) -> Vec<f64> {
+    values.iter().map(|value| (value * 100.0).round() / 100.0).collect()
+}
+
+#[test]
+fn one_day_window_gives_the_daily_stars() {
+    let cumulative = [3, 3, 8, 10];
+    assert_eq!(velocity(&cumulative, &DerivativeOptions::default()), vec![3.0, 0.0, 5.0, 2.0]);
+    assert_eq!(acceleration(&cumulative, &DerivativeOptions::default()), vec![3.0, -3.0, 5.0, -3.0]);
+}
+
+#[test]
+fn centered_window_averages_the_days_around() {
+    let totals = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+    // From before the first day, then 3 days centered on each, then cut short at the end.
+    assert_eq!(rounded(&centered_difference(&totals, 3)), vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+
+    let cumulative = [0, 0, 0, 7, 7, 7, 7];
+    let options = DerivativeOptions { window_days: 3, ema_span_days: None };
+    assert_eq!(rounded(&velocity(&cumulative, &options)), vec![0.0, 0.0, 2.33, 2.33, 2.33, 0.0, 0.0]);
+}
+
+#[test]
+fn moving_average_starts_from_the_first_value() {
+    // A span of 3 days weighs each new value by a half.
+    assert_eq!(exponential_moving_average(&[4.0, 0.0, 0.0, 8.0], 3), vec![4.0, 2.0, 1.0, 4.5]);
+    assert!(exponential_moving_average(&[], 3).is_empty());
+
+    let options = DerivativeOptions { window_days: 1, ema_span_days: Some(3) };
+    assert_eq!(velocity(&[4, 4, 4, 12], &options), vec![4.0, 2.0, 1.0, 4.5]);
+}
@@ -0,0 +1,95 @@
+//! Conditional requests of the read endpoints, answered `304` until a sync
+//!
+//! Tags change with each sync, and the `Vary` header of a handler is kept
+//! alongside the one the tags add.
+
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::routing::get;
+use axum::{middleware, Extension, Router};
+use projects_databases::db::tenant::models::DEFAULT_TENANT_ID;
+use projects_databases::endpoints::github::etag::etag_middleware;
+use projects_databases::endpoints::tenant::TenantId;
+use projects_databases::testing::{day, test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResponse, TestResult};
+use serde_json::{json, Value};
+
+async fn get_if_none_match(app: &TestApp, uri: &str, etag: &str) -> Result<TestResponse, Box<dyn std::error::Error>> {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header(header::IF_NONE_MATCH, etag)
+        .body(Body::empty())?;
+    Ok(app.request(request).await?)
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn daily_counts_are_not_sent_again_until_a_sync() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 0, 5])).await;
+    let app = TestApp::spawn(&database, &github)?;
+    let sync = json!({ "owner": "acme", "name": "rocket" });
+    assert_eq!(app.post_json("/github/repo_stars/update", &sync).await?.status, StatusCode::OK);
+
+    let uri = "/github/repo_stars/read_per_day/acme/rocket";
+    let first = app.get(uri).await?;
+    assert_eq!(first.status, StatusCode::OK, "{}", String::from_utf8_lossy(&first.body));
+    assert_eq!(first.json::<Value>()?, json!([["2025-01-01", 3], ["2025-01-03", 5]]));
+    let etag = first.headers.get(header::ETAG).ok_or("no ETag")?.to_str()?.to_owned();
+    assert!(etag.starts_with("W/\"8-"), "{etag}");
+    assert!(first.headers.contains_key(header::LAST_MODIFIED));
+    assert_eq!(first.headers.get(header::VARY).ok_or("no Vary")?, "X-API-Key, Authorization");
+
+    // Paginated the same way as the POST.
+    let page: Value = app.get(&format!("{uri}?limit=1&offset=1")).await?.json()?;
+    assert_eq!(page, json!({ "data": [["2025-01-03", 5]], "total": 2, "limit": 1, "offset": 1 }));
+
+    let unchanged = get_if_none_match(&app, uri, &etag).await?;
+    assert_eq!(unchanged.status, StatusCode::NOT_MODIFIED);
+    assert!(unchanged.body.is_empty());
+    assert_eq!(unchanged.headers.get(header::ETAG).ok_or("no ETag")?.to_str()?, etag);
+    assert!(unchanged.headers.contains_key(header::VARY));
+
+    // The heatmap is tagged the same way.
+    let heatmap = app.get("/github/repo_stars/heatmap/acme/rocket?weeks=1").await?;
+    assert_eq!(heatmap.status, StatusCode::OK);
+    assert_eq!(heatmap.headers.get(header::ETAG).ok_or("no ETag")?.to_str()?, etag);
+
+    // A sync refreshes `fetched_at`, so the tag changes with it.
+    assert_eq!(app.post_json("/github/repo_stars/update", &sync).await?.status, StatusCode::OK);
+    let changed = get_if_none_match(&app, uri, &etag).await?;
+    assert_eq!(changed.status, StatusCode::OK);
+    assert_ne!(changed.headers.get(header::ETAG).ok_or("no ETag")?.to_str()?, etag);
+
+    // Unknown repositories are left to the handler, untagged.
+    let unknown = app.get("/github/repo_stars/read_per_day/acme/missing").await?;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+    assert!(!unknown.headers.contains_key(header::ETAG));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn handler_vary_headers_are_kept() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3])).await;
+    let dependencies = test_dependencies(&database, github.forge_clients()?);
+    let read_pool = dependencies.read_pool.clone();
+    let app = TestApp::new(dependencies);
+    let sync = json!({ "owner": "acme", "name": "rocket" });
+    assert_eq!(app.post_json("/github/repo_stars/update", &sync).await?.status, StatusCode::OK);
+
+    let negotiated = TestApp::from_router(Router::new()
+        .route("/{owner}/{name}", get(|| async { ([(header::VARY, "Accept")], "[]") }))
+        .route_layer(middleware::from_fn(etag_middleware))
+        .layer(Extension(TenantId(DEFAULT_TENANT_ID)))
+        .layer(Extension(read_pool)));
+    let response = negotiated.get("/acme/rocket").await?;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.headers.contains_key(header::ETAG));
+    let vary: Vec<_> = response.headers.get_all(header::VARY).iter().map(|value| value.to_str()).collect::<Result<_, _>>()?;
+    assert_eq!(vary, ["Accept", "X-API-Key, Authorization"]);
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Repository names of the CLI and the star history rows it exports as CSV or JSON
+//!
+//! No database needed.
+
+use projects_databases::export::{day_rows, write_rows, DayRow, ExportFormat, RepoDayRow, RepoName};
+use projects_databases::testing::{day, TestResult};
+
+#[test]
+fn repository_names_are_an_owner_and_a_name() -> TestResult {
+    let repo: RepoName = "rust-lang/rust".parse()?;
+    assert_eq!((repo.owner.as_str(), repo.name.as_str()), ("rust-lang", "rust"));
+    assert_eq!(repo.to_string(), "rust-lang/rust");
+
+    for invalid in ["", "rust", "/rust", "rust-lang/", "/", "rust-lang/rust/extra", "rust-lang//rust"] {
+        let error = invalid.parse::<RepoName>().err().ok_or(format!("{invalid} accepted"))?;
+        assert_eq!(error, format!("expected owner/name, got {invalid}"));
+    }
+    Ok(())
+}
+
+#[test]
+fn rows_cover_every_day_with_the_running_total() -> TestResult {
+    let rows = day_rows(&[(day("2025-01-01")?, 3), (day("2025-01-04")?, 2), (day("2025-01-05")?, 1)]);
+    assert_eq!(rows, [
+        DayRow { day: day("2025-01-01")?, stars: 3, total: 3 },
+        DayRow { day: day("2025-01-02")?, stars: 0, total: 3 },
+        DayRow { day: day("2025-01-03")?, stars: 0, total: 3 },
+        DayRow { day: day("2025-01-04")?, stars: 2, total: 5 },
+        DayRow { day: day("2025-01-05")?, stars: 1, total: 6 },
+    ]);
+    assert!(day_rows(&[]).is_empty());
+    Ok(())
+}
+
+#[test]
+fn rows_are_written_as_csv_with_a_header() -> TestResult {
+    let rows = day_rows(&[(day("2025-01-01")?, 3), (day("2025-01-03")?, 2)]);
+    let mut out = Vec::new();
+    write_rows(&rows, ExportFormat::Csv, &mut out)?;
+    assert_eq!(String::from_utf8(out)?, "day,stars,total\n2025-01-01,3,3\n2025-01-02,0,3\n2025-01-03,2,5\n");
+
+    let repo: RepoName = "acme/rocket".parse()?;
+    let tagged: Vec<RepoDayRow> = rows.into_iter().take(1).map(|row| RepoDayRow::new(&repo, row)).collect();
+    let mut out = Vec::new();
+    write_rows(&tagged, ExportFormat::Csv, &mut out)?;
+    assert_eq!(String::from_utf8(out)?, "repo,day,stars,total\nacme/rocket,2025-01-01,3,3\n");
+    Ok(())
+}
+
+#[test]
+fn rows_are_written_as_a_json_array() -> TestResult {
+    let rows = day_rows(&[(day("2025-01-01")?, 3), (day("2025-01-02")?, 1)]);
+    let mut out = Vec::new();
+    write_rows(&rows, ExportFormat::Json, &mut out)?;
+    let written = String::from_utf8(out)?;
+    assert!(written.ends_with("]\n"), "{written}");
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&written)?, serde_json::json!([
+        { "day": "2025-01-01", "stars": 3, "total": 3 },
+        { "day": "2025-01-02", "stars": 1, "total": 4 },
+    ]));
+
+    let mut out = Vec::new();
+    write_rows::<DayRow>(&[], ExportFormat::Json, &mut out)?;
+    assert_eq!(String::from_utf8(out)?, "[]\n");
+    Ok(())
+}
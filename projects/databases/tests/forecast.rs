@@ -0,0 +1,109 @@
+//! Star count projections over fixed series
+//!
+//! No database needed.
+
+use chrono::{Duration, NaiveDate};
+use projects_databases::analytics::forecast::{forecast, Forecast, ForecastModel};
+use projects_databases::testing::day;
+
+/// Cumulative series starting on 2025-01-01, `total(x)` on day `x`.
+fn series(days: i64, total: impl Fn(i64) -> i64) -> Result<Vec<(NaiveDate, i64)>, chrono::ParseError> {
+    let first = day("2025-01-01")?;
+    Ok((0..days).map(|x| (first + Duration::days(x), total(x))).collect())
+}
+
+fn projected(forecast: &Forecast) -> Vec<(u32, String, i64)> {
+    forecast.projections.iter().map(|projection| (projection.days, projection.date.to_string(), projection.total)).collect()
+}
+
+#[test]
+fn steady_growth_is_extended_at_its_rate() -> Result<(), Box<dyn std::error::Error>> {
+    let cumulative = series(30, |x| 10 + 5 * x)?;
+    let steady = forecast(&cumulative, ForecastModel::Linear, 14, &[1, 7, 30]).ok_or("no forecast")?;
+
+    assert_eq!((steady.window_days, steady.current_total), (14, 155));
+    assert!((steady.daily_rate - 5.0).abs() < 1e-9, "{}", steady.daily_rate);
+    assert_eq!(projected(&steady), [
+        (1, "2025-01-31".into(), 160),
+        (7, "2025-02-06".into(), 190),
+        (30, "2025-03-01".into(), 305),
+    ]);
+    Ok(())
+}
+
+#[test]
+fn only_the_trailing_window_is_fitted() -> Result<(), Box<dyn std::error::Error>> {
+    // A slow start, then 10 stars a day over the last 10 days.
+    let cumulative = series(30, |x| if x < 20 { x } else { 19 + 10 * (x - 19) })?;
+
+    let recent = forecast(&cumulative, ForecastModel::Linear, 7, &[10]).ok_or("no forecast")?;
+    assert_eq!(recent.window_days, 7);
+    assert!((recent.daily_rate - 10.0).abs() < 1e-9, "{}", recent.daily_rate);
+    assert_eq!(projected(&recent), [(10, "2025-02-09".into(), 219)]);
+
+    // The whole history slows the projection down, yet it still starts from today's total.
+    let whole = forecast(&cumulative, ForecastModel::Linear, 365, &[10]).ok_or("no forecast")?;
+    assert_eq!(whole.window_days, 30);
+    assert!(whole.daily_rate < recent.daily_rate);
+    assert!(whole.projections[0].total > whole.current_total && whole.projections[0].total < 219);
+    Ok(())
+}
+
+#[test]
+fn compounding_growth_is_extended_on_a_log_scale() -> Result<(), Box<dyn std::error::Error>> {
+    // Doubling every week.
+    let cumulative = series(28, |x| (100.0 * 2f64.powf(x as f64 / 7.0)).round() as i64)?;
+    let compounding = forecast(&cumulative, ForecastModel::LogLinear, 28, &[7, 14]).ok_or("no forecast")?;
+
+    let current = compounding.current_total as f64;
+    assert_eq!(compounding.current_total, 1_449);
+    let expected_rate = current * (2f64.powf(1.0 / 7.0) - 1.0);
+    assert!((compounding.daily_rate - expected_rate).abs() < 1.0, "{} vs {expected_rate}", compounding.daily_rate);
+    let [week, fortnight] = [&compounding.projections[0], &compounding.projections[1]];
+    assert!((week.total as f64 / current - 2.0).abs() < 0.01, "{}", week.total);
+    assert!((fortnight.total as f64 / current - 4.0).abs() < 0.02, "{}", fortnight.total);
+
+    // The same history fitted on a straight line falls far short.
+    let linear = forecast(&cumulative, ForecastModel::Linear, 28, &[7]).ok_or("no forecast")?;
+    assert!(linear.projections[0].total < week.total, "{} vs {}", linear.projections[0].total, week.total);
+    Ok(())
+}
+
+#[test]
+fn short_histories_are_not_projected() -> Result<(), Box<dyn std::error::Error>> {
+    assert!(forecast(&[], ForecastModel::Linear, 14, &[7]).is_none());
+    assert!(forecast(&[], ForecastModel::LogLinear, 14, &[7]).is_none());
+
+    let single = series(1, |_| 42)?;
+    assert!(forecast(&single, ForecastModel::Linear, 14, &[7]).is_none());
+    assert!(forecast(&single, ForecastModel::LogLinear, 14, &[7]).is_none());
+
+    // Two days are enough, and windows shorter than that are widened to them.
+    let two = series(2, |x| 3 + 4 * x)?;
+    let widened = forecast(&two, ForecastModel::Linear, 0, &[2]).ok_or("no forecast")?;
+    assert_eq!((widened.window_days, widened.daily_rate), (2, 4.0));
+    assert_eq!(projected(&widened), [(2, "2025-01-04".into(), 15)]);
+    Ok(())
+}
+
+#[test]
+fn flat_and_shrinking_series_stay_at_the_current_total() -> Result<(), Box<dyn std::error::Error>> {
+    for model in [ForecastModel::Linear, ForecastModel::LogLinear] {
+        let flat = series(20, |_| 250)?;
+        let fitted = forecast(&flat, model, 14, &[0, 7, 90]).ok_or("no forecast")?;
+        assert_eq!(fitted.daily_rate, 0.0);
+        assert_eq!(fitted.projections.iter().map(|projection| projection.total).collect::<Vec<_>>(), [250, 250, 250]);
+
+        // Unstarred more than starred, projections never go below today.
+        let shrinking = series(20, |x| 300 - 2 * x)?;
+        let fitted = forecast(&shrinking, model, 14, &[7, 30]).ok_or("no forecast")?;
+        assert_eq!(fitted.daily_rate, 0.0);
+        assert!(fitted.projections.iter().all(|projection| projection.total == 262));
+
+        // Empty repositories are projected as such, even on a log scale.
+        let empty = series(10, |_| 0)?;
+        let fitted = forecast(&empty, model, 14, &[7]).ok_or("no forecast")?;
+        assert_eq!((fitted.current_total, fitted.projections[0].total), (0, 0));
+    }
+    Ok(())
+}
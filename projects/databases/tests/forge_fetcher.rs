@@ -0,0 +1,112 @@
+//! Syncs driven by a scripted forge, pages and failures decided by the test
+//!
+//! `POST /github/repo_stars/update` runs against a [`ScriptedFetcher`] in place
+//! of the forge's client, so no network is needed.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use interfaces_github_stargazers::forge::{Forge, ForgeErrorKind};
+use interfaces_github_stargazers::retry::RetryConfig;
+use projects_databases::testing::{day, test_dependencies, MockGitHub, MockRepository, ScriptedCall, ScriptedFetcher, TestApp, TestDatabase, TestResponse, TestResult};
+use serde_json::{json, Value};
+
+/// App on `database` whose clients of `fetcher`'s forge are `fetcher`.
+async fn app_with(database: &TestDatabase, fetcher: &Arc<ScriptedFetcher>) -> Result<TestApp, Box<dyn std::error::Error>> {
+    let github = MockGitHub::start().await;
+    let forges = github.forge_clients()?.with_fetcher(fetcher.clone());
+    Ok(TestApp::new(test_dependencies(database, forges)))
+}
+
+async fn sync(app: &TestApp, forge: Forge, name: &str) -> Result<TestResponse, Box<dyn std::error::Error>> {
+    Ok(app.post_json("/github/repo_stars/update", &json!({ "forge": forge, "owner": "acme", "name": name })).await?)
+}
+
+async fn daily(app: &TestApp, forge: Forge) -> Result<TestResponse, Box<dyn std::error::Error>> {
+    Ok(app.post_json("/github/repo_stars/read_per_day", &json!({ "forge": forge, "owner": "acme", "name": "comet" })).await?)
+}
+
+fn comet() -> Result<MockRepository, chrono::ParseError> {
+    Ok(MockRepository::new("acme", "comet").with_daily_stars(day("2025-03-01")?, &[100, 100, 50]))
+}
+
+fn call(cursor: Option<&str>, page_size: u32) -> ScriptedCall {
+    ScriptedCall { cursor: cursor.map(Into::into), page_size }
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn sync_follows_the_cursor_to_the_last_page() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let fetcher = Arc::new(ScriptedFetcher::new(Forge::GitHub, comet()?));
+    let app = app_with(&database, &fetcher).await?;
+
+    let response = sync(&app, Forge::GitHub, "comet").await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    assert_eq!((&summary["pages"], &summary["stars"], &summary["retries"]), (&json!(3), &json!(250), &json!(0)));
+    assert_eq!(fetcher.calls(), vec![call(None, 100), call(Some("100"), 100), call(Some("200"), 100)]);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn overloaded_pages_are_retried_smaller() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let fetcher = Arc::new(ScriptedFetcher::new(Forge::GitLab, comet()?).failing_on(1, ForgeErrorKind::Overloaded));
+    let app = app_with(&database, &fetcher).await?;
+
+    let response = sync(&app, Forge::GitLab, "comet").await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    assert_eq!((&summary["stars"], &summary["retries"]), (&json!(250), &json!(1)));
+    assert_eq!(fetcher.calls()[..3], [call(None, 100), call(Some("100"), 100), call(Some("100"), 50)]);
+
+    let stored: Vec<(String, i64)> = daily(&app, Forge::GitLab).await?.json()?;
+    assert_eq!(stored.iter().map(|(_, stars)| stars).sum::<i64>(), 250);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn overloaded_pages_give_up_without_retries_left() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let fetcher = ScriptedFetcher::new(Forge::GitHub, comet()?)
+        .with_retry(RetryConfig { max_attempts: 2, base_delay: Duration::ZERO, max_delay: Duration::ZERO })
+        .failing_on(0, ForgeErrorKind::Overloaded)
+        .failing_on(1, ForgeErrorKind::Overloaded);
+    let fetcher = Arc::new(fetcher);
+    let app = app_with(&database, &fetcher).await?;
+
+    assert_eq!(sync(&app, Forge::GitHub, "comet").await?.status, StatusCode::BAD_GATEWAY);
+    assert_eq!(fetcher.calls(), vec![call(None, 100), call(None, 50)]);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn failures_mid_sync_keep_the_pages_stored() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let fetcher = Arc::new(ScriptedFetcher::new(Forge::GitHub, comet()?).failing_on(1, ForgeErrorKind::RateLimited));
+    let app = app_with(&database, &fetcher).await?;
+
+    assert_eq!(sync(&app, Forge::GitHub, "comet").await?.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(fetcher.calls().len(), 2);
+
+    let stored: Vec<(String, i64)> = daily(&app, Forge::GitHub).await?.json()?;
+    assert_eq!(stored, vec![("2025-03-01".into(), 100)]);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn missing_repositories_are_not_found() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let fetcher = Arc::new(ScriptedFetcher::missing(Forge::Gitea));
+    let app = app_with(&database, &fetcher).await?;
+
+    assert_eq!(sync(&app, Forge::Gitea, "gone").await?.status, StatusCode::NOT_FOUND);
+    assert_eq!(daily(&app, Forge::Gitea).await?.status, StatusCode::NOT_FOUND);
+    Ok(())
+}
@@ -0,0 +1,36 @@
+//! Statuses answered for GitHub failures, the caller's own ones kept apart from the service's
+//!
+//! Canned GitHub answers classified by the interface crate, no network or database needed.
+
+use axum::http::StatusCode;
+use interfaces_github_stargazers::graphql::GitHubGraphQLResult;
+use interfaces_github_stargazers::index::parse_repo_stargazers_response;
+use projects_databases::endpoints::github::status::github_error_status_code;
+use serde_json::json;
+
+#[test]
+fn failures_are_answered_with_their_status() {
+    let errors = |error_type: &str| {
+        json!({ "data": null, "errors": [{ "type": error_type, "message": "failed" }] }).to_string()
+    };
+    let cases = [
+        (StatusCode::OK, errors("NOT_FOUND"), StatusCode::NOT_FOUND),
+        (StatusCode::OK, errors("FORBIDDEN"), StatusCode::FORBIDDEN),
+        (StatusCode::OK, errors("RATE_LIMITED"), StatusCode::TOO_MANY_REQUESTS),
+        (StatusCode::OK, errors("MAX_NODE_LIMIT_EXCEEDED"), StatusCode::BAD_GATEWAY),
+        (StatusCode::OK, "<html>".into(), StatusCode::BAD_GATEWAY),
+        // The service's token is rejected, not the caller's request.
+        (StatusCode::UNAUTHORIZED, r#"{ "message": "Bad credentials" }"#.into(), StatusCode::BAD_GATEWAY),
+        (StatusCode::FORBIDDEN, r#"{ "message": "Must have admin rights" }"#.into(), StatusCode::FORBIDDEN),
+        (StatusCode::TOO_MANY_REQUESTS, "slow down".into(), StatusCode::TOO_MANY_REQUESTS),
+        (StatusCode::BAD_GATEWAY, "<html>Bad gateway</html>".into(), StatusCode::BAD_GATEWAY),
+    ];
+
+    for (status, body, answered) in cases {
+        let result = GitHubGraphQLResult { body: body.clone(), status, retries: 0 };
+        let Err(error) = parse_repo_stargazers_response(&result) else {
+            panic!("{status} {body} parsed");
+        };
+        assert_eq!(github_error_status_code(&error), answered, "{error}");
+    }
+}
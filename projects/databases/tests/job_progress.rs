@@ -0,0 +1,47 @@
+//! Progress of sync jobs, as shown by the job status and live updates
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use projects_databases::sync_queue::{JobProgress, SyncPace};
+use projects_databases::testing::TestResult;
+
+fn now() -> Result<NaiveDateTime, &'static str> {
+    NaiveDate::from_ymd_opt(2025, 5, 29).and_then(|day| day.and_hms_opt(10, 0, 0)).ok_or("invalid date")
+}
+
+#[test]
+fn rates_and_eta_follow_the_pace() -> TestResult {
+    let pace = SyncPace { stars: 1200, pages: 12, elapsed: TimeDelta::seconds(60) };
+    let progress = JobProgress::new(1200, Some(4800), pace, now()?);
+
+    assert_eq!(progress.percent_complete, Some(25.0));
+    assert_eq!(progress.elapsed_seconds, Some(60.0));
+    assert_eq!(progress.stars_per_second, Some(20.0));
+    assert_eq!(progress.pages_per_minute, Some(12.0));
+    assert_eq!(progress.eta, Some(now()? + TimeDelta::seconds(180)));
+    Ok(())
+}
+
+#[test]
+fn resumed_jobs_are_paced_on_their_run_only() -> TestResult {
+    // 1000 stars came from an earlier attempt, 200 from this one.
+    let pace = SyncPace { stars: 200, pages: 2, elapsed: TimeDelta::seconds(20) };
+    let progress = JobProgress::new(1200, Some(2200), pace, now()?);
+
+    assert_eq!(progress.stars_per_second, Some(10.0));
+    assert_eq!(progress.eta, Some(now()? + TimeDelta::seconds(100)));
+    Ok(())
+}
+
+#[test]
+fn nothing_is_measured_without_a_page() -> TestResult {
+    let progress = JobProgress::new(0, Some(4800), SyncPace::default(), now()?);
+
+    assert_eq!(progress.percent_complete, Some(0.0));
+    assert_eq!((progress.elapsed_seconds, progress.stars_per_second, progress.pages_per_minute, progress.eta), (None, None, None, None));
+
+    // Empty pages give a rate but no ETA.
+    let pace = SyncPace { stars: 0, pages: 1, elapsed: TimeDelta::seconds(2) };
+    let progress = JobProgress::new(0, Some(4800), pace, now()?);
+    assert_eq!((progress.stars_per_second, progress.pages_per_minute, progress.eta), (Some(0.0), Some(30.0), None));
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Request limits as the router wires them: which routes get the deadline and
+//! which the default body limit
+//!
+//! Slow answers are cut at the deadline but syncs run on, and imports read
+//! bodies past the default limit up to their own.
+
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use projects_databases::endpoints::github::repo_stars::import::index::MAX_IMPORT_BYTES;
+use projects_databases::endpoints::limits::RequestLimits;
+use projects_databases::testing::{day, test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+use wiremock::{matchers::{method, path}, Mock, Respond, ResponseTemplate};
+
+/// Deadline of [`limited_app`].
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Time [`Slowly`] takes to answer, well past [`TIMEOUT`].
+const GITHUB_DELAY: Duration = Duration::from_secs(2);
+
+/// Default body limit of [`limited_app`].
+const MAX_BODY_BYTES: usize = 1024;
+
+/// GitHub answering every GraphQL query for the repository after [`GITHUB_DELAY`].
+struct Slowly(MockRepository);
+
+impl Respond for Slowly {
+    fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+        self.0.respond(request).set_delay(GITHUB_DELAY)
+    }
+}
+
+/// Router with a [`TIMEOUT`] deadline and bodies capped at [`MAX_BODY_BYTES`].
+fn limited_app(database: &TestDatabase, github: &MockGitHub) -> Result<TestApp, Box<dyn std::error::Error>> {
+    let mut dependencies = test_dependencies(database, github.forge_clients()?);
+    dependencies.request_limits = RequestLimits { timeout: TIMEOUT, max_body_bytes: MAX_BODY_BYTES, ..RequestLimits::default() };
+    Ok(TestApp::new(dependencies))
+}
+
+fn post(uri: &str, content_type: &str, body: Vec<u8>) -> Result<Request<Body>, axum::http::Error> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, body.len())
+        .body(Body::from(body))
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn slow_requests_time_out_but_syncs_do_not() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let repository = MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 1]);
+    Mock::given(method("POST")).and(path("/graphql")).respond_with(Slowly(repository)).mount(github.server()).await;
+    let app = limited_app(&database, &github)?;
+
+    // Listing the owner's repositories waits on GitHub past the deadline.
+    let response = app.post_json("/github/org_stars/update", &json!({ "owner": "acme" })).await?;
+    assert_eq!(response.status, StatusCode::REQUEST_TIMEOUT);
+    let error: Value = response.json()?;
+    assert_eq!(error["code"], "timeout");
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    assert_eq!(summary["stars"], 4);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn bodies_over_the_limit_are_refused_but_imports_have_their_own() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let app = limited_app(&database, &github)?;
+
+    let compared = serde_json::to_vec(&json!({ "repositories": [{ "owner": "acme", "name": "a".repeat(MAX_BODY_BYTES) }] }))?;
+    let response = app.request(post("/github/repo_stars/compare", "application/json", compared)?).await?;
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+    let error: Value = response.json()?;
+    assert_eq!(error["code"], "payload_too_large");
+    assert_eq!(error["details"]["max_body_bytes"], MAX_BODY_BYTES);
+
+    let mut csv = String::from("stargazer,starred_at\n");
+    for stargazer in 0..100 {
+        csv.push_str(&format!("stargazer-{stargazer},2025-03-01T12:00:00Z\n"));
+    }
+    assert!(csv.len() > MAX_BODY_BYTES);
+    let response = app.request(post("/github/repo_stars/import?owner=acme&name=rocket", "text/csv", csv.into_bytes())?).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    assert_eq!(summary["inserted"], 100);
+
+    // Up to the import's own limit the body is read, beyond it it's refused.
+    let response = app.request(post("/github/repo_stars/import?owner=acme&name=rocket", "text/csv", vec![b'x'; MAX_IMPORT_BYTES])?).await?;
+    assert_ne!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+    let response = app.request(post("/github/repo_stars/import?owner=acme&name=rocket", "text/csv", vec![b'x'; MAX_IMPORT_BYTES + 1])?).await?;
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+    Ok(())
+}
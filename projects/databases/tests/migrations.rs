@@ -0,0 +1,109 @@
+//! Repositories of a tenant differing only by case merged by their migration,
+//! the stars, counts, tags, alert rules and jobs of both kept on one of them
+//!
+//! The database is migrated back to just before the merge, seeded, then
+//! migrated again.
+
+use chrono::NaiveDate;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError};
+use diesel::{connection::SimpleConnection, prelude::*, sql_query};
+use diesel_migrations::MigrationHarness;
+use projects_databases::db::migrations::{run_pending_migrations, MIGRATIONS};
+use projects_databases::db::schema::{alert_rules, daily_star_counts, repositories, repository_tags, stars, sync_jobs};
+use projects_databases::testing::{day, TestDatabase, TestResult};
+use uuid::Uuid;
+
+const MERGE: &str = "20250602090000";
+
+const KEEPER: &str = "00000000-0000-0000-0000-00000000000a";
+const DUPLICATE: &str = "00000000-0000-0000-0000-00000000000b";
+const OTHER_TENANT: &str = "00000000-0000-0000-0000-00000000000c";
+
+/// Reverts the migrations down to the merge, included.
+fn revert_to_before_merge(conn: &mut PgConnection) -> TestResult {
+    loop {
+        let reverted = conn.revert_last_migration(MIGRATIONS).map_err(|error| error.to_string())?;
+        if reverted.to_string() == MERGE {
+            return Ok(());
+        }
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn case_duplicates_are_merged_into_the_most_compacted() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let mut conn = database.pool().get()?;
+    revert_to_before_merge(&mut conn)?;
+
+    // acme/Rocket was compacted up to 2025-01-03, its stars from before only counted per day.
+    conn.batch_execute(&format!("
+        INSERT INTO tenants (id, name) VALUES ('{OTHER_TENANT}', 'globex');
+        INSERT INTO repositories (id, tenant_id, owner, name, compacted_before, created_at) VALUES
+            ('{KEEPER}', '00000000-0000-0000-0000-000000000000', 'acme', 'Rocket', '2025-01-03', '2025-01-10'),
+            ('{DUPLICATE}', '00000000-0000-0000-0000-000000000000', 'ACME', 'rocket', NULL, '2025-01-05'),
+            ('{OTHER_TENANT}', '{OTHER_TENANT}', 'acme', 'ROCKET', NULL, '2025-01-05');
+        INSERT INTO daily_star_aggregates (repository_id, day, stars) VALUES
+            ('{KEEPER}', '2025-01-01', 2), ('{KEEPER}', '2025-01-02', 1), ('{DUPLICATE}', '2025-01-02', 3);
+        INSERT INTO stars (repository_id, stargazer, starred_at, fetched_at) VALUES
+            ('{KEEPER}', 'ada', '2025-01-03 10:00', '2025-01-10'),
+            ('{DUPLICATE}', 'ada', '2025-01-03 10:00', '2025-01-05'),
+            ('{DUPLICATE}', 'bob', '2025-01-01 10:00', '2025-01-05'),
+            ('{DUPLICATE}', 'cyd', '2025-01-04 10:00', '2025-01-05'),
+            ('{OTHER_TENANT}', 'dee', '2025-01-04 10:00', '2025-01-05');
+        INSERT INTO daily_star_counts (repository_id, day, stars) VALUES
+            ('{KEEPER}', '2025-01-01', 2), ('{KEEPER}', '2025-01-02', 1), ('{KEEPER}', '2025-01-03', 1),
+            ('{DUPLICATE}', '2025-01-01', 1), ('{DUPLICATE}', '2025-01-02', 3), ('{DUPLICATE}', '2025-01-03', 1), ('{DUPLICATE}', '2025-01-04', 1),
+            ('{OTHER_TENANT}', '2025-01-04', 1);
+        INSERT INTO repository_tags (repository_id, tag) VALUES
+            ('{KEEPER}', 'cli'), ('{DUPLICATE}', 'cli'), ('{DUPLICATE}', 'rust');
+        INSERT INTO alert_rules (id, repository_id, kind, threshold, target, destination) VALUES
+            ('{DUPLICATE}', '{DUPLICATE}', 'daily_stars', 10, 'webhook', 'https://hooks.example.com/stars');
+        INSERT INTO sync_jobs (id, repository_id, status) VALUES
+            ('{KEEPER}', '{KEEPER}', 'completed'), ('{DUPLICATE}', '{DUPLICATE}', 'failed');
+    "))?;
+
+    let applied = run_pending_migrations(&mut conn)?;
+    assert_eq!(applied.first().map(String::as_str), Some(MERGE));
+
+    let keeper: Uuid = KEEPER.parse()?;
+    let mut left: Vec<(String, String)> = repositories::table.select((repositories::owner, repositories::name)).load(&mut conn)?;
+    left.sort();
+    assert_eq!(left, [("acme".into(), "ROCKET".into()), ("acme".into(), "Rocket".into())]);
+
+    // Bob starred before the keeper's compaction, already counted in its aggregates.
+    let gazers: Vec<String> = stars::table
+        .filter(stars::repository_id.eq(keeper))
+        .select(stars::stargazer)
+        .order(stars::stargazer)
+        .load(&mut conn)?;
+    assert_eq!(gazers, ["ada", "cyd"]);
+    let daily: Vec<(NaiveDate, i32)> = daily_star_counts::table
+        .filter(daily_star_counts::repository_id.eq(keeper))
+        .select((daily_star_counts::day, daily_star_counts::stars))
+        .order(daily_star_counts::day)
+        .load(&mut conn)?;
+    assert_eq!(daily, [(day("2025-01-01")?, 2), (day("2025-01-02")?, 3), (day("2025-01-03")?, 1), (day("2025-01-04")?, 1)]);
+    let other_tenant: i64 = daily_star_counts::table
+        .filter(daily_star_counts::repository_id.eq(OTHER_TENANT.parse::<Uuid>()?))
+        .count()
+        .get_result(&mut conn)?;
+    assert_eq!(other_tenant, 1);
+
+    let tags: Vec<String> = repository_tags::table
+        .filter(repository_tags::repository_id.eq(keeper))
+        .select(repository_tags::tag)
+        .order(repository_tags::tag)
+        .load(&mut conn)?;
+    assert_eq!(tags, ["cli", "rust"]);
+    let rules: Vec<Uuid> = alert_rules::table.select(alert_rules::repository_id).load(&mut conn)?;
+    assert_eq!(rules, [keeper]);
+    let jobs: Vec<Uuid> = sync_jobs::table.select(sync_jobs::repository_id).load(&mut conn)?;
+    assert_eq!(jobs, [keeper, keeper]);
+
+    // Only one spelling of a name per tenant from now on.
+    let respelled = sql_query("INSERT INTO repositories (id, tenant_id, owner, name) VALUES (gen_random_uuid(), '00000000-0000-0000-0000-000000000000', 'Acme', 'ROCKET')")
+        .execute(&mut conn);
+    assert!(matches!(respelled, Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _))), "{respelled:?}");
+    Ok(())
+}
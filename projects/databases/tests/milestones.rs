@@ -0,0 +1,93 @@
+//! Milestone crossings and the estimated day a target star count is reached
+//!
+//! Fixed series but for the `window` check, answered through the endpoint.
+
+use axum::http::StatusCode;
+use chrono::{Duration, NaiveDate};
+use projects_databases::analytics::milestones::{milestone_crossings, milestone_eta};
+use projects_databases::testing::{day, MockGitHub, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+
+/// Cumulative series starting on 2025-01-01, `total(x)` on day `x`.
+fn series(days: i64, total: impl Fn(i64) -> i64) -> Result<Vec<(NaiveDate, i64)>, chrono::ParseError> {
+    let first = day("2025-01-01")?;
+    Ok((0..days).map(|x| (first + Duration::days(x), total(x))).collect())
+}
+
+#[test]
+fn crossings_are_the_first_day_at_or_over_each_threshold() -> TestResult {
+    let cumulative = series(10, |x| 300 * x)?;
+    assert_eq!(milestone_crossings(&cumulative, &[900, 1_000, 5_000]), [
+        (900, day("2025-01-04")?),
+        (1_000, day("2025-01-05")?),
+    ]);
+    assert!(milestone_crossings(&[], &[1_000]).is_empty());
+    Ok(())
+}
+
+#[test]
+fn reached_targets_are_dated_by_their_crossing() -> TestResult {
+    let cumulative = series(30, |x| 10 * x)?;
+    let eta = milestone_eta(&cumulative, 100, day("2025-01-30")?, 7);
+    assert_eq!((eta.current_total, eta.reached_on, eta.eta), (290, Some(day("2025-01-11")?), Some(day("2025-01-11")?)));
+    Ok(())
+}
+
+#[test]
+fn targets_ahead_are_reached_at_the_window_velocity() -> TestResult {
+    // 10 stars a day, 5 a day over the last week.
+    let cumulative = series(30, |x| if x < 23 { 10 * x } else { 220 + 5 * (x - 22) })?;
+    let eta = milestone_eta(&cumulative, 300, day("2025-01-30")?, 7);
+    assert_eq!(eta.current_total, 255);
+    assert!((eta.daily_rate - 5.0).abs() < 1e-9, "{}", eta.daily_rate);
+    assert_eq!((eta.reached_on, eta.eta), (None, Some(day("2025-02-08")?)));
+
+    // Days past the last one keep its total.
+    let later = milestone_eta(&cumulative, 300, day("2025-02-02")?, 7);
+    assert_eq!(later.current_total, 255);
+    assert!((later.daily_rate - 20.0 / 7.0).abs() < 1e-9, "{}", later.daily_rate);
+    Ok(())
+}
+
+#[test]
+fn targets_without_recent_growth_have_no_eta() -> TestResult {
+    let cumulative = series(30, |x| x.min(10))?;
+    let flat = milestone_eta(&cumulative, 100, day("2025-01-30")?, 7);
+    assert_eq!((flat.daily_rate, flat.reached_on, flat.eta), (0.0, None, None));
+
+    let empty = milestone_eta(&[], 100, day("2025-01-30")?, 7);
+    assert_eq!((empty.current_total, empty.eta), (0, None));
+    Ok(())
+}
+
+#[test]
+fn dates_out_of_range_leave_no_eta() -> TestResult {
+    let cumulative = series(30, |x| x)?;
+    let today = day("2025-01-30")?;
+
+    let unreachable = milestone_eta(&cumulative, i64::MAX, today, 7);
+    assert_eq!((unreachable.reached_on, unreachable.eta), (None, None));
+
+    // Windows reaching before the first representable date start from no stars.
+    for window in [1_000_000_000, 200_000_000_000_000, i64::MAX] {
+        let eta = milestone_eta(&cumulative, 100, today, window);
+        assert_eq!(eta.current_total, 29);
+        assert_eq!(eta.eta, None, "{window}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn windows_out_of_range_are_rejected() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    for window in ["0", "366", "1000000000"] {
+        let response = app.get(&format!("/github/repo_stars/milestones/acme/rocket/eta?target=10&window={window}")).await?;
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY, "{window}");
+        assert_eq!(response.json::<Value>()?["details"]["fields"], json!([{ "field": "window", "message": "must be between 1 and 365" }]));
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! Releases fetched by syncs, weighed against the star velocity around them
+//!
+//! The velocity after each release is weighed against the rest of the series,
+//! then a sync stores the releases and the lift of each is read back.
+
+use axum::http::StatusCode;
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use projects_databases::analytics::releases::release_correlation;
+use projects_databases::testing::{day, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+
+#[test]
+fn velocity_after_releases_is_weighed_against_the_rest() -> TestResult {
+    // A star a day in March, five a day for the three days of v1, none on the 15th.
+    let without_stars = day("2025-03-15")?;
+    let daily_counts: Vec<(NaiveDate, i64)> = day("2025-03-01")?
+        .iter_days()
+        .take(20)
+        .filter(|day| *day != without_stars)
+        .map(|day| (day, if (10..=12).contains(&day.day()) { 5 } else { 1 }))
+        .collect();
+    let releases = [
+        ("v1".to_string(), day("2025-03-10")?),
+        ("v2".to_string(), day("2025-03-20")?),
+        ("v3".to_string(), day("2025-04-01")?),
+    ];
+
+    let correlation = release_correlation(&daily_counts, &releases, 3, day("2025-03-20")?);
+
+    // Not published yet on the 20th, v3 is left out.
+    assert_eq!(correlation.releases.len(), 2);
+    let v1 = &correlation.releases[0];
+    assert_eq!((v1.stars_before, v1.stars_after, v1.days_after), (3, 15, 3));
+    assert_eq!((v1.velocity_before, v1.velocity_after, v1.lift), (1.0, 5.0, Some(5.0)));
+    // Released on the last day, only that one is counted after it.
+    let v2 = &correlation.releases[1];
+    assert_eq!((v2.stars_before, v2.stars_after, v2.days_after, v2.lift), (3, 1, 1, Some(1.0)));
+
+    assert_eq!(correlation.post_release_velocity, 4.0);
+    assert_eq!(correlation.baseline_velocity, 15.0 / 16.0);
+    assert_eq!(correlation.lift, Some(4.0 / (15.0 / 16.0)));
+    Ok(())
+}
+
+#[test]
+fn releases_without_stars_before_have_no_lift() -> TestResult {
+    let correlation = release_correlation(&[(day("2025-03-02")?, 4)], &[("v0.1".into(), day("2025-03-02")?)], 7, day("2025-03-02")?);
+
+    assert_eq!(correlation.releases[0].lift, None);
+    assert_eq!((correlation.post_release_velocity, correlation.baseline_velocity, correlation.lift), (4.0, 0.0, None));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn sync_stores_releases_and_weighs_them() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let published_at = Utc.with_ymd_and_hms(2025, 3, 4, 9, 0, 0).single().ok_or("invalid date")?;
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_daily_stars(day("2025-03-01")?, &[1, 1, 1, 6, 4, 1])
+        .with_release("v1.0", published_at)).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+
+    let correlation: Value = app.get("/github/repo_stars/releases/acme/rocket?window_days=2").await?.json()?;
+    assert_eq!(correlation["window_days"], 2);
+    let release = &correlation["releases"][0];
+    assert_eq!((&release["tag_name"], &release["published_on"]), (&json!("v1.0"), &json!("2025-03-04")));
+    assert_eq!((&release["stars_before"], &release["stars_after"], &release["lift"]), (&json!(2), &json!(10), &json!(5.0)));
+
+    let response = app.get("/github/repo_stars/releases/acme/rocket?window_days=0").await?;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    #[cfg(feature = "charting")]
+    {
+        let chart = json!({ "repositories": [{ "owner": "acme", "name": "rocket" }], "annotate_releases": true });
+        let response = app.post_json("/github/repo_stars/compare/chart", &chart).await?;
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(String::from_utf8(response.body.to_vec())?.contains("v1.0"));
+    }
+    Ok(())
+}
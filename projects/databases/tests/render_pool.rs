@@ -0,0 +1,45 @@
+//! Charts rendered on blocking threads, a few at once
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use projects_databases::render_pool::{RenderError, RenderPool};
+use tokio::task::JoinSet;
+
+#[tokio::test]
+async fn renders_past_the_cap_wait_for_a_free_slot() {
+    let renders = RenderPool::new(2);
+    let running = Arc::new(AtomicUsize::new(0));
+    let most_running = Arc::new(AtomicUsize::new(0));
+
+    let mut tasks = JoinSet::new();
+    for render in 0..6 {
+        let (renders, running, most_running) = (renders.clone(), running.clone(), most_running.clone());
+        tasks.spawn(async move {
+            renders.run(move || {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most_running.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(20));
+                running.fetch_sub(1, Ordering::SeqCst);
+                render
+            }).await
+        });
+    }
+    let mut rendered: Vec<i32> = tasks.join_all().await.into_iter().map(|render| render.expect("render failed")).collect();
+    rendered.sort();
+
+    assert_eq!(rendered, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(most_running.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn a_panicking_render_fails_alone() {
+    let renders = RenderPool::new(1);
+    let failed = renders.run(|| -> String { panic!("out of bounds") }).await;
+    assert!(matches!(failed, Err(RenderError::RenderTask { .. })));
+    // Its slot is given back.
+    assert_eq!(renders.run(|| 1 + 1).await.ok(), Some(2));
+}
@@ -0,0 +1,128 @@
+//! Public emails of stargazers, collected on demand and exported to API keys only
+//!
+//! Emails are only collected when enabled, are exported with an API key only,
+//! and exports are rate limited per tenant.
+
+use std::time::{Duration, Instant};
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use chrono::{TimeZone, Utc};
+use projects_databases::db::{
+    api_key::{models::NewApiKey, queries::insert_api_key},
+    tenant::{models::NewTenant, queries::insert_tenant},
+};
+use projects_databases::endpoints::limits::TenantRateLimit;
+use projects_databases::endpoints::tenant::{generate_api_key, hash_api_key};
+use projects_databases::sync_queue::{StargazerDetails, SyncQueue};
+use projects_databases::testing::{test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResponse, TestResult};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+async fn get_with_key(app: &TestApp, uri: &str, key: &str) -> Result<TestResponse, Box<dyn std::error::Error>> {
+    let request = Request::get(uri).header("x-api-key", key).body(Body::empty())?;
+    Ok(app.request(request).await?)
+}
+
+async fn post_with_key(app: &TestApp, uri: &str, key: &str, body: &Value) -> Result<TestResponse, Box<dyn std::error::Error>> {
+    let request = Request::post(uri)
+        .header("x-api-key", key)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(body)?))?;
+    Ok(app.request(request).await?)
+}
+
+#[test]
+fn rate_limit_counts_requests_per_tenant_and_window() {
+    let limit = TenantRateLimit::new(2, Duration::from_secs(60));
+    let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+    let start = Instant::now();
+
+    assert_eq!(limit.acquire(acme, start), Ok(()));
+    assert_eq!(limit.acquire(acme, start + Duration::from_secs(10)), Ok(()));
+    assert_eq!(limit.acquire(acme, start + Duration::from_secs(15)), Err(Duration::from_secs(45)));
+    assert_eq!(limit.acquire(globex, start + Duration::from_secs(15)), Ok(()));
+    assert_eq!(limit.acquire(acme, start + Duration::from_secs(60)), Ok(()));
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn public_emails_are_exported_to_api_keys_only() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let starred_at = |day| Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).single().ok_or("invalid date");
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_stargazer("ada", starred_at(1)?)
+        .with_stargazer("bob", starred_at(2)?)
+        .with_stargazer("cyd", starred_at(3)?)
+        .with_stargazer("dee", starred_at(4)?)
+        .with_stargazer("eve", starred_at(5)?)
+        .with_email("ada", "ada@Example.com")
+        .with_email("bob", "bob@elsewhere.org")
+        .with_email("cyd", "cyd@users.noreply.github.com")
+        .with_email("dee", " ")
+        .with_email("eve", "eve@example.com")).await;
+    // Ada has hidden their email by the time comet is synced.
+    github.mount_repository(MockRepository::new("acme", "comet").with_stargazer("ada", starred_at(6)?)).await;
+
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    let collect = StargazerDetails { profiles: false, emails: true, bots: false, activity: false };
+    dependencies.sync_queue = SyncQueue::start(1, database.pool().clone(), dependencies.forge_clients.clone(), dependencies.notifier.clone(), dependencies.live_updates.clone(), collect, dependencies.stargazer_ids.clone(), dependencies.chart_cache.clone());
+    let app = TestApp::new(dependencies);
+
+    let key = generate_api_key()?;
+    let mut conn = database.pool().get()?;
+    let tenant = insert_tenant(&mut conn, &NewTenant { id: Uuid::new_v4(), name: "acme" })?;
+    insert_api_key(&mut conn, &NewApiKey { id: Uuid::new_v4(), tenant_id: tenant.id, key_hash: &hash_api_key(&key) })?;
+
+    let response = post_with_key(&app, "/github/repo_stars/update", &key, &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+
+    let anonymous = app.get("/github/stargazers/emails/acme/rocket").await?;
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+
+    let response = get_with_key(&app, "/github/stargazers/emails/acme/rocket?limit=2", &key).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    assert_eq!(response.headers.get("cache-control").and_then(|value| value.to_str().ok()), Some("no-store"));
+    let page: Value = response.json()?;
+    assert_eq!(page["stargazers"].as_array().map(|stargazers| stargazers.iter().map(|stargazer| stargazer["email"].clone()).collect()), Some(vec![json!("ada@Example.com"), json!("bob@elsewhere.org")]));
+    assert_eq!(page["next_after"], "bob");
+
+    let page: Value = get_with_key(&app, "/github/stargazers/emails/acme/rocket?limit=2&after=bob", &key).await?.json()?;
+    assert_eq!(page["stargazers"].as_array().map(|stargazers| stargazers.iter().map(|stargazer| stargazer["login"].clone()).collect()), Some(vec![json!("eve")]));
+    assert_eq!(page["next_after"], Value::Null);
+
+    let page: Value = get_with_key(&app, "/github/stargazers/emails/acme/rocket?domain=EXAMPLE.com&since=2025-03-02", &key).await?.json()?;
+    assert_eq!(page["stargazers"].as_array().map(|stargazers| stargazers.iter().map(|stargazer| stargazer["login"].clone()).collect()), Some(vec![json!("eve")]));
+
+    let invalid = get_with_key(&app, "/github/stargazers/emails/acme/rocket?domain=%25.com", &key).await?;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+
+    let response = post_with_key(&app, "/github/repo_stars/update", &key, &json!({ "owner": "acme", "name": "comet" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let page: Value = get_with_key(&app, "/github/stargazers/emails/acme/rocket", &key).await?.json()?;
+    assert_eq!(page["stargazers"].as_array().map(|stargazers| stargazers.iter().map(|stargazer| stargazer["login"].clone()).collect()), Some(vec![json!("bob"), json!("eve")]));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn emails_are_not_collected_unless_enabled() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_stargazer("ada", Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).single().ok_or("invalid date")?)
+        .with_email("ada", "ada@example.com")).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let key = generate_api_key()?;
+    let mut conn = database.pool().get()?;
+    let tenant = insert_tenant(&mut conn, &NewTenant { id: Uuid::new_v4(), name: "acme" })?;
+    insert_api_key(&mut conn, &NewApiKey { id: Uuid::new_v4(), tenant_id: tenant.id, key_hash: &hash_api_key(&key) })?;
+
+    let response = post_with_key(&app, "/github/repo_stars/update", &key, &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let page: Value = get_with_key(&app, "/github/stargazers/emails/acme/rocket", &key).await?.json()?;
+    assert_eq!(page["stargazers"], json!([]));
+    Ok(())
+}
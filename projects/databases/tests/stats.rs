@@ -0,0 +1,67 @@
+//! Headline numbers of a star history: trailing windows, best day and streaks
+//!
+//! No database needed.
+
+use chrono::NaiveDate;
+use projects_databases::analytics::stats::star_stats;
+use projects_databases::testing::{day, TestResult};
+
+/// Stars of each listed day of January 2025.
+fn january(days: &[(u32, i64)]) -> Result<Vec<(NaiveDate, i64)>, chrono::ParseError> {
+    days.iter().map(|&(of_month, stars)| Ok((day(&format!("2025-01-{of_month:02}"))?, stars))).collect()
+}
+
+#[test]
+fn empty_histories_have_no_stars_nor_streak() -> TestResult {
+    let stats = star_stats(&[], day("2025-01-31")?);
+    assert_eq!((stats.total_stars, stats.last_7_days, stats.last_30_days, stats.last_365_days), (0, 0, 0, 0));
+    assert!(stats.best_day.is_none());
+    assert_eq!((stats.first_star_on, stats.current_streak_days), (None, 0));
+    assert_eq!(stats.average_daily_rate, 0.0);
+    Ok(())
+}
+
+#[test]
+fn trailing_windows_include_today_and_stop_there() -> TestResult {
+    // The 24th is the first day of the 7 ending on the 30th, the 31st is after it.
+    let daily = january(&[(1, 1), (23, 2), (24, 4), (30, 8), (31, 16)])?;
+    let stats = star_stats(&daily, day("2025-01-30")?);
+    assert_eq!(stats.total_stars, 31);
+    assert_eq!((stats.last_7_days, stats.last_30_days, stats.last_365_days), (12, 15, 15));
+    assert_eq!(stats.first_star_on, Some(day("2025-01-01")?));
+    assert!((stats.average_daily_rate - 31.0 / 30.0).abs() < 1e-9, "{}", stats.average_daily_rate);
+    Ok(())
+}
+
+#[test]
+fn best_day_is_the_earliest_of_a_tie() -> TestResult {
+    let stats = star_stats(&january(&[(3, 5), (9, 7), (14, 2), (20, 7)])?, day("2025-01-31")?);
+    let best_day = stats.best_day.ok_or("no best day")?;
+    assert_eq!((best_day.date, best_day.stars), (day("2025-01-09")?, 7));
+    Ok(())
+}
+
+#[test]
+fn streaks_run_up_to_today_or_yesterday() -> TestResult {
+    let daily = january(&[(10, 1), (12, 3), (13, 1), (14, 2)])?;
+    assert_eq!(star_stats(&daily, day("2025-01-14")?).current_streak_days, 3);
+
+    // Today has no stars yet, the streak ending yesterday still counts.
+    assert_eq!(star_stats(&daily, day("2025-01-15")?).current_streak_days, 3);
+    assert_eq!(star_stats(&daily, day("2025-01-16")?).current_streak_days, 0);
+    Ok(())
+}
+
+#[test]
+fn streaks_are_broken_by_a_day_without_stars() -> TestResult {
+    // The 12th has no stars, recorded as 0 or missing.
+    let daily = january(&[(9, 1), (10, 1), (11, 2), (12, 0), (13, 4), (14, 1)])?;
+    assert_eq!(star_stats(&daily, day("2025-01-14")?).current_streak_days, 2);
+
+    let missing = january(&[(9, 1), (10, 1), (11, 2), (13, 4), (14, 1)])?;
+    assert_eq!(star_stats(&missing, day("2025-01-14")?).current_streak_days, 2);
+
+    // Days after today don't count.
+    assert_eq!(star_stats(&daily, day("2025-01-11")?).current_streak_days, 3);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! SQLite storage picked by the `DATABASE_URL` scheme, in an in-memory database
+
+use chrono::{NaiveDate, NaiveDateTime};
+use interfaces_github_stargazers::forge::Forge;
+use projects_databases::storage::{
+    NewStoredRepository, NewStoredStar, ParseStorageBackendError, StarStorage, Storage, StorageBackend,
+};
+use uuid::Uuid;
+
+fn at(day: u32, hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 3, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+}
+
+#[test]
+fn backends_are_picked_by_scheme() {
+    assert_eq!(StorageBackend::from_url("postgres://user@localhost/stars").unwrap(), StorageBackend::Postgres);
+    assert_eq!(StorageBackend::from_url("postgresql://localhost/stars").unwrap(), StorageBackend::Postgres);
+    assert_eq!(StorageBackend::from_url("sqlite://stars.db").unwrap(), StorageBackend::Sqlite { path: "stars.db".into() });
+    assert_eq!(StorageBackend::from_url("sqlite:///var/lib/stars.db").unwrap(), StorageBackend::Sqlite { path: "/var/lib/stars.db".into() });
+    assert!(matches!(
+        StorageBackend::from_url("mysql://localhost/stars"),
+        Err(ParseStorageBackendError::UnsupportedScheme { scheme }) if scheme == "mysql"
+    ));
+}
+
+#[tokio::test]
+async fn repositories_are_found_whatever_their_case() {
+    let storage = Storage::from_url("sqlite://:memory:", Uuid::nil()).unwrap();
+
+    let repo = storage.insert_repository(NewStoredRepository { owner: "rust-lang", name: "rust", forge: Forge::GitHub }).await.unwrap();
+    let again = storage.insert_repository(NewStoredRepository { owner: " Rust-Lang", name: "RUST ", forge: Forge::GitHub }).await.unwrap();
+    assert_eq!(again, repo);
+    assert_eq!((repo.owner.as_str(), repo.name.as_str(), repo.forge), ("rust-lang", "rust", Forge::GitHub));
+
+    assert_eq!(storage.get_repository_by_name("RUST-LANG", "Rust").await.unwrap(), Some(repo));
+    assert_eq!(storage.get_repository_by_name("rust-lang", "cargo").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn stars_are_counted_per_day_once_per_stargazer() {
+    let storage = Storage::from_url("sqlite://:memory:", Uuid::nil()).unwrap();
+    let repo = storage.insert_repository(NewStoredRepository { owner: "gitlab-org", name: "gitlab", forge: Forge::GitLab }).await.unwrap();
+    let other = storage.insert_repository(NewStoredRepository { owner: "gitlab-org", name: "gitaly", forge: Forge::GitLab }).await.unwrap();
+
+    let stars = [
+        NewStoredStar { stargazer: "alice", starred_at: at(1, 9) },
+        NewStoredStar { stargazer: "bob", starred_at: at(1, 23) },
+        NewStoredStar { stargazer: "carol", starred_at: at(4, 0) },
+    ];
+    assert_eq!(storage.insert_stars_batch(repo.id, &stars).await.unwrap(), 3);
+    storage.insert_stars_batch(other.id, &stars[..1]).await.unwrap();
+
+    // A second sync stores the stars given since, the others only have their fetch time refreshed.
+    let resynced = [stars[2], NewStoredStar { stargazer: "dave", starred_at: at(4, 12) }];
+    assert_eq!(storage.insert_stars_batch(repo.id, &resynced).await.unwrap(), 2);
+
+    let daily = storage.get_daily_star_count(repo.id).await.unwrap();
+    assert_eq!(daily, vec![(at(1, 0).date(), 2), (at(4, 0).date(), 2)]);
+    assert_eq!(storage.get_daily_star_count(other.id).await.unwrap(), vec![(at(1, 0).date(), 1)]);
+    assert!(storage.get_daily_star_count(Uuid::new_v4()).await.unwrap().is_empty());
+}
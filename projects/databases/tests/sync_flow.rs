@@ -0,0 +1,430 @@
+//! Syncs through `POST /github/repo_stars/update`, then reads of what they stored
+//!
+//! Every test gets a database of its own and a mocked GitHub, see
+//! `projects_databases::testing`. They need Docker, or `TEST_DATABASE_URL`
+//! naming a server whose user may create databases, so they only run when
+//! asked for:
+//!
+//! ```sh
+//! cargo test -p projects_databases --test sync_flow -- --ignored
+//! ```
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use chrono::{NaiveDate, TimeZone, Utc};
+use interfaces_github_stargazers::forge::{Forge, ForgeErrorKind};
+use projects_databases::sync_lock::{try_lock_repository, unlock_repository, RepositoryLock};
+use projects_databases::testing::{day, test_dependencies, MockGitHub, MockRepository, ScriptedFetcher, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+
+async fn sync(app: &TestApp, owner: &str, name: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": owner, "name": name })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    Ok(response.json()?)
+}
+
+fn ndjson(body: &[u8]) -> Result<Vec<Value>, serde_json::Error> {
+    body.split(|byte| *byte == b'\n').filter(|line| !line.is_empty()).map(serde_json::from_slice).collect()
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn sync_then_read_daily_counts() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 0, 5, 2])).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let summary = sync(&app, "acme", "rocket").await?;
+    assert_eq!(summary["stars"], 10);
+    assert_eq!(summary["pages"], 1);
+
+    let response = app.post_json("/github/repo_stars/read_per_day", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK);
+    let daily: Vec<(NaiveDate, i64)> = response.json()?;
+    assert_eq!(daily, vec![(day("2025-01-01")?, 3), (day("2025-01-03")?, 5), (day("2025-01-04")?, 2)]);
+
+    let response = app.post_json("/github/repo_stars/read_per_day", &json!({ "owner": "ACME", "name": "Rocket", "limit": 2, "offset": 1 })).await?;
+    assert_eq!(response.status, StatusCode::OK);
+    let page: Value = response.json()?;
+    assert_eq!(page, json!({ "data": [["2025-01-03", 5], ["2025-01-04", 2]], "total": 3, "limit": 2, "offset": 1 }));
+
+    let response = app.post_json("/github/repo_stars/read_per_day", &json!({ "owner": "acme", "name": "rocket", "limit": 0, "offset": -1 })).await?;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let error: Value = response.json()?;
+    assert_eq!(error["details"]["fields"], json!([
+        { "field": "limit", "message": "must be at least 1" },
+        { "field": "offset", "message": "cannot be negative" },
+    ]));
+    let response = app.get("/github/repo_stars/read_per_day/acme/rocket?offset=-5").await?;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn heatmap_lays_out_daily_stars_by_weekday() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    // 2025-01-01 is a Wednesday.
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 0, 5, 2])).await;
+    let app = TestApp::spawn(&database, &github)?;
+    sync(&app, "acme", "rocket").await?;
+
+    let response = app.get("/github/repo_stars/heatmap/acme/rocket?weeks=2&as_of=2025-01-03").await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let heatmap: Value = response.json()?;
+    assert_eq!(heatmap, json!({
+        "as_of": "2025-01-03",
+        "total_stars": 8,
+        "max_day_stars": 5,
+        "weekday_totals": [0, 0, 3, 0, 5, 0, 0],
+        "weeks": [
+            { "start": "2024-12-23", "stars": [0, 0, 0, 0, 0, 0, 0] },
+            { "start": "2024-12-30", "stars": [0, 0, 3, 0, 5, null, null] },
+        ],
+    }));
+
+    let response = app.get("/github/repo_stars/heatmap/acme/rocket?weeks=1&as_of=2025-01-05&format=svg&theme=dark").await?;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.headers.get(header::CONTENT_TYPE).map(|value| value.as_bytes()), Some(&b"image/svg+xml"[..]));
+    let svg = String::from_utf8(response.body.to_vec())?;
+    assert!(svg.contains("acme/rocket stars by weekday"));
+    assert_eq!(svg.matches("<title>").count(), 7);
+    assert!(svg.contains("<title>2025-01-03: 5</title>"));
+
+    let response = app.get("/github/repo_stars/heatmap/acme/rocket?weeks=0").await?;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[cfg(feature = "charting")]
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn sync_then_draw_social_card() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[4, 1, 7])).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    sync(&app, "acme", "rocket").await?;
+
+    let response = app.get("/github/repo_stars/social_card/acme/rocket").await?;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.headers[header::CONTENT_TYPE], "image/png");
+    assert!(response.body.starts_with(b"\x89PNG\r\n\x1a\n"));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn sync_fetches_every_page() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "comet").with_daily_stars(day("2025-03-01")?, &[100, 100, 50])).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let summary = sync(&app, "acme", "comet").await?;
+    assert_eq!(summary["stars"], 250);
+    assert_eq!(summary["pages"], 3);
+
+    let response = app.post_json("/github/repo_stars/read_per_day", &json!({ "owner": "acme", "name": "comet" })).await?;
+    let daily: Vec<(NaiveDate, i64)> = response.json()?;
+    assert_eq!(daily.iter().map(|(_, stars)| stars).sum::<i64>(), 250);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn resync_stores_new_stars_once() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let repository = MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[2, 2]);
+    github.mount_repository(repository.clone()).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    sync(&app, "acme", "rocket").await?;
+
+    github.server().reset().await;
+    github.mount_repository(repository.with_stargazer("late", Utc.with_ymd_and_hms(2025, 1, 2, 18, 0, 0).single().ok_or("invalid date")?)).await;
+    sync(&app, "acme", "rocket").await?;
+
+    let response = app.post_json("/github/repo_stars/read_per_day", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    let daily: Vec<(NaiveDate, i64)> = response.json()?;
+    assert_eq!(daily, vec![(day("2025-01-01")?, 2), (day("2025-01-02")?, 3)]);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn unknown_repositories_are_not_found() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_missing_repository("acme", "gone").await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let response = app.post_json("/github/repo_stars/read_per_day", &json!({ "owner": "acme", "name": "gone" })).await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "gone" })).await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn transferred_repositories_are_followed_and_keep_their_former_name() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[2])).await;
+    let app = TestApp::spawn(&database, &github)?;
+    let before = sync(&app, "acme", "rocket").await?;
+
+    github.server().reset().await;
+    github.mount_moved_repository("acme", "rocket", MockRepository::new("orbit", "rocket").with_daily_stars(day("2025-01-01")?, &[2, 1])).await;
+    let after = sync(&app, "acme", "rocket").await?;
+    assert_eq!(after["repository_id"], before["repository_id"]);
+
+    let response = app.get("/github/repositories").await?;
+    let repositories: Vec<Value> = response.json()?;
+    assert_eq!(repositories.len(), 1);
+    assert_eq!((&repositories[0]["owner"], &repositories[0]["name"]), (&json!("orbit"), &json!("rocket")));
+    assert_eq!(repositories[0]["former_names"], json!(["acme/rocket"]));
+
+    for owner in ["acme", "orbit"] {
+        let response = app.post_json("/github/repo_stars/read_per_day", &json!({ "owner": owner, "name": "rocket" })).await?;
+        let daily: Vec<(NaiveDate, i64)> = response.json()?;
+        assert_eq!(daily, vec![(day("2025-01-01")?, 2), (day("2025-01-02")?, 1)]);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn repository_details_follow_the_forge_on_every_sync() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    // Imported first, the repository has none of them.
+    let import = Request::post("/github/repo_stars/import?owner=acme&name=rocket")
+        .header(header::CONTENT_TYPE, "text/csv")
+        .body(Body::from("stargazer,starred_at\nada,2025-01-01T12:00:00Z\n"))?;
+    assert_eq!(app.request(import).await?.status, StatusCode::OK);
+    let details = |repositories: Vec<Value>| repositories.first().map(|repo| (repo["description"].clone(), repo["topics"].clone(), repo["github_created_at"].is_null()));
+    assert_eq!(details(app.get("/github/repositories").await?.json()?), Some((Value::Null, json!([]), true)));
+
+    github.mount_repository(MockRepository::new("acme", "rocket").with_description("Rockets", &["space", "rust"]).with_daily_stars(day("2025-01-01")?, &[1])).await;
+    sync(&app, "acme", "rocket").await?;
+    assert_eq!(details(app.get("/github/repositories").await?.json()?), Some((json!("Rockets"), json!(["space", "rust"]), false)));
+
+    // Edited on GitHub since.
+    github.server().reset().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[1])).await;
+    sync(&app, "acme", "rocket").await?;
+    assert_eq!(details(app.get("/github/repositories").await?.json()?), Some((Value::Null, json!([]), false)));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn streamed_sync_sends_pages_then_the_summary() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "comet").with_daily_stars(day("2025-03-01")?, &[100, 100, 50])).await;
+    github.mount_missing_repository("acme", "gone").await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "comet", "stream": true })).await?;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.headers[header::CONTENT_TYPE], "application/x-ndjson");
+    let lines = ndjson(&response.body)?;
+    let events: Vec<_> = lines.iter().map(|line| line["event"].as_str()).collect();
+    assert_eq!(events, [Some("page"), Some("page"), Some("page"), Some("completed")]);
+    assert_eq!(lines[2]["page"], 3);
+    assert_eq!(lines[2]["stargazers"].as_array().map(Vec::len), Some(50));
+    assert_eq!(lines[3]["stars"], 250);
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "gone", "stream": true })).await?;
+    assert_eq!(response.status, StatusCode::OK);
+    let lines = ndjson(&response.body)?;
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["event"], "failed");
+    assert_eq!(lines[0]["status"], 404);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn diff_lists_stargazers_gained_and_lost_between_runs() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let starred_at = |hour| Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).single().ok_or("invalid date");
+    let before = MockRepository::new("acme", "rocket").with_stargazer("ada", starred_at(1)?).with_stargazer("bob", starred_at(2)?);
+    github.mount_repository(before).await;
+    let app = TestApp::spawn(&database, &github)?;
+    sync(&app, "acme", "rocket").await?;
+
+    github.server().reset().await;
+    let after = MockRepository::new("acme", "rocket").with_stargazer("ada", starred_at(1)?).with_stargazer("cy", starred_at(3)?);
+    github.mount_repository(after).await;
+    sync(&app, "acme", "rocket").await?;
+
+    let history: Vec<Value> = app.get("/github/repositories/acme/rocket/sync_history").await?.json()?;
+    // Newest first
+    let to_run = history[0]["id"].as_str().ok_or("run without an ID")?;
+    let from_run = history[1]["id"].as_str().ok_or("run without an ID")?;
+
+    let response = app.get(&format!("/github/repo_stars/diff/acme/rocket?from_run={from_run}&to_run={to_run}")).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let diff: Value = response.json()?;
+    assert_eq!(diff["added"], json!([{ "stargazer": "cy", "starred_at": "2025-01-01T03:00:00" }]));
+    assert_eq!(diff["removed"], json!([{ "stargazer": "bob", "starred_at": "2025-01-01T02:00:00" }]));
+    assert_eq!((diff["added_count"].as_i64(), diff["removed_count"].as_i64()), (Some(1), Some(1)));
+
+    let response = app.get(&format!("/github/repo_stars/diff/acme/rocket?from_run={to_run}&to_run={from_run}")).await?;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    let response = app.get(&format!("/github/repo_stars/diff/acme/rocket?from_run={from_run}&to_run={}", uuid::Uuid::new_v4())).await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn page_limit_stops_the_sync_partially_completed() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "comet").with_daily_stars(day("2025-03-01")?, &[100, 100, 50])).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "comet", "max_pages": 2 })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    assert_eq!((summary["pages"].as_u64(), summary["stars"].as_u64()), (Some(2), Some(200)));
+    assert_eq!(summary["stop_reason"], "max_pages");
+
+    let job_id = summary["job_id"].as_str().ok_or("summary without a job ID")?;
+    let job: Value = app.get(&format!("/github/repo_stars/jobs/{job_id}")).await?.json()?;
+    assert_eq!(job["status"], "partially_completed");
+    assert_eq!(job["stop_reason"], "max_pages");
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "comet", "max_pages": 0 })).await?;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn partially_completed_sync_resumes_from_its_cursor() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "comet").with_daily_stars(day("2025-03-01")?, &[100, 100, 50])).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "comet", "max_pages": 2 })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    let job_id = summary["job_id"].as_str().ok_or("summary without a job ID")?;
+
+    let response = app.post_json(&format!("/github/repo_stars/jobs/{job_id}/resume"), &json!({})).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let job: Value = app.get(&format!("/github/repo_stars/jobs/{job_id}")).await?.json()?;
+    assert_eq!(job["status"], "completed");
+    assert_eq!(job["stop_reason"], Value::Null);
+    assert_eq!((job["pages_fetched"].as_u64(), job["stars_fetched"].as_u64()), (Some(3), Some(250)));
+
+    let response = app.post_json("/github/repo_stars/read_per_day", &json!({ "owner": "acme", "name": "comet" })).await?;
+    let daily: Vec<(NaiveDate, i64)> = response.json()?;
+    assert_eq!(daily, vec![(day("2025-03-01")?, 100), (day("2025-03-02")?, 100), (day("2025-03-03")?, 50)]);
+
+    let response = app.post_json(&format!("/github/repo_stars/jobs/{job_id}/resume"), &json!({})).await?;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn job_status_reports_the_retries() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let repository = MockRepository::new("acme", "comet").with_daily_stars(day("2025-03-01")?, &[100, 100, 50]);
+    let fetcher = Arc::new(ScriptedFetcher::new(Forge::GitHub, repository).failing_on(1, ForgeErrorKind::Overloaded));
+    let app = TestApp::new(test_dependencies(&database, github.forge_clients()?.with_fetcher(fetcher)));
+
+    let summary = sync(&app, "acme", "comet").await?;
+    assert_eq!(summary["retries"], 1);
+
+    let job_id = summary["job_id"].as_str().ok_or("summary without a job ID")?;
+    let job: Value = app.get(&format!("/github/repo_stars/jobs/{job_id}")).await?.json()?;
+    assert_eq!(job["retries"], 1);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn panicking_sync_fails_alone_and_frees_its_worker() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let repository = MockRepository::new("acme", "comet").with_daily_stars(day("2025-03-01")?, &[3, 2]);
+    let fetcher = Arc::new(ScriptedFetcher::new(Forge::GitHub, repository).panicking_on(0));
+    let app = TestApp::new(test_dependencies(&database, github.forge_clients()?.with_fetcher(fetcher)));
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "comet" })).await?;
+    assert_eq!(response.status, StatusCode::INTERNAL_SERVER_ERROR, "{}", String::from_utf8_lossy(&response.body));
+    let queue: Value = app.get("/github/repo_stars/jobs/queue").await?.json()?;
+    assert_eq!(queue["running"], 0);
+
+    // The only worker is still there to run the next sync.
+    let summary = sync(&app, "acme", "comet").await?;
+    assert_eq!(summary["stars"], 5);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn repository_locked_by_another_instance_is_not_synced() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 1])).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let summary = sync(&app, "acme", "rocket").await?;
+    let repository_id: uuid::Uuid = summary["repository_id"].as_str().ok_or("summary without a repository ID")?.parse()?;
+
+    // Another instance's session, syncing the repository meanwhile.
+    let mut other = database.pool().get()?;
+    assert!(try_lock_repository(&mut other, repository_id)?);
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    let error: Value = response.json()?;
+    assert_eq!(error["code"], "sync_in_progress");
+
+    unlock_repository(&mut other, repository_id)?;
+    sync(&app, "acme", "rocket").await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn repository_lock_is_released_when_its_sync_panics() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let repository_id = uuid::Uuid::new_v4();
+    let mut conn = database.pool().get()?;
+
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let lock = RepositoryLock::try_acquire(&mut conn, repository_id);
+        assert!(matches!(lock, Ok(Some(_))));
+        panic!("sync panicked while holding the lock");
+    }));
+    assert!(panicked.is_err());
+
+    // The connection is back to the pool as it would be, without the lock.
+    drop(conn);
+    let mut other = database.pool().get()?;
+    assert!(try_lock_repository(&mut other, repository_id)?);
+    unlock_repository(&mut other, repository_id)?;
+    Ok(())
+}
@@ -0,0 +1,132 @@
+//! Lifecycle of sync jobs: the moves each status allows, the times they stamp,
+//! and the unfinished jobs interrupted when the service starts
+
+use axum::http::StatusCode;
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use projects_databases::db::sync_job::{
+    models::{NewSyncJob, SyncJobState, SyncJobStatus},
+    queries::{get_sync_job_by_id, insert_sync_job},
+};
+use projects_databases::sync_lock::{try_lock_repository, unlock_repository};
+use projects_databases::sync_queue::interrupt_abandoned_sync_jobs;
+use projects_databases::testing::{day, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use SyncJobStatus::*;
+
+const STATUSES: [SyncJobStatus; 6] = [Pending, Running, Completed, PartiallyCompleted, Failed, Interrupted];
+
+fn now() -> Result<NaiveDateTime, &'static str> {
+    NaiveDate::from_ymd_opt(2025, 5, 29).and_then(|day| day.and_hms_opt(10, 0, 0)).ok_or("invalid date")
+}
+
+fn state(status: SyncJobStatus) -> SyncJobState {
+    SyncJobState { status, started_at: None, finished_at: None }
+}
+
+#[test]
+fn only_the_lifecycle_moves_are_allowed() {
+    let allowed = [
+        (Pending, Running),
+        (Pending, Interrupted),
+        (Running, Completed),
+        (Running, PartiallyCompleted),
+        (Running, Failed),
+        (Running, Interrupted),
+        (PartiallyCompleted, Pending),
+        (Failed, Pending),
+        (Interrupted, Pending),
+    ];
+    for from in STATUSES {
+        for to in STATUSES {
+            let expected = allowed.contains(&(from, to));
+            assert_eq!(from.can_become(to), expected, "{} to {}", from.as_str(), to.as_str());
+
+            let mut moved = state(from);
+            assert_eq!(moved.transition(Uuid::nil(), to, NaiveDateTime::MIN).is_ok(), expected, "{} to {}", from.as_str(), to.as_str());
+            assert_eq!(moved.status, if expected { to } else { from });
+        }
+    }
+}
+
+#[test]
+fn refused_moves_name_both_statuses_and_change_nothing() -> TestResult {
+    let mut completed = SyncJobState { status: Completed, started_at: Some(now()?), finished_at: Some(now()?) };
+    let refused = completed.transition(Uuid::nil(), Pending, now()? + TimeDelta::hours(1)).err().ok_or("completed job resumed")?;
+    assert_eq!((refused.from, refused.to), ("completed", "pending"));
+    assert_eq!(completed, SyncJobState { status: Completed, started_at: Some(now()?), finished_at: Some(now()?) });
+    Ok(())
+}
+
+#[test]
+fn runs_are_stamped_when_they_start_and_end() -> TestResult {
+    let start = now()?;
+    let mut job = state(Pending);
+
+    job.transition(Uuid::nil(), Running, start)?;
+    assert_eq!((job.started_at, job.finished_at), (Some(start), None));
+    job.transition(Uuid::nil(), Failed, start + TimeDelta::minutes(1))?;
+    assert_eq!((job.started_at, job.finished_at), (Some(start), Some(start + TimeDelta::minutes(1))));
+
+    // Resumed, the job keeps its first start and is unfinished until its run ends.
+    job.transition(Uuid::nil(), Pending, start + TimeDelta::minutes(2))?;
+    assert_eq!((job.started_at, job.finished_at), (Some(start), None));
+    job.transition(Uuid::nil(), Running, start + TimeDelta::minutes(3))?;
+    assert_eq!((job.started_at, job.finished_at), (Some(start), None));
+    job.transition(Uuid::nil(), Completed, start + TimeDelta::minutes(4))?;
+    assert_eq!((job.started_at, job.finished_at), (Some(start), Some(start + TimeDelta::minutes(4))));
+    Ok(())
+}
+
+#[test]
+fn pending_jobs_interrupted_before_running_have_no_start() -> TestResult {
+    let mut job = state(Pending);
+    job.transition(Uuid::nil(), Interrupted, now()?)?;
+    assert_eq!((job.started_at, job.finished_at), (None, Some(now()?)));
+    assert!(job.status.is_finished());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn unfinished_jobs_are_interrupted_on_start_then_resumable() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 1])).await;
+    github.mount_repository(MockRepository::new("acme", "comet").with_daily_stars(day("2025-01-01")?, &[2])).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let mut repository_ids = Vec::new();
+    for name in ["rocket", "comet"] {
+        let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": name })).await?;
+        assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+        let summary: Value = response.json()?;
+        repository_ids.push(summary["repository_id"].as_str().ok_or("summary without a repository ID")?.parse::<Uuid>()?);
+    }
+    let (rocket, comet) = (repository_ids[0], repository_ids[1]);
+
+    // Left behind by a previous run, but comet's sync is still going on another instance.
+    let mut conn = database.pool().get()?;
+    let mut leave = |repository_id: Uuid, status: SyncJobStatus| {
+        let job = NewSyncJob { id: Uuid::new_v4(), repository_id, status: status.as_str(), batch_id: None, started_at: None };
+        insert_sync_job(&mut conn, &job).map(|job| job.id)
+    };
+    let pending = leave(rocket, Pending)?;
+    let running = leave(rocket, Running)?;
+    let elsewhere = leave(comet, Running)?;
+    let mut other = database.pool().get()?;
+    assert!(try_lock_repository(&mut other, comet)?);
+
+    assert_eq!(interrupt_abandoned_sync_jobs(&mut conn)?, 2);
+    let status = |conn: &mut _, id| get_sync_job_by_id(conn, id).map(|job| job.map(|job| job.status));
+    assert_eq!(status(&mut conn, pending)?.as_deref(), Some("interrupted"));
+    assert_eq!(status(&mut conn, running)?.as_deref(), Some("interrupted"));
+    assert_eq!(status(&mut conn, elsewhere)?.as_deref(), Some("running"));
+    unlock_repository(&mut other, comet)?;
+
+    let response = app.post_json(&format!("/github/repo_stars/jobs/{pending}/resume"), &json!({})).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    assert_eq!(status(&mut conn, pending)?.as_deref(), Some("completed"));
+    Ok(())
+}
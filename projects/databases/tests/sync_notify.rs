@@ -0,0 +1,83 @@
+//! Sync job endings announced over PostgreSQL NOTIFY
+//!
+//! Channel names are checked before use, and listeners hear of a finished sync
+//! with its repository and star count.
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use chrono::{TimeZone, Utc};
+use diesel::{sql_query, RunQueryDsl};
+use projects_databases::live::{is_notify_channel, LiveUpdates, SyncNotification};
+use projects_databases::sync_queue::{StargazerDetails, SyncQueue};
+use projects_databases::testing::{test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+#[test]
+fn notify_channels_are_plain_lowercase_identifiers() {
+    assert!(is_notify_channel("star_syncs"));
+    assert!(is_notify_channel("_syncs2"));
+    assert!(!is_notify_channel(""));
+    assert!(!is_notify_channel("Star_Syncs"));
+    assert!(!is_notify_channel("2syncs"));
+    assert!(!is_notify_channel("star syncs; DROP TABLE stars"));
+    assert!(!is_notify_channel(&"a".repeat(64)));
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn finished_syncs_are_notified_to_listeners() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let day = |day| Utc.with_ymd_and_hms(2025, 3, day, 9, 0, 0).single().ok_or("invalid date");
+    github.mount_repository(MockRepository::new("acme", "rocket")
+        .with_stargazer("ada", day(1)?)
+        .with_stargazer("grace", day(2)?)).await;
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    dependencies.live_updates = LiveUpdates::new().with_notify_channel("star_syncs");
+    dependencies.sync_queue = SyncQueue::start(1, database.pool().clone(), dependencies.forge_clients.clone(), dependencies.notifier.clone(), dependencies.live_updates.clone(), StargazerDetails::default(), dependencies.stargazer_ids.clone(), dependencies.chart_cache.clone());
+    let app = TestApp::new(dependencies);
+
+    let mut listener = database.pool().get()?;
+    sql_query("LISTEN star_syncs").execute(&mut listener)?;
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    let repository_id: Uuid = summary["repository_id"].as_str().ok_or("summary without a repository ID")?.parse()?;
+    let job_id: Uuid = summary["job_id"].as_str().ok_or("summary without a job ID")?.parse()?;
+
+    // Delivered once committed, which the answer may have beaten.
+    let mut payloads = Vec::new();
+    for _ in 0..50 {
+        for notification in listener.notifications_iter() {
+            payloads.push(notification?.payload);
+        }
+        if !payloads.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(payloads.len(), 1);
+    let notification: SyncNotification = serde_json::from_str(&payloads[0])?;
+    assert_eq!(notification.repository_id, repository_id);
+    assert_eq!(notification.job_id, job_id);
+    assert_eq!((notification.owner.as_str(), notification.name.as_str()), ("acme", "rocket"));
+    assert_eq!(notification.status, "completed");
+    assert_eq!(notification.stars_added, Some(2));
+
+    // Nothing new to store is announced as well.
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let mut notification = None;
+    for _ in 0..50 {
+        if let Some(received) = listener.notifications_iter().next() {
+            notification = Some(serde_json::from_str::<SyncNotification>(&received?.payload)?);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(notification.map(|notification| notification.stars_added), Some(Some(0)));
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Tenants only reaching their own repositories, jobs, alerts, reports and
+//! batches, and requests without an API key turned away when keys are required
+//!
+//! Another tenant's resources are reported as missing, not forbidden.
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use projects_databases::db::{
+    api_key::{models::NewApiKey, queries::insert_api_key},
+    report::{models::{NewReport, ReportKind}, queries::insert_report},
+    sync_batch::{models::NewSyncBatch, queries::insert_sync_batch},
+    tenant::{models::NewTenant, queries::insert_tenant},
+};
+use projects_databases::endpoints::tenant::{generate_api_key, hash_api_key};
+use projects_databases::testing::{day, test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResponse, TestResult};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+async fn request_with_key(app: &TestApp, method: Method, uri: &str, key: &str, body: Option<&Value>) -> Result<TestResponse, Box<dyn std::error::Error>> {
+    let request = Request::builder().method(method).uri(uri).header("x-api-key", key);
+    let request = match body {
+        Some(body) => request.header("content-type", "application/json").body(Body::from(serde_json::to_vec(body)?))?,
+        None => request.body(Body::empty())?,
+    };
+    Ok(app.request(request).await?)
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn tenants_only_reach_their_own_resources() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3, 1])).await;
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    dependencies.tenant_auth.require_api_key = true;
+    let app = TestApp::new(dependencies);
+
+    let mut conn = database.pool().get()?;
+    let mut keys = Vec::new();
+    for name in ["acme", "globex"] {
+        let key = generate_api_key()?;
+        let tenant = insert_tenant(&mut conn, &NewTenant { id: Uuid::new_v4(), name })?;
+        insert_api_key(&mut conn, &NewApiKey { id: Uuid::new_v4(), tenant_id: tenant.id, key_hash: &hash_api_key(&key) })?;
+        keys.push((tenant.id, key));
+    }
+    let ((tenant_a, key_a), (_, key_b)) = (&keys[0], &keys[1]);
+
+    let response = request_with_key(&app, Method::POST, "/github/repo_stars/update", key_a, Some(&json!({ "owner": "acme", "name": "rocket" }))).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    let job_id = summary["job_id"].as_str().ok_or("summary without a job ID")?;
+
+    let alert = json!({ "owner": "acme", "name": "rocket", "kind": "daily_stars", "threshold": 10, "target": "webhook", "destination": "https://hooks.example.com/stars" });
+    let response = request_with_key(&app, Method::POST, "/github/alerts", key_a, Some(&alert)).await?;
+    assert_eq!(response.status, StatusCode::CREATED, "{}", String::from_utf8_lossy(&response.body));
+    let rule: Value = response.json()?;
+    let rule_id = rule["id"].as_str().ok_or("rule without an ID")?;
+
+    let report = insert_report(&mut conn, &NewReport {
+        id: Uuid::new_v4(),
+        tenant_id: *tenant_a,
+        kind: ReportKind::WeeklyDigest.as_str(),
+        period_start: day("2025-01-06")?,
+        period_end: day("2025-01-12")?,
+        content: json!({}),
+        html: "<html></html>",
+    })?.ok_or("report not stored")?;
+    let batch = insert_sync_batch(&mut conn, &NewSyncBatch { id: Uuid::new_v4(), owner: Some("acme"), total_repositories: 0, tenant_id: *tenant_a })?;
+
+    let reads = [
+        "/github/repo_stars/read_per_day/acme/rocket".to_string(),
+        "/github/repo_stars/stats/acme/rocket".to_string(),
+        format!("/github/repo_stars/jobs/{job_id}"),
+        format!("/github/reports/{}", report.id),
+        format!("/github/org_stars/batches/{}", batch.id),
+    ];
+    for uri in &reads {
+        let response = request_with_key(&app, Method::GET, uri, key_b, None).await?;
+        assert_eq!(response.status, StatusCode::NOT_FOUND, "{uri}: {}", String::from_utf8_lossy(&response.body));
+    }
+    let response = request_with_key(&app, Method::POST, &format!("/github/repo_stars/jobs/{job_id}/resume"), key_b, Some(&json!({}))).await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = request_with_key(&app, Method::DELETE, &format!("/github/alerts/{rule_id}"), key_b, None).await?;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    // Still there for their tenant.
+    for uri in &reads {
+        let response = request_with_key(&app, Method::GET, uri, key_a, None).await?;
+        assert_eq!(response.status, StatusCode::OK, "{uri}: {}", String::from_utf8_lossy(&response.body));
+    }
+    let response = request_with_key(&app, Method::DELETE, &format!("/github/alerts/{rule_id}"), key_a, None).await?;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn requests_without_a_known_key_are_turned_away_when_keys_are_required() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-01-01")?, &[3])).await;
+    let mut dependencies = test_dependencies(&database, github.forge_clients()?);
+    dependencies.tenant_auth.require_api_key = true;
+    let app = TestApp::new(dependencies);
+
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    let error: Value = response.json()?;
+    assert_eq!(error["code"], "unauthorized");
+    assert_eq!(app.get("/github/alerts").await?.status, StatusCode::UNAUTHORIZED);
+
+    let response = request_with_key(&app, Method::GET, "/github/alerts", &generate_api_key()?, None).await?;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Views and clones recorded by traffic snapshots, lined up with the stars
+//!
+//! Traffic is lined up with the days of the stars, and snapshots record the
+//! repositories the token may push to, updating days already recorded.
+
+use axum::http::StatusCode;
+use projects_databases::analytics::compare::{compare_series, CompareMetric, CompareOptions, DailyTraffic, RepositoryHistory};
+use projects_databases::testing::{day, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use projects_databases::traffic::snapshot_traffic;
+use serde_json::{json, Value};
+
+#[test]
+fn traffic_shares_the_days_of_the_stars() -> TestResult {
+    let history = RepositoryHistory {
+        repository: "acme/rocket".into(),
+        daily_counts: vec![(day("2025-03-02")?, 3)],
+        from_snapshots: false,
+        with_forks: None,
+        activity: None,
+        first_contributions: None,
+        traffic: Some(DailyTraffic {
+            // Views before the first star fall before the series starts.
+            views: vec![(day("2025-03-01")?, 9), (day("2025-03-02")?, 40), (day("2025-03-04")?, 12)],
+            clones: vec![(day("2025-03-03")?, 2)],
+        }),
+    };
+    let without_traffic = RepositoryHistory { repository: "acme/probe".into(), traffic: None, ..history.clone() };
+
+    let metrics = [CompareMetric::Daily, CompareMetric::Views, CompareMetric::Clones];
+    let comparison = compare_series(&[history, without_traffic], &metrics, &CompareOptions::default(), day("2025-03-04")?);
+
+    let rocket = &comparison.series[0];
+    assert_eq!(rocket.daily.as_deref(), Some(&[3, 0, 0][..]));
+    assert_eq!(rocket.views.as_deref(), Some(&[40, 0, 12][..]));
+    assert_eq!(rocket.clones.as_deref(), Some(&[0, 2, 0][..]));
+    assert_eq!(comparison.series[1].views.as_deref(), Some(&[0, 0, 0][..]));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn snapshots_record_traffic_of_pushable_repositories() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(day("2025-03-01")?, &[2, 1])).await;
+    github.mount_repository(MockRepository::new("other", "probe").with_daily_stars(day("2025-03-01")?, &[1])).await;
+    github.mount_traffic("acme", "rocket", &[(day("2025-03-01")?, 30), (day("2025-03-02")?, 50)], &[(day("2025-03-02")?, 4)]).await;
+    github.mount_traffic_forbidden("other", "probe").await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    for (owner, name) in [("acme", "rocket"), ("other", "probe")] {
+        let response = app.post_json("/github/repo_stars/update", &json!({ "owner": owner, "name": name })).await?;
+        assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    }
+
+    let summary = snapshot_traffic(database.pool(), &github.forge_clients()?).await?;
+    assert_eq!((summary.repositories, summary.no_access, summary.not_found, summary.failed), (1, 1, 0, 0));
+    // Days already recorded are updated, not added again.
+    let summary = snapshot_traffic(database.pool(), &github.forge_clients()?).await?;
+    assert_eq!(summary.repositories, 1);
+
+    let query = json!({ "repositories": [{ "owner": "acme", "name": "rocket" }, { "owner": "other", "name": "probe" }], "metrics": ["daily", "views", "clones"] });
+    let comparison: Value = app.post_json("/github/repo_stars/compare", &query).await?.json()?;
+    let rocket = &comparison["series"][0];
+    assert_eq!((&rocket["daily"][0], &rocket["daily"][1]), (&json!(2), &json!(1)));
+    assert_eq!((&rocket["views"][0], &rocket["views"][1], &rocket["views"][2]), (&json!(30), &json!(50), &json!(0)));
+    assert_eq!((&rocket["clones"][0], &rocket["clones"][1]), (&json!(0), &json!(4)));
+    let probe = &comparison["series"][1];
+    assert!(probe["views"].as_array().ok_or("no views")?.iter().all(|views| views == 0));
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//! Watch lists read from `REPOS_FILE`, and the repositories they register
+//!
+//! Lists are read with their defaults and every invalid entry reported, then
+//! loading registers the repositories and syncs each once.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use interfaces_github_stargazers::forge::Forge;
+use projects_databases::testing::{test_dependencies, MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use projects_databases::watch_list::{load_watch_list, read_watch_list, ReadWatchListError};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// `contents` written to a file of its own, removed when dropped.
+struct WatchListFile(PathBuf);
+
+impl WatchListFile {
+    fn new(contents: &str) -> std::io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("repos-{}.toml", Uuid::new_v4()));
+        std::fs::write(&path, contents)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for WatchListFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn lists_are_read_with_their_defaults() -> TestResult {
+    let file = WatchListFile::new(r#"
+        sync = true
+
+        [[repositories]]
+        owner = "acme"
+        name = "rocket"
+
+        [[repositories]]
+        owner = "acme"
+        name = "comet"
+        forge = "gitlab"
+        tenant = "orbit"
+        sync = false
+    "#)?;
+
+    let list = read_watch_list(&file.0)?;
+    assert!(list.sync);
+    assert_eq!(list.repositories.len(), 2);
+    assert_eq!((list.repositories[0].forge, list.repositories[0].tenant.as_deref(), list.repositories[0].sync), (Forge::GitHub, None, None));
+    assert_eq!((list.repositories[1].forge, list.repositories[1].tenant.as_deref(), list.repositories[1].sync), (Forge::GitLab, Some("orbit"), Some(false)));
+    Ok(())
+}
+
+#[test]
+fn invalid_entries_are_all_rejected() -> TestResult {
+    let file = WatchListFile::new(r#"
+        [[repositories]]
+        owner = "-acme"
+        name = "rocket"
+
+        [[repositories]]
+        owner = "acme"
+        name = ".."
+    "#)?;
+
+    let Err(ReadWatchListError::InvalidRepositories { source, .. }) = read_watch_list(&file.0) else {
+        panic!("both entries should be rejected");
+    };
+    let fields: Vec<_> = source.errors().iter().map(|error| error.field.as_str()).collect();
+    assert_eq!(fields, ["repositories[0].owner", "repositories[1].name"]);
+
+    let file = WatchListFile::new("[[repositories]]\nowner = \"acme\"\nname = \"rocket\"\nstars = 3\n")?;
+    assert!(matches!(read_watch_list(&file.0), Err(ReadWatchListError::ParseToml { .. })));
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn loading_registers_repositories_and_syncs_them_once() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(NaiveDate::from_ymd_opt(2025, 1, 1).ok_or("invalid date")?, &[3, 2])).await;
+    let dependencies = test_dependencies(&database, github.forge_clients()?);
+    let (pool, queue) = (dependencies.db_pool.clone(), dependencies.sync_queue.clone());
+    let app = TestApp::new(dependencies);
+
+    let file = WatchListFile::new(r#"
+        sync = true
+
+        [[repositories]]
+        owner = "acme"
+        name = "rocket"
+
+        [[repositories]]
+        owner = "acme"
+        name = "comet"
+        sync = false
+    "#)?;
+    let list = read_watch_list(&file.0)?;
+
+    let summary = load_watch_list(&pool, &queue, &list, Default::default()).await?;
+    assert_eq!((summary.registered, summary.already_tracked, summary.syncs_queued), (2, 0, 1));
+
+    let mut history: Vec<Value> = Vec::new();
+    for _ in 0..50 {
+        history = app.get("/github/repositories/acme/rocket/sync_history").await?.json()?;
+        if history.first().is_some_and(|run| run["status"] == "completed") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(history.len(), 1);
+    assert_eq!((history[0]["trigger"].as_str(), history[0]["status"].as_str()), (Some("watch_list"), Some("completed")));
+
+    let summary = load_watch_list(&pool, &queue, &list, Default::default()).await?;
+    assert_eq!((summary.registered, summary.already_tracked, summary.syncs_queued), (0, 2, 0));
+
+    let repositories: Vec<Value> = app.get("/github/repositories").await?.json()?;
+    let names: Vec<_> = repositories.iter().map(|repo| repo["name"].as_str()).collect();
+    assert!(names.contains(&Some("comet")) && names.contains(&Some("rocket")), "{names:?}");
+    Ok(())
+}
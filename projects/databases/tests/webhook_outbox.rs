@@ -0,0 +1,188 @@
+//! Webhook alerts queued by finished syncs, then posted from the outbox by a
+//! client that follows no redirects and refuses names resolving to private addresses
+//!
+//! Retries back off up to an hour, and deliveries the policy refuses are given
+//! up on rather than retried.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use chrono::{TimeDelta, Utc};
+use projects_databases::db::{
+    alert_rule::{models::NewAlertRule, queries::insert_alert_rule},
+    webhook_outbox::queries::get_webhook_deliveries_by_job,
+};
+use projects_databases::notifier::Notifier;
+use projects_databases::outbox::{dispatch_due_deliveries, retry_backoff};
+use projects_databases::testing::{MockGitHub, MockRepository, TestApp, TestDatabase, TestResult};
+use projects_databases::validation::WebhookUrlPolicy;
+use projects_databases::webhook::{webhook_client, webhook_client_resolving_with, DELIVERY_ID_HEADER};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use wiremock::matchers::{header_exists, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Resolver answering every name with `address`.
+struct ResolvingTo(SocketAddr);
+
+impl Resolve for ResolvingTo {
+    fn resolve(&self, _: Name) -> Resolving {
+        let address = self.0;
+        Box::pin(async move { Ok(Box::new(std::iter::once(address)) as Addrs) })
+    }
+}
+
+/// Job and repository IDs of a sync of acme/rocket.
+async fn sync(app: &TestApp) -> Result<(Uuid, Uuid), Box<dyn std::error::Error>> {
+    let response = app.post_json("/github/repo_stars/update", &json!({ "owner": "acme", "name": "rocket" })).await?;
+    assert_eq!(response.status, StatusCode::OK, "{}", String::from_utf8_lossy(&response.body));
+    let summary: Value = response.json()?;
+    let job_id = summary["job_id"].as_str().ok_or("summary without a job ID")?.parse()?;
+    let repository_id = summary["repository_id"].as_str().ok_or("summary without a repository ID")?.parse()?;
+    Ok((job_id, repository_id))
+}
+
+#[test]
+fn retries_back_off_up_to_an_hour() {
+    assert_eq!(retry_backoff(1), TimeDelta::seconds(30));
+    assert_eq!(retry_backoff(2), TimeDelta::seconds(60));
+    assert_eq!(retry_backoff(5), TimeDelta::seconds(480));
+    assert_eq!(retry_backoff(8), TimeDelta::hours(1));
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn webhook_alerts_are_delivered_from_the_outbox_until_accepted() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    let repository = MockRepository::new("acme", "rocket").with_daily_stars(Utc::now().date_naive(), &[3]);
+    github.mount_repository(repository.clone()).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let (_, repository_id) = sync(&app).await?;
+
+    let receiver = MockServer::start().await;
+    let mut conn = database.pool().get()?;
+    insert_alert_rule(&mut conn, &NewAlertRule {
+        id: Uuid::new_v4(),
+        repository_id,
+        kind: "daily_stars",
+        threshold: 2,
+        target: "webhook",
+        destination: &format!("{}/hook", receiver.uri()),
+    })?;
+
+    let (job_id, _) = sync(&app).await?;
+    let deliveries = get_webhook_deliveries_by_job(&mut conn, job_id)?;
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!((deliveries[0].status.as_str(), deliveries[0].attempts), ("pending", 0));
+    assert_eq!(deliveries[0].payload["stars"], 3);
+
+    let notifier = Notifier::new(reqwest::Client::new(), None, WebhookUrlPolicy {
+        allowed_schemes: vec!["http".into()],
+        allowed_hosts: vec!["127.0.0.1".into()],
+    });
+    Mock::given(method("POST")).and(path("/hook")).respond_with(ResponseTemplate::new(500)).up_to_n_times(1).mount(&receiver).await;
+    Mock::given(method("POST")).and(path("/hook")).and(header_exists(DELIVERY_ID_HEADER)).respond_with(ResponseTemplate::new(204)).mount(&receiver).await;
+
+    let now = Utc::now().naive_utc();
+    let summary = dispatch_due_deliveries(database.pool(), &notifier, now).await?;
+    assert_eq!((summary.delivered, summary.retried, summary.failed), (0, 1, 0));
+    // Not due again before its backoff.
+    let summary = dispatch_due_deliveries(database.pool(), &notifier, now).await?;
+    assert_eq!((summary.delivered, summary.retried), (0, 0));
+
+    let summary = dispatch_due_deliveries(database.pool(), &notifier, now + TimeDelta::minutes(1)).await?;
+    assert_eq!(summary.delivered, 1);
+    let delivery = &get_webhook_deliveries_by_job(&mut conn, job_id)?[0];
+    assert_eq!((delivery.status.as_str(), delivery.attempts), ("delivered", 2));
+    assert_eq!(receiver.received_requests().await.map(|requests| requests.len()), Some(2));
+
+    // The rule fired once its delivery was queued.
+    let (job_id, _) = sync(&app).await?;
+    assert!(get_webhook_deliveries_by_job(&mut conn, job_id)?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs Docker or TEST_DATABASE_URL"]
+async fn webhooks_the_policy_refuses_are_given_up_on() -> TestResult {
+    let database = TestDatabase::start().await?;
+    let github = MockGitHub::start().await;
+    github.mount_repository(MockRepository::new("acme", "rocket").with_daily_stars(Utc::now().date_naive(), &[3])).await;
+    let app = TestApp::spawn(&database, &github)?;
+
+    let (_, repository_id) = sync(&app).await?;
+    let mut conn = database.pool().get()?;
+    insert_alert_rule(&mut conn, &NewAlertRule {
+        id: Uuid::new_v4(),
+        repository_id,
+        kind: "daily_stars",
+        threshold: 2,
+        target: "webhook",
+        destination: "http://127.0.0.1:9/hook",
+    })?;
+
+    let (job_id, _) = sync(&app).await?;
+
+    // The default policy only allows public https hosts.
+    let notifier = Notifier::new(reqwest::Client::new(), None, WebhookUrlPolicy::default());
+    let summary = dispatch_due_deliveries(database.pool(), &notifier, Utc::now().naive_utc()).await?;
+    assert_eq!((summary.delivered, summary.retried, summary.failed), (0, 0, 1));
+    let delivery = &get_webhook_deliveries_by_job(&mut conn, job_id)?[0];
+    assert_eq!((delivery.status.as_str(), delivery.attempts), ("failed", 1));
+    assert!(delivery.last_error.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhooks_redirecting_elsewhere_are_not_followed() -> TestResult {
+    let receiver = MockServer::start().await;
+    let policy = WebhookUrlPolicy { allowed_schemes: vec!["http".into()], allowed_hosts: vec!["127.0.0.1".into()] };
+    let notifier = Notifier::new(webhook_client(&policy, Duration::from_secs(5))?, None, policy);
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "http://169.254.169.254/latest/meta-data/"))
+        .mount(&receiver)
+        .await;
+    Mock::given(method("POST")).and(path("/internal")).respond_with(ResponseTemplate::new(204)).mount(&receiver).await;
+
+    let result = notifier.deliver_webhook(&format!("{}/hook", receiver.uri()), None, &json!({ "stars": 3 })).await;
+    assert!(result.is_err());
+
+    // Nor to a host the policy lists.
+    Mock::given(method("POST"))
+        .and(path("/moved"))
+        .respond_with(ResponseTemplate::new(307).insert_header("Location", format!("{}/internal", receiver.uri())))
+        .mount(&receiver)
+        .await;
+    let result = notifier.deliver_webhook(&format!("{}/moved", receiver.uri()), None, &json!({ "stars": 3 })).await;
+    assert!(result.is_err());
+    let requests = receiver.received_requests().await.ok_or("requests not recorded")?;
+    assert!(requests.iter().all(|request| request.url.path() != "/internal"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn webhook_hosts_resolving_to_loopback_are_refused_unless_listed() -> TestResult {
+    let receiver = MockServer::start().await;
+    let url = format!("http://hooks.example.com:{}/hook", receiver.address().port());
+    Mock::given(method("POST")).and(path("/hook")).respond_with(ResponseTemplate::new(204)).mount(&receiver).await;
+    let lookup = Arc::new(ResolvingTo(*receiver.address()));
+
+    // The name looks public, only its address gives it away.
+    let policy = WebhookUrlPolicy { allowed_schemes: vec!["http".into()], allowed_hosts: Vec::new() };
+    policy.validate(&url)?;
+    let notifier = Notifier::new(webhook_client_resolving_with(&policy, Duration::from_secs(5), lookup.clone())?, None, policy);
+    assert!(notifier.deliver_webhook(&url, None, &json!({ "stars": 3 })).await.is_err());
+    assert_eq!(receiver.received_requests().await.map(|requests| requests.len()), Some(0));
+
+    let policy = WebhookUrlPolicy { allowed_schemes: vec!["http".into()], allowed_hosts: vec!["hooks.example.com".into()] };
+    let notifier = Notifier::new(webhook_client_resolving_with(&policy, Duration::from_secs(5), lookup)?, None, policy);
+    notifier.deliver_webhook(&url, None, &json!({ "stars": 3 })).await?;
+    assert_eq!(receiver.received_requests().await.map(|requests| requests.len()), Some(1));
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Webhook deliveries ignoring the proxy environment variables, a proxy
+//! resolving hosts on its side where private addresses aren't refused
+//!
+//! Alone in its binary, the variables being set for the whole process.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use projects_databases::notifier::Notifier;
+use projects_databases::testing::TestResult;
+use projects_databases::validation::WebhookUrlPolicy;
+use projects_databases::webhook::webhook_client_resolving_with;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde_json::json;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Resolver answering every name with `address`.
+struct ResolvingTo(SocketAddr);
+
+impl Resolve for ResolvingTo {
+    fn resolve(&self, _: Name) -> Resolving {
+        let address = self.0;
+        Box::pin(async move { Ok(Box::new(std::iter::once(address)) as Addrs) })
+    }
+}
+
+#[tokio::test]
+async fn webhooks_are_not_sent_through_a_proxy() -> TestResult {
+    let proxy = MockServer::start().await;
+    Mock::given(method("POST")).respond_with(ResponseTemplate::new(204)).mount(&proxy).await;
+    std::env::set_var("HTTP_PROXY", proxy.uri());
+    std::env::set_var("HTTPS_PROXY", proxy.uri());
+    std::env::remove_var("NO_PROXY");
+
+    let receiver = MockServer::start().await;
+    let url = format!("http://hooks.example.com:{}/hook", receiver.address().port());
+    let policy = WebhookUrlPolicy { allowed_schemes: vec!["http".into()], allowed_hosts: Vec::new() };
+    policy.validate(&url)?;
+    let lookup = Arc::new(ResolvingTo(*receiver.address()));
+    let notifier = Notifier::new(webhook_client_resolving_with(&policy, Duration::from_secs(5), lookup)?, None, policy);
+
+    // Connecting directly, the loopback address the name resolves to is refused.
+    assert!(notifier.deliver_webhook(&url, None, &json!({ "stars": 3 })).await.is_err());
+    assert_eq!(proxy.received_requests().await.map(|requests| requests.len()), Some(0));
+    assert_eq!(receiver.received_requests().await.map(|requests| requests.len()), Some(0));
+    Ok(())
+}